@@ -1,18 +1,63 @@
+use crate::calibre::{DEFAULT_CALIBREDB, add_to_calibre};
 use crate::components::chapter_editor::ChapterEditorState;
-use crate::conversion::{ConversionFacade, ConversionRequest};
+use crate::components::chapter_preview::StyleSampleState;
+use crate::components::find_replace::FindReplaceState;
+use crate::components::reading_preview::ReadingPreviewState;
+use crate::components::text_history::TextHistory;
+use crate::components::thumbnails::ThumbnailCache;
+use crate::conversion::{
+    BatchItemStatus, BatchRequest, CancelToken, ConversionError, ConversionFacade,
+    ConversionProgress, ConversionReport, ConversionRequest, ConversionResult,
+    DEFAULT_CHAPTER_LENGTH, Preprocessing, SplitOptions,
+};
+use crate::cover::{CoverDesign, CoverFit, CoverGeneration, fit_cover};
+use crate::devices::{DevicePaths, MountedDevice, copy_to_device, mounted_devices};
+use crate::epub_import::{ImportedEpub, import_epub, import_epub_bytes};
+use crate::epubcheck::EpubcheckReport;
+use crate::epubworker::{
+    BuildError, DEFAULT_CHAPTER_SPLIT_BYTES, EpubBuildOptions, ImageSizeChange,
+    expand_filename_variables, generate_filename, kepub_filename,
+};
+use crate::kindle::{KindleSettings, send_to_kindle};
+use crate::merge::{MergeInput, merge_files};
+use crate::numerals::TitleNumerals;
+use crate::pdfworker::pdf_filename;
+use crate::project::{PROJECT_EXTENSION, Project, ProjectChapter, ProjectImage};
+use crate::punctuation::{PunctuationNormalization, PunctuationPreview};
+use crate::split_config::SplitConfig;
+use crate::text_header::strip_text_header_if;
+use crate::validation::ValidationIssue;
+use crate::whitespace::{WhitespaceSummary, clean_whitespace_if};
+use crate::zhconv::ChineseConversion;
 use crate::{
-    BookInfo, ConversionMethod, FontAsset, ImageFileReader, Key, Locale, PanelIndex,
-    TextFileReader, TextStyle, TocOptions, t, t1,
+    BackMatter, BookInfo, ChapterDraft, CleanupRule, ConversionMethod, DEFAULT_DESCRIPTION_CHARS,
+    EpubVersion, FontAsset, FrontMatter, ImageFileReader, ImageOptimization, Key, Locale,
+    OutputFormat, OverwritePolicy, PanelIndex, PdfOptions, SourceFormat, SpineSection,
+    TextFileReader, TextStyle, TocOptions, ZipCompression, t, t1,
 };
-use regex::Regex;
+use bytes::Bytes;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 
 mod app_helpers;
+mod lookup;
+mod shortcuts;
 mod ui;
+mod web;
 use app_helpers::{
     apply_theme, chapter_header_asset_from_reader, collect_image_assets, cover_asset_from_reader,
-    load_font_asset,
+    image_reader_from_bytes, image_reader_from_path, is_epub_path, load_font_asset, load_kana_font,
+    open_in_file_manager, parse_filename_to_book_info,
 };
+use lookup::{LookupEvent, MetadataLookupState};
+use shortcuts::{ShortcutAction, Shortcuts};
+use web::{WebFile, WebInbox, WebPick};
+
+/// 持久化状态的格式版本；结构发生不兼容变化时递增，旧版本程序不会读取更新的存档。
+const STATE_VERSION: u32 = 1;
+
+/// 封面图片的大小上限。
+const COVER_SIZE_LIMIT: u64 = 10 * 1024 * 1024;
 
 /// 派生 `Deserialize/Serialize`，用于在关闭时持久化应用状态。
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -24,42 +69,99 @@ use app_helpers::{
 /// - `#[serde(skip)]` 字段是运行时缓存/瞬时 UI 状态，不会写入存档。
 /// - 转换流程读取此状态，并通过结果弹窗字段回传成功/失败信息。
 pub struct MainApp {
+    state_version: u32, // 存档格式版本
     // 基础输入与配置状态
     input_txt_path: String,            // 输入文本文件路径
     input_image_path: String,          // 输入图片路径
     chapter_header_image_path: String, // 章头图路径
+    generate_cover: bool,              // 按书籍信息生成封面，取代上传的封面图
+    cover_design: CoverDesign,         // 生成封面的背景与配色
+    cover_fit: CoverFit,               // 上传封面的比例裁切/补边
     custom_regex_path: String,         // 自定义正则配置文件路径
     #[serde(skip)]
-    custom_regex_pattern: String, // 自定义正则表达式
+    custom_regex_patterns: Vec<String>, // 自定义正则，按优先顺序排列
     #[serde(skip)]
     custom_regex_file: Option<std::path::PathBuf>,
     #[serde(skip)]
     custom_regex_status: Option<(bool, String)>,
     // 转换策略配置
+    #[serde(skip)]
     available_methods: Vec<ConversionMethod>, // 可用的转换方法（使用枚举）
-    selected_method: ConversionMethod,        // 当前选中的转换方法
-    available_panels: Vec<PanelIndex>,        // 可用的面板索引
-    panel_index: PanelIndex,                  // 当前面板索引
-    book_info: BookInfo,                      // 书籍信息
+    selected_method: ConversionMethod, // 当前选中的转换方法
+    strip_text_header: bool,           // 分章前去掉开头的书名、作者信息块
+    clean_whitespace: bool,            // 清理规则之前去掉不可见字符与多余空白
+    #[serde(skip)]
+    whitespace_summary: Option<WhitespaceSummary>, // 空白清理的统计预览
+    cleanup_rules: Vec<CleanupRule>,   // 分章前的文本清理规则
+    punctuation: PunctuationNormalization, // 清理规则之后的标点规范化
+    #[serde(skip)]
+    punctuation_preview: Option<PunctuationPreview>, // 标点规范化的改动预览
+    title_exclusions: Vec<String>,     // 命中后不作为章节标题的正则
+    chapter_length: usize,             // 按字数分章时每章的目标字数
+    #[serde(skip)]
+    available_panels: Vec<PanelIndex>, // 可用的面板索引
+    panel_index: PanelIndex,           // 当前面板索引
+    book_info: BookInfo,               // 书籍信息
+    description_length: usize,         // 从正文生成简介时的字数上限
     // 版式与字体配置
     text_style: TextStyle,
     // 界面主题
     theme_mode: ThemeMode,
+    theme_custom: Option<ThemeColors>, // 主题编辑器的自定义配色，None 时使用预设
     locale: Locale,
+    recent: RecentFiles, // 最近打开的文本与生成的文件
     // 插图配置
     #[serde(skip)]
     images: Vec<ImageFileReader>, // 插图列表
     #[serde(skip)]
+    thumbnails: ThumbnailCache, // 插图面板的缩略图缓存
+    #[serde(skip)]
+    chapter_titles: Option<(u64, Vec<String>)>, // “插入到章节”下拉框的章节标题，按分章签名缓存
+    #[serde(skip)]
     include_images_section: bool, // 是否生成插图章节
+    image_optimization: ImageOptimization, // 图片缩放与重新压缩
     toc_options: TocOptions,
+    front_matter: FrontMatter, // 书名页、版权页、献词与前言
+    back_matter: BackMatter,   // 书末的后记或版权页
     // 其他输出相关配置
-    output_path: String,       // 输出路径
-    filename_template: String, // 文件命名模板
+    output_path: String,                   // 输出路径
+    filename_template: String,             // 文件命名模板
+    include_generator_meta: bool,          // 是否写入生成器信息
+    safe_mode: bool,                       // 安全模式：不读取磁盘上的主题素材
+    run_epubcheck: bool,                   // 转换后运行 epubcheck
+    epubcheck_path: String,                // epubcheck 可执行文件或 jar 路径
+    open_after_export: bool,               // 导出后用系统默认阅读器打开
+    add_to_calibre_after_export: bool,     // 导出后加入 Calibre 书库
+    calibredb_path: String,                // calibredb 命令或完整路径
+    calibre_library: String,               // Calibre 书库路径，留空用默认书库
+    kindle: KindleSettings,                // 发送到 Kindle 的邮箱设置
+    device_paths: DevicePaths,             // 复制到阅读器时各设备的目标目录模板
+    shortcuts: Shortcuts,                  // 全局快捷键绑定
+    epub_version: EpubVersion,             // 输出的 EPUB 版本
+    rtl_page_progression: bool,            // 从右到左翻页
+    kepub: bool,                           // 输出 Kobo KEPUB
+    reproducible: bool,                    // 可复现输出（固定时间戳与条目顺序）
+    compression: ZipCompression,           // ZIP 压缩级别与图片是否压缩
+    split_large_chapters: bool,            // 拆分超过 300 KB 的章节
+    spine_order: Vec<SpineSection>,        // 封面、目录、正文等部分的阅读顺序
+    output_format: OutputFormat,           // 输出 EPUB 或 PDF
+    pdf_options: PdfOptions,               // PDF 纸张与页边距
+    overwrite_policy: OverwritePolicy,     // 输出文件已存在时的处理方式
+    chinese_conversion: ChineseConversion, // 简繁转换方向
+    title_numerals: TitleNumerals,         // 章节编号的数字写法
     // 文本/章节编辑状态
     show_editor: bool, // 是否显示编辑器
     #[serde(skip)]
+    editor_history: TextHistory, // 文本编辑器的撤销/重做历史
+    #[serde(skip)]
+    editor_find: FindReplaceState, // 文本编辑器的查找与替换
+    #[serde(skip)]
     chapter_editor: ChapterEditorState,
     #[serde(skip)]
+    style_sample: StyleSampleState, // 格式与字体面板的样章预览
+    #[serde(skip)]
+    reading_preview: ReadingPreviewState, // 预览阅读窗口
+    #[serde(skip)]
     chapter_preview: Option<ChapterPreview>,
     #[serde(skip)]
     chapter_preview_error: Option<String>,
@@ -73,52 +175,226 @@ pub struct MainApp {
     #[serde(skip)]
     conversion_error: Option<String>, // 转换错误信息
     #[serde(skip)]
+    conversion_warnings: Vec<String>, // 转换警告（非致命）
+    #[serde(skip)]
+    conversion_image_sizes: Vec<ImageSizeChange>, // 图片优化前后体积
+    #[serde(skip)]
+    conversion_validation: Vec<ValidationIssue>, // 生成文件的自检问题
+    #[serde(skip)]
+    conversion_epubcheck: Option<EpubcheckReport>, // epubcheck 报告
+    #[serde(skip)]
+    conversion_report: Option<ConversionReport>, // 章节与字数统计
+    #[serde(skip)]
+    overwrite_conflict: Option<String>, // 询问覆盖时已存在的输出文件
+    #[serde(skip)]
+    overwrite_once: Option<OverwritePolicy>, // 仅用于下一次转换的覆盖策略
+    #[serde(skip)]
+    calibre_job: Option<Receiver<Result<Vec<u32>, String>>>, // 运行中的 calibredb add
+    #[serde(skip)]
+    calibre_notice: Option<String>, // 加入 Calibre 的进度或结果
+    #[serde(skip)]
+    show_kindle_settings: bool, // 是否显示 Kindle 邮箱设置对话框
+    #[serde(skip)]
+    show_help: bool, // 是否显示帮助窗口
+    #[serde(skip)]
+    show_shortcut_settings: bool, // 是否显示快捷键设置对话框
+    #[serde(skip)]
+    shortcut_recording: Option<ShortcutAction>, // 正在等待新按键的动作
+    #[serde(skip)]
+    kindle_job: Option<Receiver<Result<(), String>>>, // 发送中的 Kindle 邮件
+    #[serde(skip)]
+    kindle_notice: Option<String>, // 发送到 Kindle 的进度或结果
+    #[serde(skip)]
+    devices: Vec<MountedDevice>, // 已挂载的阅读器
+    #[serde(skip)]
+    device_notice: Option<String>, // 复制到设备的结果
+    #[serde(skip)]
+    conversion_job: Option<ConversionJob>, // 运行中的后台转换任务
+    #[serde(skip)]
+    metadata_lookup: MetadataLookupState, // 在线元数据查询对话框
+    #[serde(skip)]
     input_file: TextFileReader, // 文本文件读取器
     #[serde(skip)]
+    merged_inputs: Vec<MergeInput>, // 源文本由多个文件合并而来时的文件列表
+    #[serde(skip)]
+    merge_items: Vec<MergeItem>, // 合并多个文件对话框中的文件列表
+    #[serde(skip)]
+    show_merge_files: bool, // 是否显示合并多个文件对话框
+    #[serde(skip)]
     input_image: ImageFileReader, // 图片文件读取器
     #[serde(skip)]
+    fitted_cover: ImageFileReader, // 调整比例后的封面
+    #[serde(skip)]
+    fitted_cover_dirty: bool, // 比例设置已改动、尚未重新处理
+    #[serde(skip)]
+    cover_texture: ImageFileReader, // 生成封面的纹理背景
+    #[serde(skip)]
+    generated_cover: ImageFileReader, // 生成封面的预览
+    #[serde(skip)]
     chapter_header_image: ImageFileReader, // 章头图读取器
     #[serde(skip)]
-    font_asset: Option<FontAsset>,
+    font_assets: Vec<(String, FontAsset)>, // 已加载字体缓存（按路径）
     #[serde(skip)]
     font_error: Option<String>,
     #[serde(skip)]
     runtime_notice: Option<String>,
     chapter_header_fullbleed: bool, // 章头图全宽/全屏
+    // 批量转换状态
+    #[serde(skip)]
+    batch_queue: Vec<BatchItem>, // 批量转换队列
+    #[serde(skip)]
+    batch_job: Option<BatchRequest>, // 运行中的批量任务（启动时冻结设置）
+    #[serde(skip)]
+    project_path: Option<PathBuf>, // 当前工程文件，保存时直接覆盖
+    #[serde(skip)]
+    web_inbox: WebInbox, // Web 端异步读入的文件
+    #[serde(skip)]
+    kana_font_loaded: bool, // 是否已尝试载入日文后备字体
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
 enum ThemeMode {
     Light,
     Dark,
+    Sepia,
+    Midnight,
 }
 
 impl ThemeMode {
+    const ALL: [ThemeMode; 4] = [
+        ThemeMode::Light,
+        ThemeMode::Dark,
+        ThemeMode::Sepia,
+        ThemeMode::Midnight,
+    ];
+
+    fn label(self, locale: Locale) -> &'static str {
+        t(
+            locale,
+            match self {
+                ThemeMode::Light => Key::ThemeLight,
+                ThemeMode::Dark => Key::ThemeDark,
+                ThemeMode::Sepia => Key::ThemeSepia,
+                ThemeMode::Midnight => Key::ThemeMidnight,
+            },
+        )
+    }
+
+    /// 是否基于 egui 的暗色外观。
+    fn is_dark(self) -> bool {
+        matches!(self, ThemeMode::Dark | ThemeMode::Midnight)
+    }
+
+    /// 预设的强调色、面板底色与圆角。
+    fn colors(self) -> ThemeColors {
+        let (accent, panel_fill) = match self {
+            ThemeMode::Light => ((47, 125, 113), (248, 249, 250)),
+            ThemeMode::Dark => ((94, 194, 177), (28, 30, 33)),
+            ThemeMode::Sepia => ((160, 98, 48), (246, 239, 226)),
+            ThemeMode::Midnight => ((122, 162, 247), (21, 25, 37)),
+        };
+        ThemeColors {
+            accent: egui::Color32::from_rgb(accent.0, accent.1, accent.2),
+            panel_fill: egui::Color32::from_rgb(panel_fill.0, panel_fill.1, panel_fill.2),
+            corner_radius: 12,
+        }
+    }
+
+    /// 在亮色与暗色之间切换。
     fn toggle(&mut self) {
-        *self = match self {
-            ThemeMode::Light => ThemeMode::Dark,
-            ThemeMode::Dark => ThemeMode::Light,
+        *self = if self.is_dark() {
+            ThemeMode::Light
+        } else {
+            ThemeMode::Dark
         };
     }
 }
 
+/// 主题编辑器可调整的界面配色。
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+struct ThemeColors {
+    accent: egui::Color32,
+    panel_fill: egui::Color32,
+    /// 窗口圆角；菜单与控件按比例缩小。
+    corner_radius: u8,
+}
+
+/// 最近打开的源文本与最近生成的输出文件，最新的排在最前。
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+#[serde(default)]
+struct RecentFiles {
+    texts: Vec<PathBuf>,
+    outputs: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// 每个列表最多保留的条目数。
+    const LIMIT: usize = 8;
+
+    fn push_text(&mut self, path: PathBuf) {
+        Self::push(&mut self.texts, path);
+    }
+
+    fn push_output(&mut self, path: PathBuf) {
+        Self::push(&mut self.outputs, path);
+    }
+
+    fn push(list: &mut Vec<PathBuf>, path: PathBuf) {
+        list.retain(|existing| *existing != path);
+        list.insert(0, path);
+        list.truncate(Self::LIMIT);
+    }
+
+    /// 列表中显示的文件名，完整路径放在悬停提示中。
+    fn label(path: &Path) -> String {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.texts.is_empty() && self.outputs.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.texts.clear();
+        self.outputs.clear();
+    }
+}
+
 impl Default for MainApp {
     // 初始化默认状态
     fn default() -> Self {
         Self {
+            state_version: STATE_VERSION,
             input_txt_path: String::new(),
             input_image_path: String::new(),
+            generate_cover: false,
+            cover_design: CoverDesign::default(),
+            cover_fit: CoverFit::default(),
             chapter_header_image_path: String::new(),
             custom_regex_path: String::new(),
-            custom_regex_pattern: String::new(),
+            custom_regex_patterns: Vec::new(),
             custom_regex_file: None,
             custom_regex_status: None,
             available_methods: vec![
-                ConversionMethod::SimpleRules,  // 简单规则
-                ConversionMethod::Regex,        // 正则表达式
-                ConversionMethod::CustomConfig, // 自定义配置
+                ConversionMethod::SimpleRules,      // 简单规则
+                ConversionMethod::Regex,            // 正则表达式
+                ConversionMethod::CustomConfig,     // 自定义配置
+                ConversionMethod::MarkdownHeadings, // Markdown 标题
+                ConversionMethod::ByLength,         // 按字数
+                ConversionMethod::FromToc,          // 内嵌目录
+                ConversionMethod::Heuristic,        // 自动识别
             ],
             selected_method: ConversionMethod::Regex, // 默认使用正则表达式方法
+            cleanup_rules: Vec::new(),
+            punctuation: PunctuationNormalization::default(),
+            punctuation_preview: None,
+            strip_text_header: false,
+            clean_whitespace: false,
+            whitespace_summary: None,
+            title_exclusions: Vec::new(),
+            chapter_length: DEFAULT_CHAPTER_LENGTH,
             available_panels: vec![
                 PanelIndex::Format,      // 版式面板
                 PanelIndex::Chapter,     // 章节面板
@@ -127,32 +403,99 @@ impl Default for MainApp {
                 PanelIndex::CSS,         // CSS 面板
                 PanelIndex::Images,      // 插图面板
                 PanelIndex::Misc,        // 杂项面板
+                PanelIndex::Batch,       // 批量转换面板
             ],
             panel_index: PanelIndex::Format, // 默认显示排版面板
             book_info: BookInfo::default(),
+            description_length: DEFAULT_DESCRIPTION_CHARS,
             text_style: TextStyle::default(),
             theme_mode: ThemeMode::Light,
+            theme_custom: None,
             locale: Locale::Zh,
+            recent: RecentFiles::default(),
             images: Vec::new(),
+            thumbnails: ThumbnailCache::default(),
+            chapter_titles: None,
             include_images_section: true,
+            image_optimization: ImageOptimization::default(),
             toc_options: TocOptions::default(),
+            front_matter: FrontMatter::default(),
+            back_matter: BackMatter::default(),
             output_path: ".".to_owned(),
             filename_template: "{书名}_{作者}.epub".to_owned(),
+            include_generator_meta: true,
+            safe_mode: cfg!(target_arch = "wasm32"),
+            run_epubcheck: false,
+            epubcheck_path: String::new(),
+            open_after_export: false,
+            add_to_calibre_after_export: false,
+            calibredb_path: DEFAULT_CALIBREDB.to_string(),
+            calibre_library: String::new(),
+            kindle: KindleSettings::default(),
+            device_paths: DevicePaths::default(),
+            shortcuts: Shortcuts::default(),
+            epub_version: EpubVersion::default(),
+            rtl_page_progression: false,
+            kepub: false,
+            reproducible: false,
+            compression: ZipCompression::default(),
+            split_large_chapters: false,
+            spine_order: SpineSection::ALL.to_vec(),
+            output_format: OutputFormat::default(),
+            overwrite_policy: OverwritePolicy::Ask,
+            pdf_options: PdfOptions::default(),
+            chinese_conversion: ChineseConversion::default(),
+            title_numerals: TitleNumerals::default(),
             show_editor: false,
+            editor_history: TextHistory::default(),
+            editor_find: FindReplaceState::default(),
             chapter_editor: ChapterEditorState::default(),
+            style_sample: StyleSampleState::default(),
+            reading_preview: ReadingPreviewState::default(),
             chapter_preview: None,
             chapter_preview_error: None,
             chapter_preview_signature: None,
             show_conversion_modal: false,
             conversion_result: None,
             conversion_error: None,
+            conversion_warnings: Vec::new(),
+            conversion_image_sizes: Vec::new(),
+            conversion_validation: Vec::new(),
+            conversion_epubcheck: None,
+            conversion_report: None,
+            overwrite_conflict: None,
+            overwrite_once: None,
+            calibre_job: None,
+            calibre_notice: None,
+            show_kindle_settings: false,
+            show_help: false,
+            show_shortcut_settings: false,
+            shortcut_recording: None,
+            kindle_job: None,
+            kindle_notice: None,
+            devices: Vec::new(),
+            device_notice: None,
+            conversion_job: None,
+            metadata_lookup: MetadataLookupState::default(),
             input_file: TextFileReader::default(),
+            merged_inputs: Vec::new(),
+            merge_items: Vec::new(),
+            show_merge_files: false,
             input_image: ImageFileReader::default(),
+            fitted_cover: ImageFileReader::default(),
+            fitted_cover_dirty: false,
+            cover_texture: ImageFileReader::default(),
+            generated_cover: ImageFileReader::default(),
             chapter_header_image: ImageFileReader::default(),
-            font_asset: None,
+            font_assets: Vec::new(),
             font_error: None,
             runtime_notice: None,
             chapter_header_fullbleed: false,
+            batch_queue: Vec::new(),
+            batch_job: None,
+            project_path: None,
+            web_inbox: WebInbox::default(),
+            kana_font_loaded: false,
         }
     }
 }
@@ -169,9 +512,7 @@ impl MainApp {
         // 注入自定义字体数据。
         fonts.font_data.insert(
             "stdg".to_owned(),
-            Arc::new(egui::FontData::from_static(include_bytes!(
-                "../assets/stdg-regular.ttf"
-            ))), // 注意这里补全了括号
+            Arc::new(egui::FontData::from_static(crate::cover::BUNDLED_FONT)),
         );
 
         // 应用字体设置。
@@ -190,26 +531,88 @@ impl MainApp {
 
         cc.egui_ctx.set_fonts(fonts);
 
-        // 加载之前保存的应用状态（如果存在）。
-        // 注意：需要启用 `persistence` 功能。
-        /*         if let Some(storage) = cc.storage {
-                   return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-               }
-        */
-        let app = Self::default();
-        apply_theme(&cc.egui_ctx, app.theme_mode);
+        // 载入翻译目录中的社区翻译；无法识别的文件只记录日志。
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = crate::i18n::translations_dir() {
+            for problem in crate::i18n::load_translation_dir(&dir) {
+                log::warn!("Translation file skipped: {problem}");
+            }
+        }
+
+        // 加载之前保存的应用状态（如果存在）；存档损坏或来自更新版本时使用默认设置。
+        let app = cc
+            .storage
+            .and_then(|storage| storage.get_string(eframe::APP_KEY))
+            .and_then(|saved| Self::restore(&saved))
+            .unwrap_or_default();
+        apply_theme(&cc.egui_ctx, app.theme_mode, app.theme_custom);
         app
     }
 
+    /// 从存档文本恢复状态。
+    ///
+    /// 缺失的字段取默认值；无法解析或版本高于 `STATE_VERSION` 时返回 `None`。
+    /// 存档只保存路径，恢复后重新关联仍然存在的自定义正则配置文件。
+    fn restore(saved: &str) -> Option<Self> {
+        let mut app: Self = ron::from_str(saved).ok()?;
+        if app.state_version > STATE_VERSION {
+            return None;
+        }
+        app.state_version = STATE_VERSION;
+        if !app.available_panels.contains(&app.panel_index) {
+            app.panel_index = PanelIndex::Format;
+        }
+        let config = PathBuf::from(&app.custom_regex_path);
+        if !app.custom_regex_path.is_empty() && config.is_file() {
+            app.custom_regex_status = Some(app.validate_custom_config(app.locale, &config));
+            app.custom_regex_file = Some(config);
+        } else {
+            app.custom_regex_path.clear();
+        }
+        Some(app)
+    }
+
+    /// 把所有设置恢复为默认值；已载入的文本、图片、章节编辑、书籍信息与最近文件保持不变。
+    fn reset_settings(&mut self) {
+        let current = std::mem::take(self);
+        *self = Self {
+            input_txt_path: current.input_txt_path,
+            input_image_path: current.input_image_path,
+            chapter_header_image_path: current.chapter_header_image_path,
+            book_info: current.book_info,
+            recent: current.recent,
+            images: current.images,
+            chapter_editor: current.chapter_editor,
+            input_file: current.input_file,
+            merged_inputs: current.merged_inputs,
+            merge_items: current.merge_items,
+            input_image: current.input_image,
+            chapter_header_image: current.chapter_header_image,
+            font_assets: current.font_assets,
+            batch_queue: current.batch_queue,
+            project_path: current.project_path,
+            web_inbox: current.web_inbox,
+            ..Self::default()
+        };
+        self.refresh_fitted_cover();
+    }
+
     /// 基于当前 UI 状态构建并执行一次转换请求。
     ///
     /// 流程：
     /// 1）校验必要输入与章节编辑器状态；
     /// 2）收集可选资源（封面/插图/字体/章头图）；
-    /// 3）调用 `ConversionFacade`，并将结果映射到弹窗状态字段。
+    /// 3）在后台线程调用 `ConversionFacade`，由 `poll_conversion` 将结果映射到弹窗状态字段。
     fn run_conversion(&mut self) {
+        if self.conversion_job.is_some() || self.batch_job.is_some() {
+            return;
+        }
         self.conversion_error = None;
         self.conversion_result = None;
+        self.overwrite_conflict = None;
+        self.calibre_notice = None;
+        self.kindle_notice = None;
+        self.device_notice = None;
 
         if self.input_file.content.trim().is_empty() {
             self.conversion_error = Some(t(self.locale, Key::PreviewTextEmpty).to_string());
@@ -232,11 +635,6 @@ impl MainApp {
             }
         }
 
-        let cover = cover_asset_from_reader(&self.input_image);
-        let chapter_header_image = chapter_header_asset_from_reader(&self.chapter_header_image);
-        let images = collect_image_assets(&self.images);
-        let font = self.resolve_font_asset();
-
         let chapters_override = if self.chapter_editor.use_for_conversion {
             Some(self.chapter_editor.chapters.clone())
         } else {
@@ -245,8 +643,274 @@ impl MainApp {
 
         let request = ConversionRequest {
             text: self.input_file.content.clone(),
+            chapters_override,
+            source_format: self
+                .input_file
+                .path
+                .as_deref()
+                .map(SourceFormat::from_path)
+                .unwrap_or_default(),
+            // Web 端无法写入文件系统，在内存中生成后交给浏览器下载。
+            in_memory: cfg!(target_arch = "wasm32"),
+            ..self.conversion_settings()
+        };
+
+        self.conversion_warnings.clear();
+        self.conversion_image_sizes.clear();
+        self.conversion_validation.clear();
+        self.conversion_epubcheck = None;
+        self.conversion_report = None;
+        self.show_conversion_modal = true;
+        // wasm 不支持线程，直接在 UI 线程中转换。
+        if cfg!(target_arch = "wasm32") {
+            self.finish_conversion(ConversionFacade::convert(request));
+        } else {
+            self.conversion_job = Some(ConversionJob::spawn(request));
+        }
+    }
+
+    /// 将转换结果映射到弹窗状态字段。
+    fn finish_conversion(&mut self, result: Result<ConversionResult, ConversionError>) {
+        match result {
+            Ok(result) => {
+                match &result.bytes {
+                    Some(bytes) => {
+                        if let Err(err) = web::download(&result.output_path, bytes) {
+                            self.conversion_error = Some(t1(self.locale, Key::DownloadFailed, err));
+                            self.show_conversion_modal = true;
+                            return;
+                        }
+                    }
+                    None => {
+                        let path = PathBuf::from(&result.output_path);
+                        self.recent.push_output(path.clone());
+                        self.devices = mounted_devices();
+                        if self.open_after_export {
+                            let _ = open_in_file_manager(&path);
+                        }
+                        if self.add_to_calibre_after_export {
+                            self.send_to_calibre(path.clone());
+                        }
+                        if self.kindle.send_after_export {
+                            self.send_to_kindle(path);
+                        }
+                    }
+                }
+                self.conversion_result = Some(result.output_path);
+                self.conversion_warnings = result.warnings;
+                self.conversion_image_sizes = result.image_sizes;
+                self.conversion_validation = result.validation;
+                self.conversion_epubcheck = result.epubcheck;
+                self.conversion_report = Some(result.report);
+                self.conversion_error = None;
+            }
+            Err(ConversionError::Build(BuildError::OutputExists(path))) => {
+                self.overwrite_conflict = Some(path.display().to_string());
+                self.conversion_error = None;
+                self.conversion_result = None;
+            }
+            Err(err) => {
+                self.conversion_error = Some(match err {
+                    ConversionError::Cancelled => {
+                        t(self.locale, Key::ConversionCancelled).to_string()
+                    }
+                    err => err.to_string(),
+                });
+                self.conversion_result = None;
+                self.conversion_warnings.clear();
+                self.conversion_image_sizes.clear();
+                self.conversion_validation.clear();
+                self.conversion_epubcheck = None;
+                self.conversion_report = None;
+            }
+        }
+        self.show_conversion_modal = true;
+    }
+
+    /// 读取后台任务的进度与结果；任务进行中时持续请求重绘。
+    ///
+    /// 批量条目完成后记录其状态并启动下一项。
+    fn poll_conversion(&mut self, ctx: &egui::Context) {
+        let Some(job) = self.conversion_job.as_mut() else {
+            return;
+        };
+        if let Some(progress) = job.progress_rx.try_iter().last() {
+            job.progress = progress;
+        }
+        let result = match job.result_rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint();
+                return;
+            }
+            Err(TryRecvError::Disconnected) => Err(ConversionError::InvalidInput(
+                "Conversion worker stopped unexpectedly.".to_string(),
+            )),
+        };
+        let batch_item = job.batch_item;
+        self.conversion_job = None;
+        match batch_item {
+            Some(index) => {
+                self.finish_batch_item(index, result);
+                self.next_batch_item();
+                ctx.request_repaint();
+            }
+            None => self.finish_conversion(result),
+        }
+    }
+
+    /// 在后台运行 `calibredb add`，把导出的文件加入 Calibre 书库。
+    fn send_to_calibre(&mut self, path: PathBuf) {
+        if self.calibre_job.is_some() {
+            return;
+        }
+        if cfg!(target_arch = "wasm32") {
+            self.runtime_notice = Some(t(self.locale, Key::DesktopOnlyAction).to_string());
+            return;
+        }
+        let calibredb = self.calibredb_path.clone();
+        let library = Some(self.calibre_library.trim())
+            .filter(|library| !library.is_empty())
+            .map(PathBuf::from);
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(add_to_calibre(&calibredb, library.as_deref(), &path));
+        });
+        self.calibre_job = Some(rx);
+        self.calibre_notice = Some(t(self.locale, Key::CalibreAdding).to_string());
+    }
+
+    /// 读取 `calibredb add` 的结果。
+    fn poll_calibre(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.calibre_job else {
+            return;
+        };
+        let result = match job.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint();
+                return;
+            }
+            Err(TryRecvError::Disconnected) => Err("calibredb worker stopped".to_string()),
+        };
+        self.calibre_job = None;
+        self.calibre_notice = Some(match result {
+            Ok(ids) => {
+                let ids: Vec<String> = ids.iter().map(|id| format!("#{id}")).collect();
+                t1(self.locale, Key::CalibreAdded, ids.join(", "))
+            }
+            Err(err) => t1(self.locale, Key::CalibreFailed, err),
+        });
+    }
+
+    /// 在后台把导出的文件发送到 Kindle 邮箱；设置不完整时改为打开设置对话框。
+    fn send_to_kindle(&mut self, path: PathBuf) {
+        if self.kindle_job.is_some() {
+            return;
+        }
+        if cfg!(target_arch = "wasm32") {
+            self.runtime_notice = Some(t(self.locale, Key::DesktopOnlyAction).to_string());
+            return;
+        }
+        if !self.kindle.is_complete() {
+            self.show_kindle_settings = true;
+            return;
+        }
+        let settings = self.kindle.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(send_to_kindle(&settings, &path));
+        });
+        self.kindle_job = Some(rx);
+        self.kindle_notice = Some(t(self.locale, Key::KindleSending).to_string());
+    }
+
+    /// 把导出的文件复制到第 `index` 台已挂载的阅读器，目标目录按该设备的路径模板展开。
+    fn copy_to_device(&mut self, index: usize, path: &Path) {
+        let Some(device) = self.devices.get(index) else {
+            return;
+        };
+        let folder = expand_filename_variables(
+            &self.book_info,
+            self.device_paths.template(device.kind),
+            self.chapter_editor.chapters.len(),
+            self.text_style.css_template,
+        );
+        self.device_notice = Some(match copy_to_device(device, &folder, path) {
+            Ok(target) => t1(self.locale, Key::DeviceCopied, target.display()),
+            Err(err) => t1(self.locale, Key::DeviceCopyFailed, err),
+        });
+    }
+
+    /// 读取 Kindle 邮件的发送结果。
+    fn poll_kindle(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.kindle_job else {
+            return;
+        };
+        let result = match job.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint();
+                return;
+            }
+            Err(TryRecvError::Disconnected) => Err("mail worker stopped".to_string()),
+        };
+        self.kindle_job = None;
+        self.kindle_notice = Some(match result {
+            Ok(()) => t1(self.locale, Key::KindleSent, self.kindle.to.trim()),
+            Err(err) => t1(self.locale, Key::KindleFailed, err),
+        });
+    }
+
+    /// 读取在线元数据查询的结果；补全的详情填入书籍信息，下载完成的封面直接载入。
+    fn poll_metadata_lookup(&mut self, ctx: &egui::Context) {
+        if !self.metadata_lookup.is_busy() {
+            return;
+        }
+        match self.metadata_lookup.poll() {
+            Some(LookupEvent::Cover(url, Ok(content))) => {
+                let name = url.rsplit('/').next().unwrap_or("cover.jpg").to_string();
+                self.set_cover_image(PathBuf::from(name), content);
+            }
+            Some(LookupEvent::Cover(_, Err(err))) => {
+                self.runtime_notice = Some(t1(self.locale, Key::CoverDownloadFailed, err));
+            }
+            Some(LookupEvent::Details(cover_url, result)) => {
+                match result {
+                    Ok(details) => details.apply_to(&mut self.book_info),
+                    Err(err) => {
+                        self.runtime_notice = Some(t1(self.locale, Key::LookupFailed, err));
+                    }
+                }
+                if self.metadata_lookup.download_cover
+                    && let Some(url) = cover_url
+                {
+                    self.metadata_lookup.download_cover(url);
+                }
+            }
+            Some(LookupEvent::Candidates(_)) | None => {}
+        }
+        if self.metadata_lookup.is_busy() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// 收集当前界面上与具体文本无关的转换设置（版式、资源、输出选项等）。
+    ///
+    /// 单本转换与批量转换共用这份设置，调用方再填入文本与章节来源。
+    fn conversion_settings(&mut self) -> ConversionRequest {
+        let cover = if self.cover_fit.enabled && !self.fitted_cover.content.is_empty() {
+            cover_asset_from_reader(&self.fitted_cover)
+        } else {
+            cover_asset_from_reader(&self.input_image)
+        };
+        let chapter_header_image = chapter_header_asset_from_reader(&self.chapter_header_image);
+        let images = collect_image_assets(&self.images);
+        let fonts = self.resolve_font_assets();
+
+        ConversionRequest {
             method: self.selected_method,
-            custom_regex: self.custom_regex_pattern.clone(),
+            custom_regex: self.custom_regex(),
             custom_config_path: self.custom_regex_file.clone(),
             book_info: self.book_info.clone(),
             output_dir: PathBuf::from(&self.output_path),
@@ -254,51 +918,703 @@ impl MainApp {
             style: self.text_style.clone(),
             cover,
             images,
-            font,
+            fonts,
             chapter_header_image,
             chapter_header_fullbleed: self.chapter_header_fullbleed,
-            chapters_override,
             include_images_section: self.include_images_section,
             toc_options: self.toc_options.clone(),
-        };
+            front_matter: self.front_matter.clone(),
+            back_matter: self.back_matter.clone(),
+            include_generator_meta: self.include_generator_meta,
+            safe_mode: self.safe_mode,
+            epubcheck_path: (self.run_epubcheck && !self.epubcheck_path.trim().is_empty())
+                .then(|| PathBuf::from(self.epubcheck_path.trim())),
+            epub_version: self.epub_version,
+            rtl_page_progression: self.rtl_page_progression,
+            kepub: self.kepub,
+            reproducible: self.reproducible,
+            compression: self.compression,
+            split_chapter_bytes: self
+                .split_large_chapters
+                .then_some(DEFAULT_CHAPTER_SPLIT_BYTES),
+            spine_order: self.spine_order.clone(),
+            output_format: self.output_format,
+            pdf: self.pdf_options,
+            overwrite_policy: self.overwrite_once.take().unwrap_or(self.overwrite_policy),
+            strip_text_header: self.strip_text_header,
+            clean_whitespace: self.clean_whitespace,
+            cleanup_rules: self.cleanup_rules.clone(),
+            punctuation: self.punctuation,
+            title_exclusions: self.title_exclusions.clone(),
+            chapter_length: self.chapter_length,
+            chinese_conversion: self.chinese_conversion,
+            title_numerals: self.title_numerals,
+            image_optimization: self.image_optimization,
+            generated_cover: self.generate_cover.then(|| self.cover_generation()),
+            ..Default::default()
+        }
+    }
 
-        match ConversionFacade::convert(request) {
-            Ok(result) => {
-                self.conversion_result = Some(result.output_path);
-                self.conversion_error = None;
-                self.show_conversion_modal = true;
+    /// 章节预览的版式参数：与导出相同的样式、版本与源格式，不含图片与字体资源。
+    fn chapter_preview_options(&self) -> EpubBuildOptions {
+        EpubBuildOptions {
+            book_info: self.book_info.clone(),
+            style: self.text_style.clone(),
+            chapter_header_fullbleed: self.chapter_header_fullbleed,
+            safe_mode: self.safe_mode,
+            epub_version: self.epub_version,
+            source_format: self
+                .input_file
+                .path
+                .as_deref()
+                .map(SourceFormat::from_path)
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// 按当前设置解析出的输出文件名；章节数取章节编辑器中的章节。
+    fn output_filename_preview(&self) -> String {
+        let filename = generate_filename(
+            &self.book_info,
+            &self.filename_template,
+            self.chapter_editor.chapters.len(),
+            self.text_style.css_template,
+        );
+        match self.output_format {
+            OutputFormat::Pdf => pdf_filename(&filename),
+            OutputFormat::Epub if self.kepub => kepub_filename(&filename),
+            OutputFormat::Epub => filename,
+        }
+    }
+
+    /// 读取源文本文件并记入最近文件；书名与作者为空时从文件名推断。
+    fn load_text_file(&mut self, path: PathBuf) {
+        if is_epub_path(&path) {
+            match import_epub(&path) {
+                Ok(imported) => {
+                    self.set_imported_epub(path.clone(), imported);
+                    self.recent.push_text(path);
+                }
+                Err(err) => self.input_file.error = Some(t1(self.locale, Key::ReadFailed, err)),
             }
-            Err(err) => {
-                self.conversion_error = Some(err.to_string());
-                self.conversion_result = None;
-                self.show_conversion_modal = true;
+            return;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                self.set_source_text(path.clone(), content);
+                self.recent.push_text(path);
+            }
+            Err(e) => {
+                self.input_file.error = Some(t1(self.locale, Key::ReadFailed, e));
             }
         }
     }
 
-    /// 解析字体资源（优先使用缓存）。
+    /// 按合并列表的顺序读取并拼接各文件作为源文本，每个文件成为一卷，并开启按卷分组。
     ///
-    /// 若字体已在内存中加载则直接复用；否则按 `text_style.font_path`
-    /// 延迟加载，成功后写回缓存。
-    fn resolve_font_asset(&mut self) -> Option<FontAsset> {
-        if let Some(asset) = self.font_asset.clone() {
-            return Some(asset);
+    /// 合并结果不对应单个文件，编辑器保存时不会写回任何源文件。
+    fn merge_source_files(&mut self) {
+        let inputs: Vec<MergeInput> = self
+            .merge_items
+            .iter()
+            .map(|item| item.input.clone())
+            .collect();
+        let Some(first) = inputs.first() else {
+            return;
+        };
+        match merge_files(&inputs) {
+            Ok(content) => {
+                self.set_source_text(first.path.clone(), content);
+                self.input_file.path = None;
+                self.input_txt_path = t1(self.locale, Key::MergedFiles, inputs.len());
+                self.merged_inputs = inputs;
+                self.toc_options.nest_volumes = true;
+                self.show_merge_files = false;
+            }
+            Err(err) => self.input_file.error = Some(t1(self.locale, Key::ReadFailed, err)),
         }
-        if self.text_style.font_path.trim().is_empty() {
-            return None;
+    }
+
+    /// 载入源文本；`path` 在 Web 端只是文件名，用于判断源格式与推断书名。
+    fn set_source_text(&mut self, path: PathBuf, content: String) {
+        self.merged_inputs.clear();
+        self.input_file.content = content;
+        self.input_file.error = None;
+        self.editor_history.reset(&self.input_file.content);
+        self.input_file.path = Some(path.clone());
+        self.input_txt_path = path.to_string_lossy().to_string();
+        self.runtime_notice = None;
+        if SourceFormat::from_path(&path) == SourceFormat::Markdown {
+            self.selected_method = ConversionMethod::MarkdownHeadings;
+        }
+
+        if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
+            let (title, author) = parse_filename_to_book_info(filename);
+            if self.book_info.title.trim().is_empty() {
+                self.book_info.title = title;
+            }
+            if self.book_info.author().is_empty() && !author.is_empty() {
+                *self.book_info.primary_author_mut() = author;
+            }
+        }
+    }
+
+    /// 载入导入的 EPUB：正文还原为按 Markdown 标题分隔的源文本，章节编辑器直接使用导入的章节，
+    /// 书籍信息整体替换，带封面时改用原书封面。
+    fn set_imported_epub(&mut self, path: PathBuf, imported: ImportedEpub) {
+        self.set_source_text(path, imported.source_text());
+        self.selected_method = ConversionMethod::MarkdownHeadings;
+        self.book_info = imported.book_info;
+        if let Some((name, bytes)) = imported.cover {
+            self.generate_cover = false;
+            self.set_cover_image(PathBuf::from(name), bytes);
+        }
+        let count = imported.chapters.len();
+        let signature = self.preview_signature();
+        self.chapter_editor = ChapterEditorState::default();
+        self.chapter_editor.restore(imported.chapters, signature);
+        self.chapter_preview = None;
+        self.chapter_preview_signature = None;
+        self.runtime_notice = Some(t1(self.locale, Key::EpubImported, count));
+    }
+
+    /// 读取封面图片；超过 10 MB 的文件不予载入。
+    fn load_cover_image(&mut self, path: PathBuf) {
+        if let Ok(metadata) = std::fs::metadata(&path)
+            && metadata.len() > COVER_SIZE_LIMIT
+        {
+            self.input_image.error = Some(t(self.locale, Key::FileTooLarge).to_string());
+            return;
+        }
+
+        match std::fs::read(&path) {
+            Ok(content) => self.set_cover_image(path, content),
+            Err(e) => {
+                self.input_image.error = Some(t1(self.locale, Key::ReadFailed, e));
+            }
+        }
+    }
+
+    /// 载入封面图片内容并刷新适配后的封面。
+    fn set_cover_image(&mut self, path: PathBuf, content: Vec<u8>) {
+        if content.len() as u64 > COVER_SIZE_LIMIT {
+            self.input_image.error = Some(t(self.locale, Key::FileTooLarge).to_string());
+            return;
+        }
+        self.input_image.content = Bytes::from(content);
+        self.input_image.error = None;
+        self.input_image_path = path.to_string_lossy().to_string();
+        self.input_image.caption = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string());
+        self.input_image.path = Some(path);
+        self.input_image.texture = None;
+        self.refresh_fitted_cover();
+    }
+
+    /// 载入 Web 端异步读取完成的文件。
+    fn poll_web_files(&mut self) {
+        while let Some((pick, files)) = self.web_inbox.try_recv() {
+            self.load_web_files(pick, files);
+        }
+    }
+
+    /// 按用途载入内存中的文件；文件名充当路径，用于推断格式、书名与图片说明。
+    fn load_web_files(&mut self, pick: WebPick, files: Vec<WebFile>) {
+        let locale = self.locale;
+        self.runtime_notice = None;
+        match pick {
+            WebPick::Text => {
+                let Some(file) = files.into_iter().next() else {
+                    return;
+                };
+                let path = PathBuf::from(file.name);
+                if is_epub_path(&path) {
+                    match import_epub_bytes(&file.bytes) {
+                        Ok(imported) => self.set_imported_epub(path, imported),
+                        Err(e) => self.input_file.error = Some(t1(locale, Key::ReadFailed, e)),
+                    }
+                    return;
+                }
+                match String::from_utf8(file.bytes) {
+                    Ok(content) => self.set_source_text(path, content),
+                    Err(e) => self.input_file.error = Some(t1(locale, Key::ReadFailed, e)),
+                }
+            }
+            WebPick::Cover => {
+                if let Some(file) = files.into_iter().next() {
+                    self.set_cover_image(PathBuf::from(file.name), file.bytes);
+                }
+            }
+            WebPick::ChapterHeader => {
+                if let Some(file) = files.into_iter().next() {
+                    self.chapter_header_image_path = file.name.clone();
+                    self.chapter_header_image =
+                        image_reader_from_bytes(Path::new(&file.name), file.bytes);
+                }
+            }
+            WebPick::Images => {
+                self.images.extend(
+                    files
+                        .into_iter()
+                        .map(|file| image_reader_from_bytes(Path::new(&file.name), file.bytes)),
+                );
+            }
+        }
+    }
+
+    /// 处理拖入窗口的文件：文本作为源文件载入，单张图片设为封面，多张图片加入插图列表。
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        if dropped.is_empty() {
+            return;
+        }
+        let locale = self.locale;
+        let mut projects = Vec::new();
+        let mut texts = Vec::new();
+        let mut images = Vec::new();
+        let mut skipped = Vec::new();
+        let mut web_texts = Vec::new();
+        let mut web_images = Vec::new();
+        for file in dropped {
+            let Some(path) = file.path else {
+                // Web 端拖入的文件没有路径，只带有文件名与内容。
+                match (DroppedKind::from_path(Path::new(&file.name)), file.bytes) {
+                    (Some(DroppedKind::Text), Some(bytes)) => web_texts.push(WebFile {
+                        name: file.name,
+                        bytes: bytes.to_vec(),
+                    }),
+                    (Some(DroppedKind::Image), Some(bytes)) => web_images.push(WebFile {
+                        name: file.name,
+                        bytes: bytes.to_vec(),
+                    }),
+                    _ => skipped.push(file.name),
+                }
+                continue;
+            };
+            match DroppedKind::from_path(&path) {
+                Some(DroppedKind::Project) => projects.push(path),
+                Some(DroppedKind::Text) => texts.push(path),
+                Some(DroppedKind::Image) => images.push(path),
+                None => skipped.push(path.to_string_lossy().to_string()),
+            }
         }
 
-        match load_font_asset(Path::new(&self.text_style.font_path)) {
-            Ok(asset) => {
-                self.font_error = None;
-                self.font_asset = Some(asset.clone());
-                Some(asset)
+        self.runtime_notice = None;
+        // 工程包含完整状态，同时拖入的其他文件在其基础上生效。
+        if let Some(path) = projects.into_iter().next() {
+            self.open_project(path);
+        }
+        if let Some(path) = texts.into_iter().next() {
+            self.load_text_file(path);
+        }
+        match images.len() {
+            0 => {}
+            1 => {
+                let path = images.remove(0);
+                self.load_cover_image(path);
             }
+            _ => {
+                self.images.extend(
+                    images
+                        .iter()
+                        .map(|path| image_reader_from_path(locale, path)),
+                );
+                self.panel_index = PanelIndex::Images;
+            }
+        }
+        if !web_texts.is_empty() {
+            self.load_web_files(WebPick::Text, web_texts);
+        }
+        match web_images.len() {
+            0 => {}
+            1 => self.load_web_files(WebPick::Cover, web_images),
+            _ => {
+                self.load_web_files(WebPick::Images, web_images);
+                self.panel_index = PanelIndex::Images;
+            }
+        }
+        if !skipped.is_empty() {
+            self.runtime_notice = Some(t1(locale, Key::DropUnsupported, skipped.join(", ")));
+        }
+    }
+
+    /// 在文件管理器中打开输出文件所在的文件夹。
+    fn open_output_folder(&mut self, path: &Path) {
+        if cfg!(target_arch = "wasm32") {
+            self.runtime_notice = Some(t(self.locale, Key::DesktopOnlyAction).to_string());
+        } else if let Some(folder) = path.parent() {
+            let _ = open_in_file_manager(folder);
+        }
+    }
+
+    /// 把当前工作状态整理为工程。
+    fn project(&self) -> Project {
+        let path =
+            |reader: &ImageFileReader| reader.path.clone().filter(|_| !reader.content.is_empty());
+        Project {
+            input_path: self.input_file.path.clone(),
+            merge_inputs: self.merged_inputs.clone(),
+            method: self.selected_method,
+            custom_regex: self.custom_regex(),
+            custom_regex_path: self.custom_regex_file.clone(),
+            strip_text_header: self.strip_text_header,
+            clean_whitespace: self.clean_whitespace,
+            cleanup_rules: self.cleanup_rules.clone(),
+            punctuation: self.punctuation,
+            title_exclusions: self.title_exclusions.clone(),
+            chapter_length: self.chapter_length,
+            chapters: self.chapter_editor.use_for_conversion.then(|| {
+                self.chapter_editor
+                    .chapters
+                    .iter()
+                    .map(ProjectChapter::from)
+                    .collect()
+            }),
+            book_info: self.book_info.clone(),
+            style: self.text_style.clone(),
+            cover_path: path(&self.input_image),
+            generate_cover: self.generate_cover,
+            cover_design: self.cover_design,
+            cover_fit: self.cover_fit,
+            chapter_header_image_path: path(&self.chapter_header_image),
+            chapter_header_fullbleed: self.chapter_header_fullbleed,
+            images: self
+                .images
+                .iter()
+                .filter_map(|image| {
+                    image.path.clone().map(|path| ProjectImage {
+                        path,
+                        caption: image.caption.clone(),
+                        chapter: image.chapter,
+                    })
+                })
+                .collect(),
+            include_images_section: self.include_images_section,
+            image_optimization: self.image_optimization,
+            toc_options: self.toc_options.clone(),
+            front_matter: self.front_matter.clone(),
+            back_matter: self.back_matter.clone(),
+            output_dir: self.output_path.clone(),
+            filename_template: self.filename_template.clone(),
+            include_generator_meta: self.include_generator_meta,
+            safe_mode: self.safe_mode,
+            epub_version: self.epub_version,
+            rtl_page_progression: self.rtl_page_progression,
+            kepub: self.kepub,
+            reproducible: self.reproducible,
+            compression: self.compression,
+            split_large_chapters: self.split_large_chapters,
+            spine_order: self.spine_order.clone(),
+            output_format: self.output_format,
+            pdf: self.pdf_options,
+            overwrite_policy: self.overwrite_policy,
+            chinese_conversion: self.chinese_conversion,
+            title_numerals: self.title_numerals,
+            ..Default::default()
+        }
+    }
+
+    /// 保存工程到 `path`，成功后记住该路径。
+    fn save_project(&mut self, path: PathBuf) {
+        match self.project().save(&path) {
+            Ok(()) => {
+                self.runtime_notice = None;
+                self.project_path = Some(path);
+            }
+            Err(err) => self.runtime_notice = Some(t1(self.locale, Key::ProjectSaveFailed, err)),
+        }
+    }
+
+    /// 打开工程：恢复全部设置并重新读取工程引用的文件。
+    ///
+    /// 个别文件读取失败不会中止打开，失败信息汇总到 `runtime_notice`。
+    fn open_project(&mut self, path: PathBuf) {
+        let project = match Project::load(&path) {
+            Ok(project) => project,
             Err(err) => {
-                self.font_error = Some(t1(self.locale, Key::ReadFailed, err));
-                None
+                self.runtime_notice = Some(t1(self.locale, Key::ProjectOpenFailed, err));
+                return;
+            }
+        };
+        let locale = self.locale;
+        let mut problems = Vec::new();
+
+        self.input_file = TextFileReader::default();
+        self.input_txt_path.clear();
+        if let Some(input) = project.input_path {
+            match std::fs::read_to_string(&input) {
+                Ok(content) => self.input_file.content = content,
+                Err(err) => problems.push(format!("{}: {err}", input.display())),
             }
+            self.input_txt_path = input.to_string_lossy().to_string();
+            self.input_file.path = Some(input);
+        }
+        self.merged_inputs = project.merge_inputs;
+        if !self.merged_inputs.is_empty() {
+            match merge_files(&self.merged_inputs) {
+                Ok(content) => self.input_file.content = content,
+                Err(err) => problems.push(err.to_string()),
+            }
+            self.input_txt_path = t1(locale, Key::MergedFiles, self.merged_inputs.len());
+            self.merge_items = self
+                .merged_inputs
+                .iter()
+                .cloned()
+                .map(MergeItem::new)
+                .collect();
+        }
+        self.editor_history.reset(&self.input_file.content);
+        self.selected_method = project.method;
+        self.custom_regex_patterns = project.custom_regex.lines().map(String::from).collect();
+        self.custom_regex_status = project
+            .custom_regex_path
+            .as_deref()
+            .map(|config| self.validate_custom_config(locale, config));
+        self.custom_regex_path = project
+            .custom_regex_path
+            .as_ref()
+            .map(|config| config.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.custom_regex_file = project.custom_regex_path;
+        self.strip_text_header = project.strip_text_header;
+        self.clean_whitespace = project.clean_whitespace;
+        self.whitespace_summary = None;
+        self.cleanup_rules = project.cleanup_rules;
+        self.punctuation = project.punctuation;
+        self.punctuation_preview = None;
+        self.title_exclusions = project.title_exclusions;
+        self.chapter_length = project.chapter_length;
+
+        self.book_info = project.book_info;
+        self.text_style = project.style;
+        self.generate_cover = project.generate_cover;
+        self.cover_design = project.cover_design;
+        self.cover_fit = project.cover_fit;
+        let mut read_image = |path: Option<PathBuf>| match path {
+            Some(path) => {
+                let reader = image_reader_from_path(locale, &path);
+                if let Some(err) = &reader.error {
+                    problems.push(format!("{}: {err}", path.display()));
+                }
+                reader
+            }
+            None => ImageFileReader::default(),
+        };
+        self.input_image = read_image(project.cover_path);
+        self.chapter_header_image = read_image(project.chapter_header_image_path);
+        self.images = project
+            .images
+            .into_iter()
+            .map(|image| ImageFileReader {
+                caption: image.caption,
+                chapter: image.chapter,
+                ..read_image(Some(image.path))
+            })
+            .collect();
+        self.input_image_path = self
+            .input_image
+            .path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.chapter_header_image_path = self
+            .chapter_header_image
+            .path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.chapter_header_fullbleed = project.chapter_header_fullbleed;
+        self.include_images_section = project.include_images_section;
+        self.image_optimization = project.image_optimization;
+        self.toc_options = project.toc_options;
+        self.front_matter = project.front_matter;
+        self.back_matter = project.back_matter;
+
+        self.output_path = project.output_dir;
+        self.filename_template = project.filename_template;
+        self.include_generator_meta = project.include_generator_meta;
+        self.safe_mode = project.safe_mode;
+        self.epub_version = project.epub_version;
+        self.rtl_page_progression = project.rtl_page_progression;
+        self.kepub = project.kepub;
+        self.reproducible = project.reproducible;
+        self.compression = project.compression;
+        self.split_large_chapters = project.split_large_chapters;
+        self.spine_order = project.spine_order;
+        self.output_format = project.output_format;
+        self.pdf_options = project.pdf;
+        self.overwrite_policy = project.overwrite_policy;
+        self.chinese_conversion = project.chinese_conversion;
+        self.title_numerals = project.title_numerals;
+
+        self.chapter_editor = ChapterEditorState::default();
+        if let Some(chapters) = project.chapters {
+            let signature = self.preview_signature();
+            self.chapter_editor
+                .restore(chapters.into_iter().map(Into::into).collect(), signature);
+        }
+        self.chapter_preview = None;
+        self.chapter_preview_error = None;
+        self.chapter_preview_signature = None;
+        self.refresh_fitted_cover();
+        if self.generate_cover {
+            self.refresh_cover_preview();
+        }
+
+        self.project_path = Some(path);
+        self.runtime_notice = (!problems.is_empty())
+            .then(|| t1(locale, Key::ProjectMissingFiles, problems.join("; ")));
+    }
+
+    /// 使用当前设置启动批量转换，所有条目重置为等待状态。
+    fn start_batch(&mut self) {
+        if self.batch_queue.is_empty() || self.conversion_job.is_some() {
+            return;
+        }
+        for item in &mut self.batch_queue {
+            item.status = BatchItemStatus::Pending;
+        }
+        let inputs = self
+            .batch_queue
+            .iter()
+            .map(|item| item.path.clone())
+            .collect();
+        self.batch_job = Some(BatchRequest::new(inputs, self.conversion_settings()));
+        self.next_batch_item();
+    }
+
+    /// 在后台启动下一个等待中的批量条目；没有剩余条目或批量已取消时结束批量任务。
+    fn next_batch_item(&mut self) {
+        while let Some(batch) = self.batch_job.as_ref() {
+            let Some(index) = self
+                .batch_queue
+                .iter()
+                .position(|item| item.status == BatchItemStatus::Pending)
+            else {
+                self.batch_job = None;
+                return;
+            };
+            let path = self.batch_queue[index].path.clone();
+            // wasm 不支持线程，直接在 UI 线程中转换。
+            if cfg!(target_arch = "wasm32") {
+                let result = batch.convert_item(&path, |_| {});
+                self.finish_batch_item(index, result);
+                continue;
+            }
+            self.conversion_job = Some(ConversionJob::spawn_batch_item(batch.clone(), path, index));
+            return;
+        }
+    }
+
+    /// 记录一个批量条目的转换结果。
+    fn finish_batch_item(
+        &mut self,
+        index: usize,
+        result: Result<ConversionResult, ConversionError>,
+    ) {
+        let Some(item) = self.batch_queue.get_mut(index) else {
+            return;
+        };
+        item.status = match result {
+            Ok(result) => {
+                self.recent.push_output(PathBuf::from(&result.output_path));
+                BatchItemStatus::Done(result.output_path)
+            }
+            Err(ConversionError::Cancelled) => {
+                BatchItemStatus::Failed(t(self.locale, Key::ConversionCancelled).to_string())
+            }
+            Err(err) => BatchItemStatus::Failed(err.to_string()),
+        };
+    }
+
+    /// 取消批量任务：中止正在转换的条目，其余条目保持等待。
+    fn cancel_batch(&mut self) {
+        self.batch_job = None;
+        if let Some(job) = &self.conversion_job
+            && job.batch_item.is_some()
+        {
+            job.cancel.cancel();
+        }
+    }
+
+    /// 按比例设置重新裁切/补边上传的封面；未启用或没有封面时清空结果。
+    fn refresh_fitted_cover(&mut self) {
+        if !self.cover_fit.enabled || self.input_image.content.is_empty() {
+            self.fitted_cover = ImageFileReader::default();
+            return;
+        }
+        self.fitted_cover = match fit_cover(&self.input_image.content, &self.cover_fit) {
+            // 路径只用于决定封面资源的扩展名。
+            Ok((bytes, extension)) => ImageFileReader {
+                content: Bytes::from(bytes),
+                path: Some(PathBuf::from(format!("cover.{extension}"))),
+                ..Default::default()
+            },
+            Err(err) => ImageFileReader {
+                error: Some(err),
+                ..Default::default()
+            },
+        };
+    }
+
+    fn cover_generation(&self) -> CoverGeneration {
+        CoverGeneration {
+            design: self.cover_design,
+            texture: (!self.cover_texture.content.is_empty())
+                .then(|| self.cover_texture.content.clone()),
+        }
+    }
+
+    /// 按当前书籍信息与封面设计重新渲染生成封面的预览。
+    fn refresh_cover_preview(&mut self) {
+        let fonts = self.resolve_font_assets();
+        self.generated_cover = match self.cover_generation().render(&self.book_info, &fonts) {
+            Ok(png) => ImageFileReader {
+                content: Bytes::from(png),
+                ..Default::default()
+            },
+            Err(err) => ImageFileReader {
+                error: Some(err),
+                ..Default::default()
+            },
+        };
+    }
+
+    /// 解析全部字体资源（优先使用缓存）。
+    ///
+    /// 按 `text_style.fonts` 的顺序返回，角色取自当前选择；未缓存的字体
+    /// 延迟加载，成功后写回缓存，读取失败的字体会被跳过并记录错误。
+    fn resolve_font_assets(&mut self) -> Vec<FontAsset> {
+        let mut assets = Vec::new();
+        for choice in &self.text_style.fonts {
+            let cached = self
+                .font_assets
+                .iter()
+                .find(|(path, _)| path == &choice.path)
+                .map(|(_, asset)| asset.clone());
+            let asset = match cached {
+                Some(asset) => asset,
+                None => match load_font_asset(Path::new(&choice.path)) {
+                    Ok(asset) => {
+                        self.font_error = None;
+                        self.font_assets.push((choice.path.clone(), asset.clone()));
+                        asset
+                    }
+                    Err(err) => {
+                        self.font_error = Some(t1(self.locale, Key::ReadFailed, err));
+                        continue;
+                    }
+                },
+            };
+            assets.push(FontAsset {
+                roles: choice.roles.clone(),
+                ..asset
+            });
         }
+        assets
     }
 
     /// 使用当前分章策略与源文本重建章节预览。
@@ -306,67 +1622,262 @@ impl MainApp {
     /// 预览仅保存轻量信息（章节数 + 前几个标题），在保证可校验性的同时
     /// 控制 UI 刷新成本。
     fn refresh_chapter_preview(&mut self) {
-        if self.input_file.content.trim().is_empty() {
-            self.chapter_preview_error = Some(t(self.locale, Key::PreviewTextEmpty).to_string());
-            self.chapter_preview = None;
-            return;
-        }
-
         let signature = self.preview_signature();
-        match crate::conversion::StrategyFactory::create(
-            self.selected_method,
-            &self.custom_regex_pattern,
-            self.custom_regex_file.as_ref(),
-        ) {
-            Ok(strategy) => match strategy.split(&self.input_file.content) {
-                Ok(chapters) => {
-                    let titles = chapters
-                        .iter()
-                        .take(2)
-                        .map(|c| c.title.clone())
-                        .collect::<Vec<_>>();
-                    self.chapter_preview = Some(ChapterPreview {
-                        count: chapters.len(),
-                        titles,
-                    });
-                    self.chapter_preview_error = None;
-                    self.chapter_preview_signature = Some(signature);
-                }
-                Err(err) => {
-                    self.chapter_preview_error = Some(err.to_string());
-                    self.chapter_preview = None;
-                }
-            },
+        match self.split_source_text() {
+            Ok(chapters) => {
+                let titles = chapters
+                    .iter()
+                    .take(2)
+                    .map(|c| c.title.clone())
+                    .collect::<Vec<_>>();
+                self.chapter_preview = Some(ChapterPreview {
+                    count: chapters.len(),
+                    titles,
+                });
+                self.chapter_preview_error = None;
+                self.chapter_preview_signature = Some(signature);
+            }
             Err(err) => {
-                self.chapter_preview_error = Some(err.to_string());
+                self.chapter_preview_error = Some(err);
                 self.chapter_preview = None;
             }
         }
     }
 
+    /// 按当前清理与分章设置切分源文本，与导出时的分章结果一致。
+    fn split_source_text(&self) -> Result<Vec<ChapterDraft>, String> {
+        if self.input_file.content.trim().is_empty() {
+            return Err(t(self.locale, Key::PreviewTextEmpty).to_string());
+        }
+        let source = clean_whitespace_if(
+            strip_text_header_if(&self.input_file.content, self.strip_text_header),
+            self.clean_whitespace,
+        );
+        let text = crate::apply_cleanup_rules(&source, &self.cleanup_rules)
+            .map_err(|err| t1(self.locale, Key::RegexError, err))?;
+        let text = self.punctuation.normalize(&text);
+        crate::conversion::StrategyFactory::create_with_options(
+            self.selected_method,
+            &self.custom_regex(),
+            self.custom_regex_file.as_ref(),
+            &self.split_options(),
+        )
+        .and_then(|strategy| strategy.split(&text))
+        .map_err(|err| err.to_string())
+    }
+
+    /// 打开预览阅读：启用章节编辑时翻看编辑后的章节，否则按当前设置重新分章。
+    fn open_reading_preview(&mut self) {
+        let chapters =
+            if self.chapter_editor.use_for_conversion && !self.chapter_editor.chapters.is_empty() {
+                Ok(self.chapter_editor.chapters.clone())
+            } else {
+                self.split_source_text()
+            };
+        let options = self.chapter_preview_options();
+        self.reading_preview.open(chapters, options);
+    }
+
+    /// 转换时的章节标题，供插图选择插入的章节；未启用章节编辑时按当前设置分章，结果按签名缓存。
+    fn conversion_chapter_titles(&mut self) -> Vec<String> {
+        if self.chapter_editor.use_for_conversion {
+            return self
+                .chapter_editor
+                .chapters
+                .iter()
+                .map(|chapter| chapter.title.clone())
+                .collect();
+        }
+        let signature = self.preview_signature();
+        if self
+            .chapter_titles
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != signature)
+        {
+            let titles = self
+                .split_source_text()
+                .map(|chapters| chapters.into_iter().map(|chapter| chapter.title).collect())
+                .unwrap_or_default();
+            self.chapter_titles = Some((signature, titles));
+        }
+        self.chapter_titles
+            .as_ref()
+            .map(|(_, titles)| titles.clone())
+            .unwrap_or_default()
+    }
+
+    /// 自定义正则按优先顺序逐行拼接，即 `ConversionRequest::custom_regex` 的格式。
+    fn custom_regex(&self) -> String {
+        self.custom_regex_patterns.join("\n")
+    }
+
     /// 计算分章相关输入的确定性签名。
     ///
     /// 当文本或分章配置变化后，用于判断章节编辑结果是否“过期”。
     fn preview_signature(&self) -> u64 {
         crate::chapter_signature(
             &self.input_file.content,
+            &self.preprocessing(),
             self.selected_method,
-            &self.custom_regex_pattern,
+            &self.custom_regex(),
+            &self.split_options(),
             self.custom_regex_file.as_deref(),
         )
     }
 
+    /// 分章前的文本预处理。
+    fn preprocessing(&self) -> Preprocessing<'_> {
+        Preprocessing {
+            strip_text_header: self.strip_text_header,
+            clean_whitespace: self.clean_whitespace,
+            cleanup_rules: &self.cleanup_rules,
+            punctuation: self.punctuation,
+        }
+    }
+
+    /// 分章方式之外的分章参数。
+    fn split_options(&self) -> SplitOptions<'_> {
+        SplitOptions {
+            title_exclusions: &self.title_exclusions,
+            chapter_length: self.chapter_length,
+        }
+    }
+
     fn validate_custom_config(&self, locale: Locale, path: &Path) -> (bool, String) {
-        match std::fs::read_to_string(path) {
-            Ok(content) => match Regex::new(content.trim()) {
-                Ok(_) => (true, t(locale, Key::ConfigRegexOk).to_string()),
-                Err(err) => (false, t1(locale, Key::RegexError, err)),
-            },
-            Err(err) => (false, t1(locale, Key::ReadFailed, err)),
+        match SplitConfig::load(path).and_then(|config| config.validate()) {
+            Ok(()) => (true, t(locale, Key::ConfigRegexOk).to_string()),
+            Err(ConversionError::Regex(err)) => (false, t1(locale, Key::RegexError, err)),
+            Err(ConversionError::Io(err)) => (false, t1(locale, Key::ReadFailed, err)),
+            Err(err) => (false, err.to_string()),
         }
     }
 }
 
+/// 后台转换任务：工作线程通过通道回传进度与最终结果。
+struct ConversionJob {
+    progress_rx: Receiver<ConversionProgress>,
+    result_rx: Receiver<Result<ConversionResult, ConversionError>>,
+    cancel: CancelToken,
+    progress: ConversionProgress,
+    batch_item: Option<usize>, // 批量转换时对应的队列下标
+}
+
+impl ConversionJob {
+    fn spawn(request: ConversionRequest) -> Self {
+        let cancel = request.cancel.clone();
+        Self::spawn_with(cancel, ConversionProgress::Splitting, move |on_progress| {
+            ConversionFacade::convert_with_progress(request, on_progress)
+        })
+    }
+
+    /// 批量条目在工作线程中读取源文件，读取阶段也会上报进度。
+    fn spawn_batch_item(batch: BatchRequest, path: PathBuf, index: usize) -> Self {
+        let cancel = batch.settings.cancel.clone();
+        Self {
+            batch_item: Some(index),
+            ..Self::spawn_with(cancel, ConversionProgress::Decoding, move |on_progress| {
+                batch.convert_item(&path, on_progress)
+            })
+        }
+    }
+
+    fn spawn_with(
+        cancel: CancelToken,
+        progress: ConversionProgress,
+        work: impl FnOnce(
+            &mut dyn FnMut(ConversionProgress),
+        ) -> Result<ConversionResult, ConversionError>
+        + Send
+        + 'static,
+    ) -> Self {
+        let (progress_tx, progress_rx) = mpsc::channel();
+        let (result_tx, result_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            // 界面已关闭时接收端会被丢弃，进度消息静默忽略。
+            let result = work(&mut |event| {
+                let _ = progress_tx.send(event);
+            });
+            let _ = result_tx.send(result);
+        });
+        Self {
+            progress_rx,
+            result_rx,
+            cancel,
+            progress,
+            batch_item: None,
+        }
+    }
+}
+
+/// 拖入文件按扩展名划分的类别。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DroppedKind {
+    Project,
+    Text,
+    Image,
+}
+
+impl DroppedKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            PROJECT_EXTENSION => Some(Self::Project),
+            "txt" | "md" | "markdown" | "epub" => Some(Self::Text),
+            "jpg" | "jpeg" | "png" | "webp" | "gif" | "svg" => Some(Self::Image),
+            _ => None,
+        }
+    }
+}
+
+/// 批量队列中的一个源文件及其转换状态。
+#[derive(Clone, Debug)]
+struct BatchItem {
+    path: PathBuf,
+    status: BatchItemStatus,
+}
+
+impl BatchItem {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            status: BatchItemStatus::Pending,
+        }
+    }
+}
+
+/// 合并列表中的一个文件，附带按所选编码解码后的首行，便于确认编码是否正确。
+#[derive(Clone, Debug)]
+struct MergeItem {
+    input: MergeInput,
+    preview: String,
+}
+
+impl MergeItem {
+    fn new(input: MergeInput) -> Self {
+        let mut item = Self {
+            input,
+            preview: String::new(),
+        };
+        item.refresh_preview();
+        item
+    }
+
+    fn refresh_preview(&mut self) {
+        const PREVIEW_CHARS: usize = 30;
+        self.preview = match self.input.read() {
+            Ok(text) => text
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .chars()
+                .take(PREVIEW_CHARS)
+                .collect(),
+            Err(err) => err.to_string(),
+        };
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 struct ChapterPreview {
     count: usize,
@@ -381,11 +1892,138 @@ impl eframe::App for MainApp {
 
     /// 每一帧都会调用。
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        apply_theme(ctx, self.theme_mode);
+        apply_theme(ctx, self.theme_mode, self.theme_custom);
+        // 内置字体不含假名，首次切换到日文界面时再载入系统字体。
+        if self.locale == Locale::Ja && !self.kana_font_loaded {
+            self.kana_font_loaded = true;
+            if !load_kana_font(ctx) {
+                log::warn!("No Japanese system font found; kana may not render.");
+            }
+        }
+        self.handle_dropped_files(ctx);
+        self.poll_web_files();
+        ui::shortcuts(self, ctx);
         ui::top_panel(self, ctx);
         ui::side_nav(self, ctx);
         ui::preview_panel(self, ctx);
         ui::central_panel(self, ctx);
         ui::dialogs(self, ctx);
+        ui::drop_overlay(self, ctx);
+        self.poll_conversion(ctx);
+        self.poll_metadata_lookup(ctx);
+        self.poll_calibre(ctx);
+        self.poll_kindle(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_round_trips_and_fills_missing_fields() {
+        let mut app = MainApp::default();
+        app.book_info.title = "书名".to_string();
+        app.text_style.font_size = 21.0;
+        app.output_format = OutputFormat::Pdf;
+        app.locale = Locale::En;
+        app.theme_mode = ThemeMode::Sepia;
+        app.theme_custom = Some(ThemeColors {
+            corner_radius: 4,
+            ..ThemeMode::Sepia.colors()
+        });
+        app.recent.push_text(PathBuf::from("book.txt"));
+        let saved = ron::to_string(&app).expect("serialize");
+
+        let restored = MainApp::restore(&saved).expect("restore");
+        assert_eq!(restored.book_info.title, "书名");
+        assert_eq!(restored.text_style.font_size, 21.0);
+        assert_eq!(restored.output_format, OutputFormat::Pdf);
+        assert_eq!(restored.locale, Locale::En);
+        assert_eq!(restored.theme_mode, ThemeMode::Sepia);
+        assert_eq!(restored.theme_custom, app.theme_custom);
+        assert_eq!(restored.recent.texts, vec![PathBuf::from("book.txt")]);
+        assert_eq!(
+            restored.available_panels,
+            MainApp::default().available_panels
+        );
+
+        // 旧存档缺少的字段取默认值，早期版本没有 `state_version` 字段。
+        let restored = MainApp::restore("(locale: En, book_info: (title: \"旧\"))").expect("old");
+        assert_eq!(restored.book_info.title, "旧");
+        assert_eq!(restored.state_version, STATE_VERSION);
+        assert_eq!(restored.filename_template, "{书名}_{作者}.epub");
+    }
+
+    #[test]
+    fn restore_rejects_newer_or_corrupt_state() {
+        assert!(MainApp::restore("(state_version: 99)").is_none());
+        assert!(MainApp::restore("not ron").is_none());
+        assert!(MainApp::restore("(epub_version: Epub9)").is_none());
+    }
+
+    #[test]
+    fn reset_settings_keeps_loaded_book() {
+        let mut app = MainApp::default();
+        app.book_info.title = "书名".to_string();
+        app.input_file.content = "正文".to_string();
+        app.text_style.font_size = 30.0;
+        app.kepub = true;
+        app.theme_mode = ThemeMode::Dark;
+        app.theme_custom = Some(ThemeMode::Midnight.colors());
+
+        app.reset_settings();
+        assert_eq!(app.book_info.title, "书名");
+        assert_eq!(app.input_file.content, "正文");
+        assert_eq!(app.text_style.font_size, TextStyle::default().font_size);
+        assert!(!app.kepub);
+        assert_eq!(app.theme_mode, ThemeMode::Light);
+        assert_eq!(app.theme_custom, None);
+    }
+
+    #[test]
+    fn batch_converts_in_background_and_advances_from_poll() {
+        let dir = std::env::temp_dir().join(format!("reasypub-batch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let mut app = MainApp {
+            output_path: dir.to_string_lossy().to_string(),
+            filename_template: "{书名}".to_string(),
+            ..Default::default()
+        };
+        for name in ["甲", "乙"] {
+            let path = dir.join(format!("{name}.txt"));
+            std::fs::write(&path, "第一章 开始\n正文。\n").expect("write text");
+            app.batch_queue.push(BatchItem::new(path));
+        }
+        app.batch_queue
+            .push(BatchItem::new(dir.join("missing.txt")));
+
+        app.start_batch();
+        // 第一项在后台线程中转换，启动后立即返回。
+        let job = app.conversion_job.as_ref().expect("background job");
+        assert_eq!(job.batch_item, Some(0));
+        assert_eq!(app.batch_queue[0].status, BatchItemStatus::Pending);
+
+        let ctx = egui::Context::default();
+        let started = std::time::Instant::now();
+        while app.batch_job.is_some() || app.conversion_job.is_some() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(60));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            app.poll_conversion(&ctx);
+        }
+        assert!(matches!(
+            app.batch_queue[0].status,
+            BatchItemStatus::Done(_)
+        ));
+        assert!(matches!(
+            app.batch_queue[1].status,
+            BatchItemStatus::Done(_)
+        ));
+        assert!(matches!(
+            app.batch_queue[2].status,
+            BatchItemStatus::Failed(_)
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
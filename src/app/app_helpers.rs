@@ -1,43 +1,64 @@
 use bytes::Bytes;
 #[cfg(not(target_arch = "wasm32"))]
 use rfd::FileDialog;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
 
-use crate::{BookInfo, FontAsset, ImageAsset, ImageFileReader, Key, Locale, TextFileReader, t, t1};
+use crate::{
+    FontAsset, ImageAsset, ImageFileReader, Key, Locale, TextFileReader, image_mime_from_extension,
+    t, t1,
+};
 
-use super::ThemeMode;
+use super::web::{WebInbox, WebPick};
+use super::{ThemeColors, ThemeMode};
 
-pub(super) fn apply_theme(ctx: &egui::Context, mode: ThemeMode) {
-    let mut visuals = match mode {
-        ThemeMode::Light => egui::Visuals::light(),
-        ThemeMode::Dark => egui::Visuals::dark(),
-    };
+/// 可作为源文本打开的扩展名；EPUB 先导入为章节。
+pub(super) const TEXT_EXTENSIONS: &[&str] = &["txt", "md", "markdown", "epub"];
+
+/// 源文件是否为要导入的 EPUB。
+pub(super) fn is_epub_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"))
+}
 
-    let accent = match mode {
-        ThemeMode::Light => egui::Color32::from_rgb(47, 125, 113),
-        ThemeMode::Dark => egui::Color32::from_rgb(94, 194, 177),
+/// 按预设与主题编辑器的自定义配色设置界面外观；`custom` 为空时使用预设配色。
+pub(super) fn apply_theme(ctx: &egui::Context, mode: ThemeMode, custom: Option<ThemeColors>) {
+    let mut visuals = if mode.is_dark() {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
     };
+    let colors = custom.unwrap_or(mode.colors());
+    let accent = colors.accent;
 
     visuals.selection.bg_fill = accent;
     visuals.hyperlink_color = accent;
     visuals.widgets.active.bg_fill = accent;
     visuals.widgets.hovered.bg_fill = accent.gamma_multiply(0.9);
-    visuals.window_corner_radius = egui::CornerRadius::same(12);
-    visuals.panel_fill = if mode == ThemeMode::Light {
-        egui::Color32::from_rgb(248, 249, 250)
-    } else {
-        egui::Color32::from_rgb(28, 30, 33)
-    };
-    visuals.widgets.noninteractive.bg_fill = if mode == ThemeMode::Light {
-        egui::Color32::from_rgb(243, 245, 247)
-    } else {
-        egui::Color32::from_rgb(36, 38, 42)
-    };
-    visuals.widgets.noninteractive.bg_stroke.color = if mode == ThemeMode::Light {
-        egui::Color32::from_rgb(221, 225, 229)
-    } else {
-        egui::Color32::from_rgb(60, 64, 68)
+    visuals.window_corner_radius = egui::CornerRadius::same(colors.corner_radius);
+    visuals.menu_corner_radius = egui::CornerRadius::same(colors.corner_radius / 2);
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        widget.corner_radius = egui::CornerRadius::same(colors.corner_radius / 6);
+    }
+    visuals.panel_fill = colors.panel_fill;
+    let (card_fill, card_stroke) = match mode {
+        ThemeMode::Light => ((243, 245, 247), (221, 225, 229)),
+        ThemeMode::Dark => ((36, 38, 42), (60, 64, 68)),
+        ThemeMode::Sepia => ((239, 229, 210), (219, 204, 180)),
+        ThemeMode::Midnight => ((29, 34, 50), (52, 60, 84)),
     };
+    visuals.widgets.noninteractive.bg_fill =
+        egui::Color32::from_rgb(card_fill.0, card_fill.1, card_fill.2);
+    visuals.widgets.noninteractive.bg_stroke.color =
+        egui::Color32::from_rgb(card_stroke.0, card_stroke.1, card_stroke.2);
 
     ctx.set_visuals(visuals);
 
@@ -47,6 +68,49 @@ pub(super) fn apply_theme(ctx: &egui::Context, mode: ThemeMode) {
     ctx.set_style(style);
 }
 
+/// 常见的系统日文字体位置（Windows、macOS、Linux 的 Noto CJK）。
+#[cfg(not(target_arch = "wasm32"))]
+const KANA_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\msgothic.ttc",
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+];
+
+/// 内置字体不含假名：载入第一个找到的系统日文字体作为后备字体，找不到时返回 `false`。
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn load_kana_font(ctx: &egui::Context) -> bool {
+    use egui::epaint::text::{FontInsert, FontPriority, InsertFontFamily};
+
+    let Some(bytes) = KANA_FONT_CANDIDATES
+        .iter()
+        .find_map(|path| std::fs::read(path).ok())
+    else {
+        return false;
+    };
+    let families = [egui::FontFamily::Proportional, egui::FontFamily::Monospace]
+        .into_iter()
+        .map(|family| InsertFontFamily {
+            family,
+            priority: FontPriority::Lowest,
+        })
+        .collect();
+    ctx.add_font(FontInsert::new(
+        "kana",
+        egui::FontData::from_owned(bytes),
+        families,
+    ));
+    true
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(super) fn load_kana_font(_ctx: &egui::Context) -> bool {
+    false
+}
+
 pub(super) fn card(ui: &mut egui::Ui, title: &str, add_contents: impl FnOnce(&mut egui::Ui)) {
     let fill = ui.visuals().widgets.noninteractive.bg_fill;
     let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
@@ -83,6 +147,19 @@ pub(super) fn display_or_placeholder(value: &str, fallback: &str) -> String {
     }
 }
 
+/// 以 B/KB/MB 显示字节数，用于图片优化前后的体积对比。
+pub(super) fn format_size(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    let size = bytes as f64;
+    if size >= KB * KB {
+        format!("{:.1} MB", size / (KB * KB))
+    } else if size >= KB {
+        format!("{:.0} KB", size / KB)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
 pub(super) fn open_in_file_manager(path: &Path) -> std::io::Result<()> {
     let _ = path;
 
@@ -110,7 +187,7 @@ pub(super) fn open_in_file_manager(path: &Path) -> std::io::Result<()> {
 #[cfg(not(target_arch = "wasm32"))]
 fn pick_text_file(filter_name: &str) -> Option<PathBuf> {
     FileDialog::new()
-        .add_filter(filter_name, &["txt"])
+        .add_filter(filter_name, TEXT_EXTENSIONS)
         .pick_file()
 }
 
@@ -136,6 +213,7 @@ pub(super) fn cover_asset_from_reader(reader: &ImageFileReader) -> Option<ImageA
         bytes: reader.content.clone(),
         mime,
         caption: None,
+        chapter: None,
     })
 }
 
@@ -156,9 +234,28 @@ pub(super) fn chapter_header_asset_from_reader(reader: &ImageFileReader) -> Opti
         bytes: reader.content.clone(),
         mime,
         caption: None,
+        chapter: None,
     })
 }
 
+/// 列出文件夹中可转换的文本文件（txt/md/markdown），按路径排序，不递归子目录。
+pub(super) fn collect_text_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_text = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .is_some_and(|ext| matches!(ext.as_str(), "txt" | "md" | "markdown"));
+        if path.is_file() && is_text {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 pub(super) fn collect_image_assets(images: &[ImageFileReader]) -> Vec<ImageAsset> {
     images
         .iter()
@@ -167,6 +264,48 @@ pub(super) fn collect_image_assets(images: &[ImageFileReader]) -> Vec<ImageAsset
         .collect()
 }
 
+/// 找出内容重复的插图：每项为首个相同图片的下标。
+///
+/// 按读入时算好的 `content_hash` 分组，只有哈希相同的图片才逐字节比较。
+pub(super) fn duplicate_images(images: &[ImageFileReader]) -> Vec<Option<usize>> {
+    let mut groups: HashMap<u64, Vec<usize>> = HashMap::new();
+    images
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            if image.content.is_empty() {
+                return None;
+            }
+            let group = groups.entry(image.content_hash).or_default();
+            let first = group
+                .iter()
+                .copied()
+                .find(|&earlier| images[earlier].content == image.content);
+            if first.is_none() {
+                group.push(index);
+            }
+            first
+        })
+        .collect()
+}
+
+/// 图片内容的哈希，只在读入图片时计算。
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 把重复插图合并到首个相同图片：首张未指定章节时沿用副本的章节。
+pub(super) fn merge_duplicate_image(images: &mut Vec<ImageFileReader>, index: usize, first: usize) {
+    let duplicate = images.remove(index);
+    if let Some(kept) = images.get_mut(first)
+        && kept.chapter.is_none()
+    {
+        kept.chapter = duplicate.chapter;
+    }
+}
+
 fn image_asset_from_reader(reader: &ImageFileReader, index: usize) -> Option<ImageAsset> {
     if reader.content.is_empty() {
         return None;
@@ -198,6 +337,7 @@ fn image_asset_from_reader(reader: &ImageFileReader, index: usize) -> Option<Ima
         bytes: reader.content.clone(),
         mime,
         caption: reader.caption.clone(),
+        chapter: reader.chapter,
     })
 }
 
@@ -225,6 +365,7 @@ pub(super) fn load_font_asset(path: &Path) -> Result<FontAsset, std::io::Error>
         family,
         bytes,
         mime,
+        roles: Vec::new(),
     })
 }
 
@@ -237,15 +378,6 @@ fn sanitize_resource_name(name: &str) -> String {
     cleaned.replace(' ', "_")
 }
 
-fn image_mime_from_extension(ext: &str) -> &'static str {
-    match ext {
-        "jpg" | "jpeg" => "image/jpeg",
-        "webp" => "image/webp",
-        "gif" => "image/gif",
-        _ => "image/png",
-    }
-}
-
 fn font_mime_from_extension(ext: &str) -> &'static str {
     match ext {
         "otf" => "font/otf",
@@ -269,7 +401,7 @@ pub(super) fn powered_by_egui_and_eframe(ui: &mut egui::Ui, locale: Locale) {
 }
 
 /// 从文件名中提取书名与作者。
-fn parse_filename_to_book_info(filename: &str) -> (String, String) {
+pub(super) fn parse_filename_to_book_info(filename: &str) -> (String, String) {
     let stem = std::path::Path::new(filename)
         .file_stem()
         .and_then(|s| s.to_str())
@@ -297,80 +429,78 @@ fn parse_filename_to_book_info(filename: &str) -> (String, String) {
     (title, author)
 }
 
-/// 读取文本文件。
+/// 选择要打开的文本文件；Web 端改为异步读取，选中的文件稍后经 `web_inbox` 送达。
+pub(super) fn pick_text_input(
+    ctx: &egui::Context,
+    locale: Locale,
+    web_inbox: &WebInbox,
+) -> Option<PathBuf> {
+    let filter_name = t(locale, Key::TextFileFilter);
+    if cfg!(target_arch = "wasm32") {
+        web_inbox.pick(ctx, WebPick::Text, filter_name, TEXT_EXTENSIONS);
+        None
+    } else {
+        pick_text_file(filter_name)
+    }
+}
+
+/// 显示打开文本文件按钮与当前文件状态，返回用户选中的文件路径。
+///
+/// Web 端改为异步读取，选中的文件稍后经 `web_inbox` 送达。
 pub(super) fn readtxt(
     ui: &mut egui::Ui,
     locale: Locale,
-    input_txt: &mut TextFileReader,
-    input_txt_path: &mut String,
-    book_info: &mut BookInfo,
-    runtime_notice: &mut Option<String>,
-) {
+    input_txt: &TextFileReader,
+    input_txt_path: &str,
+    web_inbox: &WebInbox,
+) -> Option<PathBuf> {
+    let mut picked = None;
     ui.horizontal(|ui| {
         if ui.button(t(locale, Key::OpenTextFile)).clicked() {
-            if let Some(path) = pick_text_file(t(locale, Key::TextFileFilter)) {
-                match std::fs::read_to_string(&path) {
-                    Ok(content) => {
-                        input_txt.content = content;
-                        input_txt.error = None;
-                        input_txt.path = Some(path.clone());
-                        *input_txt_path = path.to_string_lossy().to_string();
-                        *runtime_notice = None;
-
-                        if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                            let (title, author) = parse_filename_to_book_info(filename);
-                            if book_info.title.trim().is_empty() {
-                                book_info.title = title;
-                            }
-                            if book_info.author.trim().is_empty() {
-                                book_info.author = author;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        input_txt.error = Some(t1(locale, Key::ReadFailed, e));
-                    }
-                }
-            } else if cfg!(target_arch = "wasm32") {
-                *runtime_notice = Some(t(locale, Key::DesktopOnlyAction).to_string());
-            }
+            picked = pick_text_input(ui.ctx(), locale, web_inbox);
         }
 
         // 显示错误信息。
         if let Some(err) = &input_txt.error {
             ui.label(egui::RichText::new(err).color(egui::Color32::RED));
-        } else if input_txt.path.is_some() && !input_txt_path.is_empty() {
-            ui.label(input_txt_path.clone());
+        } else if (input_txt.path.is_some() || !input_txt.content.is_empty())
+            && !input_txt_path.is_empty()
+        {
+            ui.label(input_txt_path);
         } else {
             ui.label(t(locale, Key::InputTxtPlaceholder));
         }
     });
+    picked
 }
 
 /// 根据图片路径构建读取器。
 pub(super) fn image_reader_from_path(locale: Locale, path: &Path) -> ImageFileReader {
-    let caption = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_string());
     match std::fs::read(path) {
-        Ok(content) => ImageFileReader {
-            content: Bytes::from(content),
-            error: None,
-            path: Some(path.to_path_buf()),
-            texture: None,
-            caption,
-        },
+        Ok(content) => image_reader_from_bytes(path, content),
         Err(err) => ImageFileReader {
-            content: Bytes::new(),
             error: Some(t1(locale, Key::ReadFailed, err)),
-            path: Some(path.to_path_buf()),
-            texture: None,
-            caption,
+            ..image_reader_from_bytes(path, Vec::new())
         },
     }
 }
 
+/// 根据已读入内存的图片构建读取器；Web 端的 `path` 只是文件名。
+pub(super) fn image_reader_from_bytes(path: &Path, content: Vec<u8>) -> ImageFileReader {
+    ImageFileReader {
+        content_hash: content_hash(&content),
+        content: Bytes::from(content),
+        error: None,
+        path: Some(path.to_path_buf()),
+        texture: None,
+        caption: path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string()),
+        chapter: None,
+    }
+}
+
 /// 渲染图片预览区域。
 pub(super) fn show_image_ui(ui: &mut egui::Ui, locale: Locale, reader: &mut ImageFileReader) {
     // 带边框的容器。
@@ -403,6 +533,28 @@ mod tests {
     use std::path::PathBuf;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    #[test]
+    fn collect_text_files_filters_and_sorts() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("reasypub-batch-dir-{suffix}"));
+        std::fs::create_dir_all(dir.join("nested.txt")).expect("create dir");
+        for name in ["b.txt", "a.MD", "c.png", "d.markdown"] {
+            std::fs::write(dir.join(name), "x").expect("write");
+        }
+
+        let files = collect_text_files(&dir).expect("list");
+        let names: Vec<_> = files
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["a.MD", "b.txt", "d.markdown"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn parse_filename_splits_title_author() {
         let (title, author) = parse_filename_to_book_info("My Book - Alice.txt");
@@ -445,6 +597,7 @@ mod tests {
         assert_eq!(image_mime_from_extension("jpeg"), "image/jpeg");
         assert_eq!(image_mime_from_extension("webp"), "image/webp");
         assert_eq!(image_mime_from_extension("gif"), "image/gif");
+        assert_eq!(image_mime_from_extension("svg"), "image/svg+xml");
         assert_eq!(image_mime_from_extension("png"), "image/png");
     }
 
@@ -463,6 +616,8 @@ mod tests {
             path: Some(PathBuf::from("cover.jpg")),
             texture: None,
             caption: None,
+            chapter: None,
+            content_hash: 0,
         };
         let asset = cover_asset_from_reader(&reader).expect("asset");
         assert_eq!(asset.name, "cover.jpg");
@@ -478,6 +633,8 @@ mod tests {
             path: Some(PathBuf::from("header.webp")),
             texture: None,
             caption: None,
+            chapter: None,
+            content_hash: 0,
         };
         let asset = chapter_header_asset_from_reader(&reader).expect("asset");
         assert_eq!(asset.name, "chapter-header.webp");
@@ -492,6 +649,8 @@ mod tests {
             path: Some(PathBuf::from("hero image.JPG")),
             texture: None,
             caption: Some("Hero".to_string()),
+            chapter: None,
+            content_hash: 0,
         };
         let asset = image_asset_from_reader(&reader, 0).expect("asset");
         assert_eq!(asset.name, "hero_image.JPG");
@@ -499,6 +658,64 @@ mod tests {
         assert_eq!(asset.caption.as_deref(), Some("Hero"));
     }
 
+    #[test]
+    fn collect_image_assets_keeps_order_and_chapters() {
+        let reader = |name: &str, chapter: Option<usize>| ImageFileReader {
+            chapter,
+            ..image_reader_from_bytes(Path::new(name), b"img".to_vec())
+        };
+        let images = [
+            reader("b.png", Some(3)),
+            ImageFileReader::default(),
+            reader("a.png", None),
+        ];
+        let assets = collect_image_assets(&images);
+        let placed: Vec<(&str, Option<usize>)> = assets
+            .iter()
+            .map(|asset| (asset.name.as_str(), asset.chapter))
+            .collect();
+        assert_eq!(placed, [("b.png", Some(3)), ("a.png", None)]);
+    }
+
+    #[test]
+    fn duplicate_images_point_at_first_copy() {
+        let reader =
+            |name: &str, content: &[u8]| image_reader_from_bytes(Path::new(name), content.to_vec());
+        let mut images = vec![
+            reader("a.png", b"same"),
+            reader("b.png", b"diff"),
+            ImageFileReader::default(),
+            reader("c.png", b"same"),
+            ImageFileReader::default(),
+            ImageFileReader {
+                chapter: Some(4),
+                ..reader("d.png", b"same")
+            },
+        ];
+        assert_eq!(images[0].content_hash, images[3].content_hash);
+        assert_ne!(images[0].content_hash, images[1].content_hash);
+        assert_eq!(
+            duplicate_images(&images),
+            [None, None, None, Some(0), None, Some(0)]
+        );
+        // 哈希碰撞时仍逐字节比较，不会误判为重复。
+        let collided = ImageFileReader {
+            content_hash: images[0].content_hash,
+            ..reader("e.png", b"other")
+        };
+        assert_eq!(
+            duplicate_images(&[images[0].clone(), collided]),
+            [None, None]
+        );
+
+        merge_duplicate_image(&mut images, 5, 0);
+        assert_eq!(images.len(), 5);
+        assert_eq!(images[0].chapter, Some(4));
+        merge_duplicate_image(&mut images, 3, 0);
+        assert_eq!(images[0].chapter, Some(4));
+        assert_eq!(duplicate_images(&images), [None; 4]);
+    }
+
     #[test]
     fn display_or_placeholder_uses_fallback() {
         assert_eq!(
@@ -0,0 +1,116 @@
+//! 在线元数据查询对话框的状态与后台任务。
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use crate::BookInfo;
+use crate::metadata_lookup::{
+    LookupQuery, MetadataCandidate, MetadataSource, fetch_cover, fetch_details, lookup,
+};
+
+/// 后台任务回传的结果。
+pub(super) enum LookupEvent {
+    Candidates(Result<Vec<MetadataCandidate>, String>),
+    /// 封面地址与下载到的图片内容。
+    Cover(String, Result<Vec<u8>, String>),
+    /// 选用的候选结果的封面地址，与读取条目页面补全后的结果。
+    Details(Option<String>, Result<Box<MetadataCandidate>, String>),
+}
+
+/// 查询或下载封面的后台任务。Web 端没有线程，请求直接返回错误，任务同步完成。
+pub(super) struct LookupJob {
+    rx: Receiver<LookupEvent>,
+}
+
+impl LookupJob {
+    fn spawn(task: impl FnOnce() -> LookupEvent + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(move || {
+            let _ = tx.send(task());
+        });
+        #[cfg(target_arch = "wasm32")]
+        let _ = tx.send(task());
+        Self { rx }
+    }
+}
+
+/// 对话框状态。
+#[derive(Default)]
+pub(super) struct MetadataLookupState {
+    pub open: bool,
+    pub source: MetadataSource,
+    pub query: LookupQuery,
+    /// 选用候选结果时一并下载封面。
+    pub download_cover: bool,
+    pub candidates: Vec<MetadataCandidate>,
+    /// 已完成过一次查询（用于区分“尚未查询”和“没有结果”）。
+    pub searched: bool,
+    pub error: Option<String>,
+    job: Option<LookupJob>,
+}
+
+impl MetadataLookupState {
+    /// 打开对话框，用当前书籍信息预填查询条件。
+    pub fn open_for(&mut self, info: &BookInfo) {
+        self.open = true;
+        self.query = LookupQuery::from_book_info(info);
+        self.candidates.clear();
+        self.searched = false;
+        self.error = None;
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    pub fn search(&mut self) {
+        let source = self.source;
+        let query = self.query.clone();
+        self.error = None;
+        self.job = Some(LookupJob::spawn(move || {
+            LookupEvent::Candidates(lookup(source, &query))
+        }));
+    }
+
+    pub fn download_cover(&mut self, url: String) {
+        self.job = Some(LookupJob::spawn(move || {
+            let result = fetch_cover(&url);
+            LookupEvent::Cover(url, result)
+        }));
+    }
+
+    /// 读取候选结果的条目页面，补全简介与标签等搜索结果中缺少的字段。
+    pub fn fetch_details(&mut self, candidate: MetadataCandidate) {
+        self.job = Some(LookupJob::spawn(move || {
+            let result = fetch_details(&candidate).map(Box::new);
+            LookupEvent::Details(candidate.cover_url, result)
+        }));
+    }
+
+    /// 取出已完成任务的结果；候选结果直接记录在对话框中，其余事件交给调用方处理。
+    pub fn poll(&mut self) -> Option<LookupEvent> {
+        let job = self.job.as_ref()?;
+        let event = match job.rx.try_recv() {
+            Ok(event) => event,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                LookupEvent::Candidates(Err("Lookup worker stopped unexpectedly.".to_string()))
+            }
+        };
+        self.job = None;
+        match event {
+            LookupEvent::Candidates(result) => {
+                self.searched = true;
+                match result {
+                    Ok(candidates) => self.candidates = candidates,
+                    Err(err) => {
+                        self.candidates.clear();
+                        self.error = Some(err);
+                    }
+                }
+                None
+            }
+            event => Some(event),
+        }
+    }
+}
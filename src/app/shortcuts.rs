@@ -0,0 +1,155 @@
+//! 全局快捷键：可绑定的动作、默认按键与随应用状态保存的自定义绑定。
+
+use egui::{Key as KeyCode, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::{Key, Locale, t};
+
+/// 可绑定快捷键的全局动作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ShortcutAction {
+    OpenText,
+    SaveProject,
+    OpenEditor,
+    Convert,
+    Help,
+}
+
+impl ShortcutAction {
+    pub(super) const ALL: [ShortcutAction; 5] = [
+        ShortcutAction::OpenText,
+        ShortcutAction::SaveProject,
+        ShortcutAction::OpenEditor,
+        ShortcutAction::Convert,
+        ShortcutAction::Help,
+    ];
+
+    pub(super) fn label(self, locale: Locale) -> &'static str {
+        t(
+            locale,
+            match self {
+                ShortcutAction::OpenText => Key::OpenTextFile,
+                ShortcutAction::SaveProject => Key::SaveProject,
+                ShortcutAction::OpenEditor => Key::EditTxt,
+                ShortcutAction::Convert => Key::Convert,
+                ShortcutAction::Help => Key::Help,
+            },
+        )
+    }
+}
+
+/// 各动作的快捷键。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub(super) struct Shortcuts {
+    pub open_text: KeyboardShortcut,
+    pub save_project: KeyboardShortcut,
+    pub open_editor: KeyboardShortcut,
+    pub convert: KeyboardShortcut,
+    pub help: KeyboardShortcut,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            open_text: KeyboardShortcut::new(Modifiers::COMMAND, KeyCode::O),
+            save_project: KeyboardShortcut::new(Modifiers::COMMAND, KeyCode::S),
+            open_editor: KeyboardShortcut::new(Modifiers::COMMAND, KeyCode::E),
+            convert: KeyboardShortcut::new(Modifiers::COMMAND, KeyCode::Enter),
+            help: KeyboardShortcut::new(Modifiers::NONE, KeyCode::F1),
+        }
+    }
+}
+
+impl Shortcuts {
+    pub(super) fn get(&self, action: ShortcutAction) -> KeyboardShortcut {
+        match action {
+            ShortcutAction::OpenText => self.open_text,
+            ShortcutAction::SaveProject => self.save_project,
+            ShortcutAction::OpenEditor => self.open_editor,
+            ShortcutAction::Convert => self.convert,
+            ShortcutAction::Help => self.help,
+        }
+    }
+
+    pub(super) fn get_mut(&mut self, action: ShortcutAction) -> &mut KeyboardShortcut {
+        match action {
+            ShortcutAction::OpenText => &mut self.open_text,
+            ShortcutAction::SaveProject => &mut self.save_project,
+            ShortcutAction::OpenEditor => &mut self.open_editor,
+            ShortcutAction::Convert => &mut self.convert,
+            ShortcutAction::Help => &mut self.help,
+        }
+    }
+
+    /// 与 `action` 使用相同按键的其他动作。
+    pub(super) fn conflict(&self, action: ShortcutAction) -> Option<ShortcutAction> {
+        let shortcut = self.get(action);
+        ShortcutAction::ALL
+            .into_iter()
+            .find(|other| *other != action && self.get(*other) == shortcut)
+    }
+
+    /// 消费本帧按下的快捷键并返回对应动作；修饰键多的组合优先匹配。
+    pub(super) fn consume(&self, input: &mut egui::InputState) -> Option<ShortcutAction> {
+        let mut actions = ShortcutAction::ALL;
+        actions
+            .sort_by_key(|action| std::cmp::Reverse(modifier_count(self.get(*action).modifiers)));
+        actions
+            .into_iter()
+            .find(|action| input.consume_shortcut(&self.get(*action)))
+    }
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [
+        modifiers.alt,
+        modifiers.ctrl || modifiers.command,
+        modifiers.shift,
+        modifiers.mac_cmd,
+    ]
+    .into_iter()
+    .filter(|pressed| *pressed)
+    .count()
+}
+
+/// 录制新快捷键时，取本帧第一个按下的按键（Esc 除外）及其修饰键。
+pub(super) fn pressed_shortcut(input: &egui::InputState) -> Option<KeyboardShortcut> {
+    input.events.iter().find_map(|event| match event {
+        egui::Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } if *key != KeyCode::Escape => Some(KeyboardShortcut::new(*modifiers, *key)),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_have_no_conflicts_and_round_trip() {
+        let shortcuts = Shortcuts::default();
+        for action in ShortcutAction::ALL {
+            assert_eq!(shortcuts.conflict(action), None);
+        }
+        let saved = ron::to_string(&shortcuts).expect("serialize");
+        assert_eq!(
+            ron::from_str::<Shortcuts>(&saved).expect("deserialize"),
+            shortcuts
+        );
+    }
+
+    #[test]
+    fn conflict_reports_other_action() {
+        let mut shortcuts = Shortcuts::default();
+        *shortcuts.get_mut(ShortcutAction::Help) = shortcuts.get(ShortcutAction::Convert);
+        assert_eq!(
+            shortcuts.conflict(ShortcutAction::Help),
+            Some(ShortcutAction::Convert)
+        );
+    }
+}
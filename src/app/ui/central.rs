@@ -1,16 +1,38 @@
-use bytes::Bytes;
 use regex::Regex;
 #[cfg(not(target_arch = "wasm32"))]
 use rfd::FileDialog;
 use std::path::PathBuf;
 
-use crate::{ConversionMethod, CssTemplate, ImageFileReader, Key, PanelIndex, t, t1, t2};
+use crate::components::thumbnails::{Thumbnail, ThumbnailCache};
+use crate::conversion::BatchItemStatus;
+use crate::cover::{CoverBackground, CoverFit, CoverFitMode};
+use crate::devices::DeviceKind;
+use crate::language::{detect_language, normalize_language_tag};
+use crate::numerals::TitleNumerals;
+use crate::punctuation::PunctuationNormalization;
+use crate::text_header::TextHeader;
+use crate::toc::InlineToc;
+use crate::whitespace::clean_whitespace;
+use crate::zhconv::ChineseConversion;
+use crate::{
+    Accessibility, BackMatter, BackMatterKind, ChapterDraft, CleanupRule, ConversionMethod,
+    Creator, CreatorRole, CssTemplate, DEFAULT_COPYRIGHT_TEMPLATE, EpubVersion, FontChoice,
+    FontRole, FrontMatter, FrontMatterPage, ImageCompatibility, ImageFileReader, Key, Locale,
+    MetadataEntry, OutputFormat, OverwritePolicy, PanelIndex, PdfPageSize, SpineSection,
+    description_from_text, t, t1, t2,
+};
 
-use super::super::MainApp;
 use super::super::app_helpers::{
-    card, image_reader_from_path, load_font_asset, powered_by_egui_and_eframe, primary_button,
-    readtxt,
+    card, collect_text_files, duplicate_images, image_reader_from_path, load_font_asset,
+    merge_duplicate_image, powered_by_egui_and_eframe, primary_button, readtxt,
 };
+use super::super::shortcuts::ShortcutAction;
+use super::super::web::WebPick;
+use super::super::{BatchItem, MainApp};
+use super::dialogs::progress_stage;
+
+/// 标点规范化预览中最多列出的改动行数。
+const PUNCTUATION_PREVIEW_LINES: usize = 50;
 
 #[cfg(not(target_arch = "wasm32"))]
 fn pick_image_file(filter_name: &str, exts: &[&str]) -> Option<PathBuf> {
@@ -25,7 +47,7 @@ fn pick_image_file(_filter_name: &str, _exts: &[&str]) -> Option<PathBuf> {
 #[cfg(not(target_arch = "wasm32"))]
 fn pick_config_file(filter_name: &str) -> Option<PathBuf> {
     FileDialog::new()
-        .add_filter(filter_name, &["txt", "conf", "regex"])
+        .add_filter(filter_name, &["toml", "txt", "conf", "regex"])
         .pick_file()
 }
 
@@ -44,6 +66,16 @@ fn pick_css_import_file() -> Option<PathBuf> {
     None
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_epubcheck_file() -> Option<PathBuf> {
+    FileDialog::new().pick_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pick_epubcheck_file() -> Option<PathBuf> {
+    None
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn pick_css_export_file() -> Option<PathBuf> {
     FileDialog::new().add_filter("CSS", &["css"]).save_file()
@@ -86,6 +118,458 @@ fn pick_folder() -> Option<PathBuf> {
     None
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn pick_text_files(filter_name: &str) -> Option<Vec<PathBuf>> {
+    FileDialog::new()
+        .add_filter(filter_name, &["txt", "md", "markdown"])
+        .pick_files()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(super) fn pick_text_files(_filter_name: &str) -> Option<Vec<PathBuf>> {
+    None
+}
+
+/// 格式与字体面板底部的样章预览，随行高、缩进、字号、颜色等设置实时更新。
+fn style_sample_ui(app: &mut MainApp, ui: &mut egui::Ui) {
+    let locale = app.locale;
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(8.0);
+    ui.label(egui::RichText::new(t(locale, Key::StyleSample)).strong());
+    ui.add_space(4.0);
+    let sample = ChapterDraft {
+        title: t(locale, Key::StyleSampleTitle).to_string(),
+        content: t(locale, Key::StyleSampleText).to_string(),
+        ..Default::default()
+    };
+    let options = app.chapter_preview_options();
+    app.style_sample.show(ui, &sample, &options);
+}
+
+/// 插图缩略图：占位框大小固定，滚动到可见区域时才请求解码。
+fn thumbnail_ui(
+    ui: &mut egui::Ui,
+    thumbnails: &mut ThumbnailCache,
+    image: &ImageFileReader,
+    locale: Locale,
+) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(150.0, 150.0), egui::Sense::hover());
+    if image.content.is_empty() || !ui.is_rect_visible(rect) {
+        return;
+    }
+    match thumbnails.request(ui.ctx(), &image.content, image.is_svg()) {
+        Thumbnail::Ready(texture) => {
+            ui.put(
+                rect,
+                egui::Image::from_texture(texture)
+                    .max_size(rect.size())
+                    .corner_radius(4.0),
+            );
+        }
+        Thumbnail::Loading => {
+            ui.put(rect, egui::Label::new(t(locale, Key::Loading)));
+        }
+        Thumbnail::Failed(err) => {
+            ui.put(
+                rect,
+                egui::Label::new(egui::RichText::new(err).color(egui::Color32::RED)).wrap(),
+            );
+        }
+    }
+}
+
+/// 插图的“插入到章节”下拉框；不选择章节时只放在插图页。
+fn image_chapter_ui(
+    ui: &mut egui::Ui,
+    locale: Locale,
+    index: usize,
+    chapter: &mut Option<usize>,
+    titles: &[String],
+) {
+    let label = |number: usize| match titles.get(number) {
+        Some(title) => t2(locale, Key::ChapterIndex, number + 1, title),
+        None => t1(locale, Key::ImageChapterMissing, number + 1),
+    };
+    let selected = match *chapter {
+        Some(number) => label(number),
+        None => t(locale, Key::InsertIntoGallery).to_string(),
+    };
+    ui.horizontal(|ui| {
+        ui.label(t(locale, Key::InsertIntoChapter));
+        egui::ComboBox::from_id_salt(("image_chapter", index))
+            .selected_text(selected)
+            .width(240.0)
+            .truncate()
+            .show_ui(ui, |ui| {
+                ui.selectable_value(chapter, None, t(locale, Key::InsertIntoGallery));
+                for number in 0..titles.len() {
+                    ui.selectable_value(chapter, Some(number), label(number));
+                }
+            });
+    });
+}
+
+/// 拖动插图经过第 `index` 张时按指针位于上半或下半部分画出插入线；松开时返回 `(被拖动的序号, 插入位置)`。
+fn drop_target(ui: &egui::Ui, row: &egui::Response, index: usize) -> Option<(usize, usize)> {
+    row.dnd_hover_payload::<usize>()?;
+    let pointer = ui.input(|input| input.pointer.interact_pos())?;
+    let before = pointer.y < row.rect.center().y;
+    let y = if before {
+        row.rect.top()
+    } else {
+        row.rect.bottom()
+    };
+    ui.painter().hline(
+        row.rect.x_range(),
+        y,
+        egui::Stroke::new(2.0, ui.visuals().selection.bg_fill),
+    );
+    let from = row.dnd_release_payload::<usize>()?;
+    Some((*from, if before { index } else { index + 1 }))
+}
+
+/// 按优先顺序编辑分章正则；全部为空时使用内置中文章节规则。
+fn regex_patterns_ui(ui: &mut egui::Ui, locale: Locale, patterns: &mut Vec<String>) {
+    let tr = |key| t(locale, key);
+    pattern_rows_ui(ui, locale, patterns);
+    if patterns.iter().all(|pattern| pattern.trim().is_empty()) {
+        ui.label(egui::RichText::new(tr(Key::BuiltinChinesePattern)).color(egui::Color32::GRAY));
+    } else if patterns.len() > 1 {
+        ui.label(egui::RichText::new(tr(Key::RegexPriorityHint)).small());
+    }
+    if ui.button(tr(Key::AddRegexPattern)).clicked() {
+        patterns.push(String::new());
+    }
+}
+
+/// 编辑标题排除规则。
+fn title_exclusions_ui(ui: &mut egui::Ui, locale: Locale, patterns: &mut Vec<String>) {
+    let tr = |key| t(locale, key);
+    ui.label(tr(Key::TitleExclusions));
+    ui.label(egui::RichText::new(tr(Key::TitleExclusionsHint)).small());
+    pattern_rows_ui(ui, locale, patterns);
+    if ui.button(tr(Key::AddTitleExclusion)).clicked() {
+        patterns.push(String::new());
+    }
+}
+
+/// 标点与全半角规范化方式，以及按清理后文本计算的改动预览。
+fn punctuation_ui(ui: &mut egui::Ui, app: &mut MainApp, locale: Locale) {
+    let tr = |key| t(locale, key);
+    ui.horizontal(|ui| {
+        ui.label(tr(Key::PunctuationNormalize));
+        let before = app.punctuation;
+        egui::ComboBox::from_id_salt("punctuation_normalization")
+            .selected_text(app.punctuation.label(locale))
+            .show_ui(ui, |ui| {
+                for mode in PunctuationNormalization::ALL {
+                    ui.selectable_value(&mut app.punctuation, mode, mode.label(locale));
+                }
+            });
+        if app.punctuation != before {
+            app.punctuation_preview = None;
+        }
+        if ui
+            .add_enabled(
+                app.punctuation != PunctuationNormalization::Off,
+                egui::Button::new(tr(Key::PreviewPunctuation)),
+            )
+            .clicked()
+        {
+            let text = crate::apply_cleanup_rules(&app.input_file.content, &app.cleanup_rules)
+                .unwrap_or_else(|_| app.input_file.content.clone());
+            app.punctuation_preview =
+                Some(app.punctuation.preview(&text, PUNCTUATION_PREVIEW_LINES));
+        }
+    });
+    ui.label(egui::RichText::new(tr(Key::PunctuationHint)).small());
+    let Some(preview) = &app.punctuation_preview else {
+        return;
+    };
+    if preview.total == 0 {
+        ui.label(tr(Key::PunctuationNoChanges));
+        return;
+    }
+    ui.label(t1(locale, Key::PunctuationChanges, preview.total));
+    egui::ScrollArea::vertical()
+        .id_salt("punctuation_preview")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for change in &preview.changes {
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", change.line, change.before))
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+                ui.label(egui::RichText::new(format!("{}: {}", change.line, change.after)).small());
+            }
+        });
+}
+
+/// 源文本开头识别到的书名、作者信息块，以及填入书籍信息的按钮。
+fn text_header_ui(ui: &mut egui::Ui, app: &mut MainApp, locale: Locale, header: &TextHeader) {
+    const PREVIEW_CHARS: usize = 40;
+    let tr = |key| t(locale, key);
+    ui.group(|ui| {
+        ui.label(tr(Key::TextHeaderFound));
+        for (key, value) in header.fields() {
+            let mut value: String = value.lines().next().unwrap_or_default().to_string();
+            if value.chars().count() > PREVIEW_CHARS {
+                value = value.chars().take(PREVIEW_CHARS).collect::<String>() + "…";
+            }
+            let label = tr(key).trim_end_matches(':');
+            ui.label(egui::RichText::new(format!("{label}: {value}")).small());
+        }
+        if ui.button(tr(Key::FillFromTextHeader)).clicked() {
+            header.apply_to(&mut app.book_info, true);
+        }
+    });
+}
+
+/// 不可见字符与多余空白清理：开关与按需计算的统计。
+fn whitespace_ui(ui: &mut egui::Ui, app: &mut MainApp, locale: Locale) {
+    let tr = |key| t(locale, key);
+    ui.horizontal(|ui| {
+        if ui
+            .checkbox(&mut app.clean_whitespace, tr(Key::CleanWhitespace))
+            .on_hover_text(tr(Key::CleanWhitespaceHint))
+            .changed()
+        {
+            app.whitespace_summary = None;
+        }
+        if ui
+            .add_enabled(
+                app.clean_whitespace,
+                egui::Button::new(tr(Key::CountWhitespace)),
+            )
+            .clicked()
+        {
+            app.whitespace_summary = Some(clean_whitespace(&app.input_file.content).1);
+        }
+    });
+    if let Some(summary) = &app.whitespace_summary {
+        ui.label(summary.label(locale));
+        if summary.total() > 0 {
+            ui.label(egui::RichText::new(summary.details(locale)).small());
+        }
+    }
+}
+
+/// 正则列表的行：编号、输入框、上移/下移/删除按钮与校验结果。
+fn pattern_rows_ui(ui: &mut egui::Ui, locale: Locale, patterns: &mut Vec<String>) {
+    let tr = |key| t(locale, key);
+    let total = patterns.len();
+    let mut swap = None;
+    let mut remove = None;
+    for (index, pattern) in patterns.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}.", index + 1));
+            ui.add(egui::TextEdit::singleline(pattern).desired_width(220.0));
+            if ui.small_button(tr(Key::Up)).clicked() && index > 0 {
+                swap = Some((index - 1, index));
+            }
+            if ui.small_button(tr(Key::Down)).clicked() && index + 1 < total {
+                swap = Some((index, index + 1));
+            }
+            if ui.small_button(tr(Key::Delete)).clicked() {
+                remove = Some(index);
+            }
+        });
+        if pattern.trim().is_empty() {
+            continue;
+        }
+        match Regex::new(pattern.trim()) {
+            Ok(_) => {
+                ui.label(egui::RichText::new(tr(Key::RegexOk)).color(egui::Color32::GREEN));
+            }
+            Err(err) => {
+                ui.label(
+                    egui::RichText::new(t1(locale, Key::RegexError, err)).color(egui::Color32::RED),
+                );
+            }
+        }
+    }
+    if let Some((a, b)) = swap {
+        patterns.swap(a, b);
+    }
+    if let Some(index) = remove {
+        patterns.remove(index);
+    }
+}
+
+fn creators_ui(ui: &mut egui::Ui, locale: Locale, creators: &mut Vec<Creator>) {
+    let tr = |key| t(locale, key);
+    let mut remove = None;
+    for (index, creator) in creators.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut creator.name).desired_width(160.0));
+            egui::ComboBox::from_id_salt(("creator_role", index))
+                .selected_text(creator.role.label(locale))
+                .show_ui(ui, |ui| {
+                    for role in CreatorRole::ALL {
+                        ui.selectable_value(&mut creator.role, role, role.label(locale));
+                    }
+                });
+            if ui.small_button(tr(Key::Delete)).clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        creators.remove(index);
+    }
+    if ui.button(tr(Key::AddCreator)).clicked() {
+        creators.push(Creator::default());
+    }
+}
+
+fn custom_metadata_ui(ui: &mut egui::Ui, locale: Locale, entries: &mut Vec<MetadataEntry>) {
+    let tr = |key| t(locale, key);
+    ui.label(egui::RichText::new(tr(Key::AdvancedMetadataHint)).small());
+    let mut remove = None;
+    egui::Grid::new("custom_metadata_grid")
+        .num_columns(3)
+        .show(ui, |ui| {
+            ui.label(tr(Key::MetadataKey));
+            ui.label(tr(Key::MetadataValue));
+            ui.end_row();
+            for (index, entry) in entries.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut entry.key).desired_width(180.0));
+                ui.add(egui::TextEdit::singleline(&mut entry.value).desired_width(180.0));
+                ui.horizontal(|ui| {
+                    if ui.small_button(tr(Key::Delete)).clicked() {
+                        remove = Some(index);
+                    }
+                    if !entry.key.trim().is_empty()
+                        && !entry.value.trim().is_empty()
+                        && !entry.is_valid()
+                    {
+                        ui.label(
+                            egui::RichText::new(tr(Key::InvalidMetadataKey))
+                                .color(egui::Color32::RED),
+                        );
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    if let Some(index) = remove {
+        entries.remove(index);
+    }
+    if ui.button(tr(Key::AddMetadataEntry)).clicked() {
+        entries.push(MetadataEntry::default());
+    }
+}
+
+fn front_matter_ui(ui: &mut egui::Ui, locale: Locale, front_matter: &mut FrontMatter) {
+    let tr = |key| t(locale, key);
+    ui.label(egui::RichText::new(tr(Key::FrontMatterHint)).small());
+    ui.checkbox(
+        &mut front_matter.title_page,
+        FrontMatterPage::TitlePage.label(locale),
+    );
+    ui.horizontal(|ui| {
+        ui.label(FrontMatterPage::Copyright.label(locale));
+        if ui.small_button(tr(Key::UseCopyrightTemplate)).clicked() {
+            front_matter.copyright = DEFAULT_COPYRIGHT_TEMPLATE.to_string();
+        }
+    });
+    ui.add(
+        egui::TextEdit::multiline(&mut front_matter.copyright)
+            .hint_text(DEFAULT_COPYRIGHT_TEMPLATE)
+            .desired_rows(4),
+    );
+    ui.label(FrontMatterPage::Dedication.label(locale));
+    ui.add(egui::TextEdit::multiline(&mut front_matter.dedication).desired_rows(2));
+    ui.label(FrontMatterPage::Preface.label(locale));
+    ui.add(egui::TextEdit::multiline(&mut front_matter.preface).desired_rows(6));
+}
+
+fn back_matter_ui(ui: &mut egui::Ui, locale: Locale, back_matter: &mut BackMatter) {
+    let tr = |key| t(locale, key);
+    ui.label(egui::RichText::new(tr(Key::BackMatterHint)).small());
+    ui.horizontal(|ui| {
+        for kind in BackMatterKind::ALL {
+            ui.radio_value(&mut back_matter.kind, kind, kind.label(locale));
+        }
+    });
+    ui.label(tr(Key::BackMatterTitle));
+    ui.add(
+        egui::TextEdit::singleline(&mut back_matter.title)
+            .hint_text(back_matter.kind.label(locale)),
+    );
+    ui.checkbox(&mut back_matter.from_text, tr(Key::BackMatterFromText));
+    ui.add(egui::TextEdit::multiline(&mut back_matter.text).desired_rows(4));
+}
+
+fn accessibility_ui(ui: &mut egui::Ui, locale: Locale, accessibility: &mut Accessibility) {
+    let tr = |key| t(locale, key);
+    ui.label(egui::RichText::new(tr(Key::AccessibilityHint)).small());
+    ui.label(tr(Key::AccessModes));
+    ui.text_edit_singleline(&mut accessibility.access_modes);
+    ui.label(tr(Key::AccessibilityFeatures));
+    ui.text_edit_singleline(&mut accessibility.features);
+    ui.label(tr(Key::AccessibilityHazards));
+    ui.text_edit_singleline(&mut accessibility.hazards);
+    ui.label(tr(Key::AccessibilitySummary));
+    ui.add(egui::TextEdit::multiline(&mut accessibility.summary).desired_rows(3));
+}
+
+/// 可拖动排序的 spine 部分列表。
+fn spine_order_ui(ui: &mut egui::Ui, locale: Locale, order: &mut Vec<SpineSection>) {
+    let tr = |key| t(locale, key);
+    ui.label(egui::RichText::new(tr(Key::SpineOrderHint)).small());
+    *order = SpineSection::ordered(order);
+    let mut drop_move = None;
+    for (index, section) in order.iter().enumerate() {
+        let row = ui
+            .horizontal(|ui| {
+                let drag_id = ui.make_persistent_id(("spine_order_drag", index));
+                ui.dnd_drag_source(drag_id, *section, |ui| {
+                    ui.label(egui::RichText::new("≡").strong());
+                })
+                .response
+                .on_hover_text(tr(Key::DragToReorder));
+                ui.label(section.label(locale));
+            })
+            .response;
+        // 与章节编辑器相同：按指针位于上半或下半部分，在本项之前或之后显示插入线。
+        if row.dnd_hover_payload::<SpineSection>().is_some()
+            && let Some(pointer) = ui.input(|input| input.pointer.interact_pos())
+        {
+            let before = pointer.y < row.rect.center().y;
+            let y = if before {
+                row.rect.top()
+            } else {
+                row.rect.bottom()
+            };
+            ui.painter().hline(
+                row.rect.x_range(),
+                y,
+                egui::Stroke::new(2.0, ui.visuals().selection.bg_fill),
+            );
+            if let Some(dragged) = row.dnd_release_payload::<SpineSection>() {
+                drop_move = Some((*dragged, if before { index } else { index + 1 }));
+            }
+        }
+    }
+    if let Some((dragged, insert_before)) = drop_move
+        && let Some(from) = order.iter().position(|section| *section == dragged)
+    {
+        let section = order.remove(from);
+        let position = if from < insert_before {
+            insert_before - 1
+        } else {
+            insert_before
+        };
+        order.insert(position, section);
+    }
+    if ui.button(tr(Key::ResetSpineOrder)).clicked() {
+        *order = SpineSection::ALL.to_vec();
+    }
+}
+
 pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
     let locale = app.locale;
     let tr = |key| t(locale, key);
@@ -96,14 +580,18 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
             .show(ui, |ui| {
                 ui.set_width(ui.available_width());
                 card(ui, tr(Key::Basics), |ui| {
-                    readtxt(
+                    if let Some(path) = readtxt(
                         ui,
                         locale,
-                        &mut app.input_file,
-                        &mut app.input_txt_path,
-                        &mut app.book_info,
-                        &mut app.runtime_notice,
-                    );
+                        &app.input_file,
+                        &app.input_txt_path,
+                        &app.web_inbox,
+                    ) {
+                        app.load_text_file(path);
+                    }
+                    if ui.button(tr(Key::MergeTextFiles)).clicked() {
+                        app.show_merge_files = true;
+                    }
 
                     if let Some(notice) = &app.runtime_notice {
                         ui.add_space(4.0);
@@ -114,40 +602,18 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
 
                     ui.horizontal(|ui| {
                         if ui.button(tr(Key::ChangeCover)).clicked() {
-                            if let Some(path) =
-                                pick_image_file(tr(Key::PanelImages), &["jpeg", "png", "webp", "jpg"])
-                            {
+                            let exts = &["jpeg", "png", "webp", "jpg", "svg"];
+                            if cfg!(target_arch = "wasm32") {
+                                app.web_inbox.pick(ctx, WebPick::Cover, tr(Key::PanelImages), exts);
+                            } else if let Some(path) = pick_image_file(tr(Key::PanelImages), exts) {
                                 app.runtime_notice = None;
-                                if let Ok(metadata) = std::fs::metadata(&path)
-                                    && metadata.len() > 10 * 1024 * 1024
-                                {
-                                    app.input_image.error = Some(t(locale, Key::FileTooLarge).to_string());
-                                    return;
-                                }
-
-                                match std::fs::read(&path) {
-                                    Ok(content) => {
-                                        app.input_image.content = Bytes::from(content);
-                                        app.input_image.error = None;
-                                        app.input_image.path = Some(path.clone());
-                                        app.input_image_path = path.to_string_lossy().to_string();
-                                        app.input_image.caption = path
-                                            .file_stem()
-                                            .and_then(|s| s.to_str())
-                                            .map(|s| s.to_string());
-                                        app.input_image.texture = None;
-                                    }
-                                    Err(e) => {
-                                        app.input_image.error = Some(t1(locale, Key::ReadFailed, e));
-                                    }
-                                }
-                            } else if cfg!(target_arch = "wasm32") {
-                                app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
+                                app.load_cover_image(path);
                             }
                         }
                         if ui.button(tr(Key::ClearCover)).clicked() {
                             app.input_image = ImageFileReader::default();
                             app.input_image_path.clear();
+                            app.refresh_fitted_cover();
                         }
 
                         if app.input_image.error.is_none() {
@@ -162,23 +628,145 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                         }
                     });
 
+                    if !app.generate_cover && !app.input_image.content.is_empty() {
+                        let mut changed = ui
+                            .checkbox(&mut app.cover_fit.enabled, tr(Key::CoverFitEnable))
+                            .changed();
+                        if app.cover_fit.enabled {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::CoverFitRatio));
+                                for (width, height) in CoverFit::PRESETS {
+                                    let selected = app.cover_fit.ratio_width == width
+                                        && app.cover_fit.ratio_height == height;
+                                    if ui
+                                        .selectable_label(selected, format!("{width}:{height}"))
+                                        .clicked()
+                                    {
+                                        app.cover_fit.ratio_width = width;
+                                        app.cover_fit.ratio_height = height;
+                                        changed = true;
+                                    }
+                                }
+                                changed |= ui
+                                    .add(egui::DragValue::new(&mut app.cover_fit.ratio_width).range(1..=100))
+                                    .changed();
+                                ui.label(":");
+                                changed |= ui
+                                    .add(egui::DragValue::new(&mut app.cover_fit.ratio_height).range(1..=100))
+                                    .changed();
+                            });
+                            ui.horizontal(|ui| {
+                                for mode in CoverFitMode::ALL {
+                                    changed |= ui
+                                        .radio_value(&mut app.cover_fit.mode, mode, mode.label(locale))
+                                        .changed();
+                                }
+                                match app.cover_fit.mode {
+                                    CoverFitMode::Crop => {
+                                        ui.label(tr(Key::CoverFitAnchor));
+                                        changed |= ui
+                                            .add(egui::Slider::new(&mut app.cover_fit.anchor, 0.0..=1.0))
+                                            .changed();
+                                    }
+                                    CoverFitMode::Pad => {
+                                        ui.label(tr(Key::CoverFitPadColor));
+                                        changed |= ui
+                                            .color_edit_button_srgba(&mut app.cover_fit.pad_color)
+                                            .changed();
+                                    }
+                                }
+                            });
+                        }
+                        // 拖动滑块或取色时等指针松开再处理，避免每帧重新解码封面。
+                        app.fitted_cover_dirty |= changed;
+                        if app.fitted_cover_dirty && !ui.input(|input| input.pointer.any_down()) {
+                            app.fitted_cover_dirty = false;
+                            app.refresh_fitted_cover();
+                        }
+                    }
+                    if ui.checkbox(&mut app.generate_cover, tr(Key::GenerateCover)).changed()
+                        && app.generate_cover
+                    {
+                        app.refresh_cover_preview();
+                    }
+                    if app.generate_cover {
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::CoverBackgroundLabel));
+                            egui::ComboBox::from_id_salt("cover_background")
+                                .selected_text(app.cover_design.background.label(locale))
+                                .show_ui(ui, |ui| {
+                                    for background in CoverBackground::ALL {
+                                        ui.selectable_value(
+                                            &mut app.cover_design.background,
+                                            background,
+                                            background.label(locale),
+                                        );
+                                    }
+                                });
+                            ui.label(tr(Key::CoverPrimaryColor));
+                            ui.color_edit_button_srgba(&mut app.cover_design.primary);
+                            if app.cover_design.background == CoverBackground::Gradient {
+                                ui.label(tr(Key::CoverSecondaryColor));
+                                ui.color_edit_button_srgba(&mut app.cover_design.secondary);
+                            }
+                            ui.label(tr(Key::CoverTextColor));
+                            ui.color_edit_button_srgba(&mut app.cover_design.text_color);
+                        });
+                        if app.cover_design.background == CoverBackground::Texture {
+                            ui.horizontal(|ui| {
+                                if ui.button(tr(Key::ChooseCoverTexture)).clicked() {
+                                    if let Some(path) = pick_image_file(
+                                        tr(Key::PanelImages),
+                                        &["jpeg", "png", "webp", "jpg"],
+                                    ) {
+                                        app.runtime_notice = None;
+                                        app.cover_texture = image_reader_from_path(locale, &path);
+                                        app.refresh_cover_preview();
+                                    } else if cfg!(target_arch = "wasm32") {
+                                        app.runtime_notice =
+                                            Some(tr(Key::DesktopOnlyAction).to_string());
+                                    }
+                                }
+                                if let Some(path) = &app.cover_texture.path {
+                                    ui.label(path.to_string_lossy());
+                                }
+                                if let Some(err) = &app.cover_texture.error {
+                                    ui.label(egui::RichText::new(err).color(egui::Color32::RED));
+                                }
+                            });
+                        }
+                        if ui.button(tr(Key::RefreshCoverPreview)).clicked() {
+                            app.refresh_cover_preview();
+                        }
+                        ui.label(egui::RichText::new(tr(Key::GenerateCoverHint)).small());
+                    }
+
                     ui.add_space(4.0);
                     ui.horizontal(|ui| {
                         ui.label(tr(Key::TitleLabel));
                         ui.text_edit_singleline(&mut app.book_info.title);
                         ui.label(tr(Key::AuthorLabel));
-                        ui.text_edit_singleline(&mut app.book_info.author);
+                        ui.text_edit_singleline(app.book_info.primary_author_mut());
                     });
 
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
-                        if ui.button(tr(Key::EditTxt)).clicked() {
+                        let shortcut_text =
+                            |action| ctx.format_shortcut(&app.shortcuts.get(action));
+                        if ui
+                            .button(tr(Key::EditTxt))
+                            .on_hover_text(shortcut_text(ShortcutAction::OpenEditor))
+                            .clicked()
+                        {
                             app.show_editor = true;
                         }
                         if ui.button(tr(Key::ChapterEditor)).clicked() {
                             app.chapter_editor.open = true;
                         }
-                        if primary_button(ui, tr(Key::Convert)).clicked() {
+                        if primary_button(ui, tr(Key::Convert))
+                            .on_hover_text(shortcut_text(ShortcutAction::Convert))
+                            .clicked()
+                        {
                             app.run_conversion();
                         }
                     });
@@ -272,33 +860,8 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                                                 method.label(locale),
                                             );
                                             ui.label(tr(Key::RegexPattern));
-                                            ui.text_edit_singleline(&mut app.custom_regex_pattern);
                                         });
-                                        if app.custom_regex_pattern.trim().is_empty() {
-                                            ui.label(
-                                                egui::RichText::new(tr(Key::BuiltinChinesePattern))
-                                                    .color(egui::Color32::GRAY),
-                                            );
-                                        } else {
-                                            match Regex::new(app.custom_regex_pattern.trim()) {
-                                                Ok(_) => {
-                                                    ui.label(
-                                                        egui::RichText::new(tr(Key::RegexOk))
-                                                            .color(egui::Color32::GREEN),
-                                                    );
-                                                }
-                                                Err(err) => {
-                                                    ui.label(
-                                                        egui::RichText::new(t1(
-                                                            locale,
-                                                            Key::RegexError,
-                                                            err,
-                                                        ))
-                                                        .color(egui::Color32::RED),
-                                                    );
-                                                }
-                                            }
-                                        }
+                                        regex_patterns_ui(ui, locale, &mut app.custom_regex_patterns);
                                     });
                                 }
                                 ConversionMethod::SimpleRules => {
@@ -318,8 +881,127 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                                         );
                                     });
                                 }
+                                ConversionMethod::MarkdownHeadings => {
+                                    ui.horizontal(|ui| {
+                                        ui.radio_value(
+                                            &mut app.selected_method,
+                                            method,
+                                            method.label(locale),
+                                        );
+                                        ui.label(
+                                            egui::RichText::new("# / ##")
+                                                .monospace()
+                                                .size(12.0),
+                                        );
+                                    });
+                                }
+                                ConversionMethod::ByLength => {
+                                    ui.horizontal(|ui| {
+                                        ui.radio_value(
+                                            &mut app.selected_method,
+                                            method,
+                                            method.label(locale),
+                                        );
+                                        ui.add(
+                                            egui::DragValue::new(&mut app.chapter_length)
+                                                .range(100..=100_000)
+                                                .speed(100),
+                                        );
+                                        ui.label(tr(Key::ChapterLengthChars));
+                                    });
+                                }
+                                ConversionMethod::FromToc => {
+                                    ui.horizontal(|ui| {
+                                        ui.radio_value(
+                                            &mut app.selected_method,
+                                            method,
+                                            method.label(locale),
+                                        );
+                                        // 只扫描文本开头，每帧检测的开销很小。
+                                        let status = match InlineToc::detect(
+                                            &app.input_file.content,
+                                        ) {
+                                            Some(toc) => egui::RichText::new(t1(
+                                                locale,
+                                                Key::TocDetected,
+                                                toc.titles.len(),
+                                            )),
+                                            None => egui::RichText::new(tr(Key::TocNotFound))
+                                                .color(egui::Color32::GRAY),
+                                        };
+                                        ui.label(status.small());
+                                    });
+                                }
+                                ConversionMethod::Heuristic => {
+                                    ui.horizontal(|ui| {
+                                        ui.radio_value(
+                                            &mut app.selected_method,
+                                            method,
+                                            method.label(locale),
+                                        );
+                                        if ui.button(tr(Key::ReviewInChapterEditor)).clicked() {
+                                            app.selected_method = method;
+                                            app.chapter_editor.open_for_review();
+                                        }
+                                    });
+                                }
+                            }
+                        }
+                        ui.add_space(8.0);
+                        ui.separator();
+                        title_exclusions_ui(ui, locale, &mut app.title_exclusions);
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.label(tr(Key::TextCleanup));
+                        ui.label(egui::RichText::new(tr(Key::TextCleanupHint)).small());
+                        ui.checkbox(&mut app.strip_text_header, tr(Key::StripTextHeader))
+                            .on_hover_text(tr(Key::StripTextHeaderHint));
+                        whitespace_ui(ui, app, locale);
+                        let total = app.cleanup_rules.len();
+                        let mut swap = None;
+                        let mut remove = None;
+                        for (index, rule) in app.cleanup_rules.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut rule.enabled, "");
+                                ui.label(tr(Key::CleanupFind));
+                                ui.add(egui::TextEdit::singleline(&mut rule.find).desired_width(160.0));
+                                ui.label(tr(Key::CleanupReplace));
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut rule.replace).desired_width(120.0),
+                                );
+                                ui.checkbox(&mut rule.regex, tr(Key::CleanupRegex));
+                                if ui.small_button(tr(Key::Up)).clicked() && index > 0 {
+                                    swap = Some((index - 1, index));
+                                }
+                                if ui.small_button(tr(Key::Down)).clicked() && index + 1 < total {
+                                    swap = Some((index, index + 1));
+                                }
+                                if ui.small_button(tr(Key::Delete)).clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                            if rule.regex
+                                && !rule.find.is_empty()
+                                && let Err(err) = Regex::new(&rule.find)
+                            {
+                                ui.label(
+                                    egui::RichText::new(t1(locale, Key::RegexError, err))
+                                        .color(egui::Color32::RED),
+                                );
                             }
                         }
+                        if let Some((a, b)) = swap {
+                            app.cleanup_rules.swap(a, b);
+                        }
+                        if let Some(index) = remove {
+                            app.cleanup_rules.remove(index);
+                        }
+                        if ui.button(tr(Key::AddCleanupRule)).clicked() {
+                            app.cleanup_rules.push(CleanupRule::default());
+                        }
+                        ui.add_space(6.0);
+                        punctuation_ui(ui, app, locale);
+
                         ui.add_space(6.0);
                         ui.checkbox(
                             &mut app.chapter_editor.use_for_conversion,
@@ -380,6 +1062,45 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                             );
                             ui.label(format!("{:.1}", app.text_style.text_indent));
                         });
+                        ui.checkbox(
+                            &mut app.text_style.strip_heading_number,
+                            tr(Key::StripHeadingNumber),
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::HeadingTemplate));
+                            ui.add(
+                                egui::TextEdit::singleline(&mut app.text_style.heading_template)
+                                    .hint_text("{label} · {title}")
+                                    .desired_width(180.0),
+                            );
+                        })
+                        .response
+                        .on_hover_text(tr(Key::HeadingTemplateHint));
+                        ui.checkbox(&mut app.text_style.vertical, tr(Key::VerticalWriting));
+                        ui.checkbox(
+                            &mut app.text_style.smart_typography,
+                            tr(Key::SmartTypography),
+                        )
+                        .on_hover_text(tr(Key::SmartTypographyHint));
+                        ui.checkbox(&mut app.text_style.inline_markup, tr(Key::InlineMarkup))
+                            .on_hover_text(tr(Key::InlineMarkupHint));
+                        ui.checkbox(
+                            &mut app.text_style.reflow_paragraphs,
+                            tr(Key::ReflowParagraphs),
+                        );
+                        if app.text_style.reflow_paragraphs {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::ReflowSensitivity));
+                                ui.add(
+                                    egui::Slider::new(
+                                        &mut app.text_style.reflow_sensitivity,
+                                        0.0..=1.0,
+                                    )
+                                    .step_by(0.05),
+                                );
+                            });
+                            ui.label(egui::RichText::new(tr(Key::ReflowHint)).small());
+                        }
 
                         ui.add_space(10.0);
                         ui.separator();
@@ -403,6 +1124,7 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                             egui::RichText::new(app.text_style.css_template.description(locale))
                                 .small(),
                         );
+                        style_sample_ui(app, ui);
                     }
                     PanelIndex::CSS => {
                         ui.add_space(8.0);
@@ -467,15 +1189,36 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                             if ui.button(tr(Key::ChooseFont)).clicked() {
                                 if let Some(path) = pick_font_file(tr(Key::PanelFonts)) {
                                     app.runtime_notice = None;
-                                    match load_font_asset(&path) {
-                                        Ok(asset) => {
-                                            app.font_asset = Some(asset);
-                                            app.font_error = None;
-                                            app.text_style.font_path =
-                                                path.to_string_lossy().to_string();
-                                        }
-                                        Err(err) => {
-                                            app.font_error = Some(t1(locale, Key::ReadFailed, err));
+                                    let path_text = path.to_string_lossy().to_string();
+                                    let known = app
+                                        .text_style
+                                        .fonts
+                                        .iter()
+                                        .any(|choice| choice.path == path_text);
+                                    if !known {
+                                        match load_font_asset(&path) {
+                                            Ok(asset) => {
+                                                app.font_error = None;
+                                                // 第一款字体默认用于正文。
+                                                let has_body = app
+                                                    .text_style
+                                                    .fonts
+                                                    .iter()
+                                                    .any(|choice| choice.roles.contains(&FontRole::Body));
+                                                let roles = if has_body {
+                                                    Vec::new()
+                                                } else {
+                                                    vec![FontRole::Body]
+                                                };
+                                                app.font_assets.push((path_text.clone(), asset));
+                                                app.text_style.fonts.push(FontChoice {
+                                                    path: path_text,
+                                                    roles,
+                                                });
+                                            }
+                                            Err(err) => {
+                                                app.font_error = Some(t1(locale, Key::ReadFailed, err));
+                                            }
                                         }
                                     }
                                 } else if cfg!(target_arch = "wasm32") {
@@ -483,27 +1226,70 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                                 }
                             }
                             if ui.button(tr(Key::ClearFont)).clicked() {
-                                app.font_asset = None;
-                                app.text_style.font_path.clear();
+                                app.font_assets.clear();
+                                app.text_style.fonts.clear();
                             }
                         });
                         if let Some(err) = &app.font_error {
                             ui.label(egui::RichText::new(err).color(egui::Color32::RED));
-                        } else if !app.text_style.font_path.is_empty() {
-                            ui.label(t1(locale, Key::FontLabel, &app.text_style.font_path));
                         }
+                        let mut remove = None;
+                        let mut claim = None;
+                        for (index, choice) in app.text_style.fonts.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                let name = std::path::Path::new(&choice.path)
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy().to_string())
+                                    .unwrap_or_else(|| choice.path.clone());
+                                ui.label(t1(locale, Key::FontLabel, name))
+                                    .on_hover_text(&choice.path);
+                                for role in FontRole::ALL {
+                                    let mut assigned = choice.roles.contains(&role);
+                                    if ui.checkbox(&mut assigned, role.label(locale)).changed() {
+                                        if assigned {
+                                            claim = Some((index, role));
+                                        } else {
+                                            choice.roles.retain(|r| *r != role);
+                                        }
+                                    }
+                                }
+                                if ui.button(tr(Key::Delete)).clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                        // 每个角色只对应一款字体：勾选时从其他字体上移除该角色。
+                        if let Some((index, role)) = claim {
+                            for (other, choice) in app.text_style.fonts.iter_mut().enumerate() {
+                                choice.roles.retain(|r| *r != role);
+                                if other == index {
+                                    choice.roles.push(role);
+                                }
+                            }
+                        }
+                        if let Some(index) = remove {
+                            let choice = app.text_style.fonts.remove(index);
+                            app.font_assets.retain(|(path, _)| path != &choice.path);
+                        }
+                        if !app.text_style.fonts.is_empty() {
+                            ui.label(egui::RichText::new(tr(Key::FontRolesHint)).small());
+                        }
+                        ui.checkbox(&mut app.text_style.subset_font, tr(Key::SubsetFont));
+                        ui.label(egui::RichText::new(tr(Key::SubsetFontHint)).small());
+                        ui.checkbox(&mut app.text_style.obfuscate_fonts, tr(Key::ObfuscateFonts));
+                        ui.label(egui::RichText::new(tr(Key::ObfuscateFontsHint)).small());
+                        style_sample_ui(app, ui);
                     }
                     PanelIndex::Images => {
                         ui.horizontal(|ui| {
                             if ui.button(tr(Key::AddImage)).clicked() {
-                                if let Some(path) = pick_image_file(
-                                    tr(Key::PanelImages),
-                                    &["jpeg", "png", "webp", "jpg", "gif"],
-                                ) {
+                                let exts = &["jpeg", "png", "webp", "jpg", "gif", "svg"];
+                                if cfg!(target_arch = "wasm32") {
+                                    app.web_inbox.pick(ctx, WebPick::Images, tr(Key::PanelImages), exts);
+                                } else if let Some(path) = pick_image_file(tr(Key::PanelImages), exts)
+                                {
                                     app.runtime_notice = None;
                                     app.images.push(image_reader_from_path(locale, &path));
-                                } else if cfg!(target_arch = "wasm32") {
-                                    app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
                                 }
                             }
                             ui.label(t1(locale, Key::TotalImages, app.images.len()));
@@ -515,14 +1301,19 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                         ui.label(tr(Key::ChapterHeaderImage));
                         ui.horizontal(|ui| {
                             if ui.button(tr(Key::ChooseChapterHeader)).clicked() {
-                                if let Some(path) =
-                                    pick_image_file(tr(Key::PanelImages), &["jpeg", "png", "webp", "jpg"])
+                                let exts = &["jpeg", "png", "webp", "jpg"];
+                                if cfg!(target_arch = "wasm32") {
+                                    app.web_inbox.pick(
+                                        ctx,
+                                        WebPick::ChapterHeader,
+                                        tr(Key::PanelImages),
+                                        exts,
+                                    );
+                                } else if let Some(path) = pick_image_file(tr(Key::PanelImages), exts)
                                 {
                                     app.runtime_notice = None;
                                     app.chapter_header_image = image_reader_from_path(locale, &path);
                                     app.chapter_header_image_path = path.to_string_lossy().to_string();
-                                } else if cfg!(target_arch = "wasm32") {
-                                    app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
                                 }
                             }
                             if ui.button(tr(Key::ClearChapterHeader)).clicked() {
@@ -549,6 +1340,13 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                         );
 
                         ui.add_space(8.0);
+                        app.thumbnails.poll(ctx);
+                        let chapter_titles = if app.images.is_empty() {
+                            Vec::new()
+                        } else {
+                            app.conversion_chapter_titles()
+                        };
+                        let duplicates = duplicate_images(&app.images);
                         egui::ScrollArea::vertical()
                             .max_height(360.0)
                             .show(ui, |ui| {
@@ -557,17 +1355,30 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                                         ui.label(tr(Key::NoImages));
                                     });
                                 } else {
-                                    let mut indices_to_remove = Vec::new();
+                                    // 每帧只应用一项结构改动（合并、删除或拖动），
+                                    // 避免先合并再按旧下标删除时删错图片。
+                                    let mut remove: Option<usize> = None;
+                                    let mut drop_move: Option<(usize, usize)> = None;
+                                    let mut merge: Option<(usize, usize)> = None;
                                     for (index, image) in app.images.iter_mut().enumerate() {
-                                        ui.group(|ui| {
+                                        let row = ui.group(|ui| {
                                             ui.horizontal(|ui| {
+                                                ui.dnd_drag_source(
+                                                    egui::Id::new(("image_drag", index)),
+                                                    index,
+                                                    |ui| {
+                                                        ui.label(egui::RichText::new("≡").strong());
+                                                    },
+                                                )
+                                                .response
+                                                .on_hover_text(tr(Key::DragToReorder));
                                                 ui.label(t1(locale, Key::ImageIndex, index + 1));
                                                 ui.with_layout(
                                                     egui::Layout::right_to_left(egui::Align::Center),
                                                     |ui| {
                                                         if ui.small_button(tr(Key::Delete)).clicked()
                                                         {
-                                                            indices_to_remove.push(index);
+                                                            remove = Some(index);
                                                         }
                                                     },
                                                 );
@@ -576,30 +1387,66 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                                             if let Some(path) = &image.path {
                                                 ui.label(path.to_string_lossy().to_string());
                                             }
+                                            if let Some(first) = duplicates[index] {
+                                                ui.horizontal(|ui| {
+                                                    ui.label(
+                                                        egui::RichText::new(t1(
+                                                            locale,
+                                                            Key::DuplicateImage,
+                                                            first + 1,
+                                                        ))
+                                                        .color(egui::Color32::from_rgb(207, 95, 38)),
+                                                    );
+                                                    if ui
+                                                        .small_button(tr(Key::MergeDuplicate))
+                                                        .on_hover_text(tr(Key::MergeDuplicateHint))
+                                                        .clicked()
+                                                    {
+                                                        merge = Some((index, first));
+                                                    }
+                                                });
+                                            }
 
                                             ui.label(tr(Key::Caption));
                                             let caption =
                                                 image.caption.get_or_insert_with(String::new);
                                             ui.text_edit_singleline(caption);
+                                            image_chapter_ui(
+                                                ui,
+                                                locale,
+                                                index,
+                                                &mut image.chapter,
+                                                &chapter_titles,
+                                            );
 
-                                            image.update_texture(ui.ctx());
-                                            if let Some(texture) = &image.texture {
-                                                ui.add(
-                                                    egui::Image::from_texture(texture)
-                                                        .max_width(150.0),
-                                                );
-                                            } else if let Some(err) = &image.error {
+                                            if let Some(err) = &image.error {
                                                 ui.label(
                                                     egui::RichText::new(err)
                                                         .color(egui::Color32::RED),
                                                 );
                                             } else {
-                                                ui.label(tr(Key::Loading));
+                                                thumbnail_ui(ui, &mut app.thumbnails, image, locale);
                                             }
-                                        });
+                                        })
+                                        .response;
+                                        if let Some(target) = drop_target(ui, &row, index) {
+                                            drop_move = Some(target);
+                                        }
                                     }
-                                    for index in indices_to_remove.into_iter().rev() {
+                                    if let Some((index, first)) = merge {
+                                        merge_duplicate_image(&mut app.images, index, first);
+                                    } else if let Some(index) = remove {
                                         app.images.remove(index);
+                                    } else if let Some((from, insert_before)) = drop_move
+                                        && from < app.images.len()
+                                    {
+                                        let image = app.images.remove(from);
+                                        let to = if insert_before > from {
+                                            insert_before - 1
+                                        } else {
+                                            insert_before
+                                        };
+                                        app.images.insert(to.min(app.images.len()), image);
                                     }
                                 }
                             });
@@ -607,37 +1454,192 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                         ui.add_space(8.0);
                         ui.horizontal(|ui| {
                             if ui.button(tr(Key::BatchImport)).clicked() {
-                                if let Some(paths) = pick_image_files(
-                                    tr(Key::PanelImages),
-                                    &["jpeg", "png", "webp", "jpg", "gif"],
-                                ) {
+                                let exts = &["jpeg", "png", "webp", "jpg", "gif", "svg"];
+                                if cfg!(target_arch = "wasm32") {
+                                    app.web_inbox.pick(ctx, WebPick::Images, tr(Key::PanelImages), exts);
+                                } else if let Some(paths) = pick_image_files(tr(Key::PanelImages), exts)
+                                {
                                     app.runtime_notice = None;
                                     for path in paths {
                                         app.images.push(image_reader_from_path(locale, &path));
                                     }
-                                } else if cfg!(target_arch = "wasm32") {
-                                    app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
                                 }
                             }
                             if ui.button(tr(Key::ClearAll)).clicked() {
                                 app.images.clear();
                             }
                         });
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.checkbox(&mut app.image_optimization.enabled, tr(Key::OptimizeImages));
+                        ui.add_enabled_ui(app.image_optimization.enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::ImageMaxDimension));
+                                ui.add(
+                                    egui::Slider::new(
+                                        &mut app.image_optimization.max_dimension,
+                                        480..=4096,
+                                    )
+                                    .step_by(80.0),
+                                );
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::JpegQuality));
+                                ui.add(egui::Slider::new(
+                                    &mut app.image_optimization.jpeg_quality,
+                                    40..=100,
+                                ));
+                            });
+                        });
+                        ui.label(egui::RichText::new(tr(Key::OptimizeImagesHint)).small());
+                        ui.add_space(6.0);
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::ImageCompatLabel));
+                            egui::ComboBox::from_id_salt("image_compatibility")
+                                .selected_text(app.image_optimization.compatibility.label(locale))
+                                .show_ui(ui, |ui| {
+                                    for profile in ImageCompatibility::ALL {
+                                        ui.selectable_value(
+                                            &mut app.image_optimization.compatibility,
+                                            profile,
+                                            profile.label(locale),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.label(egui::RichText::new(tr(Key::ImageCompatHint)).small());
+                        ui.add_space(6.0);
+                        ui.checkbox(
+                            &mut app.image_optimization.grayscale,
+                            tr(Key::GrayscaleImages),
+                        );
+                        ui.label(egui::RichText::new(tr(Key::GrayscaleImagesHint)).small());
+                        ui.add_space(6.0);
+                        ui.checkbox(
+                            &mut app.image_optimization.rasterize_svg,
+                            tr(Key::RasterizeSvg),
+                        );
+                        ui.label(egui::RichText::new(tr(Key::RasterizeSvgHint)).small());
                     }
                     PanelIndex::PublishInfo => {
+                        if ui.button(tr(Key::LookupMetadata)).clicked() {
+                            app.metadata_lookup.open_for(&app.book_info);
+                        }
+                        if let Some(header) = TextHeader::detect(&app.input_file.content) {
+                            ui.add_space(6.0);
+                            text_header_ui(ui, app, locale, &header);
+                        }
+                        ui.add_space(6.0);
+                        ui.label(tr(Key::Creators));
+                        creators_ui(ui, locale, &mut app.book_info.creators);
+                        ui.add_space(6.0);
                         ui.label(tr(Key::LanguageField));
-                        ui.text_edit_singleline(&mut app.book_info.language);
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(&mut app.book_info.language);
+                            let has_text = !app.input_file.content.trim().is_empty();
+                            if ui
+                                .add_enabled(has_text, egui::Button::new(tr(Key::DetectLanguage)))
+                                .clicked()
+                                && let Some(language) = detect_language(&app.input_file.content)
+                            {
+                                app.book_info.language = language.to_string();
+                            }
+                        });
+                        let language = app.book_info.language.trim();
+                        match normalize_language_tag(language) {
+                            _ if language.is_empty() => {
+                                ui.label(egui::RichText::new(tr(Key::LanguageHint)).small());
+                            }
+                            Some(tag) if tag != language => {
+                                ui.label(
+                                    egui::RichText::new(t1(locale, Key::LanguageNormalized, tag))
+                                        .small(),
+                                );
+                            }
+                            Some(_) => {}
+                            None => {
+                                ui.label(
+                                    egui::RichText::new(tr(Key::LanguageInvalid))
+                                        .small()
+                                        .color(egui::Color32::RED),
+                                );
+                            }
+                        }
                         ui.label(tr(Key::Publisher));
                         ui.text_edit_singleline(&mut app.book_info.publisher);
                         ui.label(tr(Key::Isbn));
                         ui.text_edit_singleline(&mut app.book_info.isbn);
+                        ui.label(tr(Key::Identifier));
+                        ui.text_edit_singleline(&mut app.book_info.identifier);
+                        if app.book_info.identifier.trim().is_empty() {
+                            let derived =
+                                uuid::fmt::Urn::from_uuid(app.book_info.derived_uuid());
+                            ui.label(
+                                egui::RichText::new(t1(locale, Key::IdentifierHint, derived))
+                                    .small(),
+                            );
+                        } else if app.book_info.uuid().is_err() {
+                            ui.label(
+                                egui::RichText::new(tr(Key::IdentifierInvalid))
+                                    .small()
+                                    .color(egui::Color32::RED),
+                            );
+                        }
                         ui.label(tr(Key::Category));
                         ui.text_edit_singleline(&mut app.book_info.category);
                         ui.label(tr(Key::PublishDate));
                         ui.text_edit_singleline(&mut app.book_info.publish_date);
+                        ui.label(tr(Key::Series));
+                        ui.text_edit_singleline(&mut app.book_info.series);
+                        ui.label(tr(Key::SeriesIndex));
+                        ui.text_edit_singleline(&mut app.book_info.series_index);
                         ui.add_space(6.0);
-                        ui.label(tr(Key::Description));
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::Description));
+                            let has_text = !app.input_file.content.trim().is_empty();
+                            if ui
+                                .add_enabled(
+                                    has_text,
+                                    egui::Button::new(tr(Key::DescriptionFromText)),
+                                )
+                                .clicked()
+                                && let Some(description) = description_from_text(
+                                    &app.input_file.content,
+                                    app.description_length,
+                                )
+                            {
+                                app.book_info.description = description;
+                            }
+                            ui.add(
+                                egui::DragValue::new(&mut app.description_length)
+                                    .range(50..=2000)
+                                    .speed(10),
+                            );
+                            ui.label(tr(Key::DescriptionLength));
+                        });
                         ui.text_edit_multiline(&mut app.book_info.description);
+                        ui.add_space(6.0);
+                        egui::CollapsingHeader::new(tr(Key::AdvancedMetadata))
+                            .id_salt("advanced_metadata")
+                            .show(ui, |ui| {
+                                custom_metadata_ui(ui, locale, &mut app.book_info.custom_metadata);
+                            });
+                        egui::CollapsingHeader::new(tr(Key::Accessibility))
+                            .id_salt("accessibility")
+                            .show(ui, |ui| {
+                                accessibility_ui(ui, locale, &mut app.book_info.accessibility);
+                            });
+                        egui::CollapsingHeader::new(tr(Key::FrontMatter))
+                            .id_salt("front_matter")
+                            .show(ui, |ui| {
+                                front_matter_ui(ui, locale, &mut app.front_matter);
+                            });
+                        egui::CollapsingHeader::new(tr(Key::BackMatter))
+                            .id_salt("back_matter")
+                            .show(ui, |ui| {
+                                back_matter_ui(ui, locale, &mut app.back_matter);
+                            });
                     }
                     PanelIndex::Misc => {
                         ui.label(tr(Key::OutputFolder));
@@ -655,10 +1657,174 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
 
                         ui.add_space(8.0);
                         ui.label(tr(Key::FilenameTemplate));
+                        ui.text_edit_singleline(&mut app.filename_template);
+                        ui.label(egui::RichText::new(tr(Key::VarsHint)).small().weak());
+                        ui.label(t1(
+                            locale,
+                            Key::FilenamePreview,
+                            app.output_filename_preview(),
+                        ));
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::OverwriteLabel));
+                            for policy in OverwritePolicy::ALL {
+                                ui.selectable_value(
+                                    &mut app.overwrite_policy,
+                                    policy,
+                                    policy.label(locale),
+                                );
+                            }
+                        });
+                        ui.checkbox(
+                            &mut app.include_generator_meta,
+                            tr(Key::IncludeGeneratorMeta),
+                        );
+                        ui.checkbox(&mut app.safe_mode, tr(Key::SafeMode));
                         ui.horizontal(|ui| {
-                            ui.text_edit_singleline(&mut app.filename_template);
-                            ui.label(tr(Key::VarsHint));
+                            ui.label(tr(Key::OutputFormatLabel));
+                            for format in OutputFormat::ALL {
+                                ui.selectable_value(&mut app.output_format, format, format.label());
+                            }
                         });
+                        if app.output_format == OutputFormat::Pdf {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::PdfPageSize));
+                                egui::ComboBox::from_id_salt("pdf_page_size")
+                                    .selected_text(app.pdf_options.page_size.label())
+                                    .show_ui(ui, |ui| {
+                                        for size in PdfPageSize::ALL {
+                                            ui.selectable_value(
+                                                &mut app.pdf_options.page_size,
+                                                size,
+                                                size.label(),
+                                            );
+                                        }
+                                    });
+                                ui.label(tr(Key::PdfMargin));
+                                ui.add(
+                                    egui::DragValue::new(&mut app.pdf_options.margin_mm)
+                                        .range(0.0..=40.0)
+                                        .suffix(" mm"),
+                                );
+                            });
+                            ui.label(egui::RichText::new(tr(Key::PdfHint)).small());
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::EpubVersionLabel));
+                            egui::ComboBox::from_id_salt("epub_version")
+                                .selected_text(app.epub_version.label())
+                                .show_ui(ui, |ui| {
+                                    for version in EpubVersion::ALL {
+                                        ui.selectable_value(
+                                            &mut app.epub_version,
+                                            version,
+                                            version.label(),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.label(egui::RichText::new(tr(Key::EpubVersionHint)).small());
+                        ui.checkbox(&mut app.rtl_page_progression, tr(Key::RtlPageProgression));
+                        ui.checkbox(&mut app.kepub, tr(Key::KepubOutput));
+                        ui.checkbox(&mut app.reproducible, tr(Key::ReproducibleBuild))
+                            .on_hover_text(tr(Key::ReproducibleBuildHint));
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::CompressionLevel));
+                            ui.add(egui::DragValue::new(&mut app.compression.level).range(0..=9));
+                        });
+                        ui.checkbox(
+                            &mut app.compression.store_images,
+                            tr(Key::StoreImagesUncompressed),
+                        );
+                        ui.checkbox(&mut app.split_large_chapters, tr(Key::SplitLargeChapters))
+                            .on_hover_text(tr(Key::SplitLargeChaptersHint));
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::ZhConvertLabel));
+                            egui::ComboBox::from_id_salt("chinese_conversion")
+                                .selected_text(app.chinese_conversion.label(locale))
+                                .show_ui(ui, |ui| {
+                                    for conversion in ChineseConversion::ALL {
+                                        ui.selectable_value(
+                                            &mut app.chinese_conversion,
+                                            conversion,
+                                            conversion.label(locale),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.label(egui::RichText::new(tr(Key::ZhConvertHint)).small());
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::TitleNumeralsLabel));
+                            egui::ComboBox::from_id_salt("title_numerals")
+                                .selected_text(app.title_numerals.label(locale))
+                                .show_ui(ui, |ui| {
+                                    for numerals in TitleNumerals::ALL {
+                                        ui.selectable_value(
+                                            &mut app.title_numerals,
+                                            numerals,
+                                            numerals.label(locale),
+                                        );
+                                    }
+                                });
+                        })
+                        .response
+                        .on_hover_text(tr(Key::TitleNumeralsHint));
+
+                        ui.add_space(12.0);
+                        ui.separator();
+                        ui.add_space(8.0);
+                        ui.checkbox(&mut app.run_epubcheck, tr(Key::RunEpubcheck));
+                        ui.add_enabled_ui(app.run_epubcheck, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::EpubcheckPath));
+                                ui.text_edit_singleline(&mut app.epubcheck_path);
+                                if ui.button(tr(Key::Browse)).clicked() {
+                                    if let Some(path) = pick_epubcheck_file() {
+                                        app.runtime_notice = None;
+                                        app.epubcheck_path = path.to_string_lossy().to_string();
+                                    } else if cfg!(target_arch = "wasm32") {
+                                        app.runtime_notice =
+                                            Some(tr(Key::DesktopOnlyAction).to_string());
+                                    }
+                                }
+                            });
+                        });
+                        ui.label(egui::RichText::new(tr(Key::EpubcheckHint)).small());
+
+                        ui.add_space(12.0);
+                        ui.separator();
+                        ui.add_space(8.0);
+                        ui.label(tr(Key::PostExportActions));
+                        ui.checkbox(&mut app.open_after_export, tr(Key::OpenAfterExport));
+                        ui.checkbox(
+                            &mut app.add_to_calibre_after_export,
+                            tr(Key::AddToCalibreAfterExport),
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::CalibredbPath));
+                            ui.text_edit_singleline(&mut app.calibredb_path);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(tr(Key::CalibreLibrary));
+                            ui.text_edit_singleline(&mut app.calibre_library);
+                        });
+                        ui.label(egui::RichText::new(tr(Key::CalibreHint)).small());
+                        ui.horizontal(|ui| {
+                            ui.checkbox(
+                                &mut app.kindle.send_after_export,
+                                tr(Key::SendToKindleAfterExport),
+                            );
+                            if ui.button(tr(Key::KindleSettingsButton)).clicked() {
+                                app.show_kindle_settings = true;
+                            }
+                        });
+                        ui.label(tr(Key::DeviceFolders));
+                        for kind in DeviceKind::ALL {
+                            ui.horizontal(|ui| {
+                                ui.label(kind.label());
+                                ui.text_edit_singleline(app.device_paths.template_mut(kind));
+                            });
+                        }
+                        ui.label(egui::RichText::new(tr(Key::DeviceFoldersHint)).small());
 
                         ui.add_space(12.0);
                         ui.separator();
@@ -672,12 +1838,140 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
                             &mut app.toc_options.include_gallery_in_toc,
                             tr(Key::IncludeGalleryInToc),
                         );
+                        ui.checkbox(&mut app.toc_options.nest_volumes, tr(Key::NestVolumes));
+                        ui.add_enabled_ui(app.toc_options.insert_toc_page, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(Key::TocColumns));
+                                for columns in 1..=3u8 {
+                                    ui.selectable_value(
+                                        &mut app.toc_options.columns,
+                                        columns,
+                                        columns.to_string(),
+                                    );
+                                }
+                            });
+                            ui.checkbox(
+                                &mut app.toc_options.dotted_leaders,
+                                tr(Key::TocDottedLeaders),
+                            );
+                            ui.checkbox(
+                                &mut app.toc_options.volume_headings,
+                                tr(Key::TocVolumeHeadings),
+                            );
+                            ui.checkbox(
+                                &mut app.toc_options.show_chapter_numbers,
+                                tr(Key::TocShowChapterNumbers),
+                            );
+                        });
+
+                        ui.add_space(12.0);
+                        ui.separator();
+                        ui.add_space(8.0);
+                        ui.label(tr(Key::SpineOrder));
+                        spine_order_ui(ui, locale, &mut app.spine_order);
 
                         ui.add_space(8.0);
                         ui.label(tr(Key::Current));
                         ui.label(t1(locale, Key::OutputLabel, &app.output_path));
                         ui.label(t1(locale, Key::TemplateLabel, &app.filename_template));
                     }
+                    PanelIndex::Batch => {
+                        ui.label(tr(Key::BatchHint));
+                        ui.add_space(6.0);
+                        let running = app.batch_job.is_some() || app.conversion_job.is_some();
+                        ui.add_enabled_ui(!running, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.button(tr(Key::BatchAddFiles)).clicked() {
+                                    if let Some(paths) = pick_text_files(tr(Key::TextFileFilter)) {
+                                        app.runtime_notice = None;
+                                        app.batch_queue.extend(paths.into_iter().map(BatchItem::new));
+                                    } else if cfg!(target_arch = "wasm32") {
+                                        app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
+                                    }
+                                }
+                                if ui.button(tr(Key::BatchAddFolder)).clicked() {
+                                    if let Some(dir) = pick_folder() {
+                                        match collect_text_files(&dir) {
+                                            Ok(paths) => {
+                                                app.runtime_notice = None;
+                                                app.batch_queue
+                                                    .extend(paths.into_iter().map(BatchItem::new));
+                                            }
+                                            Err(err) => {
+                                                app.runtime_notice =
+                                                    Some(t1(locale, Key::ReadFailed, err));
+                                            }
+                                        }
+                                    } else if cfg!(target_arch = "wasm32") {
+                                        app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
+                                    }
+                                }
+                                if ui.button(tr(Key::BatchClear)).clicked() {
+                                    app.batch_queue.clear();
+                                }
+                            });
+                            ui.add_space(6.0);
+                            if primary_button(ui, tr(Key::BatchStart)).clicked() {
+                                app.start_batch();
+                            }
+                        });
+                        if app.batch_job.is_some() && ui.button(tr(Key::Cancel)).clicked() {
+                            app.cancel_batch();
+                        }
+
+                        ui.add_space(8.0);
+                        if app.batch_queue.is_empty() {
+                            ui.label(tr(Key::BatchEmpty));
+                        } else {
+                            let finished = app
+                                .batch_queue
+                                .iter()
+                                .filter(|item| item.status != BatchItemStatus::Pending)
+                                .count();
+                            ui.label(t2(locale, Key::BatchProgress, finished, app.batch_queue.len()));
+                            let current = app
+                                .conversion_job
+                                .as_ref()
+                                .and_then(|job| job.batch_item.map(|index| (index, job.progress)));
+                            egui::Grid::new("batch_queue")
+                                .num_columns(2)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (index, item) in app.batch_queue.iter().enumerate() {
+                                        let name = item
+                                            .path
+                                            .file_name()
+                                            .map(|name| name.to_string_lossy().to_string())
+                                            .unwrap_or_default();
+                                        ui.label(name);
+                                        match &item.status {
+                                            BatchItemStatus::Pending => match current {
+                                                Some((running, progress)) if running == index => {
+                                                    ui.add(
+                                                        egui::ProgressBar::new(progress.fraction())
+                                                            .desired_width(200.0)
+                                                            .text(progress_stage(locale, progress)),
+                                                    );
+                                                }
+                                                _ => {
+                                                    ui.label(tr(Key::BatchPending));
+                                                }
+                                            },
+                                            BatchItemStatus::Done(path) => {
+                                                ui.label(t1(locale, Key::BatchDone, path));
+                                            }
+                                            BatchItemStatus::Failed(err) => {
+                                                ui.label(
+                                                    egui::RichText::new(t1(locale, Key::BatchFailed, err))
+                                                        .color(egui::Color32::RED),
+                                                );
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        }
+                    }
                 });
 
                 ui.add_space(12.0);
@@ -1,10 +1,161 @@
 use std::path::{Path, PathBuf};
 
 use crate::components::chapter_editor::ChapterEditorInput;
-use crate::{Key, t, t1};
+use crate::components::find_replace::{FindReplaceState, find_matches, replace_all, replace_one};
+use crate::conversion::{
+    ConversionProgress, ConversionReport, OutlierKind, Preprocessing, SplitOptions,
+};
+use crate::devices::mounted_devices;
+use crate::epubcheck::{EpubcheckReport, EpubcheckSeverity};
+use crate::kindle::SmtpSecurity;
+use crate::merge::{MergeInput, TextEncoding};
+use crate::metadata_lookup::{MetadataCandidate, MetadataSource};
+use crate::{Key, Locale, OverwritePolicy, t, t1, t2};
 
-use super::super::MainApp;
-use super::super::app_helpers::open_in_file_manager;
+use super::super::app_helpers::{format_size, open_in_file_manager};
+use super::super::shortcuts::{ShortcutAction, Shortcuts, pressed_shortcut};
+use super::super::{MainApp, MergeItem};
+use super::central::pick_text_files;
+
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+const REDO_SHORTCUT_ALT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
+
+const FIND_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FindAction {
+    Replace,
+    ReplaceAll,
+}
+
+/// 查找栏：查询、模式开关、匹配计数与上下跳转，以及替换输入；返回用户触发的替换操作。
+fn find_bar_ui(
+    ui: &mut egui::Ui,
+    locale: Locale,
+    query_id: egui::Id,
+    find: &mut FindReplaceState,
+    pattern: &Result<Option<regex::Regex>, regex::Error>,
+    count: usize,
+) -> Option<FindAction> {
+    let tr = |key| t(locale, key);
+    let mut action = None;
+    ui.horizontal(|ui| {
+        let query = ui.add(
+            egui::TextEdit::singleline(&mut find.query)
+                .id(query_id)
+                .hint_text(tr(Key::FindHint))
+                .desired_width(220.0),
+        );
+        let mut changed = query.changed();
+        changed |= ui
+            .checkbox(&mut find.use_regex, tr(Key::FindRegex))
+            .changed();
+        changed |= ui
+            .checkbox(&mut find.match_case, tr(Key::FindMatchCase))
+            .changed();
+        if changed {
+            find.current = 0;
+            find.replaced = None;
+            find.reveal = true;
+        }
+        if query.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            find.step(count, !ui.input(|input| input.modifiers.shift));
+            query.request_focus();
+        }
+        if ui.add_enabled(count > 0, egui::Button::new("▲")).clicked() {
+            find.step(count, false);
+        }
+        if ui.add_enabled(count > 0, egui::Button::new("▼")).clicked() {
+            find.step(count, true);
+        }
+        match pattern {
+            Err(err) => {
+                ui.label(
+                    egui::RichText::new(t1(locale, Key::RegexError, err)).color(egui::Color32::RED),
+                );
+            }
+            Ok(Some(_)) => {
+                let position = if count == 0 { 0 } else { find.current + 1 };
+                ui.label(t2(locale, Key::FindMatches, position, count));
+            }
+            Ok(None) => {}
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut find.replacement)
+                .hint_text(tr(Key::ReplaceHint))
+                .desired_width(220.0),
+        );
+        if ui
+            .add_enabled(count > 0, egui::Button::new(tr(Key::ReplaceOne)))
+            .clicked()
+        {
+            action = Some(FindAction::Replace);
+        }
+        if ui
+            .add_enabled(count > 0, egui::Button::new(tr(Key::ReplaceAll)))
+            .clicked()
+        {
+            action = Some(FindAction::ReplaceAll);
+        }
+        if let Some(replaced) = find.replaced {
+            ui.label(t1(locale, Key::ReplacedCount, replaced));
+        }
+    });
+    action
+}
+
+/// 为编辑器文本生成带匹配高亮的排版任务，当前匹配使用更醒目的底色。
+fn highlight_matches(
+    ui: &egui::Ui,
+    text: &str,
+    matches: &[std::ops::Range<usize>],
+    current: usize,
+) -> egui::text::LayoutJob {
+    let font_id = egui::FontSelection::default().resolve(ui.style());
+    let color = ui
+        .visuals()
+        .override_text_color
+        .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+    let plain = egui::TextFormat::simple(font_id, color);
+    let highlight = ui.visuals().warn_fg_color;
+    let mut job = egui::text::LayoutJob {
+        text: text.to_owned(),
+        ..Default::default()
+    };
+    let mut push = |range: std::ops::Range<usize>, format: egui::TextFormat| {
+        if !range.is_empty() {
+            job.sections.push(egui::text::LayoutSection {
+                leading_space: 0.0,
+                byte_range: range,
+                format,
+            });
+        }
+    };
+    let mut last = 0;
+    for (index, range) in matches.iter().enumerate() {
+        push(last..range.start, plain.clone());
+        let alpha = if index == current { 0.7 } else { 0.3 };
+        push(
+            range.clone(),
+            egui::TextFormat {
+                background: highlight.gamma_multiply(alpha),
+                ..plain.clone()
+            },
+        );
+        last = range.end;
+    }
+    push(last..text.len(), plain);
+    job
+}
 
 pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
     let locale = app.locale;
@@ -27,16 +178,159 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
                     if ui.button(tr(Key::Close)).clicked() {
                         app.show_editor = false;
                     }
+                    ui.separator();
+                    if ui
+                        .add_enabled(
+                            app.editor_history.can_undo(),
+                            egui::Button::new(tr(Key::Undo)),
+                        )
+                        .on_hover_text("Ctrl+Z")
+                        .clicked()
+                    {
+                        app.editor_history.undo(&mut app.input_file.content);
+                    }
+                    if ui
+                        .add_enabled(
+                            app.editor_history.can_redo(),
+                            egui::Button::new(tr(Key::Redo)),
+                        )
+                        .on_hover_text("Ctrl+Y")
+                        .clicked()
+                    {
+                        app.editor_history.redo(&mut app.input_file.content);
+                    }
+                    ui.separator();
+                    if ui
+                        .selectable_label(app.editor_find.open, tr(Key::FindReplace))
+                        .on_hover_text("Ctrl+F")
+                        .clicked()
+                    {
+                        app.editor_find.open = !app.editor_find.open;
+                    }
                 });
 
+                // 编辑框获得焦点时由历史记录接管撤销/重做快捷键，避免与 TextEdit 自带的撤销冲突。
+                let editor_id = egui::Id::new("txt_editor");
+                let find_id = egui::Id::new("txt_editor_find");
+                if ctx.memory(|memory| memory.has_focus(editor_id)) {
+                    let (undo, redo, find) = ui.input_mut(|input| {
+                        // 先匹配带 Shift 的组合，否则 Ctrl+Shift+Z 会被当作 Ctrl+Z。
+                        let redo = input.consume_shortcut(&REDO_SHORTCUT_ALT)
+                            || input.consume_shortcut(&REDO_SHORTCUT);
+                        let undo = input.consume_shortcut(&UNDO_SHORTCUT);
+                        let find = input.consume_shortcut(&FIND_SHORTCUT);
+                        (undo, redo, find)
+                    });
+                    if redo {
+                        app.editor_history.redo(&mut app.input_file.content);
+                    } else if undo {
+                        app.editor_history.undo(&mut app.input_file.content);
+                    }
+                    if find {
+                        app.editor_find.open = true;
+                        ctx.memory_mut(|memory| memory.request_focus(find_id));
+                    }
+                }
+
+                let mut matches = Vec::new();
+                if app.editor_find.open {
+                    ui.add_space(6.0);
+                    let pattern = app.editor_find.pattern();
+                    if let Ok(Some(pattern)) = &pattern {
+                        matches = find_matches(pattern, &app.input_file.content);
+                    }
+                    let action = find_bar_ui(
+                        ui,
+                        locale,
+                        find_id,
+                        &mut app.editor_find,
+                        &pattern,
+                        matches.len(),
+                    );
+                    if let (Some(action), Ok(Some(pattern))) = (action, &pattern) {
+                        let find = &mut app.editor_find;
+                        let current = matches.get(find.current).map(|range| range.start);
+                        app.editor_history.apply(
+                            &mut app.input_file.content,
+                            |text| match action {
+                                FindAction::Replace => {
+                                    if let Some(start) = current {
+                                        replace_one(
+                                            pattern,
+                                            text,
+                                            start,
+                                            &find.replacement,
+                                            find.use_regex,
+                                        );
+                                    }
+                                }
+                                FindAction::ReplaceAll => {
+                                    find.replaced = Some(replace_all(
+                                        pattern,
+                                        text,
+                                        &find.replacement,
+                                        find.use_regex,
+                                    ));
+                                }
+                            },
+                        );
+                        find.reveal = action == FindAction::Replace;
+                        matches = find_matches(pattern, &app.input_file.content);
+                    }
+                    if app.editor_find.current >= matches.len() {
+                        app.editor_find.current = 0;
+                    }
+                }
+
                 ui.add_space(10.0);
                 ui.separator();
                 ui.add_space(10.0);
 
+                let current = app.editor_find.current;
+                let mut highlighter =
+                    |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+                        let mut job = highlight_matches(ui, text.as_str(), &matches, current);
+                        job.wrap.max_width = wrap_width;
+                        ui.fonts_mut(|fonts| fonts.layout_job(job))
+                    };
                 egui::ScrollArea::vertical()
                     .max_height(500.0)
                     .show(ui, |ui| {
-                        ui.text_edit_multiline(&mut app.input_file.content);
+                        let mut editor =
+                            egui::TextEdit::multiline(&mut app.input_file.content).id(editor_id);
+                        if !matches.is_empty() {
+                            editor = editor.layouter(&mut highlighter);
+                        }
+                        let mut output = editor.show(ui);
+                        let now = ui.input(|input| input.time);
+                        if output.response.changed() {
+                            app.editor_history.edited(now);
+                        }
+                        app.editor_history.tick(&app.input_file.content, now);
+
+                        // 选中当前匹配并滚动到可见位置。
+                        if app.editor_find.reveal
+                            && let Some(range) = matches.get(current)
+                        {
+                            app.editor_find.reveal = false;
+                            let text = &app.input_file.content;
+                            let start = text[..range.start.min(text.len())].chars().count();
+                            let end = start
+                                + text
+                                    .get(range.clone())
+                                    .map_or(0, |found| found.chars().count());
+                            let selection = egui::text::CCursorRange::two(
+                                egui::text::CCursor::new(start),
+                                egui::text::CCursor::new(end),
+                            );
+                            output.state.cursor.set_char_range(Some(selection));
+                            output.state.store(ui.ctx(), editor_id);
+                            let rect = output
+                                .galley
+                                .pos_from_cursor(egui::text::CCursor::new(start))
+                                .translate(output.galley_pos.to_vec2());
+                            ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                        }
                     });
 
                 ui.add_space(10.0);
@@ -48,6 +342,10 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
             });
     }
 
+    if app.metadata_lookup.open {
+        metadata_lookup_window(app, ctx);
+    }
+
     if app.show_conversion_modal {
         egui::Window::new(tr(Key::ConversionResult))
             .collapsible(false)
@@ -58,7 +356,50 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
                 ui.vertical_centered(|ui| {
                     ui.add_space(20.0);
 
-                    if let Some(error) = &app.conversion_error {
+                    if let Some(job) = &app.conversion_job
+                        && job.batch_item.is_none()
+                    {
+                        ui.label(egui::RichText::new(tr(Key::ConversionRunning)).size(24.0));
+                        ui.add_space(20.0);
+                        ui.add(
+                            egui::ProgressBar::new(job.progress.fraction())
+                                .show_percentage()
+                                .animate(true),
+                        );
+                        ui.add_space(5.0);
+                        ui.label(progress_stage(locale, job.progress));
+                        ui.add_space(20.0);
+                        if ui.button(tr(Key::Cancel)).clicked() {
+                            job.cancel.cancel();
+                        }
+                        return;
+                    }
+
+                    if let Some(path) = app.overwrite_conflict.clone() {
+                        ui.label(
+                            egui::RichText::new(tr(Key::OutputExists))
+                                .size(24.0)
+                                .color(egui::Color32::from_rgb(207, 95, 38)),
+                        );
+                        ui.add_space(20.0);
+                        ui.label(t1(locale, Key::OutputExistsPrompt, path));
+                        ui.add_space(20.0);
+                        let mut retry = None;
+                        ui.horizontal(|ui| {
+                            if ui.button(tr(Key::OverwriteConfirm)).clicked() {
+                                retry = Some(OverwritePolicy::Overwrite);
+                            }
+                            if ui.button(tr(Key::OverwriteRenumber)).clicked() {
+                                retry = Some(OverwritePolicy::Number);
+                            }
+                        });
+                        if let Some(policy) = retry {
+                            app.overwrite_conflict = None;
+                            app.overwrite_once = Some(policy);
+                            app.run_conversion();
+                            return;
+                        }
+                    } else if let Some(error) = &app.conversion_error {
                         ui.label(
                             egui::RichText::new(tr(Key::ConversionFailed))
                                 .size(24.0)
@@ -66,7 +407,8 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
                         );
                         ui.add_space(20.0);
                         ui.label(egui::RichText::new(error).size(16.0));
-                    } else if let Some(output_path) = &app.conversion_result {
+                    } else if let Some(output_path) = app.conversion_result.clone() {
+                        let output_path = output_path.as_str();
                         ui.label(
                             egui::RichText::new(tr(Key::ConversionSuccess))
                                 .size(24.0)
@@ -77,6 +419,74 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
                         ui.label(tr(Key::OutputFile));
                         ui.add_space(5.0);
                         ui.label(egui::RichText::new(output_path).size(14.0).monospace());
+                        if let Some(report) = &app.conversion_report {
+                            conversion_report_ui(ui, locale, report);
+                        }
+                        if !app.conversion_image_sizes.is_empty() {
+                            let before: usize = app
+                                .conversion_image_sizes
+                                .iter()
+                                .map(|image| image.before)
+                                .sum();
+                            let after: usize = app
+                                .conversion_image_sizes
+                                .iter()
+                                .map(|image| image.after)
+                                .sum();
+                            ui.add_space(10.0);
+                            ui.label(t2(
+                                locale,
+                                Key::ImageOptimizationSummary,
+                                format_size(before),
+                                format_size(after),
+                            ));
+                            for image in &app.conversion_image_sizes {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{}: {} → {}",
+                                        image.name,
+                                        format_size(image.before),
+                                        format_size(image.after)
+                                    ))
+                                    .size(12.0),
+                                );
+                            }
+                        }
+                        if !app.conversion_warnings.is_empty() {
+                            ui.add_space(10.0);
+                            ui.label(tr(Key::ConversionWarnings));
+                            for warning in &app.conversion_warnings {
+                                ui.label(
+                                    egui::RichText::new(warning)
+                                        .size(12.0)
+                                        .color(egui::Color32::from_rgb(207, 95, 38)),
+                                );
+                            }
+                        }
+                        ui.add_space(10.0);
+                        if app.conversion_validation.is_empty() {
+                            ui.label(
+                                egui::RichText::new(tr(Key::ValidationPassed))
+                                    .size(12.0)
+                                    .color(egui::Color32::from_rgb(46, 125, 50)),
+                            );
+                        } else {
+                            ui.label(t1(
+                                locale,
+                                Key::ValidationIssues,
+                                app.conversion_validation.len(),
+                            ));
+                            for issue in &app.conversion_validation {
+                                ui.label(
+                                    egui::RichText::new(issue.to_string())
+                                        .size(12.0)
+                                        .color(egui::Color32::from_rgb(207, 95, 38)),
+                                );
+                            }
+                        }
+                        if let Some(report) = &app.conversion_epubcheck {
+                            epubcheck_report_ui(ui, locale, report);
+                        }
                         ui.add_space(20.0);
 
                         ui.horizontal(|ui| {
@@ -96,7 +506,49 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
                                     let _ = open_in_file_manager(Path::new(output_path));
                                 }
                             }
+                            let adding = app.calibre_job.is_some();
+                            if ui
+                                .add_enabled(!adding, egui::Button::new(tr(Key::SendToCalibre)))
+                                .clicked()
+                            {
+                                app.send_to_calibre(PathBuf::from(output_path));
+                            }
+                            let sending = app.kindle_job.is_some();
+                            if ui
+                                .add_enabled(!sending, egui::Button::new(tr(Key::SendToKindle)))
+                                .clicked()
+                            {
+                                app.send_to_kindle(PathBuf::from(output_path));
+                            }
                         });
+                        if !cfg!(target_arch = "wasm32") {
+                            let mut copy_to = None;
+                            ui.horizontal_wrapped(|ui| {
+                                if app.devices.is_empty() {
+                                    ui.label(egui::RichText::new(tr(Key::NoDevices)).weak());
+                                }
+                                for (index, device) in app.devices.iter().enumerate() {
+                                    if ui
+                                        .button(t1(locale, Key::CopyToDevice, device.name()))
+                                        .clicked()
+                                    {
+                                        copy_to = Some(index);
+                                    }
+                                }
+                                if ui.button(tr(Key::RefreshDevices)).clicked() {
+                                    app.devices = mounted_devices();
+                                }
+                            });
+                            if let Some(index) = copy_to {
+                                app.copy_to_device(index, Path::new(output_path));
+                            }
+                        }
+                        for notice in [&app.calibre_notice, &app.kindle_notice, &app.device_notice]
+                            .into_iter()
+                            .flatten()
+                        {
+                            ui.label(egui::RichText::new(notice).size(12.0));
+                        }
                     }
 
                     ui.add_space(20.0);
@@ -105,20 +557,558 @@ pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
 
                     if ui.button(tr(Key::Close)).clicked() {
                         app.show_conversion_modal = false;
+                        app.overwrite_conflict = None;
+                        app.calibre_notice = None;
+                        app.kindle_notice = None;
+                        app.device_notice = None;
                         app.conversion_result = None;
                         app.conversion_error = None;
+                        app.conversion_warnings.clear();
+                        app.conversion_image_sizes.clear();
+                        app.conversion_validation.clear();
+                        app.conversion_epubcheck = None;
+                        app.conversion_report = None;
                     }
                 });
             });
     }
 
     if app.chapter_editor.open {
+        let preview_options = app.chapter_preview_options();
+        let regex = app.custom_regex();
         let input = ChapterEditorInput {
             text: &app.input_file.content,
             method: app.selected_method,
-            regex: &app.custom_regex_pattern,
+            regex: &regex,
+            split: SplitOptions {
+                title_exclusions: &app.title_exclusions,
+                chapter_length: app.chapter_length,
+            },
             config_path: app.custom_regex_file.as_ref(),
+            preprocessing: Preprocessing {
+                strip_text_header: app.strip_text_header,
+                clean_whitespace: app.clean_whitespace,
+                cleanup_rules: &app.cleanup_rules,
+                punctuation: app.punctuation,
+            },
+            preview_options: &preview_options,
         };
         app.chapter_editor.show(ctx, &input, app.locale);
     }
+    app.reading_preview.show(ctx, app.locale);
+
+    if app.show_merge_files {
+        merge_files_window(app, ctx);
+    }
+
+    if app.show_kindle_settings {
+        kindle_settings_window(app, ctx);
+    }
+
+    if app.show_help {
+        help_window(app, ctx);
+    }
+
+    if app.show_shortcut_settings {
+        shortcut_settings_window(app, ctx);
+    }
+}
+
+/// 帮助：基本流程与当前快捷键。
+fn help_window(app: &mut MainApp, ctx: &egui::Context) {
+    let locale = app.locale;
+    let tr = |key| t(locale, key);
+    let mut open = app.show_help;
+    egui::Window::new(tr(Key::Help))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(tr(Key::HelpWorkflow));
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new(tr(Key::KeyboardShortcuts)).strong());
+            egui::Grid::new("help_shortcuts")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for action in ShortcutAction::ALL {
+                        ui.label(action.label(locale));
+                        ui.label(ctx.format_shortcut(&app.shortcuts.get(action)));
+                        ui.end_row();
+                    }
+                });
+            if ui.button(tr(Key::CustomizeShortcuts)).clicked() {
+                app.show_shortcut_settings = true;
+            }
+        });
+    app.show_help = open;
+}
+
+/// 快捷键设置：点击某个动作的按键后按下新的组合键，Esc 取消。
+fn shortcut_settings_window(app: &mut MainApp, ctx: &egui::Context) {
+    let locale = app.locale;
+    let tr = |key| t(locale, key);
+    if let Some(action) = app.shortcut_recording {
+        let (pressed, cancelled) = ctx.input(|input| {
+            (
+                pressed_shortcut(input),
+                input.key_pressed(egui::Key::Escape),
+            )
+        });
+        if let Some(shortcut) = pressed {
+            *app.shortcuts.get_mut(action) = shortcut;
+            app.shortcut_recording = None;
+        } else if cancelled {
+            app.shortcut_recording = None;
+        }
+    }
+
+    let mut open = app.show_shortcut_settings;
+    egui::Window::new(tr(Key::KeyboardShortcuts))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(tr(Key::ShortcutsHint)).small());
+            egui::Grid::new("shortcut_settings")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for action in ShortcutAction::ALL {
+                        ui.label(action.label(locale));
+                        let text = if app.shortcut_recording == Some(action) {
+                            tr(Key::ShortcutRecording).to_string()
+                        } else {
+                            ctx.format_shortcut(&app.shortcuts.get(action))
+                        };
+                        let response = ui.button(text);
+                        if response.clicked() {
+                            // 让出焦点，避免按下空格或回车时再次触发按钮。
+                            response.surrender_focus();
+                            app.shortcut_recording = Some(action);
+                        }
+                        if let Some(other) = app.shortcuts.conflict(action) {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(207, 95, 38),
+                                t1(locale, Key::ShortcutConflict, other.label(locale)),
+                            );
+                        }
+                        ui.end_row();
+                    }
+                });
+            if ui.button(tr(Key::ResetShortcuts)).clicked() {
+                app.shortcuts = Shortcuts::default();
+                app.shortcut_recording = None;
+            }
+        });
+    if !open {
+        app.shortcut_recording = None;
+    }
+    app.show_shortcut_settings = open;
+}
+
+/// 合并多个文本文件：调整顺序、卷名与编码后合并为源文本，每个文件成为一卷。
+fn merge_files_window(app: &mut MainApp, ctx: &egui::Context) {
+    let locale = app.locale;
+    let tr = |key| t(locale, key);
+    let mut open = app.show_merge_files;
+    egui::Window::new(tr(Key::MergeFilesTitle))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(640.0)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(tr(Key::MergeFilesHint)).small());
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr(Key::BatchAddFiles)).clicked() {
+                    if let Some(paths) = pick_text_files(tr(Key::TextFileFilter)) {
+                        app.merge_items.extend(
+                            paths
+                                .into_iter()
+                                .map(|path| MergeItem::new(MergeInput::new(path))),
+                        );
+                    } else if cfg!(target_arch = "wasm32") {
+                        app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
+                    }
+                }
+                if ui.button(tr(Key::BatchClear)).clicked() {
+                    app.merge_items.clear();
+                }
+            });
+            ui.add_space(6.0);
+            if app.merge_items.is_empty() {
+                ui.label(tr(Key::MergeFilesEmpty));
+            } else {
+                merge_items_ui(ui, locale, &mut app.merge_items);
+            }
+            ui.add_space(6.0);
+            if ui
+                .add_enabled(
+                    !app.merge_items.is_empty(),
+                    egui::Button::new(tr(Key::MergeAndLoad)),
+                )
+                .clicked()
+            {
+                app.merge_source_files();
+            }
+            if let Some(err) = &app.input_file.error {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+        });
+    app.show_merge_files &= open;
+}
+
+/// 合并列表：文件名与首行预览、卷名、编码，以及上移/下移/删除按钮。
+fn merge_items_ui(ui: &mut egui::Ui, locale: Locale, items: &mut Vec<MergeItem>) {
+    let tr = |key| t(locale, key);
+    let total = items.len();
+    let mut swap = None;
+    let mut remove = None;
+    egui::Grid::new("merge_files")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.label(tr(Key::MergeFileColumn));
+            ui.label(tr(Key::MergeVolumeColumn));
+            ui.label(tr(Key::MergeEncodingColumn));
+            ui.end_row();
+            for (index, item) in items.iter_mut().enumerate() {
+                ui.label(format!("{}.", index + 1));
+                ui.vertical(|ui| {
+                    let name = item
+                        .input
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    ui.label(name);
+                    ui.label(
+                        egui::RichText::new(&item.preview)
+                            .small()
+                            .color(egui::Color32::GRAY),
+                    );
+                });
+                ui.add(
+                    egui::TextEdit::singleline(&mut item.input.volume_title).desired_width(160.0),
+                );
+                let mut encoding = item.input.encoding;
+                egui::ComboBox::from_id_salt(("merge_encoding", index))
+                    .selected_text(encoding.label(locale))
+                    .show_ui(ui, |ui| {
+                        for option in TextEncoding::ALL {
+                            ui.selectable_value(&mut encoding, option, option.label(locale));
+                        }
+                    });
+                if encoding != item.input.encoding {
+                    item.input.encoding = encoding;
+                    item.refresh_preview();
+                }
+                ui.horizontal(|ui| {
+                    if ui.small_button(tr(Key::Up)).clicked() && index > 0 {
+                        swap = Some((index - 1, index));
+                    }
+                    if ui.small_button(tr(Key::Down)).clicked() && index + 1 < total {
+                        swap = Some((index, index + 1));
+                    }
+                    if ui.small_button(tr(Key::Delete)).clicked() {
+                        remove = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    if let Some((a, b)) = swap {
+        items.swap(a, b);
+    }
+    if let Some(index) = remove {
+        items.remove(index);
+    }
+}
+
+/// 发送到 Kindle 的 SMTP 设置；切换加密方式时端口随之换成该方式的常用端口。
+fn kindle_settings_window(app: &mut MainApp, ctx: &egui::Context) {
+    let locale = app.locale;
+    let tr = |key| t(locale, key);
+    let mut open = app.show_kindle_settings;
+    egui::Window::new(tr(Key::KindleSettingsTitle))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let settings = &mut app.kindle;
+            egui::Grid::new("kindle_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(tr(Key::SmtpServer));
+                    ui.text_edit_singleline(&mut settings.smtp_host);
+                    ui.end_row();
+                    ui.label(tr(Key::SmtpSecurityLabel));
+                    ui.horizontal(|ui| {
+                        for security in SmtpSecurity::ALL {
+                            if ui
+                                .selectable_value(
+                                    &mut settings.security,
+                                    security,
+                                    security.label(),
+                                )
+                                .changed()
+                            {
+                                settings.smtp_port = security.default_port();
+                            }
+                        }
+                    });
+                    ui.end_row();
+                    ui.label(tr(Key::SmtpPort));
+                    ui.add(egui::DragValue::new(&mut settings.smtp_port).range(1..=65535));
+                    ui.end_row();
+                    ui.label(tr(Key::SmtpUsername));
+                    ui.text_edit_singleline(&mut settings.username);
+                    ui.end_row();
+                    ui.label(tr(Key::SmtpPassword));
+                    ui.add(egui::TextEdit::singleline(&mut settings.password).password(true));
+                    ui.end_row();
+                    ui.label(tr(Key::KindleFrom));
+                    ui.text_edit_singleline(&mut settings.from);
+                    ui.end_row();
+                    ui.label(tr(Key::KindleTo));
+                    ui.text_edit_singleline(&mut settings.to);
+                    ui.end_row();
+                });
+            ui.checkbox(
+                &mut settings.send_after_export,
+                tr(Key::SendToKindleAfterExport),
+            );
+            ui.label(egui::RichText::new(tr(Key::KindleHint)).small());
+            if let Err(err) = settings.validate() {
+                ui.colored_label(
+                    egui::Color32::from_rgb(207, 95, 38),
+                    t1(locale, Key::KindleIncomplete, err),
+                );
+            }
+        });
+    app.show_kindle_settings = open;
+}
+
+/// 在线元数据查询：填写条件、查询并选用一条候选结果。
+fn metadata_lookup_window(app: &mut MainApp, ctx: &egui::Context) {
+    let locale = app.locale;
+    let tr = |key| t(locale, key);
+    let mut open = app.metadata_lookup.open;
+    let mut chosen = None;
+    egui::Window::new(tr(Key::LookupMetadata))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            let state = &mut app.metadata_lookup;
+            egui::Grid::new("metadata_lookup_query")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(tr(Key::LookupSource));
+                    egui::ComboBox::from_id_salt("metadata_lookup_source")
+                        .selected_text(state.source.label())
+                        .show_ui(ui, |ui| {
+                            for source in MetadataSource::ALL {
+                                ui.selectable_value(&mut state.source, source, source.label());
+                            }
+                        });
+                    ui.end_row();
+                    ui.label(tr(Key::TitleLabel));
+                    ui.text_edit_singleline(&mut state.query.title);
+                    ui.end_row();
+                    ui.label(tr(Key::AuthorLabel));
+                    ui.text_edit_singleline(&mut state.query.author);
+                    ui.end_row();
+                    ui.label(tr(Key::Isbn));
+                    ui.text_edit_singleline(&mut state.query.isbn);
+                    ui.end_row();
+                });
+            ui.horizontal(|ui| {
+                let enabled = !state.is_busy() && !state.query.is_empty();
+                if ui
+                    .add_enabled(enabled, egui::Button::new(tr(Key::LookupSearch)))
+                    .clicked()
+                {
+                    state.search();
+                }
+                ui.checkbox(&mut state.download_cover, tr(Key::LookupDownloadCover));
+                if state.is_busy() {
+                    ui.spinner();
+                    ui.label(tr(Key::LookupSearching));
+                }
+            });
+            if let Some(err) = &state.error {
+                ui.colored_label(egui::Color32::RED, t1(locale, Key::LookupFailed, err));
+            } else if state.searched && state.candidates.is_empty() {
+                ui.label(tr(Key::LookupNoResults));
+            }
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    for (index, candidate) in state.candidates.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.button(tr(Key::LookupApply)).clicked() {
+                                chosen = Some(index);
+                            }
+                            ui.label(egui::RichText::new(&candidate.title).strong());
+                        });
+                        candidate_summary_ui(ui, candidate);
+                        ui.separator();
+                    }
+                });
+        });
+    let state = &mut app.metadata_lookup;
+    state.open = open;
+    if let Some(candidate) = chosen.and_then(|index| state.candidates.get(index).cloned()) {
+        candidate.apply_to(&mut app.book_info);
+        if candidate.details_url.is_some() {
+            // 封面在补全详情后再下载，避免两个任务互相覆盖。
+            state.fetch_details(candidate);
+        } else if state.download_cover
+            && let Some(url) = candidate.cover_url
+        {
+            state.download_cover(url);
+        }
+        state.open = false;
+    }
+}
+
+fn candidate_summary_ui(ui: &mut egui::Ui, candidate: &MetadataCandidate) {
+    let authors = candidate.authors.join(", ");
+    let details: Vec<&str> = [
+        authors.as_str(),
+        candidate.publisher.as_str(),
+        candidate.publish_date.as_str(),
+        candidate.isbn.as_str(),
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect();
+    if !details.is_empty() {
+        ui.label(details.join(" · "));
+    }
+    if !candidate.categories.is_empty() {
+        ui.label(egui::RichText::new(candidate.categories.join(" / ")).small());
+    }
+    if !candidate.description.is_empty() {
+        let summary: String = candidate.description.chars().take(160).collect();
+        ui.label(egui::RichText::new(summary).small().weak());
+    }
+}
+
+/// 成功弹窗中的统计摘要；篇幅异常的章节收在折叠区内。
+fn conversion_report_ui(ui: &mut egui::Ui, locale: Locale, report: &ConversionReport) {
+    ui.add_space(10.0);
+    ui.label(t2(
+        locale,
+        Key::ReportChapters,
+        report.chapters,
+        report.images,
+    ));
+    ui.label(t2(locale, Key::ReportLength, report.chars, report.words));
+    if let Some(whitespace) = report.whitespace.filter(|summary| summary.total() > 0) {
+        ui.label(t1(locale, Key::ReportWhitespace, whitespace.total()));
+        ui.label(egui::RichText::new(whitespace.details(locale)).small());
+    }
+    if report.outliers.is_empty() {
+        return;
+    }
+    egui::CollapsingHeader::new(t1(locale, Key::ReportOutliers, report.outliers.len()))
+        .id_salt("conversion_report_outliers")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    for outlier in &report.outliers {
+                        let kind = match outlier.kind {
+                            OutlierKind::Short => t(locale, Key::OutlierShort),
+                            OutlierKind::Long => t(locale, Key::OutlierLong),
+                        };
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "#{} {} · {} · {}",
+                                outlier.index + 1,
+                                outlier.title,
+                                outlier.chars,
+                                kind
+                            ))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(207, 95, 38)),
+                        );
+                    }
+                });
+        });
+}
+
+fn epubcheck_report_ui(ui: &mut egui::Ui, locale: Locale, report: &EpubcheckReport) {
+    let errors = report.count(EpubcheckSeverity::Fatal) + report.count(EpubcheckSeverity::Error);
+    let warnings = report.count(EpubcheckSeverity::Warning);
+    let color = if report.passed() {
+        egui::Color32::from_rgb(46, 125, 50)
+    } else {
+        egui::Color32::from_rgb(207, 95, 38)
+    };
+    ui.add_space(6.0);
+    ui.label(
+        egui::RichText::new(t2(locale, Key::EpubcheckSummary, errors, warnings))
+            .size(12.0)
+            .color(color),
+    );
+    if report.messages.is_empty() {
+        return;
+    }
+    egui::CollapsingHeader::new(t(locale, Key::EpubcheckDetails))
+        .id_salt("epubcheck_details")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("epubcheck_messages")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for message in &report.messages {
+                                ui.label(egui::RichText::new(message.severity.label()).size(12.0));
+                                ui.label(egui::RichText::new(&message.id).size(12.0));
+                                ui.label(egui::RichText::new(&message.location).size(12.0));
+                                ui.label(egui::RichText::new(&message.message).size(12.0));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+}
+
+/// 文件拖到窗口上方时覆盖一层提示。
+pub(super) fn drop_overlay(app: &MainApp, ctx: &egui::Context) {
+    if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+        return;
+    }
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("file_drop_overlay"),
+    ));
+    let rect = ctx.content_rect();
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(160));
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        t(app.locale, Key::DropFilesHint),
+        egui::FontId::proportional(20.0),
+        egui::Color32::WHITE,
+    );
+}
+
+/// 转换进度对应的阶段说明。
+pub(super) fn progress_stage(locale: Locale, progress: ConversionProgress) -> String {
+    match progress {
+        ConversionProgress::Decoding => t(locale, Key::ProgressDecoding).to_string(),
+        ConversionProgress::Splitting => t(locale, Key::ProgressSplitting).to_string(),
+        ConversionProgress::Split { chapters } => t1(locale, Key::ProgressSplit, chapters),
+        ConversionProgress::ChapterRendered { done, total } => {
+            t2(locale, Key::ProgressRendering, done, total)
+        }
+        ConversionProgress::Writing => t(locale, Key::ProgressWriting).to_string(),
+    }
 }
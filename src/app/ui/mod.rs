@@ -6,6 +6,10 @@ mod top_panel;
 
 use super::MainApp;
 
+pub(super) fn shortcuts(app: &mut MainApp, ctx: &egui::Context) {
+    top_panel::handle_shortcuts(app, ctx);
+}
+
 pub(super) fn top_panel(app: &mut MainApp, ctx: &egui::Context) {
     top_panel::top_panel(app, ctx);
 }
@@ -25,3 +29,7 @@ pub(super) fn central_panel(app: &mut MainApp, ctx: &egui::Context) {
 pub(super) fn dialogs(app: &mut MainApp, ctx: &egui::Context) {
     dialogs::dialogs(app, ctx);
 }
+
+pub(super) fn drop_overlay(app: &MainApp, ctx: &egui::Context) {
+    dialogs::drop_overlay(app, ctx);
+}
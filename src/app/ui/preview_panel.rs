@@ -13,7 +13,14 @@ pub(super) fn preview_panel(app: &mut MainApp, ctx: &egui::Context) {
         .default_width(240.0)
         .show(ctx, |ui| {
             card(ui, tr(Key::CoverPreview), |ui| {
-                show_image_ui(ui, locale, &mut app.input_image);
+                let cover = if app.generate_cover {
+                    &mut app.generated_cover
+                } else if app.cover_fit.enabled && !app.input_image.content.is_empty() {
+                    &mut app.fitted_cover
+                } else {
+                    &mut app.input_image
+                };
+                show_image_ui(ui, locale, cover);
             });
 
             ui.add_space(10.0);
@@ -27,11 +34,19 @@ pub(super) fn preview_panel(app: &mut MainApp, ctx: &egui::Context) {
                 ui.label(format!(
                     "{}: {}",
                     tr(Key::AuthorLabel),
-                    display_or_placeholder(&app.book_info.author, tr(Key::PlaceholderUnknown))
+                    display_or_placeholder(&app.book_info.author(), tr(Key::PlaceholderUnknown))
                 ));
                 ui.label(t1(locale, Key::OutputLabel, &app.output_path));
                 ui.label(t1(locale, Key::TemplateLabel, &app.filename_template));
                 ui.label(t1(locale, Key::ImagesLabel, app.images.len()));
+                ui.add_space(6.0);
+                if ui
+                    .button(tr(Key::ReadingPreview))
+                    .on_hover_text(tr(Key::ReadingPreviewHint))
+                    .clicked()
+                {
+                    app.open_reading_preview();
+                }
             });
         });
 }
@@ -1,6 +1,28 @@
-use crate::{Key, t};
+use std::path::PathBuf;
 
-use super::super::MainApp;
+use crate::{Key, Locale, t};
+
+use super::super::app_helpers::card;
+use super::super::{MainApp, RecentFiles};
+
+/// 以按钮列出最近文件，返回被点击的路径。
+pub(super) fn recent_list(ui: &mut egui::Ui, locale: Locale, paths: &[PathBuf]) -> Option<PathBuf> {
+    if paths.is_empty() {
+        ui.weak(t(locale, Key::NoRecentFiles));
+        return None;
+    }
+    let mut clicked = None;
+    for path in paths {
+        if ui
+            .button(RecentFiles::label(path))
+            .on_hover_text(path.to_string_lossy())
+            .clicked()
+        {
+            clicked = Some(path.clone());
+        }
+    }
+    clicked
+}
 
 pub(super) fn side_nav(app: &mut MainApp, ctx: &egui::Context) {
     let locale = app.locale;
@@ -35,5 +57,28 @@ pub(super) fn side_nav(app: &mut MainApp, ctx: &egui::Context) {
             ui.add_space(6.0);
             ui.checkbox(&mut app.include_images_section, tr(Key::IncludeGallery));
             ui.checkbox(&mut app.toc_options.insert_toc_page, tr(Key::InsertToc));
+
+            if !app.recent.is_empty() {
+                ui.add_space(10.0);
+                let mut reopen = None;
+                let mut reveal = None;
+                card(ui, tr(Key::RecentFiles), |ui| {
+                    if !app.recent.texts.is_empty() {
+                        ui.label(egui::RichText::new(tr(Key::RecentTexts)).size(12.0));
+                        reopen = recent_list(ui, locale, &app.recent.texts);
+                    }
+                    if !app.recent.outputs.is_empty() {
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(tr(Key::RecentOutputs)).size(12.0));
+                        reveal = recent_list(ui, locale, &app.recent.outputs);
+                    }
+                });
+                if let Some(path) = reopen {
+                    app.load_text_file(path);
+                }
+                if let Some(path) = reveal {
+                    app.open_output_folder(&path);
+                }
+            }
         });
 }
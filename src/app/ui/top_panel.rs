@@ -1,11 +1,142 @@
-use crate::{Key, Locale, t};
+#[cfg(not(target_arch = "wasm32"))]
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
 
-use super::super::app_helpers::apply_theme;
+use crate::epub_import::import_epub;
+use crate::project::PROJECT_EXTENSION;
+use crate::{Key, Locale, t, t1};
+
+use super::super::app_helpers::{apply_theme, pick_text_input};
+use super::super::shortcuts::ShortcutAction;
 use super::super::{MainApp, ThemeMode};
+use super::side_nav::recent_list;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_project_file(filter_name: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter(filter_name, &[PROJECT_EXTENSION])
+        .pick_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pick_project_file(_filter_name: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_epub_file() -> Option<PathBuf> {
+    FileDialog::new().add_filter("EPUB", &["epub"]).pick_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pick_epub_file() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_text_file(epub: &Path) -> Option<PathBuf> {
+    let stem = epub.file_stem().unwrap_or_default().to_string_lossy();
+    let mut dialog = FileDialog::new()
+        .add_filter("TXT", &["txt"])
+        .set_file_name(format!("{stem}.txt"));
+    if let Some(dir) = epub.parent() {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.save_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_text_file(_epub: &Path) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_project_file(filter_name: &str, stem: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter(filter_name, &[PROJECT_EXTENSION])
+        .set_file_name(format!("{stem}.{PROJECT_EXTENSION}"))
+        .save_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_project_file(_filter_name: &str, _stem: &str) -> Option<PathBuf> {
+    None
+}
+
+/// 保存工程：已有工程路径且不是“另存为”时直接覆盖，否则先选择保存位置。
+fn save_project_flow(app: &mut MainApp, save_as: bool) {
+    let tr = |key| t(app.locale, key);
+    let path = match &app.project_path {
+        Some(path) if !save_as => Some(path.clone()),
+        _ => {
+            let stem = if app.book_info.title.trim().is_empty() {
+                "book"
+            } else {
+                app.book_info.title.trim()
+            };
+            save_project_file(tr(Key::ProjectFilter), stem)
+        }
+    };
+    if let Some(path) = path {
+        app.save_project(path);
+    } else if cfg!(target_arch = "wasm32") {
+        app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
+    }
+}
+
+/// EPUB 转 TXT：导出纯文本并作为源文本载入，便于按章节规则重新分章。
+fn extract_text_flow(app: &mut MainApp) {
+    let locale = app.locale;
+    let Some(epub) = pick_epub_file() else {
+        if cfg!(target_arch = "wasm32") {
+            app.runtime_notice = Some(t(locale, Key::DesktopOnlyAction).to_string());
+        }
+        return;
+    };
+    let Some(output) = save_text_file(&epub) else {
+        return;
+    };
+    let written = import_epub(&epub).and_then(|imported| {
+        std::fs::write(&output, imported.plain_text())
+            .map_err(|err| format!("{}: {err}", output.display()))
+    });
+    match written {
+        Ok(()) => {
+            app.load_text_file(output.clone());
+            app.runtime_notice = Some(t1(locale, Key::ExtractedText, output.display()));
+        }
+        Err(err) => app.runtime_notice = Some(t1(locale, Key::ExtractTextFailed, err)),
+    }
+}
+
+fn open_text_flow(app: &mut MainApp, ctx: &egui::Context) {
+    if let Some(path) = pick_text_input(ctx, app.locale, &app.web_inbox) {
+        app.load_text_file(path);
+    }
+}
+
+/// 执行本帧按下的全局快捷键；录制新快捷键时不响应。
+pub(super) fn handle_shortcuts(app: &mut MainApp, ctx: &egui::Context) {
+    if app.shortcut_recording.is_some() {
+        return;
+    }
+    let Some(action) = ctx.input_mut(|input| app.shortcuts.consume(input)) else {
+        return;
+    };
+    match action {
+        ShortcutAction::OpenText => open_text_flow(app, ctx),
+        ShortcutAction::SaveProject => save_project_flow(app, false),
+        ShortcutAction::OpenEditor => app.show_editor = true,
+        ShortcutAction::Convert => app.run_conversion(),
+        ShortcutAction::Help => app.show_help = true,
+    }
+}
 
 pub(super) fn top_panel(app: &mut MainApp, ctx: &egui::Context) {
     let locale = app.locale;
     let tr = |key| t(locale, key);
+    let shortcuts = app.shortcuts.clone();
+    let shortcut_text = |action| ctx.format_shortcut(&shortcuts.get(action));
 
     egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
         let accent = ui.visuals().selection.bg_fill;
@@ -16,6 +147,95 @@ pub(super) fn top_panel(app: &mut MainApp, ctx: &egui::Context) {
                 ui.horizontal(|ui| {
                     ui.label(egui::RichText::new("Reasypub").size(24.0).strong());
                     ui.add_space(6.0);
+                    ui.menu_button(tr(Key::MenuFile), |ui| {
+                        if ui
+                            .add(
+                                egui::Button::new(tr(Key::OpenTextFile))
+                                    .shortcut_text(shortcut_text(ShortcutAction::OpenText)),
+                            )
+                            .clicked()
+                        {
+                            ui.close();
+                            open_text_flow(app, ctx);
+                        }
+                        if ui.button(tr(Key::MergeTextFiles)).clicked() {
+                            ui.close();
+                            app.show_merge_files = true;
+                        }
+                        if ui.button(tr(Key::ExtractEpubText)).clicked() {
+                            ui.close();
+                            extract_text_flow(app);
+                        }
+                        if ui.button(tr(Key::OpenProject)).clicked() {
+                            ui.close();
+                            if let Some(path) = pick_project_file(tr(Key::ProjectFilter)) {
+                                app.open_project(path);
+                            } else if cfg!(target_arch = "wasm32") {
+                                app.runtime_notice = Some(tr(Key::DesktopOnlyAction).to_string());
+                            }
+                        }
+                        ui.separator();
+                        let save = ui
+                            .add(
+                                egui::Button::new(tr(Key::SaveProject))
+                                    .shortcut_text(shortcut_text(ShortcutAction::SaveProject)),
+                            )
+                            .clicked();
+                        let save_as = ui.button(tr(Key::SaveProjectAs)).clicked();
+                        if save || save_as {
+                            ui.close();
+                            save_project_flow(app, save_as);
+                        }
+                        ui.separator();
+                        ui.menu_button(tr(Key::RecentTexts), |ui| {
+                            if let Some(path) = recent_list(ui, locale, &app.recent.texts) {
+                                ui.close();
+                                app.load_text_file(path);
+                            }
+                        });
+                        ui.menu_button(tr(Key::RecentOutputs), |ui| {
+                            if let Some(path) = recent_list(ui, locale, &app.recent.outputs) {
+                                ui.close();
+                                app.open_output_folder(&path);
+                            }
+                        });
+                        if ui
+                            .add_enabled(
+                                !app.recent.is_empty(),
+                                egui::Button::new(tr(Key::ClearRecent)),
+                            )
+                            .clicked()
+                        {
+                            ui.close();
+                            app.recent.clear();
+                        }
+                        ui.separator();
+                        let idle = app.conversion_job.is_none() && app.batch_job.is_none();
+                        if ui
+                            .add_enabled(idle, egui::Button::new(tr(Key::ResetSettings)))
+                            .on_hover_text(tr(Key::ResetSettingsHint))
+                            .clicked()
+                        {
+                            ui.close();
+                            app.reset_settings();
+                        }
+                        ui.separator();
+                        if ui.button(tr(Key::KeyboardShortcuts)).clicked() {
+                            ui.close();
+                            app.show_shortcut_settings = true;
+                        }
+                        if ui
+                            .add(
+                                egui::Button::new(tr(Key::Help))
+                                    .shortcut_text(shortcut_text(ShortcutAction::Help)),
+                            )
+                            .clicked()
+                        {
+                            ui.close();
+                            app.show_help = true;
+                        }
+                    });
+                    ui.add_space(6.0);
                     ui.label(
                         egui::RichText::new(tr(Key::Subtitle))
                             .size(12.0)
@@ -31,17 +251,67 @@ pub(super) fn top_panel(app: &mut MainApp, ctx: &egui::Context) {
                             });
                         ui.add_space(6.0);
                         if ui
-                            .button(match app.theme_mode {
-                                ThemeMode::Light => tr(Key::ThemeDark),
-                                ThemeMode::Dark => tr(Key::ThemeLight),
+                            .button(if app.theme_mode.is_dark() {
+                                tr(Key::ThemeLight)
+                            } else {
+                                tr(Key::ThemeDark)
                             })
                             .clicked()
                         {
                             app.theme_mode.toggle();
-                            apply_theme(ctx, app.theme_mode);
+                            app.theme_custom = None;
+                            apply_theme(ctx, app.theme_mode, app.theme_custom);
                         }
+                        ui.menu_button(tr(Key::Theme), |ui| theme_menu(app, ui));
                     });
                 });
             });
     });
 }
+
+/// 主题菜单：选择预设，或调整强调色、面板底色与圆角。
+fn theme_menu(app: &mut MainApp, ui: &mut egui::Ui) {
+    let locale = app.locale;
+    let tr = |key| t(locale, key);
+    for mode in ThemeMode::ALL {
+        if ui
+            .selectable_label(app.theme_mode == mode, mode.label(locale))
+            .clicked()
+        {
+            app.theme_mode = mode;
+            app.theme_custom = None;
+        }
+    }
+    ui.separator();
+    let preset = app.theme_mode.colors();
+    let mut colors = app.theme_custom.unwrap_or(preset);
+    ui.horizontal(|ui| {
+        egui::color_picker::color_edit_button_srgba(
+            ui,
+            &mut colors.accent,
+            egui::color_picker::Alpha::Opaque,
+        );
+        ui.label(tr(Key::AccentColor));
+    });
+    ui.horizontal(|ui| {
+        egui::color_picker::color_edit_button_srgba(
+            ui,
+            &mut colors.panel_fill,
+            egui::color_picker::Alpha::Opaque,
+        );
+        ui.label(tr(Key::PanelFill));
+    });
+    ui.add(egui::Slider::new(&mut colors.corner_radius, 0..=24).text(tr(Key::CornerRadius)));
+    if colors != app.theme_custom.unwrap_or(preset) {
+        app.theme_custom = Some(colors);
+    }
+    if ui
+        .add_enabled(
+            app.theme_custom.is_some(),
+            egui::Button::new(tr(Key::ResetTheme)),
+        )
+        .clicked()
+    {
+        app.theme_custom = None;
+    }
+}
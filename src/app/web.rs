@@ -0,0 +1,125 @@
+//! 浏览器端的文件读写：通过异步文件对话框把文件读入内存，并以下载的方式交付生成的电子书。
+//!
+//! Web 端没有可用的文件系统路径，选中的文件以“文件名 + 字节”的形式经通道送回 UI 线程，
+//! 由 `MainApp::poll_web_files` 在下一帧载入。桌面端仍使用同步的 `rfd::FileDialog`。
+
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// 浏览器端选取文件的用途，决定载入后放到哪里。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WebPick {
+    Text,
+    Cover,
+    ChapterHeader,
+    Images,
+}
+
+impl WebPick {
+    /// 是否允许一次选取多个文件。
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn multiple(self) -> bool {
+        matches!(self, Self::Images)
+    }
+}
+
+/// 读入内存的文件。
+#[derive(Debug, Clone)]
+pub(super) struct WebFile {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// 异步选取结果的收件箱。
+pub(super) struct WebInbox {
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    sender: Sender<(WebPick, Vec<WebFile>)>,
+    receiver: Receiver<(WebPick, Vec<WebFile>)>,
+}
+
+impl Default for WebInbox {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+}
+
+impl WebInbox {
+    /// 打开浏览器文件对话框；读取完成后把文件送入收件箱并请求重绘。
+    #[cfg(target_arch = "wasm32")]
+    pub(super) fn pick(
+        &self,
+        ctx: &egui::Context,
+        pick: WebPick,
+        filter_name: &str,
+        extensions: &[&str],
+    ) {
+        let dialog = rfd::AsyncFileDialog::new().add_filter(filter_name, extensions);
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let handles = if pick.multiple() {
+                dialog.pick_files().await.unwrap_or_default()
+            } else {
+                dialog.pick_file().await.into_iter().collect()
+            };
+            let mut files = Vec::with_capacity(handles.len());
+            for handle in handles {
+                files.push(WebFile {
+                    name: handle.file_name(),
+                    bytes: handle.read().await,
+                });
+            }
+            if !files.is_empty() && sender.send((pick, files)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// 桌面端直接使用同步对话框，不会走到这里。
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) fn pick(
+        &self,
+        _ctx: &egui::Context,
+        _pick: WebPick,
+        _filter_name: &str,
+        _extensions: &[&str],
+    ) {
+    }
+
+    /// 取出一批已读取完成的文件。
+    pub(super) fn try_recv(&self) -> Option<(WebPick, Vec<WebFile>)> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// 触发浏览器下载，把内存中的电子书交给用户保存。
+#[cfg(target_arch = "wasm32")]
+pub(super) fn download(filename: &str, bytes: &[u8]) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let js_error = |err: wasm_bindgen::JsValue| format!("{err:?}");
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("application/epub+zip");
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(js_error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| "document is unavailable".to_string())?;
+    let anchor = document
+        .create_element("a")
+        .map_err(js_error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "failed to create download link".to_string())?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(js_error)
+}
+
+/// 桌面端的产物已写入磁盘，无需下载。
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn download(_filename: &str, _bytes: &[u8]) -> Result<(), String> {
+    Ok(())
+}
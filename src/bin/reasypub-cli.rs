@@ -0,0 +1,6 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+// 无界面命令行入口：参数解析与转换逻辑见 `reasypub::cli`。
+fn main() {
+    std::process::exit(reasypub::cli::run(std::env::args().skip(1)));
+}
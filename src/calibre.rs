@@ -0,0 +1,107 @@
+//! 导出后把生成的电子书加入 Calibre 书库：调用 `calibredb add`。
+//!
+//! `calibredb` 可以是 PATH 中的命令名，也可以是完整路径；未指定书库时由 Calibre
+//! 使用其默认书库（Calibre 本身正在运行时，需要在其偏好设置中允许外部连接）。
+
+use std::path::Path;
+use std::process::Command;
+
+/// 默认的 `calibredb` 命令名，依赖 PATH 查找。
+pub const DEFAULT_CALIBREDB: &str = "calibredb";
+
+/// 运行 `calibredb add`，成功时返回新书在书库中的编号。
+///
+/// `calibredb` 以非零状态退出时返回其错误输出的最后一行。
+pub fn add_to_calibre(
+    calibredb: &str,
+    library: Option<&Path>,
+    book: &Path,
+) -> Result<Vec<u32>, String> {
+    let calibredb = if calibredb.trim().is_empty() {
+        DEFAULT_CALIBREDB
+    } else {
+        calibredb.trim()
+    };
+    let output = calibredb_command(calibredb, library, book)
+        .output()
+        .map_err(|err| format!("failed to start {calibredb}: {err}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .unwrap_or("no error output");
+        return Err(format!("calibredb exited with {}: {detail}", output.status));
+    }
+    Ok(parse_added_ids(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn calibredb_command(calibredb: &str, library: Option<&Path>, book: &Path) -> Command {
+    let mut command = Command::new(calibredb);
+    command.arg("add");
+    if let Some(library) = library {
+        command.arg("--with-library").arg(library);
+    }
+    command.arg(book);
+    command
+}
+
+/// 从 `calibredb add` 的输出中读取 `Added book ids: 12, 13` 一行的编号。
+fn parse_added_ids(stdout: &str) -> Vec<u32> {
+    stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Added book ids:"))
+        .map(|ids| {
+            ids.split(',')
+                .filter_map(|id| id.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::path::PathBuf;
+
+    #[test]
+    fn command_passes_library_and_book() {
+        let book = PathBuf::from("out/书.epub");
+        let command = calibredb_command("calibredb", None, &book);
+        assert_eq!(command.get_program(), OsStr::new("calibredb"));
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(args, [OsStr::new("add"), book.as_os_str()]);
+
+        let library = PathBuf::from("/books/Calibre Library");
+        let command = calibredb_command("/opt/calibre/calibredb", Some(&library), &book);
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                OsStr::new("add"),
+                OsStr::new("--with-library"),
+                library.as_os_str(),
+                book.as_os_str()
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_added_book_ids() {
+        assert_eq!(parse_added_ids("Added book ids: 12\n"), vec![12]);
+        assert_eq!(
+            parse_added_ids("Backing up metadata\nAdded book ids: 3, 4\n"),
+            vec![3, 4]
+        );
+        assert!(parse_added_ids("The following books were not added").is_empty());
+    }
+
+    #[test]
+    fn missing_calibredb_reports_start_failure() {
+        let err =
+            add_to_calibre("reasypub-missing-calibredb", None, Path::new("book.epub")).unwrap_err();
+        assert!(err.contains("failed to start reasypub-missing-calibredb"));
+    }
+}
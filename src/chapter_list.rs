@@ -0,0 +1,296 @@
+//! 章节列表的导入导出：把章节结构（序号、标题、字数）导出为 JSON 或 CSV，在表格软件中
+//! 调整顺序、修改标题或删除行后再导入，按序号把改动应用回章节。
+//!
+//! 序号从 1 开始，指导出时的章节；导入时行的顺序即新的章节顺序，缺少的序号视为删除，
+//! 标题留空时保留原标题，字数列只供参考、导入时忽略。
+
+use std::path::Path;
+
+use crate::ChapterDraft;
+use crate::conversion::ChapterStats;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChapterListFormat {
+    Json,
+    /// 逗号分隔，带 UTF-8 BOM 以便表格软件正确识别中文。
+    Csv,
+}
+
+impl ChapterListFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChapterListFormat::Json => "json",
+            ChapterListFormat::Csv => "csv",
+        }
+    }
+
+    /// 按扩展名判断格式。
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(ChapterListFormat::Json),
+            "csv" => Some(ChapterListFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// 章节列表中的一行。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChapterListEntry {
+    /// 导出时的章节序号（从 1 开始）。
+    pub index: usize,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub chars: usize,
+}
+
+/// 当前章节的列表。
+pub fn chapter_list(chapters: &[ChapterDraft]) -> Vec<ChapterListEntry> {
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| ChapterListEntry {
+            index: index + 1,
+            title: chapter.title.clone(),
+            chars: ChapterStats::of(chapter).chars,
+        })
+        .collect()
+}
+
+pub fn export_chapter_list(chapters: &[ChapterDraft], format: ChapterListFormat) -> String {
+    let entries = chapter_list(chapters);
+    let mut out = String::new();
+    match format {
+        ChapterListFormat::Json => {
+            out.push_str("[\n");
+            for (position, entry) in entries.iter().enumerate() {
+                let separator = if position + 1 < entries.len() {
+                    ","
+                } else {
+                    ""
+                };
+                let line = serde_json::to_string(entry).expect("chapter entries serialize");
+                out.push_str(&format!("  {line}{separator}\n"));
+            }
+            out.push_str("]\n");
+        }
+        ChapterListFormat::Csv => {
+            out.push_str("\u{feff}index,title,chars\r\n");
+            for entry in &entries {
+                out.push_str(&format!(
+                    "{},{},{}\r\n",
+                    entry.index,
+                    csv_field(&entry.title),
+                    entry.chars
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// 解析编辑后的章节列表；字数列可以缺失。
+pub fn parse_chapter_list(
+    text: &str,
+    format: ChapterListFormat,
+) -> Result<Vec<ChapterListEntry>, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    match format {
+        ChapterListFormat::Json => parse_json(text),
+        ChapterListFormat::Csv => parse_csv(text),
+    }
+}
+
+/// 按列表重排、重命名并删减章节；序号越界或重复时报错。
+pub fn apply_chapter_list(
+    chapters: &[ChapterDraft],
+    entries: &[ChapterListEntry],
+) -> Result<Vec<ChapterDraft>, String> {
+    if entries.is_empty() {
+        return Err("the chapter list is empty".to_string());
+    }
+    let mut used = vec![false; chapters.len()];
+    let mut result = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let slot = entry
+            .index
+            .checked_sub(1)
+            .and_then(|index| used.get_mut(index))
+            .ok_or_else(|| {
+                format!(
+                    "chapter {} does not exist (there are {} chapters)",
+                    entry.index,
+                    chapters.len()
+                )
+            })?;
+        if std::mem::replace(slot, true) {
+            return Err(format!("chapter {} is listed more than once", entry.index));
+        }
+        let mut chapter = chapters[entry.index - 1].clone();
+        let title = entry.title.trim();
+        if !title.is_empty() {
+            chapter.title = title.to_string();
+        }
+        result.push(chapter);
+    }
+    Ok(result)
+}
+
+fn parse_json(text: &str) -> Result<Vec<ChapterListEntry>, String> {
+    serde_json::from_str(text).map_err(|err| format!("invalid chapter list: {err}"))
+}
+
+fn parse_csv(text: &str) -> Result<Vec<ChapterListEntry>, String> {
+    let mut records = csv_records(text).into_iter();
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let column = |names: &[&str]| {
+        header.iter().position(|field| {
+            let field = field.trim().to_lowercase();
+            names.contains(&field.as_str())
+        })
+    };
+    let index_column = column(&["index", "序号", "#"])
+        .ok_or_else(|| "missing the index column in the CSV header".to_string())?;
+    let title_column = column(&["title", "标题"]);
+    let chars_column = column(&["chars", "字数"]);
+    records
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|field| !field.trim().is_empty()))
+        .map(|(row, record)| {
+            let field = |column: Option<usize>| {
+                column
+                    .and_then(|column| record.get(column))
+                    .map_or("", |field| field.trim())
+            };
+            let index = field(Some(index_column))
+                .parse()
+                .map_err(|_| format!("line {}: invalid chapter index", row + 2))?;
+            Ok(ChapterListEntry {
+                index,
+                title: field(title_column).to_string(),
+                chars: field(chars_column).parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// 拆分 CSV 记录：支持双引号包裹的字段（内含逗号、换行与 `""` 转义）；表头行只有分号时按分号分隔。
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = if !first_line.contains(',') && first_line.contains(';') {
+        ';'
+    } else {
+        ','
+    };
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            ch if ch == delimiter && !quoted => record.push(std::mem::take(&mut field)),
+            ch => field.push(ch),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r', ';']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapters() -> Vec<ChapterDraft> {
+        ["第一章 开端", "第二章 \"雾\", 起", "第三章 尾声"]
+            .into_iter()
+            .map(|title| ChapterDraft {
+                title: title.to_string(),
+                content: "正文".to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn export_round_trips_in_both_formats() {
+        let chapters = chapters();
+        for format in [ChapterListFormat::Json, ChapterListFormat::Csv] {
+            let exported = export_chapter_list(&chapters, format);
+            let entries = parse_chapter_list(&exported, format).expect("parse");
+            assert_eq!(entries, chapter_list(&chapters), "{format:?}");
+            assert_eq!(entries[1].chars, 10);
+            let applied = apply_chapter_list(&chapters, &entries).expect("apply");
+            assert_eq!(chapter_list(&applied), entries);
+        }
+    }
+
+    #[test]
+    fn import_reorders_renames_and_deletes() {
+        let csv = "序号;标题;字数\n3;终章;0\n\n1;;\n";
+        let entries = parse_chapter_list(csv, ChapterListFormat::Csv).expect("parse");
+        let edited = apply_chapter_list(&chapters(), &entries).expect("apply");
+        let titles: Vec<&str> = edited
+            .iter()
+            .map(|chapter| chapter.title.as_str())
+            .collect();
+        assert_eq!(titles, ["终章", "第一章 开端"]);
+
+        let json = r#"[{"index": 2, "title": "新标题"}]"#;
+        let entries = parse_chapter_list(json, ChapterListFormat::Json).expect("parse");
+        assert_eq!(
+            apply_chapter_list(&chapters(), &entries).unwrap()[0].title,
+            "新标题"
+        );
+    }
+
+    #[test]
+    fn import_rejects_unknown_and_duplicate_indices() {
+        let entry = |index| ChapterListEntry {
+            index,
+            title: String::new(),
+            chars: 0,
+        };
+        assert!(apply_chapter_list(&chapters(), &[entry(4)]).is_err());
+        assert!(apply_chapter_list(&chapters(), &[entry(0)]).is_err());
+        assert!(apply_chapter_list(&chapters(), &[entry(1), entry(1)]).is_err());
+        assert!(apply_chapter_list(&chapters(), &[]).is_err());
+        assert!(parse_chapter_list("title\n甲\n", ChapterListFormat::Csv).is_err());
+        assert!(parse_chapter_list("index\nx\n", ChapterListFormat::Csv).is_err());
+        assert!(parse_chapter_list(r#"[{"title": "甲"}]"#, ChapterListFormat::Json).is_err());
+        assert!(parse_chapter_list(r#"[{"index": 1.5}]"#, ChapterListFormat::Json).is_err());
+        // 过深的嵌套直接报错，而不是递归到栈溢出。
+        let nested = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(parse_chapter_list(&nested, ChapterListFormat::Json).is_err());
+    }
+}
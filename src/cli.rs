@@ -0,0 +1,939 @@
+//! 无界面（headless）命令行转换。
+//!
+//! `reasypub-cli` 二进制只负责把进程参数交给 [`run`]；参数解析与请求构建放在库内，
+//! 便于测试，也让脚本/服务器用户无需 egui 即可复用 [`ConversionFacade`]。
+
+use std::path::{Path, PathBuf};
+
+use crate::conversion::{
+    ConversionError, ConversionFacade, ConversionProgress, ConversionRequest,
+    DEFAULT_CHAPTER_LENGTH, OutlierKind,
+};
+use crate::cover::CoverGeneration;
+use crate::epub_import::extract_text;
+use crate::merge::TextEncoding;
+use crate::numerals::TitleNumerals;
+use crate::punctuation::PunctuationNormalization;
+use crate::text_header::TextHeader;
+use crate::zhconv::ChineseConversion;
+use crate::{
+    BookInfo, ConversionMethod, Creator, CreatorRole, CssTemplate, EpubVersion, ImageAsset,
+    ImageCompatibility, ImageOptimization, MetadataEntry, OutputFormat, OverwritePolicy,
+    PdfOptions, PdfPageSize, SourceFormat, TextStyle, ZipCompression, image_mime_from_extension,
+};
+
+/// 命令行帮助文本。
+pub const USAGE: &str = "\
+Usage: reasypub-cli --input <FILE> [OPTIONS]
+       reasypub-cli --extract-text --input <EPUB> [--output-dir <DIR>]
+
+Convert a TXT or Markdown file into EPUB (or PDF) without the GUI, or turn an
+EPUB back into plain TXT for re-splitting.
+
+Options:
+  -i, --input <FILE>          Source text file (.txt, .md, .markdown)
+  -o, --output-dir <DIR>      Output folder [default: .]
+  -n, --filename <TEMPLATE>   Filename template, supports {书名} {作者} {日期}
+                              {日期:%Y%m%d} {章节数} {isbn} {出版社} {模板}
+      --if-exists <POLICY>    When the output file exists: overwrite, number
+                              (append (1), (2) ...) or fail [default: overwrite]
+  -m, --method <METHOD>       Split method: regex, config, simple, markdown,
+                              length, toc, auto
+  -r, --regex <PATTERN>       Custom chapter regex (implies --method regex);
+                              repeat to try several patterns in priority order
+      --exclude-title <PATTERN>
+                              Never treat lines matching this regex as chapter
+                              titles; may be repeated
+  -c, --config <FILE>         Split config (.toml, or a file holding one regex;
+                              implies --method config)
+      --chapter-length <N>    Characters per chapter (implies --method length)
+                              [default: 5000]
+  -t, --title <TITLE>         Book title [default: input file name]
+  -a, --author <AUTHOR>       Book author (repeatable)
+      --translator <NAME>     Translator (repeatable)
+      --illustrator <NAME>    Illustrator (repeatable)
+      --editor <NAME>         Editor (repeatable)
+      --language <LANG>       Book language, e.g. zh-CN or en (detected from the text if omitted)
+      --publisher <NAME>      Publisher
+      --isbn <ISBN>           ISBN
+      --identifier <UUID>     Publication identifier [default: derived from title and author]
+      --category <NAME>       Category
+      --date <DATE>           Publish date
+      --description <TEXT>    Description
+      --series <NAME>         Series name
+      --series-index <N>      Position in the series, e.g. 2 or 2.5
+      --meta <KEY=VALUE>      Extra OPF metadata, e.g. dc:rights=CC-BY (repeatable)
+      --template <NAME>       CSS template: classic, modern, clean, elegant, folio, fantasy, minimal
+      --cover <FILE>          Cover image (jpg/png/webp/gif)
+      --generate-cover        Typeset title/author/publisher into a PNG cover
+      --epub3                 Write EPUB 3 instead of EPUB 2
+      --heading-template <T>  One-line chapter heading, e.g. {label} · {title} or {num}. {title}
+      --vertical              Vertical writing with right-to-left page order
+      --smart-typography      Curly quotes, dashes and ellipses for non-CJK text
+      --inline-markup         Render **bold**, *italic*, ..emphasis dots.. and {ruby|text}
+      --rtl                   Right-to-left page progression
+      --kepub                 Write a Kobo .kepub.epub
+      --reproducible          Fixed timestamps and entry order for byte-identical output
+      --compression-level <N> Deflate level 0-9 for the EPUB container, 0 = store [default: 6]
+      --store-images          Store images uncompressed (JPEG/PNG/WebP are already compressed)
+      --split-chapters <KB>   Split chapters larger than KB into several XHTML files (e.g. 300)
+      --pdf                   Write a printable PDF instead of EPUB
+      --page-size <NAME>      PDF page size: a4, a5, b6, letter [default: a5]
+      --margin <MM>           PDF page margins in millimetres [default: 18]
+      --zh-convert <MODE>     Chinese script conversion: s2t, s2tw, t2s, tw2s
+      --title-numerals <MODE> Chapter numbers in titles: arabic (第103章), chinese (第一百零三章)
+      --punctuation <MODE>    Normalize punctuation before splitting: full (Chinese), half (English)
+      --header-info           Fill empty book info from a 书名：/作者：/简介： block at the start of the text
+      --strip-header          Remove that block before splitting
+      --clean-whitespace      Strip BOMs, zero-width spaces, soft hyphens, trailing spaces and 3+ blank lines
+      --optimize-images       Downscale and recompress JPEG/PNG images
+      --max-image-size <PX>   Longest image side when optimizing [default: 1600]
+      --jpeg-quality <1-100>  JPEG quality when optimizing [default: 85]
+      --image-compat <NAME>   Transcode images for readers: original, no-webp, eink
+      --grayscale-images      Convert all images to high-contrast 8-bit grayscale for e-ink
+      --rasterize-svg         Render SVG cover and images to PNG for readers without SVG
+      --epubcheck <PATH>      Run epubcheck (executable or .jar) on the output
+      --safe-mode             Skip optional theme assets on disk
+      --no-generator-meta     Omit the reasypub generator tag from the OPF
+      --progress              Report conversion progress on stderr
+      --extract-text          Write the EPUB given with --input as <name>.txt into
+                              the output folder instead of converting
+  -h, --help                  Print help
+  -V, --version               Print version";
+
+/// 解析后的命令行动作。
+#[derive(Debug, Clone)]
+pub enum CliCommand {
+    Help,
+    Version,
+    Convert(Box<CliOptions>),
+    /// 把 EPUB 导出为纯文本。
+    ExtractText {
+        input: PathBuf,
+        output_dir: PathBuf,
+    },
+}
+
+/// 一次命令行转换所需的全部参数；文件内容在 [`CliOptions::into_request`] 时才读取。
+#[derive(Debug, Clone)]
+pub struct CliOptions {
+    pub input: PathBuf,
+    pub output_dir: PathBuf,
+    pub filename_template: Option<String>,
+    pub method: Option<ConversionMethod>,
+    pub custom_regex: String,
+    pub title_exclusions: Vec<String>,
+    pub chapter_length: usize,
+    pub config_path: Option<PathBuf>,
+    pub book_info: BookInfo,
+    pub css_template: CssTemplate,
+    pub cover: Option<PathBuf>,
+    pub generate_cover: bool,
+    pub epub_version: EpubVersion,
+    pub vertical: bool,
+    pub smart_typography: bool,
+    pub inline_markup: bool,
+    pub heading_template: String,
+    pub rtl_page_progression: bool,
+    pub kepub: bool,
+    pub reproducible: bool,
+    pub compression: ZipCompression,
+    pub split_chapter_bytes: Option<usize>,
+    pub output_format: OutputFormat,
+    pub pdf: PdfOptions,
+    pub overwrite_policy: OverwritePolicy,
+    pub safe_mode: bool,
+    pub include_generator_meta: bool,
+    pub chinese_conversion: ChineseConversion,
+    pub title_numerals: TitleNumerals,
+    pub header_info: bool,
+    pub strip_text_header: bool,
+    pub clean_whitespace: bool,
+    pub punctuation: PunctuationNormalization,
+    pub image_optimization: ImageOptimization,
+    pub epubcheck: Option<PathBuf>,
+    pub progress: bool,
+}
+
+impl CliOptions {
+    fn new(input: PathBuf) -> Self {
+        Self {
+            input,
+            output_dir: PathBuf::from("."),
+            filename_template: None,
+            method: None,
+            custom_regex: String::new(),
+            title_exclusions: Vec::new(),
+            chapter_length: DEFAULT_CHAPTER_LENGTH,
+            config_path: None,
+            book_info: BookInfo::default(),
+            css_template: TextStyle::default().css_template,
+            cover: None,
+            generate_cover: false,
+            epub_version: EpubVersion::default(),
+            vertical: false,
+            smart_typography: false,
+            inline_markup: false,
+            heading_template: String::new(),
+            rtl_page_progression: false,
+            kepub: false,
+            reproducible: false,
+            compression: ZipCompression::default(),
+            split_chapter_bytes: None,
+            output_format: OutputFormat::Epub,
+            pdf: PdfOptions::default(),
+            overwrite_policy: OverwritePolicy::Overwrite,
+            safe_mode: false,
+            include_generator_meta: true,
+            chinese_conversion: ChineseConversion::None,
+            title_numerals: TitleNumerals::Keep,
+            header_info: false,
+            strip_text_header: false,
+            clean_whitespace: false,
+            punctuation: PunctuationNormalization::Off,
+            image_optimization: ImageOptimization::default(),
+            epubcheck: None,
+            progress: false,
+        }
+    }
+
+    /// 读取输入与封面文件，构建转换请求。
+    ///
+    /// 输入按 BOM、UTF-8、GB18030 的顺序自动识别编码（同合并多个文件）。
+    /// 未指定分章方式时，Markdown 文件按标题分章，其余按内置中文章节正则。
+    pub fn into_request(self) -> Result<ConversionRequest, ConversionError> {
+        let text = TextEncoding::Auto.decode(&std::fs::read(&self.input)?);
+        let source_format = SourceFormat::from_path(&self.input);
+        let method = self.method.unwrap_or(match source_format {
+            SourceFormat::Markdown => ConversionMethod::MarkdownHeadings,
+            SourceFormat::PlainText => ConversionMethod::Regex,
+        });
+
+        let mut book_info = self.book_info;
+        if self.header_info
+            && let Some(header) = TextHeader::detect(&text)
+        {
+            header.apply_to(&mut book_info, false);
+        }
+        if book_info.title.trim().is_empty()
+            && let Some(stem) = self.input.file_stem().and_then(|s| s.to_str())
+        {
+            book_info.title = stem.to_string();
+        }
+
+        let cover = match self.cover.as_deref() {
+            Some(path) => Some(cover_asset_from_path(path)?),
+            None => None,
+        };
+
+        let defaults = ConversionRequest::default();
+        Ok(ConversionRequest {
+            text,
+            method,
+            custom_regex: self.custom_regex,
+            title_exclusions: self.title_exclusions,
+            chapter_length: self.chapter_length,
+            custom_config_path: self.config_path,
+            book_info,
+            output_dir: self.output_dir,
+            filename_template: self.filename_template.unwrap_or(defaults.filename_template),
+            style: TextStyle {
+                css_template: self.css_template,
+                vertical: self.vertical,
+                smart_typography: self.smart_typography,
+                inline_markup: self.inline_markup,
+                heading_template: self.heading_template,
+                ..Default::default()
+            },
+            cover,
+            include_generator_meta: self.include_generator_meta,
+            safe_mode: self.safe_mode,
+            epub_version: self.epub_version,
+            rtl_page_progression: self.rtl_page_progression,
+            kepub: self.kepub,
+            reproducible: self.reproducible,
+            compression: self.compression,
+            split_chapter_bytes: self.split_chapter_bytes,
+            output_format: self.output_format,
+            pdf: self.pdf,
+            overwrite_policy: self.overwrite_policy,
+            source_format,
+            chinese_conversion: self.chinese_conversion,
+            title_numerals: self.title_numerals,
+            strip_text_header: self.strip_text_header,
+            clean_whitespace: self.clean_whitespace,
+            punctuation: self.punctuation,
+            image_optimization: self.image_optimization,
+            generated_cover: self.generate_cover.then(CoverGeneration::default),
+            epubcheck_path: self.epubcheck,
+            ..Default::default()
+        })
+    }
+}
+
+fn cover_asset_from_path(path: &Path) -> Result<ImageAsset, ConversionError> {
+    let bytes = std::fs::read(path)?;
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("png")
+        .to_lowercase();
+    Ok(ImageAsset {
+        name: format!("cover.{}", ext),
+        bytes: bytes.into(),
+        mime: image_mime_from_extension(&ext).to_string(),
+        caption: None,
+        chapter: None,
+    })
+}
+
+fn parse_method(value: &str) -> Result<ConversionMethod, ConversionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "regex" => Ok(ConversionMethod::Regex),
+        "config" => Ok(ConversionMethod::CustomConfig),
+        "simple" => Ok(ConversionMethod::SimpleRules),
+        "markdown" | "md" => Ok(ConversionMethod::MarkdownHeadings),
+        "length" => Ok(ConversionMethod::ByLength),
+        "toc" => Ok(ConversionMethod::FromToc),
+        "auto" => Ok(ConversionMethod::Heuristic),
+        _ => Err(ConversionError::InvalidInput(format!(
+            "Unknown split method: {value}"
+        ))),
+    }
+}
+
+fn parse_template(value: &str) -> Result<CssTemplate, ConversionError> {
+    CssTemplate::ALL
+        .into_iter()
+        .find(|template| template.to_string().eq_ignore_ascii_case(value))
+        .ok_or_else(|| ConversionError::InvalidInput(format!("Unknown CSS template: {value}")))
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, ConversionError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConversionError::InvalidInput(format!("Invalid number for {flag}: {value}")))
+}
+
+fn parse_image_compat(value: &str) -> Result<ImageCompatibility, ConversionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "original" => Ok(ImageCompatibility::Original),
+        "no-webp" => Ok(ImageCompatibility::NoWebp),
+        "eink" | "e-ink" => Ok(ImageCompatibility::EInk),
+        _ => Err(ConversionError::InvalidInput(format!(
+            "Unknown image compatibility profile: {value}"
+        ))),
+    }
+}
+
+fn parse_page_size(value: &str) -> Result<PdfPageSize, ConversionError> {
+    PdfPageSize::ALL
+        .into_iter()
+        .find(|size| size.label().eq_ignore_ascii_case(value.trim()))
+        .ok_or_else(|| ConversionError::InvalidInput(format!("Unknown page size: {value}")))
+}
+
+fn parse_chinese_conversion(value: &str) -> Result<ChineseConversion, ConversionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(ChineseConversion::None),
+        "s2t" => Ok(ChineseConversion::S2T),
+        "s2tw" => Ok(ChineseConversion::S2TW),
+        "t2s" => Ok(ChineseConversion::T2S),
+        "tw2s" => Ok(ChineseConversion::TW2S),
+        _ => Err(ConversionError::InvalidInput(format!(
+            "Unknown Chinese conversion: {value}"
+        ))),
+    }
+}
+
+fn parse_title_numerals(value: &str) -> Result<TitleNumerals, ConversionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "keep" | "none" => Ok(TitleNumerals::Keep),
+        "arabic" => Ok(TitleNumerals::Arabic),
+        "chinese" => Ok(TitleNumerals::Chinese),
+        _ => Err(ConversionError::InvalidInput(format!(
+            "Unknown title numerals: {value}"
+        ))),
+    }
+}
+
+fn parse_punctuation(value: &str) -> Result<PunctuationNormalization, ConversionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "off" | "none" => Ok(PunctuationNormalization::Off),
+        "full" => Ok(PunctuationNormalization::FullWidth),
+        "half" => Ok(PunctuationNormalization::HalfWidth),
+        _ => Err(ConversionError::InvalidInput(format!(
+            "Unknown punctuation normalization: {value}"
+        ))),
+    }
+}
+
+fn parse_overwrite_policy(value: &str) -> Result<OverwritePolicy, ConversionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "overwrite" => Ok(OverwritePolicy::Overwrite),
+        "number" => Ok(OverwritePolicy::Number),
+        "fail" => Ok(OverwritePolicy::Ask),
+        _ => Err(ConversionError::InvalidInput(format!(
+            "Unknown --if-exists policy: {value}"
+        ))),
+    }
+}
+
+/// 解析命令行参数（不含程序名）。
+pub fn parse_args<I>(args: I) -> Result<CliCommand, ConversionError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut input = None;
+    let mut extract_text = false;
+    let mut options = CliOptions::new(PathBuf::new());
+
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .ok_or_else(|| ConversionError::InvalidInput(format!("Missing value for {flag}")))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "-V" | "--version" => return Ok(CliCommand::Version),
+            "-i" | "--input" => input = Some(PathBuf::from(value(&arg)?)),
+            "-o" | "--output-dir" => options.output_dir = PathBuf::from(value(&arg)?),
+            "-n" | "--filename" => options.filename_template = Some(value(&arg)?),
+            "--if-exists" => options.overwrite_policy = parse_overwrite_policy(&value(&arg)?)?,
+            "-m" | "--method" => options.method = Some(parse_method(&value(&arg)?)?),
+            "-r" | "--regex" => {
+                // 可重复指定，按出现顺序作为优先级。
+                let pattern = value(&arg)?;
+                if !options.custom_regex.is_empty() {
+                    options.custom_regex.push('\n');
+                }
+                options.custom_regex.push_str(&pattern);
+                options.method = Some(ConversionMethod::Regex);
+            }
+            "--exclude-title" => options.title_exclusions.push(value(&arg)?),
+            "--chapter-length" => {
+                let length: usize = parse_number(&arg, &value(&arg)?)?;
+                if length == 0 {
+                    return Err(ConversionError::InvalidInput(format!(
+                        "{arg} must be greater than 0"
+                    )));
+                }
+                options.chapter_length = length;
+                options.method = Some(ConversionMethod::ByLength);
+            }
+            "-c" | "--config" => {
+                options.config_path = Some(PathBuf::from(value(&arg)?));
+                options.method = Some(ConversionMethod::CustomConfig);
+            }
+            "-t" | "--title" => options.book_info.title = value(&arg)?,
+            "-a" | "--author" | "--translator" | "--illustrator" | "--editor" => {
+                let role = match arg.as_str() {
+                    "--translator" => CreatorRole::Translator,
+                    "--illustrator" => CreatorRole::Illustrator,
+                    "--editor" => CreatorRole::Editor,
+                    _ => CreatorRole::Author,
+                };
+                options
+                    .book_info
+                    .creators
+                    .push(Creator::new(value(&arg)?, role));
+            }
+            "--language" => options.book_info.language = value(&arg)?,
+            "--publisher" => options.book_info.publisher = value(&arg)?,
+            "--isbn" => options.book_info.isbn = value(&arg)?,
+            "--identifier" => options.book_info.identifier = value(&arg)?,
+            "--category" => options.book_info.category = value(&arg)?,
+            "--date" => options.book_info.publish_date = value(&arg)?,
+            "--description" => options.book_info.description = value(&arg)?,
+            "--series" => options.book_info.series = value(&arg)?,
+            "--meta" => {
+                let pair = value(&arg)?;
+                let entry = pair
+                    .split_once('=')
+                    .map(|(key, value)| MetadataEntry::new(key.trim(), value))
+                    .filter(MetadataEntry::is_valid)
+                    .ok_or_else(|| {
+                        ConversionError::InvalidInput(format!(
+                            "{arg} expects KEY=VALUE with a valid key, got {pair}"
+                        ))
+                    })?;
+                options.book_info.custom_metadata.push(entry);
+            }
+            "--series-index" => {
+                let index = value(&arg)?;
+                parse_number::<f64>(&arg, &index)?;
+                options.book_info.series_index = index;
+            }
+            "--template" => options.css_template = parse_template(&value(&arg)?)?,
+            "--cover" => options.cover = Some(PathBuf::from(value(&arg)?)),
+            "--generate-cover" => options.generate_cover = true,
+            "--epub3" => options.epub_version = EpubVersion::Epub3,
+            "--vertical" => options.vertical = true,
+            "--smart-typography" => options.smart_typography = true,
+            "--inline-markup" => options.inline_markup = true,
+            "--heading-template" => options.heading_template = value(&arg)?,
+            "--rtl" => options.rtl_page_progression = true,
+            "--kepub" => options.kepub = true,
+            "--reproducible" => options.reproducible = true,
+            "--compression-level" => {
+                let level: u8 = parse_number(&arg, &value(&arg)?)?;
+                if level > 9 {
+                    return Err(ConversionError::InvalidInput(format!(
+                        "{arg} must be between 0 and 9"
+                    )));
+                }
+                options.compression.level = level;
+            }
+            "--store-images" => options.compression.store_images = true,
+            "--split-chapters" => {
+                let kilobytes: usize = parse_number(&arg, &value(&arg)?)?;
+                if kilobytes == 0 {
+                    return Err(ConversionError::InvalidInput(format!(
+                        "{arg} must be greater than 0"
+                    )));
+                }
+                options.split_chapter_bytes = Some(kilobytes * 1024);
+            }
+            "--pdf" => options.output_format = OutputFormat::Pdf,
+            "--page-size" => options.pdf.page_size = parse_page_size(&value(&arg)?)?,
+            "--margin" => {
+                let margin: f32 = parse_number(&arg, &value(&arg)?)?;
+                if !(0.0..=40.0).contains(&margin) {
+                    return Err(ConversionError::InvalidInput(format!(
+                        "{arg} must be between 0 and 40"
+                    )));
+                }
+                options.pdf.margin_mm = margin;
+            }
+            "--zh-convert" => options.chinese_conversion = parse_chinese_conversion(&value(&arg)?)?,
+            "--title-numerals" => options.title_numerals = parse_title_numerals(&value(&arg)?)?,
+            "--punctuation" => options.punctuation = parse_punctuation(&value(&arg)?)?,
+            "--header-info" => options.header_info = true,
+            "--strip-header" => options.strip_text_header = true,
+            "--clean-whitespace" => options.clean_whitespace = true,
+            "--optimize-images" => options.image_optimization.enabled = true,
+            "--max-image-size" => {
+                options.image_optimization.max_dimension = parse_number(&arg, &value(&arg)?)?;
+            }
+            "--jpeg-quality" => {
+                let quality: u8 = parse_number(&arg, &value(&arg)?)?;
+                if !(1..=100).contains(&quality) {
+                    return Err(ConversionError::InvalidInput(format!(
+                        "{arg} must be between 1 and 100"
+                    )));
+                }
+                options.image_optimization.jpeg_quality = quality;
+            }
+            "--image-compat" => {
+                options.image_optimization.compatibility = parse_image_compat(&value(&arg)?)?;
+            }
+            "--grayscale-images" => options.image_optimization.grayscale = true,
+            "--rasterize-svg" => options.image_optimization.rasterize_svg = true,
+            "--epubcheck" => options.epubcheck = Some(PathBuf::from(value(&arg)?)),
+            "--safe-mode" => options.safe_mode = true,
+            "--no-generator-meta" => options.include_generator_meta = false,
+            "--progress" => options.progress = true,
+            "--extract-text" => extract_text = true,
+            other => {
+                return Err(ConversionError::InvalidInput(format!(
+                    "Unknown argument: {other}"
+                )));
+            }
+        }
+    }
+
+    options.input = input.ok_or_else(|| {
+        ConversionError::InvalidInput("Missing required argument --input".to_string())
+    })?;
+    if extract_text {
+        return Ok(CliCommand::ExtractText {
+            input: options.input,
+            output_dir: options.output_dir,
+        });
+    }
+    Ok(CliCommand::Convert(Box::new(options)))
+}
+
+/// 执行命令行转换，返回进程退出码。
+pub fn run<I>(args: I) -> i32
+where
+    I: IntoIterator<Item = String>,
+{
+    let options = match parse_args(args) {
+        Ok(CliCommand::Help) => {
+            println!("{USAGE}");
+            return 0;
+        }
+        Ok(CliCommand::Version) => {
+            println!("reasypub-cli {}", env!("CARGO_PKG_VERSION"));
+            return 0;
+        }
+        Ok(CliCommand::Convert(options)) => *options,
+        Ok(CliCommand::ExtractText { input, output_dir }) => {
+            return match extract_text(&input, &output_dir) {
+                Ok(output) => {
+                    println!("{}", output.display());
+                    0
+                }
+                Err(err) => {
+                    eprintln!("{err}");
+                    1
+                }
+            };
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    let show_progress = options.progress;
+    // 同一行内刷新，避免上万章节刷屏。
+    let report_progress = |event: ConversionProgress| {
+        eprint!("\r\x1b[K[{:>3.0}%] {event}", event.fraction() * 100.0);
+    };
+    if show_progress {
+        report_progress(ConversionProgress::Decoding);
+    }
+    let converted = options.into_request().and_then(|request| {
+        if !show_progress {
+            return ConversionFacade::convert(request);
+        }
+        let result = ConversionFacade::convert_with_progress(request, report_progress);
+        eprintln!();
+        result
+    });
+    match converted {
+        Ok(result) => {
+            for warning in &result.warnings {
+                eprintln!("warning: {warning}");
+            }
+            for issue in &result.validation {
+                eprintln!("validation: {issue}");
+            }
+            if let Some(report) = &result.epubcheck {
+                for message in &report.messages {
+                    eprintln!(
+                        "epubcheck: {} {} {}: {}",
+                        message.severity.label(),
+                        message.id,
+                        message.location,
+                        message.message
+                    );
+                }
+            }
+            let report = &result.report;
+            eprintln!(
+                "report: {} chapters, {} characters, {} words, {} images",
+                report.chapters, report.chars, report.words, report.images
+            );
+            if let Some(whitespace) = &report.whitespace {
+                eprintln!("report: {whitespace}");
+            }
+            for outlier in &report.outliers {
+                let kind = match outlier.kind {
+                    OutlierKind::Short => "short",
+                    OutlierKind::Long => "long",
+                };
+                eprintln!(
+                    "report: chapter {} \"{}\" is {kind} ({} characters)",
+                    outlier.index + 1,
+                    outlier.title,
+                    outlier.chars
+                );
+            }
+            for image in &result.image_sizes {
+                eprintln!(
+                    "image: {} {} -> {} bytes",
+                    image.name, image.before, image.after
+                );
+            }
+            println!("{}", result.output_path);
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_reads_flags() {
+        let command = parse_args(args(&[
+            "-i",
+            "book.txt",
+            "-o",
+            "out",
+            "--regex",
+            "^Chapter",
+            "-t",
+            "Title",
+            "--author",
+            "Someone",
+            "--translator",
+            "Helper",
+            "-a",
+            "Another",
+            "--series",
+            "Saga",
+            "--series-index",
+            "2.5",
+            "--meta",
+            "dc:rights=All rights reserved",
+            "--template",
+            "fantasy",
+            "--epub3",
+            "--vertical",
+            "--smart-typography",
+            "--inline-markup",
+            "--heading-template",
+            "{num}. {title}",
+            "--rtl",
+            "--kepub",
+            "--reproducible",
+            "--compression-level",
+            "9",
+            "--store-images",
+            "--split-chapters",
+            "300",
+            "--pdf",
+            "--page-size",
+            "letter",
+            "--margin",
+            "12.5",
+            "--generate-cover",
+            "--zh-convert",
+            "s2tw",
+            "--title-numerals",
+            "arabic",
+            "--header-info",
+            "--strip-header",
+            "--clean-whitespace",
+            "--punctuation",
+            "full",
+            "--if-exists",
+            "number",
+            "--optimize-images",
+            "--max-image-size",
+            "1200",
+            "--jpeg-quality",
+            "70",
+            "--image-compat",
+            "eink",
+            "--grayscale-images",
+            "--rasterize-svg",
+            "--epubcheck",
+            "tools/epubcheck.jar",
+            "--progress",
+        ]))
+        .expect("parse");
+        let CliCommand::Convert(options) = command else {
+            panic!("expected convert command");
+        };
+        assert_eq!(options.input, PathBuf::from("book.txt"));
+        assert_eq!(options.output_dir, PathBuf::from("out"));
+        assert_eq!(options.method, Some(ConversionMethod::Regex));
+        assert_eq!(options.custom_regex, "^Chapter");
+        assert_eq!(options.book_info.title, "Title");
+        assert_eq!(options.book_info.author(), "Someone, Another");
+        assert_eq!(
+            options.book_info.creators[1],
+            Creator::new("Helper", CreatorRole::Translator)
+        );
+        assert_eq!(options.book_info.series, "Saga");
+        assert_eq!(options.book_info.series_index, "2.5");
+        assert_eq!(
+            options.book_info.custom_metadata,
+            [MetadataEntry::new("dc:rights", "All rights reserved")]
+        );
+        assert_eq!(options.css_template, CssTemplate::Fantasy);
+        assert_eq!(options.epub_version, EpubVersion::Epub3);
+        assert!(options.vertical);
+        assert!(options.smart_typography);
+        assert!(options.inline_markup);
+        assert_eq!(options.heading_template, "{num}. {title}");
+        assert!(options.rtl_page_progression);
+        assert!(options.kepub);
+        assert!(options.reproducible);
+        assert_eq!(options.split_chapter_bytes, Some(300 * 1024));
+        assert_eq!(
+            options.compression,
+            ZipCompression {
+                level: 9,
+                store_images: true,
+            }
+        );
+        assert_eq!(options.output_format, OutputFormat::Pdf);
+        assert_eq!(
+            options.pdf,
+            PdfOptions {
+                page_size: PdfPageSize::Letter,
+                margin_mm: 12.5,
+            }
+        );
+        assert!(options.generate_cover);
+        assert_eq!(options.chinese_conversion, ChineseConversion::S2TW);
+        assert_eq!(options.title_numerals, TitleNumerals::Arabic);
+        assert_eq!(options.punctuation, PunctuationNormalization::FullWidth);
+        assert!(options.header_info);
+        assert!(options.strip_text_header);
+        assert!(options.clean_whitespace);
+        assert_eq!(options.overwrite_policy, OverwritePolicy::Number);
+        assert_eq!(
+            options.image_optimization,
+            ImageOptimization {
+                enabled: true,
+                max_dimension: 1200,
+                jpeg_quality: 70,
+                compatibility: ImageCompatibility::EInk,
+                grayscale: true,
+                rasterize_svg: true,
+            }
+        );
+        assert_eq!(
+            options.epubcheck,
+            Some(PathBuf::from("tools/epubcheck.jar"))
+        );
+        assert!(options.progress);
+    }
+
+    #[test]
+    fn parse_args_repeats_regex_in_priority_order() {
+        let command = parse_args(args(&[
+            "-i", "book.txt", "-r", "^第.卷", "--regex", "^第.章",
+        ]))
+        .expect("parse");
+        let CliCommand::Convert(options) = command else {
+            panic!("expected convert command");
+        };
+        assert_eq!(options.custom_regex, "^第.卷\n^第.章");
+        assert_eq!(options.method, Some(ConversionMethod::Regex));
+    }
+
+    #[test]
+    fn parse_args_collects_title_exclusions() {
+        let command = parse_args(args(&[
+            "-i",
+            "book.txt",
+            "--exclude-title",
+            "见书末$",
+            "--exclude-title",
+            "^第.章.*（续）",
+        ]))
+        .expect("parse");
+        let CliCommand::Convert(options) = command else {
+            panic!("expected convert command");
+        };
+        assert_eq!(options.title_exclusions, vec!["见书末$", "^第.章.*（续）"]);
+        assert_eq!(options.method, None);
+    }
+
+    #[test]
+    fn parse_args_chapter_length_implies_length_method() {
+        let command =
+            parse_args(args(&["-i", "essay.txt", "--chapter-length", "3000"])).expect("parse");
+        let CliCommand::Convert(options) = command else {
+            panic!("expected convert command");
+        };
+        assert_eq!(options.chapter_length, 3000);
+        assert_eq!(options.method, Some(ConversionMethod::ByLength));
+        assert_eq!(
+            parse_method("LENGTH").expect("method"),
+            ConversionMethod::ByLength
+        );
+        assert!(parse_args(args(&["-i", "a.txt", "--chapter-length", "0"])).is_err());
+    }
+
+    #[test]
+    fn parse_args_extract_text_mode() {
+        let command =
+            parse_args(args(&["--extract-text", "-i", "book.epub", "-o", "out"])).expect("parse");
+        let CliCommand::ExtractText { input, output_dir } = command else {
+            panic!("expected extract-text command");
+        };
+        assert_eq!(input, PathBuf::from("book.epub"));
+        assert_eq!(output_dir, PathBuf::from("out"));
+        assert!(parse_args(args(&["--extract-text"])).is_err());
+    }
+
+    #[test]
+    fn parse_args_rejects_invalid_input() {
+        assert!(parse_args(args(&["-o", "out"])).is_err());
+        assert!(parse_args(args(&["-i"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--bogus"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--method", "nope"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--jpeg-quality", "0"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--max-image-size", "big"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--image-compat", "kindle"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--page-size", "a3"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--margin", "80"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--series-index", "二"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--meta", "novalue"])).is_err());
+        assert!(parse_args(args(&["-i", "a.txt", "--meta", "bad key=x"])).is_err());
+        assert!(matches!(
+            parse_args(args(&["-i", "a.txt", "--help"])),
+            Ok(CliCommand::Help)
+        ));
+    }
+
+    #[test]
+    fn markdown_input_defaults_to_heading_split() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-cli-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let input = dir.join("notes.md");
+        std::fs::write(&input, "# One\nbody\n# Two\nmore").expect("write input");
+
+        let CliCommand::Convert(options) = parse_args(args(&[
+            "-i",
+            input.to_str().expect("utf-8 path"),
+            "-o",
+            dir.to_str().expect("utf-8 path"),
+            "-n",
+            "cli",
+        ]))
+        .expect("parse") else {
+            panic!("expected convert command");
+        };
+        let request = options.into_request().expect("request");
+        assert_eq!(request.method, ConversionMethod::MarkdownHeadings);
+        assert_eq!(request.source_format, SourceFormat::Markdown);
+        assert_eq!(request.book_info.title, "notes");
+
+        let result = ConversionFacade::convert(request).expect("convert");
+        assert!(Path::new(&result.output_path).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn gbk_input_is_decoded() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let input = fixtures.join("novel_zh_gbk.txt");
+        let CliCommand::Convert(options) =
+            parse_args(args(&["-i", input.to_str().expect("utf-8 path")])).expect("parse")
+        else {
+            panic!("expected convert command");
+        };
+        let request = options.into_request().expect("request");
+        let utf8 = std::fs::read_to_string(fixtures.join("novel_zh.txt")).expect("read fixture");
+        assert_eq!(request.text, utf8);
+        assert_eq!(request.book_info.title, "novel_zh_gbk");
+    }
+}
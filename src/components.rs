@@ -1 +1,6 @@
 pub mod chapter_editor;
+pub mod chapter_preview;
+pub mod find_replace;
+pub mod reading_preview;
+pub mod text_history;
+pub mod thumbnails;
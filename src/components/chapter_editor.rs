@@ -1,15 +1,138 @@
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
 use egui::{Context, Id, Modal, ScrollArea, Ui};
+use regex::Regex;
 
-use crate::conversion::StrategyFactory;
-use crate::{ChapterDraft, ConversionMethod, Key, Locale, t, t1, t2};
+use crate::chapter_list::{
+    ChapterListFormat, apply_chapter_list, export_chapter_list, parse_chapter_list,
+};
+use crate::components::chapter_preview::ChapterPreviewState;
+use crate::components::find_replace::{
+    ChapterMatch, FindReplaceState, find_in_chapters, match_context, replace_in_chapters,
+};
+use crate::conversion::{
+    ChapterStats, ConversionReport, OutlierKind, Preprocessing, SplitOptions, StrategyFactory,
+};
+use crate::epubworker::EpubBuildOptions;
+use crate::text_header::strip_text_header_if;
+use crate::whitespace::clean_whitespace_if;
+use crate::{
+    ChapterDraft, ChapterHeaderImage, ChapterVariant, ConversionMethod, ImageAsset, Key, Locale,
+    apply_cleanup_rules, image_mime_from_extension, t, t1, t2,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_header_image(filter_name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(filter_name, &["jpeg", "jpg", "png", "webp"])
+        .pick_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pick_header_image(_filter_name: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_chapter_list(filter_name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(filter_name, &["csv", "json"])
+        .pick_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pick_chapter_list(_filter_name: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_chapter_list(filter_name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(format!("{filter_name} (CSV)"), &["csv"])
+        .add_filter(format!("{filter_name} (JSON)"), &["json"])
+        .set_file_name("chapters.csv")
+        .save_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_chapter_list(_filter_name: &str) -> Option<PathBuf> {
+    None
+}
+
+/// 读取单章头图；资源名在构建时按章节序号重新生成。
+fn read_header_image(path: &Path) -> std::io::Result<ImageAsset> {
+    let bytes = std::fs::read(path)?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("header.png")
+        .to_string();
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("png")
+        .to_lowercase();
+    Ok(ImageAsset {
+        name,
+        bytes: bytes.into(),
+        mime: image_mime_from_extension(&ext).to_string(),
+        caption: None,
+        chapter: None,
+    })
+}
+
+/// 批量重命名的默认模板。
+pub const DEFAULT_RENAME_TEMPLATE: &str = "第{n}章 {标题}";
+
+/// 标题开头的章节编号，如 `第十二章`、`Chapter 3`、`12.`。
+static TITLE_NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^\s*(?:第\s*[0-9零〇一二两三四五六七八九十百千万]+\s*[章回节卷集部篇]|chapter\s*\d+|\d+)[\s.:：、\-—]*",
+    )
+    .expect("valid title number regex")
+});
+
+/// 按模板生成标题。
+///
+/// 占位符：`{n}` 为序号，`{原标题}` 为原标题，`{标题}` 为去掉开头章节编号后的原标题。
+pub fn apply_title_template(template: &str, number: usize, title: &str) -> String {
+    let title = title.trim();
+    let stripped = TITLE_NUMBER.replace(title, "");
+    template
+        .replace("{n}", &number.to_string())
+        .replace("{原标题}", title)
+        .replace("{标题}", stripped.trim())
+        .trim()
+        .to_string()
+}
 
 pub struct ChapterEditorInput<'a> {
     pub text: &'a str,
     pub method: ConversionMethod,
     pub regex: &'a str,
+    /// 标题排除规则与按字数分章的目标字数。
+    pub split: SplitOptions<'a>,
     pub config_path: Option<&'a PathBuf>,
+    /// 重新分章前的信息块去除、空白清理、文本清理规则与标点规范化。
+    pub preprocessing: Preprocessing<'a>,
+    /// 章节预览使用的版式参数。
+    pub preview_options: &'a EpubBuildOptions,
+}
+
+impl ChapterEditorInput<'_> {
+    /// 当前输入对应的分章签名，与上次重新分章时不同即视为过期。
+    pub fn signature(&self) -> u64 {
+        crate::chapter_signature(
+            self.text,
+            &self.preprocessing,
+            self.method,
+            self.regex,
+            &self.split,
+            self.config_path.map(|path| path.as_path()),
+        )
+    }
 }
 
 #[derive(Default)]
@@ -19,11 +142,49 @@ pub struct ChapterEditorState {
     pub chapters: Vec<ChapterDraft>,
     pub stale: bool,
     pub error: Option<String>,
+    /// 勾选的章节序号；章节增删或移动后清空。
+    pub selected: BTreeSet<usize>,
+    /// 编辑区显示的章节序号。
+    active: usize,
+    /// 下次绘制时把列表滚动到当前章节。
+    reveal_active: bool,
+    rename: RenameState,
+    find: ChapterFindState,
+    /// “移动所选章节”目标位置（从 1 开始）。
+    move_to: usize,
+    preview: ChapterPreviewState,
     last_refresh_signature: Option<u64>,
+    /// 下次显示时按当前分章设置重新分章。
+    refresh_on_show: bool,
     was_open: bool,
     modal_size: Option<egui::Vec2>,
 }
 
+/// 批量重命名面板的状态。
+struct RenameState {
+    open: bool,
+    template: String,
+    start: usize,
+}
+
+/// 跨章查找替换面板的状态。
+#[derive(Default)]
+struct ChapterFindState {
+    find: FindReplaceState,
+    /// 取消勾选、替换时跳过的匹配：(章节序号, 起始字节)。
+    excluded: BTreeSet<(usize, usize)>,
+}
+
+impl Default for RenameState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            template: DEFAULT_RENAME_TEMPLATE.to_string(),
+            start: 1,
+        }
+    }
+}
+
 impl ChapterEditorState {
     pub fn show(&mut self, ctx: &Context, input: &ChapterEditorInput<'_>, locale: Locale) {
         if !self.open {
@@ -31,13 +192,12 @@ impl ChapterEditorState {
             return;
         }
 
-        let current_signature = crate::chapter_signature(
-            input.text,
-            input.method,
-            input.regex,
-            input.config_path.map(|path| path.as_path()),
-        );
+        let current_signature = input.signature();
         self.update_stale(current_signature);
+        if std::mem::take(&mut self.refresh_on_show) {
+            self.refresh(input, current_signature);
+            self.selected.clear();
+        }
 
         let ignore_input = !self.was_open;
         self.was_open = true;
@@ -74,19 +234,13 @@ impl ChapterEditorState {
                 ui.separator();
                 ui.add_space(8.0);
 
-                let scroll_height = ui.available_height().max(120.0);
-                ScrollArea::vertical()
-                    .auto_shrink([false, false])
-                    .max_height(scroll_height)
-                    .show(ui, |ui| {
-                        if self.chapters.is_empty() {
-                            ui.centered_and_justified(|ui| {
-                                ui.label(t(locale, Key::NoChapters));
-                            });
-                        } else {
-                            self.chapters_ui(ui, locale);
-                        }
+                if self.chapters.is_empty() {
+                    ui.centered_and_justified(|ui| {
+                        ui.label(t(locale, Key::NoChapters));
                     });
+                } else {
+                    self.chapters_ui(ui, locale);
+                }
             };
 
             if ignore_input {
@@ -100,7 +254,12 @@ impl ChapterEditorState {
             self.open = false;
             self.was_open = false;
             self.modal_size = None;
+            self.preview.index = None;
+            return;
         }
+
+        self.preview
+            .show(ctx, &self.chapters, input.preview_options, locale);
     }
 
     fn header_ui(
@@ -113,22 +272,71 @@ impl ChapterEditorState {
         ui.horizontal(|ui| {
             if ui.button(t(locale, Key::Refresh)).clicked() {
                 self.refresh(input, signature);
+                self.selected.clear();
             }
             if ui.button(t(locale, Key::AddChapter)).clicked() {
                 self.chapters.push(ChapterDraft {
                     title: t(locale, Key::NewChapter).to_string(),
-                    content: String::new(),
+                    ..Default::default()
                 });
+                self.focus_chapter(self.chapters.len() - 1);
                 self.stale = false;
                 self.error = None;
             }
             if ui.button(t(locale, Key::Clear)).clicked() {
                 self.chapters.clear();
+                self.selected.clear();
                 self.stale = false;
                 self.error = None;
             }
+            ui.separator();
+            if ui
+                .selectable_label(self.rename.open, t(locale, Key::BulkRename))
+                .clicked()
+            {
+                self.rename.open = !self.rename.open;
+            }
+            if ui
+                .selectable_label(self.find.find.open, t(locale, Key::ChapterFind))
+                .clicked()
+            {
+                self.find.find.open = !self.find.find.open;
+            }
+            ui.separator();
+            let has_chapters = !self.chapters.is_empty();
+            if ui
+                .add_enabled(
+                    has_chapters,
+                    egui::Button::new(t(locale, Key::ExportChapterList)),
+                )
+                .on_hover_text(t(locale, Key::ChapterListHint))
+                .clicked()
+                && let Some(path) = save_chapter_list(t(locale, Key::ChapterListFilter))
+            {
+                self.export_chapter_list(&path, locale);
+            }
+            if ui
+                .add_enabled(
+                    has_chapters,
+                    egui::Button::new(t(locale, Key::ImportChapterList)),
+                )
+                .on_hover_text(t(locale, Key::ChapterListHint))
+                .clicked()
+                && let Some(path) = pick_chapter_list(t(locale, Key::ChapterListFilter))
+            {
+                self.import_chapter_list(&path, locale);
+            }
         });
 
+        if self.rename.open {
+            ui.add_space(4.0);
+            self.rename_ui(ui, locale);
+        }
+        if self.find.find.open {
+            ui.add_space(4.0);
+            self.find_ui(ui, locale);
+        }
+
         ui.horizontal(|ui| {
             ui.checkbox(
                 &mut self.use_for_conversion,
@@ -145,77 +353,676 @@ impl ChapterEditorState {
         if let Some(err) = &self.error {
             ui.label(egui::RichText::new(err).color(egui::Color32::RED));
         } else {
-            ui.label(t1(locale, Key::ChaptersCount, self.chapters.len()));
+            let stats: Vec<ChapterStats> = self.chapters.iter().map(ChapterStats::of).collect();
+            let total: ChapterStats = stats.iter().copied().sum();
+            ui.horizontal_wrapped(|ui| {
+                ui.label(t1(locale, Key::ChaptersCount, self.chapters.len()));
+                ui.separator();
+                ui.label(t2(locale, Key::ReportLength, total.chars, total.words));
+                ui.separator();
+                ui.label(t1(locale, Key::ReadingTime, total.reading_minutes()));
+                if let Some((index, longest)) = stats
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(index, stats)| (stats.chars, std::cmp::Reverse(*index)))
+                    .filter(|_| stats.len() > 1)
+                {
+                    ui.separator();
+                    ui.label(t2(locale, Key::LongestChapter, index + 1, longest.chars));
+                }
+            });
+        }
+
+        if !self.selected.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(t1(locale, Key::SelectedCount, self.selected.len()));
+                ui.label(t(locale, Key::MoveSelectedTo));
+                ui.add(
+                    egui::DragValue::new(&mut self.move_to).range(1..=self.chapters.len().max(1)),
+                );
+                if ui.button(t(locale, Key::Move)).clicked() {
+                    let selected = self.selected.clone();
+                    self.move_block(&selected, self.move_to.saturating_sub(1));
+                }
+            });
         }
     }
 
-    fn chapters_ui(&mut self, ui: &mut Ui, locale: Locale) {
-        let mut move_actions: Vec<(usize, isize)> = Vec::new();
-        let mut remove_indices: Vec<usize> = Vec::new();
-
-        let total = self.chapters.len();
-        for (index, chapter) in self.chapters.iter_mut().enumerate() {
-            let header = t2(locale, Key::ChapterIndex, index + 1, &chapter.title);
-            let header_id = ui.make_persistent_id(("chapter_editor_chapter", index));
-            let header_response = egui::collapsing_header::CollapsingState::load_with_default_open(
-                ui.ctx(),
-                header_id,
-                index < 2,
-            )
-            .show_header(ui, |ui| {
-                ui.add(egui::Label::new(header).wrap());
+    /// 导出章节列表；未选择扩展名时按 CSV 写出。
+    fn export_chapter_list(&mut self, path: &Path, locale: Locale) {
+        let format = ChapterListFormat::from_path(path).unwrap_or(ChapterListFormat::Csv);
+        self.error = std::fs::write(path, export_chapter_list(&self.chapters, format))
+            .err()
+            .map(|err| t1(locale, Key::ChapterListExportFailed, err));
+    }
+
+    /// 按编辑后的章节列表重排、重命名与删减章节，并启用章节编辑结果。
+    fn import_chapter_list(&mut self, path: &Path, locale: Locale) {
+        let imported = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                let format = ChapterListFormat::from_path(path).unwrap_or(
+                    if text
+                        .trim_start_matches('\u{feff}')
+                        .trim_start()
+                        .starts_with('[')
+                    {
+                        ChapterListFormat::Json
+                    } else {
+                        ChapterListFormat::Csv
+                    },
+                );
+                parse_chapter_list(&text, format)
+            })
+            .and_then(|entries| apply_chapter_list(&self.chapters, &entries));
+        match imported {
+            Ok(chapters) => {
+                self.chapters = chapters;
+                self.selected.clear();
+                self.use_for_conversion = true;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(t1(locale, Key::ChapterListImportFailed, err)),
+        }
+    }
+
+    fn rename_ui(&mut self, ui: &mut Ui, locale: Locale) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(t(locale, Key::RenameTemplate));
+                ui.add(egui::TextEdit::singleline(&mut self.rename.template).desired_width(200.0));
+                ui.label(t(locale, Key::RenameStart));
+                ui.add(egui::DragValue::new(&mut self.rename.start).range(0..=99_999));
+            });
+            ui.label(egui::RichText::new(t(locale, Key::RenameTemplateHint)).size(11.0));
+            ui.horizontal(|ui| {
+                if ui.small_button(t(locale, Key::SelectAll)).clicked() {
+                    self.selected = (0..self.chapters.len()).collect();
+                }
+                if ui.small_button(t(locale, Key::SelectNone)).clicked() {
+                    self.selected.clear();
+                }
+                ui.label(t1(locale, Key::SelectedCount, self.selected.len()));
             });
 
-            header_response.body(|ui| {
-                ui.horizontal(|ui| {
-                    if ui.small_button(t(locale, Key::Up)).clicked() && index > 0 {
-                        move_actions.push((index, -1));
-                    }
-                    if ui.small_button(t(locale, Key::Down)).clicked() && index + 1 < total {
-                        move_actions.push((index, 1));
-                    }
-                    if ui.small_button(t(locale, Key::Delete)).clicked() {
-                        remove_indices.push(index);
+            let preview = self.rename_preview();
+            if preview.is_empty() {
+                ui.label(t(locale, Key::RenameSelectHint));
+            } else {
+                ScrollArea::vertical()
+                    .id_salt("chapter_rename_preview")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        for (index, title) in &preview {
+                            ui.label(format!("{} → {title}", self.chapters[*index].title));
+                        }
+                    });
+            }
+            if ui
+                .add_enabled(
+                    !preview.is_empty(),
+                    egui::Button::new(t(locale, Key::RenameApply)),
+                )
+                .clicked()
+            {
+                self.apply_rename();
+            }
+        });
+    }
+
+    /// 跨章查找替换：结果按章节分组，可逐条或整章取消勾选后只替换所选匹配。
+    fn find_ui(&mut self, ui: &mut Ui, locale: Locale) {
+        let state = &mut self.find;
+        let chapters = &mut self.chapters;
+        let mut jump: Option<usize> = None;
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut state.find.query)
+                            .hint_text(t(locale, Key::FindHint))
+                            .desired_width(200.0),
+                    )
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut state.find.use_regex, t(locale, Key::FindRegex))
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut state.find.match_case, t(locale, Key::FindMatchCase))
+                    .changed();
+            });
+            ui.add(
+                egui::TextEdit::singleline(&mut state.find.replacement)
+                    .hint_text(t(locale, Key::ReplaceHint))
+                    .desired_width(200.0),
+            );
+            if changed {
+                state.excluded.clear();
+                state.find.replaced = None;
+            }
+
+            let pattern = match state.find.pattern() {
+                Ok(Some(pattern)) => pattern,
+                Ok(None) => return,
+                Err(err) => {
+                    ui.label(
+                        egui::RichText::new(t1(locale, Key::RegexError, err))
+                            .color(egui::Color32::RED),
+                    );
+                    return;
+                }
+            };
+            let matches = find_in_chapters(&pattern, chapters);
+            let is_selected = |found: &ChapterMatch| {
+                !state.excluded.contains(&(found.chapter, found.range.start))
+            };
+            let selected: Vec<ChapterMatch> = matches
+                .iter()
+                .filter(|found| is_selected(found))
+                .cloned()
+                .collect();
+            let groups: Vec<&[ChapterMatch]> =
+                matches.chunk_by(|a, b| a.chapter == b.chapter).collect();
+
+            let mut replace = false;
+            ui.horizontal(|ui| {
+                ui.label(t2(
+                    locale,
+                    Key::ChapterFindResults,
+                    matches.len(),
+                    groups.len(),
+                ));
+                replace = ui
+                    .add_enabled(
+                        !selected.is_empty(),
+                        egui::Button::new(t1(locale, Key::ReplaceSelected, selected.len())),
+                    )
+                    .clicked();
+                if let Some(replaced) = state.find.replaced {
+                    ui.label(t1(locale, Key::ReplacedCount, replaced));
+                }
+            });
+
+            let mut toggles: Vec<(&ChapterMatch, bool)> = Vec::new();
+            ScrollArea::vertical()
+                .id_salt("chapter_find_results")
+                .max_height(220.0)
+                .show(ui, |ui| {
+                    for (position, group) in groups.iter().enumerate() {
+                        let chapter = group[0].chapter;
+                        let id = ui.make_persistent_id(("chapter_find_group", chapter));
+                        egui::collapsing_header::CollapsingState::load_with_default_open(
+                            ui.ctx(),
+                            id,
+                            position < 3,
+                        )
+                        .show_header(ui, |ui| {
+                            let all = group.iter().all(is_selected);
+                            let mut checked = all;
+                            if ui.checkbox(&mut checked, "").changed() {
+                                toggles.extend(group.iter().map(|found| (found, checked)));
+                            }
+                            let label = format!(
+                                "{} ({})",
+                                t2(
+                                    locale,
+                                    Key::ChapterIndex,
+                                    chapter + 1,
+                                    &chapters[chapter].title
+                                ),
+                                group.len()
+                            );
+                            if ui.link(label).clicked() {
+                                jump = Some(chapter);
+                            }
+                        })
+                        .body(|ui| {
+                            for found in group.iter() {
+                                ui.horizontal(|ui| {
+                                    let mut checked = is_selected(found);
+                                    if ui.checkbox(&mut checked, "").changed() {
+                                        toggles.push((found, checked));
+                                    }
+                                    ui.label(context_job(
+                                        ui,
+                                        &chapters[chapter].content,
+                                        found.range.clone(),
+                                    ));
+                                });
+                            }
+                        });
                     }
                 });
 
-                ui.label(t(locale, Key::ChapterTitle));
-                let title_width = ui.available_width().max(120.0);
-                ui.add_sized(
-                    [title_width, ui.spacing().interact_size.y],
-                    egui::TextEdit::singleline(&mut chapter.title),
+            for (found, checked) in toggles {
+                let key = (found.chapter, found.range.start);
+                if checked {
+                    state.excluded.remove(&key);
+                } else {
+                    state.excluded.insert(key);
+                }
+            }
+            if replace {
+                let replaced = replace_in_chapters(
+                    &pattern,
+                    chapters,
+                    &selected,
+                    &state.find.replacement,
+                    state.find.use_regex,
                 );
-                ui.add_space(6.0);
-                ui.label(t(locale, Key::ChapterContent));
-                let content_height = 160.0;
-                let content_width = ui.available_width().max(120.0);
-                ui.add_sized(
-                    [content_width, content_height],
-                    egui::TextEdit::multiline(&mut chapter.content)
-                        .desired_rows(8)
-                        .lock_focus(true),
+                state.find.replaced = Some(replaced);
+                state.excluded.clear();
+            }
+        });
+        if let Some(index) = jump {
+            self.focus_chapter(index);
+        }
+    }
+
+    /// 所选章节按模板重命名后的标题，序号按章节顺序从起始值递增。
+    pub fn rename_preview(&self) -> Vec<(usize, String)> {
+        self.selected
+            .iter()
+            .filter(|index| **index < self.chapters.len())
+            .enumerate()
+            .map(|(offset, &index)| {
+                let title = apply_title_template(
+                    &self.rename.template,
+                    self.rename.start + offset,
+                    &self.chapters[index].title,
                 );
-            });
-            ui.add_space(6.0);
+                (index, title)
+            })
+            .collect()
+    }
+
+    /// 应用批量重命名，返回改名的章节数。
+    pub fn apply_rename(&mut self) -> usize {
+        let preview = self.rename_preview();
+        for (index, title) in &preview {
+            self.chapters[*index].title.clone_from(title);
         }
+        preview.len()
+    }
 
-        for (index, direction) in move_actions {
-            let new_index = (index as isize + direction) as usize;
-            if index < self.chapters.len() && new_index < self.chapters.len() {
-                self.chapters.swap(index, new_index);
+    /// 章节列表与当前章节的编辑区。
+    ///
+    /// 左侧列表每章只占一行，按可见范围逐行绘制（`show_rows`），上千章时每帧也只创建可见行的
+    /// 控件；标题、正文等编辑框只为右侧当前选中的一章创建。
+    fn chapters_ui(&mut self, ui: &mut Ui, locale: Locale) {
+        self.active = self.active.min(self.chapters.len() - 1);
+        let mut drop_move: Option<(usize, usize)> = None;
+        let mut activate: Option<usize> = None;
+        let stats: Vec<ChapterStats> = self.chapters.iter().map(ChapterStats::of).collect();
+        let lengths: Vec<usize> = stats.iter().map(|stats| stats.chars).collect();
+        let outliers = ConversionReport::outlier_kinds(&lengths);
+        let longest = lengths.iter().copied().max().unwrap_or_default();
+
+        let height = ui.available_height().max(120.0);
+        let list_width = (ui.available_width() * 0.45).clamp(260.0, 400.0);
+        let row_height = ui.spacing().interact_size.y;
+        ui.horizontal_top(|ui| {
+            ui.allocate_ui_with_layout(
+                egui::vec2(list_width, height),
+                egui::Layout::top_down(egui::Align::Min),
+                |ui| {
+                    let mut scroll = ScrollArea::vertical()
+                        .id_salt("chapter_editor_list")
+                        .auto_shrink([false, false])
+                        .max_height(height);
+                    if std::mem::take(&mut self.reveal_active) {
+                        let row_span = row_height + ui.spacing().item_spacing.y;
+                        let offset = self.active as f32 * row_span - (height - row_span) / 2.0;
+                        scroll = scroll.vertical_scroll_offset(offset.max(0.0));
+                    }
+                    scroll.show_rows(ui, row_height, self.chapters.len(), |ui, rows| {
+                        for index in rows {
+                            let header = t2(
+                                locale,
+                                Key::ChapterIndex,
+                                index + 1,
+                                &self.chapters[index].title,
+                            );
+                            let row = ui
+                                .horizontal(|ui| {
+                                    ui.set_height(row_height);
+                                    let drag_id =
+                                        ui.make_persistent_id(("chapter_editor_drag", index));
+                                    ui.dnd_drag_source(drag_id, index, |ui| {
+                                        ui.label(egui::RichText::new("≡").strong());
+                                    })
+                                    .response
+                                    .on_hover_text(t(locale, Key::DragToReorder));
+                                    let mut checked = self.selected.contains(&index);
+                                    if ui.checkbox(&mut checked, "").changed() {
+                                        if checked {
+                                            self.selected.insert(index);
+                                        } else {
+                                            self.selected.remove(&index);
+                                        }
+                                    }
+                                    length_bar_ui(
+                                        ui,
+                                        stats[index],
+                                        outliers[index],
+                                        longest,
+                                        locale,
+                                    );
+                                    let label =
+                                        egui::Button::selectable(index == self.active, header)
+                                            .truncate();
+                                    if ui.add(label).clicked() {
+                                        activate = Some(index);
+                                    }
+                                })
+                                .response;
+
+                            // 拖动经过时按指针位于上半或下半部分，在本章之前或之后显示插入线。
+                            if row.dnd_hover_payload::<usize>().is_some()
+                                && let Some(pointer) =
+                                    ui.input(|input| input.pointer.interact_pos())
+                            {
+                                let before = pointer.y < row.rect.center().y;
+                                let y = if before {
+                                    row.rect.top()
+                                } else {
+                                    row.rect.bottom()
+                                };
+                                ui.painter().hline(
+                                    row.rect.x_range(),
+                                    y,
+                                    egui::Stroke::new(2.0, ui.visuals().selection.bg_fill),
+                                );
+                                if let Some(from) = row.dnd_release_payload::<usize>() {
+                                    drop_move =
+                                        Some((*from, if before { index } else { index + 1 }));
+                                }
+                            }
+                        }
+                    });
+                },
+            );
+            ui.separator();
+            ui.allocate_ui_with_layout(
+                egui::vec2(ui.available_width(), height),
+                egui::Layout::top_down(egui::Align::Min),
+                |ui| {
+                    ScrollArea::vertical()
+                        .id_salt("chapter_editor_detail")
+                        .auto_shrink([false, false])
+                        .max_height(height)
+                        .show(ui, |ui| self.chapter_detail_ui(ui, locale));
+                },
+            );
+        });
+
+        if let Some(index) = activate {
+            self.active = index;
+        }
+
+        // 拖动已勾选的章节时整组移动，否则只移动被拖动的一章。
+        if let Some((from, insert_before)) = drop_move {
+            let block = if self.selected.contains(&from) {
+                self.selected.clone()
+            } else {
+                BTreeSet::from([from])
+            };
+            let position = insert_before - block.range(..insert_before).count();
+            self.move_block(&block, position);
+        }
+    }
+
+    /// 当前章节的编辑区：操作按钮、标题、正文、章节 CSS 与章首图设置。
+    fn chapter_detail_ui(&mut self, ui: &mut Ui, locale: Locale) {
+        let index = self.active;
+        let mut remove = false;
+        let mut merge = false;
+        let mut split_cursor: Option<usize> = None;
+        let mut pick_error: Option<String> = None;
+        let mut preview = false;
+        let content_id = ui.make_persistent_id(("chapter_editor_content", index));
+        let content_height = (ui.available_height() - 200.0).max(160.0);
+        let chapter = &mut self.chapters[index];
+
+        ui.horizontal(|ui| {
+            if ui.small_button(t(locale, Key::ChapterPreview)).clicked() {
+                preview = true;
+            }
+            if ui.small_button(t(locale, Key::Delete)).clicked() {
+                remove = true;
+            }
+            if ui
+                .add_enabled(
+                    index > 0,
+                    egui::Button::new(t(locale, Key::MergePrevious)).small(),
+                )
+                .clicked()
+            {
+                merge = true;
+            }
+            if ui.small_button(t(locale, Key::SplitAtCursor)).clicked() {
+                // 点击按钮后编辑框失去焦点，但光标位置仍保存在其状态中。
+                let cursor = egui::TextEdit::load_state(ui.ctx(), content_id)
+                    .and_then(|state| state.cursor.char_range())
+                    .map(|range| range.primary.index);
+                match cursor {
+                    Some(cursor) => split_cursor = Some(cursor),
+                    None => pick_error = Some(t(locale, Key::SplitNeedsCursor).to_string()),
+                }
+            }
+        });
+
+        ui.label(t(locale, Key::ChapterTitle));
+        let title_width = ui.available_width().max(120.0);
+        ui.add_sized(
+            [title_width, ui.spacing().interact_size.y],
+            egui::TextEdit::singleline(&mut chapter.title),
+        );
+        ui.add_space(6.0);
+        ui.label(t(locale, Key::ChapterContent));
+        let content_width = ui.available_width().max(120.0);
+        ui.add_sized(
+            [content_width, content_height],
+            egui::TextEdit::multiline(&mut chapter.content)
+                .id(content_id)
+                .desired_rows(8)
+                .lock_focus(true),
+        );
+        ui.add_space(6.0);
+        ui.label(t(locale, Key::ChapterCss));
+        ui.add_sized(
+            [content_width, 60.0],
+            egui::TextEdit::multiline(&mut chapter.custom_css)
+                .code_editor()
+                .desired_rows(3),
+        );
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.label(t(locale, Key::ChapterBodyClass));
+            ui.add(egui::TextEdit::singleline(&mut chapter.css_class).desired_width(140.0));
+            ui.label(t(locale, Key::ChapterVariantLabel));
+            egui::ComboBox::from_id_salt(("chapter_variant", index))
+                .selected_text(chapter.variant.label(locale))
+                .show_ui(ui, |ui| {
+                    for variant in ChapterVariant::ALL {
+                        ui.selectable_value(&mut chapter.variant, variant, variant.label(locale));
+                    }
+                });
+        });
+        ui.horizontal_wrapped(|ui| {
+            ui.label(t(locale, Key::ChapterHeaderOverride));
+            if ui
+                .radio(
+                    matches!(chapter.header_image, ChapterHeaderImage::Inherit),
+                    t(locale, Key::ChapterHeaderInherit),
+                )
+                .clicked()
+            {
+                chapter.header_image = ChapterHeaderImage::Inherit;
+            }
+            if ui
+                .radio(
+                    matches!(chapter.header_image, ChapterHeaderImage::Hidden),
+                    t(locale, Key::ChapterHeaderHidden),
+                )
+                .clicked()
+            {
+                chapter.header_image = ChapterHeaderImage::Hidden;
             }
+            if ui.button(t(locale, Key::ChapterHeaderCustom)).clicked() {
+                if let Some(path) = pick_header_image(t(locale, Key::PanelImages)) {
+                    match read_header_image(&path) {
+                        Ok(image) => chapter.header_image = ChapterHeaderImage::Custom(image),
+                        Err(err) => pick_error = Some(err.to_string()),
+                    }
+                } else if cfg!(target_arch = "wasm32") {
+                    pick_error = Some(t(locale, Key::DesktopOnlyAction).to_string());
+                }
+            }
+            if let ChapterHeaderImage::Custom(image) = &chapter.header_image {
+                ui.label(&image.name);
+            }
+        });
+
+        if pick_error.is_some() {
+            self.error = pick_error;
+        }
+        if preview {
+            self.preview.open(index);
         }
 
-        for index in remove_indices.into_iter().rev() {
-            if index < self.chapters.len() {
-                self.chapters.remove(index);
+        // 章节数量变化后原有勾选不再对应同一章节。
+        if remove || merge || split_cursor.is_some() {
+            self.selected.clear();
+        }
+        if remove {
+            self.chapters.remove(index);
+            self.active = self.active.min(self.chapters.len().saturating_sub(1));
+        }
+        if merge {
+            self.merge_with_previous(index);
+        }
+        if let Some(cursor) = split_cursor
+            && !self.split_at(index, cursor)
+        {
+            self.error = Some(t(locale, Key::SplitNeedsCursor).to_string());
+        }
+    }
+
+    /// 在编辑区打开第 `index` 章，并把列表滚动到该章。
+    fn focus_chapter(&mut self, index: usize) {
+        self.active = index;
+        self.reveal_active = true;
+    }
+
+    /// 把 `indices` 中的章节按原顺序移动为连续的一组，使第一章落在移动后的第 `position` 位（从 0 开始）。
+    ///
+    /// 移动后勾选集合更新为这组章节的新位置，便于继续移动；编辑区仍显示原来那一章。
+    pub fn move_block(&mut self, indices: &BTreeSet<usize>, position: usize) {
+        let indices: Vec<usize> = indices
+            .iter()
+            .copied()
+            .filter(|index| *index < self.chapters.len())
+            .collect();
+        if indices.is_empty() {
+            return;
+        }
+        let mut block = Vec::with_capacity(indices.len());
+        for index in indices.iter().rev() {
+            block.push(self.chapters.remove(*index));
+        }
+        block.reverse();
+        let position = position.min(self.chapters.len());
+        let count = block.len();
+        self.active = match indices.iter().position(|index| *index == self.active) {
+            Some(rank) => position + rank,
+            None => {
+                let rest = self.active - indices.partition_point(|index| *index < self.active);
+                if rest < position { rest } else { rest + count }
             }
+        };
+        self.chapters.splice(position..position, block);
+        self.selected = (position..position + count).collect();
+    }
+
+    /// 把第 `index` 章并入上一章：原标题作为正文中的一行保留，便于再次拆分。
+    pub fn merge_with_previous(&mut self, index: usize) -> bool {
+        if index == 0 || index >= self.chapters.len() {
+            return false;
+        }
+        let chapter = self.chapters.remove(index);
+        if self.active >= index {
+            self.active -= 1;
         }
+        let previous = &mut self.chapters[index - 1];
+        let mut content = previous.content.trim_end().to_string();
+        for part in [chapter.title.trim(), chapter.content.trim_start()] {
+            if part.is_empty() {
+                continue;
+            }
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(part);
+        }
+        previous.content = content;
+        true
+    }
+
+    /// 在第 `index` 章正文的第 `cursor` 个字符处拆分。
+    ///
+    /// 光标之后的第一行作为新章节标题，其余为正文；新章节沿用原章节所属的卷。
+    /// 光标之后没有文字时不拆分并返回 `false`。
+    pub fn split_at(&mut self, index: usize, cursor: usize) -> bool {
+        let Some(chapter) = self.chapters.get_mut(index) else {
+            return false;
+        };
+        let offset = chapter
+            .content
+            .char_indices()
+            .nth(cursor)
+            .map_or(chapter.content.len(), |(offset, _)| offset);
+        let rest = chapter.content[offset..].trim_start();
+        if rest.is_empty() {
+            return false;
+        }
+        let (title, content) = rest.split_once('\n').unwrap_or((rest, ""));
+        let new_chapter = ChapterDraft {
+            title: title.trim().to_string(),
+            content: content.trim_start_matches('\n').to_string(),
+            volume: chapter.volume.clone(),
+            ..Default::default()
+        };
+        chapter.content.truncate(offset);
+        let trimmed = chapter.content.trim_end().len();
+        chapter.content.truncate(trimmed);
+        self.chapters.insert(index + 1, new_chapter);
+        if self.active > index {
+            self.active += 1;
+        }
+        true
     }
 
     fn refresh(&mut self, input: &ChapterEditorInput<'_>, signature: u64) {
-        match StrategyFactory::create(input.method, input.regex, input.config_path) {
-            Ok(strategy) => match strategy.split(input.text) {
+        let source = clean_whitespace_if(
+            strip_text_header_if(input.text, input.preprocessing.strip_text_header),
+            input.preprocessing.clean_whitespace,
+        );
+        let text = match apply_cleanup_rules(&source, input.preprocessing.cleanup_rules) {
+            Ok(text) => input.preprocessing.punctuation.normalize(&text),
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+        match StrategyFactory::create_with_options(
+            input.method,
+            input.regex,
+            input.config_path,
+            &input.split,
+        ) {
+            Ok(strategy) => match strategy.split(&text) {
                 Ok(chapters) => {
                     self.chapters = chapters;
                     self.error = None;
@@ -232,6 +1039,23 @@ impl ChapterEditorState {
         }
     }
 
+    /// 恢复已保存的章节编辑结果，并视为基于 `signature` 对应的源文本生成。
+    pub fn restore(&mut self, chapters: Vec<ChapterDraft>, signature: u64) {
+        self.chapters = chapters;
+        self.selected.clear();
+        self.use_for_conversion = true;
+        self.stale = false;
+        self.error = None;
+        self.last_refresh_signature = Some(signature);
+    }
+
+    /// 打开编辑器并按当前分章设置重新分章，供审阅后用于转换。
+    pub fn open_for_review(&mut self) {
+        self.open = true;
+        self.use_for_conversion = true;
+        self.refresh_on_show = true;
+    }
+
     pub fn update_stale(&mut self, signature: u64) -> bool {
         let stale = self
             .last_refresh_signature
@@ -241,3 +1065,249 @@ impl ChapterEditorState {
         stale
     }
 }
+
+/// 查找结果的一行：匹配所在行的前后文，匹配部分高亮。
+fn context_job(ui: &Ui, text: &str, range: std::ops::Range<usize>) -> egui::text::LayoutJob {
+    let (before, found, after) = match_context(text, range, 24);
+    let font_id = egui::TextStyle::Small.resolve(ui.style());
+    let color = ui.visuals().text_color();
+    let plain = egui::TextFormat::simple(font_id, color);
+    let mut job = egui::text::LayoutJob::default();
+    job.append(before, 0.0, plain.clone());
+    job.append(
+        found,
+        0.0,
+        egui::TextFormat {
+            background: ui.visuals().warn_fg_color.gamma_multiply(0.4),
+            ..plain.clone()
+        },
+    );
+    job.append(after, 0.0, plain);
+    job
+}
+
+/// 章节标题前的篇幅条：长度按最长章节归一，篇幅异常的章节用警示色标出。
+fn length_bar_ui(
+    ui: &mut Ui,
+    stats: ChapterStats,
+    outlier: Option<OutlierKind>,
+    longest: usize,
+    locale: Locale,
+) {
+    let fraction = if longest == 0 {
+        0.0
+    } else {
+        stats.chars as f32 / longest as f32
+    };
+    let mut bar = egui::ProgressBar::new(fraction)
+        .desired_width(56.0)
+        .desired_height(8.0);
+    let mut hover = t2(locale, Key::ReportLength, stats.chars, stats.words);
+    if let Some(kind) = outlier {
+        let (color, label) = match kind {
+            OutlierKind::Short => (egui::Color32::GRAY, Key::OutlierShort),
+            OutlierKind::Long => (egui::Color32::from_rgb(207, 95, 38), Key::OutlierLong),
+        };
+        bar = bar.fill(color);
+        hover = format!("{hover} · {}", t(locale, label));
+    }
+    ui.add(bar).on_hover_text(hover);
+    ui.add_sized(
+        [96.0, ui.spacing().interact_size.y],
+        egui::Label::new(
+            egui::RichText::new(t2(
+                locale,
+                Key::ChapterLength,
+                stats.chars,
+                stats.reading_minutes(),
+            ))
+            .small(),
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CleanupRule;
+    use crate::punctuation::PunctuationNormalization;
+
+    fn editor(chapters: &[(&str, &str)]) -> ChapterEditorState {
+        ChapterEditorState {
+            chapters: chapters
+                .iter()
+                .map(|(title, content)| ChapterDraft {
+                    title: title.to_string(),
+                    content: content.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn changing_preprocessing_marks_review_stale() {
+        let preview_options = EpubBuildOptions::default();
+        let rules = vec![CleanupRule {
+            find: "广告".to_string(),
+            ..Default::default()
+        }];
+        let input = ChapterEditorInput {
+            text: "第一章\n正文广告",
+            method: ConversionMethod::Regex,
+            regex: "",
+            split: SplitOptions::default(),
+            config_path: None,
+            preprocessing: Preprocessing::default(),
+            preview_options: &preview_options,
+        };
+        let mut state = editor(&[("第一章", "正文广告")]);
+        state.restore(state.chapters.clone(), input.signature());
+        assert!(!state.update_stale(input.signature()));
+
+        let edited = ChapterEditorInput {
+            preprocessing: Preprocessing {
+                cleanup_rules: &rules,
+                ..Preprocessing::default()
+            },
+            ..input
+        };
+        assert!(state.update_stale(edited.signature()));
+
+        let normalized = ChapterEditorInput {
+            preprocessing: Preprocessing {
+                punctuation: PunctuationNormalization::FullWidth,
+                ..Preprocessing::default()
+            },
+            ..input
+        };
+        assert!(state.update_stale(normalized.signature()));
+
+        let cleaned = ChapterEditorInput {
+            preprocessing: Preprocessing {
+                clean_whitespace: true,
+                ..Preprocessing::default()
+            },
+            ..input
+        };
+        assert!(state.update_stale(cleaned.signature()));
+
+        let stripped = ChapterEditorInput {
+            preprocessing: Preprocessing {
+                strip_text_header: true,
+                ..Preprocessing::default()
+            },
+            ..input
+        };
+        assert!(state.update_stale(stripped.signature()));
+    }
+
+    #[test]
+    fn merge_then_split_restores_chapters() {
+        let mut state = editor(&[
+            ("第一章", "甲\n乙"),
+            ("第二章 误判", "丙\n丁"),
+            ("第三章", "戊"),
+        ]);
+        state.chapters[1].volume = Some("上卷".to_string());
+
+        assert!(!state.merge_with_previous(0));
+        assert!(state.merge_with_previous(1));
+        assert_eq!(state.chapters.len(), 2);
+        assert_eq!(state.chapters[0].content, "甲\n乙\n第二章 误判\n丙\n丁");
+
+        // 光标位于“第二章”之前（“甲\n乙\n”之后）。
+        assert!(state.split_at(0, 4));
+        assert_eq!(state.chapters.len(), 3);
+        assert_eq!(state.chapters[0].content, "甲\n乙");
+        assert_eq!(state.chapters[1].title, "第二章 误判");
+        assert_eq!(state.chapters[1].content, "丙\n丁");
+        assert_eq!(state.chapters[2].title, "第三章");
+    }
+
+    #[test]
+    fn title_template_numbers_and_strips_old_numbers() {
+        assert_eq!(
+            apply_title_template("第{n}章 {标题}", 3, "第十二章  重逢"),
+            "第3章 重逢"
+        );
+        assert_eq!(
+            apply_title_template("第{n}章 {标题}", 4, "Chapter 7: Home"),
+            "第4章 Home"
+        );
+        assert_eq!(
+            apply_title_template("{n}. {原标题}", 1, " 12、序 "),
+            "1. 12、序"
+        );
+        assert_eq!(apply_title_template("第{n}章", 2, "随便"), "第2章");
+
+        let mut state = editor(&[("一", ""), ("第9章 二", ""), ("三", "")]);
+        state.selected = [1, 2].into_iter().collect();
+        state.rename.start = 10;
+        assert_eq!(
+            state.rename_preview(),
+            vec![(1, "第10章 二".to_string()), (2, "第11章 三".to_string())]
+        );
+        assert_eq!(state.apply_rename(), 2);
+        assert_eq!(state.chapters[0].title, "一");
+        assert_eq!(state.chapters[2].title, "第11章 三");
+    }
+
+    #[test]
+    fn move_block_keeps_order_and_selection() {
+        let mut state = editor(&[("0", ""), ("1", ""), ("2", ""), ("3", ""), ("4", "")]);
+        let titles = |state: &ChapterEditorState| {
+            state
+                .chapters
+                .iter()
+                .map(|chapter| chapter.title.as_str())
+                .collect::<Vec<_>>()
+                .join("")
+        };
+
+        state.move_block(&BTreeSet::from([1, 3]), 0);
+        assert_eq!(titles(&state), "13024");
+        assert_eq!(state.selected, BTreeSet::from([0, 1]));
+
+        state.move_block(&BTreeSet::from([0, 1]), 99);
+        assert_eq!(titles(&state), "02413");
+        assert_eq!(state.selected, BTreeSet::from([3, 4]));
+
+        state.move_block(&BTreeSet::from([4]), 1);
+        assert_eq!(titles(&state), "03241");
+    }
+
+    #[test]
+    fn active_chapter_follows_structural_edits() {
+        let mut state = editor(&[("0", ""), ("1", "甲\n乙"), ("2", ""), ("3", "")]);
+        let active_title = |state: &ChapterEditorState| state.chapters[state.active].title.clone();
+
+        state.active = 2;
+        state.move_block(&BTreeSet::from([0, 3]), 0);
+        assert_eq!(active_title(&state), "2");
+        state.move_block(&BTreeSet::from([3]), 0);
+        assert_eq!(active_title(&state), "2");
+
+        let mut state = editor(&[("0", ""), ("1", "甲\n乙"), ("2", "")]);
+        state.active = 2;
+        assert!(state.split_at(1, 0));
+        assert_eq!(active_title(&state), "2");
+        assert!(state.merge_with_previous(3));
+        assert_eq!(active_title(&state), "甲");
+    }
+
+    #[test]
+    fn split_at_end_or_out_of_range_is_rejected() {
+        let mut state = editor(&[("第一章", "甲乙\n")]);
+        assert!(!state.split_at(0, 2));
+        assert!(!state.split_at(0, 100));
+        assert!(!state.split_at(3, 0));
+        assert_eq!(state.chapters.len(), 1);
+
+        assert!(state.split_at(0, 1));
+        assert_eq!(state.chapters[0].content, "甲");
+        assert_eq!(state.chapters[1].title, "乙");
+        assert_eq!(state.chapters[1].content, "");
+    }
+}
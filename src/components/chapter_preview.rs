@@ -0,0 +1,670 @@
+//! 章节排版预览。
+//!
+//! 复用导出时的 `preview_chapter` 生成章节 XHTML 与样式表，再把标题、段落等块级元素
+//! 按样式表中的字号、行高、缩进、段间距与首字下沉近似绘制，用于导出前检查版式。
+//! 预览只解析影响排版的少数声明，阅读器中的实际效果仍可能略有不同。
+
+use egui::text::LayoutJob;
+use egui::{Color32, Context, FontId, Id, Modal, ScrollArea, TextFormat, Ui};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::epubworker::{BuildError, EpubBuildOptions, preview_chapter};
+use crate::{ChapterDraft, Key, Locale, TextStyle, t, t2};
+
+/// 预览页面的纸张底色。
+pub(crate) const PAGE_FILL: Color32 = Color32::from_rgb(251, 248, 242);
+/// 章节序号标签与装饰线的颜色，与样式表中的 `#6b5b4b` 一致。
+pub(crate) const ACCENT: Color32 = Color32::from_rgb(107, 91, 75);
+/// 首字下沉的颜色，与样式表中的 `#5a4a3b` 一致。
+pub(crate) const DROP_CAP_COLOR: Color32 = Color32::from_rgb(90, 74, 59);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Heading,
+    /// 章节序号标签等标题上方的小字。
+    Label,
+    /// 标题上下的装饰线。
+    Ornament,
+    Paragraph,
+}
+
+/// 章节正文中的一个块级元素。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewBlock {
+    pub kind: BlockKind,
+    pub text: String,
+    pub class: String,
+    /// 行内样式中的首行缩进（em）。
+    pub indent: Option<f32>,
+}
+
+impl PreviewBlock {
+    pub(crate) fn has_class(&self, class: &str) -> bool {
+        self.class.split_whitespace().any(|name| name == class)
+    }
+}
+
+/// 从样式表中解析出的正文版式。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewTypography {
+    pub font_size: f32,
+    pub line_height: f32,
+    pub paragraph_spacing: f32,
+    pub text_indent: f32,
+    pub color: Color32,
+    pub drop_cap: bool,
+    /// 居中版式：段落居中且不缩进。
+    pub centered: bool,
+}
+
+/// 解析后的章节预览。
+#[derive(Debug, Clone)]
+pub struct RenderedChapter {
+    pub body_class: String,
+    pub blocks: Vec<PreviewBlock>,
+    pub typography: PreviewTypography,
+}
+
+/// 渲染第 `index` 章并解析为可绘制的预览。
+pub fn render_preview(
+    chapter: &ChapterDraft,
+    index: usize,
+    options: &EpubBuildOptions,
+) -> Result<RenderedChapter, BuildError> {
+    let preview = preview_chapter(chapter, index, options)?;
+    let (body_class, chapter_css, blocks) = parse_chapter(&preview.xhtml);
+    // 章节专属样式写在 `<head>` 中，排在全书样式表之后。
+    let css = format!("{}\n{}", preview.stylesheet, chapter_css);
+    let typography = PreviewTypography::resolve(&css, &body_class, &options.style);
+    Ok(RenderedChapter {
+        body_class,
+        blocks,
+        typography,
+    })
+}
+
+/// 解析章节 XHTML，返回 body class、`<head>` 内的章节样式与块级元素。
+///
+/// `chapter-title-hidden` 标题与图片不参与预览。
+pub fn parse_chapter(xhtml: &str) -> (String, String, Vec<PreviewBlock>) {
+    let mut reader = Reader::from_str(xhtml);
+    let mut body_class = String::new();
+    let mut chapter_css = String::new();
+    let mut blocks = Vec::new();
+    let mut current: Option<(PreviewBlock, usize)> = None;
+    let mut in_style = false;
+    let mut hidden_depth: Option<usize> = None;
+    let mut depth = 0usize;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => {
+                depth += 1;
+                let name = start.local_name();
+                let class = attribute(&start, "class");
+                if name.as_ref() == b"style" {
+                    in_style = true;
+                } else if name.as_ref() == b"body" {
+                    body_class = class;
+                } else if hidden_depth.is_none()
+                    && class
+                        .split_whitespace()
+                        .any(|c| c == "chapter-title-hidden")
+                {
+                    hidden_depth = Some(depth);
+                } else if current.is_none()
+                    && let Some(kind) = block_kind(name.as_ref(), &class)
+                {
+                    let indent = text_indent(&attribute(&start, "style"));
+                    let block = PreviewBlock {
+                        kind,
+                        text: String::new(),
+                        class,
+                        indent,
+                    };
+                    current = Some((block, depth));
+                }
+            }
+            Ok(Event::Empty(start)) => {
+                let name = start.local_name();
+                if name.as_ref() == b"br" {
+                    if let Some((block, _)) = current.as_mut() {
+                        block.text.push('\n');
+                    }
+                } else if current.is_none()
+                    && hidden_depth.is_none()
+                    && block_kind(name.as_ref(), &attribute(&start, "class"))
+                        == Some(BlockKind::Ornament)
+                {
+                    blocks.push(PreviewBlock {
+                        kind: BlockKind::Ornament,
+                        text: String::new(),
+                        class: attribute(&start, "class"),
+                        indent: None,
+                    });
+                }
+            }
+            Ok(Event::End(end)) => {
+                if end.local_name().as_ref() == b"style" {
+                    in_style = false;
+                }
+                if hidden_depth == Some(depth) {
+                    hidden_depth = None;
+                } else if current
+                    .as_ref()
+                    .is_some_and(|(_, start_depth)| *start_depth == depth)
+                    && let Some((mut block, _)) = current.take()
+                {
+                    block.text = block.text.trim().to_string();
+                    if !block.text.is_empty() || block.kind == BlockKind::Ornament {
+                        blocks.push(block);
+                    }
+                }
+                depth = depth.saturating_sub(1);
+            }
+            Ok(Event::Text(text)) => {
+                let text = text.decode().unwrap_or_default();
+                if in_style {
+                    chapter_css.push_str(&text);
+                } else if hidden_depth.is_none()
+                    && let Some((block, _)) = current.as_mut()
+                {
+                    block.text.push_str(&text);
+                }
+            }
+            Ok(Event::CData(data)) => {
+                if in_style {
+                    chapter_css.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            Ok(Event::GeneralRef(reference)) => {
+                if hidden_depth.is_none()
+                    && let Some((block, _)) = current.as_mut()
+                {
+                    let resolved = if reference.is_char_ref() {
+                        reference.resolve_char_ref().ok().flatten()
+                    } else {
+                        let name: &[u8] = reference.as_ref();
+                        match name {
+                            b"amp" => Some('&'),
+                            b"lt" => Some('<'),
+                            b"gt" => Some('>'),
+                            b"quot" => Some('"'),
+                            b"apos" => Some('\''),
+                            _ => None,
+                        }
+                    };
+                    block.text.extend(resolved);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    // 样式可能包在 `/*<![CDATA[*/ ... /*]]>*/` 中，去掉残留的注释标记。
+    let chapter_css = chapter_css.replace("/*", "").replace("*/", "");
+    (body_class, chapter_css, blocks)
+}
+
+fn attribute(start: &BytesStart<'_>, name: &str) -> String {
+    start
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+fn block_kind(name: &[u8], class: &str) -> Option<BlockKind> {
+    let has = |wanted: &str| class.split_whitespace().any(|c| c == wanted);
+    if has("chapter-ornament") {
+        Some(BlockKind::Ornament)
+    } else if has("chapter-label") || has("nt") || has("et") {
+        Some(BlockKind::Label)
+    } else if has("ct") || matches!(name, b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6") {
+        Some(BlockKind::Heading)
+    } else if name == b"p" {
+        Some(BlockKind::Paragraph)
+    } else {
+        None
+    }
+}
+
+fn text_indent(style: &str) -> Option<f32> {
+    declaration(style, "text-indent").and_then(|value| parse_length(&value, 1.0))
+}
+
+/// 在声明块中查找属性值，去掉 `!important`；同名声明取最后一个。
+fn declaration(block: &str, property: &str) -> Option<String> {
+    block
+        .rsplit(';')
+        .filter_map(|item| item.split_once(':'))
+        .find(|(name, _)| name.trim() == property)
+        .map(|(_, value)| value.replace("!important", "").trim().to_string())
+}
+
+/// 把 `em` 或 `px` 长度换算为 em；`font_size` 为 1em 对应的像素数。
+fn parse_length(value: &str, font_size: f32) -> Option<f32> {
+    let value = value.trim();
+    if let Some(number) = value.strip_suffix("em") {
+        number.trim().parse().ok()
+    } else if let Some(number) = value.strip_suffix("px") {
+        number.trim().parse::<f32>().ok().map(|px| px / font_size)
+    } else {
+        value.parse().ok()
+    }
+}
+
+/// 按规则顺序查找选择器的属性值，后出现的规则覆盖先出现的。
+///
+/// 只比较完整的简单选择器；`body.<class> 选择器` 形式的规则在 body 带有该 class 时也算匹配，
+/// 以覆盖章节专属样式。`@` 规则整体跳过。
+pub fn css_value(
+    css: &str,
+    selector: &str,
+    property: &str,
+    body_classes: &[&str],
+) -> Option<String> {
+    let mut value = None;
+    let mut rest = strip_comments(css);
+    let applies = |candidate: &str| {
+        let candidate = candidate.trim();
+        if candidate == selector {
+            return true;
+        }
+        candidate
+            .strip_prefix("body.")
+            .and_then(|scoped| scoped.split_once(' '))
+            .is_some_and(|(class, target)| {
+                target.trim() == selector && body_classes.contains(&class)
+            })
+    };
+    while let Some(open) = rest.find('{') {
+        let prelude = rest[..open].trim().to_string();
+        let Some(close) = matching_brace(&rest[open..]).map(|offset| open + offset) else {
+            break;
+        };
+        if !prelude.starts_with('@') && prelude.split(',').any(applies) {
+            value = declaration(&rest[open + 1..close], property).or(value);
+        }
+        rest = rest[close + 1..].to_string();
+    }
+    value
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => return out,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn matching_brace(block: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (offset, ch) in block.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(offset);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+impl PreviewTypography {
+    /// 从样式表中读取 `p` 规则与首字下沉规则；缺失的值取 `style` 中的设置。
+    pub fn resolve(css: &str, body_class: &str, style: &TextStyle) -> Self {
+        let classes: Vec<&str> = body_class.split_whitespace().collect();
+        let value = |selector: &str, property: &str| css_value(css, selector, property, &classes);
+        let font_size = value("p", "font-size")
+            .and_then(|size| size.trim().strip_suffix("px")?.trim().parse().ok())
+            .unwrap_or(style.font_size);
+        let line_height = value("p", "line-height")
+            .and_then(|height| parse_length(&height, font_size))
+            .unwrap_or(style.line_height);
+        let paragraph_spacing = value("p", "margin")
+            .and_then(|margin| {
+                let parts: Vec<&str> = margin.split_whitespace().collect();
+                // 简写按上、右、下、左；竖排时段间距在左侧外边距。
+                let index = match (parts.len(), style.vertical) {
+                    (4, true) => 3,
+                    (3 | 4, false) => 2,
+                    _ => 0,
+                };
+                parse_length(parts.get(index)?, font_size)
+            })
+            .unwrap_or(style.paragraph_spacing);
+        let text_indent = value("p", "text-indent")
+            .and_then(|indent| parse_length(&indent, font_size))
+            .unwrap_or(style.text_indent);
+        let color = value("p", "color")
+            .and_then(|color| Color32::from_hex(color.trim()).ok())
+            .unwrap_or(style.font_color);
+        let centered = classes.contains(&"chapter-centered");
+        let drop_cap = !centered
+            && value(".chapter-paragraph-first::first-letter", "float").as_deref() == Some("left");
+        Self {
+            font_size,
+            line_height,
+            paragraph_spacing,
+            text_indent,
+            color,
+            drop_cap,
+            centered,
+        }
+    }
+}
+
+/// 章节预览窗口的状态。
+#[derive(Default)]
+pub struct ChapterPreviewState {
+    /// 正在预览的章节序号；为 `None` 时不显示预览窗口。
+    pub index: Option<usize>,
+    rendered: Option<(usize, Result<RenderedChapter, String>)>,
+}
+
+impl ChapterPreviewState {
+    pub fn open(&mut self, index: usize) {
+        self.index = Some(index);
+        self.rendered = None;
+    }
+
+    /// 以模态窗口显示预览；需在章节编辑器之后调用，使其位于编辑器之上。
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        chapters: &[ChapterDraft],
+        options: &EpubBuildOptions,
+        locale: Locale,
+    ) {
+        let Some(index) = self.index.filter(|index| *index < chapters.len()) else {
+            self.index = None;
+            return;
+        };
+        if self.rendered.as_ref().map(|(cached, _)| *cached) != Some(index) {
+            let rendered =
+                render_preview(&chapters[index], index, options).map_err(|err| err.to_string());
+            self.rendered = Some((index, rendered));
+        }
+
+        let screen = ctx.content_rect();
+        let size = egui::vec2(
+            620.0_f32.min(screen.width() * 0.9),
+            640.0_f32.min(screen.height() * 0.9),
+        );
+        let mut target = Some(index);
+        let modal = Modal::new(Id::new("chapter_preview_modal")).show(ctx, |ui| {
+            ui.set_min_size(size);
+            ui.set_max_size(size);
+            ui.horizontal(|ui| {
+                ui.heading(t(locale, Key::ChapterPreviewTitle));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(t(locale, Key::Close)).clicked() {
+                        target = None;
+                    }
+                    if ui
+                        .add_enabled(
+                            index + 1 < chapters.len(),
+                            egui::Button::new(t(locale, Key::NextChapter)),
+                        )
+                        .clicked()
+                    {
+                        target = Some(index + 1);
+                    }
+                    if ui
+                        .add_enabled(
+                            index > 0,
+                            egui::Button::new(t(locale, Key::PreviousChapter)),
+                        )
+                        .clicked()
+                    {
+                        target = Some(index - 1);
+                    }
+                });
+            });
+            ui.label(t2(
+                locale,
+                Key::ChapterIndex,
+                index + 1,
+                &chapters[index].title,
+            ));
+            ui.label(egui::RichText::new(t(locale, Key::ChapterPreviewHint)).size(11.0));
+            ui.separator();
+
+            match self.rendered.as_ref().map(|(_, rendered)| rendered) {
+                Some(Ok(rendered)) => page_ui(ui, rendered, true),
+                Some(Err(err)) => {
+                    ui.label(egui::RichText::new(err).color(Color32::RED));
+                }
+                None => {}
+            }
+        });
+
+        if modal.should_close() {
+            target = None;
+        }
+        if target != self.index {
+            self.index = target;
+        }
+    }
+}
+
+/// 格式与字体面板中的样章预览；版式设置或样章文字变化时才重新渲染。
+#[derive(Default)]
+pub struct StyleSampleState {
+    key: String,
+    rendered: Option<Result<RenderedChapter, String>>,
+}
+
+impl StyleSampleState {
+    pub fn show(&mut self, ui: &mut Ui, sample: &ChapterDraft, options: &EpubBuildOptions) {
+        let key = format!(
+            "{:?}|{:?}|{}|{}",
+            options.style, options.epub_version, sample.title, sample.content
+        );
+        if self.rendered.is_none() || self.key != key {
+            self.rendered = Some(render_preview(sample, 0, options).map_err(|err| err.to_string()));
+            self.key = key;
+        }
+        match self.rendered.as_ref() {
+            Some(Ok(rendered)) => page_ui(ui, rendered, false),
+            Some(Err(err)) => {
+                ui.label(egui::RichText::new(err).color(Color32::RED));
+            }
+            None => {}
+        }
+    }
+}
+
+/// 在纸色页面中绘制预览；`scrolling` 为真时页面填满剩余高度并可滚动。
+fn page_ui(ui: &mut Ui, rendered: &RenderedChapter, scrolling: bool) {
+    egui::Frame::new()
+        .fill(PAGE_FILL)
+        .inner_margin(egui::Margin::symmetric(24, 16))
+        .show(ui, |ui| {
+            if scrolling {
+                ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| blocks_ui(ui, rendered));
+            } else {
+                ui.set_width(ui.available_width());
+                blocks_ui(ui, rendered);
+            }
+        });
+}
+
+/// 按解析出的版式逐块绘制章节。
+fn blocks_ui(ui: &mut Ui, rendered: &RenderedChapter) {
+    let typo = rendered.typography;
+    let size = typo.font_size;
+    ui.spacing_mut().item_spacing.y = 0.0;
+    let width = ui.available_width();
+    for block in &rendered.blocks {
+        match block.kind {
+            BlockKind::Ornament => {
+                ui.add_space(size * 0.6);
+                ui.add(egui::Separator::default().horizontal().shrink(width * 0.23));
+                ui.add_space(size * 0.6);
+            }
+            BlockKind::Label => {
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        egui::RichText::new(&block.text)
+                            .size(size * 0.85)
+                            .color(ACCENT),
+                    );
+                });
+                ui.add_space(size * 0.3);
+            }
+            BlockKind::Heading => {
+                ui.add_space(size * 0.4);
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        egui::RichText::new(&block.text)
+                            .size(size * 1.5)
+                            .color(typo.color)
+                            .strong(),
+                    );
+                });
+                ui.add_space(size * 0.8);
+            }
+            BlockKind::Paragraph => {
+                let format = TextFormat {
+                    font_id: FontId::proportional(size),
+                    color: typo.color,
+                    line_height: Some(size * typo.line_height),
+                    ..Default::default()
+                };
+                let indent = if typo.centered {
+                    0.0
+                } else {
+                    block.indent.unwrap_or(typo.text_indent) * size
+                };
+                let mut job = LayoutJob::default();
+                let mut text = block.text.as_str();
+                if typo.drop_cap
+                    && block.has_class("chapter-paragraph-first")
+                    && let Some(first) = text.chars().next()
+                {
+                    let (cap, rest) = text.split_at(first.len_utf8());
+                    job.append(
+                        cap,
+                        0.0,
+                        TextFormat {
+                            font_id: FontId::proportional(size * 2.4),
+                            color: DROP_CAP_COLOR,
+                            ..format.clone()
+                        },
+                    );
+                    text = rest;
+                    job.append(text, 0.0, format);
+                } else {
+                    job.append(text, indent, format);
+                }
+                job.wrap.max_width = width;
+                if typo.centered {
+                    ui.vertical_centered(|ui| ui.label(job));
+                } else {
+                    ui.label(job);
+                }
+                ui.add_space(size * typo.paragraph_spacing);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(style: TextStyle) -> EpubBuildOptions {
+        EpubBuildOptions {
+            style,
+            safe_mode: true,
+            ..Default::default()
+        }
+    }
+
+    fn chapter(title: &str, content: &str) -> ChapterDraft {
+        ChapterDraft {
+            title: title.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_heading_and_paragraphs_with_indent() {
+        let rendered = render_preview(
+            &chapter("第一章 开始", "甲 & 乙\n\n第二段"),
+            0,
+            &options(TextStyle::default()),
+        )
+        .unwrap();
+        let kinds: Vec<BlockKind> = rendered.blocks.iter().map(|block| block.kind).collect();
+        assert!(kinds.contains(&BlockKind::Heading));
+        let paragraphs: Vec<&PreviewBlock> = rendered
+            .blocks
+            .iter()
+            .filter(|block| block.kind == BlockKind::Paragraph)
+            .collect();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text, "甲 & 乙");
+        assert!(paragraphs[0].has_class("chapter-paragraph-first"));
+        assert_eq!(paragraphs[0].indent, Some(0.0));
+        assert_eq!(paragraphs[1].indent, Some(2.0));
+        assert!(rendered.typography.drop_cap);
+    }
+
+    #[test]
+    fn typography_follows_settings_and_custom_css() {
+        let style = TextStyle {
+            line_height: 1.8,
+            paragraph_spacing: 0.5,
+            font_size: 18.0,
+            custom_css: "p { line-height: 2.2em; }".to_string(),
+            ..Default::default()
+        };
+        let rendered = render_preview(&chapter("序", "正文"), 0, &options(style)).unwrap();
+        let typo = rendered.typography;
+        assert_eq!(typo.font_size, 18.0);
+        assert_eq!(typo.line_height, 2.2);
+        assert_eq!(typo.paragraph_spacing, 0.5);
+
+        let vertical = TextStyle {
+            vertical: true,
+            ..Default::default()
+        };
+        let rendered = render_preview(&chapter("序", "正文"), 0, &options(vertical)).unwrap();
+        assert!(!rendered.typography.drop_cap);
+    }
+
+    #[test]
+    fn chapter_css_applies_through_scoped_selectors() {
+        let draft = ChapterDraft {
+            custom_css: "p { text-indent: 4em; color: #112233; }".to_string(),
+            ..chapter("序", "正文")
+        };
+        let rendered = render_preview(&draft, 2, &options(TextStyle::default())).unwrap();
+        assert!(rendered.body_class.contains("chapter-css-0003"));
+        assert_eq!(rendered.typography.text_indent, 4.0);
+        assert_eq!(
+            rendered.typography.color,
+            Color32::from_rgb(0x11, 0x22, 0x33)
+        );
+    }
+}
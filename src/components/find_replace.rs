@@ -0,0 +1,303 @@
+//! 文本编辑器与章节编辑器的查找与替换。
+//!
+//! 字面模式会转义查询后交给正则引擎，两种模式共用同一套匹配与替换逻辑；
+//! 空匹配（如 `^`、`a*`）不计入结果，也不会被替换。
+
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::ChapterDraft;
+
+#[derive(Debug, Default)]
+pub struct FindReplaceState {
+    pub open: bool,
+    pub query: String,
+    pub replacement: String,
+    pub use_regex: bool,
+    pub match_case: bool,
+    /// 当前匹配在结果中的序号。
+    pub current: usize,
+    /// 下一帧把当前匹配选中并滚动到可见位置。
+    pub reveal: bool,
+    /// 最近一次“全部替换”的替换次数。
+    pub replaced: Option<usize>,
+}
+
+impl FindReplaceState {
+    /// 编译查询；查询为空时返回 `Ok(None)`。
+    pub fn pattern(&self) -> Result<Option<Regex>, regex::Error> {
+        if self.query.is_empty() {
+            return Ok(None);
+        }
+        let source = if self.use_regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        RegexBuilder::new(&source)
+            .case_insensitive(!self.match_case)
+            .build()
+            .map(Some)
+    }
+
+    /// 跳到下一个（`forward`）或上一个匹配，首尾循环。
+    pub fn step(&mut self, count: usize, forward: bool) {
+        if count == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.reveal = true;
+    }
+}
+
+/// 所有非空匹配的字节范围。
+pub fn find_matches(pattern: &Regex, text: &str) -> Vec<Range<usize>> {
+    pattern
+        .find_iter(text)
+        .filter(|found| !found.is_empty())
+        .map(|found| found.range())
+        .collect()
+}
+
+/// 替换从 `start` 开始的那个匹配，返回替换后文本的字节范围。
+///
+/// 正则模式下替换文本支持 `$1`、`${name}` 等捕获组引用。
+pub fn replace_one(
+    pattern: &Regex,
+    text: &mut String,
+    start: usize,
+    replacement: &str,
+    use_regex: bool,
+) -> Option<Range<usize>> {
+    let captures = pattern.captures_at(text, start)?;
+    let found = captures.get(0)?;
+    if found.start() != start || found.is_empty() {
+        return None;
+    }
+    let range = found.range();
+    let mut replaced = String::new();
+    if use_regex {
+        captures.expand(replacement, &mut replaced);
+    } else {
+        replaced.push_str(replacement);
+    }
+    text.replace_range(range.clone(), &replaced);
+    Some(range.start..range.start + replaced.len())
+}
+
+/// 替换全部非空匹配，返回替换次数。
+pub fn replace_all(
+    pattern: &Regex,
+    text: &mut String,
+    replacement: &str,
+    use_regex: bool,
+) -> usize {
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut count = 0;
+    for captures in pattern.captures_iter(text) {
+        let Some(found) = captures.get(0).filter(|found| !found.is_empty()) else {
+            continue;
+        };
+        output.push_str(&text[last..found.start()]);
+        if use_regex {
+            captures.expand(replacement, &mut output);
+        } else {
+            output.push_str(replacement);
+        }
+        last = found.end();
+        count += 1;
+    }
+    if count > 0 {
+        output.push_str(&text[last..]);
+        *text = output;
+    }
+    count
+}
+
+/// 跨章节查找的一处匹配：章节序号与该章正文中的字节范围。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterMatch {
+    pub chapter: usize,
+    pub range: Range<usize>,
+}
+
+/// 各章正文中的全部非空匹配，按章节与位置排序。
+pub fn find_in_chapters(pattern: &Regex, chapters: &[ChapterDraft]) -> Vec<ChapterMatch> {
+    chapters
+        .iter()
+        .enumerate()
+        .flat_map(|(chapter, draft)| {
+            find_matches(pattern, &draft.content)
+                .into_iter()
+                .map(move |range| ChapterMatch { chapter, range })
+        })
+        .collect()
+}
+
+/// 只替换 `matches` 列出的匹配，返回替换次数。
+///
+/// 同一章内从后往前替换，前面匹配的位置不受影响；正文已改动、位置上不再是匹配的条目会被跳过。
+pub fn replace_in_chapters(
+    pattern: &Regex,
+    chapters: &mut [ChapterDraft],
+    matches: &[ChapterMatch],
+    replacement: &str,
+    use_regex: bool,
+) -> usize {
+    let mut targets: Vec<&ChapterMatch> = matches.iter().collect();
+    targets.sort_by_key(|found| (found.chapter, std::cmp::Reverse(found.range.start)));
+    targets
+        .into_iter()
+        .filter(|found| {
+            chapters.get_mut(found.chapter).is_some_and(|draft| {
+                replace_one(
+                    pattern,
+                    &mut draft.content,
+                    found.range.start,
+                    replacement,
+                    use_regex,
+                )
+                .is_some()
+            })
+        })
+        .count()
+}
+
+/// 匹配所在行的上下文：匹配前后各最多 `radius` 个字符，不跨行。
+pub fn match_context(text: &str, range: Range<usize>, radius: usize) -> (&str, &str, &str) {
+    let line_start = text[..range.start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = text[range.end..]
+        .find('\n')
+        .map_or(text.len(), |index| range.end + index);
+    let before = &text[line_start..range.start];
+    let before_start = before
+        .char_indices()
+        .rev()
+        .nth(radius.saturating_sub(1))
+        .map_or(0, |(index, _)| index);
+    let after = &text[range.end..line_end];
+    let after_end = after
+        .char_indices()
+        .nth(radius)
+        .map_or(after.len(), |(index, _)| index);
+    (
+        &before[before_start..],
+        &text[range.clone()],
+        &after[..after_end],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(query: &str, use_regex: bool) -> FindReplaceState {
+        FindReplaceState {
+            query: query.to_string(),
+            use_regex,
+            match_case: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn literal_mode_escapes_and_respects_case() {
+        let pattern = state("a.b", false).pattern().unwrap().unwrap();
+        assert_eq!(find_matches(&pattern, "a.b axb A.B"), vec![0..3]);
+
+        let mut insensitive = state("a.b", false);
+        insensitive.match_case = false;
+        let pattern = insensitive.pattern().unwrap().unwrap();
+        assert_eq!(find_matches(&pattern, "a.b axb A.B").len(), 2);
+
+        assert!(state("", false).pattern().unwrap().is_none());
+        assert!(state("(", true).pattern().is_err());
+    }
+
+    #[test]
+    fn regex_replace_expands_groups_and_skips_empty_matches() {
+        let pattern = state(r"第(\d+)章", true).pattern().unwrap().unwrap();
+        let mut text = "第1章 开始\n第2章 继续".to_string();
+        let matches = find_matches(&pattern, &text);
+        assert_eq!(matches.len(), 2);
+
+        let replaced = replace_one(&pattern, &mut text, matches[1].start, "Chapter $1", true);
+        assert_eq!(text, "第1章 开始\nChapter 2 继续");
+        assert_eq!(&text[replaced.unwrap()], "Chapter 2");
+
+        assert_eq!(replace_all(&pattern, &mut text, "[$1]", true), 1);
+        assert_eq!(text, "[1] 开始\nChapter 2 继续");
+
+        let empty = state("x*", true).pattern().unwrap().unwrap();
+        assert_eq!(replace_all(&empty, &mut text, "y", true), 0);
+        assert_eq!(text, "[1] 开始\nChapter 2 继续");
+    }
+
+    #[test]
+    fn literal_replace_keeps_dollar_signs() {
+        let pattern = state("价格", false).pattern().unwrap().unwrap();
+        let mut text = "价格 价格".to_string();
+        assert_eq!(replace_all(&pattern, &mut text, "$1", false), 2);
+        assert_eq!(text, "$1 $1");
+    }
+
+    #[test]
+    fn replaces_only_selected_matches_across_chapters() {
+        let chapter = |content: &str| ChapterDraft {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let mut chapters = vec![chapter("雾起。雾散。"), chapter("无"), chapter("浓雾")];
+        let pattern = state("雾", false).pattern().unwrap().unwrap();
+        let matches = find_in_chapters(&pattern, &chapters);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|found| found.chapter)
+                .collect::<Vec<_>>(),
+            [0, 0, 2]
+        );
+
+        // 跳过第一章的第一处。
+        let replaced = replace_in_chapters(&pattern, &mut chapters, &matches[1..], "霾", false);
+        assert_eq!(replaced, 2);
+        assert_eq!(chapters[0].content, "雾起。霾散。");
+        assert_eq!(chapters[2].content, "浓霾");
+        // 位置上已不是匹配时跳过。
+        assert_eq!(
+            replace_in_chapters(&pattern, &mut chapters, &matches[2..], "霾", false),
+            0
+        );
+    }
+
+    #[test]
+    fn match_context_stays_on_the_line() {
+        let text = "第一行\n前面的一些文字雾起后面的文字\n下一行";
+        let start = text.find('雾').unwrap();
+        let range = start..start + '雾'.len_utf8();
+        assert_eq!(
+            match_context(text, range.clone(), 3),
+            ("些文字", "雾", "起后面")
+        );
+        assert_eq!(
+            match_context(text, range, 20),
+            ("前面的一些文字", "雾", "起后面的文字")
+        );
+    }
+
+    #[test]
+    fn step_wraps_around() {
+        let mut find = state("a", false);
+        find.step(3, false);
+        assert_eq!(find.current, 2);
+        find.step(3, true);
+        assert_eq!(find.current, 0);
+        assert!(find.reveal);
+    }
+}
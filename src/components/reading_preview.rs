@@ -0,0 +1,533 @@
+//! 预览阅读：把章节按导出版式排成一页一页的书页，导出前可以像在阅读器里一样翻看。
+//!
+//! 每章从新的一页开始，段落在页尾按行拆到下一页。排版复用章节预览的解析结果，字号、
+//! 行高、缩进、段间距与首字下沉都与 [`render_preview`] 一致；只在打开的章节上排版，
+//! 整本书很长时也不会一次渲染全部章节。
+
+use std::sync::Arc;
+
+use egui::text::LayoutJob;
+use egui::{Align, Color32, Context, FontId, Galley, Id, Key as KeyCode, Modal, Pos2, Rect, Ui};
+
+use crate::components::chapter_preview::{
+    ACCENT, BlockKind, DROP_CAP_COLOR, PAGE_FILL, RenderedChapter, render_preview,
+};
+use crate::epubworker::EpubBuildOptions;
+use crate::{ChapterDraft, Key, Locale, t, t2};
+
+/// 页面的宽高比，接近常见阅读器的竖屏比例。
+const PAGE_ASPECT: f32 = 0.68;
+/// 页面四周的留白。
+const PAGE_MARGIN: egui::Vec2 = egui::vec2(28.0, 30.0);
+
+/// 已折行的一块内容在页面上的高度信息，用于分页。
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMetrics {
+    /// 各行相对块顶部的上下边界。
+    pub rows: Vec<(f32, f32)>,
+    pub space_before: f32,
+    pub space_after: f32,
+}
+
+/// 页面上的一段内容：第 `block` 块中 `rows` 范围内的行，从页面内容区的 `top` 处开始绘制。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSlice {
+    pub block: usize,
+    pub rows: std::ops::Range<usize>,
+    pub top: f32,
+}
+
+/// 把各块按行排入高 `height` 的页面；页首不保留段前空白，单行高于页面时单独占一页。
+pub fn paginate(blocks: &[BlockMetrics], height: f32) -> Vec<Vec<PageSlice>> {
+    let mut pages = vec![Vec::new()];
+    let mut y = 0.0_f32;
+    for (index, block) in blocks.iter().enumerate() {
+        if y > 0.0 {
+            y += block.space_before;
+        }
+        let mut row = 0;
+        while row < block.rows.len() {
+            let start = block.rows[row].0;
+            let mut end = row;
+            while end < block.rows.len() && y + block.rows[end].1 - start <= height {
+                end += 1;
+            }
+            if end == row {
+                if y > 0.0 {
+                    pages.push(Vec::new());
+                    y = 0.0;
+                    continue;
+                }
+                end = row + 1;
+            }
+            pages
+                .last_mut()
+                .expect("at least one page")
+                .push(PageSlice {
+                    block: index,
+                    rows: row..end,
+                    top: y,
+                });
+            y += block.rows[end - 1].1 - start;
+            row = end;
+            if row < block.rows.len() {
+                pages.push(Vec::new());
+                y = 0.0;
+            }
+        }
+        y += block.space_after;
+    }
+    pages
+}
+
+/// 排好版的一块内容。
+enum LaidContent {
+    /// 文字；`centered` 时以 x = 0 为中线排版。
+    Text { galley: Arc<Galley>, centered: bool },
+    /// 标题上下的装饰线，占内容区宽度的一部分。
+    Rule,
+}
+
+/// 一章排好的页面。
+struct ChapterPages {
+    chapter: usize,
+    size: egui::Vec2,
+    blocks: Vec<LaidContent>,
+    metrics: Vec<BlockMetrics>,
+    pages: Vec<Vec<PageSlice>>,
+}
+
+/// 预览阅读窗口的状态。
+#[derive(Default)]
+pub struct ReadingPreviewState {
+    pub open: bool,
+    chapters: Vec<ChapterDraft>,
+    options: EpubBuildOptions,
+    error: Option<String>,
+    /// 当前章节与章内页码。
+    chapter: usize,
+    page: usize,
+    rendered: Option<(usize, Result<RenderedChapter, String>)>,
+    layout: Option<ChapterPages>,
+}
+
+impl ReadingPreviewState {
+    /// 从第一章第一页开始预览；`chapters` 出错时在窗口中显示错误。
+    pub fn open(&mut self, chapters: Result<Vec<ChapterDraft>, String>, options: EpubBuildOptions) {
+        *self = Self {
+            open: true,
+            options,
+            ..Default::default()
+        };
+        match chapters {
+            Ok(chapters) => self.chapters = chapters,
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, locale: Locale) {
+        if !self.open {
+            return;
+        }
+        let screen = ctx.content_rect();
+        // 标题、章节选择与翻页按钮约占 150 像素，其余高度留给页面。
+        let page_height = (720.0_f32.min(screen.height() * 0.9) - 150.0).max(240.0);
+        let page_size = egui::vec2(
+            (page_height * PAGE_ASPECT).min(screen.width() * 0.9),
+            page_height,
+        );
+        let mut close = false;
+
+        let modal = Modal::new(Id::new("reading_preview_modal")).show(ctx, |ui| {
+            ui.set_width(page_size.x);
+            ui.horizontal(|ui| {
+                ui.heading(t(locale, Key::ReadingPreview));
+                ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                    if ui.button(t(locale, Key::Close)).clicked() {
+                        close = true;
+                    }
+                });
+            });
+            ui.label(egui::RichText::new(t(locale, Key::ReadingPreviewHint)).size(11.0));
+            ui.separator();
+
+            if let Some(err) = &self.error {
+                ui.label(egui::RichText::new(err).color(Color32::RED));
+                return;
+            }
+            if self.chapters.is_empty() {
+                ui.label(t(locale, Key::NoChapters));
+                return;
+            }
+            self.chapter_picker_ui(ui, locale);
+            ui.add_space(6.0);
+            self.page_ui(ui, page_size, locale);
+            ui.add_space(6.0);
+            self.navigation_ui(ui, locale);
+        });
+
+        if close || modal.should_close() {
+            self.open = false;
+        }
+    }
+
+    fn chapter_picker_ui(&mut self, ui: &mut Ui, locale: Locale) {
+        let mut chapter = self.chapter;
+        egui::ComboBox::from_id_salt("reading_preview_chapter")
+            .width(ui.available_width())
+            .selected_text(t2(
+                locale,
+                Key::ChapterIndex,
+                chapter + 1,
+                &self.chapters[chapter].title,
+            ))
+            .show_ui(ui, |ui| {
+                for (index, draft) in self.chapters.iter().enumerate() {
+                    ui.selectable_value(
+                        &mut chapter,
+                        index,
+                        t2(locale, Key::ChapterIndex, index + 1, &draft.title),
+                    );
+                }
+            });
+        if chapter != self.chapter {
+            self.chapter = chapter;
+            self.page = 0;
+        }
+    }
+
+    fn navigation_ui(&mut self, ui: &mut Ui, locale: Locale) {
+        let page_count = self.layout.as_ref().map_or(1, |layout| layout.pages.len());
+        let at_start = self.chapter == 0 && self.page == 0;
+        let at_end = self.chapter + 1 >= self.chapters.len() && self.page + 1 >= page_count;
+        let (mut previous, mut next) = ui.input(|input| {
+            (
+                input.key_pressed(KeyCode::ArrowLeft) || input.key_pressed(KeyCode::PageUp),
+                input.key_pressed(KeyCode::ArrowRight) || input.key_pressed(KeyCode::PageDown),
+            )
+        });
+        ui.horizontal(|ui| {
+            previous |= ui
+                .add_enabled(!at_start, egui::Button::new(t(locale, Key::PreviousPage)))
+                .clicked();
+            ui.label(t2(locale, Key::PageNumber, self.page + 1, page_count));
+            next |= ui
+                .add_enabled(!at_end, egui::Button::new(t(locale, Key::NextPage)))
+                .clicked();
+        });
+        if previous && !at_start {
+            if self.page > 0 {
+                self.page -= 1;
+            } else {
+                // 翻到上一章的最后一页，排版后再收紧到实际页数。
+                self.chapter -= 1;
+                self.page = usize::MAX;
+            }
+        } else if next && !at_end {
+            if self.page + 1 < page_count {
+                self.page += 1;
+            } else {
+                self.chapter += 1;
+                self.page = 0;
+            }
+        }
+    }
+
+    /// 绘制当前页；章节或页面尺寸变化时重新排版。
+    fn page_ui(&mut self, ui: &mut Ui, size: egui::Vec2, locale: Locale) {
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 4.0, PAGE_FILL);
+        painter.rect_stroke(
+            rect,
+            4.0,
+            egui::Stroke::new(1.0, Color32::from_gray(200)),
+            egui::StrokeKind::Inside,
+        );
+
+        if self.rendered.as_ref().map(|(cached, _)| *cached) != Some(self.chapter) {
+            let rendered =
+                render_preview(&self.chapters[self.chapter], self.chapter, &self.options)
+                    .map_err(|err| err.to_string());
+            self.rendered = Some((self.chapter, rendered));
+        }
+        let rendered = match self.rendered.as_ref().map(|(_, rendered)| rendered) {
+            Some(Ok(rendered)) => rendered,
+            Some(Err(err)) => {
+                self.layout = None;
+                self.page = 0;
+                painter.text(
+                    rect.shrink2(PAGE_MARGIN).left_top(),
+                    egui::Align2::LEFT_TOP,
+                    err,
+                    FontId::proportional(13.0),
+                    Color32::RED,
+                );
+                return;
+            }
+            None => return,
+        };
+
+        let footer = 18.0;
+        let content = Rect::from_min_max(
+            rect.min + PAGE_MARGIN,
+            rect.max - PAGE_MARGIN - egui::vec2(0.0, footer),
+        );
+        let stale = self
+            .layout
+            .as_ref()
+            .is_none_or(|layout| layout.chapter != self.chapter || layout.size != content.size());
+        if stale {
+            self.layout = Some(layout_chapter(ui, rendered, self.chapter, content.size()));
+        }
+        let Some(layout) = self.layout.as_ref() else {
+            return;
+        };
+        self.page = self.page.min(layout.pages.len() - 1);
+
+        let clip = painter.with_clip_rect(content);
+        for slice in &layout.pages[self.page] {
+            let rows = &layout.metrics[slice.block].rows;
+            let (start, end) = (rows[slice.rows.start].0, rows[slice.rows.end - 1].1);
+            let top = content.top() + slice.top;
+            let slice_rect = Rect::from_x_y_ranges(content.x_range(), top..=top + end - start);
+            match &layout.blocks[slice.block] {
+                LaidContent::Text { galley, centered } => {
+                    let x = if *centered {
+                        content.center().x
+                    } else {
+                        content.left()
+                    };
+                    clip.with_clip_rect(slice_rect.intersect(content)).galley(
+                        Pos2::new(x, top - start),
+                        galley.clone(),
+                        rendered.typography.color,
+                    );
+                }
+                LaidContent::Rule => {
+                    let inset = content.width() * 0.23;
+                    let y = slice_rect.center().y;
+                    clip.hline(
+                        content.left() + inset..=content.right() - inset,
+                        y,
+                        egui::Stroke::new(1.0, ACCENT),
+                    );
+                }
+            }
+        }
+
+        let footer_y = rect.bottom() - PAGE_MARGIN.y * 0.6;
+        let small = FontId::proportional(11.0);
+        painter.text(
+            Pos2::new(content.left(), footer_y),
+            egui::Align2::LEFT_CENTER,
+            &self.chapters[self.chapter].title,
+            small.clone(),
+            ACCENT,
+        );
+        painter.text(
+            Pos2::new(content.right(), footer_y),
+            egui::Align2::RIGHT_CENTER,
+            t2(locale, Key::PageNumber, self.page + 1, layout.pages.len()),
+            small,
+            ACCENT,
+        );
+    }
+}
+
+/// 按章节版式把各块折行并分页。
+fn layout_chapter(
+    ui: &Ui,
+    rendered: &RenderedChapter,
+    chapter: usize,
+    size: egui::Vec2,
+) -> ChapterPages {
+    let typo = rendered.typography;
+    let font = typo.font_size;
+    let width = size.x;
+    let mut blocks = Vec::new();
+    let mut metrics = Vec::new();
+    let mut push = |content: LaidContent, before: f32, after: f32| {
+        let rows = match &content {
+            LaidContent::Text { galley, .. } => galley
+                .rows
+                .iter()
+                .map(|row| (row.rect().min.y, row.rect().max.y))
+                .collect(),
+            LaidContent::Rule => vec![(0.0, 1.0)],
+        };
+        blocks.push(content);
+        metrics.push(BlockMetrics {
+            rows,
+            space_before: before,
+            space_after: after,
+        });
+    };
+
+    for block in &rendered.blocks {
+        let text_job = |text: &str, size: f32, color: Color32, centered: bool| {
+            let mut job = LayoutJob::single_section(
+                text.to_string(),
+                egui::TextFormat {
+                    font_id: FontId::proportional(size),
+                    color,
+                    ..Default::default()
+                },
+            );
+            job.wrap.max_width = width;
+            if centered {
+                job.halign = Align::Center;
+            }
+            job
+        };
+        match block.kind {
+            BlockKind::Ornament => push(LaidContent::Rule, font * 0.6, font * 0.6),
+            BlockKind::Label => {
+                let job = text_job(&block.text, font * 0.85, ACCENT, true);
+                let galley = ui.fonts_mut(|fonts| fonts.layout_job(job));
+                push(
+                    LaidContent::Text {
+                        galley,
+                        centered: true,
+                    },
+                    0.0,
+                    font * 0.3,
+                );
+            }
+            BlockKind::Heading => {
+                let job = text_job(&block.text, font * 1.5, typo.color, true);
+                let galley = ui.fonts_mut(|fonts| fonts.layout_job(job));
+                push(
+                    LaidContent::Text {
+                        galley,
+                        centered: true,
+                    },
+                    font * 0.4,
+                    font * 0.8,
+                );
+            }
+            BlockKind::Paragraph => {
+                let format = egui::TextFormat {
+                    font_id: FontId::proportional(font),
+                    color: typo.color,
+                    line_height: Some(font * typo.line_height),
+                    ..Default::default()
+                };
+                let indent = if typo.centered {
+                    0.0
+                } else {
+                    block.indent.unwrap_or(typo.text_indent) * font
+                };
+                let mut job = LayoutJob::default();
+                let mut text = block.text.as_str();
+                if typo.drop_cap
+                    && block.has_class("chapter-paragraph-first")
+                    && let Some(first) = text.chars().next()
+                {
+                    let (cap, rest) = text.split_at(first.len_utf8());
+                    job.append(
+                        cap,
+                        0.0,
+                        egui::TextFormat {
+                            font_id: FontId::proportional(font * 2.4),
+                            color: DROP_CAP_COLOR,
+                            ..format.clone()
+                        },
+                    );
+                    text = rest;
+                    job.append(text, 0.0, format);
+                } else {
+                    job.append(text, indent, format);
+                }
+                job.wrap.max_width = width;
+                if typo.centered {
+                    job.halign = Align::Center;
+                } else {
+                    job.justify = true;
+                }
+                let galley = ui.fonts_mut(|fonts| fonts.layout_job(job));
+                push(
+                    LaidContent::Text {
+                        galley,
+                        centered: typo.centered,
+                    },
+                    0.0,
+                    font * typo.paragraph_spacing,
+                );
+            }
+        }
+    }
+
+    let pages = paginate(&metrics, size.y);
+    ChapterPages {
+        chapter,
+        size,
+        blocks,
+        metrics,
+        pages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(lines: usize, before: f32, after: f32) -> BlockMetrics {
+        BlockMetrics {
+            rows: (0..lines)
+                .map(|line| (line as f32 * 10.0, (line + 1) as f32 * 10.0))
+                .collect(),
+            space_before: before,
+            space_after: after,
+        }
+    }
+
+    #[test]
+    fn paginate_splits_paragraphs_between_lines() {
+        // 每页 35：第一段 2 行 + 段后 5，第二段只能放下 1 行。
+        let pages = paginate(&[block(2, 0.0, 5.0), block(4, 0.0, 5.0)], 35.0);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(
+            pages[0],
+            vec![
+                PageSlice {
+                    block: 0,
+                    rows: 0..2,
+                    top: 0.0
+                },
+                PageSlice {
+                    block: 1,
+                    rows: 0..1,
+                    top: 25.0
+                },
+            ]
+        );
+        assert_eq!(
+            pages[1],
+            vec![PageSlice {
+                block: 1,
+                rows: 1..4,
+                top: 0.0
+            }]
+        );
+    }
+
+    #[test]
+    fn paginate_drops_leading_space_and_keeps_oversized_rows() {
+        // 段前空白放不下时整段移到下一页，页首不留段前空白。
+        let pages = paginate(&[block(3, 0.0, 0.0), block(1, 8.0, 0.0)], 35.0);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1][0].top, 0.0);
+
+        let tall = BlockMetrics {
+            rows: vec![(0.0, 50.0), (50.0, 60.0)],
+            space_before: 0.0,
+            space_after: 0.0,
+        };
+        let pages = paginate(&[tall], 35.0);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0][0].rows, 0..1);
+        assert_eq!(pages[1][0].rows, 1..2);
+        assert_eq!(paginate(&[], 35.0), vec![Vec::new()]);
+    }
+}
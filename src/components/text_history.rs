@@ -0,0 +1,162 @@
+//! 文本编辑器的撤销/重做历史。
+//!
+//! 以整段文本快照记录历史：连续输入在停顿 `COALESCE_SECS` 秒后合并为一步，
+//! 超过深度或总字节上限时丢弃最早的快照，避免大文件占用过多内存。
+
+use std::collections::VecDeque;
+
+/// 最多保留的撤销步数。
+pub const MAX_DEPTH: usize = 50;
+/// 撤销快照的总字节上限。
+pub const MAX_BYTES: usize = 64 * 1024 * 1024;
+/// 连续编辑合并为一步的停顿时间（秒）。
+const COALESCE_SECS: f64 = 1.0;
+
+#[derive(Debug, Default)]
+pub struct TextHistory {
+    undo: VecDeque<String>,
+    redo: Vec<String>,
+    /// 最近一次提交时的文本，即下一步撤销前的状态。
+    last: String,
+    /// 尚未提交的编辑最后发生的时间。
+    edited_at: Option<f64>,
+}
+
+impl TextHistory {
+    /// 以 `text` 为起点清空历史；从外部替换文本（如载入文件）后调用。
+    pub fn reset(&mut self, text: &str) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last = text.to_string();
+        self.edited_at = None;
+    }
+
+    /// 记录文本在 `now` 时刻被编辑。
+    pub fn edited(&mut self, now: f64) {
+        self.edited_at = Some(now);
+    }
+
+    /// 编辑停顿足够久时提交为一步。
+    pub fn tick(&mut self, text: &str, now: f64) {
+        if self
+            .edited_at
+            .is_some_and(|edited_at| now - edited_at >= COALESCE_SECS)
+        {
+            self.commit(text);
+        }
+    }
+
+    /// 立即提交尚未记录的编辑。
+    pub fn commit(&mut self, text: &str) {
+        if self.edited_at.take().is_none() || self.last == text {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.last, text.to_string());
+        self.undo.push_back(previous);
+        self.redo.clear();
+        self.trim();
+    }
+
+    /// 把一次整体修改（如替换）记录为单独的一步。
+    pub fn apply(&mut self, text: &mut String, edit: impl FnOnce(&mut String)) {
+        self.commit(text);
+        edit(text);
+        self.edited_at = Some(f64::NEG_INFINITY);
+        self.commit(text);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.edited_at.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty() && self.edited_at.is_none()
+    }
+
+    /// 撤销一步；没有可撤销的内容时返回 `false`。
+    pub fn undo(&mut self, text: &mut String) -> bool {
+        self.commit(text);
+        let Some(previous) = self.undo.pop_back() else {
+            return false;
+        };
+        self.last.clone_from(&previous);
+        self.redo.push(std::mem::replace(text, previous));
+        true
+    }
+
+    /// 重做一步；撤销后又有新编辑时重做历史被清空。
+    pub fn redo(&mut self, text: &mut String) -> bool {
+        self.commit(text);
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.last.clone_from(&next);
+        self.undo.push_back(std::mem::replace(text, next));
+        self.trim();
+        true
+    }
+
+    fn trim(&mut self) {
+        let mut bytes: usize = self.undo.iter().map(String::len).sum();
+        while self.undo.len() > MAX_DEPTH || (bytes > MAX_BYTES && self.undo.len() > 1) {
+            if let Some(oldest) = self.undo.pop_front() {
+                bytes -= oldest.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(history: &mut TextHistory, text: &mut String, add: &str, now: f64) {
+        text.push_str(add);
+        history.edited(now);
+        history.tick(text, now);
+    }
+
+    #[test]
+    fn coalesces_bursts_and_undoes_redoes() {
+        let mut history = TextHistory::default();
+        let mut text = String::new();
+        history.reset(&text);
+
+        type_text(&mut history, &mut text, "a", 0.0);
+        type_text(&mut history, &mut text, "b", 0.2);
+        history.tick(&text, 2.0);
+        type_text(&mut history, &mut text, "c", 3.0);
+        assert_eq!(text, "abc");
+
+        assert!(history.undo(&mut text));
+        assert_eq!(text, "ab");
+        assert!(history.undo(&mut text));
+        assert_eq!(text, "");
+        assert!(!history.undo(&mut text));
+
+        assert!(history.redo(&mut text));
+        assert_eq!(text, "ab");
+        type_text(&mut history, &mut text, "x", 10.0);
+        history.commit(&text);
+        assert!(!history.can_redo());
+        assert!(history.undo(&mut text));
+        assert_eq!(text, "ab");
+    }
+
+    #[test]
+    fn drops_oldest_snapshots_beyond_depth() {
+        let mut history = TextHistory::default();
+        let mut text = String::new();
+        history.reset(&text);
+        for step in 0..MAX_DEPTH + 5 {
+            type_text(&mut history, &mut text, "x", step as f64 * 10.0);
+            history.commit(&text);
+        }
+        let mut undone = 0;
+        while history.undo(&mut text) {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_DEPTH);
+        assert_eq!(text.len(), 5);
+    }
+}
@@ -0,0 +1,340 @@
+//! 插图面板的缩略图缓存：后台解码、按需加载与 LRU 纹理回收。
+//!
+//! 只有滚动到可见区域的图片才会请求缩略图；请求排队后由后台线程（Web 端每帧同步一张）
+//! 解码并缩小到 `THUMBNAIL_SIDE`，再上传为纹理。纹理数量超过容量时回收最久未显示的，
+//! 几百张插图也不会一次占满显存。滚出可见区域、还没开始解码的请求直接丢弃；很小的图片
+//! 直接在界面线程解码。
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry as MapEntry;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use bytes::Bytes;
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+
+/// 缩略图最长边（像素）；面板以约一半大小显示，高分屏下仍然清晰。
+pub const THUMBNAIL_SIDE: u32 = 256;
+/// 默认最多保留的缩略图纹理数。
+const DEFAULT_CAPACITY: usize = 48;
+/// 同时在后台解码的图片数。
+const MAX_DECODING: usize = 2;
+/// 不超过这个大小的图片直接在界面线程解码，比交给后台线程再等一帧更快。
+const INLINE_DECODE_BYTES: usize = 64 * 1024;
+
+/// 以图片内容所在的内存地址与长度区分图片；缓存条目持有这份 `Bytes`，地址在条目存在期间不会被复用。
+type ThumbnailKey = (usize, usize);
+
+/// 某张图片缩略图的当前状态。
+pub enum Thumbnail<'a> {
+    Ready(&'a TextureHandle),
+    Loading,
+    Failed(&'a str),
+}
+
+enum EntryState {
+    /// 已请求、等待后台解码。
+    Queued {
+        svg: bool,
+    },
+    Decoding,
+    Ready(TextureHandle),
+    Failed(String),
+}
+
+struct Entry {
+    bytes: Bytes,
+    /// 最近一次请求时的绘制轮次。
+    last_used: u64,
+    state: EntryState,
+}
+
+type DecodeResult = (ThumbnailKey, Result<ColorImage, String>);
+
+pub struct ThumbnailCache {
+    capacity: usize,
+    inline_decode_bytes: usize,
+    entries: HashMap<ThumbnailKey, Entry>,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    tx: Sender<DecodeResult>,
+    rx: Receiver<DecodeResult>,
+}
+
+impl Default for ThumbnailCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl ThumbnailCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            capacity: capacity.max(1),
+            inline_decode_bytes: INLINE_DECODE_BYTES,
+            entries: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// 请求一张图片的缩略图；应只对可见的图片调用。
+    pub fn request(&mut self, ctx: &Context, bytes: &Bytes, svg: bool) -> Thumbnail<'_> {
+        let pass = ctx.cumulative_pass_nr();
+        let key = key(bytes);
+        let entry = match self.entries.entry(key) {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => {
+                let state = if bytes.len() <= self.inline_decode_bytes {
+                    decoded(ctx, key, decode_thumbnail(bytes, svg, THUMBNAIL_SIDE))
+                } else {
+                    EntryState::Queued { svg }
+                };
+                entry.insert(Entry {
+                    bytes: bytes.clone(),
+                    last_used: pass,
+                    state,
+                })
+            }
+        };
+        entry.last_used = pass;
+        match &entry.state {
+            EntryState::Ready(texture) => Thumbnail::Ready(texture),
+            EntryState::Failed(err) => Thumbnail::Failed(err),
+            EntryState::Queued { .. } | EntryState::Decoding => {
+                ctx.request_repaint();
+                Thumbnail::Loading
+            }
+        }
+    }
+
+    /// 每帧在绘制列表之前调用：上传解码完成的缩略图、开始新的解码并回收多余的纹理。
+    pub fn poll(&mut self, ctx: &Context) {
+        while let Ok((key, result)) = self.rx.try_recv() {
+            self.finish(ctx, key, result);
+        }
+
+        // 上一帧没有再请求的排队项已滚出可见区域。
+        let pass = ctx.cumulative_pass_nr();
+        self.entries.retain(|_, entry| {
+            !matches!(entry.state, EntryState::Queued { .. }) || entry.last_used + 1 >= pass
+        });
+
+        let decoding = self
+            .entries
+            .values()
+            .filter(|entry| matches!(entry.state, EntryState::Decoding))
+            .count();
+        let mut queued: Vec<(ThumbnailKey, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| matches!(entry.state, EntryState::Queued { .. }))
+            .map(|(key, entry)| (*key, entry.last_used))
+            .collect();
+        queued.sort_by_key(|(_, last_used)| std::cmp::Reverse(*last_used));
+        for (key, _) in queued
+            .into_iter()
+            .take(MAX_DECODING.saturating_sub(decoding))
+        {
+            let entry = self.entries.get_mut(&key).expect("queued entry");
+            let EntryState::Queued { svg } = entry.state else {
+                continue;
+            };
+            entry.state = EntryState::Decoding;
+            let bytes = entry.bytes.clone();
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let tx = self.tx.clone();
+                let ctx = ctx.clone();
+                std::thread::spawn(move || {
+                    let _ = tx.send((key, decode_thumbnail(&bytes, svg, THUMBNAIL_SIDE)));
+                    ctx.request_repaint();
+                });
+            }
+            // Web 端没有线程，每帧同步解码一张。
+            #[cfg(target_arch = "wasm32")]
+            {
+                let result = decode_thumbnail(&bytes, svg, THUMBNAIL_SIDE);
+                self.finish(ctx, key, result);
+                break;
+            }
+        }
+
+        self.evict(pass);
+    }
+
+    /// 已生成的缩略图纹理数。
+    pub fn texture_count(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| matches!(entry.state, EntryState::Ready(_)))
+            .count()
+    }
+
+    fn finish(&mut self, ctx: &Context, key: ThumbnailKey, result: Result<ColorImage, String>) {
+        // 解码期间被回收的条目不再上传。
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return;
+        };
+        entry.state = decoded(ctx, key, result);
+    }
+
+    /// 纹理数超过容量时，按最近使用时间回收；本帧和上一帧显示过的不回收。
+    fn evict(&mut self, pass: u64) {
+        let mut ready: Vec<(ThumbnailKey, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| matches!(entry.state, EntryState::Ready(_)))
+            .map(|(key, entry)| (*key, entry.last_used))
+            .collect();
+        if ready.len() <= self.capacity {
+            return;
+        }
+        ready.sort_by_key(|(_, last_used)| *last_used);
+        let excess = ready.len() - self.capacity;
+        for (key, last_used) in ready.into_iter().take(excess) {
+            if last_used + 1 >= pass {
+                break;
+            }
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// 把解码结果上传为纹理。
+fn decoded(ctx: &Context, key: ThumbnailKey, result: Result<ColorImage, String>) -> EntryState {
+    match result {
+        Ok(image) => EntryState::Ready(ctx.load_texture(
+            format!("thumbnail:{:x}:{}", key.0, key.1),
+            image,
+            TextureOptions::LINEAR,
+        )),
+        Err(err) => EntryState::Failed(err),
+    }
+}
+
+fn key(bytes: &Bytes) -> ThumbnailKey {
+    (bytes.as_ptr() as usize, bytes.len())
+}
+
+/// 解码图片并缩小到最长边不超过 `max_side`；小图保持原尺寸。
+pub fn decode_thumbnail(bytes: &[u8], svg: bool, max_side: u32) -> Result<ColorImage, String> {
+    if svg {
+        let tree = resvg::usvg::Tree::from_data(bytes, &Default::default())
+            .map_err(|err| err.to_string())?;
+        let size = tree.size();
+        let scale = (max_side as f32 / size.width().max(size.height())).min(1.0);
+        let width = (size.width() * scale).round().max(1.0) as u32;
+        let height = (size.height() * scale).round().max(1.0) as u32;
+        let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height).ok_or("invalid SVG size")?;
+        resvg::render(
+            &tree,
+            resvg::tiny_skia::Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+        return Ok(ColorImage::from_rgba_premultiplied(
+            [width as _, height as _],
+            pixmap.data(),
+        ));
+    }
+    let mut image = image::load_from_memory(bytes).map_err(|err| err.to_string())?;
+    if image.width().max(image.height()) > max_side {
+        image = image.thumbnail(max_side, max_side);
+    }
+    let rgba = image.to_rgba8();
+    Ok(ColorImage::from_rgba_unmultiplied(
+        [rgba.width() as _, rgba.height() as _],
+        rgba.as_flat_samples().as_slice(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Bytes {
+        let image = image::RgbaImage::from_pixel(width, height, image::Rgba([40, 90, 160, 255]));
+        let mut out = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut out, image::ImageFormat::Png)
+            .expect("encode");
+        Bytes::from(out.into_inner())
+    }
+
+    /// 模拟一帧：先 `poll`，再请求 `visible` 中的图片，直到它们都解码完成。
+    fn show_until_ready(cache: &mut ThumbnailCache, ctx: &Context, visible: &[Bytes]) {
+        for _ in 0..500 {
+            ctx.begin_pass(Default::default());
+            cache.poll(ctx);
+            let ready = visible
+                .iter()
+                .filter(|bytes| !matches!(cache.request(ctx, bytes, false), Thumbnail::Loading))
+                .count();
+            let _ = ctx.end_pass();
+            if ready == visible.len() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        panic!("thumbnails were not decoded");
+    }
+
+    #[test]
+    fn decodes_downscaled_thumbnails() {
+        let image = decode_thumbnail(&png(1024, 512), false, 256).expect("decode");
+        assert_eq!(image.size, [256, 128]);
+        let image = decode_thumbnail(&png(40, 30), false, 256).expect("decode");
+        assert_eq!(image.size, [40, 30]);
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="600" height="300"/>"#;
+        let image = decode_thumbnail(svg, true, 256).expect("decode");
+        assert_eq!(image.size, [256, 128]);
+        assert!(decode_thumbnail(b"not an image", false, 256).is_err());
+    }
+
+    #[test]
+    fn decodes_small_images_without_waiting() {
+        let ctx = Context::default();
+        let mut cache = ThumbnailCache::default();
+        let bytes = png(32, 32);
+        ctx.begin_pass(Default::default());
+        assert!(matches!(
+            cache.request(&ctx, &bytes, false),
+            Thumbnail::Ready(_)
+        ));
+        let _ = ctx.end_pass();
+    }
+
+    #[test]
+    fn evicts_least_recently_shown_textures() {
+        let ctx = Context::default();
+        let mut cache = ThumbnailCache::with_capacity(2);
+        cache.inline_decode_bytes = 0;
+        let images: Vec<Bytes> = (1..=4).map(|side| png(side * 10, side * 10)).collect();
+
+        show_until_ready(&mut cache, &ctx, &images[0..2]);
+        show_until_ready(&mut cache, &ctx, &images[2..4]);
+        // 再过一帧，最早的两张已不可见，超出容量被回收。
+        show_until_ready(&mut cache, &ctx, &images[2..4]);
+        assert_eq!(cache.texture_count(), 2);
+        assert!(!cache.entries.contains_key(&key(&images[0])));
+        assert!(cache.entries.contains_key(&key(&images[3])));
+
+        // 同时最多解码两张；其余排队的请求滚出可见区域后直接丢弃。
+        let scrolled: Vec<Bytes> = (5..=7).map(|side| png(side * 10, side * 10)).collect();
+        ctx.begin_pass(Default::default());
+        for bytes in &scrolled {
+            cache.request(&ctx, bytes, false);
+        }
+        let _ = ctx.end_pass();
+        for _ in 0..2 {
+            ctx.begin_pass(Default::default());
+            cache.poll(&ctx);
+            let _ = ctx.end_pass();
+        }
+        let started = scrolled
+            .iter()
+            .filter_map(|bytes| cache.entries.get(&key(bytes)))
+            .inspect(|entry| assert!(!matches!(entry.state, EntryState::Queued { .. })))
+            .count();
+        assert_eq!(started, MAX_DECODING);
+    }
+}
@@ -1,34 +1,465 @@
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use bytes::Bytes;
 use regex::Regex;
 
-use crate::epubworker::{BuildError, EpubBuildOptions, build_epub};
+use crate::cover::CoverGeneration;
+use crate::epubcheck::{EpubcheckReport, run_epubcheck};
+use crate::epubworker::{
+    BuildError, BuildProgress, EpubBuildOptions, EpubBuildOutput, ImageSizeChange,
+    build_epub_to_writer_with_progress, build_epub_with_progress,
+};
+use crate::merge::TextEncoding;
+use crate::numerals::TitleNumerals;
+use crate::pdfworker::{PdfBuildOptions, build_pdf_with_progress};
+use crate::punctuation::PunctuationNormalization;
+use crate::split_config::SplitConfig;
+use crate::text_header::strip_text_header_if;
+use crate::toc::InlineToc;
+use crate::validation::{ValidationIssue, validate_epub, validate_epub_bytes};
+use crate::whitespace::{WhitespaceSummary, clean_whitespace};
+use crate::zhconv::ChineseConversion;
 use crate::{
-    BookInfo, ChapterDraft, ConversionMethod, FontAsset, ImageAsset, Pattern, TextProcessor,
-    TextStyle, TocOptions,
+    BackMatter, BookInfo, ChapterDraft, CleanupRule, ConversionMethod, EpubVersion, FontAsset,
+    FrontMatter, ImageAsset, ImageOptimization, OutputFormat, OverwritePolicy, Pattern, PdfOptions,
+    SourceFormat, SpineSection, TextProcessor, TextStyle, TocOptions, ZipCompression,
+    apply_cleanup_rules, assign_volumes, assign_volumes_by, has_heading_groups, take_back_matter,
 };
 
+#[derive(Clone)]
 pub struct ConversionRequest {
     pub text: String,
     pub method: ConversionMethod,
+    /// `Regex` 分章使用的正则，每行一条，按优先顺序组合；为空时使用内置中文章节规则。
     pub custom_regex: String,
     pub custom_config_path: Option<PathBuf>,
+    /// 标题排除规则：看起来像标题、但所在整行匹配任一规则的行不分章（如对话中的“第X章见书末”）。
+    pub title_exclusions: Vec<String>,
+    /// 按字数分章时每章的目标字数（不含空白）。
+    pub chapter_length: usize,
     pub book_info: BookInfo,
     pub output_dir: PathBuf,
     pub filename_template: String,
     pub style: TextStyle,
     pub cover: Option<ImageAsset>,
     pub images: Vec<ImageAsset>,
-    pub font: Option<FontAsset>,
+    pub fonts: Vec<FontAsset>,
     pub chapter_header_image: Option<ImageAsset>,
     pub chapter_header_fullbleed: bool,
     pub chapters_override: Option<Vec<ChapterDraft>>,
     pub include_images_section: bool,
     pub toc_options: TocOptions,
+    /// 正文前的书名页、版权页、献词与前言；PDF 输出不使用。
+    pub front_matter: FrontMatter,
+    /// 插图页之后的后记或版权页；PDF 输出不使用。
+    pub back_matter: BackMatter,
+    pub include_generator_meta: bool,
+    pub safe_mode: bool,
+    pub epub_version: EpubVersion,
+    /// spine 按从右到左翻页，适用于漫画或 RTL 语言书籍。
+    pub rtl_page_progression: bool,
+    /// 输出 Kobo 专用的 `.kepub.epub`。
+    pub kepub: bool,
+    /// 可复现输出：相同输入两次转换得到逐字节相同的 EPUB，便于比较和 CI。
+    pub reproducible: bool,
+    /// EPUB 容器的 ZIP 压缩级别与图片是否压缩。
+    pub compression: ZipCompression,
+    /// 超过该字节数的章节拆成多个 XHTML 文件；`None` 不拆分。
+    pub split_chapter_bytes: Option<usize>,
+    /// 封面、前置页、目录页、正文、插图页与后记在 spine 中的顺序。
+    pub spine_order: Vec<SpineSection>,
+    /// 输出 EPUB 或 PDF；PDF 只使用书籍信息、章节、`style` 与 `fonts`。
+    pub output_format: OutputFormat,
+    pub pdf: PdfOptions,
+    pub source_format: SourceFormat,
+    /// 分章前最先去掉开头的 `书名：` / `作者：` 信息块；使用 `chapters_override` 时不生效。
+    pub strip_text_header: bool,
+    /// 分章前、清理规则之前去掉 BOM、零宽字符、软连字符与行尾空白并合并连续空行；
+    /// 使用 `chapters_override` 时不生效。
+    pub clean_whitespace: bool,
+    /// 分章前依次应用的文本清理规则；使用 `chapters_override` 时不生效。
+    pub cleanup_rules: Vec<CleanupRule>,
+    /// 清理规则之后、分章之前的标点与全半角规范化；使用 `chapters_override` 时不生效。
+    pub punctuation: PunctuationNormalization,
+    /// 分章后统一章节标题与卷名中编号的写法（中文数字 ↔ 阿拉伯数字），在简繁转换之前进行。
+    pub title_numerals: TitleNumerals,
+    /// 分章后对章节标题、正文与书籍元数据统一做简繁转换。
+    pub chinese_conversion: ChineseConversion,
+    /// 嵌入前缩小并重新压缩图片。
+    pub image_optimization: ImageOptimization,
+    /// 输出文件已存在时覆盖、自动编号或返回错误；内存输出时不生效。
+    pub overwrite_policy: OverwritePolicy,
+    /// 按书籍信息生成封面图，设置后取代 `cover`。
+    pub generated_cover: Option<CoverGeneration>,
+    /// 转换完成后运行的外部 epubcheck（可执行文件或 `.jar`）。
+    pub epubcheck_path: Option<PathBuf>,
+    /// 不写出文件，EPUB 在内存中生成并放入结果的 `bytes`；用于没有文件系统的浏览器端。
+    pub in_memory: bool,
+    /// 取消标志：在分章后、逐章简繁转换与逐章渲染之间检查，置位后返回
+    /// `ConversionError::Cancelled`。克隆出的请求共享同一个标志。
+    pub cancel: CancelToken,
+}
+
+impl Default for ConversionRequest {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            method: ConversionMethod::Regex,
+            custom_regex: String::new(),
+            custom_config_path: None,
+            title_exclusions: Vec::new(),
+            chapter_length: DEFAULT_CHAPTER_LENGTH,
+            book_info: BookInfo::default(),
+            output_dir: PathBuf::from("."),
+            filename_template: "{书名}_{作者}.epub".to_string(),
+            style: TextStyle::default(),
+            cover: None,
+            images: Vec::new(),
+            fonts: Vec::new(),
+            chapter_header_image: None,
+            chapter_header_fullbleed: false,
+            chapters_override: None,
+            include_images_section: true,
+            toc_options: TocOptions::default(),
+            front_matter: FrontMatter::default(),
+            back_matter: BackMatter::default(),
+            include_generator_meta: true,
+            safe_mode: false,
+            epub_version: EpubVersion::default(),
+            rtl_page_progression: false,
+            kepub: false,
+            reproducible: false,
+            compression: ZipCompression::default(),
+            split_chapter_bytes: None,
+            spine_order: SpineSection::ALL.to_vec(),
+            output_format: OutputFormat::default(),
+            pdf: PdfOptions::default(),
+            source_format: SourceFormat::default(),
+            strip_text_header: false,
+            clean_whitespace: false,
+            cleanup_rules: Vec::new(),
+            punctuation: PunctuationNormalization::default(),
+            title_numerals: TitleNumerals::default(),
+            chinese_conversion: ChineseConversion::default(),
+            image_optimization: ImageOptimization::default(),
+            overwrite_policy: OverwritePolicy::default(),
+            generated_cover: None,
+            epubcheck_path: None,
+            in_memory: false,
+            cancel: CancelToken::new(),
+        }
+    }
 }
 
 pub struct ConversionResult {
     pub output_path: String,
+    pub warnings: Vec<String>,
+    pub image_sizes: Vec<ImageSizeChange>,
+    /// 对生成文件的内置自检结果，为空表示未发现问题。
+    pub validation: Vec<ValidationIssue>,
+    /// 设置了 `epubcheck_path` 且 epubcheck 成功运行时的报告。
+    pub epubcheck: Option<EpubcheckReport>,
+    /// `in_memory` 转换生成的 EPUB 内容；此时 `output_path` 只是建议的文件名。
+    pub bytes: Option<Vec<u8>>,
+    /// 章节、字数与图片统计。
+    pub report: ConversionReport,
+}
+
+/// 转换统计：章节数、字数、嵌入图片数与篇幅异常的章节。
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub chapters: usize,
+    /// 标题与正文的非空白字符数。
+    pub chars: usize,
+    /// 词数：每个汉字、假名或谚文计一词，连续的字母数字计一词。
+    pub words: usize,
+    /// 嵌入的图片数（封面、章头图与插图）。
+    pub images: usize,
+    /// 明显短于或长于中位篇幅的章节，按章节顺序排列。
+    pub outliers: Vec<ChapterOutlier>,
+    /// 启用空白清理时去掉的字符统计。
+    pub whitespace: Option<WhitespaceSummary>,
+}
+
+/// 篇幅异常的章节。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChapterOutlier {
+    /// 章节序号（从 0 开始）。
+    pub index: usize,
+    pub title: String,
+    pub chars: usize,
+    pub kind: OutlierKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutlierKind {
+    Short,
+    Long,
+}
+
+impl ConversionReport {
+    /// 篇幅不足中位数 `1 / OUTLIER_RATIO` 或超过其 `OUTLIER_RATIO` 倍的章节视为异常。
+    const OUTLIER_RATIO: usize = 4;
+    /// 章节太少时中位数没有参考意义，不标记异常。
+    const OUTLIER_MIN_CHAPTERS: usize = 3;
+
+    /// 统计章节篇幅；图片数与空白清理统计由调用方填写。
+    pub fn from_chapters(chapters: &[ChapterDraft]) -> Self {
+        let stats: Vec<ChapterStats> = chapters.iter().map(ChapterStats::of).collect();
+        let lengths: Vec<usize> = stats.iter().map(|stats| stats.chars).collect();
+        let outliers = Self::outlier_kinds(&lengths)
+            .into_iter()
+            .zip(lengths.iter().zip(chapters))
+            .enumerate()
+            .filter_map(|(index, (kind, (&chars, chapter)))| {
+                Some(ChapterOutlier {
+                    index,
+                    title: chapter.title.clone(),
+                    chars,
+                    kind: kind?,
+                })
+            })
+            .collect();
+        Self {
+            chapters: chapters.len(),
+            chars: lengths.iter().sum(),
+            words: stats.iter().map(|stats| stats.words).sum(),
+            images: 0,
+            outliers,
+            whitespace: None,
+        }
+    }
+
+    /// 按字数判断每章是否篇幅异常；章节少于 `OUTLIER_MIN_CHAPTERS` 时全部为 `None`。
+    pub fn outlier_kinds(lengths: &[usize]) -> Vec<Option<OutlierKind>> {
+        if lengths.len() < Self::OUTLIER_MIN_CHAPTERS {
+            return vec![None; lengths.len()];
+        }
+        let mut sorted = lengths.to_vec();
+        sorted.sort_unstable();
+        let median = sorted[sorted.len() / 2];
+        lengths
+            .iter()
+            .map(|&chars| {
+                if chars * Self::OUTLIER_RATIO < median {
+                    Some(OutlierKind::Short)
+                } else if chars > median * Self::OUTLIER_RATIO {
+                    Some(OutlierKind::Long)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// 单章篇幅：标题与正文的字数、词数，计法与 [`ConversionReport`] 相同。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChapterStats {
+    pub chars: usize,
+    pub words: usize,
+}
+
+impl ChapterStats {
+    /// 估算阅读时间的速度：每分钟读多少词（中文即字数）。
+    pub const WORDS_PER_MINUTE: usize = 400;
+
+    pub fn of(chapter: &ChapterDraft) -> Self {
+        Self {
+            chars: count_chars(&chapter.title) + count_chars(&chapter.content),
+            words: count_words(&chapter.title) + count_words(&chapter.content),
+        }
+    }
+
+    /// 预计阅读分钟数，不足一分钟按一分钟计。
+    pub fn reading_minutes(self) -> usize {
+        self.words.div_ceil(Self::WORDS_PER_MINUTE)
+    }
+}
+
+impl std::iter::Sum for ChapterStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, stats| Self {
+            chars: total.chars + stats.chars,
+            words: total.words + stats.words,
+        })
+    }
+}
+
+fn count_chars(text: &str) -> usize {
+    text.chars().filter(|ch| !ch.is_whitespace()).count()
+}
+
+/// 汉字、假名与谚文逐字计数，其余文字按连续的字母数字计数。
+fn count_words(text: &str) -> usize {
+    let mut words = 0;
+    let mut in_word = false;
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            words += 1;
+            in_word = false;
+        } else if ch.is_alphanumeric() {
+            if !in_word {
+                words += 1;
+            }
+            in_word = true;
+        } else {
+            in_word = false;
+        }
+    }
+    words
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// 批量转换请求：多个源文件共享同一份转换设置。
+///
+/// `settings` 中的 `text` 会被每个源文件的内容替换；标识、ISBN、简介、系列、
+/// 封面、插图、献词、前言、后记文字与章节编辑结果只属于单本书，不会跨书复用。
+/// 版权页文字是带 `{书名}` 等变量的模板，按每本书的信息展开，因此保留。
+#[derive(Clone)]
+pub struct BatchRequest {
+    pub inputs: Vec<PathBuf>,
+    pub settings: ConversionRequest,
+}
+
+impl BatchRequest {
+    pub fn new(inputs: Vec<PathBuf>, settings: ConversionRequest) -> Self {
+        Self { inputs, settings }
+    }
+
+    /// 为单个源文件构建转换请求。
+    ///
+    /// 书名取自文件名；Markdown 文件自动改用按标题分章。源文件按 BOM、UTF-8、GB18030
+    /// 的顺序自动识别编码（同合并多个文件）。
+    pub fn request_for(&self, path: &Path) -> Result<ConversionRequest, ConversionError> {
+        let mut req = self.settings.clone();
+        req.text = TextEncoding::Auto.decode(&std::fs::read(path)?);
+        req.source_format = SourceFormat::from_path(path);
+        if req.source_format == SourceFormat::Markdown {
+            req.method = ConversionMethod::MarkdownHeadings;
+        }
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            req.book_info.title = stem.to_string();
+        }
+        let info = &mut req.book_info;
+        info.identifier.clear();
+        info.isbn.clear();
+        info.description.clear();
+        info.series.clear();
+        info.series_index.clear();
+        req.cover = None;
+        req.images.clear();
+        req.front_matter.dedication.clear();
+        req.front_matter.preface.clear();
+        req.back_matter.text.clear();
+        req.back_matter.title.clear();
+        req.chapters_override = None;
+        Ok(req)
+    }
+
+    /// 读取并转换单个源文件；读取期间先上报 `Decoding`，之后同 `convert_with_progress`。
+    pub fn convert_item(
+        &self,
+        path: &Path,
+        mut on_progress: impl FnMut(ConversionProgress),
+    ) -> Result<ConversionResult, ConversionError> {
+        on_progress(ConversionProgress::Decoding);
+        let request = self.request_for(path)?;
+        ConversionFacade::convert_with_progress(request, on_progress)
+    }
+}
+
+/// 批量队列中单个条目的状态。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchItemStatus {
+    Pending,
+    Done(String),
+    Failed(String),
+}
+
+/// 转换进度事件，由 `ConversionFacade::convert_with_progress` 通过回调上报。
+///
+/// `Decoding` 只在转换方自己读取源文件时出现（`BatchRequest::convert_item`、命令行）；
+/// 请求里已带正文时从 `Splitting` 开始。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConversionProgress {
+    Decoding,
+    Splitting,
+    Split { chapters: usize },
+    ChapterRendered { done: usize, total: usize },
+    Writing,
+}
+
+impl ConversionProgress {
+    /// 估算整体完成比例（0.0–1.0）：读取与分章占前 10%，章节渲染占 80%，写出压缩包占最后 10%。
+    pub fn fraction(self) -> f32 {
+        match self {
+            ConversionProgress::Decoding | ConversionProgress::Splitting => 0.0,
+            ConversionProgress::Split { .. } => 0.1,
+            ConversionProgress::ChapterRendered { done, total } => {
+                (1.0 + 8.0 * done as f32 / total.max(1) as f32) / 10.0
+            }
+            ConversionProgress::Writing => 0.9,
+        }
+    }
+}
+
+/// 命令行进度输出使用的英文描述。
+impl std::fmt::Display for ConversionProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionProgress::Decoding => write!(f, "Reading input"),
+            ConversionProgress::Splitting => write!(f, "Splitting chapters"),
+            ConversionProgress::Split { chapters } => write!(f, "Split into {chapters} chapters"),
+            ConversionProgress::ChapterRendered { done, total } => {
+                write!(f, "Rendering chapter {done}/{total}")
+            }
+            ConversionProgress::Writing => write!(f, "Compressing output"),
+        }
+    }
+}
+
+impl From<BuildProgress> for ConversionProgress {
+    fn from(progress: BuildProgress) -> Self {
+        match progress {
+            BuildProgress::ChapterRendered { done, total } => {
+                ConversionProgress::ChapterRendered { done, total }
+            }
+            BuildProgress::Writing => ConversionProgress::Writing,
+        }
+    }
+}
+
+/// 可跨线程共享的取消标志；克隆出的句柄指向同一个标志。
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 pub struct EpubPlanBuilder {
@@ -38,11 +469,25 @@ pub struct EpubPlanBuilder {
     style: TextStyle,
     cover: Option<ImageAsset>,
     images: Vec<ImageAsset>,
-    font: Option<FontAsset>,
+    fonts: Vec<FontAsset>,
     chapter_header_image: Option<ImageAsset>,
     chapter_header_fullbleed: bool,
     include_images_section: bool,
     toc_options: TocOptions,
+    front_matter: FrontMatter,
+    back_matter: BackMatter,
+    include_generator_meta: bool,
+    safe_mode: bool,
+    epub_version: EpubVersion,
+    rtl_page_progression: bool,
+    kepub: bool,
+    reproducible: bool,
+    compression: ZipCompression,
+    split_chapter_bytes: Option<usize>,
+    spine_order: Vec<SpineSection>,
+    source_format: SourceFormat,
+    image_optimization: ImageOptimization,
+    overwrite_policy: OverwritePolicy,
 }
 
 impl EpubPlanBuilder {
@@ -54,11 +499,25 @@ impl EpubPlanBuilder {
             style: TextStyle::default(),
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             include_images_section: true,
             toc_options: TocOptions::default(),
+            front_matter: FrontMatter::default(),
+            back_matter: BackMatter::default(),
+            include_generator_meta: true,
+            safe_mode: false,
+            epub_version: EpubVersion::default(),
+            rtl_page_progression: false,
+            kepub: false,
+            reproducible: false,
+            compression: ZipCompression::default(),
+            split_chapter_bytes: None,
+            spine_order: SpineSection::ALL.to_vec(),
+            source_format: SourceFormat::default(),
+            image_optimization: ImageOptimization::default(),
+            overwrite_policy: OverwritePolicy::default(),
         }
     }
 
@@ -87,8 +546,8 @@ impl EpubPlanBuilder {
         self
     }
 
-    pub fn font(mut self, font: Option<FontAsset>) -> Self {
-        self.font = font;
+    pub fn fonts(mut self, fonts: Vec<FontAsset>) -> Self {
+        self.fonts = fonts;
         self
     }
 
@@ -112,21 +571,143 @@ impl EpubPlanBuilder {
         self
     }
 
-    pub fn build(self, chapters: &[ChapterDraft]) -> Result<String, ConversionError> {
-        let options = EpubBuildOptions {
+    pub fn front_matter(mut self, front_matter: FrontMatter) -> Self {
+        self.front_matter = front_matter;
+        self
+    }
+
+    pub fn back_matter(mut self, back_matter: BackMatter) -> Self {
+        self.back_matter = back_matter;
+        self
+    }
+
+    pub fn include_generator_meta(mut self, include: bool) -> Self {
+        self.include_generator_meta = include;
+        self
+    }
+
+    pub fn safe_mode(mut self, safe_mode: bool) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    pub fn epub_version(mut self, epub_version: EpubVersion) -> Self {
+        self.epub_version = epub_version;
+        self
+    }
+
+    pub fn rtl_page_progression(mut self, rtl: bool) -> Self {
+        self.rtl_page_progression = rtl;
+        self
+    }
+
+    pub fn kepub(mut self, kepub: bool) -> Self {
+        self.kepub = kepub;
+        self
+    }
+
+    pub fn reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    pub fn compression(mut self, compression: ZipCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn split_chapter_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.split_chapter_bytes = max_bytes;
+        self
+    }
+
+    pub fn spine_order(mut self, spine_order: Vec<SpineSection>) -> Self {
+        self.spine_order = spine_order;
+        self
+    }
+
+    pub fn source_format(mut self, source_format: SourceFormat) -> Self {
+        self.source_format = source_format;
+        self
+    }
+
+    pub fn image_optimization(mut self, settings: ImageOptimization) -> Self {
+        self.image_optimization = settings;
+        self
+    }
+
+    pub fn overwrite_policy(mut self, policy: OverwritePolicy) -> Self {
+        self.overwrite_policy = policy;
+        self
+    }
+
+    /// 与 `build_with_progress` 相同，但在内存中生成 EPUB 而不写出文件。
+    pub fn build_in_memory_with_progress(
+        self,
+        chapters: &[ChapterDraft],
+        on_progress: &mut dyn FnMut(BuildProgress),
+        cancel: &AtomicBool,
+    ) -> Result<(EpubBuildOutput, Vec<u8>), ConversionError> {
+        let options = self.into_options();
+        let mut bytes = Vec::new();
+        let output = build_epub_to_writer_with_progress(
+            chapters,
+            &options,
+            &mut bytes,
+            on_progress,
+            cancel,
+        )?;
+        Ok((output, bytes))
+    }
+
+    pub fn build(self, chapters: &[ChapterDraft]) -> Result<EpubBuildOutput, ConversionError> {
+        self.build_with_progress(chapters, &mut |_| {}, &AtomicBool::new(false))
+    }
+
+    /// 与 `build` 相同，并上报章节渲染进度、响应取消标志。
+    pub fn build_with_progress(
+        self,
+        chapters: &[ChapterDraft],
+        on_progress: &mut dyn FnMut(BuildProgress),
+        cancel: &AtomicBool,
+    ) -> Result<EpubBuildOutput, ConversionError> {
+        let options = self.into_options();
+        Ok(build_epub_with_progress(
+            chapters,
+            &options,
+            on_progress,
+            cancel,
+        )?)
+    }
+
+    fn into_options(self) -> EpubBuildOptions {
+        EpubBuildOptions {
             book_info: self.book_info,
             output_dir: self.output_dir,
             filename_template: self.filename_template,
             style: self.style,
             cover: self.cover,
             images: self.images,
-            font: self.font,
+            fonts: self.fonts,
             chapter_header_image: self.chapter_header_image,
             chapter_header_fullbleed: self.chapter_header_fullbleed,
             include_images_section: self.include_images_section,
             toc_options: self.toc_options,
-        };
-        Ok(build_epub(chapters, &options)?)
+            front_matter: self.front_matter,
+            back_matter: self.back_matter,
+            include_generator_meta: self.include_generator_meta,
+            safe_mode: self.safe_mode,
+            epub_version: self.epub_version,
+            rtl_page_progression: self.rtl_page_progression,
+            kepub: self.kepub,
+            reproducible: self.reproducible,
+            compression: self.compression,
+            split_chapter_bytes: self.split_chapter_bytes,
+            spine_order: self.spine_order,
+            source_format: self.source_format,
+            image_optimization: self.image_optimization,
+            overwrite_policy: self.overwrite_policy,
+        }
     }
 }
 
@@ -136,6 +717,7 @@ pub enum ConversionError {
     Regex(regex::Error),
     Io(std::io::Error),
     Build(BuildError),
+    Cancelled,
 }
 
 impl std::fmt::Display for ConversionError {
@@ -145,6 +727,7 @@ impl std::fmt::Display for ConversionError {
             ConversionError::Regex(err) => write!(f, "Regex error: {}", err),
             ConversionError::Io(err) => write!(f, "IO error: {}", err),
             ConversionError::Build(err) => write!(f, "EPUB build failed: {}", err),
+            ConversionError::Cancelled => write!(f, "Conversion cancelled."),
         }
     }
 }
@@ -165,7 +748,10 @@ impl From<std::io::Error> for ConversionError {
 
 impl From<BuildError> for ConversionError {
     fn from(err: BuildError) -> Self {
-        ConversionError::Build(err)
+        match err {
+            BuildError::Cancelled => ConversionError::Cancelled,
+            err => ConversionError::Build(err),
+        }
     }
 }
 
@@ -175,30 +761,418 @@ pub trait ChapterSplitStrategy {
 
 pub struct RegexSplitStrategy {
     pattern: Pattern,
+    exclusions: Vec<Regex>,
+    /// 含 `vol` / `num` / `title` 命名分组时，用于从标题行提取结构化标题的正则。
+    heading_patterns: Vec<Regex>,
 }
 
 impl RegexSplitStrategy {
     pub fn new(pattern: Pattern) -> Self {
-        Self { pattern }
+        let heading_patterns = match &pattern {
+            Pattern::Custom(re) if has_heading_groups(re) => vec![re.clone()],
+            _ => Vec::new(),
+        };
+        Self {
+            pattern,
+            exclusions: Vec::new(),
+            heading_patterns,
+        }
+    }
+
+    /// 标题所在整行匹配任一排除规则时不在此处分章。
+    pub fn with_exclusions(mut self, exclusions: Vec<Regex>) -> Self {
+        self.exclusions = exclusions;
+        self
     }
 }
 
 impl ChapterSplitStrategy for RegexSplitStrategy {
     fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
-        let processor = TextProcessor::new(self.pattern.clone(), text.to_string());
-        Ok(processor.split_to_drafts())
+        let processor = TextProcessor::new(self.pattern.clone(), text.to_string())
+            .with_exclusions(self.exclusions.clone());
+        let mut drafts = processor.split_to_drafts();
+        if !self.heading_patterns.is_empty() {
+            for draft in &mut drafts {
+                draft.apply_named_groups(&self.heading_patterns);
+            }
+        }
+        Ok(drafts)
     }
 }
 
-pub struct SimpleRulesStrategy;
+#[derive(Default)]
+pub struct SimpleRulesStrategy {
+    exclusions: Vec<Regex>,
+}
+
+impl SimpleRulesStrategy {
+    /// 标题行匹配任一排除规则时视为正文。
+    pub fn with_exclusions(mut self, exclusions: Vec<Regex>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+}
 
 impl ChapterSplitStrategy for SimpleRulesStrategy {
     fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
-        let processor = TextProcessor::new(Pattern::SimpleRules, text.to_string());
+        let processor = TextProcessor::new(Pattern::SimpleRules, text.to_string())
+            .with_exclusions(self.exclusions.clone());
+        Ok(processor.split_to_drafts())
+    }
+}
+
+/// 按 Markdown `#` / `##` 标题分章，标题文本去掉 `#` 前缀。
+#[derive(Default)]
+pub struct MarkdownSplitStrategy {
+    exclusions: Vec<Regex>,
+}
+
+impl MarkdownSplitStrategy {
+    /// 标题行匹配任一排除规则时视为正文。
+    pub fn with_exclusions(mut self, exclusions: Vec<Regex>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+}
+
+impl ChapterSplitStrategy for MarkdownSplitStrategy {
+    fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
+        let processor = TextProcessor::new(Pattern::MarkdownHeading, text.to_string())
+            .with_exclusions(self.exclusions.clone());
         Ok(processor.split_to_drafts())
     }
 }
 
+/// 多条正则按优先顺序组合：任一正则匹配的行都是章节标题，
+/// 多条正则在同一位置都能匹配时取排在前面的那条。
+///
+/// 各条正则的命名分组在合并时加上序号前缀以免重名，提取结构化标题时仍使用原正则。
+pub struct CompositeSplitStrategy {
+    inner: RegexSplitStrategy,
+}
+
+impl CompositeSplitStrategy {
+    pub fn new(patterns: &[Regex]) -> Result<Self, ConversionError> {
+        let union = patterns
+            .iter()
+            .enumerate()
+            .map(|(index, re)| {
+                let renamed = re
+                    .as_str()
+                    .replace("(?P<", &format!("(?P<_{index}_"))
+                    .replace("(?<", &format!("(?<_{index}_"));
+                format!("(?:{renamed})")
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+        let mut inner = RegexSplitStrategy::new(Pattern::Custom(Regex::new(&union)?));
+        inner.heading_patterns = if patterns.iter().any(has_heading_groups) {
+            patterns.to_vec()
+        } else {
+            Vec::new()
+        };
+        Ok(Self { inner })
+    }
+
+    /// 标题所在整行匹配任一排除规则时不在此处分章。
+    pub fn with_exclusions(self, exclusions: Vec<Regex>) -> Self {
+        Self {
+            inner: self.inner.with_exclusions(exclusions),
+        }
+    }
+}
+
+impl ChapterSplitStrategy for CompositeSplitStrategy {
+    fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
+        self.inner.split(text)
+    }
+}
+
+/// 用文本开头的内嵌目录分章：剥离目录块后，以目录中的标题逐行匹配章节。
+#[derive(Default)]
+pub struct TocSplitStrategy {
+    exclusions: Vec<Regex>,
+}
+
+impl TocSplitStrategy {
+    /// 标题行匹配任一排除规则时视为正文。
+    pub fn with_exclusions(mut self, exclusions: Vec<Regex>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+}
+
+impl ChapterSplitStrategy for TocSplitStrategy {
+    fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
+        let toc = InlineToc::detect(text).ok_or_else(|| {
+            ConversionError::InvalidInput(
+                "No table of contents (目录) was found at the top of the text.".to_string(),
+            )
+        })?;
+        let pattern = Pattern::Custom(Regex::new(&toc.pattern())?);
+        RegexSplitStrategy::new(pattern)
+            .with_exclusions(self.exclusions.clone())
+            .split(&toc.strip(text))
+    }
+}
+
+/// 启发式分章：不依赖正则，按行的特征打分，得分达到阈值的行视为章节标题。
+///
+/// 特征包括行长、是否含句读、是否含数字，以及前后是否为空行；
+/// 全文普遍用空行分段时空行不再提供信息，不计分。
+/// 连续三行以上都像标题的片段（诗歌、列表、目录）整体忽略。
+#[derive(Default)]
+pub struct HeuristicSplitStrategy {
+    exclusions: Vec<Regex>,
+}
+
+impl HeuristicSplitStrategy {
+    const MAX_TITLE_CHARS: usize = 30;
+    const SHORT_TITLE_CHARS: usize = 15;
+    /// 空行计分时的阈值；不计空行时少 1 分。
+    const THRESHOLD: u32 = 6;
+    /// 连续这么多行都像标题时视为列表而非标题。
+    const MAX_TITLE_RUN: usize = 3;
+
+    /// 标题行匹配任一排除规则时视为正文。
+    pub fn with_exclusions(mut self, exclusions: Vec<Regex>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    /// 返回判定为章节标题的行号。
+    pub fn headings(&self, lines: &[&str]) -> Vec<usize> {
+        let blank = |index: Option<usize>| {
+            index
+                .and_then(|index| lines.get(index))
+                .is_none_or(|line| line.trim().is_empty())
+        };
+        let non_empty: Vec<usize> = (0..lines.len())
+            .filter(|&index| !lines[index].trim().is_empty())
+            .collect();
+        let surrounded = non_empty
+            .iter()
+            .filter(|&&index| blank(index.checked_sub(1)) && blank(Some(index + 1)))
+            .count();
+        let use_blanks = surrounded * 2 <= non_empty.len();
+        let threshold = if use_blanks {
+            Self::THRESHOLD
+        } else {
+            Self::THRESHOLD - 1
+        };
+
+        let candidates: Vec<bool> = non_empty
+            .iter()
+            .map(|&index| {
+                let line = lines[index].trim();
+                let mut score = title_line_score(line);
+                if score > 0 && use_blanks {
+                    score += u32::from(blank(index.checked_sub(1)));
+                    score += u32::from(blank(Some(index + 1)));
+                }
+                score >= threshold && !self.exclusions.iter().any(|re| re.is_match(line))
+            })
+            .collect();
+
+        let mut headings = Vec::new();
+        let mut run_start = 0;
+        for position in 0..=candidates.len() {
+            if position < candidates.len() && candidates[position] {
+                continue;
+            }
+            if position - run_start < Self::MAX_TITLE_RUN {
+                headings.extend(non_empty[run_start..position].iter().copied());
+            }
+            run_start = position + 1;
+        }
+        headings
+    }
+}
+
+/// 行本身的标题特征得分；以句读结尾、过长或不含文字的行为 0。
+fn title_line_score(line: &str) -> u32 {
+    const SENTENCE_PUNCT: [char; 16] = [
+        '。', '，', '、', '；', '：', '？', '！', '…', '”', '」', '．', '～', '.', ',', '?', '!',
+    ];
+    let chars = line.chars().count();
+    if chars == 0
+        || chars > HeuristicSplitStrategy::MAX_TITLE_CHARS
+        || line.ends_with(SENTENCE_PUNCT)
+        || !line.chars().any(char::is_alphanumeric)
+    {
+        return 0;
+    }
+    let mut score = if chars <= HeuristicSplitStrategy::SHORT_TITLE_CHARS {
+        2
+    } else {
+        1
+    };
+    if !line.contains(SENTENCE_PUNCT) && !line.contains(['“', '「']) {
+        score += 2;
+    }
+    if line
+        .chars()
+        .any(|ch| ch.is_numeric() || "一二三四五六七八九十百千零〇两".contains(ch))
+    {
+        score += 2;
+    }
+    score
+}
+
+impl ChapterSplitStrategy for HeuristicSplitStrategy {
+    fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        let headings = self.headings(&lines);
+        let mut bounds = Vec::with_capacity(headings.len() + 1);
+        if headings.first() != Some(&0) {
+            bounds.push(0);
+        }
+        bounds.extend(&headings);
+
+        let mut chapters = Vec::new();
+        for (position, &start) in bounds.iter().enumerate() {
+            let end = bounds.get(position + 1).copied().unwrap_or(lines.len());
+            let raw = lines[start..end].join("\n");
+            if raw.trim().is_empty() {
+                continue;
+            }
+            let mut chapter = ChapterDraft::from_raw(raw.trim_start());
+            chapter.content = chapter.content.trim().to_string();
+            chapters.push(chapter);
+        }
+        Ok(chapters)
+    }
+}
+
+/// “从文件加载”的分章：先应用配置中的清理规则，再按配置的正则分章并标注卷。
+pub struct ConfigSplitStrategy {
+    inner: Box<dyn ChapterSplitStrategy>,
+    cleanup: Vec<CleanupRule>,
+    volumes: Vec<Regex>,
+}
+
+impl ConfigSplitStrategy {
+    /// `exclusions` 与配置中的排除规则合并使用。
+    pub fn new(config: &SplitConfig, exclusions: Vec<Regex>) -> Result<Self, ConversionError> {
+        config.validate()?;
+        let mut patterns = compile_patterns(config.patterns.iter().map(String::as_str))?;
+        let mut exclusions = exclusions;
+        exclusions.extend(compile_patterns(config.exclude.iter().map(String::as_str))?);
+        let inner: Box<dyn ChapterSplitStrategy> = if patterns.len() == 1 {
+            Box::new(
+                RegexSplitStrategy::new(Pattern::Custom(patterns.remove(0)))
+                    .with_exclusions(exclusions),
+            )
+        } else {
+            Box::new(CompositeSplitStrategy::new(&patterns)?.with_exclusions(exclusions))
+        };
+        Ok(Self {
+            inner,
+            cleanup: config.cleanup.clone(),
+            volumes: compile_patterns(config.volume_patterns.iter().map(String::as_str))?,
+        })
+    }
+}
+
+impl ChapterSplitStrategy for ConfigSplitStrategy {
+    fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
+        let text = apply_cleanup_rules(text, &self.cleanup)?;
+        let mut chapters = self.inner.split(&text)?;
+        if !self.volumes.is_empty() {
+            assign_volumes_by(&mut chapters, |title| {
+                self.volumes.iter().any(|re| re.is_match(title))
+            });
+        }
+        Ok(chapters)
+    }
+}
+
+/// 按字数分章时每章的默认目标字数。
+pub const DEFAULT_CHAPTER_LENGTH: usize = 5000;
+
+/// 按字数分章：在段落（行）边界处切分，每章累计到目标字数即结束。
+///
+/// 用于没有可识别标题的文档（随笔、访谈记录等），章节标题为序号。
+pub struct LengthSplitStrategy {
+    target: usize,
+}
+
+impl LengthSplitStrategy {
+    /// `target` 为每章目标字数（不含空白），至少为 1。
+    pub fn new(target: usize) -> Self {
+        Self {
+            target: target.max(1),
+        }
+    }
+}
+
+impl ChapterSplitStrategy for LengthSplitStrategy {
+    fn split(&self, text: &str) -> Result<Vec<ChapterDraft>, ConversionError> {
+        let mut chunks: Vec<Vec<&str>> = Vec::new();
+        let mut current = Vec::new();
+        let mut chars = 0;
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if current.is_empty() && line.trim().is_empty() {
+                continue;
+            }
+            current.push(line);
+            chars += count_chars(line);
+            if chars >= self.target {
+                chunks.push(std::mem::take(&mut current));
+                chars = 0;
+            }
+        }
+        if chars > 0 {
+            chunks.push(current);
+        }
+        Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, lines)| ChapterDraft {
+                title: (index + 1).to_string(),
+                content: lines.join("\n").trim_end().to_string(),
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+/// 分章方式之外的分章参数。
+#[derive(Debug, Clone, Copy)]
+pub struct SplitOptions<'a> {
+    /// 标题排除规则，空白规则忽略。
+    pub title_exclusions: &'a [String],
+    /// 按字数分章时每章的目标字数。
+    pub chapter_length: usize,
+}
+
+impl Default for SplitOptions<'_> {
+    fn default() -> Self {
+        Self {
+            title_exclusions: &[],
+            chapter_length: DEFAULT_CHAPTER_LENGTH,
+        }
+    }
+}
+
+/// 分章前对正文的预处理。章节编辑结果基于预处理后的文本，
+/// 这些设置变化后编辑结果即过期（见 [`crate::chapter_signature`]）。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Preprocessing<'a> {
+    /// 最先去掉开头的书名、作者信息块。
+    pub strip_text_header: bool,
+    /// 清理规则之前的不可见字符与多余空白清理。
+    pub clean_whitespace: bool,
+    /// 分章前按顺序应用的文本清理规则。
+    pub cleanup_rules: &'a [CleanupRule],
+    /// 清理规则之后的标点规范化。
+    pub punctuation: PunctuationNormalization,
+}
+
 pub struct StrategyFactory;
 
 impl StrategyFactory {
@@ -207,14 +1181,47 @@ impl StrategyFactory {
         custom_regex: &str,
         config_path: Option<&PathBuf>,
     ) -> Result<Box<dyn ChapterSplitStrategy>, ConversionError> {
+        Self::create_with_options(method, custom_regex, config_path, &SplitOptions::default())
+    }
+
+    /// 与 `create` 相同，并附加标题排除规则（空白规则忽略）。
+    pub fn create_with_exclusions(
+        method: ConversionMethod,
+        custom_regex: &str,
+        config_path: Option<&PathBuf>,
+        title_exclusions: &[String],
+    ) -> Result<Box<dyn ChapterSplitStrategy>, ConversionError> {
+        let options = SplitOptions {
+            title_exclusions,
+            ..Default::default()
+        };
+        Self::create_with_options(method, custom_regex, config_path, &options)
+    }
+
+    /// 按完整的分章参数创建策略。
+    pub fn create_with_options(
+        method: ConversionMethod,
+        custom_regex: &str,
+        config_path: Option<&PathBuf>,
+        options: &SplitOptions<'_>,
+    ) -> Result<Box<dyn ChapterSplitStrategy>, ConversionError> {
+        let exclusions = compile_patterns(options.title_exclusions.iter().map(String::as_str))?;
         match method {
             ConversionMethod::Regex => {
-                let pattern = if custom_regex.trim().is_empty() {
-                    Pattern::ChineseChapter
-                } else {
-                    Pattern::Custom(Regex::new(custom_regex.trim())?)
-                };
-                Ok(Box::new(RegexSplitStrategy::new(pattern)))
+                let mut patterns = compile_patterns(custom_regex.lines())?;
+                match patterns.len() {
+                    0 => Ok(Box::new(
+                        RegexSplitStrategy::new(Pattern::ChineseChapter)
+                            .with_exclusions(exclusions),
+                    )),
+                    1 => Ok(Box::new(
+                        RegexSplitStrategy::new(Pattern::Custom(patterns.remove(0)))
+                            .with_exclusions(exclusions),
+                    )),
+                    _ => Ok(Box::new(
+                        CompositeSplitStrategy::new(&patterns)?.with_exclusions(exclusions),
+                    )),
+                }
             }
             ConversionMethod::CustomConfig => {
                 let path = config_path.ok_or_else(|| {
@@ -222,34 +1229,153 @@ impl StrategyFactory {
                         "Please choose a valid regex config file.".to_string(),
                     )
                 })?;
-                let regex_str = std::fs::read_to_string(path)?;
-                let pattern = Pattern::Custom(Regex::new(regex_str.trim())?);
-                Ok(Box::new(RegexSplitStrategy::new(pattern)))
+                let config = SplitConfig::load(path)?;
+                Ok(Box::new(ConfigSplitStrategy::new(&config, exclusions)?))
+            }
+            ConversionMethod::SimpleRules => Ok(Box::new(
+                SimpleRulesStrategy::default().with_exclusions(exclusions),
+            )),
+            ConversionMethod::MarkdownHeadings => Ok(Box::new(
+                MarkdownSplitStrategy::default().with_exclusions(exclusions),
+            )),
+            ConversionMethod::ByLength => {
+                Ok(Box::new(LengthSplitStrategy::new(options.chapter_length)))
+            }
+            ConversionMethod::FromToc => Ok(Box::new(
+                TocSplitStrategy::default().with_exclusions(exclusions),
+            )),
+            ConversionMethod::Heuristic => Ok(Box::new(
+                HeuristicSplitStrategy::default().with_exclusions(exclusions),
+            )),
+        }
+    }
+}
+
+/// 编译逐条给出的正则，跳过空白条目。
+fn compile_patterns<'a>(
+    patterns: impl Iterator<Item = &'a str>,
+) -> Result<Vec<Regex>, ConversionError> {
+    Ok(patterns
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(Regex::new)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn convert_chapters_script(
+    conversion: ChineseConversion,
+    chapters: &mut [ChapterDraft],
+    cancel: &AtomicBool,
+) -> Result<(), ConversionError> {
+    for chapter in chapters {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(ConversionError::Cancelled);
+        }
+        chapter.title = conversion.convert(&chapter.title);
+        chapter.content = conversion.convert(&chapter.content);
+        if let Some(volume) = chapter.volume.as_mut() {
+            *volume = conversion.convert(volume);
+        }
+        if let Some(heading) = chapter.heading.as_mut() {
+            for part in [&mut heading.number, &mut heading.title]
+                .into_iter()
+                .flatten()
+            {
+                *part = conversion.convert(part);
             }
-            ConversionMethod::SimpleRules => Ok(Box::new(SimpleRulesStrategy)),
         }
     }
+    Ok(())
+}
+
+/// 统一标题、卷名与结构化标题编号中的数字写法，使目录与正文标题一致。
+fn convert_title_numerals(numerals: TitleNumerals, chapters: &mut [ChapterDraft]) {
+    for chapter in chapters {
+        chapter.title = numerals.convert(&chapter.title);
+        if let Some(volume) = chapter.volume.as_mut() {
+            *volume = numerals.convert(volume);
+        }
+        if let Some(number) = chapter
+            .heading
+            .as_mut()
+            .and_then(|heading| heading.number.as_mut())
+        {
+            *number = numerals.convert(number);
+        }
+    }
+}
+
+/// ISBN、日期与语言代码不含汉字，无需转换。
+fn convert_book_info_script(conversion: ChineseConversion, info: &mut BookInfo) {
+    for creator in &mut info.creators {
+        creator.name = conversion.convert(&creator.name);
+    }
+    for field in [
+        &mut info.title,
+        &mut info.publisher,
+        &mut info.category,
+        &mut info.description,
+        &mut info.series,
+    ] {
+        *field = conversion.convert(field);
+    }
 }
 
 pub struct ConversionFacade;
 
 impl ConversionFacade {
+    /// 执行一次转换；`req.cancel` 置位后会在下一个检查点中止。
     pub fn convert(req: ConversionRequest) -> Result<ConversionResult, ConversionError> {
+        Self::run(req, &mut |_| {})
+    }
+
+    /// 与 `convert` 相同，但每进入一个阶段、每渲染一章都调用 `on_progress`。
+    ///
+    /// 回调在转换线程中同步执行；界面可在回调里把事件发往通道，命令行可直接打印。
+    pub fn convert_with_progress(
+        req: ConversionRequest,
+        mut on_progress: impl FnMut(ConversionProgress),
+    ) -> Result<ConversionResult, ConversionError> {
+        Self::run(req, &mut on_progress)
+    }
+
+    fn run(
+        mut req: ConversionRequest,
+        on_progress: &mut dyn FnMut(ConversionProgress),
+    ) -> Result<ConversionResult, ConversionError> {
+        let cancel_token = req.cancel.clone();
+        let cancel = &*cancel_token.0;
         if req.text.trim().is_empty() {
             return Err(ConversionError::InvalidInput(
                 "Text content is empty.".to_string(),
             ));
         }
 
-        let chapters = if let Some(chapters) = req.chapters_override {
+        on_progress(ConversionProgress::Splitting);
+        let mut whitespace = None;
+        let mut chapters = if let Some(chapters) = req.chapters_override {
             chapters
         } else {
-            let strategy = StrategyFactory::create(
+            let strategy = StrategyFactory::create_with_options(
                 req.method,
                 &req.custom_regex,
                 req.custom_config_path.as_ref(),
+                &SplitOptions {
+                    title_exclusions: &req.title_exclusions,
+                    chapter_length: req.chapter_length,
+                },
             )?;
-            strategy.split(&req.text)?
+            let text = strip_text_header_if(&req.text, req.strip_text_header);
+            let text = if req.clean_whitespace {
+                let (text, summary) = clean_whitespace(text);
+                whitespace = Some(summary);
+                Cow::Owned(text)
+            } else {
+                Cow::Borrowed(text)
+            };
+            let text = apply_cleanup_rules(&text, &req.cleanup_rules)?;
+            let text = req.punctuation.normalize(&text);
+            strategy.split(&text)?
         };
 
         if chapters.is_empty() {
@@ -257,28 +1383,187 @@ impl ConversionFacade {
                 "No chapters detected.".to_string(),
             ));
         }
+        if cancel.load(Ordering::Relaxed) {
+            return Err(ConversionError::Cancelled);
+        }
+        if req.back_matter.from_text
+            && let Some((title, text)) = take_back_matter(&mut chapters)
+        {
+            if req.back_matter.title.trim().is_empty() {
+                req.back_matter.title = title;
+            }
+            req.back_matter.text = text;
+        }
+        if req.toc_options.nest_volumes {
+            assign_volumes(&mut chapters);
+        }
+        if req.title_numerals != TitleNumerals::Keep {
+            convert_title_numerals(req.title_numerals, &mut chapters);
+        }
+        if req.chinese_conversion != ChineseConversion::None {
+            let conversion = req.chinese_conversion;
+            convert_chapters_script(conversion, &mut chapters, cancel)?;
+            convert_book_info_script(conversion, &mut req.book_info);
+            req.toc_options.toc_title_override =
+                conversion.convert(&req.toc_options.toc_title_override);
+            let front_matter = &mut req.front_matter;
+            for text in [
+                &mut front_matter.copyright,
+                &mut front_matter.dedication,
+                &mut front_matter.preface,
+                &mut req.back_matter.title,
+                &mut req.back_matter.text,
+            ] {
+                *text = conversion.convert(text);
+            }
+            for image in &mut req.images {
+                if let Some(caption) = image.caption.as_mut() {
+                    *caption = conversion.convert(caption);
+                }
+            }
+        }
+        if let Some(generation) = &req.generated_cover {
+            let png = generation
+                .render(&req.book_info, &req.fonts)
+                .map_err(|err| {
+                    ConversionError::InvalidInput(format!("Cover generation failed: {err}"))
+                })?;
+            req.cover = Some(ImageAsset {
+                name: "cover.png".to_string(),
+                bytes: Bytes::from(png),
+                mime: "image/png".to_string(),
+                caption: None,
+                chapter: None,
+            });
+        }
+        on_progress(ConversionProgress::Split {
+            chapters: chapters.len(),
+        });
+        if cancel.load(Ordering::Relaxed) {
+            return Err(ConversionError::Cancelled);
+        }
+        let mut report = ConversionReport {
+            whitespace,
+            ..ConversionReport::from_chapters(&chapters)
+        };
+
+        if req.output_format == OutputFormat::Pdf {
+            if req.in_memory {
+                return Err(ConversionError::InvalidInput(
+                    "PDF output needs a file system; choose EPUB in the browser.".to_string(),
+                ));
+            }
+            let options = PdfBuildOptions {
+                book_info: req.book_info,
+                output_dir: req.output_dir,
+                filename_template: req.filename_template,
+                style: req.style,
+                fonts: req.fonts,
+                pdf: req.pdf,
+                include_generator_meta: req.include_generator_meta,
+                overwrite_policy: req.overwrite_policy,
+            };
+            let output = build_pdf_with_progress(
+                &chapters,
+                &options,
+                &mut |event| on_progress(event.into()),
+                cancel,
+            )?;
+            return Ok(ConversionResult {
+                output_path: output.output_path,
+                warnings: output.warnings,
+                image_sizes: Vec::new(),
+                validation: Vec::new(),
+                epubcheck: None,
+                bytes: None,
+                report,
+            });
+        }
 
-        let output_path = EpubPlanBuilder::new(req.book_info)
+        report.images = req.images.len()
+            + usize::from(req.cover.is_some())
+            + usize::from(req.chapter_header_image.is_some());
+        let plan = EpubPlanBuilder::new(req.book_info)
             .output_dir(req.output_dir)
             .filename_template(req.filename_template)
             .style(req.style)
             .cover(req.cover)
             .images(req.images)
-            .font(req.font)
+            .fonts(req.fonts)
             .chapter_header_image(req.chapter_header_image)
             .chapter_header_fullbleed(req.chapter_header_fullbleed)
             .include_images_section(req.include_images_section)
             .toc_options(req.toc_options)
-            .build(&chapters)?;
+            .front_matter(req.front_matter)
+            .back_matter(req.back_matter)
+            .include_generator_meta(req.include_generator_meta)
+            .safe_mode(req.safe_mode)
+            .epub_version(req.epub_version)
+            .rtl_page_progression(req.rtl_page_progression)
+            .kepub(req.kepub)
+            .reproducible(req.reproducible)
+            .compression(req.compression)
+            .split_chapter_bytes(req.split_chapter_bytes)
+            .spine_order(req.spine_order)
+            .source_format(req.source_format)
+            .image_optimization(req.image_optimization)
+            .overwrite_policy(req.overwrite_policy);
+        let mut on_build = |event: BuildProgress| on_progress(event.into());
+
+        if req.in_memory {
+            let (output, bytes) =
+                plan.build_in_memory_with_progress(&chapters, &mut on_build, cancel)?;
+            return Ok(ConversionResult {
+                output_path: output.output_path,
+                warnings: output.warnings,
+                image_sizes: output.image_sizes,
+                validation: validate_epub_bytes(&bytes),
+                epubcheck: None,
+                bytes: Some(bytes),
+                report,
+            });
+        }
 
-        Ok(ConversionResult { output_path })
+        let output = plan.build_with_progress(&chapters, &mut on_build, cancel)?;
+        let validation = validate_epub(Path::new(&output.output_path)).unwrap_or_else(|err| {
+            vec![ValidationIssue {
+                path: String::new(),
+                message: format!("validation could not read the output: {err}"),
+            }]
+        });
+
+        let mut warnings = output.warnings;
+        let epubcheck = req.epubcheck_path.as_deref().and_then(|epubcheck| {
+            run_epubcheck(epubcheck, Path::new(&output.output_path))
+                .map_err(|err| warnings.push(format!("epubcheck did not run: {err}")))
+                .ok()
+        });
+
+        Ok(ConversionResult {
+            output_path: output.output_path,
+            warnings,
+            image_sizes: output.image_sizes,
+            validation,
+            epubcheck,
+            bytes: None,
+            report,
+        })
+    }
+
+    /// 按顺序转换批量请求中的全部文件，单个失败不会中断后续条目。
+    pub fn convert_batch(batch: &BatchRequest) -> Vec<Result<ConversionResult, ConversionError>> {
+        batch
+            .inputs
+            .iter()
+            .map(|path| batch.convert_item(path, |_| {}))
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
+    use crate::{ChapterHeading, Creator, CreatorRole};
     use std::time::{SystemTime, UNIX_EPOCH};
 
     #[test]
@@ -291,6 +1576,169 @@ mod tests {
         assert_eq!(chapters.len(), 2);
     }
 
+    #[test]
+    fn regex_lines_combine_in_priority_order() {
+        let text = "第一卷 风起\n第1章 开始\n内容\n番外 后日谈\n尾声";
+        let strategy = StrategyFactory::create(
+            ConversionMethod::Regex,
+            "(?m)^第.卷.*\n\n(?m)^第\\d+章.*\n(?m)^番外.*",
+            None,
+        )
+        .expect("strategy");
+        let titles: Vec<_> = strategy
+            .split(text)
+            .expect("split")
+            .into_iter()
+            .map(|chapter| chapter.title)
+            .collect();
+        assert_eq!(titles, ["第一卷 风起", "第1章 开始", "番外 后日谈"]);
+
+        assert!(matches!(
+            StrategyFactory::create(ConversionMethod::Regex, "(?m)^第\n(", None),
+            Err(ConversionError::Regex(_))
+        ));
+    }
+
+    #[test]
+    fn title_exclusions_apply_to_every_split_method() {
+        let text = "第一章 开始\n详见第二章 附录\n内容\n第二章 附录\n结束";
+        let exclusions = ["^详见".to_string()];
+        for method in [ConversionMethod::Regex, ConversionMethod::SimpleRules] {
+            let strategy = StrategyFactory::create_with_exclusions(method, "", None, &exclusions)
+                .expect("strategy");
+            let chapters = strategy.split(text).expect("split");
+            let titles: Vec<_> = chapters
+                .iter()
+                .map(|chapter| chapter.title.as_str())
+                .collect();
+            assert_eq!(titles, ["第一章 开始", "第二章 附录"], "{method:?}");
+            assert!(chapters[0].content.contains("详见第二章 附录"));
+        }
+
+        let markdown = "# 一\n正文\n# 目录\n# 二\n正文";
+        let strategy = StrategyFactory::create_with_exclusions(
+            ConversionMethod::MarkdownHeadings,
+            "",
+            None,
+            &["目录".to_string()],
+        )
+        .expect("strategy");
+        let titles: Vec<_> = strategy
+            .split(markdown)
+            .expect("split")
+            .into_iter()
+            .map(|chapter| chapter.title)
+            .collect();
+        assert_eq!(titles, ["一", "二"]);
+
+        assert!(matches!(
+            StrategyFactory::create_with_exclusions(
+                ConversionMethod::Regex,
+                "",
+                None,
+                &["(".to_string()]
+            ),
+            Err(ConversionError::Regex(_))
+        ));
+    }
+
+    #[test]
+    fn length_split_breaks_between_paragraphs() {
+        let text = "\n一二三四\n五六\n\n七八九十\n十一\n";
+        let strategy = StrategyFactory::create_with_options(
+            ConversionMethod::ByLength,
+            "",
+            None,
+            &SplitOptions {
+                chapter_length: 5,
+                ..Default::default()
+            },
+        )
+        .expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        let parts: Vec<_> = chapters
+            .iter()
+            .map(|chapter| (chapter.title.as_str(), chapter.content.as_str()))
+            .collect();
+        assert_eq!(parts, [("1", "一二三四\n五六"), ("2", "七八九十\n十一")]);
+
+        // 单个段落超过目标字数时整段成章，不在段落中间切开。
+        let chapters = LengthSplitStrategy::new(2)
+            .split("甲乙丙丁\n戊")
+            .expect("split");
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].content, "甲乙丙丁");
+        assert!(
+            LengthSplitStrategy::new(10)
+                .split("  \n")
+                .expect("split")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn toc_split_uses_inline_contents() {
+        let text = "书名\n目录\n楔子\n风起\n\n楔子\n开头。\n风起\n正文。";
+        let strategy =
+            StrategyFactory::create(ConversionMethod::FromToc, "", None).expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["书名", "楔子", "风起"]);
+
+        assert!(matches!(
+            strategy.split("第一章\n正文。"),
+            Err(ConversionError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn heuristic_split_finds_irregular_titles() {
+        // 全文以空行分段，空行不计分；“好”和对话不是标题。
+        let text = "楔子\n\n很久以前，有一座山。\n\n第1章 出发\n\n他说：“走吧。”\n\n好\n\n第2章 归来\n\n结束了。";
+        let strategy =
+            StrategyFactory::create(ConversionMethod::Heuristic, "", None).expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["楔子", "第1章 出发", "第2章 归来"]);
+        assert_eq!(chapters[1].content, "他说：“走吧。”\n\n好");
+
+        // 不以空行分段时，前后空行是标题的信号；连续的短行不算标题。
+        let text =
+            "正文第一段。\n\n幕间\n\n正文第二段。\n一行白鹭\n两个黄鹂\n三更灯火\n正文第三段。";
+        let strategy = HeuristicSplitStrategy::default();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(strategy.headings(&lines), [2]);
+    }
+
+    #[test]
+    fn named_groups_fill_structured_headings() {
+        let text = "序\n第一卷：第1章:开始\n内容\n第2章  继续\n更多\nEp.3 Rain\n尾";
+        let regex = "(?m)^(?:(?P<vol>第.卷)：)?(?P<num>第\\d+章)[:\\s]*(?P<title>.*)$\n\
+                     (?m)^(?P<num>Ep\\.\\d+)\\s+(?P<title>.+)$";
+        let strategy =
+            StrategyFactory::create(ConversionMethod::Regex, regex, None).expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["序", "第1章 开始", "第2章 继续", "Ep.3 Rain"]);
+        assert_eq!(chapters[0].heading, None);
+        assert_eq!(chapters[1].volume.as_deref(), Some("第一卷"));
+        assert_eq!(chapters[2].volume, None);
+        assert_eq!(
+            chapters[3].heading,
+            Some(ChapterHeading {
+                number: Some("Ep.3".to_string()),
+                title: Some("Rain".to_string()),
+            })
+        );
+
+        // 没有命名分组时标题保持原样。
+        let strategy = StrategyFactory::create(ConversionMethod::Regex, "(?m)^第\\d+章.*$", None)
+            .expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        assert_eq!(chapters[1].title, "第2章  继续");
+        assert!(chapters.iter().all(|c| c.heading.is_none()));
+    }
+
     #[test]
     fn custom_config_without_path_is_error() {
         let err = StrategyFactory::create(ConversionMethod::CustomConfig, "", None)
@@ -302,6 +1750,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn custom_config_reads_toml_and_legacy_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-split-config-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).expect("dir");
+        let toml_path = dir.join("split.toml");
+        std::fs::write(
+            &toml_path,
+            r#"
+patterns = ['(?m)^第.卷.*', '(?m)^第\d+章.*']
+exclude = ['见下章']
+volume_patterns = ['^第.卷']
+
+[[cleanup]]
+find = "【广告】\n"
+"#,
+        )
+        .expect("write config");
+        let text = "第一卷 风起\n第1章 开始\n【广告】\n第2章 见下章\n第2章 继续\n内容";
+        let strategy =
+            StrategyFactory::create(ConversionMethod::CustomConfig, "", Some(&toml_path))
+                .expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["第一卷 风起", "第1章 开始", "第2章 继续"]);
+        assert!(!chapters[1].content.contains("广告"));
+        assert_eq!(chapters[0].volume, None);
+        assert_eq!(chapters[2].volume.as_deref(), Some("第一卷 风起"));
+
+        let legacy_path = dir.join("legacy.txt");
+        std::fs::write(&legacy_path, "(?m)^第\\d+章.*\n").expect("write legacy");
+        let strategy =
+            StrategyFactory::create(ConversionMethod::CustomConfig, "", Some(&legacy_path))
+                .expect("strategy");
+        assert_eq!(strategy.split(text).expect("split").len(), 4);
+
+        std::fs::write(&toml_path, "patterns = '^第'").expect("write invalid");
+        assert!(matches!(
+            StrategyFactory::create(ConversionMethod::CustomConfig, "", Some(&toml_path)),
+            Err(ConversionError::InvalidInput(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn simple_rules_strategy_splits_chapters() {
         let text = "第1章 开始\n内容\n\n第2章 继续\n更多";
@@ -322,6 +1819,712 @@ mod tests {
         assert_eq!(chapters.len(), 2);
     }
 
+    #[test]
+    fn markdown_headings_strategy_uses_heading_text_as_title() {
+        let text = "Intro line\n\n# Chapter One\nFirst body\n\n## Chapter Two\nSecond body\n#hashtag stays";
+        let strategy = StrategyFactory::create(ConversionMethod::MarkdownHeadings, "", None)
+            .expect("strategy");
+        let chapters = strategy.split(text).expect("split");
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[0].title, "Intro line");
+        assert_eq!(chapters[1].title, "Chapter One");
+        assert_eq!(chapters[1].content, "First body");
+        assert_eq!(chapters[2].title, "Chapter Two");
+        assert_eq!(chapters[2].content, "Second body\n#hashtag stays");
+    }
+
+    #[test]
+    fn batch_conversion_applies_settings_per_file() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("reasypub-batch-{suffix}"));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let first = dir.join("甲.txt");
+        let second = dir.join("乙.md");
+        let empty = dir.join("丙.txt");
+        std::fs::write(&first, "第1章 开始\n内容").expect("write");
+        std::fs::write(&second, "# One\nbody").expect("write");
+        std::fs::write(&empty, "").expect("write");
+
+        let batch = BatchRequest::new(
+            vec![first, second.clone(), empty],
+            ConversionRequest {
+                output_dir: dir.clone(),
+                filename_template: "{书名}.epub".to_string(),
+                book_info: BookInfo {
+                    creators: vec![Creator::new("作者", CreatorRole::Author)],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let request = batch.request_for(&second).expect("request");
+        assert_eq!(request.method, ConversionMethod::MarkdownHeadings);
+        assert_eq!(request.book_info.title, "乙");
+
+        let results = ConversionFacade::convert_batch(&batch);
+        assert_eq!(results.len(), 3);
+        let first_path = results[0].as_ref().expect("first converts");
+        assert!(first_path.output_path.ends_with("甲.epub"));
+        assert!(results[1].is_ok());
+        assert!(results[2].is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn batch_requests_decode_legacy_encodings() {
+        let dir =
+            std::env::temp_dir().join(format!("reasypub-batch-encoding-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("旧文.txt");
+        let (gbk, _, _) = encoding_rs::GBK.encode("第1章 开始\n中文内容");
+        std::fs::write(&path, &gbk).expect("write");
+
+        let batch = BatchRequest::new(vec![path.clone()], ConversionRequest::default());
+        let request = batch.request_for(&path).expect("request");
+        assert_eq!(request.text, "第1章 开始\n中文内容");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn batch_requests_drop_per_book_details() {
+        let dir = std::env::temp_dir().join(format!("reasypub-batch-books-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let first = dir.join("甲.txt");
+        let second = dir.join("乙.txt");
+        for path in [&first, &second] {
+            std::fs::write(path, "第1章 开始\n内容").expect("write");
+        }
+        let image = ImageAsset {
+            name: "map.png".to_string(),
+            bytes: bytes::Bytes::from_static(b"img"),
+            mime: "image/png".to_string(),
+            caption: None,
+            chapter: None,
+        };
+        let batch = BatchRequest::new(
+            vec![first.clone(), second.clone()],
+            ConversionRequest {
+                book_info: BookInfo {
+                    title: "当前书".to_string(),
+                    creators: vec![Creator::new("作者", CreatorRole::Author)],
+                    identifier: "urn:uuid:6f2d3c7e-58a4-4b0f-9a51-0f1c2b3d4e5f".to_string(),
+                    isbn: "9787020002207".to_string(),
+                    description: "当前书的简介".to_string(),
+                    series: "系列".to_string(),
+                    series_index: "2".to_string(),
+                    publisher: "出版社".to_string(),
+                    ..Default::default()
+                },
+                images: vec![image],
+                front_matter: FrontMatter {
+                    copyright: "{书名}".to_string(),
+                    dedication: "献给某人".to_string(),
+                    preface: "前言".to_string(),
+                    ..Default::default()
+                },
+                back_matter: BackMatter {
+                    text: "后记".to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let mut events = Vec::new();
+        let missing = batch.convert_item(&dir.join("缺失.txt"), |event| events.push(event));
+        assert!(missing.is_err());
+        assert_eq!(events, [ConversionProgress::Decoding]);
+
+        let a = batch.request_for(&first).expect("first request");
+        let b = batch.request_for(&second).expect("second request");
+        assert_ne!(
+            a.book_info.uuid().expect("first id"),
+            b.book_info.uuid().expect("second id")
+        );
+        for request in [&a, &b] {
+            let info = &request.book_info;
+            assert!(info.identifier.is_empty() && info.isbn.is_empty());
+            assert!(info.description.is_empty() && info.series.is_empty());
+            assert!(info.series_index.is_empty());
+            assert!(request.images.is_empty());
+            assert!(request.front_matter.dedication.is_empty());
+            assert!(request.front_matter.preface.is_empty());
+            assert!(request.back_matter.text.is_empty());
+            // 共享的设置保留。
+            assert_eq!(info.publisher, "出版社");
+            assert_eq!(request.front_matter.copyright, "{书名}");
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversion_respects_overwrite_policy() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("reasypub-overwrite-{suffix}"));
+        let convert = |policy| {
+            ConversionFacade::convert(ConversionRequest {
+                text: "第1章 开始\n内容".to_string(),
+                output_dir: dir.clone(),
+                filename_template: "书.epub".to_string(),
+                overwrite_policy: policy,
+                ..Default::default()
+            })
+        };
+        let first = convert(OverwritePolicy::Ask).expect("first export");
+        assert!(first.output_path.ends_with("书.epub"));
+        match convert(OverwritePolicy::Ask) {
+            Err(ConversionError::Build(BuildError::OutputExists(path))) => {
+                assert_eq!(path, dir.join("书.epub"));
+            }
+            other => panic!("expected OutputExists, got {:?}", other.err()),
+        }
+        let numbered = convert(OverwritePolicy::Number).expect("numbered");
+        assert!(numbered.output_path.ends_with("书 (1).epub"));
+        let numbered = convert(OverwritePolicy::Number).expect("numbered again");
+        assert!(numbered.output_path.ends_with("书 (2).epub"));
+        let overwritten = convert(OverwritePolicy::Overwrite).expect("overwrite");
+        assert_eq!(overwritten.output_path, first.output_path);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn report_counts_chapters_words_and_outliers() {
+        let chapter = |title: &str, content: &str| ChapterDraft {
+            title: title.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let long = "长".repeat(400);
+        let chapters = vec![
+            chapter("第1章", "今天天气很好。"),
+            chapter("第2章", "Hello world, 你好。"),
+            chapter("第3章", "他说：我们走吧"),
+            chapter("第4章", &long),
+        ];
+        let report = ConversionReport::from_chapters(&chapters);
+        assert_eq!(report.chapters, 4);
+        assert_eq!(count_chars("Hello world, 你好。"), 14);
+        assert_eq!(count_words("Hello world, 你好。"), 4);
+        assert_eq!(count_words("第1章"), 3);
+        assert_eq!(
+            report.chars,
+            chapters
+                .iter()
+                .map(|c| count_chars(&c.title) + count_chars(&c.content))
+                .sum::<usize>()
+        );
+        assert_eq!(report.outliers.len(), 1);
+        assert_eq!(report.outliers[0].index, 3);
+        assert_eq!(report.outliers[0].kind, OutlierKind::Long);
+
+        let short = ConversionReport::from_chapters(&[
+            chapter("一", &"字".repeat(100)),
+            chapter("二", &"字".repeat(120)),
+            chapter("三", "短"),
+        ]);
+        assert_eq!(short.outliers.len(), 1);
+        assert_eq!(short.outliers[0].kind, OutlierKind::Short);
+        assert!(
+            ConversionReport::from_chapters(&chapters[..2])
+                .outliers
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn chapter_stats_estimate_reading_time() {
+        let stats = ChapterStats::of(&ChapterDraft {
+            title: "第1章".to_string(),
+            content: "字".repeat(798),
+            ..Default::default()
+        });
+        assert_eq!(
+            stats,
+            ChapterStats {
+                chars: 801,
+                words: 801
+            }
+        );
+        assert_eq!(stats.reading_minutes(), 3);
+        assert_eq!(ChapterStats::default().reading_minutes(), 0);
+        let total: ChapterStats = [stats, stats].into_iter().sum();
+        assert_eq!(total.chars, 1602);
+        assert_eq!(
+            ConversionReport::outlier_kinds(&[100, 120, 2000]),
+            vec![None, None, Some(OutlierKind::Long)]
+        );
+    }
+
+    #[test]
+    fn in_memory_conversion_returns_bytes_without_writing() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-memory-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容".to_string(),
+            output_dir: dir.clone(),
+            filename_template: "{书名}.epub".to_string(),
+            book_info: BookInfo {
+                title: "内存".to_string(),
+                ..Default::default()
+            },
+            in_memory: true,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req.clone()).expect("convert");
+        assert_eq!(result.output_path, "内存.epub");
+        let bytes = result.bytes.expect("epub bytes");
+        assert!(bytes.starts_with(b"PK"));
+        assert!(result.validation.is_empty(), "{:?}", result.validation);
+        assert!(!dir.exists());
+        assert_eq!(result.report.chapters, 1);
+        assert_eq!(result.report.images, 0);
+
+        let pdf = ConversionRequest {
+            output_format: OutputFormat::Pdf,
+            ..req
+        };
+        assert!(matches!(
+            ConversionFacade::convert(pdf),
+            Err(ConversionError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn convert_with_progress_reports_stages() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-progress-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容\n第2章 继续\n更多".to_string(),
+            output_dir: dir.clone(),
+            filename_template: "progress".to_string(),
+            ..Default::default()
+        };
+        let mut events = Vec::new();
+        let result = ConversionFacade::convert_with_progress(req, |event| events.push(event))
+            .expect("convert");
+        assert!(Path::new(&result.output_path).exists());
+
+        assert_eq!(events.first(), Some(&ConversionProgress::Splitting));
+        assert!(events.contains(&ConversionProgress::Split { chapters: 2 }));
+        assert!(events.contains(&ConversionProgress::ChapterRendered { done: 2, total: 2 }));
+        assert_eq!(events.last(), Some(&ConversionProgress::Writing));
+        assert!(
+            events
+                .windows(2)
+                .all(|pair| pair[0].fraction() <= pair[1].fraction())
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cancelled_conversion_leaves_no_output() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-cancel-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容".to_string(),
+            output_dir: dir.clone(),
+            filename_template: "cancelled".to_string(),
+            ..Default::default()
+        };
+        req.cancel.clone().cancel();
+        assert!(req.cancel.is_cancelled());
+        let err = ConversionFacade::convert(req).err().expect("cancelled");
+        assert!(matches!(err, ConversionError::Cancelled));
+        assert!(!dir.join("cancelled.epub").exists());
+
+        // 渲染途中取消：第一章渲染完成后置位，剩余章节不再渲染。
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容\n第2章 继续\n更多\n第3章 结束\n完".to_string(),
+            output_dir: dir.clone(),
+            filename_template: "cancelled".to_string(),
+            ..Default::default()
+        };
+        let cancel = req.cancel.clone();
+        let mut rendered = 0;
+        let err = ConversionFacade::convert_with_progress(req, |event| {
+            if let ConversionProgress::ChapterRendered { done, .. } = event {
+                rendered = done;
+                cancel.cancel();
+            }
+        })
+        .err()
+        .expect("cancelled");
+        assert!(matches!(err, ConversionError::Cancelled));
+        assert_eq!(rendered, 1);
+        assert!(!dir.join("cancelled.epub").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversion_applies_cleanup_rules_before_split() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-cleanup-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容\n第99章 本书由某某网首发\n第2章 继续\n更多".to_string(),
+            output_dir: dir.clone(),
+            filename_template: "cleanup".to_string(),
+            cleanup_rules: vec![CleanupRule {
+                find: r"^.*某某网首发.*\n".to_string(),
+                regex: true,
+                ..Default::default()
+            }],
+            toc_options: TocOptions {
+                insert_toc_page: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        let file = std::fs::File::open(&result.output_path).expect("open epub");
+        let archive = zip::ZipArchive::new(file).expect("zip");
+        let chapters = archive
+            .file_names()
+            .filter(|name| name.contains("chapter_"))
+            .count();
+        assert_eq!(chapters, 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversion_normalizes_punctuation_before_split() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-punctuation-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 开始\n他说, \"走吧\"。。。\n第2章 继续\n好!".to_string(),
+            output_dir: dir.clone(),
+            filename_template: "punctuation".to_string(),
+            punctuation: PunctuationNormalization::FullWidth,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        let file = std::fs::File::open(&result.output_path).expect("open epub");
+        let mut archive = zip::ZipArchive::new(file).expect("zip");
+        let mut all = String::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).expect("entry");
+            if entry.name().contains("chapter_") {
+                entry.read_to_string(&mut all).expect("read entry");
+            }
+        }
+        assert!(all.contains("他说，“走吧”……"), "{all}");
+        assert!(all.contains("好！"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversion_rewrites_title_numerals_in_toc() {
+        use std::io::Read;
+
+        let req = ConversionRequest {
+            text: "第一百零三章 重逢\n内容\n第一百零四章 告别\n完".to_string(),
+            title_numerals: TitleNumerals::Arabic,
+            in_memory: true,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        let bytes = result.bytes.expect("epub bytes");
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("zip");
+        let mut ncx = String::new();
+        archive
+            .by_name("OEBPS/toc.ncx")
+            .expect("toc.ncx")
+            .read_to_string(&mut ncx)
+            .expect("read entry");
+        assert!(ncx.contains("第103章 重逢"), "{ncx}");
+        assert!(ncx.contains("第104章 告别"), "{ncx}");
+    }
+
+    #[test]
+    fn conversion_cleans_whitespace_before_split() {
+        let text = "\u{feff}第1章 开始  \n内容\u{ad}\n\n\n\n\u{200b}第2章 继续\n好\n".to_string();
+        let req = ConversionRequest {
+            text: text.clone(),
+            in_memory: true,
+            ..Default::default()
+        };
+        // 零宽空格使第二章标题无法匹配。
+        let result = ConversionFacade::convert(req).expect("convert");
+        assert_eq!(result.report.chapters, 1);
+        assert_eq!(result.report.whitespace, None);
+
+        let req = ConversionRequest {
+            text,
+            clean_whitespace: true,
+            in_memory: true,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        assert_eq!(result.report.chapters, 2);
+        let summary = result.report.whitespace.expect("summary");
+        assert_eq!(
+            (summary.bom, summary.zero_width, summary.soft_hyphens),
+            (1, 1, 1)
+        );
+        assert_eq!((summary.trailing_spaces, summary.blank_lines), (2, 2));
+    }
+
+    #[test]
+    fn merged_files_split_into_nested_volumes() {
+        let text = crate::merge::merge_volumes([
+            ("风起", "第1章 开始\n内容\n第2章 继续\n好\n"),
+            ("云涌", "第3章 重逢\n正文\n"),
+        ]);
+        let mut chapters = StrategyFactory::create(ConversionMethod::Regex, "", None)
+            .expect("strategy")
+            .split(&text)
+            .expect("split");
+        assign_volumes(&mut chapters);
+        let volumes: Vec<_> = chapters
+            .iter()
+            .map(|chapter| (chapter.title.as_str(), chapter.volume.as_deref()))
+            .collect();
+        assert_eq!(
+            volumes,
+            [
+                ("第一卷 风起", None),
+                ("第1章 开始", Some("第一卷 风起")),
+                ("第2章 继续", Some("第一卷 风起")),
+                ("第二卷 云涌", None),
+                ("第3章 重逢", Some("第二卷 云涌")),
+            ]
+        );
+    }
+
+    #[test]
+    fn conversion_strips_text_header_before_split() {
+        let text = "书名：雾镇\n作者：某人\n\n第1章 开始\n内容\n第2章 继续\n好\n".to_string();
+        let req = ConversionRequest {
+            text: text.clone(),
+            in_memory: true,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        assert_eq!(result.report.chapters, 3);
+
+        let req = ConversionRequest {
+            text,
+            strip_text_header: true,
+            in_memory: true,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        assert_eq!(result.report.chapters, 2);
+    }
+
+    #[test]
+    fn conversion_moves_trailing_afterword_to_back_matter() {
+        use std::io::Read;
+
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容\n第2章 结束\n完\n后记\n感谢《{书名}》的读者。".to_string(),
+            book_info: BookInfo {
+                title: "长夜".to_string(),
+                ..Default::default()
+            },
+            images: vec![ImageAsset {
+                name: "a.png".to_string(),
+                bytes: Bytes::from_static(b"png"),
+                mime: "image/png".to_string(),
+                caption: None,
+                chapter: None,
+            }],
+            back_matter: BackMatter {
+                from_text: true,
+                ..Default::default()
+            },
+            in_memory: true,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        assert_eq!(result.report.chapters, 2);
+        let bytes = result.bytes.expect("epub bytes");
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("zip");
+        let mut read = |name: &str| {
+            let mut text = String::new();
+            archive
+                .by_name(name)
+                .expect(name)
+                .read_to_string(&mut text)
+                .expect("read entry");
+            text
+        };
+        let afterword = read("OEBPS/afterword.xhtml");
+        assert!(afterword.contains("<h2>后记</h2>"));
+        assert!(afterword.contains("<p>感谢《长夜》的读者。</p>"));
+        assert!(!read("OEBPS/chapter_0002.xhtml").contains("感谢"));
+        let opf = read("OEBPS/content.opf");
+        let gallery = opf.find("href=\"images.xhtml\"").expect("gallery");
+        let back = opf.find("href=\"afterword.xhtml\"").expect("afterword");
+        assert!(gallery < back);
+        assert!(read("OEBPS/toc.ncx").contains("<text>后记</text>"));
+    }
+
+    #[test]
+    fn conversion_applies_chinese_conversion_to_titles_and_metadata() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-zhconv-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 龙门\n这里的头发\n第2章 后来\n继续".to_string(),
+            book_info: BookInfo {
+                title: "龙门客栈".to_string(),
+                creators: vec![Creator::new("张三", CreatorRole::Author)],
+                ..Default::default()
+            },
+            output_dir: dir.clone(),
+            filename_template: "zhconv".to_string(),
+            chinese_conversion: ChineseConversion::S2T,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        let file = std::fs::File::open(&result.output_path).expect("open epub");
+        let mut archive = zip::ZipArchive::new(file).expect("zip");
+        let mut all = String::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).expect("entry");
+            if entry.name().ends_with(".xhtml") || entry.name().ends_with(".opf") {
+                entry.read_to_string(&mut all).expect("read entry");
+            }
+        }
+        for expected in ["龍門客棧", "張三", "第1章 龍門", "這裏的頭髮", "第2章 後來"]
+        {
+            assert!(all.contains(expected), "missing {expected}");
+        }
+        assert!(!all.contains("龙门"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversion_output_passes_self_check() {
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-self-check-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        for (index, template) in [crate::CssTemplate::Classic, crate::CssTemplate::Fantasy]
+            .into_iter()
+            .enumerate()
+        {
+            for epub_version in [EpubVersion::Epub2, EpubVersion::Epub3] {
+                let req = ConversionRequest {
+                    text: "第1章 开始\n内容[^1]\n\n[^1]: 注释\n第2章 继续\n“引号”&<>".to_string(),
+                    book_info: BookInfo {
+                        title: "自检".to_string(),
+                        ..Default::default()
+                    },
+                    output_dir: dir.clone(),
+                    filename_template: format!("check_{index}_{epub_version:?}"),
+                    style: TextStyle {
+                        css_template: template,
+                        ..Default::default()
+                    },
+                    epub_version,
+                    generated_cover: Some(CoverGeneration::default()),
+                    ..Default::default()
+                };
+                let result = ConversionFacade::convert(req).expect("convert");
+                assert_eq!(
+                    result.validation,
+                    Vec::new(),
+                    "{template:?} {epub_version:?}"
+                );
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversion_generates_cover_image() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir().join(format!(
+            "reasypub-generated-cover-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let req = ConversionRequest {
+            text: "第1章 开始\n内容".to_string(),
+            book_info: BookInfo {
+                title: "封面测试".to_string(),
+                creators: vec![Creator::new("作者", CreatorRole::Author)],
+                ..Default::default()
+            },
+            output_dir: dir.clone(),
+            filename_template: "cover".to_string(),
+            generated_cover: Some(CoverGeneration::default()),
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(req).expect("convert");
+        let file = std::fs::File::open(&result.output_path).expect("open epub");
+        let mut archive = zip::ZipArchive::new(file).expect("zip");
+        let mut png = Vec::new();
+        archive
+            .by_name("OEBPS/cover.png")
+            .expect("cover entry")
+            .read_to_end(&mut png)
+            .expect("read cover");
+        let cover = image::load_from_memory(&png).expect("decode cover");
+        assert_eq!(
+            (cover.width(), cover.height()),
+            (crate::cover::COVER_WIDTH, crate::cover::COVER_HEIGHT)
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn invalid_custom_regex_is_error() {
         let err = StrategyFactory::create(ConversionMethod::Regex, "(", None)
@@ -346,7 +2549,7 @@ mod tests {
             style: TextStyle::default(),
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             chapters_override: None,
@@ -355,6 +2558,7 @@ mod tests {
                 insert_toc_page: false,
                 ..Default::default()
             },
+            ..Default::default()
         };
         let err = ConversionFacade::convert(req).err().expect("error");
         match err {
@@ -376,7 +2580,7 @@ mod tests {
             style: TextStyle::default(),
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             chapters_override: Some(Vec::new()),
@@ -385,6 +2589,7 @@ mod tests {
                 insert_toc_page: false,
                 ..Default::default()
             },
+            ..Default::default()
         };
         let err = ConversionFacade::convert(req).err().expect("error");
         match err {
@@ -403,6 +2608,7 @@ mod tests {
         let chapter = ChapterDraft {
             title: "Chapter 1".to_string(),
             content: "Hello".to_string(),
+            ..Default::default()
         };
         let req = ConversionRequest {
             text: "content".to_string(),
@@ -415,7 +2621,7 @@ mod tests {
             style: TextStyle::default(),
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             chapters_override: Some(vec![chapter]),
@@ -424,6 +2630,7 @@ mod tests {
                 insert_toc_page: false,
                 ..Default::default()
             },
+            ..Default::default()
         };
         let result = ConversionFacade::convert(req).expect("convert");
         assert!(!result.output_path.is_empty());
@@ -495,18 +2702,20 @@ mod tests {
             style: TextStyle::default(),
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             chapters_override: Some(vec![ChapterDraft {
                 title: "Chapter 1".to_string(),
                 content: "Hello".to_string(),
+                ..Default::default()
             }]),
             include_images_section: false,
             toc_options: TocOptions {
                 insert_toc_page: false,
                 ..Default::default()
             },
+            ..Default::default()
         };
         let err = ConversionFacade::convert(req).err().expect("error");
         match err {
@@ -0,0 +1,549 @@
+//! 封面生成：把 `BookInfo` 中的书名、作者与出版社排版到背景上，输出 PNG 封面图。
+//!
+//! 背景可选纯色、上下渐变或用户上传的纹理图片（按封面比例裁切填满）。
+//! 书名过长时自动换行并逐级缩小字号；中日韩文字逐字断行，西文在空格处断行。
+//!
+//! 上传的封面图可通过 [`fit_cover`] 裁切或补边到指定宽高比（如 Kindle 常用的 2:3）。
+
+use std::io::Cursor;
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{BookInfo, FontAsset, FontRole, Key, Locale, t};
+
+/// 生成封面的宽度（像素），比例为常见的 2:3。
+pub const COVER_WIDTH: u32 = 1200;
+/// 生成封面的高度（像素）。
+pub const COVER_HEIGHT: u32 = 1800;
+
+/// 应用内置的中文字体，未指定字体时用于排版封面。
+pub const BUNDLED_FONT: &[u8] = include_bytes!("../assets/stdg-regular.ttf");
+
+/// 行首不宜出现的标点：换行时让它们留在上一行末尾。
+pub(crate) const NO_LINE_START: &str = "，。、！？；：）》」』】〕…,.!?;:)]";
+
+/// 封面背景类型。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverBackground {
+    /// 使用 `CoverDesign::primary` 纯色填充。
+    #[default]
+    Solid,
+    /// 自上而下由 `primary` 渐变到 `secondary`。
+    Gradient,
+    /// 上传的纹理图片，叠加一层半透明 `primary` 以保证文字可读。
+    Texture,
+}
+
+impl CoverBackground {
+    pub const ALL: [CoverBackground; 3] = [
+        CoverBackground::Solid,
+        CoverBackground::Gradient,
+        CoverBackground::Texture,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Solid => t(locale, Key::CoverBackgroundSolid),
+            Self::Gradient => t(locale, Key::CoverBackgroundGradient),
+            Self::Texture => t(locale, Key::CoverBackgroundTexture),
+        }
+    }
+}
+
+/// 生成封面的配色与背景设置。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct CoverDesign {
+    pub background: CoverBackground,
+    pub primary: egui::Color32,
+    pub secondary: egui::Color32,
+    pub text_color: egui::Color32,
+}
+
+impl Default for CoverDesign {
+    fn default() -> Self {
+        Self {
+            background: CoverBackground::Solid,
+            primary: egui::Color32::from_rgb(38, 52, 78),
+            secondary: egui::Color32::from_rgb(122, 74, 58),
+            text_color: egui::Color32::from_rgb(246, 240, 228),
+        }
+    }
+}
+
+/// 封面比例不符时的处理方式。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverFitMode {
+    /// 裁掉多出的部分，`CoverFit::anchor` 决定保留区域的位置。
+    #[default]
+    Crop,
+    /// 在短边两侧补上 `CoverFit::pad_color`，保留完整画面。
+    Pad,
+}
+
+impl CoverFitMode {
+    pub const ALL: [CoverFitMode; 2] = [CoverFitMode::Crop, CoverFitMode::Pad];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Crop => t(locale, Key::CoverFitCrop),
+            Self::Pad => t(locale, Key::CoverFitPad),
+        }
+    }
+}
+
+/// 上传封面的宽高比调整设置。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct CoverFit {
+    pub enabled: bool,
+    pub mode: CoverFitMode,
+    /// 目标宽高比的宽度部分，如 2:3 中的 2。
+    pub ratio_width: u32,
+    /// 目标宽高比的高度部分，如 2:3 中的 3。
+    pub ratio_height: u32,
+    /// 裁切时保留区域的位置：0 为左/上，0.5 居中，1 为右/下。
+    pub anchor: f32,
+    pub pad_color: egui::Color32,
+}
+
+impl CoverFit {
+    /// 常用封面比例：Kindle / 多数商店的 2:3、3:4、5:8 与正方形。
+    pub const PRESETS: [(u32, u32); 4] = [(2, 3), (3, 4), (5, 8), (1, 1)];
+}
+
+impl Default for CoverFit {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: CoverFitMode::Crop,
+            ratio_width: 2,
+            ratio_height: 3,
+            anchor: 0.5,
+            pad_color: egui::Color32::WHITE,
+        }
+    }
+}
+
+/// 按 `fit` 把封面裁切或补边到目标宽高比，返回新的图片数据与扩展名。
+///
+/// JPEG 输入仍输出 JPEG，其余格式输出 PNG；比例已符合时只重新编码。
+pub fn fit_cover(bytes: &[u8], fit: &CoverFit) -> Result<(Vec<u8>, &'static str), String> {
+    let format = image::guess_format(bytes).map_err(|err| err.to_string())?;
+    let image =
+        image::load_from_memory_with_format(bytes, format).map_err(|err| err.to_string())?;
+    let (width, height) = (image.width(), image.height());
+    let ratio = fit.ratio_width.max(1) as f64 / fit.ratio_height.max(1) as f64;
+    let anchor = fit.anchor.clamp(0.0, 1.0) as f64;
+
+    let fitted = match fit.mode {
+        CoverFitMode::Crop => {
+            if width as f64 / height as f64 > ratio {
+                let target = ((height as f64 * ratio).round() as u32).clamp(1, width);
+                let x = ((width - target) as f64 * anchor).round() as u32;
+                image.crop_imm(x, 0, target, height)
+            } else {
+                let target = ((width as f64 / ratio).round() as u32).clamp(1, height);
+                let y = ((height - target) as f64 * anchor).round() as u32;
+                image.crop_imm(0, y, width, target)
+            }
+        }
+        CoverFitMode::Pad => {
+            let (canvas_width, canvas_height) = if width as f64 / height as f64 > ratio {
+                (width, ((width as f64 / ratio).round() as u32).max(height))
+            } else {
+                (((height as f64 * ratio).round() as u32).max(width), height)
+            };
+            let mut canvas =
+                RgbaImage::from_pixel(canvas_width, canvas_height, rgba(fit.pad_color, 255));
+            image::imageops::overlay(
+                &mut canvas,
+                &image.to_rgba8(),
+                ((canvas_width - width) / 2) as i64,
+                ((canvas_height - height) / 2) as i64,
+            );
+            DynamicImage::ImageRgba8(canvas)
+        }
+    };
+
+    let mut output = Vec::new();
+    if format == ImageFormat::Jpeg {
+        DynamicImage::ImageRgb8(fitted.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut output, 92))
+            .map_err(|err| err.to_string())?;
+        Ok((output, "jpg"))
+    } else {
+        fitted
+            .write_with_encoder(PngEncoder::new(Cursor::new(&mut output)))
+            .map_err(|err| err.to_string())?;
+        Ok((output, "png"))
+    }
+}
+
+/// 转换时生成封面的设置。
+///
+/// 封面在分章与简繁转换之后按最终的书籍信息渲染，批量转换中每本书各自生成。
+#[derive(Clone, Debug, Default)]
+pub struct CoverGeneration {
+    pub design: CoverDesign,
+    /// `CoverBackground::Texture` 使用的背景图片数据。
+    pub texture: Option<Bytes>,
+}
+
+impl CoverGeneration {
+    /// 渲染封面；优先使用承担标题、其次正文角色的嵌入字体，否则使用内置字体。
+    pub fn render(&self, info: &BookInfo, fonts: &[FontAsset]) -> Result<Vec<u8>, String> {
+        let font = [FontRole::Heading, FontRole::Body]
+            .iter()
+            .find_map(|role| fonts.iter().find(|font| font.roles.contains(role)))
+            .map_or(BUNDLED_FONT, |font| font.bytes.as_ref());
+        render_cover(info, &self.design, self.texture.as_deref(), font)
+    }
+}
+
+/// 渲染封面并编码为 PNG。
+///
+/// `texture` 仅在 `CoverBackground::Texture` 时使用，缺失时退回纯色背景；
+/// `font` 为 TrueType/OpenType 字体数据，通常传入 [`BUNDLED_FONT`] 或用户嵌入的字体。
+pub fn render_cover(
+    info: &BookInfo,
+    design: &CoverDesign,
+    texture: Option<&[u8]>,
+    font: &[u8],
+) -> Result<Vec<u8>, String> {
+    let font = FontRef::try_from_slice(font).map_err(|err| err.to_string())?;
+    let mut canvas = match (design.background, texture) {
+        (CoverBackground::Texture, Some(bytes)) => texture_background(bytes, design.primary)?,
+        (CoverBackground::Gradient, _) => gradient_background(design.primary, design.secondary),
+        _ => RgbaImage::from_pixel(COVER_WIDTH, COVER_HEIGHT, rgba(design.primary, 255)),
+    };
+
+    let width = COVER_WIDTH as f32;
+    let height = COVER_HEIGHT as f32;
+    let max_width = width * 0.8;
+    let color = design.text_color;
+
+    let title = info.title.trim();
+    let title = if title.is_empty() { "Untitled" } else { title };
+    let (title_size, title_lines) = fit_title(&font, title, max_width);
+    let line_height = title_size * 1.3;
+    let block_height = line_height * title_lines.len() as f32;
+    let mut y = height * 0.34 - block_height / 2.0;
+    for line in &title_lines {
+        draw_centered(&mut canvas, &font, line, title_size, y, color);
+        y += line_height;
+    }
+
+    let rule_y = (y + height * 0.04) as u32;
+    let rule_half = (width * 0.12) as u32;
+    for x in COVER_WIDTH / 2 - rule_half..COVER_WIDTH / 2 + rule_half {
+        for dy in 0..3 {
+            blend(&mut canvas, x, rule_y + dy, color, 0.6);
+        }
+    }
+
+    let author = info.author();
+    if !author.is_empty() {
+        let mut y = rule_y as f32 + height * 0.05;
+        for line in wrap_text(&font, &author, 56.0, max_width) {
+            draw_centered(&mut canvas, &font, &line, 56.0, y, color);
+            y += 56.0 * 1.3;
+        }
+    }
+
+    let publisher = info.publisher.trim();
+    if !publisher.is_empty() {
+        draw_centered(&mut canvas, &font, publisher, 40.0, height * 0.9, color);
+    }
+
+    let mut png = Vec::new();
+    canvas
+        .write_with_encoder(PngEncoder::new(Cursor::new(&mut png)))
+        .map_err(|err| err.to_string())?;
+    Ok(png)
+}
+
+fn rgba(color: egui::Color32, alpha: u8) -> Rgba<u8> {
+    Rgba([color.r(), color.g(), color.b(), alpha])
+}
+
+fn gradient_background(top: egui::Color32, bottom: egui::Color32) -> RgbaImage {
+    let mix = |a: u8, b: u8, t: f32| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    RgbaImage::from_fn(COVER_WIDTH, COVER_HEIGHT, |_, y| {
+        let t = y as f32 / (COVER_HEIGHT - 1) as f32;
+        Rgba([
+            mix(top.r(), bottom.r(), t),
+            mix(top.g(), bottom.g(), t),
+            mix(top.b(), bottom.b(), t),
+            255,
+        ])
+    })
+}
+
+fn texture_background(bytes: &[u8], tint: egui::Color32) -> Result<RgbaImage, String> {
+    let texture = image::load_from_memory(bytes).map_err(|err| err.to_string())?;
+    let mut canvas = texture
+        .resize_to_fill(COVER_WIDTH, COVER_HEIGHT, FilterType::Triangle)
+        .to_rgba8();
+    for y in 0..COVER_HEIGHT {
+        for x in 0..COVER_WIDTH {
+            blend(&mut canvas, x, y, tint, 0.45);
+        }
+    }
+    Ok(canvas)
+}
+
+/// 以 `alpha` 的不透明度把 `color` 叠加到像素上，越界坐标直接忽略。
+fn blend(canvas: &mut RgbaImage, x: u32, y: u32, color: egui::Color32, alpha: f32) {
+    let Some(pixel) = canvas.get_pixel_mut_checked(x, y) else {
+        return;
+    };
+    for (channel, value) in pixel.0.iter_mut().zip([color.r(), color.g(), color.b()]) {
+        *channel = (*channel as f32 * (1.0 - alpha) + value as f32 * alpha).round() as u8;
+    }
+}
+
+fn text_width(font: &FontRef<'_>, text: &str, size: f32) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for ch in text.chars() {
+        let id = scaled.glyph_id(ch);
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// 书名字号从大到小尝试，取第一个不超过三行的字号。
+fn fit_title(font: &FontRef<'_>, title: &str, max_width: f32) -> (f32, Vec<String>) {
+    let mut fitted = None;
+    for size in [128.0, 112.0, 96.0, 84.0, 72.0] {
+        let lines = wrap_text(font, title, size, max_width);
+        let fits = lines.len() <= 3;
+        fitted = Some((size, lines));
+        if fits {
+            break;
+        }
+    }
+    fitted.expect("at least one title size")
+}
+
+/// 按宽度贪心换行：中日韩文字可在任意字间断开，西文单词在空格处断开。
+fn wrap_text(font: &FontRef<'_>, text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for ch in text.chars() {
+        let mut candidate = line.clone();
+        candidate.push(ch);
+        if line.is_empty()
+            || NO_LINE_START.contains(ch)
+            || text_width(font, &candidate, size) <= max_width
+        {
+            line = candidate;
+            continue;
+        }
+        if ch.is_whitespace() {
+            lines.push(line.trim_end().to_string());
+            line = String::new();
+            continue;
+        }
+        // 西文单词中途超宽时，退回到最后一个空格处换行。
+        let break_at = (!is_cjk(ch))
+            .then(|| line.rfind(' '))
+            .flatten()
+            .filter(|&index| index > 0);
+        if let Some(index) = break_at {
+            let rest = line.split_off(index);
+            lines.push(line.trim_end().to_string());
+            line = rest.trim_start().to_string();
+            line.push(ch);
+        } else {
+            lines.push(line.trim_end().to_string());
+            line = ch.to_string();
+        }
+    }
+    if !line.trim().is_empty() {
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{2E80}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}' | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}' | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// 以 `top` 为行框顶部，水平居中绘制一行文字。
+fn draw_centered(
+    canvas: &mut RgbaImage,
+    font: &FontRef<'_>,
+    text: &str,
+    size: f32,
+    top: f32,
+    color: egui::Color32,
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let mut x = (COVER_WIDTH as f32 - text_width(font, text, size)) / 2.0;
+    let baseline = top + scaled.ascent();
+    let mut previous = None;
+    for ch in text.chars() {
+        let id = scaled.glyph_id(ch);
+        if let Some(previous) = previous {
+            x += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(scale, point(x, baseline));
+        x += scaled.h_advance(id);
+        previous = Some(id);
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let px = bounds.min.x + gx as f32;
+            let py = bounds.min.y + gy as f32;
+            if px >= 0.0 && py >= 0.0 {
+                blend(canvas, px as u32, py as u32, color, coverage.min(1.0));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Creator, CreatorRole};
+
+    fn decode(png: &[u8]) -> RgbaImage {
+        image::load_from_memory(png).expect("decode").to_rgba8()
+    }
+
+    #[test]
+    fn render_cover_draws_text_on_solid_background() {
+        let info = BookInfo {
+            title: "星落之夜".to_string(),
+            creators: vec![Creator::new("某某", CreatorRole::Author)],
+            publisher: "测试出版社".to_string(),
+            ..Default::default()
+        };
+        let design = CoverDesign::default();
+        let image = decode(&render_cover(&info, &design, None, BUNDLED_FONT).expect("render"));
+        assert_eq!(image.dimensions(), (COVER_WIDTH, COVER_HEIGHT));
+        let background = rgba(design.primary, 255);
+        assert_eq!(*image.get_pixel(0, 0), background);
+        let text_pixels = image.pixels().filter(|pixel| **pixel != background).count();
+        assert!(text_pixels > 10_000, "{text_pixels}");
+    }
+
+    #[test]
+    fn render_cover_gradient_and_texture_backgrounds() {
+        let info = BookInfo::default();
+        let design = CoverDesign {
+            background: CoverBackground::Gradient,
+            primary: egui::Color32::from_rgb(0, 0, 0),
+            secondary: egui::Color32::from_rgb(255, 255, 255),
+            ..Default::default()
+        };
+        let image = decode(&render_cover(&info, &design, None, BUNDLED_FONT).expect("render"));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(
+            *image.get_pixel(0, COVER_HEIGHT - 1),
+            Rgba([255, 255, 255, 255])
+        );
+
+        let mut texture = Vec::new();
+        RgbaImage::from_pixel(30, 20, Rgba([255, 0, 0, 255]))
+            .write_with_encoder(PngEncoder::new(Cursor::new(&mut texture)))
+            .expect("encode texture");
+        let design = CoverDesign {
+            background: CoverBackground::Texture,
+            primary: egui::Color32::from_rgb(0, 0, 0),
+            ..Default::default()
+        };
+        let image =
+            decode(&render_cover(&info, &design, Some(&texture), BUNDLED_FONT).expect("render"));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([140, 0, 0, 255]));
+        assert!(render_cover(&info, &design, Some(b"broken"), BUNDLED_FONT).is_err());
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbaImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        })
+        .write_with_encoder(PngEncoder::new(Cursor::new(&mut bytes)))
+        .expect("encode png");
+        bytes
+    }
+
+    #[test]
+    fn fit_cover_crops_to_ratio_at_anchor() {
+        let fit = CoverFit {
+            enabled: true,
+            anchor: 0.0,
+            ..Default::default()
+        };
+        let (bytes, extension) = fit_cover(&png(400, 300), &fit).expect("fit");
+        assert_eq!(extension, "png");
+        let image = decode(&bytes);
+        assert_eq!(image.dimensions(), (200, 300));
+        assert_eq!(*image.get_pixel(199, 0), Rgba([255, 0, 0, 255]));
+
+        let fit = CoverFit { anchor: 1.0, ..fit };
+        let image = decode(&fit_cover(&png(400, 300), &fit).expect("fit").0);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+
+        let image = decode(&fit_cover(&png(100, 300), &fit).expect("fit").0);
+        assert_eq!(image.dimensions(), (100, 150));
+    }
+
+    #[test]
+    fn fit_cover_pads_with_color() {
+        let fit = CoverFit {
+            enabled: true,
+            mode: CoverFitMode::Pad,
+            pad_color: egui::Color32::from_rgb(0, 255, 0),
+            ..Default::default()
+        };
+        let image = decode(&fit_cover(&png(400, 300), &fit).expect("fit").0);
+        assert_eq!(image.dimensions(), (400, 600));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 255, 0, 255]));
+        assert_eq!(*image.get_pixel(0, 300), Rgba([255, 0, 0, 255]));
+
+        let mut jpeg = Vec::new();
+        DynamicImage::ImageRgb8(image::RgbImage::new(90, 90))
+            .write_with_encoder(JpegEncoder::new(&mut jpeg))
+            .expect("encode jpeg");
+        let (bytes, extension) = fit_cover(&jpeg, &fit).expect("fit");
+        assert_eq!(extension, "jpg");
+        assert_eq!(decode(&bytes).dimensions(), (90, 135));
+        assert!(fit_cover(b"broken", &fit).is_err());
+    }
+
+    #[test]
+    fn wrap_text_breaks_cjk_and_latin_words() {
+        let font = FontRef::try_from_slice(BUNDLED_FONT).expect("font");
+        let width = text_width(&font, "字字字字", 100.0);
+        let lines = wrap_text(&font, "一二三四五六七八，九", 100.0, width);
+        assert_eq!(lines, vec!["一二三四", "五六七八，", "九"]);
+
+        let width = text_width(&font, "The quick brown", 100.0);
+        let lines = wrap_text(&font, "The quick brown fox jumps", 100.0, width);
+        assert_eq!(lines, vec!["The quick brown", "fox jumps"]);
+    }
+}
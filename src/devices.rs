@@ -0,0 +1,245 @@
+//! 识别已挂载的电子书阅读器（Kindle、Kobo 的 USB 存储），并把导出的文件复制到设备上。
+//!
+//! 设备按根目录下的特征目录识别：Kindle 有 `documents` 与 `system`，Kobo 有 `.kobo`。
+//! 目标目录由每种设备各自的路径模板决定，模板相对设备根目录，可使用文件名模板的变量。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::epubworker::sanitize_filename_component;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    Kindle,
+    Kobo,
+}
+
+impl DeviceKind {
+    pub const ALL: [Self; 2] = [Self::Kindle, Self::Kobo];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Kindle => "Kindle",
+            Self::Kobo => "Kobo",
+        }
+    }
+
+    /// 按根目录下的特征目录判断设备类型。
+    pub fn detect(root: &Path) -> Option<Self> {
+        if root.join(".kobo").is_dir() {
+            Some(Self::Kobo)
+        } else if root.join("documents").is_dir() && root.join("system").is_dir() {
+            Some(Self::Kindle)
+        } else {
+            None
+        }
+    }
+}
+
+/// 一台已挂载的阅读器。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountedDevice {
+    pub kind: DeviceKind,
+    pub root: PathBuf,
+}
+
+impl MountedDevice {
+    /// 设备类型加卷名，如 `Kindle (KINDLE)`。
+    pub fn name(&self) -> String {
+        match self.root.file_name() {
+            Some(volume) => format!("{} ({})", self.kind.label(), volume.to_string_lossy()),
+            None => format!("{} ({})", self.kind.label(), self.root.display()),
+        }
+    }
+}
+
+/// 各设备的目标目录模板。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DevicePaths {
+    pub kindle: String,
+    pub kobo: String,
+}
+
+impl Default for DevicePaths {
+    fn default() -> Self {
+        Self {
+            kindle: "documents".to_string(),
+            kobo: "{作者}".to_string(),
+        }
+    }
+}
+
+impl DevicePaths {
+    pub fn template(&self, kind: DeviceKind) -> &str {
+        match kind {
+            DeviceKind::Kindle => &self.kindle,
+            DeviceKind::Kobo => &self.kobo,
+        }
+    }
+
+    pub fn template_mut(&mut self, kind: DeviceKind) -> &mut String {
+        match kind {
+            DeviceKind::Kindle => &mut self.kindle,
+            DeviceKind::Kobo => &mut self.kobo,
+        }
+    }
+}
+
+/// 列出当前已挂载、能识别出类型的阅读器。
+pub fn mounted_devices() -> Vec<MountedDevice> {
+    mount_points()
+        .into_iter()
+        .filter_map(|root| DeviceKind::detect(&root).map(|kind| MountedDevice { kind, root }))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn mount_points() -> Vec<PathBuf> {
+    ('D'..='Z')
+        .map(|letter| PathBuf::from(format!("{letter}:\\")))
+        .filter(|root| root.is_dir())
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn mount_points() -> Vec<PathBuf> {
+    subdirectories(Path::new("/Volumes"))
+}
+
+/// Linux 等系统上，可移动存储挂载在 `/media/<用户>/<卷名>`、`/run/media/<用户>/<卷名>`
+/// 或直接挂在 `/media`、`/mnt` 下，因此检查两层子目录。
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_arch = "wasm32")))]
+fn mount_points() -> Vec<PathBuf> {
+    ["/media", "/run/media", "/mnt"]
+        .iter()
+        .flat_map(|base| subdirectories(Path::new(base)))
+        .flat_map(|dir| {
+            let mut roots = subdirectories(&dir);
+            roots.insert(0, dir);
+            roots
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn mount_points() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// 把已展开变量的目录模板转换为设备上的目录：按 `/` 或 `\` 分段，逐段去掉非法字符，
+/// 忽略空段与 `.`、`..`，结果总在设备根目录之内。
+pub fn device_folder(root: &Path, folder: &str) -> PathBuf {
+    folder
+        .split(['/', '\\'])
+        .map(sanitize_filename_component)
+        .filter(|part| !part.is_empty() && part != "." && part != "..")
+        .fold(root.to_path_buf(), |path, part| path.join(part))
+}
+
+/// 把 `file` 复制到设备的目标目录（目录不存在时创建，同名文件直接覆盖），返回目标路径。
+pub fn copy_to_device(
+    device: &MountedDevice,
+    folder: &str,
+    file: &Path,
+) -> Result<PathBuf, String> {
+    let name = file
+        .file_name()
+        .ok_or_else(|| format!("{} is not a file", file.display()))?;
+    let dir = device_folder(&device.root, folder);
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let target = dir.join(name);
+    std::fs::copy(file, &target).map_err(|err| format!("{}: {err}", target.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_root(prefix: &str) -> PathBuf {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        std::env::temp_dir().join(format!("{prefix}-{suffix}"))
+    }
+
+    #[test]
+    fn detect_recognizes_kindle_and_kobo_volumes() {
+        let root = temp_root("reasypub-device");
+        let kindle = root.join("KINDLE");
+        let kobo = root.join("KOBOeReader");
+        for dir in [
+            kindle.join("documents"),
+            kindle.join("system"),
+            kobo.join(".kobo"),
+            root.join("USB").join("documents"),
+        ] {
+            std::fs::create_dir_all(dir).expect("create dir");
+        }
+
+        assert_eq!(DeviceKind::detect(&kindle), Some(DeviceKind::Kindle));
+        assert_eq!(DeviceKind::detect(&kobo), Some(DeviceKind::Kobo));
+        assert_eq!(DeviceKind::detect(&root.join("USB")), None);
+        let device = MountedDevice {
+            kind: DeviceKind::Kindle,
+            root: kindle,
+        };
+        assert_eq!(device.name(), "Kindle (KINDLE)");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn device_folder_stays_inside_device_root() {
+        let root = Path::new("/media/KOBO");
+        assert_eq!(device_folder(root, ""), root);
+        assert_eq!(
+            device_folder(root, "Books/刘慈欣"),
+            root.join("Books").join("刘慈欣")
+        );
+        assert_eq!(
+            device_folder(root, "../..\\a:b/./c"),
+            root.join("ab").join("c")
+        );
+    }
+
+    #[test]
+    fn copy_to_device_creates_target_folder() {
+        let root = temp_root("reasypub-copy");
+        std::fs::create_dir_all(root.join(".kobo")).expect("create dir");
+        let source = root.with_extension("epub");
+        std::fs::write(&source, b"epub").expect("write");
+        let device = MountedDevice {
+            kind: DeviceKind::Kobo,
+            root: root.clone(),
+        };
+
+        let target = copy_to_device(&device, "Books/作者", &source).expect("copy");
+        assert_eq!(
+            target,
+            root.join("Books")
+                .join("作者")
+                .join(source.file_name().unwrap())
+        );
+        assert_eq!(std::fs::read(&target).expect("read"), b"epub");
+
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_file(&source);
+    }
+}
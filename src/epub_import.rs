@@ -0,0 +1,961 @@
+//! 导入已有的 EPUB：读取元数据、封面与按 spine 排列的正文，还原为书籍信息与章节草稿，
+//! 以便重新排版、重新分章后再生成。
+//!
+//! 章节标题优先取目录（EPUB 3 导航文档或 NCX）中的条目，其次取正文的第一个标题；
+//! 目录中带子条目、正文为空的页面视为卷首页，其标题作为下属章节的卷名。
+//! 没有目录条目也没有标题的页面（如拆分过的长章节）并入上一章。
+//! 只保留文字：段落各占一行，`<hr>` 还原为场景分隔标记 `[scene]`，插图与行内样式不导入。
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use zip::ZipArchive;
+
+use crate::ocf::{
+    CONTAINER_PATH, ManifestItem, attribute, parent_dir, parse_opf, resolve_href, rootfile_path,
+};
+use crate::{BookInfo, ChapterDraft, Creator, CreatorRole};
+
+/// 导入结果。
+#[derive(Debug, Clone, Default)]
+pub struct ImportedEpub {
+    pub book_info: BookInfo,
+    pub chapters: Vec<ChapterDraft>,
+    /// 封面图片的文件名与内容。
+    pub cover: Option<(String, Vec<u8>)>,
+}
+
+impl ImportedEpub {
+    /// 把章节还原为以 Markdown `#` 标题分隔的纯文本，配合按 Markdown 标题分章即可重新分章。
+    pub fn source_text(&self) -> String {
+        self.chapters
+            .iter()
+            .map(|chapter| format!("# {}\n\n{}\n", chapter.title, chapter.content))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 导出为纯文本：开头是 `书名：` / `作者：` / `简介：` 信息块，其后每章以标题行开头，
+    /// 卷名在该卷第一章之前单独成行，场景分隔写作 `* * *`。按内置中文章节规则即可重新分章。
+    pub fn plain_text(&self) -> String {
+        let info = &self.book_info;
+        let mut text = String::new();
+        for (key, value) in [
+            ("书名", info.title.as_str()),
+            ("作者", info.author().as_str()),
+        ] {
+            if !value.trim().is_empty() {
+                text.push_str(&format!("{key}：{}\n", value.trim()));
+            }
+        }
+        let description: Vec<&str> = info
+            .description
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if !description.is_empty() {
+            text.push_str(&format!("简介：\n{}\n", description.join("\n")));
+        }
+        let mut volume = None;
+        for chapter in &self.chapters {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            if chapter.volume.is_some() && chapter.volume != volume {
+                text.push_str(chapter.volume.as_deref().unwrap_or_default());
+                text.push_str("\n\n");
+            }
+            volume.clone_from(&chapter.volume);
+            text.push_str(&chapter.title);
+            text.push('\n');
+            for line in chapter.content.lines() {
+                text.push_str(if line.trim() == "[scene]" {
+                    "* * *"
+                } else {
+                    line
+                });
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+/// 把 EPUB 导出为同名 TXT 写入 `output_dir`，返回写出的路径。
+pub fn extract_text(input: &Path, output_dir: &Path) -> Result<PathBuf, String> {
+    let imported = import_epub(input)?;
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "book".to_string());
+    let output = output_dir.join(format!("{stem}.txt"));
+    std::fs::write(&output, imported.plain_text())
+        .map_err(|err| format!("{}: {err}", output.display()))?;
+    Ok(output)
+}
+
+/// 读取并导入磁盘上的 EPUB 文件。
+pub fn import_epub(path: &Path) -> Result<ImportedEpub, String> {
+    let file = std::fs::File::open(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    import_archive(file)
+}
+
+/// 导入内存中的 EPUB 数据。
+pub fn import_epub_bytes(bytes: &[u8]) -> Result<ImportedEpub, String> {
+    import_archive(Cursor::new(bytes))
+}
+
+fn import_archive<R: Read + Seek>(reader: R) -> Result<ImportedEpub, String> {
+    let mut archive =
+        ZipArchive::new(reader).map_err(|err| format!("not an EPUB (ZIP) file: {err}"))?;
+    let container = read_entry(&mut archive, CONTAINER_PATH)?;
+    let opf_path = rootfile_path(&container)
+        .ok_or_else(|| format!("{CONTAINER_PATH}: no rootfile full-path found"))?;
+    let opf = read_entry(&mut archive, &opf_path)?;
+    let opf_dir = parent_dir(&opf_path);
+    let (manifest, spine) = parse_opf(&opf, opf_dir);
+    let package = parse_package(&opf, opf_dir);
+
+    let toc = read_toc(&mut archive, &manifest, &package);
+    let parents: Vec<&str> = toc
+        .iter()
+        .zip(toc.iter().skip(1))
+        .filter(|(entry, next)| next.depth > entry.depth)
+        .map(|(entry, _)| entry.path.as_str())
+        .collect();
+
+    let mut chapters: Vec<ChapterDraft> = Vec::new();
+    let mut volume: Option<String> = None;
+    for idref in &spine {
+        let Some(item) = manifest.iter().find(|item| &item.id == idref) else {
+            continue;
+        };
+        let is_page = matches!(
+            item.media_type.as_str(),
+            "application/xhtml+xml" | "text/html"
+        );
+        if !is_page
+            || item.properties.split_whitespace().any(|p| p == "nav")
+            || package.skipped_pages.contains(&item.path)
+        {
+            continue;
+        }
+        let Ok(xhtml) = read_entry(&mut archive, &item.path) else {
+            continue;
+        };
+        let page = extract_page(&xhtml);
+        let entry = toc.iter().find(|entry| entry.path == item.path);
+        if let Some(entry) = entry {
+            if parents.contains(&entry.path.as_str()) {
+                volume = Some(entry.label.clone());
+                if page.paragraphs.is_empty() {
+                    continue;
+                }
+            } else if entry.depth == 1 {
+                volume = None;
+            }
+        }
+        if page.paragraphs.is_empty() && page.heading.is_none() {
+            continue;
+        }
+        if entry.is_none()
+            && page.heading.is_none()
+            && !toc.is_empty()
+            && let Some(last) = chapters.last_mut()
+        {
+            if !last.content.is_empty() {
+                last.content.push('\n');
+            }
+            last.content.push_str(&page.paragraphs.join("\n"));
+            continue;
+        }
+        let is_parent = entry.is_some_and(|entry| parents.contains(&entry.path.as_str()));
+        let title = entry
+            .map(|entry| entry.label.clone())
+            .or(page.heading)
+            .or(page.title)
+            .unwrap_or_else(|| {
+                Path::new(&item.path)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+        chapters.push(ChapterDraft {
+            title,
+            content: page.paragraphs.join("\n"),
+            volume: volume.clone().filter(|_| !is_parent),
+            ..ChapterDraft::default()
+        });
+    }
+    if chapters.is_empty() {
+        return Err("no readable text found in the EPUB".to_string());
+    }
+
+    let cover = package
+        .cover_id
+        .as_ref()
+        .and_then(|id| manifest.iter().find(|item| &item.id == id))
+        .or_else(|| {
+            manifest.iter().find(|item| {
+                item.properties
+                    .split_whitespace()
+                    .any(|p| p == "cover-image")
+            })
+        })
+        .filter(|item| item.media_type.starts_with("image/"))
+        .and_then(|item| {
+            let bytes = read_bytes(&mut archive, &item.path).ok()?;
+            let name = item.path.rsplit('/').next().unwrap_or(&item.path);
+            Some((name.to_string(), bytes))
+        });
+
+    Ok(ImportedEpub {
+        book_info: package.book_info,
+        chapters,
+        cover,
+    })
+}
+
+fn read_bytes<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|err| format!("{name}: {err}"))?;
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|err| format!("{name}: {err}"))?;
+    Ok(bytes)
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<String, String> {
+    let bytes = read_bytes(archive, name)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// OPF 中除清单与 spine 以外用到的信息。
+#[derive(Default)]
+struct Package {
+    book_info: BookInfo,
+    cover_id: Option<String>,
+    /// spine 中 NCX 的清单 id。
+    ncx_id: Option<String>,
+    /// `<guide>` 中标为封面、目录或扉页的页面，不作为章节导入。
+    skipped_pages: Vec<String>,
+}
+
+/// `<metadata>` 中的一个元素。
+struct MetaElement {
+    name: String,
+    attributes: HashMap<String, String>,
+    text: String,
+}
+
+impl MetaElement {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+}
+
+fn parse_package(opf: &str, opf_dir: &str) -> Package {
+    let mut package = Package::default();
+    let mut unique_id = String::new();
+    let mut elements = Vec::new();
+    let mut in_metadata = false;
+    let mut reader = Reader::from_str(opf);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => match start.local_name().as_ref() {
+                b"package" => {
+                    unique_id = attribute(&start, "unique-identifier").unwrap_or_default()
+                }
+                b"metadata" => in_metadata = true,
+                _ if in_metadata => {
+                    let attributes = attributes(&start);
+                    let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                    let text = reader
+                        .read_text(start.name())
+                        .map(|raw| plain_text(&raw))
+                        .unwrap_or_default();
+                    elements.push(MetaElement {
+                        name,
+                        attributes,
+                        text,
+                    });
+                }
+                b"spine" => package.ncx_id = attribute(&start, "toc"),
+                _ => {}
+            },
+            Ok(Event::Empty(start)) => match start.local_name().as_ref() {
+                b"reference" => {
+                    let kind = attribute(&start, "type").unwrap_or_default();
+                    if matches!(kind.as_str(), "cover" | "toc" | "title-page")
+                        && let Some(href) = attribute(&start, "href")
+                    {
+                        let href = href.split('#').next().unwrap_or_default();
+                        package.skipped_pages.push(resolve_href(opf_dir, href));
+                    }
+                }
+                _ if in_metadata => elements.push(MetaElement {
+                    name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+                    attributes: attributes(&start),
+                    text: String::new(),
+                }),
+                _ => {}
+            },
+            Ok(Event::End(end)) if end.local_name().as_ref() == b"metadata" => {
+                in_metadata = false;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    // EPUB 3 用 `<meta refines="#id" property="…">` 细化其他元素。
+    let refinement = |id: Option<&str>, property: &str| {
+        let id = id?;
+        elements
+            .iter()
+            .find(|meta| {
+                meta.name == "meta"
+                    && meta.attr("property") == Some(property)
+                    && meta.attr("refines").and_then(|r| r.strip_prefix('#')) == Some(id)
+            })
+            .map(|meta| meta.text.clone())
+    };
+    let named_meta = |name: &str| {
+        elements
+            .iter()
+            .find(|meta| meta.name == "meta" && meta.attr("name") == Some(name))
+            .and_then(|meta| meta.attr("content"))
+            .map(str::to_string)
+    };
+
+    let info = &mut package.book_info;
+    for element in &elements {
+        let text = element.text.clone();
+        match element.name.as_str() {
+            "title" if info.title.is_empty() => info.title = text,
+            "language" if info.language.is_empty() => info.language = text,
+            "publisher" if info.publisher.is_empty() => info.publisher = text,
+            "description" if info.description.is_empty() => info.description = text,
+            "subject" if info.category.is_empty() => info.category = text,
+            "date" if info.publish_date.is_empty() => {
+                info.publish_date = text.split('T').next().unwrap_or_default().to_string();
+            }
+            "creator" | "contributor" if !text.is_empty() => {
+                let code = element
+                    .attr("opf:role")
+                    .or_else(|| element.attr("role"))
+                    .map(str::to_string)
+                    .or_else(|| refinement(element.attr("id"), "role"));
+                let role = match code {
+                    Some(code) => CreatorRole::ALL
+                        .into_iter()
+                        .find(|role| role.marc_code() == code.trim()),
+                    None => (element.name == "creator").then_some(CreatorRole::Author),
+                };
+                if let Some(role) = role {
+                    info.creators.push(Creator::new(text, role));
+                }
+            }
+            "identifier" => {
+                let lower = text.to_ascii_lowercase();
+                let scheme = element
+                    .attr("opf:scheme")
+                    .or_else(|| element.attr("scheme"))
+                    .unwrap_or_default();
+                if scheme.eq_ignore_ascii_case("isbn") || lower.starts_with("urn:isbn:") {
+                    if info.isbn.is_empty() {
+                        info.isbn = text
+                            .get(9..)
+                            .filter(|_| lower.starts_with("urn:isbn:"))
+                            .unwrap_or(&text)
+                            .to_string();
+                    }
+                } else if element.attr("id") == Some(unique_id.as_str())
+                    && uuid::Uuid::parse_str(lower.trim_start_matches("urn:uuid:")).is_ok()
+                {
+                    info.identifier = text;
+                }
+            }
+            "meta" if element.attr("property") == Some("belongs-to-collection") => {
+                if info.series.is_empty() {
+                    info.series = text;
+                    info.series_index =
+                        refinement(element.attr("id"), "group-position").unwrap_or_default();
+                }
+            }
+            _ => {}
+        }
+    }
+    // 本程序导出的出版社与日期写在 `<meta name=…>` 中。
+    if info.publisher.is_empty() {
+        info.publisher = named_meta("publisher").unwrap_or_default();
+    }
+    if info.publish_date.is_empty() {
+        info.publish_date = named_meta("date").unwrap_or_default();
+    }
+    if info.series.is_empty()
+        && let Some(series) = named_meta("calibre:series")
+    {
+        info.series = series;
+        info.series_index = named_meta("calibre:series_index").unwrap_or_default();
+    }
+    package.cover_id = named_meta("cover");
+    package
+}
+
+fn attributes(start: &BytesStart<'_>) -> HashMap<String, String> {
+    start
+        .attributes()
+        .flatten()
+        .filter_map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = attr.unescape_value().ok()?.into_owned();
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// 目录中的一项：层级（顶层为 1）、标题与目标页面的容器内路径。
+struct TocEntry {
+    depth: usize,
+    label: String,
+    path: String,
+}
+
+/// 读取目录：优先用 EPUB 3 导航文档，没有时用 NCX。
+fn read_toc<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    manifest: &[ManifestItem],
+    package: &Package,
+) -> Vec<TocEntry> {
+    let nav = manifest
+        .iter()
+        .find(|item| item.properties.split_whitespace().any(|p| p == "nav"));
+    if let Some(nav) = nav
+        && let Ok(text) = read_entry(archive, &nav.path)
+    {
+        let entries = nav_entries(&text, parent_dir(&nav.path));
+        if !entries.is_empty() {
+            return entries;
+        }
+    }
+    let ncx = package
+        .ncx_id
+        .as_ref()
+        .and_then(|id| manifest.iter().find(|item| &item.id == id))
+        .or_else(|| {
+            manifest
+                .iter()
+                .find(|item| item.media_type == "application/x-dtbncx+xml")
+        });
+    match ncx.map(|ncx| (read_entry(archive, &ncx.path), parent_dir(&ncx.path))) {
+        Some((Ok(text), dir)) => ncx_entries(&text, dir),
+        _ => Vec::new(),
+    }
+}
+
+fn toc_path(dir: &str, href: &str) -> String {
+    resolve_href(dir, href.split('#').next().unwrap_or_default())
+}
+
+fn nav_entries(nav: &str, dir: &str) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+    let mut reader = Reader::from_str(nav);
+    let mut in_toc = false;
+    let mut depth = 0usize;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => match start.local_name().as_ref() {
+                b"nav" => {
+                    in_toc = attribute(&start, "epub:type")
+                        .is_some_and(|kind| kind.split_whitespace().any(|k| k == "toc"));
+                }
+                b"ol" if in_toc => depth += 1,
+                b"a" if in_toc => {
+                    let href = attribute(&start, "href").unwrap_or_default();
+                    let label = reader
+                        .read_text(start.name())
+                        .map(|raw| plain_text(&raw))
+                        .unwrap_or_default();
+                    entries.push(TocEntry {
+                        depth,
+                        label,
+                        path: toc_path(dir, &href),
+                    });
+                }
+                _ => {}
+            },
+            Ok(Event::End(end)) => match end.local_name().as_ref() {
+                b"ol" if in_toc => depth = depth.saturating_sub(1),
+                b"nav" if in_toc => break,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    entries
+}
+
+fn ncx_entries(ncx: &str, dir: &str) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+    let mut reader = Reader::from_str(ncx);
+    let mut depth = 0usize;
+    let mut label = String::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => match start.local_name().as_ref() {
+                b"navPoint" => depth += 1,
+                b"text" if depth > 0 => {
+                    label = reader
+                        .read_text(start.name())
+                        .map(|raw| plain_text(&raw))
+                        .unwrap_or_default();
+                }
+                b"content" if depth > 0 => {
+                    let src = attribute(&start, "src").unwrap_or_default();
+                    entries.push(TocEntry {
+                        depth,
+                        label: std::mem::take(&mut label),
+                        path: toc_path(dir, &src),
+                    });
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(start)) if start.local_name().as_ref() == b"content" && depth > 0 => {
+                let src = attribute(&start, "src").unwrap_or_default();
+                entries.push(TocEntry {
+                    depth,
+                    label: std::mem::take(&mut label),
+                    path: toc_path(dir, &src),
+                });
+            }
+            Ok(Event::End(end)) if end.local_name().as_ref() == b"navPoint" => {
+                depth = depth.saturating_sub(1);
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// 从一个 XHTML 页面中取出的文字。
+#[derive(Debug, Default, PartialEq)]
+struct Page {
+    /// `<title>` 的内容。
+    title: Option<String>,
+    /// 出现在所有段落之前的第一个 `h1`–`h6`。
+    heading: Option<String>,
+    paragraphs: Vec<String>,
+}
+
+const BLOCK_ELEMENTS: &[&[u8]] = &[
+    b"p",
+    b"div",
+    b"li",
+    b"blockquote",
+    b"section",
+    b"article",
+    b"pre",
+    b"dd",
+    b"dt",
+    b"tr",
+    b"figcaption",
+    b"h1",
+    b"h2",
+    b"h3",
+    b"h4",
+    b"h5",
+    b"h6",
+];
+
+fn extract_page(xhtml: &str) -> Page {
+    let mut page = Page::default();
+    let mut reader = Reader::from_str(xhtml);
+    reader.config_mut().check_end_names = false;
+    let mut in_body = false;
+    let mut line = String::new();
+    // 本程序生成的章节标题把序号放在标题前的 `chapter-label` 中，与标题合并。
+    let mut label = None;
+    let flush = |line: &mut String, paragraphs: &mut Vec<String>| {
+        let text = collapse_whitespace(line);
+        if !text.is_empty() {
+            paragraphs.push(text);
+        }
+        line.clear();
+    };
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => {
+                let name = start.local_name();
+                let name = name.as_ref();
+                match name {
+                    b"body" => in_body = true,
+                    b"title" if !in_body => {
+                        page.title = reader
+                            .read_text(start.name())
+                            .ok()
+                            .map(|raw| plain_text(&raw))
+                            .filter(|title| !title.is_empty());
+                    }
+                    b"script" | b"style" | b"rt" | b"rp" => {
+                        let _ = reader.read_to_end(start.name());
+                    }
+                    _ if in_body
+                        && label.is_none()
+                        && page.paragraphs.is_empty()
+                        && has_class(&start, "chapter-label") =>
+                    {
+                        flush(&mut line, &mut page.paragraphs);
+                        label = reader
+                            .read_text(start.name())
+                            .ok()
+                            .map(|raw| plain_text(&raw))
+                            .filter(|label| !label.is_empty());
+                    }
+                    b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6"
+                        if in_body
+                            && page.heading.is_none()
+                            && page.paragraphs.is_empty()
+                            && collapse_whitespace(&line).is_empty() =>
+                    {
+                        page.heading = reader
+                            .read_text(start.name())
+                            .ok()
+                            .map(|raw| plain_text(&raw))
+                            .filter(|heading| !heading.is_empty())
+                            .map(|heading| match label.take() {
+                                Some(label) => format!("{label} {heading}"),
+                                None => heading,
+                            });
+                    }
+                    _ if BLOCK_ELEMENTS.contains(&name) => flush(&mut line, &mut page.paragraphs),
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(start)) if in_body => match start.local_name().as_ref() {
+                b"br" => flush(&mut line, &mut page.paragraphs),
+                b"hr" => {
+                    flush(&mut line, &mut page.paragraphs);
+                    page.paragraphs.push("[scene]".to_string());
+                }
+                _ => {}
+            },
+            Ok(Event::End(end)) => {
+                let name = end.local_name();
+                if name.as_ref() == b"body" {
+                    break;
+                }
+                if BLOCK_ELEMENTS.contains(&name.as_ref()) {
+                    flush(&mut line, &mut page.paragraphs);
+                }
+            }
+            Ok(Event::Text(text)) if in_body => {
+                line.push_str(&text.decode().unwrap_or_default());
+            }
+            Ok(Event::CData(text)) if in_body => {
+                line.push_str(&text.decode().unwrap_or_default());
+            }
+            Ok(Event::GeneralRef(reference)) if in_body => {
+                let name = reference.decode().unwrap_or_default();
+                if let Some(ch) = entity(&name) {
+                    line.push(ch);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    flush(&mut line, &mut page.paragraphs);
+    page
+}
+
+fn has_class(start: &BytesStart<'_>, class: &str) -> bool {
+    attributes(start)
+        .get("class")
+        .is_some_and(|classes| classes.split_whitespace().any(|name| name == class))
+}
+
+/// 去掉标签、解码实体并合并空白；`<br>` 视为空格。
+fn plain_text(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find(['<', '&']) {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with('<') {
+            let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+            if rest[1..]
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("br")
+            {
+                text.push(' ');
+            }
+            rest = &rest[end..];
+        } else {
+            match rest.find(';').filter(|&end| end <= 10) {
+                Some(end) => {
+                    if let Some(ch) = entity(&rest[1..end]) {
+                        text.push(ch);
+                    }
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    text.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    text.push_str(rest);
+    collapse_whitespace(&text)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 解析字符引用与常见的命名实体；无法识别时返回 `None`。
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "middot" => '·',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn build_epub(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        for (name, content) in files {
+            zip.start_file(*name, options).expect("start file");
+            zip.write_all(content.as_bytes()).expect("write");
+        }
+        zip.finish().expect("finish").into_inner()
+    }
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#;
+
+    const OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="uid">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    <dc:title>三体</dc:title>
+    <dc:creator opf:role="aut">刘慈欣</dc:creator>
+    <dc:contributor opf:role="trl">Ken Liu</dc:contributor>
+    <dc:contributor opf:role="bkp">calibre</dc:contributor>
+    <dc:language>zh-CN</dc:language>
+    <dc:publisher>重庆出版社</dc:publisher>
+    <dc:date>2008-01-01T00:00:00+00:00</dc:date>
+    <dc:subject>科幻</dc:subject>
+    <dc:identifier id="uid">urn:uuid:1b4e28ba-2fa1-11d2-883f-0016d3cca427</dc:identifier>
+    <dc:identifier opf:scheme="ISBN">9787536692930</dc:identifier>
+    <meta name="calibre:series" content="地球往事"/>
+    <meta name="calibre:series_index" content="1"/>
+    <meta name="cover" content="cover-image"/>
+  </metadata>
+  <manifest>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="cover-image" href="images/cover.png" media-type="image/png"/>
+    <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+    <item id="vol" href="text/volume.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="text/c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1b" href="text/c1_split.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="text/c2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="after" href="text/after.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine toc="ncx">
+    <itemref idref="cover"/><itemref idref="vol"/><itemref idref="c1"/>
+    <itemref idref="c1b"/><itemref idref="c2"/><itemref idref="after"/>
+  </spine>
+  <guide><reference type="cover" title="Cover" href="cover.xhtml"/></guide>
+</package>"#;
+
+    const NCX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1"><navMap>
+  <navPoint id="n1"><navLabel><text>第一部</text></navLabel><content src="text/volume.xhtml"/>
+    <navPoint id="n2"><navLabel><text>第一章 科学边界</text></navLabel><content src="text/c1.xhtml#top"/></navPoint>
+    <navPoint id="n3"><navLabel><text>第二章 台球</text></navLabel><content src="text/c2.xhtml"/></navPoint>
+  </navPoint>
+  <navPoint id="n4"><navLabel><text>后记</text></navLabel><content src="text/after.xhtml"/></navPoint>
+</navMap></ncx>"#;
+
+    fn page(body: &str) -> String {
+        format!(
+            "<?xml version=\"1.0\"?><html xmlns=\"http://www.w3.org/1999/xhtml\"><head><title>t</title>\
+             <style>p {{ margin: 0 }}</style></head><body>{body}</body></html>"
+        )
+    }
+
+    #[test]
+    fn import_restores_metadata_chapters_and_cover() {
+        let bytes = build_epub(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", OPF),
+            ("OEBPS/toc.ncx", NCX),
+            ("OEBPS/images/cover.png", "PNG"),
+            (
+                "OEBPS/cover.xhtml",
+                &page("<img src=\"images/cover.png\"/>"),
+            ),
+            ("OEBPS/text/volume.xhtml", &page("<h1>第一部</h1>")),
+            (
+                "OEBPS/text/c1.xhtml",
+                &page(
+                    "<h2>第一章<br/>科学边界</h2><p>　　汪淼觉得，<em>来找</em>他的</p><p>四个人&amp;一个</p><hr/><p>第二节</p>",
+                ),
+            ),
+            ("OEBPS/text/c1_split.xhtml", &page("<p>续&#x6587;</p>")),
+            ("OEBPS/text/c2.xhtml", &page("<p>台球</p>")),
+            ("OEBPS/text/after.xhtml", &page("<h1>后记</h1><p>完</p>")),
+        ]);
+        let imported = import_epub_bytes(&bytes).expect("import");
+
+        let info = &imported.book_info;
+        assert_eq!(info.title, "三体");
+        assert_eq!(
+            info.creators,
+            vec![
+                Creator::new("刘慈欣", CreatorRole::Author),
+                Creator::new("Ken Liu", CreatorRole::Translator)
+            ]
+        );
+        assert_eq!(info.language, "zh-CN");
+        assert_eq!(info.publisher, "重庆出版社");
+        assert_eq!(info.publish_date, "2008-01-01");
+        assert_eq!(info.category, "科幻");
+        assert_eq!(info.isbn, "9787536692930");
+        assert_eq!(
+            info.identifier,
+            "urn:uuid:1b4e28ba-2fa1-11d2-883f-0016d3cca427"
+        );
+        assert_eq!(
+            (info.series.as_str(), info.series_index.as_str()),
+            ("地球往事", "1")
+        );
+
+        let chapters: Vec<(&str, &str, Option<&str>)> = imported
+            .chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.content.as_str(), c.volume.as_deref()))
+            .collect();
+        assert_eq!(
+            chapters,
+            vec![
+                (
+                    "第一章 科学边界",
+                    "汪淼觉得，来找他的\n四个人&一个\n[scene]\n第二节\n续文",
+                    Some("第一部")
+                ),
+                ("第二章 台球", "台球", Some("第一部")),
+                ("后记", "完", None),
+            ]
+        );
+        assert_eq!(
+            imported.cover,
+            Some(("cover.png".to_string(), b"PNG".to_vec()))
+        );
+        assert!(
+            imported
+                .source_text()
+                .starts_with("# 第一章 科学边界\n\n汪淼觉得，来找他的\n")
+        );
+    }
+
+    #[test]
+    fn plain_text_keeps_titles_volumes_and_scene_breaks() {
+        let chapter = |title: &str, content: &str, volume: Option<&str>| ChapterDraft {
+            title: title.to_string(),
+            content: content.to_string(),
+            volume: volume.map(String::from),
+            ..Default::default()
+        };
+        let imported = ImportedEpub {
+            book_info: BookInfo {
+                title: "三体".to_string(),
+                creators: vec![Creator::new("刘慈欣", CreatorRole::Author)],
+                description: "文革。\n\n三体。".to_string(),
+                ..Default::default()
+            },
+            chapters: vec![
+                chapter("第一章 科学边界", "汪淼\n[scene]\n第二节", Some("第一部")),
+                chapter("第二章 台球", "台球", Some("第一部")),
+                chapter("后记", "完", None),
+            ],
+            cover: None,
+        };
+        assert_eq!(
+            imported.plain_text(),
+            "书名：三体\n作者：刘慈欣\n简介：\n文革。\n三体。\n\n第一部\n\n第一章 科学边界\n汪淼\n* * *\n第二节\n\n第二章 台球\n台球\n\n后记\n完\n"
+        );
+        let header =
+            crate::text_header::TextHeader::detect(&imported.plain_text()).expect("header");
+        assert_eq!(header.description, "文革。\n三体。");
+    }
+
+    #[test]
+    fn nav_entries_follow_list_nesting() {
+        let nav = r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops"><body>
+<nav epub:type="toc"><ol>
+  <li><a href="v.xhtml">卷一</a><ol><li><a href="c1.xhtml#x"><span>第一章</span></a></li></ol></li>
+  <li><a href="c2.xhtml">第二章</a></li>
+</ol></nav>
+<nav epub:type="landmarks"><ol><li><a href="cover.xhtml">Cover</a></li></ol></nav>
+</body></html>"#;
+        let entries: Vec<(usize, String, String)> = nav_entries(nav, "OEBPS")
+            .into_iter()
+            .map(|entry| (entry.depth, entry.label, entry.path))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (1, "卷一".to_string(), "OEBPS/v.xhtml".to_string()),
+                (2, "第一章".to_string(), "OEBPS/c1.xhtml".to_string()),
+                (1, "第二章".to_string(), "OEBPS/c2.xhtml".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_rejects_non_epub_data() {
+        assert!(import_epub_bytes(b"plain text").is_err());
+    }
+}
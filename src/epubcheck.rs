@@ -0,0 +1,270 @@
+//! 可选的 epubcheck 集成：转换完成后调用外部 epubcheck，解析其 JSON 报告。
+//!
+//! epubcheck 可以是可执行文件，也可以是 `epubcheck.jar`（此时通过 `java -jar` 运行）。
+//! 报告写入临时文件后读取，只取 `messages` 中的级别、编号、说明与位置。
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+
+/// epubcheck 消息级别，按严重程度从高到低排列。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EpubcheckSeverity {
+    Fatal,
+    Error,
+    Warning,
+    Usage,
+    Info,
+}
+
+impl EpubcheckSeverity {
+    fn parse(value: &str) -> Self {
+        match value.to_ascii_uppercase().as_str() {
+            "FATAL" => Self::Fatal,
+            "ERROR" => Self::Error,
+            "WARNING" => Self::Warning,
+            "USAGE" => Self::Usage,
+            _ => Self::Info,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fatal => "FATAL",
+            Self::Error => "ERROR",
+            Self::Warning => "WARNING",
+            Self::Usage => "USAGE",
+            Self::Info => "INFO",
+        }
+    }
+
+    /// 是否会导致 epubcheck 判定文件不合格。
+    pub fn is_error(self) -> bool {
+        matches!(self, Self::Fatal | Self::Error)
+    }
+}
+
+/// epubcheck 报告中的一条消息。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubcheckMessage {
+    pub severity: EpubcheckSeverity,
+    /// 规则编号，如 `RSC-005`。
+    pub id: String,
+    pub message: String,
+    /// 首个位置，格式为 `路径:行:列`；整本书级别的消息为空。
+    pub location: String,
+}
+
+/// 一次 epubcheck 运行的结果。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpubcheckReport {
+    pub messages: Vec<EpubcheckMessage>,
+}
+
+impl EpubcheckReport {
+    pub fn count(&self, severity: EpubcheckSeverity) -> usize {
+        self.messages
+            .iter()
+            .filter(|message| message.severity == severity)
+            .count()
+    }
+
+    /// 没有 FATAL/ERROR 级别的消息。
+    pub fn passed(&self) -> bool {
+        !self
+            .messages
+            .iter()
+            .any(|message| message.severity.is_error())
+    }
+}
+
+/// 对 `epub` 运行 `epubcheck` 并解析报告。
+///
+/// epubcheck 在发现错误时以非零状态退出，这不视为运行失败；
+/// 只有无法启动或没有生成报告时才返回错误。
+pub fn run_epubcheck(epubcheck: &Path, epub: &Path) -> Result<EpubcheckReport, String> {
+    let report_path = std::env::temp_dir().join(format!(
+        "reasypub-epubcheck-{}-{}.json",
+        std::process::id(),
+        uuid::Uuid::new_v4()
+    ));
+    let output = epubcheck_command(epubcheck, epub, &report_path)
+        .output()
+        .map_err(|err| format!("failed to start {}: {err}", epubcheck.display()))?;
+    let json = std::fs::read_to_string(&report_path);
+    let _ = std::fs::remove_file(&report_path);
+    let json = json.map_err(|_| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().next().unwrap_or("no report was written");
+        format!("epubcheck exited with {}: {detail}", output.status)
+    })?;
+    parse_report(&json)
+}
+
+fn epubcheck_command(epubcheck: &Path, epub: &Path, report: &Path) -> Command {
+    let is_jar = epubcheck
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jar"));
+    let mut command = if is_jar {
+        let mut command = Command::new("java");
+        command.arg("-jar").arg(epubcheck);
+        command
+    } else {
+        Command::new(epubcheck)
+    };
+    command.arg(epub).arg("--json").arg(report);
+    command
+}
+
+/// 解析 epubcheck 的 JSON 报告，消息按严重程度排序。
+pub fn parse_report(json: &str) -> Result<EpubcheckReport, String> {
+    let report: RawReport = serde_json::from_str(json).map_err(|err| err.to_string())?;
+    let mut messages: Vec<EpubcheckMessage> = report
+        .messages
+        .into_iter()
+        .map(|message| EpubcheckMessage {
+            severity: EpubcheckSeverity::parse(&message.severity.unwrap_or_default()),
+            id: message.id.unwrap_or_default(),
+            message: message.message.unwrap_or_default(),
+            location: message
+                .locations
+                .unwrap_or_default()
+                .first()
+                .map(format_location)
+                .unwrap_or_default(),
+        })
+        .collect();
+    messages.sort_by_key(|message| message.severity);
+    Ok(EpubcheckReport { messages })
+}
+
+/// epubcheck `--json` 报告中用到的部分；其余字段忽略。
+#[derive(Deserialize)]
+struct RawReport {
+    messages: Vec<RawMessage>,
+}
+
+#[derive(Deserialize)]
+struct RawMessage {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    severity: Option<String>,
+    message: Option<String>,
+    locations: Option<Vec<RawLocation>>,
+}
+
+#[derive(Deserialize)]
+struct RawLocation {
+    path: Option<String>,
+    line: Option<i64>,
+    column: Option<i64>,
+}
+
+fn format_location(location: &RawLocation) -> String {
+    let path = location.path.as_deref().unwrap_or("");
+    let number = |value: Option<i64>| value.filter(|value| *value >= 0);
+    match (number(location.line), number(location.column)) {
+        (Some(line), Some(column)) => format!("{path}:{line}:{column}"),
+        (Some(line), None) => format!("{path}:{line}"),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const REPORT: &str = r#"{
+      "checker": { "path": "book.epub", "nFatal": 0, "nError": 1, "nWarning": 1 },
+      "publication": { "title": "书 \"名\"", "language": "zh-CN", "isScripted": false },
+      "messages": [
+        {
+          "ID": "OPF-085", "severity": "WARNING",
+          "message": "\"dc:identifier\" value is marked as a `UUID`",
+          "additionalLocations": 0,
+          "locations": [ { "path": "OEBPS/content.opf", "line": 5, "column": -1, "context": null } ],
+          "suggestion": null
+        },
+        {
+          "ID": "RSC-005", "severity": "ERROR",
+          "message": "Error while parsing file: element \"p\" not allowed here 😀",
+          "locations": [ { "path": "OEBPS/chapter_0001.xhtml", "line": 12, "column": 7 } ]
+        },
+        { "ID": "INF-001", "severity": "INFO", "message": "done", "locations": [] }
+      ]
+    }"#;
+
+    #[test]
+    fn parse_report_reads_messages_sorted_by_severity() {
+        let report = parse_report(REPORT).expect("parse");
+        assert_eq!(report.messages.len(), 3);
+        assert_eq!(
+            report.messages[0],
+            EpubcheckMessage {
+                severity: EpubcheckSeverity::Error,
+                id: "RSC-005".to_string(),
+                message: "Error while parsing file: element \"p\" not allowed here 😀".to_string(),
+                location: "OEBPS/chapter_0001.xhtml:12:7".to_string(),
+            }
+        );
+        assert_eq!(report.messages[1].location, "OEBPS/content.opf:5");
+        assert_eq!(
+            report.messages[1].message,
+            "\"dc:identifier\" value is marked as a `UUID`"
+        );
+        assert_eq!(report.messages[2].location, "");
+        assert_eq!(report.count(EpubcheckSeverity::Warning), 1);
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn parse_report_rejects_invalid_json() {
+        assert!(parse_report("{\"messages\": [").is_err());
+        assert!(parse_report("{\"checker\": {}}").is_err());
+        assert!(parse_report("{\"messages\": []} x").is_err());
+        assert!(parse_report("{\"messages\": []}").expect("empty").passed());
+    }
+
+    #[test]
+    fn epubcheck_command_runs_jar_with_java() {
+        let report = PathBuf::from("report.json");
+        let jar = epubcheck_command(
+            Path::new("tools/EPUBCheck.JAR"),
+            Path::new("a.epub"),
+            &report,
+        );
+        assert_eq!(jar.get_program(), "java");
+        let args: Vec<_> = jar.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "-jar",
+                "tools/EPUBCheck.JAR",
+                "a.epub",
+                "--json",
+                "report.json"
+            ]
+        );
+
+        let exe = epubcheck_command(
+            Path::new("/usr/bin/epubcheck"),
+            Path::new("a.epub"),
+            &report,
+        );
+        assert_eq!(exe.get_program(), "/usr/bin/epubcheck");
+        assert_eq!(exe.get_args().count(), 3);
+    }
+
+    #[test]
+    fn run_epubcheck_reports_missing_executable() {
+        let err = run_epubcheck(
+            Path::new("/nonexistent/reasypub-epubcheck"),
+            Path::new("a.epub"),
+        )
+        .expect_err("missing executable");
+        assert!(err.contains("failed to start"));
+    }
+}
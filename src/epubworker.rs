@@ -1,33 +1,78 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::Cursor;
+use std::io::{Cursor, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use bytes::Bytes;
 use epub_builder::{EpubBuilder, EpubContent, ReferenceType, ZipLibrary};
 
-use crate::{BookInfo, ChapterDraft, CssTemplate, FontAsset, ImageAsset, TextStyle, TocOptions};
+use crate::cover::{COVER_HEIGHT, COVER_WIDTH};
+use crate::language::resolve_language;
+use crate::typography::{TypographyRules, smarten_chapters};
+use crate::{
+    BackMatter, BackMatterKind, BookInfo, ChapterDraft, ChapterHeaderImage, CssTemplate,
+    EpubVersion, FontAsset, FrontMatter, FrontMatterPage, ImageAsset, ImageCompatibility,
+    ImageOptimization, OverwritePolicy, SourceFormat, SpineSection, TextStyle, TocOptions,
+    ZipCompression,
+};
 
 mod assets;
 mod css;
+mod images;
+mod kobo;
 mod metadata;
+mod obfuscation;
+mod package;
 mod render;
+mod split;
+mod subset;
 mod utils;
 
-use assets::add_fantasy_assets;
-use css::{build_stylesheet, fantasy_divider_svg, folio_divider_svg};
-use metadata::{add_optional_meta_tag, add_optional_metadata};
-use render::{gallery_title, render_chapter, render_gallery, render_text_cover};
-use utils::{generate_filename, normalize_output_dir};
+use assets::{FANTASY_FONTS, add_fantasy_assets};
+use css::{BASE_CSS_PATH, build_stylesheet, fantasy_divider_svg, folio_divider_svg};
+use images::{SVG_MIME, optimize_image, svg_size, transcode_image};
+use kobo::kepubify;
+use metadata::{
+    accessibility_metadata, add_authors, add_optional_meta_tag, add_optional_metadata,
+    add_series_metadata, contributor_metadata, custom_metadata, isbn_identifier,
+};
+use obfuscation::{encryption_xml, obfuscate_font, obfuscation_key};
+use package::{PackagePatch, write_patched_epub};
+use render::{
+    ChapterRenderOptions, Landmark, TocPageEntry, back_matter_title, body_start_title, cover_title,
+    front_matter_title, gallery_title, page_breaks, render_back_matter_page, render_chapter,
+    render_front_matter_page, render_gallery, render_landmarks, render_page_list, render_svg_cover,
+    render_text_cover, render_title_page, render_toc_page, render_volume_page,
+};
+pub use split::DEFAULT_CHAPTER_SPLIT_BYTES;
+use split::split_chapter;
+
+// PDF 输出复用文件命名、输出目录、段落切分、行内标记处理与字体子集化。
+pub(crate) use kobo::kepub_filename;
+pub(crate) use render::{
+    is_scene_break, split_paragraphs, strip_block_markers, strip_inline_markup,
+};
+pub(crate) use subset::subset_font;
+pub(crate) use utils::{
+    expand_filename_variables, expand_page_template, generate_filename, normalize_output_dir,
+    resolve_output_path, sanitize_filename_component,
+};
 
 #[cfg(test)]
-use css::color_to_hex;
+use css::{color_to_hex, scope_chapter_css};
 #[cfg(test)]
-use render::{escape_html, split_paragraphs, split_title_line};
+use render::{escape_html, split_title_line};
 
 #[derive(Debug)]
 pub enum BuildError {
     Io(std::io::Error),
     Epub(epub_builder::Error),
     InvalidInput(String),
+    /// 构建被调用方通过取消标志中止，不会留下输出文件。
+    Cancelled,
+    /// 输出文件已存在且覆盖策略为 `OverwritePolicy::Ask`，没有写出任何内容。
+    OutputExists(PathBuf),
 }
 
 impl std::fmt::Display for BuildError {
@@ -36,6 +81,10 @@ impl std::fmt::Display for BuildError {
             BuildError::Io(err) => write!(f, "IO error: {}", err),
             BuildError::Epub(err) => write!(f, "EPUB error: {}", err),
             BuildError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            BuildError::Cancelled => write!(f, "Build cancelled"),
+            BuildError::OutputExists(path) => {
+                write!(f, "Output file already exists: {}", path.display())
+            }
         }
     }
 }
@@ -61,38 +110,317 @@ pub struct EpubBuildOptions {
     pub style: TextStyle,
     pub cover: Option<ImageAsset>,
     pub images: Vec<ImageAsset>,
-    pub font: Option<FontAsset>,
+    pub fonts: Vec<FontAsset>,
     pub chapter_header_image: Option<ImageAsset>,
     pub chapter_header_fullbleed: bool,
     pub include_images_section: bool,
     pub toc_options: TocOptions,
+    /// 书名页、版权页、献词与前言，插入在正文之前。
+    pub front_matter: FrontMatter,
+    /// 插图页之后的后记或版权页。
+    pub back_matter: BackMatter,
+    /// 在 OPF 中写入 `reasypub <版本>` 生成器信息；关闭后输出不含版本痕迹。
+    pub include_generator_meta: bool,
+    /// 安全模式：除输出文件外不读取任何磁盘资源（`book.css`、Fantasy 素材等）。
+    pub safe_mode: bool,
+    pub epub_version: EpubVersion,
+    /// 在 spine 上声明从右到左翻页；竖排时总是从右到左。
+    pub rtl_page_progression: bool,
+    /// 生成 Kobo 专用的 KEPUB：正文插入 `koboSpan` 标记，文件以 `.kepub.epub` 结尾。
+    pub kepub: bool,
+    /// 可复现输出：固定 ZIP 时间戳、`dcterms:modified` 与条目顺序，相同输入生成逐字节相同的文件。
+    ///
+    /// 出版物标识本就由书名和作者确定性生成（见 [`BookInfo::derived_uuid`]）。
+    pub reproducible: bool,
+    /// ZIP 压缩级别，以及图片是否不压缩直接存储。
+    pub compression: ZipCompression,
+    /// 超过该字节数的章节在段落之间拆成 `chapter_0005_a.xhtml`、`_b`……，目录中仍只有一项；
+    /// `None` 不拆分。
+    pub split_chapter_bytes: Option<usize>,
+    /// 封面、前置页、目录页、正文、插图页与后记在 spine 中的顺序；
+    /// 缺少的部分按默认顺序补在最后。
+    pub spine_order: Vec<SpineSection>,
+    /// 源文本格式；Markdown 会渲染粗体、斜体、链接与引用块。
+    pub source_format: SourceFormat,
+    /// 封面、章节头图与插图的缩放与重新压缩设置。
+    pub image_optimization: ImageOptimization,
+    /// 输出文件已存在时覆盖、自动编号或报错。
+    pub overwrite_policy: OverwritePolicy,
+}
+
+/// `build_epub_with_warnings` 的输出：文件路径与非致命警告。
+#[derive(Debug, Clone, Default)]
+pub struct EpubBuildOutput {
+    pub output_path: String,
+    pub warnings: Vec<String>,
+    /// 启用图片优化时，每张被处理图片的前后体积。
+    pub image_sizes: Vec<ImageSizeChange>,
+}
+
+/// 单张图片优化前后的字节数；保留原图时两者相等。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageSizeChange {
+    pub name: String,
+    pub before: usize,
+    pub after: usize,
 }
 
+impl Default for EpubBuildOptions {
+    fn default() -> Self {
+        Self {
+            book_info: BookInfo::default(),
+            output_dir: PathBuf::from("."),
+            filename_template: "{书名}_{作者}.epub".to_string(),
+            style: TextStyle::default(),
+            cover: None,
+            images: Vec::new(),
+            fonts: Vec::new(),
+            chapter_header_image: None,
+            chapter_header_fullbleed: false,
+            include_images_section: true,
+            toc_options: TocOptions::default(),
+            front_matter: FrontMatter::default(),
+            back_matter: BackMatter::default(),
+            include_generator_meta: true,
+            safe_mode: false,
+            epub_version: EpubVersion::default(),
+            rtl_page_progression: false,
+            kepub: false,
+            reproducible: false,
+            compression: ZipCompression::default(),
+            split_chapter_bytes: None,
+            spine_order: SpineSection::ALL.to_vec(),
+            source_format: SourceFormat::default(),
+            image_optimization: ImageOptimization::default(),
+            overwrite_policy: OverwritePolicy::default(),
+        }
+    }
+}
+
+/// 构建阶段的进度事件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildProgress {
+    /// 已渲染 `done` / `total` 个章节。
+    ChapterRendered { done: usize, total: usize },
+    /// 章节全部加入，开始写出 EPUB 压缩包。
+    Writing,
+}
+
+/// 渲染时自动生成、不直接来自正文的文字（封面分隔符、默认标题等）。
+const GENERATED_TEXT: &str = "·—…目录插图“”‘’，。：；！？、（）《》「」『』";
+
+/// 收集书中可能显示的全部字符，作为字体子集化的字形清单。
+///
+/// 始终包含可打印 ASCII，以覆盖罗马数字章节标签、`CHAPTER01` 等生成文字。
+fn collect_used_chars(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+    extra: &[&str],
+) -> BTreeSet<char> {
+    let info = &options.book_info;
+    let mut used: BTreeSet<char> = (' '..='~').collect();
+    let texts = chapters
+        .iter()
+        .flat_map(|chapter| {
+            [&chapter.title, &chapter.content]
+                .into_iter()
+                .chain(chapter.volume.as_ref())
+        })
+        .map(String::as_str)
+        .chain(
+            options
+                .images
+                .iter()
+                .filter_map(|image| image.caption.as_deref()),
+        )
+        .chain([
+            info.title.as_str(),
+            info.publisher.as_str(),
+            info.category.as_str(),
+            info.publish_date.as_str(),
+            info.description.as_str(),
+            GENERATED_TEXT,
+        ])
+        .chain(info.creators.iter().map(|creator| creator.name.as_str()))
+        .chain(extra.iter().copied());
+    for text in texts {
+        used.extend(text.chars().filter(|ch| !ch.is_control()));
+    }
+    used
+}
+
+/// 写入 OPF 的生成器标识，例如 `reasypub 0.95.0`。
+pub fn generator_name() -> String {
+    format!("reasypub {}", env!("CARGO_PKG_VERSION"))
+}
+
+/// 生成 EPUB 并写入 `options.output_dir`，返回输出文件路径。
+///
+/// 不需要落盘时使用 `build_epub_to_writer` 或 `build_epub_bytes`。
 pub fn build_epub(
     chapters: &[ChapterDraft],
     options: &EpubBuildOptions,
 ) -> Result<String, BuildError> {
+    build_epub_with_warnings(chapters, options).map(|output| output.output_path)
+}
+
+/// 与 `build_epub` 相同，但额外返回构建过程中的非致命警告。
+pub fn build_epub_with_warnings(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+) -> Result<EpubBuildOutput, BuildError> {
+    build_epub_with_progress(chapters, options, &mut |_| {}, &AtomicBool::new(false))
+}
+
+/// 与 `build_epub_with_warnings` 相同，并在每个章节后上报进度。
+///
+/// 每渲染一章以及写出压缩包前都会检查 `cancel`；一旦置位即返回
+/// `BuildError::Cancelled`。输出文件只在最后一步创建，取消不会留下残缺文件。
+pub fn build_epub_with_progress(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+    on_progress: &mut dyn FnMut(BuildProgress),
+    cancel: &AtomicBool,
+) -> Result<EpubBuildOutput, BuildError> {
+    let output_dir = normalize_output_dir(&options.output_dir)?;
+    let outpath = resolve_output_path(
+        &output_dir,
+        &output_filename(chapters, options),
+        options.overwrite_policy,
+    )?;
+    let open = || -> Result<File, BuildError> {
+        fs::create_dir_all(&output_dir)?;
+        Ok(File::create(&outpath)?)
+    };
+    let (warnings, image_sizes) = write_epub(chapters, options, open, on_progress, cancel)?;
+    Ok(EpubBuildOutput {
+        output_path: outpath.display().to_string(),
+        warnings,
+        image_sizes,
+    })
+}
+
+/// 把 EPUB 写入任意 `writer`，不触碰文件系统；`options.output_dir` 被忽略。
+///
+/// 返回的 `output_path` 只是按文件名模板生成的文件名，供调用方保存或下载时使用。
+pub fn build_epub_to_writer(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+    writer: impl Write,
+) -> Result<EpubBuildOutput, BuildError> {
+    build_epub_to_writer_with_progress(
+        chapters,
+        options,
+        writer,
+        &mut |_| {},
+        &AtomicBool::new(false),
+    )
+}
+
+/// 与 `build_epub_to_writer` 相同，并上报进度、响应取消标志。
+///
+/// 取消或出错时 `writer` 不会收到任何字节。
+pub fn build_epub_to_writer_with_progress(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+    writer: impl Write,
+    on_progress: &mut dyn FnMut(BuildProgress),
+    cancel: &AtomicBool,
+) -> Result<EpubBuildOutput, BuildError> {
+    let (warnings, image_sizes) =
+        write_epub(chapters, options, || Ok(writer), on_progress, cancel)?;
+    Ok(EpubBuildOutput {
+        output_path: output_filename(chapters, options),
+        warnings,
+        image_sizes,
+    })
+}
+
+/// 在内存中生成 EPUB 并返回完整字节。
+pub fn build_epub_bytes(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+) -> Result<Vec<u8>, BuildError> {
+    let mut bytes = Vec::new();
+    build_epub_to_writer(chapters, options, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// 按文件名模板生成输出文件名；KEPUB 使用 `.kepub.epub` 扩展名。
+fn output_filename(chapters: &[ChapterDraft], options: &EpubBuildOptions) -> String {
+    let filename = generate_filename(
+        &options.book_info,
+        &options.filename_template,
+        chapters.len(),
+        options.style.css_template,
+    );
+    if options.kepub {
+        kepub_filename(&filename)
+    } else {
+        filename
+    }
+}
+
+/// 组装 EPUB 并写入 `open` 返回的目标，返回警告与图片体积变化。
+///
+/// `open` 在全部章节渲染完成后才被调用，取消或出错时不会创建输出目标。
+fn write_epub<W: Write>(
+    chapters: &[ChapterDraft],
+    options: &EpubBuildOptions,
+    open: impl FnOnce() -> Result<W, BuildError>,
+    on_progress: &mut dyn FnMut(BuildProgress),
+    cancel: &AtomicBool,
+) -> Result<(Vec<String>, Vec<ImageSizeChange>), BuildError> {
     if chapters.is_empty() {
         return Err(BuildError::InvalidInput(
             "No chapters provided.".to_string(),
         ));
     }
 
-    let output_dir = normalize_output_dir(&options.output_dir)?;
-    fs::create_dir_all(&output_dir)?;
-
-    let filename = generate_filename(&options.book_info, &options.filename_template);
-    let outpath = output_dir.join(&filename);
-    let writer = File::create(&outpath)?;
-
     let mut builder = EpubBuilder::new(ZipLibrary::new()?)?;
+    // 固定出版物标识，而不是每次随机生成；混淆字体的密钥也由该标识计算。
+    let (book_uuid, identifier_warning) = match options.book_info.uuid() {
+        Ok(id) => (id, None),
+        Err(_) => (
+            options.book_info.derived_uuid(),
+            Some(format!(
+                "Identifier \"{}\" is not a UUID; a generated identifier was used instead.",
+                options.book_info.identifier.trim()
+            )),
+        ),
+    };
+    builder.set_uuid(book_uuid);
+    let fonts = unique_fonts(&options.fonts, options.style.css_template);
+    let font_key = (options.style.obfuscate_fonts && !fonts.is_empty())
+        .then(|| obfuscation_key(&uuid::fmt::Urn::from_uuid(book_uuid).to_string()));
+    builder.epub_version(match options.epub_version {
+        EpubVersion::Epub2 => epub_builder::EpubVersion::V20,
+        EpubVersion::Epub3 => epub_builder::EpubVersion::V30,
+    });
+    let rtl_pages = options.rtl_page_progression || options.style.vertical;
+    if rtl_pages {
+        // epub-builder 的 EPUB 3 模板读取 `direction` 元数据；EPUB 2 模板不输出翻页方向，
+        // 由生成后改写 spine 补上。
+        builder.metadata("direction", "rtl")?;
+    }
+    if options.style.vertical {
+        add_optional_meta_tag(&mut builder, "primary-writing-mode", "vertical-rl");
+    }
 
-    add_optional_metadata(&mut builder, "author", &options.book_info.author)?;
+    add_authors(&mut builder, &options.book_info.creators)?;
     add_optional_metadata(&mut builder, "title", &options.book_info.title)?;
-    add_optional_metadata(&mut builder, "lang", &options.book_info.language)?;
-    let lang = options.book_info.language.trim();
+    // 填写的语言规范为 BCP 47 标签；未填写或无效时从正文推断。
+    let (language, language_warning) = resolve_language(&options.book_info.language, chapters);
+    builder.metadata("lang", &language)?;
+    // 智能排版在确定语言后、渲染前进行，目录与正文使用同样的标点。
+    let smart_chapters = options
+        .style
+        .smart_typography
+        .then(|| smarten_chapters(chapters, &language))
+        .flatten();
+    let chapters = smart_chapters.as_deref().unwrap_or(chapters);
     let toc_name = if options.toc_options.toc_title_override.trim().is_empty() {
-        if lang.is_empty() || lang.starts_with("zh") {
+        if language.starts_with("zh") {
             "目录"
         } else {
             "Table Of Contents"
@@ -102,27 +430,135 @@ pub fn build_epub(
         options.toc_options.toc_title_override.trim().to_string()
     };
     add_optional_metadata(&mut builder, "toc_name", &toc_name)?;
+    let front_matter: Vec<(FrontMatterPage, Vec<String>)> = options
+        .front_matter
+        .pages()
+        .into_iter()
+        .map(|page| {
+            let lines = expand_page_template(
+                &options.book_info,
+                options.front_matter.text(page),
+                chapters.len(),
+                options.style.css_template,
+            );
+            (page, lines)
+        })
+        .collect();
+    let back_matter = &options.back_matter;
+    let back_matter_lines = expand_page_template(
+        &options.book_info,
+        &back_matter.text,
+        chapters.len(),
+        options.style.css_template,
+    );
+    let back_matter_title = match back_matter.title.trim() {
+        "" => back_matter_title(back_matter.kind, &language),
+        title => title,
+    };
     add_optional_metadata(&mut builder, "subject", &options.book_info.category)?;
     add_optional_metadata(&mut builder, "description", &options.book_info.description)?;
     add_optional_meta_tag(&mut builder, "publisher", &options.book_info.publisher);
-    add_optional_meta_tag(&mut builder, "identifier", &options.book_info.isbn);
     add_optional_meta_tag(&mut builder, "date", &options.book_info.publish_date);
+    add_series_metadata(&mut builder, &options.book_info, options.epub_version);
+    if options.include_generator_meta {
+        let generator = generator_name();
+        builder.set_generator(generator.as_str());
+        add_optional_meta_tag(&mut builder, "generator", &generator);
+    }
 
-    let stylesheet = build_stylesheet(&options.style, options.font.as_ref())?;
+    let mut warnings: Vec<String> = identifier_warning
+        .into_iter()
+        .chain(language_warning)
+        .collect();
+    if options.safe_mode {
+        warnings.push(format!(
+            "Safe mode: optional theme asset {} was not loaded.",
+            BASE_CSS_PATH
+        ));
+    }
+
+    let stylesheet = build_stylesheet(
+        &options.style,
+        &options.toc_options,
+        &fonts,
+        options.safe_mode,
+    )?;
     builder.stylesheet(Cursor::new(stylesheet))?;
 
-    if let Some(cover) = &options.cover {
-        builder.add_cover_image(&cover.name, cover.bytes.as_ref(), &cover.mime)?;
+    // 兼容性转码会更换扩展名，之后的资源写入与正文引用统一使用转码后的图片。
+    let (cover, chapter_header_image, mut images) = compatible_images(options, &mut warnings);
+    let mut image_sizes = Vec::new();
+    let mut prepare = |image: &ImageAsset, warnings: &mut Vec<String>| {
+        prepare_image(
+            image,
+            &options.image_optimization,
+            warnings,
+            &mut image_sizes,
+        )
+    };
+
+    if let Some(cover) = &cover {
+        let bytes = prepare(cover, &mut warnings);
+        builder.add_cover_image(&cover.name, bytes.as_ref(), &cover.mime)?;
     }
 
-    if let Some(font) = &options.font {
+    let used_chars = (options.style.subset_font && !fonts.is_empty()).then(|| {
+        let mut extra = vec![toc_name.as_str()];
+        for (page, lines) in &front_matter {
+            extra.push(front_matter_title(*page, &language));
+            extra.extend(lines.iter().map(String::as_str));
+        }
+        if !back_matter_lines.is_empty() {
+            extra.push(back_matter_title);
+            extra.extend(back_matter_lines.iter().map(String::as_str));
+        }
+        collect_used_chars(chapters, options, &extra)
+    });
+    for font in &fonts {
         let path = format!("fonts/{}", font.name);
-        builder.add_resource(path, Cursor::new(font.bytes.clone()), &font.mime)?;
+        let mut bytes = if let Some(used) = &used_chars {
+            match subset_font(&font.bytes, used) {
+                Ok(subset) => subset,
+                Err(err) => {
+                    warnings.push(format!(
+                        "Font subsetting skipped for {}: {}. The full font was embedded.",
+                        font.name, err
+                    ));
+                    font.bytes.to_vec()
+                }
+            }
+        } else {
+            font.bytes.to_vec()
+        };
+        if let Some(key) = &font_key {
+            obfuscate_font(&mut bytes, key);
+        }
+        builder.add_resource(path, Cursor::new(bytes), &font.mime)?;
     }
 
-    if let Some(header) = &options.chapter_header_image {
+    if let Some(header) = &chapter_header_image {
         let path = format!("images/{}", header.name);
-        builder.add_resource(path, Cursor::new(header.bytes.clone()), &header.mime)?;
+        let bytes = prepare(header, &mut warnings);
+        builder.add_resource(path, Cursor::new(bytes), &header.mime)?;
+    }
+    let chapter_headers: Vec<Option<ImageAsset>> = chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| match &chapter.header_image {
+            ChapterHeaderImage::Custom(image) => Some(chapter_header_override(
+                image,
+                index + 1,
+                &options.image_optimization,
+                &fonts,
+                &mut warnings,
+            )),
+            _ => None,
+        })
+        .collect();
+    for header in chapter_headers.iter().flatten() {
+        let path = format!("images/{}", header.name);
+        let bytes = prepare(header, &mut warnings);
+        builder.add_resource(path, Cursor::new(bytes), &header.mime)?;
     }
 
     if matches!(options.style.css_template, CssTemplate::Folio) {
@@ -141,61 +577,589 @@ pub fn build_epub(
         add_fantasy_assets(&mut builder)?;
     }
 
-    for image in &options.images {
+    // 内容相同的插图只写入一次，重复项改为引用首次出现的文件。
+    let mut embedded: HashMap<Bytes, String> = HashMap::new();
+    for image in &mut images {
+        if let Some(name) = embedded.get(&image.bytes) {
+            warnings.push(format!(
+                "Duplicate image: {} has the same content as {}; it was embedded once.",
+                image.name, name
+            ));
+            image.name = name.clone();
+            continue;
+        }
+        embedded.insert(image.bytes.clone(), image.name.clone());
         let path = format!("images/{}", image.name);
-        builder.add_resource(path, Cursor::new(image.bytes.clone()), &image.mime)?;
+        let bytes = prepare(image, &mut warnings);
+        builder.add_resource(path, Cursor::new(bytes), &image.mime)?;
+    }
+    // 指定了章节的插图插入该章末尾，其余的放在插图页。
+    let mut chapter_images: Vec<Vec<&ImageAsset>> = vec![Vec::new(); chapters.len()];
+    let mut gallery_images = Vec::new();
+    for image in &images {
+        match image
+            .chapter
+            .and_then(|index| chapter_images.get_mut(index))
+        {
+            Some(placed) => placed.push(image),
+            None => gallery_images.push(image.clone()),
+        }
     }
 
-    let language = if options.book_info.language.trim().is_empty() {
-        "zh-CN"
-    } else {
-        options.book_info.language.trim()
+    let language = language.as_str();
+
+    let kobo = |html: String| if options.kepub { kepubify(&html) } else { html };
+    // EPUB 3 导航文档中的 landmarks，按阅读顺序收集。
+    let mut landmarks = Vec::new();
+    let mut landmark = |epub_type: &'static str, href: &str, title: &str| {
+        landmarks.push(Landmark {
+            epub_type,
+            href: href.to_string(),
+            title: title.to_string(),
+        });
     };
 
-    if options.cover.is_none() {
-        let cover_html =
-            render_text_cover(&options.book_info, language, options.style.css_template);
+    let mut svg_documents = Vec::new();
+    // 书名页、版权页与献词属于“前置页”部分；前言总是紧接在正文之前，默认顺序下即目录之后。
+    let add_front_matter = |builder: &mut EpubBuilder<ZipLibrary>,
+                            landmark: &mut dyn FnMut(&'static str, &str, &str),
+                            page: FrontMatterPage,
+                            lines: &[String]|
+     -> Result<(), BuildError> {
+        let html = match page {
+            FrontMatterPage::TitlePage => {
+                render_title_page(&options.book_info, language, options.epub_version)
+            }
+            _ => render_front_matter_page(page, lines, language, options.epub_version),
+        };
+        let (filename, reftype, epub_type) = front_matter_file(page);
+        let title = front_matter_title(page, language);
         builder.add_content(
-            EpubContent::new("cover.xhtml", cover_html.as_bytes()).reftype(ReferenceType::Cover),
+            EpubContent::new(filename, kobo(html).as_bytes())
+                .title(title)
+                .reftype(reftype),
         )?;
+        landmark(epub_type, filename, title);
+        Ok(())
+    };
+    // 内嵌目录页的条目，与导航目录一致；生成后替换 epub-builder 的 `toc.xhtml`。
+    let mut toc_entries = Vec::new();
+    let front_matter_entry = |page: FrontMatterPage| {
+        TocPageEntry::page(
+            front_matter_file(page).0,
+            front_matter_title(page, language),
+            1,
+        )
+    };
+    let render_options = ChapterRenderOptions {
+        language,
+        style: &options.style,
+        template: options.style.css_template,
+        header_image: chapter_header_image.as_ref(),
+        header_fullbleed: options.chapter_header_fullbleed,
+        epub_version: options.epub_version,
+        markdown: matches!(options.source_format, SourceFormat::Markdown),
+        images: &[],
+    };
+    // 带 `volume` 的章节作为二级条目挂在对应卷下；
+    // 若卷标题没有对应的顶层章节，则补一页卷首页作为父节点。
+    let mut current_top: Option<&str> = None;
+    let mut volume_pages = 0usize;
+    // 只有正文的第一页标为 `bodymatter` / guide 的 `text`，阅读器的“开始阅读”跳到这里。
+    let mut body_start: Option<String> = None;
+    // 源文本页码标记生成的 `(文件, 页码)`，仅 EPUB 3 有分页锚点。
+    let mut page_list = Vec::new();
+    for section in SpineSection::ordered(&options.spine_order) {
+        match section {
+            SpineSection::Cover => {
+                if cover.is_none() {
+                    let cover_html = render_text_cover(
+                        &options.book_info,
+                        language,
+                        options.style.css_template,
+                        options.epub_version,
+                    );
+                    let cover_html = kobo(cover_html);
+                    builder.add_content(
+                        EpubContent::new("cover.xhtml", cover_html.as_bytes())
+                            .reftype(ReferenceType::Cover),
+                    )?;
+                    landmark("cover", "cover.xhtml", cover_title(language));
+                }
+                // 不少阅读器不会把 SVG 的 `cover-image` 当作封面显示，矢量封面另加一页封面页。
+                if let Some(cover) = cover.as_ref().filter(|cover| cover.mime == SVG_MIME) {
+                    let size = svg_size(&cover.bytes).unwrap_or((COVER_WIDTH, COVER_HEIGHT));
+                    let cover_html = kobo(render_svg_cover(
+                        &cover.name,
+                        &options.book_info.title,
+                        size,
+                        language,
+                        options.epub_version,
+                    ));
+                    builder.add_content(
+                        EpubContent::new("cover.xhtml", cover_html.as_bytes())
+                            .reftype(ReferenceType::Cover),
+                    )?;
+                    svg_documents.push("cover.xhtml".to_string());
+                    landmark("cover", "cover.xhtml", cover_title(language));
+                }
+            }
+            SpineSection::FrontMatter => {
+                for (page, lines) in &front_matter {
+                    if *page != FrontMatterPage::Preface {
+                        add_front_matter(&mut builder, &mut landmark, *page, lines)?;
+                        toc_entries.push(front_matter_entry(*page));
+                    }
+                }
+            }
+            SpineSection::TocPage => {
+                if options.toc_options.insert_toc_page {
+                    builder.inline_toc();
+                    landmark("toc", "toc.xhtml", &toc_name);
+                } else {
+                    landmark("toc", "nav.xhtml#toc", &toc_name);
+                }
+            }
+            SpineSection::Chapters => {
+                for (page, lines) in &front_matter {
+                    if *page == FrontMatterPage::Preface {
+                        add_front_matter(&mut builder, &mut landmark, *page, lines)?;
+                        toc_entries.push(front_matter_entry(*page));
+                    }
+                }
+                for (index, chapter) in chapters.iter().enumerate() {
+                    if cancel.load(Ordering::Relaxed) {
+                        return Err(BuildError::Cancelled);
+                    }
+                    let volume = chapter
+                        .volume
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|volume| !volume.is_empty());
+                    let level = match volume {
+                        Some(volume) => {
+                            if current_top != Some(volume) {
+                                volume_pages += 1;
+                                let html = kobo(render_volume_page(
+                                    volume,
+                                    language,
+                                    options.epub_version,
+                                ));
+                                let filename = format!("volume_{:02}.xhtml", volume_pages);
+                                let mut content =
+                                    EpubContent::new(filename.as_str(), html.as_bytes())
+                                        .title(volume);
+                                if body_start.is_none() {
+                                    content = content.reftype(ReferenceType::Text);
+                                    body_start = Some(filename.clone());
+                                }
+                                builder.add_content(content)?;
+                                toc_entries.push(TocPageEntry::page(&filename, volume, 1));
+                                current_top = Some(volume);
+                            }
+                            2
+                        }
+                        None => {
+                            current_top = Some(chapter.title.trim());
+                            1
+                        }
+                    };
+                    let chapter_options = ChapterRenderOptions {
+                        header_image: chapter_headers[index]
+                            .as_ref()
+                            .or(render_options.header_image),
+                        images: &chapter_images[index],
+                        ..render_options
+                    };
+                    let html = render_chapter(chapter, index + 1, &chapter_options);
+                    let filename = format!("chapter_{:04}.xhtml", index + 1);
+                    // 拆分后只有第一份带标题，目录、landmarks 与 guide 都指向它。
+                    let parts = match options.split_chapter_bytes {
+                        Some(max_bytes) => split_chapter(&html, &filename, max_bytes),
+                        None => vec![(filename, html)],
+                    };
+                    for (part, (filename, html)) in parts.into_iter().enumerate() {
+                        page_list.extend(
+                            page_breaks(&html)
+                                .into_iter()
+                                .map(|page| (filename.clone(), page)),
+                        );
+                        let html = kobo(html);
+                        let mut content = EpubContent::new(filename.as_str(), html.as_bytes());
+                        if part == 0 {
+                            content = content.title(chapter.title.as_str()).level(level);
+                            if body_start.is_none() {
+                                content = content.reftype(ReferenceType::Text);
+                                body_start = Some(filename.clone());
+                            }
+                            toc_entries
+                                .push(TocPageEntry::chapter(&filename, chapter, level, language));
+                        }
+                        builder.add_content(content)?;
+                    }
+                    on_progress(BuildProgress::ChapterRendered {
+                        done: index + 1,
+                        total: chapters.len(),
+                    });
+                }
+                if let Some(filename) = &body_start {
+                    landmark("bodymatter", filename, body_start_title(language));
+                }
+            }
+            SpineSection::Gallery => {
+                if options.include_images_section && !gallery_images.is_empty() {
+                    let gallery_title = gallery_title(language);
+                    let html = kobo(render_gallery(
+                        &gallery_images,
+                        language,
+                        gallery_title,
+                        options.epub_version,
+                    ));
+                    let mut content = EpubContent::new("images.xhtml", html.as_bytes())
+                        .reftype(ReferenceType::Loi);
+                    if options.toc_options.include_gallery_in_toc {
+                        content = content.title(gallery_title);
+                        toc_entries.push(TocPageEntry::page("images.xhtml", gallery_title, 1));
+                    }
+                    builder.add_content(content)?;
+                    landmark("loi", "images.xhtml", gallery_title);
+                }
+            }
+            SpineSection::BackMatter => {
+                if !back_matter_lines.is_empty() {
+                    let html = kobo(render_back_matter_page(
+                        back_matter.kind,
+                        back_matter_title,
+                        &back_matter_lines,
+                        language,
+                        options.epub_version,
+                    ));
+                    // guide 没有后记类型，后记只出现在 landmarks 中。
+                    let (filename, epub_type) = match back_matter.kind {
+                        BackMatterKind::Afterword => ("afterword.xhtml", "afterword"),
+                        BackMatterKind::Colophon => ("colophon.xhtml", "colophon"),
+                    };
+                    let mut content =
+                        EpubContent::new(filename, html.as_bytes()).title(back_matter_title);
+                    if back_matter.kind == BackMatterKind::Colophon {
+                        content = content.reftype(ReferenceType::Colophon);
+                    }
+                    builder.add_content(content)?;
+                    toc_entries.push(TocPageEntry::page(filename, back_matter_title, 1));
+                    landmark(epub_type, filename, back_matter_title);
+                }
+            }
+        }
     }
 
-    if options.toc_options.insert_toc_page {
-        builder.inline_toc();
+    if cancel.load(Ordering::Relaxed) {
+        return Err(BuildError::Cancelled);
+    }
+    on_progress(BuildProgress::Writing);
+    let mut writer = open()?;
+    // epub-builder 不能写入 META-INF 下的自定义文件，不支持 EPUB 2 的翻页方向、
+    // `dc:contributor` 和第二个 `dc:identifier`，也不能正确转义自定义元数据或为目录添加
+    // ARIA 角色，需要时先在内存中生成再改写。
+    let mut accessibility = options.book_info.accessibility.clone();
+    if !page_list.is_empty() {
+        accessibility.add_feature("pageBreakMarkers");
+        accessibility.add_feature("pageNavigation");
+    }
+    let patch = PackagePatch {
+        encryption_xml: font_key.is_some().then(|| {
+            let paths: Vec<String> = fonts
+                .iter()
+                .map(|font| format!("OEBPS/fonts/{}", font.name))
+                .collect();
+            encryption_xml(&paths)
+        }),
+        rtl_spine: rtl_pages && !options.epub_version.is_epub3(),
+        svg_documents: if options.epub_version.is_epub3() {
+            svg_documents
+        } else {
+            Vec::new()
+        },
+        opf_metadata: isbn_identifier(&options.book_info.isbn, options.epub_version)
+            + &contributor_metadata(&options.book_info.creators, options.epub_version)
+            + &custom_metadata(&options.book_info.custom_metadata)
+            + &accessibility_metadata(&accessibility, options.epub_version),
+        toc_role: options.epub_version.is_epub3(),
+        landmarks: if options.epub_version.is_epub3() {
+            render_landmarks(&landmarks)
+        } else {
+            String::new()
+        },
+        toc_page: options.toc_options.insert_toc_page.then(|| {
+            render_toc_page(
+                &toc_entries,
+                &toc_name,
+                &options.toc_options,
+                language,
+                options.epub_version,
+            )
+        }),
+        page_list: if page_list.is_empty() {
+            String::new()
+        } else {
+            render_page_list(&page_list)
+        },
+        reproducible: options.reproducible,
+        compression: options.compression,
+    };
+    if patch.is_empty() {
+        builder.generate(writer)?;
+    } else {
+        let mut epub = Vec::new();
+        builder.generate(&mut epub)?;
+        let mut patched = Cursor::new(Vec::new());
+        write_patched_epub(&epub, &mut patched, &patch)?;
+        writer.write_all(patched.get_ref())?;
     }
 
-    for (index, chapter) in chapters.iter().enumerate() {
-        let html = render_chapter(
-            chapter,
-            language,
-            &options.style,
-            options.style.css_template,
-            index + 1,
-            options.chapter_header_image.as_ref(),
-            options.chapter_header_fullbleed,
-        );
-        let filename = format!("chapter_{:04}.xhtml", index + 1);
-        builder.add_content(
-            EpubContent::new(filename, html.as_bytes())
-                .title(chapter.title.as_str())
-                .reftype(ReferenceType::Text),
-        )?;
+    Ok((warnings, image_sizes))
+}
+
+/// 前置页的文件名、guide 引用类型与 landmarks 中的 `epub:type`。
+fn front_matter_file(page: FrontMatterPage) -> (&'static str, ReferenceType, &'static str) {
+    match page {
+        FrontMatterPage::TitlePage => ("title_page.xhtml", ReferenceType::TitlePage, "titlepage"),
+        FrontMatterPage::Copyright => (
+            "copyright.xhtml",
+            ReferenceType::Copyright,
+            "copyright-page",
+        ),
+        FrontMatterPage::Dedication => {
+            ("dedication.xhtml", ReferenceType::Dedication, "dedication")
+        }
+        FrontMatterPage::Preface => ("preface.xhtml", ReferenceType::Preface, "preface"),
     }
+}
+
+/// 单章预览：与导出时相同的章节 XHTML 与全书样式表。
+#[derive(Debug, Clone, Default)]
+pub struct ChapterPreview {
+    pub xhtml: String,
+    pub stylesheet: String,
+}
+
+/// 按导出时的版式参数渲染第 `index` 章（从 0 开始）与样式表，供界面预览排版。
+///
+/// 不处理图片与 KEPUB 标记，也不写出任何文件。
+pub fn preview_chapter(
+    chapter: &ChapterDraft,
+    index: usize,
+    options: &EpubBuildOptions,
+) -> Result<ChapterPreview, BuildError> {
+    let fonts = unique_fonts(&options.fonts, options.style.css_template);
+    let stylesheet = build_stylesheet(
+        &options.style,
+        &options.toc_options,
+        &fonts,
+        options.safe_mode,
+    )?;
+    let (language, _) =
+        resolve_language(&options.book_info.language, std::slice::from_ref(chapter));
+    let smart_chapter = options
+        .style
+        .smart_typography
+        .then(|| TypographyRules::for_language(&language))
+        .flatten()
+        .map(|rules| rules.apply_chapter(chapter));
+    let chapter = smart_chapter.as_ref().unwrap_or(chapter);
+    let header_image = match &chapter.header_image {
+        ChapterHeaderImage::Custom(image) => Some(image),
+        _ => options.chapter_header_image.as_ref(),
+    };
+    let render_options = ChapterRenderOptions {
+        language: &language,
+        style: &options.style,
+        template: options.style.css_template,
+        header_image,
+        header_fullbleed: options.chapter_header_fullbleed,
+        epub_version: options.epub_version,
+        markdown: matches!(options.source_format, SourceFormat::Markdown),
+        images: &[],
+    };
+    Ok(ChapterPreview {
+        xhtml: render_chapter(chapter, index + 1, &render_options),
+        stylesheet,
+    })
+}
 
-    if options.include_images_section && !options.images.is_empty() {
-        let gallery_title = gallery_title(language);
-        let html = render_gallery(&options.images, language, gallery_title);
-        let mut content =
-            EpubContent::new("images.xhtml", html.as_bytes()).reftype(ReferenceType::Text);
-        if options.toc_options.include_gallery_in_toc {
-            content = content.title(gallery_title);
+/// 让嵌入字体的文件名与字体族互不重复，也不与 Fantasy 模板自带的字体冲突：
+/// 重名时在文件名主干与字体族后追加 `-2`、`-3`……
+///
+/// 不同目录下的同名字体（如两个 `Regular.ttf`）否则会写出重复的压缩包条目。
+fn unique_fonts(fonts: &[FontAsset], template: CssTemplate) -> Vec<FontAsset> {
+    let mut names = HashSet::new();
+    let mut families = HashSet::new();
+    if matches!(template, CssTemplate::Fantasy) {
+        for (path, _) in FANTASY_FONTS {
+            let name = path.trim_start_matches("fonts/");
+            names.insert(name.to_string());
+            families.insert(name.trim_end_matches(".ttf").to_string());
         }
-        builder.add_content(content)?;
     }
+    fonts
+        .iter()
+        .map(|font| {
+            let (stem, extension) = font
+                .name
+                .rsplit_once('.')
+                .map_or((font.name.as_str(), None), |(stem, ext)| (stem, Some(ext)));
+            let mut name = font.name.clone();
+            let mut family = font.family.clone();
+            let mut suffix = 2;
+            while names.contains(&name) || families.contains(&family) {
+                name = match extension {
+                    Some(ext) => format!("{stem}-{suffix}.{ext}"),
+                    None => format!("{stem}-{suffix}"),
+                };
+                family = format!("{}-{suffix}", font.family);
+                suffix += 1;
+            }
+            names.insert(name.clone());
+            families.insert(family.clone());
+            FontAsset {
+                name,
+                family,
+                ..font.clone()
+            }
+        })
+        .collect()
+}
+
+/// 按兼容性配置转码封面、章节头图与插图；转码失败时保留原图并给出警告。
+///
+/// 转码后的文件换用新扩展名，重名时追加序号。
+fn compatible_images(
+    options: &EpubBuildOptions,
+    warnings: &mut Vec<String>,
+) -> (Option<ImageAsset>, Option<ImageAsset>, Vec<ImageAsset>) {
+    let settings = &options.image_optimization;
+    if (settings.compatibility != ImageCompatibility::Original || settings.grayscale)
+        && matches!(options.style.css_template, CssTemplate::Fantasy)
+    {
+        warnings.push(
+            "Image compatibility: the Fantasy theme's built-in WebP ornaments were kept as-is."
+                .to_string(),
+        );
+    }
+
+    let mut taken: HashSet<String> = options
+        .cover
+        .iter()
+        .chain(options.chapter_header_image.iter())
+        .chain(options.images.iter())
+        .map(|image| image.name.clone())
+        .collect();
+    let mut convert = |image: &ImageAsset| match transcode_image(
+        &image.bytes,
+        &image.mime,
+        settings,
+        &options.fonts,
+    ) {
+        Ok(Some(transcoded)) => {
+            let stem = image
+                .name
+                .rsplit_once('.')
+                .map_or(image.name.as_str(), |(stem, _)| stem);
+            let mut name = format!("{stem}.{}", transcoded.extension);
+            let mut suffix = 2;
+            while taken.contains(&name) {
+                name = format!("{stem}-{suffix}.{}", transcoded.extension);
+                suffix += 1;
+            }
+            taken.insert(name.clone());
+            ImageAsset {
+                name,
+                bytes: Bytes::from(transcoded.bytes),
+                mime: transcoded.mime.to_string(),
+                caption: image.caption.clone(),
+                chapter: image.chapter,
+            }
+        }
+        Ok(None) => image.clone(),
+        Err(err) => {
+            warnings.push(format!(
+                "Image transcoding skipped for {}: {}. The original image was embedded.",
+                image.name, err
+            ));
+            image.clone()
+        }
+    };
+    (
+        options.cover.as_ref().map(&mut convert),
+        options.chapter_header_image.as_ref().map(&mut convert),
+        options.images.iter().map(&mut convert).collect(),
+    )
+}
 
-    builder.generate(writer)?;
+/// 为单章头图生成按章节序号命名的资源，并按兼容性配置转码。
+fn chapter_header_override(
+    image: &ImageAsset,
+    chapter_index: usize,
+    settings: &ImageOptimization,
+    fonts: &[FontAsset],
+    warnings: &mut Vec<String>,
+) -> ImageAsset {
+    let extension = image
+        .name
+        .rsplit_once('.')
+        .map_or("png", |(_, ext)| ext)
+        .to_lowercase();
+    let (bytes, extension, mime) = match transcode_image(&image.bytes, &image.mime, settings, fonts)
+    {
+        Ok(Some(transcoded)) => (
+            Bytes::from(transcoded.bytes),
+            transcoded.extension.to_string(),
+            transcoded.mime.to_string(),
+        ),
+        Ok(None) => (image.bytes.clone(), extension, image.mime.clone()),
+        Err(err) => {
+            warnings.push(format!(
+                "Image transcoding skipped for {}: {}. The original image was embedded.",
+                image.name, err
+            ));
+            (image.bytes.clone(), extension, image.mime.clone())
+        }
+    };
+    ImageAsset {
+        name: format!("chapter-header-{:04}.{}", chapter_index, extension),
+        bytes,
+        mime,
+        caption: None,
+        chapter: None,
+    }
+}
 
-    Ok(outpath.display().to_string())
+/// 按设置优化图片并记录前后体积；优化失败时嵌入原图并给出警告。
+fn prepare_image(
+    image: &ImageAsset,
+    settings: &ImageOptimization,
+    warnings: &mut Vec<String>,
+    sizes: &mut Vec<ImageSizeChange>,
+) -> Bytes {
+    if !settings.enabled && !settings.grayscale {
+        return image.bytes.clone();
+    }
+    let bytes = match optimize_image(&image.bytes, &image.mime, settings) {
+        Ok(Some(optimized)) => Bytes::from(optimized),
+        Ok(None) => image.bytes.clone(),
+        Err(err) => {
+            warnings.push(format!(
+                "Image optimization skipped for {}: {}. The original image was embedded.",
+                image.name, err
+            ));
+            image.bytes.clone()
+        }
+    };
+    sizes.push(ImageSizeChange {
+        name: image.name.clone(),
+        before: image.bytes.len(),
+        after: bytes.len(),
+    });
+    bytes
 }
 
 #[cfg(test)]
@@ -1,38 +1,52 @@
-use std::fs;
 use std::io::Cursor;
 
 use epub_builder::{EpubBuilder, ZipLibrary};
 
 use super::BuildError;
 
+/// 编译期嵌入 `assets/fantasy/` 下的素材，包内路径与素材目录内的相对路径相同。
+///
+/// 不依赖运行时工作目录，安全模式下同样可用。
+macro_rules! fantasy_asset {
+    ($path:literal) => {
+        (
+            $path,
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/assets/fantasy/",
+                $path
+            ))
+            .as_slice(),
+        )
+    };
+}
+
+const FANTASY_IMAGES: [(&str, &[u8]); 9] = [
+    fantasy_asset!("images/头图.webp"),
+    fantasy_asset!("images/头图1.webp"),
+    fantasy_asset!("images/4star.webp"),
+    fantasy_asset!("images/ttl.webp"),
+    fantasy_asset!("images/ttr.webp"),
+    fantasy_asset!("images/背景.webp"),
+    fantasy_asset!("images/背景1.webp"),
+    fantasy_asset!("images/纹理.webp"),
+    fantasy_asset!("images/纸纹.webp"),
+];
+
+pub(super) const FANTASY_FONTS: [(&str, &[u8]); 5] = [
+    fantasy_asset!("fonts/kt.ttf"),
+    fantasy_asset!("fonts/rbs.ttf"),
+    fantasy_asset!("fonts/dbs.ttf"),
+    fantasy_asset!("fonts/ys.ttf"),
+    fantasy_asset!("fonts/hyss.ttf"),
+];
+
 pub(super) fn add_fantasy_assets(builder: &mut EpubBuilder<ZipLibrary>) -> Result<(), BuildError> {
-    let image_assets = [
-        ("assets/fantasy/images/头图.webp", "images/头图.webp"),
-        ("assets/fantasy/images/头图1.webp", "images/头图1.webp"),
-        ("assets/fantasy/images/4star.webp", "images/4star.webp"),
-        ("assets/fantasy/images/ttl.webp", "images/ttl.webp"),
-        ("assets/fantasy/images/ttr.webp", "images/ttr.webp"),
-        ("assets/fantasy/images/背景.webp", "images/背景.webp"),
-        ("assets/fantasy/images/背景1.webp", "images/背景1.webp"),
-        ("assets/fantasy/images/纹理.webp", "images/纹理.webp"),
-        ("assets/fantasy/images/纸纹.webp", "images/纸纹.webp"),
-    ];
-    for (source, dest) in image_assets {
-        let bytes = fs::read(source)?;
+    for (dest, bytes) in FANTASY_IMAGES {
         builder.add_resource(dest, Cursor::new(bytes), "image/webp")?;
     }
-
-    let font_assets = [
-        ("assets/fantasy/fonts/kt.ttf", "fonts/kt.ttf"),
-        ("assets/fantasy/fonts/rbs.ttf", "fonts/rbs.ttf"),
-        ("assets/fantasy/fonts/dbs.ttf", "fonts/dbs.ttf"),
-        ("assets/fantasy/fonts/ys.ttf", "fonts/ys.ttf"),
-        ("assets/fantasy/fonts/hyss.ttf", "fonts/hyss.ttf"),
-    ];
-    for (source, dest) in font_assets {
-        let bytes = fs::read(source)?;
+    for (dest, bytes) in FANTASY_FONTS {
         builder.add_resource(dest, Cursor::new(bytes), "font/ttf")?;
     }
-
     Ok(())
 }
@@ -1,14 +1,25 @@
 use std::fs;
 
-use crate::{CssTemplate, FontAsset, TextStyle};
+use crate::{CssTemplate, FontAsset, FontRole, TextStyle, TocOptions};
 
 use super::BuildError;
 
+pub(super) const BASE_CSS_PATH: &str = "assets/book/book.css";
+
+/// 生成全书样式表。
+///
+/// `safe_mode` 为真时不读取磁盘上的基础样式 `assets/book/book.css`。
 pub(super) fn build_stylesheet(
     style: &TextStyle,
-    font: Option<&FontAsset>,
+    toc: &TocOptions,
+    fonts: &[FontAsset],
+    safe_mode: bool,
 ) -> Result<String, BuildError> {
-    let base_css = fs::read_to_string("assets/book/book.css").unwrap_or_default();
+    let base_css = if safe_mode {
+        String::new()
+    } else {
+        fs::read_to_string(BASE_CSS_PATH).unwrap_or_default()
+    };
     let mut css = String::new();
     css.push_str(&base_css);
     css.push_str("\n\n/* === template === */\n");
@@ -35,6 +46,7 @@ pub(super) fn build_stylesheet(
 
     css.push_str("\n\n/* === cover === */\n");
     css.push_str(".cover-page { text-align: center; page-break-after: always; }\n");
+    css.push_str(".cover-image { margin: 0; padding: 0; height: 100%; }\n");
     css.push_str(".cover-frame { position: relative; margin: 2.8em 1.6em; padding: 2.4em 1.8em; border: 2px double #6b5b4b; background: #fbf8f2; }\n");
     css.push_str(".cover-title { font-size: 2.2em; letter-spacing: 0.12em; line-height: 1.2; margin: 0.6em 0 0.2em; }\n");
     css.push_str(".cover-subtitle { font-size: 1.05em; letter-spacing: 0.08em; color: #6b5b4b; margin: 0.2em 0 0.6em; }\n");
@@ -44,6 +56,31 @@ pub(super) fn build_stylesheet(
     css.push_str(".cover-meta { font-size: 0.85em; letter-spacing: 0.2em; color: #6b5b4b; margin-top: 1.4em; }\n");
     css.push_str(".cover-ornament { height: 1.8em; width: 70%; margin: 0.8em auto; border-top: 1px solid #6b5b4b; border-bottom: 1px solid #cbbda9; }\n");
 
+    css.push_str("\n\n/* === volume === */\n");
+    css.push_str(
+        ".volume-page { text-align: center; page-break-before: always; break-before: page; }\n",
+    );
+    css.push_str(".volume-title { margin-top: 30%; font-size: 1.8em; letter-spacing: 0.2em; }\n");
+
+    css.push_str("\n\n/* === front matter === */\n");
+    css.push_str(
+        ".title-page { text-align: center; page-break-before: always; break-before: page; }\n",
+    );
+    css.push_str(
+        ".title-page-title { margin-top: 25%; font-size: 2em; letter-spacing: 0.12em; }\n",
+    );
+    css.push_str(
+        ".title-page-author { margin-top: 2em; font-size: 1.1em; letter-spacing: 0.2em; }\n",
+    );
+    css.push_str(
+        ".title-page-publisher { margin-top: 40%; font-size: 0.9em; letter-spacing: 0.2em; }\n",
+    );
+    css.push_str(".copyright-page .front-matter-text { margin-top: 40%; }\n");
+    css.push_str(".copyright-page p { text-indent: 0; font-size: 0.85em; margin: 0 0 0.4em; }\n");
+    css.push_str(".dedication-page .front-matter-text { margin-top: 30%; text-align: center; }\n");
+    css.push_str(".dedication-page p { text-indent: 0; font-style: italic; }\n");
+    css.push_str(".colophon-page p { text-indent: 0; font-size: 0.85em; margin: 0 0 0.4em; }\n");
+
     css.push_str("\n\n/* === chapter header === */\n");
     css.push_str(".chapter { page-break-before: always; break-before: page; }\n");
     css.push_str(".chapter-head-image { text-align: center; margin: 0 0 1.2em; }\n");
@@ -78,12 +115,47 @@ pub(super) fn build_stylesheet(
         ".chapter-paragraph-first::first-letter { float: left; font-size: 3.2em; line-height: 0.85; padding: 0.04em 0.1em 0 0; font-weight: 600; color: #5a4a3b; }\n",
     );
 
+    css.push_str("\n\n/* === chapter variants === */\n");
+    css.push_str("body.chapter-plain .chapter-header { margin: 1.6em 0 1.4em; padding: 0; border: none; background: none; }\n");
+    css.push_str(
+        "body.chapter-plain .chapter-header::before, body.chapter-plain .chapter-header::after { display: none; }\n",
+    );
+    css.push_str("body.chapter-centered .chapter-header { margin-top: 25%; }\n");
+    css.push_str(
+        "body.chapter-centered .chapter-paragraph { text-align: center; text-indent: 0 !important; }\n",
+    );
+    css.push_str(
+        "body.chapter-centered .chapter-paragraph-first::first-letter { float: none; font-size: 1em; padding: 0; font-weight: inherit; color: inherit; }\n",
+    );
+
+    css.push_str("\n\n/* === footnotes === */\n");
+    css.push_str(".noteref { text-decoration: none; }\n");
+    css.push_str(".noteref sup { font-size: 0.7em; line-height: 0; }\n");
+    css.push_str(".footnotes { margin-top: 2.4em; font-size: 0.85em; }\n");
+    css.push_str(".footnote p { text-indent: 0; margin: 0.3em 0; }\n");
+
+    css.push_str("\n\n/* === ruby === */\n");
+    css.push_str(
+        "ruby { ruby-position: over; -epub-ruby-position: over; -webkit-ruby-position: before; }\n",
+    );
+    css.push_str("rt { font-size: 0.5em; line-height: 1; text-indent: 0; letter-spacing: 0; }\n");
+    css.push_str("rp { display: none; }\n");
+
+    css.push_str("\n\n/* === emphasis === */\n");
+    css.push_str(".emphasis-dot { text-emphasis: filled dot; -webkit-text-emphasis: filled dot; -epub-text-emphasis-style: filled dot; text-emphasis-position: under right; -webkit-text-emphasis-position: under right; -epub-text-emphasis-position: under right; }\n");
+
+    css.push_str("\n\n/* === scene break === */\n");
+    css.push_str("hr.scene-break { border: none; height: 1.4em; margin: 1.2em auto; text-align: center; overflow: visible; page-break-inside: avoid; break-inside: avoid; }\n");
+    css.push_str("hr.scene-break::after { content: \"*\\2003*\\2003*\"; display: inline-block; color: #8a7a66; font-size: 0.9em; }\n");
+
     if matches!(style.css_template, CssTemplate::Folio) {
         css.push_str("\n\n/* === folio chapter header overrides === */\n");
         css.push_str(".chapter-header { margin: 2.4em 0 2.1em; padding: 0.9em 0 1.1em; border-top: 1px solid #6b5b4b; border-bottom: 1px solid #cbbda9; background: #fbf8f2; }\n");
         css.push_str(".chapter-ornament { border: none; height: 1.7em; width: 62%; margin: 0.75em auto; background: url(\"ornaments/folio-divider.svg\") center / 62% auto no-repeat; }\n");
         css.push_str(".chapter-ornament::after { display: none; }\n");
         css.push_str(".chapter-label { letter-spacing: 0.35em; color: #5a4a3b; }\n");
+        css.push_str("hr.scene-break { height: 1.5em; width: 40%; background: url(\"ornaments/folio-divider.svg\") center / contain no-repeat; }\n");
+        css.push_str("hr.scene-break::after { content: none; }\n");
         css.push_str("\n\n/* === folio cover === */\n");
         css.push_str(".cover-frame { border-color: #6b5b4b; background: #fcfaf6; box-shadow: inset 0 0 0 3px rgba(107,91,75,0.08); }\n");
         css.push_str(".cover-frame::before { content: \"\"; position: absolute; inset: 0.9em; border: 1px solid rgba(107,91,75,0.28); }\n");
@@ -126,6 +198,8 @@ pub(super) fn build_stylesheet(
         css.push_str(".chapter-ornament { border: none; height: 1.9em; width: 68%; margin: 0.85em auto; background: url(\"ornaments/fantasy-divider.svg\") center / 70% auto no-repeat; }\n");
         css.push_str(".chapter-ornament::after { display: none; }\n");
         css.push_str(".chapter-label { letter-spacing: 0.45em; color: #a66c44; }\n");
+        css.push_str("hr.scene-break { height: 1.6em; width: 44%; background: url(\"ornaments/fantasy-divider.svg\") center / contain no-repeat; }\n");
+        css.push_str("hr.scene-break::after { content: none; }\n");
 
         css.push_str("\n\n/* === fantasy cover === */\n");
         css.push_str(".cover-frame { border-color: #a66c44; background: #f6efe3 url(\"images/纸纹.webp\") center / cover no-repeat; box-shadow: inset 0 0 0 3px rgba(166,108,68,0.14); }\n");
@@ -137,16 +211,46 @@ pub(super) fn build_stylesheet(
         css.push_str(".cover-meta { letter-spacing: 0.26em; color: #6b5b4b; }\n");
     }
 
-    if let Some(font_asset) = font {
-        css.push_str("\n\n/* === embedded font === */\n");
-        css.push_str(&format!(
-            "@font-face {{ font-family: \"{}\"; src: url(\"fonts/{}\"); }}\n",
-            font_asset.family, font_asset.name
-        ));
+    css.push_str(&toc_css(style.css_template, toc));
+
+    if style.vertical {
+        // 竖排时块方向从右向左推进，段间距改为左侧外边距；首字下沉与横向装饰线不再适用。
+        css.push_str("\n\n/* === vertical writing === */\n");
+        css.push_str("html, body { writing-mode: vertical-rl; -epub-writing-mode: vertical-rl; -webkit-writing-mode: vertical-rl; }\n");
         css.push_str(&format!(
-            "body, p, li {{ font-family: \"{}\", \"Palatino\", \"Times New Roman\", serif; }}\n",
-            font_asset.family
+            "p {{ margin: 0 0 0 {}em; }}\n",
+            style.paragraph_spacing
         ));
+        css.push_str(".chapter-header { margin: 0 0 0 2em; padding: 0 0.8em; }\n");
+        css.push_str(".chapter-ornament { display: none; }\n");
+        css.push_str(".chapter-paragraph-first::first-letter { float: none; font-size: 1em; padding: 0; font-weight: inherit; color: inherit; }\n");
+        css.push_str("body.chapter-centered .chapter-header { margin: 0 25% 0 2em; }\n");
+        css.push_str("blockquote.quote { margin: 1em 0 0 1.2em; }\n");
+        css.push_str(".verse { margin: 2em 0 0 1.2em; }\n");
+        css.push_str(".verse p.verse-stanza { margin: 0 0 0 1em; }\n");
+        css.push_str("hr.scene-break { height: auto; width: 1.4em; margin: auto 1.2em; background-size: contain; }\n");
+        css.push_str(".tcy { text-combine-upright: all; -epub-text-combine: horizontal; -webkit-text-combine: horizontal; }\n");
+        css.push_str("img { max-height: 95vh; }\n");
+    }
+
+    if !fonts.is_empty() {
+        css.push_str("\n\n/* === embedded fonts === */\n");
+        for font_asset in fonts {
+            css.push_str(&format!(
+                "@font-face {{ font-family: \"{}\"; src: url(\"fonts/{}\"); }}\n",
+                font_asset.family, font_asset.name
+            ));
+        }
+        // 每个角色取第一款声明了该角色的字体。
+        for role in FontRole::ALL {
+            if let Some(font_asset) = fonts.iter().find(|font| font.roles.contains(&role)) {
+                css.push_str(&format!(
+                    "{} {{ font-family: \"{}\", \"Palatino\", \"Times New Roman\", serif; }}\n",
+                    role.css_selector(),
+                    font_asset.family
+                ));
+            }
+        }
     }
 
     if !style.custom_css.trim().is_empty() {
@@ -158,6 +262,106 @@ pub(super) fn build_stylesheet(
     Ok(css)
 }
 
+/// 内嵌目录页样式：编号与分组标题使用模板的装饰色，按选项追加多栏与点状引导线。
+fn toc_css(template: CssTemplate, toc: &TocOptions) -> String {
+    let accent = match template {
+        CssTemplate::Folio => "#6b5b4b",
+        CssTemplate::Fantasy => "#a66c44",
+        CssTemplate::Clean | CssTemplate::Minimal => "#9a9a9a",
+        _ => "#8a7a66",
+    };
+    let mut css = String::from("\n\n/* === toc page === */\n");
+    css.push_str(".toc-page h1.toc-title { text-align: center; letter-spacing: 0.2em; margin: 1.6em 0 1.2em; }\n");
+    css.push_str(".toc-page ol.toc-list { list-style: none; margin: 0; padding: 0; }\n");
+    css.push_str(".toc-page ol.toc-list ol.toc-list { padding-left: 1.5em; }\n");
+    css.push_str(".toc-page li { margin: 0.35em 0; text-indent: 0; }\n");
+    css.push_str(".toc-page a { text-decoration: none; color: inherit; }\n");
+    css.push_str(&format!(
+        ".toc-page .toc-number {{ margin-right: 0.6em; color: {accent}; }}\n"
+    ));
+    css.push_str(&format!(
+        ".toc-page h2.toc-volume-heading {{ font-size: 1.05em; text-align: left; letter-spacing: 0.12em; margin: 1.2em 0 0.4em; padding-bottom: 0.2em; border-bottom: 1px solid {accent}; }}\n"
+    ));
+    css.push_str(".toc-page li.toc-volume > ol.toc-list { padding-left: 0; }\n");
+    let columns = toc.columns.clamp(1, 3);
+    if columns > 1 {
+        css.push_str(&format!(
+            ".toc-page nav > ol.toc-list, .toc-page div.toc > ol.toc-list {{ column-count: {columns}; -webkit-column-count: {columns}; column-gap: 2em; -webkit-column-gap: 2em; }}\n"
+        ));
+        css.push_str(".toc-page li { break-inside: avoid; -webkit-column-break-inside: avoid; page-break-inside: avoid; }\n");
+    }
+    if toc.dotted_leaders {
+        css.push_str(".toc-page li.toc-entry > a { display: flex; align-items: baseline; }\n");
+        css.push_str(&format!(
+            ".toc-page li.toc-entry > a::after {{ content: \"\"; flex: 1 1 auto; margin-left: 0.4em; border-bottom: 1px dotted {accent}; }}\n"
+        ));
+    }
+    css
+}
+
+/// 将章节 CSS 的选择器限定到指定 body class 下。
+///
+/// `body` 选择器会被替换为 `body.<class>`，其余选择器加上 `body.<class> ` 前缀；
+/// `@` 规则（如 `@media`、`@font-face`）原样保留。
+pub(super) fn scope_chapter_css(css: &str, class: &str) -> String {
+    let scope = format!("body.{}", class);
+    let mut out = String::new();
+    let mut rest = css.trim();
+    while let Some(open) = rest.find('{') {
+        let selector = rest[..open].trim();
+        let close = match matching_brace(&rest[open..]) {
+            Some(offset) => open + offset,
+            None => break,
+        };
+        let block = &rest[open..=close];
+        if selector.starts_with('@') {
+            out.push_str(selector);
+        } else {
+            let scoped = selector
+                .split(',')
+                .map(|part| {
+                    let part = part.trim();
+                    if part == "body" {
+                        scope.clone()
+                    } else if let Some(tail) = part.strip_prefix("body") {
+                        if tail.starts_with([' ', '.', ':', '>', '#', '[']) {
+                            format!("{}{}", scope, tail)
+                        } else {
+                            format!("{} {}", scope, part)
+                        }
+                    } else {
+                        format!("{} {}", scope, part)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&scoped);
+        }
+        out.push(' ');
+        out.push_str(block);
+        out.push('\n');
+        rest = rest[close + 1..].trim_start();
+    }
+    out
+}
+
+fn matching_brace(input: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, ch) in input.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 pub(super) fn folio_divider_svg() -> &'static str {
     r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 600 80">
   <g fill="none" stroke="#6b5b4b" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
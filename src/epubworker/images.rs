@@ -0,0 +1,188 @@
+//! 构建时的图片处理：按阅读器兼容性转码 WebP/GIF，把 SVG 栅格化为 PNG，
+//! 按长边上限缩小图片并重新压缩 JPEG/PNG，墨水屏优化时转为灰度。
+//!
+//! 优化不改变文件名与格式；转码会换用新的扩展名，由调用方更新引用。
+
+use std::io::Cursor;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilterType, PngEncoder};
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
+
+use crate::{FontAsset, ImageCompatibility, ImageOptimization};
+
+/// SVG 图片的 MIME 类型。
+pub(super) const SVG_MIME: &str = "image/svg+xml";
+
+/// 墨水屏优化时的对比度增量（`DynamicImage::adjust_contrast` 的百分比）。
+const GRAYSCALE_CONTRAST: f32 = 15.0;
+
+/// 兼容性转码的结果：新的图片数据、扩展名与 MIME 类型。
+pub(super) struct Transcoded {
+    pub bytes: Vec<u8>,
+    pub extension: &'static str,
+    pub mime: &'static str,
+}
+
+/// 按兼容性配置转码单张图片。
+///
+/// 返回 `Ok(None)` 表示目标阅读器可直接显示原图。WebP 转为 JPEG（含透明通道时为 PNG），
+/// GIF 转为 PNG 并只保留首帧。墨水屏优化时 WebP 与所有 GIF 都转码，以便之后转为灰度。
+/// 开启 SVG 栅格化时，SVG 按长边上限渲染为 PNG，文字使用 `fonts` 中的嵌入字体。
+pub(super) fn transcode_image(
+    bytes: &[u8],
+    mime: &str,
+    settings: &ImageOptimization,
+    fonts: &[FontAsset],
+) -> Result<Option<Transcoded>, String> {
+    if mime == SVG_MIME {
+        if !settings.rasterize_svg {
+            return Ok(None);
+        }
+        return Ok(Some(Transcoded {
+            bytes: rasterize_svg(bytes, settings.max_dimension, fonts)?,
+            extension: "png",
+            mime: "image/png",
+        }));
+    }
+    let format = match (settings.compatibility, mime) {
+        (_, "image/webp") if settings.grayscale => ImageFormat::WebP,
+        (_, "image/gif") if settings.grayscale => ImageFormat::Gif,
+        (ImageCompatibility::Original, _) => return Ok(None),
+        (_, "image/webp") => ImageFormat::WebP,
+        (ImageCompatibility::EInk, "image/gif") if is_animated_gif(bytes)? => ImageFormat::Gif,
+        _ => return Ok(None),
+    };
+    let image =
+        image::load_from_memory_with_format(bytes, format).map_err(|err| err.to_string())?;
+
+    let mut output = Vec::new();
+    if format == ImageFormat::WebP && !image.color().has_alpha() {
+        encode_jpeg(&image, settings.jpeg_quality, &mut output)?;
+        Ok(Some(Transcoded {
+            bytes: output,
+            extension: "jpg",
+            mime: "image/jpeg",
+        }))
+    } else {
+        encode_png(&image, &mut output)?;
+        Ok(Some(Transcoded {
+            bytes: output,
+            extension: "png",
+            mime: "image/png",
+        }))
+    }
+}
+
+/// 按长边 `max_dimension` 渲染 SVG 并编码为 PNG；`0` 表示按 SVG 自身尺寸渲染。
+fn rasterize_svg(bytes: &[u8], max_dimension: u32, fonts: &[FontAsset]) -> Result<Vec<u8>, String> {
+    let mut options = resvg::usvg::Options::default();
+    for font in fonts {
+        options.fontdb_mut().load_font_data(font.bytes.to_vec());
+    }
+    let tree = resvg::usvg::Tree::from_data(bytes, &options).map_err(|err| err.to_string())?;
+    let size = tree.size();
+    let scale = match max_dimension {
+        0 => 1.0,
+        max => max as f32 / size.width().max(size.height()),
+    };
+    let width = (size.width() * scale).round().max(1.0) as u32;
+    let height = (size.height() * scale).round().max(1.0) as u32;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| format!("invalid SVG size {width}x{height}"))?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap.encode_png().map_err(|err| err.to_string())
+}
+
+/// 读取 SVG 的像素尺寸（取整），无法解析时返回 `None`。
+pub(super) fn svg_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let tree = resvg::usvg::Tree::from_data(bytes, &resvg::usvg::Options::default()).ok()?;
+    let size = tree.size();
+    Some((
+        size.width().round().max(1.0) as u32,
+        size.height().round().max(1.0) as u32,
+    ))
+}
+
+fn is_animated_gif(bytes: &[u8]) -> Result<bool, String> {
+    let decoder = GifDecoder::new(Cursor::new(bytes)).map_err(|err| err.to_string())?;
+    Ok(decoder.into_frames().take(2).count() > 1)
+}
+
+/// 优化单张图片。
+///
+/// 返回 `Ok(None)` 表示保留原图：格式不受支持（如 GIF、WebP），
+/// 或重新编码后没有变小。墨水屏优化的灰度结果总是采用。
+pub(super) fn optimize_image(
+    bytes: &[u8],
+    mime: &str,
+    settings: &ImageOptimization,
+) -> Result<Option<Vec<u8>>, String> {
+    let format = match mime {
+        "image/jpeg" | "image/jpg" => ImageFormat::Jpeg,
+        "image/png" => ImageFormat::Png,
+        _ => return Ok(None),
+    };
+    let mut image =
+        image::load_from_memory_with_format(bytes, format).map_err(|err| err.to_string())?;
+    if settings.enabled {
+        image = downscale(image, settings.max_dimension);
+    }
+    if settings.grayscale {
+        image = grayscale(&image);
+    }
+
+    let mut output = Vec::new();
+    match format {
+        ImageFormat::Jpeg => encode_jpeg(&image, settings.jpeg_quality, &mut output)?,
+        _ => encode_png(&image, &mut output)?,
+    }
+    Ok((settings.grayscale || output.len() < bytes.len()).then_some(output))
+}
+
+/// 转为 8 位灰度（保留透明通道）并提高对比度，减少墨水屏上的色带。
+fn grayscale(image: &DynamicImage) -> DynamicImage {
+    let gray = if image.color().has_alpha() {
+        DynamicImage::ImageLumaA8(image.to_luma_alpha8())
+    } else {
+        DynamicImage::ImageLuma8(image.to_luma8())
+    };
+    gray.adjust_contrast(GRAYSCALE_CONTRAST)
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8, output: &mut Vec<u8>) -> Result<(), String> {
+    let encoder = JpegEncoder::new_with_quality(output, quality.clamp(1, 100));
+    let image = if image.color().has_color() {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    } else {
+        DynamicImage::ImageLuma8(image.to_luma8())
+    };
+    image
+        .write_with_encoder(encoder)
+        .map_err(|err| err.to_string())
+}
+
+fn encode_png(image: &DynamicImage, output: &mut Vec<u8>) -> Result<(), String> {
+    let encoder = PngEncoder::new_with_quality(
+        Cursor::new(output),
+        CompressionType::Best,
+        PngFilterType::Adaptive,
+    );
+    image
+        .write_with_encoder(encoder)
+        .map_err(|err| err.to_string())
+}
+
+/// 长边超过 `max_dimension` 时等比缩小；`0` 表示不限制尺寸。
+fn downscale(image: DynamicImage, max_dimension: u32) -> DynamicImage {
+    if max_dimension == 0 || image.width().max(image.height()) <= max_dimension {
+        return image;
+    }
+    image.resize(max_dimension, max_dimension, FilterType::Lanczos3)
+}
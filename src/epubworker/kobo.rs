@@ -0,0 +1,198 @@
+//! KEPUB 改写：为 Kobo 阅读器在正文中插入 `koboSpan` 标记。
+//!
+//! Kobo 依赖 `<span class="koboSpan" id="kobo.段.句">` 定位阅读进度、统计与划线，
+//! 并要求正文包在 `#book-columns > #book-inner` 中。段号在每个块级元素处递增，
+//! 句号在段内按句末标点递增；图片单独占一个标记。
+
+/// 生成 KEPUB 时使用的文件后缀。
+const KEPUB_EXTENSION: &str = ".kepub.epub";
+
+/// 段号在这些元素开始时递增。
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "li",
+    "dt",
+    "dd",
+    "td",
+    "th",
+    "pre",
+    "figcaption",
+    "blockquote",
+    "aside",
+];
+
+/// 这些元素内的文本保持原样（内联 SVG、脚本与样式）。
+const OPAQUE_TAGS: &[&str] = &["svg", "script", "style"];
+
+const SENTENCE_END: &[char] = &['.', '!', '?', '。', '！', '？', '…'];
+const CLOSING: &[char] = &['"', '\'', '”', '’', '」', '』', '）', ')', '】', '》', '〉'];
+
+/// 把输出文件名换成 `.kepub.epub` 后缀。
+pub(crate) fn kepub_filename(filename: &str) -> String {
+    if filename.ends_with(KEPUB_EXTENSION) {
+        return filename.to_string();
+    }
+    let stem = filename.strip_suffix(".epub").unwrap_or(filename);
+    format!("{stem}{KEPUB_EXTENSION}")
+}
+
+/// 为一篇 XHTML 插入 Kobo 标记；没有 `<body>` 时原样返回。
+pub(super) fn kepubify(html: &str) -> String {
+    let Some(body_start) = html.find("<body") else {
+        return html.to_string();
+    };
+    let Some(open_end) = html[body_start..].find('>').map(|pos| body_start + pos + 1) else {
+        return html.to_string();
+    };
+    let Some(body_end) = html.rfind("</body>").filter(|&end| end >= open_end) else {
+        return html.to_string();
+    };
+
+    let mut out = String::with_capacity(html.len() + html.len() / 2);
+    out.push_str(&html[..open_end]);
+    out.push_str("<div id=\"book-columns\"><div id=\"book-inner\">");
+    let mut spans = SpanCounter::default();
+    let mut opaque_depth = 0usize;
+    let mut rest = &html[open_end..body_end];
+    while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = tag_end(rest);
+            let tag = &rest[..end];
+            let name = tag_name(tag);
+            let closing = tag.starts_with("</");
+            let self_closing = tag.ends_with("/>");
+            if OPAQUE_TAGS.contains(&name.as_str()) {
+                if closing {
+                    opaque_depth = opaque_depth.saturating_sub(1);
+                } else if !self_closing {
+                    opaque_depth += 1;
+                }
+                out.push_str(tag);
+            } else if opaque_depth == 0 && !closing && BLOCK_TAGS.contains(&name.as_str()) {
+                spans.next_paragraph();
+                out.push_str(tag);
+            } else if opaque_depth == 0 && name == "img" {
+                spans.push_open(&mut out);
+                out.push_str(tag);
+                out.push_str("</span>");
+            } else {
+                out.push_str(tag);
+            }
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = &rest[..end];
+            if opaque_depth > 0 || text.trim().is_empty() {
+                out.push_str(text);
+            } else {
+                for sentence in split_sentences(text) {
+                    if sentence.trim().is_empty() {
+                        out.push_str(sentence);
+                    } else {
+                        spans.push_open(&mut out);
+                        out.push_str(sentence);
+                        out.push_str("</span>");
+                    }
+                }
+            }
+            rest = &rest[end..];
+        }
+    }
+    out.push_str("</div></div>");
+    out.push_str(&html[body_end..]);
+    out
+}
+
+#[derive(Default)]
+struct SpanCounter {
+    paragraph: usize,
+    sentence: usize,
+}
+
+impl SpanCounter {
+    fn next_paragraph(&mut self) {
+        self.paragraph += 1;
+        self.sentence = 0;
+    }
+
+    fn push_open(&mut self, out: &mut String) {
+        // 块级元素之外的文本（如标题装饰）也需要有效的段号。
+        if self.paragraph == 0 {
+            self.paragraph = 1;
+        }
+        self.sentence += 1;
+        out.push_str(&format!(
+            "<span class=\"koboSpan\" id=\"kobo.{}.{}\">",
+            self.paragraph, self.sentence
+        ));
+    }
+}
+
+/// 标签结束位置（含 `>`）；注释按 `-->` 结束，跳过属性值中的 `>`。
+fn tag_end(input: &str) -> usize {
+    if input.starts_with("<!--") {
+        return input.find("-->").map_or(input.len(), |pos| pos + 3);
+    }
+    let mut quote = None;
+    for (index, ch) in input.char_indices().skip(1) {
+        match (quote, ch) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '>') => return index + 1,
+            _ => {}
+        }
+    }
+    input.len()
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|ch| ch.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// 按句末标点切分文本，句末的闭合引号与括号归入前一句，句间空白单独成段。
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        if !SENTENCE_END.contains(&ch) {
+            continue;
+        }
+        while let Some(&(_, next)) = chars.peek() {
+            if SENTENCE_END.contains(&next) || CLOSING.contains(&next) {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let end = chars.peek().map_or(text.len(), |&(index, _)| index);
+        parts.push(&text[start..end]);
+        start = end;
+        let ws_end = text[start..]
+            .char_indices()
+            .find(|(_, ch)| !ch.is_whitespace())
+            .map_or(text.len(), |(index, _)| start + index);
+        if ws_end > start {
+            parts.push(&text[start..ws_end]);
+            start = ws_end;
+            while chars.peek().is_some_and(|&(index, _)| index < ws_end) {
+                chars.next();
+            }
+        }
+    }
+    if start < text.len() {
+        parts.push(&text[start..]);
+    }
+    parts
+}
@@ -1,6 +1,8 @@
-use epub_builder::{EpubBuilder, MetadataOpf, ZipLibrary};
+use epub_builder::{EpubBuilder, MetadataOpf, MetadataOpfV3, ZipLibrary};
 
 use super::BuildError;
+use super::render::escape_html;
+use crate::{Accessibility, BookInfo, Creator, CreatorRole, EpubVersion, MetadataEntry};
 
 pub(super) fn add_optional_metadata(
     builder: &mut EpubBuilder<ZipLibrary>,
@@ -26,3 +28,148 @@ pub(super) fn add_optional_meta_tag(
         content: value.trim().to_string(),
     }));
 }
+
+/// 把作者交给 epub-builder，生成 `dc:creator`。
+pub(super) fn add_authors(
+    builder: &mut EpubBuilder<ZipLibrary>,
+    creators: &[Creator],
+) -> Result<(), BuildError> {
+    for creator in creators {
+        if creator.role == CreatorRole::Author {
+            add_optional_metadata(builder, "author", creator.name.trim())?;
+        }
+    }
+    Ok(())
+}
+
+/// ISBN 对应的附加 `dc:identifier`；`unique-identifier` 仍指向 UUID 标识。
+///
+/// EPUB 2 用 `opf:scheme="ISBN"` 标注，EPUB 3 写成 `urn:isbn:` URN。
+pub(super) fn isbn_identifier(isbn: &str, version: EpubVersion) -> String {
+    let isbn = isbn.trim();
+    if isbn.is_empty() {
+        return String::new();
+    }
+    match version {
+        EpubVersion::Epub2 => format!(
+            "    <dc:identifier opf:scheme=\"ISBN\">{}</dc:identifier>\n",
+            escape_html(isbn)
+        ),
+        EpubVersion::Epub3 => {
+            let digits: String = isbn
+                .chars()
+                .filter(|ch| !ch.is_whitespace() && *ch != '-')
+                .collect();
+            format!(
+                "    <dc:identifier id=\"isbn\">urn:isbn:{}</dc:identifier>\n",
+                escape_html(&digits)
+            )
+        }
+    }
+}
+
+/// 译者、插画、编辑对应的 `dc:contributor` 元素。
+///
+/// epub-builder 只会生成作者，这些元素在生成后补写进 OPF。EPUB 2 用 `opf:role`
+/// 标注角色，EPUB 3 用 `role` 细化元数据。
+pub(super) fn contributor_metadata(creators: &[Creator], version: EpubVersion) -> String {
+    let contributors = creators
+        .iter()
+        .filter(|creator| creator.role != CreatorRole::Author && !creator.name.trim().is_empty());
+    let mut out = String::new();
+    for (index, creator) in contributors.enumerate() {
+        let name = escape_html(creator.name.trim());
+        let code = creator.role.marc_code();
+        match version {
+            EpubVersion::Epub2 => out.push_str(&format!(
+                "    <dc:contributor opf:role=\"{code}\">{name}</dc:contributor>\n"
+            )),
+            EpubVersion::Epub3 => {
+                let id = format!("contributor-{}", index + 1);
+                out.push_str(&format!(
+                    "    <dc:contributor id=\"{id}\">{name}</dc:contributor>\n    \
+                     <meta refines=\"#{id}\" property=\"role\" scheme=\"marc:relators\">{code}</meta>\n"
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// 自定义元数据对应的 OPF 元素，与 `dc:contributor` 一样在生成后补写进 OPF。
+///
+/// epub-builder 的 `<meta>` 不转义属性中的引号，Calibre 自定义列的 JSON 值会破坏 XML，
+/// 所以这里自行转义；键无效或值为空的条目跳过。
+pub(super) fn custom_metadata(entries: &[MetadataEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries.iter().filter(|entry| entry.is_valid()) {
+        let value = escape_html(entry.value.trim());
+        match entry.dc_element() {
+            Some(name) => out.push_str(&format!("    <dc:{name}>{value}</dc:{name}>\n")),
+            None => out.push_str(&format!(
+                "    <meta name=\"{}\" content=\"{value}\"/>\n",
+                escape_html(entry.key.trim())
+            )),
+        }
+    }
+    out
+}
+
+/// schema.org 无障碍元数据对应的 OPF 元素，在生成后补写进 OPF。
+///
+/// EPUB 3 写成 `<meta property="schema:…">`（`schema` 是保留前缀），EPUB 2 写成
+/// `<meta name="schema:…" content="…"/>`。
+pub(super) fn accessibility_metadata(
+    accessibility: &Accessibility,
+    version: EpubVersion,
+) -> String {
+    let mut out = String::new();
+    for (property, value) in accessibility.properties() {
+        let value = escape_html(value);
+        match version {
+            EpubVersion::Epub2 => out.push_str(&format!(
+                "    <meta name=\"schema:{property}\" content=\"{value}\"/>\n"
+            )),
+            EpubVersion::Epub3 => out.push_str(&format!(
+                "    <meta property=\"schema:{property}\">{value}</meta>\n"
+            )),
+        }
+    }
+    out
+}
+
+/// 写入系列元数据。
+///
+/// 两个版本都写 Calibre 的 `calibre:series` / `calibre:series_index`；EPUB 3 另外写
+/// 标准的 `belongs-to-collection`，供 Apple Books 等阅读器分组。
+pub(super) fn add_series_metadata(
+    builder: &mut EpubBuilder<ZipLibrary>,
+    book_info: &BookInfo,
+    version: EpubVersion,
+) {
+    let series = book_info.series.trim();
+    if series.is_empty() {
+        return;
+    }
+    let index = book_info.series_index.trim();
+    let index = index.parse::<f64>().is_ok().then_some(index);
+
+    add_optional_meta_tag(builder, "calibre:series", series);
+    if let Some(index) = index {
+        add_optional_meta_tag(builder, "calibre:series_index", index);
+    }
+    if version == EpubVersion::Epub3 {
+        // `MetadataOpfV3` 不转义元素内容，这里先转义。
+        let meta = |property: &str, content: &str, id: Option<&str>, refines: Option<&str>| {
+            let mut meta = MetadataOpfV3::new(property.to_string(), escape_html(content));
+            meta.id = id.map(str::to_string);
+            meta.refines = refines.map(str::to_string);
+            Box::new(meta)
+        };
+        builder.add_metadata_opf(meta("belongs-to-collection", series, Some("series"), None));
+        builder.add_metadata_opf(meta("collection-type", "series", None, Some("#series")));
+        if let Some(index) = index {
+            builder.add_metadata_opf(meta("group-position", index, None, Some("#series")));
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! IDPF 字体混淆（OCF `http://www.idpf.org/2008/embedding` 算法）。
+//!
+//! 以出版物唯一标识（去掉空白后）的 SHA-1 作为 20 字节密钥，与字体前 1040 字节逐字节异或；
+//! 同时在 `META-INF/encryption.xml` 中登记被混淆的资源，阅读器据此还原字体。
+//! 混淆并非加密，只用于满足商业字体“不得以可直接提取的形式分发”的授权要求。
+
+use sha1::{Digest, Sha1};
+
+const ALGORITHM: &str = "http://www.idpf.org/2008/embedding";
+const OBFUSCATED_LENGTH: usize = 1040;
+
+/// 由出版物唯一标识（如 `urn:uuid:...`）计算混淆密钥。
+pub(super) fn obfuscation_key(identifier: &str) -> [u8; 20] {
+    let cleaned: String = identifier
+        .chars()
+        .filter(|ch| !matches!(ch, ' ' | '\t' | '\r' | '\n'))
+        .collect();
+    Sha1::digest(cleaned.as_bytes()).into()
+}
+
+/// 就地混淆（或还原）字体数据；算法对称，重复调用即可还原。
+pub(super) fn obfuscate_font(data: &mut [u8], key: &[u8; 20]) {
+    for (index, byte) in data.iter_mut().take(OBFUSCATED_LENGTH).enumerate() {
+        *byte ^= key[index % key.len()];
+    }
+}
+
+/// 生成登记混淆资源的 `encryption.xml`，`paths` 为容器内完整路径。
+pub(super) fn encryption_xml(paths: &[String]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<encryption xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\" xmlns:enc=\"http://www.w3.org/2001/04/xmlenc#\">\n",
+    );
+    for path in paths {
+        xml.push_str(&format!(
+            "  <enc:EncryptedData>\n    <enc:EncryptionMethod Algorithm=\"{}\"/>\n    <enc:CipherData>\n      <enc:CipherReference URI=\"{}\"/>\n    </enc:CipherData>\n  </enc:EncryptedData>\n",
+            ALGORITHM,
+            super::render::escape_html(path)
+        ));
+    }
+    xml.push_str("</encryption>\n");
+    xml
+}
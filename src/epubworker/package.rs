@@ -0,0 +1,241 @@
+//! 生成后改写 EPUB 容器：epub-builder 不支持的内容在这里补写。
+//!
+//! 目前用于补写 `META-INF/encryption.xml`、为 EPUB 2 的 spine 添加翻页方向，
+//! 在 OPF 元数据中补写 `dc:contributor` 等元素，为含内联 SVG 的页面声明 `properties="svg"`，
+//! 为 EPUB 3 导航文档的目录补上 ARIA 角色、重写 landmarks、追加 page-list，
+//! 用自行渲染的内嵌目录页替换 `toc.xhtml`，为可复现输出固定时间戳与条目顺序，
+//! 以及按压缩设置重新压缩各条目。
+
+use std::io::{Cursor, Read, Seek, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipArchive, ZipWriter};
+
+use super::BuildError;
+use crate::ZipCompression;
+
+const OPF_PATH: &str = "OEBPS/content.opf";
+const ENCRYPTION_PATH: &str = "META-INF/encryption.xml";
+const EPUB2_SPINE: &str = "<spine toc=\"ncx\">";
+const NAV_PATH: &str = "OEBPS/nav.xhtml";
+const TOC_PAGE_PATH: &str = "OEBPS/toc.xhtml";
+const TOC_NAV: &str = "<nav epub:type = \"toc\" id=\"toc\">";
+const LANDMARKS_NAV: &str = "<nav epub:type = \"landmarks\">";
+const MODIFIED_META: &str = "<meta property=\"dcterms:modified\">";
+/// 可复现输出写入的修改时间，与 ZIP 条目的固定时间戳（1980-01-01）一致。
+const REPRODUCIBLE_MODIFIED: &str = "1980-01-01T00:00:00Z";
+/// 本身已压缩、`store_images` 时不再压缩的图片扩展名。
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "webp", "avif"];
+
+/// 需要对已生成 EPUB 做的改写。
+#[derive(Default)]
+pub(super) struct PackagePatch {
+    /// 插入到 `mimetype` 之后的 `META-INF/encryption.xml` 内容。
+    pub encryption_xml: Option<String>,
+    /// 为 EPUB 2 的 `<spine>` 添加 `page-progression-direction="rtl"`。
+    pub rtl_spine: bool,
+    /// 含内联 `<svg>` 的 XHTML 页面（相对 `OEBPS`），EPUB 3 清单中需声明 `properties="svg"`。
+    pub svg_documents: Vec<String>,
+    /// 插入到 OPF `</metadata>` 之前的元数据元素。
+    pub opf_metadata: String,
+    /// 为 EPUB 3 目录的 `<nav epub:type="toc">` 添加 `role="doc-toc"`。
+    pub toc_role: bool,
+    /// 替换 epub-builder 生成的 landmarks `<nav>`；为空时保持原样。
+    ///
+    /// epub-builder 只列出有标题的页面，且把每一章都标为 `bodymatter`。
+    pub landmarks: String,
+    /// 插入到导航文档 `</body>` 之前的 page-list `<nav>`；为空时不插入。
+    pub page_list: String,
+    /// 替换 epub-builder 内嵌目录页 `toc.xhtml` 的完整内容。
+    pub toc_page: Option<String>,
+    /// 可复现输出：条目按名称排序（`mimetype` 仍在最前），时间戳与 `dcterms:modified` 固定。
+    pub reproducible: bool,
+    /// ZIP 压缩设置；与默认值不同时重新压缩全部条目，`mimetype` 始终不压缩。
+    pub compression: ZipCompression,
+}
+
+impl PackagePatch {
+    pub(super) fn is_empty(&self) -> bool {
+        self.encryption_xml.is_none()
+            && !self.patches_opf()
+            && !self.patches_nav()
+            && self.toc_page.is_none()
+            && !self.reproducible
+            && !self.recompresses()
+    }
+
+    fn recompresses(&self) -> bool {
+        self.compression != ZipCompression::default()
+    }
+
+    fn patches_nav(&self) -> bool {
+        self.toc_role || !self.landmarks.is_empty() || !self.page_list.is_empty()
+    }
+
+    fn patches_opf(&self) -> bool {
+        self.rtl_spine
+            || !self.svg_documents.is_empty()
+            || !self.opf_metadata.is_empty()
+            || self.reproducible
+    }
+}
+
+/// 原样复制已生成的 EPUB，并按 `patch` 补写或改写其中的条目。
+pub(super) fn write_patched_epub<W: Write + Seek>(
+    epub: &[u8],
+    writer: W,
+    patch: &PackagePatch,
+) -> Result<(), BuildError> {
+    let zip_error = |err: zip::result::ZipError| BuildError::Io(std::io::Error::other(err));
+    let mut archive = ZipArchive::new(Cursor::new(epub)).map_err(zip_error)?;
+    let mut output = ZipWriter::new(writer);
+    let file_options = |name: &str| {
+        let mut options = SimpleFileOptions::default();
+        if patch.recompresses() {
+            let compression = patch.compression;
+            options = if compression.level == 0 || (compression.store_images && is_image(name)) {
+                options.compression_method(CompressionMethod::Stored)
+            } else {
+                options.compression_level(Some(i64::from(compression.level.min(9))))
+            };
+        }
+        if patch.reproducible {
+            options = options.last_modified_time(DateTime::default());
+        }
+        options
+    };
+    let mut order: Vec<usize> = (0..archive.len()).collect();
+    if patch.reproducible {
+        let mut names = Vec::with_capacity(order.len());
+        for index in 0..archive.len() {
+            let name = archive
+                .by_index_raw(index)
+                .map_err(zip_error)?
+                .name()
+                .to_string();
+            names.push((name != "mimetype", name));
+        }
+        order.sort_by(|a, b| names[*a].cmp(&names[*b]));
+    }
+    for index in order {
+        if patch.patches_opf() && archive.by_index_raw(index).map_err(zip_error)?.name() == OPF_PATH
+        {
+            let mut opf = String::new();
+            archive
+                .by_index(index)
+                .map_err(zip_error)?
+                .read_to_string(&mut opf)?;
+            if patch.rtl_spine {
+                opf = opf.replacen(
+                    EPUB2_SPINE,
+                    "<spine toc=\"ncx\" page-progression-direction=\"rtl\">",
+                    1,
+                );
+            }
+            // 清单位于 guide 之前，只改写第一处同名引用即是 `<item>`。
+            for href in &patch.svg_documents {
+                opf = opf.replacen(
+                    &format!(" href=\"{href}\"/>"),
+                    &format!(" href=\"{href}\" properties=\"svg\"/>"),
+                    1,
+                );
+            }
+            if !patch.opf_metadata.is_empty() {
+                let metadata = format!("{}  </metadata>", patch.opf_metadata);
+                opf = opf.replacen("  </metadata>", &metadata, 1);
+            }
+            if patch.reproducible
+                && let Some(start) = opf
+                    .find(MODIFIED_META)
+                    .map(|start| start + MODIFIED_META.len())
+                && let Some(len) = opf[start..].find("</meta>")
+            {
+                opf.replace_range(start..start + len, REPRODUCIBLE_MODIFIED);
+            }
+            output
+                .start_file(OPF_PATH, file_options(OPF_PATH))
+                .map_err(zip_error)?;
+            output.write_all(opf.as_bytes())?;
+            continue;
+        }
+        let name = archive
+            .by_index_raw(index)
+            .map_err(zip_error)?
+            .name()
+            .to_string();
+        if name == TOC_PAGE_PATH
+            && let Some(toc_page) = &patch.toc_page
+        {
+            output
+                .start_file(name.as_str(), file_options(&name))
+                .map_err(zip_error)?;
+            output.write_all(toc_page.as_bytes())?;
+            continue;
+        }
+        if patch.patches_nav() && name == NAV_PATH {
+            let mut nav = String::new();
+            archive
+                .by_index(index)
+                .map_err(zip_error)?
+                .read_to_string(&mut nav)?;
+            if patch.toc_role {
+                nav = nav.replacen(
+                    TOC_NAV,
+                    "<nav epub:type = \"toc\" id=\"toc\" role=\"doc-toc\">",
+                    1,
+                );
+            }
+            if !patch.landmarks.is_empty()
+                && let Some(start) = nav.find(LANDMARKS_NAV)
+                && let Some(len) = nav[start..].find("</nav>")
+            {
+                nav.replace_range(start..start + len + "</nav>".len(), &patch.landmarks);
+            }
+            if !patch.page_list.is_empty() {
+                nav = nav.replacen("</body>", &format!("{}</body>", patch.page_list), 1);
+            }
+            output
+                .start_file(name.as_str(), file_options(&name))
+                .map_err(zip_error)?;
+            output.write_all(nav.as_bytes())?;
+            continue;
+        }
+        let is_mimetype = name == "mimetype";
+        if patch.recompresses() && !is_mimetype {
+            let mut bytes = Vec::new();
+            archive
+                .by_index(index)
+                .map_err(zip_error)?
+                .read_to_end(&mut bytes)?;
+            output
+                .start_file(name.as_str(), file_options(&name))
+                .map_err(zip_error)?;
+            output.write_all(&bytes)?;
+            continue;
+        }
+        let entry = archive.by_index_raw(index).map_err(zip_error)?;
+        if patch.reproducible {
+            output
+                .raw_copy_file_touch(entry, DateTime::default(), None)
+                .map_err(zip_error)?;
+        } else {
+            output.raw_copy_file(entry).map_err(zip_error)?;
+        }
+        if let (true, Some(xml)) = (is_mimetype, &patch.encryption_xml) {
+            output
+                .start_file(ENCRYPTION_PATH, file_options(ENCRYPTION_PATH))
+                .map_err(zip_error)?;
+            output.write_all(xml.as_bytes())?;
+        }
+    }
+    output.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+fn is_image(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .any(|image| extension.eq_ignore_ascii_case(image))
+    })
+}
@@ -1,34 +1,123 @@
-use crate::{BookInfo, ChapterDraft, CssTemplate, ImageAsset, TextStyle};
+use std::borrow::Cow;
 
-pub(super) fn render_chapter(
-    chapter: &ChapterDraft,
-    language: &str,
-    style: &TextStyle,
-    template: CssTemplate,
-    chapter_index: usize,
-    header_image: Option<&ImageAsset>,
-    header_fullbleed: bool,
-) -> String {
-    let mut html = String::new();
+use crate::{
+    BackMatterKind, BookInfo, ChapterDraft, ChapterHeaderImage, ChapterVariant, CssTemplate,
+    EpubVersion, FrontMatterPage, ImageAsset, TextStyle, TocOptions,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::css::scope_chapter_css;
+
+/// 章节渲染所需的全书级参数。
+pub(super) struct ChapterRenderOptions<'a> {
+    pub(super) language: &'a str,
+    pub(super) style: &'a TextStyle,
+    pub(super) template: CssTemplate,
+    /// 章节头图；章节自带头图时由调用方替换为该章的图片。
+    pub(super) header_image: Option<&'a ImageAsset>,
+    pub(super) header_fullbleed: bool,
+    pub(super) epub_version: EpubVersion,
+    /// 正文按 Markdown 渲染行内格式与引用块。
+    pub(super) markdown: bool,
+    /// 插入到本章正文末尾的插图，按插图列表顺序排列。
+    pub(super) images: &'a [&'a ImageAsset],
+}
+
+/// 写入 XHTML 文档开头直到 `<head>` 内样式表链接为止的部分。
+///
+/// EPUB 2 使用 XHTML 1.1 doctype；EPUB 3 使用 HTML5 doctype 并声明 `epub` 命名空间。
+fn push_document_head(html: &mut String, language: &str, epub_version: EpubVersion) {
     html.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
     html.push('\n');
-    html.push_str(
-        r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">"#,
-    );
-    html.push('\n');
-    html.push_str(&format!(
-        r#"<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="{}">"#,
-        language
-    ));
-    html.push('\n');
-    html.push_str("<head>");
-    html.push('\n');
-    html.push_str(
-        r#"<meta http-equiv="Content-Type" content="application/xhtml+xml; charset=utf-8"/>"#,
-    );
+    if epub_version.is_epub3() {
+        html.push_str("<!DOCTYPE html>");
+        html.push('\n');
+        html.push_str(&format!(
+            r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{}" lang="{}">"#,
+            language, language
+        ));
+        html.push('\n');
+        html.push_str("<head>");
+        html.push('\n');
+        html.push_str(r#"<meta charset="utf-8"/>"#);
+    } else {
+        html.push_str(
+            r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">"#,
+        );
+        html.push('\n');
+        html.push_str(&format!(
+            r#"<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="{}">"#,
+            language
+        ));
+        html.push('\n');
+        html.push_str("<head>");
+        html.push('\n');
+        html.push_str(
+            r#"<meta http-equiv="Content-Type" content="application/xhtml+xml; charset=utf-8"/>"#,
+        );
+    }
     html.push('\n');
     html.push_str(r#"<link rel="stylesheet" type="text/css" href="stylesheet.css"/>"#);
     html.push('\n');
+}
+
+/// EPUB 3 下为 `<body>` 追加 `epub:type` 语义属性。
+fn epub_type_attr(epub_version: EpubVersion, epub_type: &str) -> String {
+    if epub_version.is_epub3() {
+        format!(" epub:type=\"{}\"", epub_type)
+    } else {
+        String::new()
+    }
+}
+
+/// EPUB 3 下同时追加 `epub:type` 与对应的 DPUB-ARIA `role`，供辅助技术识别。
+fn semantic_attrs(epub_version: EpubVersion, epub_type: &str, role: &str) -> String {
+    if epub_version.is_epub3() {
+        format!(" epub:type=\"{}\" role=\"{}\"", epub_type, role)
+    } else {
+        String::new()
+    }
+}
+
+pub(super) fn render_chapter(
+    chapter: &ChapterDraft,
+    chapter_index: usize,
+    options: &ChapterRenderOptions<'_>,
+) -> String {
+    let ChapterRenderOptions {
+        language,
+        style,
+        template,
+        header_image,
+        header_fullbleed,
+        epub_version,
+        markdown,
+        images,
+    } = *options;
+    let vertical_chapter;
+    let chapter = if style.vertical {
+        vertical_chapter = ChapterDraft {
+            title: vertical_punctuation(&chapter.title),
+            content: vertical_punctuation(&chapter.content),
+            ..chapter.clone()
+        };
+        &vertical_chapter
+    } else {
+        chapter
+    };
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    // 章节专属样式使用唯一 body class 限定作用域，避免影响其他章节。
+    let chapter_css_class = if chapter.custom_css.trim().is_empty() {
+        None
+    } else {
+        let class = format!("chapter-css-{:04}", chapter_index);
+        html.push_str("<style type=\"text/css\">\n/*<![CDATA[*/\n");
+        html.push_str(&scope_chapter_css(&chapter.custom_css, &class));
+        html.push_str("/*]]>*/\n</style>\n");
+        Some(class)
+    };
     html.push_str("</head>");
     html.push('\n');
     let base_body_class = if matches!(template, CssTemplate::Fantasy) {
@@ -36,13 +125,41 @@ pub(super) fn render_chapter(
     } else {
         "chapter"
     };
-    let body_class = merge_classes(base_body_class, &style.extra_body_class);
-    html.push_str(&format!("<body class=\"{}\">", escape_html(&body_class)));
+    let mut body_class = merge_classes(base_body_class, &style.extra_body_class);
+    if let Some(class) = chapter.variant.body_class() {
+        body_class = merge_classes(&body_class, class);
+    }
+    body_class = merge_classes(&body_class, &chapter.css_class);
+    if let Some(class) = chapter_css_class.as_ref() {
+        body_class = merge_classes(&body_class, class);
+    }
+    html.push_str(&format!(
+        "<body class=\"{}\"{}>",
+        escape_html(&body_class),
+        epub_type_attr(epub_version, "bodymatter")
+    ));
     html.push('\n');
+    // `<body>` 上不允许 ARIA 角色，EPUB 3 的章节语义放在包裹正文的 `<section>` 上。
+    if epub_version.is_epub3() {
+        html.push_str(&format!(
+            "<section{}>\n",
+            semantic_attrs(epub_version, "chapter", "doc-chapter")
+        ));
+    }
 
-    if matches!(template, CssTemplate::Fantasy) {
-        if let Some((chapter_no, chapter_title)) = split_chinese_chapter_title(chapter.title.trim())
-        {
+    let show_header_image = !matches!(chapter.header_image, ChapterHeaderImage::Hidden);
+    let plain = chapter.variant == ChapterVariant::Plain;
+    let fantasy = matches!(template, CssTemplate::Fantasy);
+    let structured = structured_number_title(chapter);
+    let fantasy_title = if fantasy && !plain {
+        structured
+            .clone()
+            .or_else(|| split_chinese_chapter_title(chapter.title.trim()))
+    } else {
+        None
+    };
+    if let Some((chapter_no, chapter_title)) = fantasy_title {
+        if show_header_image {
             html.push_str("<div class=\"Header-image-dk\">");
             let fantasy_header_src = header_image
                 .map(|asset| format!("images/{}", asset.name))
@@ -52,28 +169,35 @@ pub(super) fn render_chapter(
                 escape_html(&fantasy_header_src)
             ));
             html.push_str("</div>\n");
-            let hidden_class = merge_classes("chapter-title-hidden", &style.extra_title_class);
-            html.push_str(&format!(
-                "<h2 class=\"{}\">{}</h2>\n",
-                escape_html(&hidden_class),
-                escape_html(chapter.title.trim())
-            ));
+        }
+        let hidden_class = merge_classes("chapter-title-hidden", &style.extra_title_class);
+        let hidden_title = if style.strip_heading_number {
+            chapter_title.as_str()
+        } else {
+            chapter.title.trim()
+        };
+        html.push_str(&format!(
+            "<h2 class=\"{}\">{}</h2>\n",
+            escape_html(&hidden_class),
+            escape_html(hidden_title)
+        ));
+        if !style.strip_heading_number {
             html.push_str(&format!(
-                "<p class=\"nt\"><img class=\"emoji\" src=\"images/4star.webp\" alt=\"\"/> {} <img class=\"emoji\" src=\"images/4star.webp\" alt=\"\"/></p>\n",
-                escape_html(&chapter_no)
-            ));
+                    "<p class=\"nt\"><img class=\"emoji\" src=\"images/4star.webp\" alt=\"\"/> {} <img class=\"emoji\" src=\"images/4star.webp\" alt=\"\"/></p>\n",
+                    escape_html(&chapter_no)
+                ));
             html.push_str(&format!(
                 "<p class=\"et\">CHAPTER{:02}</p>\n",
                 chapter_index
             ));
-            html.push_str(&format!(
-                "<p class=\"ct\"><img class=\"emoji1\" src=\"images/ttl.webp\" alt=\"\"/> {} <img class=\"emoji1\" src=\"images/ttr.webp\" alt=\"\"/></p>\n",
-                escape_html(&chapter_title)
-            ));
-        } else {
-            append_standard_chapter_header(&mut html, chapter.title.trim(), language, style);
         }
+        html.push_str(&format!(
+            "<p class=\"ct\"><img class=\"emoji1\" src=\"images/ttl.webp\" alt=\"\"/> {} <img class=\"emoji1\" src=\"images/ttr.webp\" alt=\"\"/></p>\n",
+            escape_html(&chapter_title)
+        ));
     } else {
+        // Fantasy 模板下无法拆分的标题沿用原有行为，不显示头图；简洁版式除外。
+        let header_image = header_image.filter(|_| show_header_image && (!fantasy || plain));
         if let Some(header) = header_image {
             let header_class = if header_fullbleed {
                 "chapter-head-image fullbleed"
@@ -86,26 +210,138 @@ pub(super) fn render_chapter(
                 escape_html(&header.name)
             ));
         }
-        append_standard_chapter_header(&mut html, chapter.title.trim(), language, style);
+        let title = chapter.title.trim();
+        if plain {
+            append_plain_chapter_header(&mut html, title, structured, language, style);
+        } else {
+            append_standard_chapter_header(&mut html, title, structured, language, style);
+        }
     }
 
+    let (content, footnotes) = extract_footnotes(&chapter.content);
+    let mut referenced = vec![false; footnotes.len()];
+    let render_line = |line: &str| {
+        let line = protect_escapes(line);
+        let html = if markdown {
+            render_markdown_inline(&line)
+        } else if style.inline_markup {
+            render_plain_emphasis(&escape_html(&line))
+        } else {
+            escape_html(&line)
+        };
+        // 注音与强调同属行内标记，未开启时 `{…|…}` 按原文输出。
+        let html = if style.inline_markup {
+            render_ruby(&html, epub_version)
+        } else {
+            html
+        };
+        restore_escapes(&html)
+    };
     let indent = format!("{:.2}", style.text_indent);
-    for (idx, mut paragraph) in split_paragraphs(&chapter.content).into_iter().enumerate() {
+    let paragraphs = split_paragraphs(&content, style.reflow());
+    let mut block: Option<ParagraphBlock> = None;
+    // 块没有结束标记时只包裹一段有内容的段落。
+    let mut single = false;
+    // 场景分隔后的第一段与章首段一样不缩进。
+    let mut after_break = false;
+    // 独占段落的页码标记并入下一段段首；章首的标记不影响首段样式。
+    let mut pending_pages = String::new();
+    let mut first_idx = 0;
+    let mut pages = Vec::new();
+    for (idx, original) in paragraphs.iter().enumerate() {
+        if let [line] = original.as_slice()
+            && is_scene_break(line)
+        {
+            html.push_str("<hr class=\"scene-break\"/>\n");
+            after_break = true;
+            continue;
+        }
+        if original.iter().all(|line| is_page_marker_line(line)) {
+            pending_pages.extend(original.iter().map(|line| line.trim()));
+            if idx == first_idx {
+                first_idx += 1;
+            }
+            continue;
+        }
+        let mut paragraph = original.clone();
+        if block.is_none()
+            && let Some(opened) = extract_block_marker(&mut paragraph)
+        {
+            single = !block_closes(&paragraphs[idx..], opened);
+            html.push_str(opened.open_tag());
+            block = Some(opened);
+        }
+        let mut closes = block.is_some_and(|current| strip_block_close(&mut paragraph, current));
         let marker_class = extract_marker_class(&mut paragraph);
-        let joined = paragraph
-            .iter()
-            .map(|line| escape_html(line))
-            .collect::<Vec<_>>()
-            .join("<br/>");
+        if paragraph.is_empty() {
+            if closes && let Some(current) = block.take() {
+                html.push_str(current.close_tag());
+            }
+            continue;
+        }
+        closes |= single;
+        let flush = std::mem::take(&mut after_break);
+        let blockquote = markdown && block.is_none() && strip_blockquote_markers(&mut paragraph);
+        // 只含页码标记的行与下一行连在一起，不产生换行。
+        let mut joined = std::mem::take(&mut pending_pages);
+        let mut line_break = false;
+        for line in &paragraph {
+            if line_break {
+                joined.push_str("<br/>");
+            }
+            joined.push_str(&render_line(line));
+            line_break = !is_page_marker_line(line);
+        }
+        let joined = link_footnote_refs(&joined, &footnotes, &mut referenced, epub_version);
+        let joined = if style.vertical {
+            upright_digits(&joined)
+        } else {
+            joined
+        };
+        let joined = link_page_markers(&joined, epub_version, &mut pages);
+        if let Some(current) = block {
+            let base_class = match current {
+                ParagraphBlock::Quote => {
+                    merge_classes("chapter-paragraph", &style.extra_paragraph_class)
+                }
+                ParagraphBlock::Verse => String::from("verse-stanza"),
+            };
+            let paragraph_class = match marker_class.as_ref() {
+                Some(marker_class) => merge_classes(&base_class, marker_class),
+                None => base_class,
+            };
+            html.push_str(&format!(
+                "<p class=\"{}\">{}</p>\n",
+                escape_html(&paragraph_class),
+                joined
+            ));
+            if closes {
+                html.push_str(current.close_tag());
+                block = None;
+                single = false;
+            }
+            continue;
+        }
+        if blockquote {
+            html.push_str(&format!(
+                "<blockquote><p class=\"{}\">{}</p></blockquote>\n",
+                escape_html(&merge_classes(
+                    "chapter-paragraph",
+                    &style.extra_paragraph_class
+                )),
+                joined
+            ));
+            continue;
+        }
         let mut paragraph_class = String::from("chapter-paragraph");
-        if idx == 0 {
+        if idx == first_idx {
             paragraph_class.push_str(" chapter-paragraph-first");
         }
         paragraph_class = merge_classes(&paragraph_class, &style.extra_paragraph_class);
         if let Some(marker_class) = marker_class.as_ref() {
             paragraph_class = merge_classes(&paragraph_class, marker_class);
         }
-        if idx == 0 {
+        if idx == first_idx || flush {
             html.push_str(&format!(
                 "<p class=\"{}\" style=\"text-indent: 0.00em;\">{}</p>\n",
                 escape_html(&paragraph_class),
@@ -120,140 +356,1064 @@ pub(super) fn render_chapter(
             ));
         }
     }
+    if let Some(current) = block {
+        html.push_str(current.close_tag());
+    }
+    if !pending_pages.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"page-marker\">{}</div>\n",
+            link_page_markers(&pending_pages, epub_version, &mut pages)
+        ));
+    }
+    for image in images {
+        push_figure(&mut html, image);
+    }
+    append_footnotes(
+        &mut html,
+        &footnotes,
+        &referenced,
+        epub_version,
+        &render_line,
+    );
+
+    if epub_version.is_epub3() {
+        html.push_str("</section>\n");
+    }
+    html.push_str("</body>");
+    html.push('\n');
+    html.push_str("</html>");
+    html
+}
+
+/// 取出正文中独占一行的 `[^标记]: 内容` 脚注定义，按出现顺序编号（重复标记只保留第一个）。
+fn extract_footnotes(content: &str) -> (String, Vec<(String, String)>) {
+    static DEFINITION: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^[ \t\u{3000}]*\[\^([\w-]+)\]:[ \t]*(.*)$").unwrap());
+
+    let mut footnotes: Vec<(String, String)> = Vec::new();
+    let mut body = Vec::new();
+    for line in content.lines() {
+        match DEFINITION.captures(line) {
+            Some(caps) => {
+                if !footnotes.iter().any(|(label, _)| label == &caps[1]) {
+                    footnotes.push((caps[1].to_string(), caps[2].trim().to_string()));
+                }
+            }
+            None => body.push(line),
+        }
+    }
+    if footnotes.is_empty() {
+        return (content.to_string(), footnotes);
+    }
+    (body.join("\n"), footnotes)
+}
+
+/// 注音标记 `{汉字|zhù yīn}`：基文与注音都不能含花括号或换行，基文不能含竖线。
+static RUBY: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^{}|\n]+)\|([^{}\n]+)\}").unwrap());
+
+/// 把已转义文本中的注音标记转换为 `<ruby>`，并用 `<rp>` 为不支持注音的阅读器保留括号。
+///
+/// EPUB 2 的 XHTML 1.1 注音模块要求基文写在 `<rb>` 中；EPUB 3 直接写基文。
+fn render_ruby(html: &str, epub_version: EpubVersion) -> String {
+    let (rb_open, rb_close) = if epub_version.is_epub3() {
+        ("", "")
+    } else {
+        ("<rb>", "</rb>")
+    };
+    RUBY.replace_all(html, |caps: &regex::Captures<'_>| {
+        format!(
+            "<ruby>{}{}{}<rp>(</rp><rt>{}</rt><rp>)</rp></ruby>",
+            rb_open,
+            caps[1].trim(),
+            rb_close,
+            caps[2].trim()
+        )
+    })
+    .into_owned()
+}
+
+/// 去掉行内标记，用于不支持注音与强调排版的 PDF：强调只保留文字，注音写在基文后的括号中，
+/// 页码标记直接删除。`markup` 对应 `TextStyle.inline_markup`，关闭时强调与注音标记原样保留。
+pub(crate) fn strip_inline_markup(text: &str, markup: bool) -> String {
+    let mut text = protect_escapes(text);
+    if markup {
+        let keep = |inner: &str| inner.to_string();
+        text = replace_marked(&text, &PLAIN_STRONG, is_star_neighbor, keep);
+        text = replace_marked(&text, &PLAIN_EM, is_star_neighbor, keep);
+        text = replace_marked(&text, &EMPHASIS_DOT, is_dot, keep);
+        text = RUBY
+            .replace_all(&text, |caps: &regex::Captures<'_>| {
+                format!("{}({})", caps[1].trim(), caps[2].trim())
+            })
+            .into_owned();
+    }
+    let text = PAGE_MARKER.replace_all(&text, "");
+    restore_escapes(&text)
+}
+
+/// 可用反斜杠转义的标记字符；转义后的字符在渲染期间暂存为私用区字符，不参与匹配。
+const ESCAPABLE: [char; 6] = ['\\', '*', '_', '.', '{', '|'];
+const ESCAPE_BASE: u32 = 0xE000;
+
+fn protect_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let escaped = (ch == '\\')
+            .then(|| {
+                chars
+                    .peek()
+                    .and_then(|next| ESCAPABLE.iter().position(|c| c == next))
+            })
+            .flatten();
+        match escaped {
+            Some(index) => {
+                chars.next();
+                out.push(char::from_u32(ESCAPE_BASE + index as u32).expect("private use char"));
+            }
+            None => out.push(ch),
+        }
+    }
+    out
+}
+
+fn restore_escapes(text: &str) -> String {
+    text.chars()
+        .map(|ch| {
+            (ch as u32)
+                .checked_sub(ESCAPE_BASE)
+                .and_then(|index| ESCAPABLE.get(index as usize))
+                .copied()
+                .unwrap_or(ch)
+        })
+        .collect()
+}
+
+// 纯文本的强调标记：内容不能以空白开头或结尾，避免把“* * *”分隔线当作强调；
+// 标记外侧紧贴字母数字（如 `3*4*5`）或句点（如 `...`）时也不算强调，见 `replace_marked`。
+static PLAIN_STRONG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\*\*([^*\s](?:[^*]*[^*\s])?)\*\*").unwrap());
+static PLAIN_EM: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*([^*\s](?:[^*]*[^*\s])?)\*").unwrap());
+static EMPHASIS_DOT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\.\.([^.\s](?:[^.]*[^.\s])?)\.\.").unwrap());
+
+/// 纯文本的行内强调：`**粗体**`、`*斜体*` 与 `..着重号..`。输入须已转义。
+fn render_plain_emphasis(escaped: &str) -> String {
+    let strong = replace_marked(escaped, &PLAIN_STRONG, is_star_neighbor, |inner| {
+        format!("<strong>{inner}</strong>")
+    });
+    let em = replace_marked(&strong, &PLAIN_EM, is_star_neighbor, |inner| {
+        format!("<em>{inner}</em>")
+    });
+    render_emphasis_dots(&em)
+}
+
+/// 把 `..文字..` 渲染为带着重号的 `<span>`；三个及以上连续句点不算标记。
+fn render_emphasis_dots(html: &str) -> String {
+    replace_marked(html, &EMPHASIS_DOT, is_dot, |inner| {
+        format!("<span class=\"emphasis-dot\">{inner}</span>")
+    })
+}
+
+/// 用 `wrap` 替换 `marker` 的匹配（第 1 组为标记内的文字）；
+/// 匹配外侧紧贴 `rejected` 字符时保留原文。
+fn replace_marked(
+    text: &str,
+    marker: &Regex,
+    rejected: fn(char) -> bool,
+    wrap: impl Fn(&str) -> String,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in marker.captures_iter(text) {
+        let whole = caps.get(0).expect("whole match");
+        let before = text[..whole.start()].chars().next_back();
+        let after = text[whole.end()..].chars().next();
+        if before.is_some_and(rejected) || after.is_some_and(rejected) {
+            continue;
+        }
+        out.push_str(&text[last..whole.start()]);
+        out.push_str(&wrap(&caps[1]));
+        last = whole.end();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// 星号标记外侧不能是字母数字或另一个星号。
+fn is_star_neighbor(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '*'
+}
+
+fn is_dot(ch: char) -> bool {
+    ch == '.'
+}
+
+/// 印刷版页码标记 `[page=123]`，页码可为数字、罗马数字或带连字符的编号。
+static PAGE_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[page=([\p{L}\p{N}-]+)\]").unwrap());
+
+/// 行内只有页码标记（可有多个）。
+fn is_page_marker_line(line: &str) -> bool {
+    PAGE_MARKER.is_match(line) && PAGE_MARKER.replace_all(line, "").trim().is_empty()
+}
+
+/// 把页码标记替换为分页锚点：EPUB 3 为 `epub:type="pagebreak"`，EPUB 2 只保留 `id`。
+///
+/// 锚点 id 为 `page-<页码>`，同一章内重复的页码只保留第一个。
+fn link_page_markers(html: &str, epub_version: EpubVersion, pages: &mut Vec<String>) -> String {
+    PAGE_MARKER
+        .replace_all(html, |caps: &regex::Captures<'_>| {
+            let page = &caps[1];
+            if pages.iter().any(|seen| seen == page) {
+                return String::new();
+            }
+            pages.push(page.to_string());
+            if epub_version.is_epub3() {
+                format!(
+                    "<span epub:type=\"pagebreak\" role=\"doc-pagebreak\" id=\"page-{page}\" aria-label=\"{page}\"></span>"
+                )
+            } else {
+                format!("<span id=\"page-{page}\"></span>")
+            }
+        })
+        .into_owned()
+}
+
+/// 已渲染章节中的分页锚点页码，按出现顺序排列，用于生成 page-list。
+pub(super) fn page_breaks(html: &str) -> Vec<String> {
+    static PAGE_BREAK: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"<span epub:type="pagebreak" role="doc-pagebreak" id="page-([^"]+)""#).unwrap()
+    });
+    PAGE_BREAK
+        .captures_iter(html)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+/// 把已渲染段落中的 `[^标记]` 替换为脚注引用链接；没有对应定义的标记原样保留。
+///
+/// 同一脚注被多次引用时只有第一次带 `id`，保证文档内 id 唯一。
+fn link_footnote_refs(
+    html: &str,
+    footnotes: &[(String, String)],
+    referenced: &mut [bool],
+    epub_version: EpubVersion,
+) -> String {
+    static REFERENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[\^([\w-]+)\]").unwrap());
+
+    if footnotes.is_empty() {
+        return html.to_string();
+    }
+    REFERENCE
+        .replace_all(html, |caps: &regex::Captures<'_>| {
+            let Some(index) = footnotes.iter().position(|(label, _)| label == &caps[1]) else {
+                return caps[0].to_string();
+            };
+            let number = index + 1;
+            let id = if referenced[index] {
+                String::new()
+            } else {
+                referenced[index] = true;
+                format!(" id=\"fnref-{}\"", number)
+            };
+            format!(
+                "<a class=\"noteref\"{} href=\"#fn-{}\"{}><sup>{}</sup></a>",
+                semantic_attrs(epub_version, "noteref", "doc-noteref"),
+                number,
+                id,
+                number
+            )
+        })
+        .into_owned()
+}
+
+/// 在章节末尾输出脚注。EPUB 3 使用 `<aside epub:type="footnote">`，
+/// 支持的阅读器（如 Apple Books）会以弹窗显示并在正文流中隐藏；EPUB 2 输出普通尾注列表。
+fn append_footnotes(
+    html: &mut String,
+    footnotes: &[(String, String)],
+    referenced: &[bool],
+    epub_version: EpubVersion,
+    render_line: &dyn Fn(&str) -> String,
+) {
+    if footnotes.is_empty() {
+        return;
+    }
+    if epub_version.is_epub3() {
+        html.push_str("<section class=\"footnotes\" epub:type=\"footnotes\">\n");
+    } else {
+        html.push_str("<div class=\"footnotes\">\n<hr/>\n");
+    }
+    for (index, (_, text)) in footnotes.iter().enumerate() {
+        let number = index + 1;
+        let marker = if referenced[index] {
+            format!("<a href=\"#fnref-{}\">{}</a>", number, number)
+        } else {
+            number.to_string()
+        };
+        let paragraph = format!("<p>{}. {}</p>", marker, render_line(text));
+        if epub_version.is_epub3() {
+            html.push_str(&format!(
+                "<aside class=\"footnote\" epub:type=\"footnote\" role=\"doc-footnote\" id=\"fn-{}\">{}</aside>\n",
+                number, paragraph
+            ));
+        } else {
+            html.push_str(&format!(
+                "<div class=\"footnote\" id=\"fn-{}\">{}</div>\n",
+                number, paragraph
+            ));
+        }
+    }
+    if epub_version.is_epub3() {
+        html.push_str("</section>\n");
+    } else {
+        html.push_str("</div>\n");
+    }
+}
+
+/// 命名分组提取出的编号与副标题；两者缺一或标题已被改动时为 `None`。
+fn structured_number_title(chapter: &ChapterDraft) -> Option<(String, String)> {
+    let heading = chapter.structured_heading()?;
+    Some((heading.number.clone()?, heading.title.clone()?))
+}
+
+fn append_standard_chapter_header(
+    html: &mut String,
+    title: &str,
+    structured: Option<(String, String)>,
+    language: &str,
+    style: &TextStyle,
+) {
+    // 去编号时正文只显示副标题，目录条目仍由调用方使用完整标题。
+    let template = style.heading_template.trim();
+    let (label, title, show_label) = match structured {
+        Some((_, rest)) if style.strip_heading_number => (String::new(), Some(rest), false),
+        Some((number, rest)) if !template.is_empty() => {
+            let num = HEADING_NUMBER
+                .captures(&number)
+                .and_then(|caps| caps.get(2).or(caps.get(3)))
+                .map_or(number.as_str(), |num| num.as_str());
+            let heading = fill_heading_template(template, &number, num, Some(&rest));
+            (heading, None, true)
+        }
+        Some((number, rest)) => (number, Some(rest), true),
+        None => match strip_heading_number(title, language) {
+            Some(rest) if style.strip_heading_number => (String::new(), Some(rest), false),
+            _ => {
+                let (label, title) = format_chapter_heading(title, language, template);
+                (label, title, true)
+            }
+        },
+    };
+    let header_class = merge_classes("chapter-header", &style.extra_chapter_class);
+    html.push_str(&format!("<div class=\"{}\">\n", header_class));
+    html.push_str("<div class=\"chapter-ornament\"></div>\n");
+    if let Some(title) = title {
+        if show_label {
+            html.push_str(&format!(
+                "<div class=\"chapter-label\">{}</div>\n",
+                escape_html(&label)
+            ));
+        }
+        if style.extra_title_class.trim().is_empty() {
+            html.push_str(&format!("<h2>{}</h2>\n", escape_html(&title)));
+        } else {
+            html.push_str(&format!(
+                "<h2 class=\"{}\">{}</h2>\n",
+                escape_html(style.extra_title_class.trim()),
+                escape_html(&title)
+            ));
+        }
+    } else if style.extra_title_class.trim().is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&label)));
+    } else {
+        html.push_str(&format!(
+            "<h2 class=\"{}\">{}</h2>\n",
+            escape_html(style.extra_title_class.trim()),
+            escape_html(&label)
+        ));
+    }
+    html.push_str("<div class=\"chapter-ornament\"></div>\n");
+    html.push_str("</div>\n");
+}
+
+/// 简洁版式的章节标题：不显示序号标签与装饰线，去编号设置仍然生效。
+fn append_plain_chapter_header(
+    html: &mut String,
+    title: &str,
+    structured: Option<(String, String)>,
+    language: &str,
+    style: &TextStyle,
+) {
+    let stripped = match structured {
+        Some((_, rest)) => Some(rest),
+        None => strip_heading_number(title, language),
+    };
+    let title = match stripped {
+        Some(rest) if style.strip_heading_number => rest,
+        _ => title.to_string(),
+    };
+    let header_class = merge_classes(
+        "chapter-header chapter-header-plain",
+        &style.extra_chapter_class,
+    );
+    html.push_str(&format!("<div class=\"{}\">\n", header_class));
+    if style.extra_title_class.trim().is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&title)));
+    } else {
+        html.push_str(&format!(
+            "<h2 class=\"{}\">{}</h2>\n",
+            escape_html(style.extra_title_class.trim()),
+            escape_html(&title)
+        ));
+    }
+    html.push_str("</div>\n");
+}
+
+/// 竖排时把弯引号换成直角引号：“”→「」，‘’→『』。
+fn vertical_punctuation(text: &str) -> String {
+    text.chars()
+        .map(|ch| match ch {
+            '“' => '「',
+            '”' => '」',
+            '‘' => '『',
+            '’' => '』',
+            _ => ch,
+        })
+        .collect()
+}
+
+/// 竖排时把一到两位的半角数字包进 `tcy` span 横向排列（纵中横），更长的数字保持原样。
+///
+/// 输入为已转义的 HTML 片段，标签与字符实体内部的数字不处理。
+fn upright_digits(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut digits = String::new();
+    let mut in_tag = false;
+    let mut in_entity = false;
+    let flush = |out: &mut String, digits: &mut String| {
+        if (1..=2).contains(&digits.len()) {
+            out.push_str(&format!("<span class=\"tcy\">{}</span>", digits));
+        } else {
+            out.push_str(digits);
+        }
+        digits.clear();
+    };
+    for ch in html.chars() {
+        if !in_tag && !in_entity && ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        flush(&mut out, &mut digits);
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            '&' if !in_tag => in_entity = true,
+            ';' => in_entity = false,
+            _ => {}
+        }
+        out.push(ch);
+    }
+    flush(&mut out, &mut digits);
+    out
+}
+
+/// 段落所有行都以 `>` 开头时视为 Markdown 引用块，去掉前缀并返回 `true`。
+fn strip_blockquote_markers(lines: &mut [String]) -> bool {
+    if lines.is_empty() || !lines.iter().all(|line| line.trim_start().starts_with('>')) {
+        return false;
+    }
+    for line in lines.iter_mut() {
+        let rest = line.trim_start().trim_start_matches('>');
+        *line = rest.strip_prefix(' ').unwrap_or(rest).to_string();
+    }
+    true
+}
+
+/// 渲染 Markdown 行内格式：链接、粗体与斜体。先转义再替换，避免注入原始 HTML。
+fn render_markdown_inline(line: &str) -> String {
+    static LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
+
+    // 链接地址原样保留，只对链接文字和普通文本做强调替换；
+    // 不在允许范围内的地址（如 `javascript:`）整段按普通文字输出。
+    let mut out = String::new();
+    let mut last = 0;
+    for caps in LINK.captures_iter(line) {
+        let whole = caps.get(0).expect("whole match");
+        out.push_str(&render_markdown_emphasis(&line[last..whole.start()]));
+        last = whole.end();
+        if !is_allowed_link(&caps[2]) {
+            out.push_str(&escape_html(whole.as_str()));
+            continue;
+        }
+        out.push_str(&format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(&caps[2]),
+            render_markdown_emphasis(&caps[1])
+        ));
+    }
+    out.push_str(&render_markdown_emphasis(&line[last..]));
+    out
+}
+
+/// 链接地址只允许 http、https、mailto 与书内锚点 `#`。
+fn is_allowed_link(target: &str) -> bool {
+    let lower = target.to_ascii_lowercase();
+    target.starts_with('#')
+        || ["http://", "https://", "mailto:"]
+            .iter()
+            .any(|scheme| lower.starts_with(scheme))
+}
+
+fn render_markdown_emphasis(text: &str) -> String {
+    static STAR_STRONG: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*\*([^*]+)\*\*").unwrap());
+    static UNDERSCORE_STRONG: Lazy<Regex> = Lazy::new(|| Regex::new(r"__([^_]+)__").unwrap());
+    static STAR_EM: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*([^*]+)\*").unwrap());
+    static UNDERSCORE_EM: Lazy<Regex> = Lazy::new(|| Regex::new(r"_([^_]+)_").unwrap());
+
+    let escaped = escape_html(text);
+    let strong = |inner: &str| format!("<strong>{inner}</strong>");
+    let em = |inner: &str| format!("<em>{inner}</em>");
+    // 下划线只在词边界生效，`snake_case_name` 保持原样。
+    let html = STAR_STRONG.replace_all(&escaped, |caps: &regex::Captures<'_>| strong(&caps[1]));
+    let html = replace_marked(&html, &UNDERSCORE_STRONG, is_word_char, strong);
+    let html = STAR_EM.replace_all(&html, |caps: &regex::Captures<'_>| em(&caps[1]));
+    let html = replace_marked(&html, &UNDERSCORE_EM, is_word_char, em);
+    render_emphasis_dots(&html)
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+fn merge_classes(base: &str, extra: &str) -> String {
+    if extra.trim().is_empty() {
+        return base.to_string();
+    }
+    let mut classes = vec![base.to_string()];
+    classes.extend(extra.split_whitespace().map(|s| s.to_string()));
+    classes.join(" ")
+}
+
+/// 段落级块标记：`[quote]` 引文、`[verse]` 诗行。
+///
+/// 标记写在段首，包裹到写有 `[/quote]`、`[/verse]` 的段落为止；没有结束标记时只包裹当前段。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParagraphBlock {
+    Quote,
+    Verse,
+}
+
+impl ParagraphBlock {
+    fn open_tag(self) -> &'static str {
+        match self {
+            ParagraphBlock::Quote => "<blockquote class=\"quote\">\n",
+            ParagraphBlock::Verse => "<div class=\"verse\">\n",
+        }
+    }
+
+    fn close_tag(self) -> &'static str {
+        match self {
+            ParagraphBlock::Quote => "</blockquote>\n",
+            ParagraphBlock::Verse => "</div>\n",
+        }
+    }
+
+    fn close_marker(self) -> &'static str {
+        match self {
+            ParagraphBlock::Quote => "[/quote]",
+            ParagraphBlock::Verse => "[/verse]",
+        }
+    }
+}
+
+/// 行首的 `[quote]` 或 `[verse]`，不区分大小写。
+fn block_marker(line: &str) -> Option<ParagraphBlock> {
+    let lower = line.trim_start().to_ascii_lowercase();
+    if lower.starts_with("[quote]") {
+        Some(ParagraphBlock::Quote)
+    } else if lower.starts_with("[verse]") {
+        Some(ParagraphBlock::Verse)
+    } else {
+        None
+    }
+}
+
+/// 取出段首的块标记，标记独占一行时删去该行。
+fn extract_block_marker(lines: &mut Vec<String>) -> Option<ParagraphBlock> {
+    let first = lines.first()?.trim_start();
+    let block = block_marker(first)?;
+    let rest = first["[quote]".len()..].trim_start().to_string();
+    if rest.is_empty() {
+        lines.remove(0);
+    } else {
+        lines[0] = rest;
+    }
+    Some(block)
+}
+
+fn ends_with_close_marker(line: &str, block: ParagraphBlock) -> bool {
+    line.trim_end()
+        .to_ascii_lowercase()
+        .ends_with(block.close_marker())
+}
+
+fn block_closes(paragraphs: &[Vec<String>], block: ParagraphBlock) -> bool {
+    paragraphs.iter().any(|paragraph| {
+        paragraph
+            .last()
+            .is_some_and(|line| ends_with_close_marker(line, block))
+    })
+}
+
+/// 去掉段落中的块标记，供不区分引文与诗行的纯文本输出使用。
+pub(crate) fn strip_block_markers(lines: &mut Vec<String>) {
+    extract_block_marker(lines);
+    for block in [ParagraphBlock::Quote, ParagraphBlock::Verse] {
+        strip_block_close(lines, block);
+    }
+}
+
+/// 去掉段末的结束标记，返回块是否在此段结束。
+fn strip_block_close(lines: &mut Vec<String>, block: ParagraphBlock) -> bool {
+    let Some(last) = lines.last_mut() else {
+        return false;
+    };
+    if !ends_with_close_marker(last, block) {
+        return false;
+    }
+    let trimmed = last.trim_end();
+    let rest = trimmed[..trimmed.len() - block.close_marker().len()].trim_end();
+    if rest.is_empty() {
+        lines.pop();
+    } else {
+        *last = rest.to_string();
+    }
+    true
+}
+
+fn extract_marker_class(lines: &mut Vec<String>) -> Option<String> {
+    if lines.is_empty() {
+        return None;
+    }
+    let first_line = lines[0].clone();
+    let first = first_line.trim_start();
+    let lower = first.to_ascii_lowercase();
+    if !lower.starts_with("[class=") {
+        return None;
+    }
+    let end = first.find(']')?;
+    let class_value = first[7..end].trim();
+    if class_value.is_empty() {
+        return None;
+    }
+    let rest = first[end + 1..].trim_start();
+    if rest.is_empty() {
+        lines.remove(0);
+    } else {
+        lines[0] = rest.to_string();
+    }
+    Some(class_value.to_string())
+}
+
+/// 渲染导航文档中的 page-list `<nav>`：`(文件, 页码)` 按阅读顺序排列。
+pub(super) fn render_page_list(pages: &[(String, String)]) -> String {
+    let mut html = String::from("  <nav epub:type=\"page-list\" id=\"page-list\" hidden=\"\">\n");
+    html.push_str("    <ol>\n");
+    for (file, page) in pages {
+        html.push_str(&format!(
+            "      <li><a href=\"{}#page-{page}\">{page}</a></li>\n",
+            escape_html(file)
+        ));
+    }
+    html.push_str("    </ol>\n  </nav>\n");
+    html
+}
+
+/// 封面在 landmarks 中的标题。
+pub(super) fn cover_title(language: &str) -> &'static str {
+    let lang = language.trim().to_ascii_lowercase();
+    if lang.is_empty() || lang.starts_with("zh") {
+        "封面"
+    } else {
+        "Cover"
+    }
+}
+
+/// 正文起点在 landmarks 中的标题。
+pub(super) fn body_start_title(language: &str) -> &'static str {
+    let lang = language.trim().to_ascii_lowercase();
+    if lang.is_empty() || lang.starts_with("zh") {
+        "正文"
+    } else {
+        "Start"
+    }
+}
+
+/// EPUB 3 landmarks 中的一项。
+pub(super) struct Landmark {
+    /// `epub:type`，如 `cover`、`toc`、`bodymatter`。
+    pub epub_type: &'static str,
+    /// 相对 `OEBPS` 的文件，可带锚点。
+    pub href: String,
+    pub title: String,
+}
+
+/// 渲染导航文档中的 landmarks `<nav>`，阅读器据此提供“转到封面 / 目录 / 开始阅读”。
+///
+/// 列表仅供阅读器使用，以 `hidden` 隐藏，避免出现在内嵌目录页中。
+pub(super) fn render_landmarks(landmarks: &[Landmark]) -> String {
+    let mut html = String::from("<nav epub:type=\"landmarks\" id=\"landmarks\" hidden=\"\">\n");
+    html.push_str("    <ol>\n");
+    for landmark in landmarks {
+        html.push_str(&format!(
+            "      <li><a epub:type=\"{}\" href=\"{}\">{}</a></li>\n",
+            landmark.epub_type,
+            escape_html(&landmark.href),
+            escape_html(&landmark.title)
+        ));
+    }
+    html.push_str("    </ol>\n  </nav>");
+    html
+}
+
+pub(super) fn gallery_title(language: &str) -> &'static str {
+    let lang = language.trim().to_ascii_lowercase();
+    if lang.is_empty() || lang.starts_with("zh") {
+        "插图"
+    } else {
+        "Illustrations"
+    }
+}
+
+pub(super) fn render_gallery(
+    images: &[ImageAsset],
+    language: &str,
+    title: &str,
+    epub_version: EpubVersion,
+) -> String {
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    html.push_str("</head>");
+    html.push('\n');
+    html.push_str(&format!(
+        "<body{}>",
+        epub_type_attr(epub_version, "backmatter")
+    ));
+    html.push('\n');
+    html.push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
+
+    for image in images {
+        push_figure(&mut html, image);
+    }
+
+    html.push_str("</body>");
+    html.push('\n');
+    html.push_str("</html>");
+    html
+}
+
+/// 插图与说明文字，插图页和插入正文的插图共用。
+fn push_figure(html: &mut String, image: &ImageAsset) {
+    let caption = image
+        .caption
+        .as_ref()
+        .map(|text| escape_html(text))
+        .unwrap_or_default();
+    html.push_str("<figure>\n");
+    html.push_str(&format!(
+        "<img src=\"images/{}\" alt=\"{}\"/>\n",
+        escape_html(&image.name),
+        caption
+    ));
+    if !caption.is_empty() {
+        html.push_str(&format!("<figcaption>{}</figcaption>\n", caption));
+    }
+    html.push_str("</figure>\n");
+}
+
+/// 渲染卷首页，用作嵌套目录中卷条目的落点。
+pub(super) fn render_volume_page(title: &str, language: &str, epub_version: EpubVersion) -> String {
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    html.push_str("</head>\n");
+    html.push_str(&format!(
+        "<body class=\"volume-page\"{}>\n",
+        epub_type_attr(epub_version, "part")
+    ));
+    html.push_str(&format!(
+        "<h1 class=\"volume-title\">{}</h1>\n",
+        escape_html(title.trim())
+    ));
+    html.push_str("</body>\n</html>");
+    html
+}
+
+/// 内嵌目录页中的一项。
+pub(super) struct TocPageEntry {
+    /// 相对 `OEBPS` 的文件。
+    pub href: String,
+    /// 章节编号（如“第1章”），无法识别时为空。
+    pub number: Option<String>,
+    /// 编号之后的标题；没有编号时为完整标题。
+    pub title: String,
+    /// 1 为顶层，2 为卷下的章节。
+    pub level: i32,
+}
+
+impl TocPageEntry {
+    /// 前置页、卷首页等没有编号的条目。
+    pub(super) fn page(href: &str, title: &str, level: i32) -> Self {
+        Self {
+            href: href.to_string(),
+            number: None,
+            title: title.trim().to_string(),
+            level,
+        }
+    }
 
-    html.push_str("</body>");
-    html.push('\n');
-    html.push_str("</html>");
-    html
+    /// 章节条目：拆出标题开头的编号，供目录页按需显示。
+    pub(super) fn chapter(href: &str, chapter: &ChapterDraft, level: i32, language: &str) -> Self {
+        let full = chapter.title.trim();
+        let (number, title) =
+            match structured_number_title(chapter).or_else(|| split_chinese_chapter_title(full)) {
+                Some((number, rest)) if !rest.trim().is_empty() => {
+                    (Some(number.trim().to_string()), rest.trim().to_string())
+                }
+                _ => match strip_heading_number(full, language) {
+                    Some(rest) if full.ends_with(rest.as_str()) => {
+                        let number = full[..full.len() - rest.len()]
+                            .trim_end_matches([':', '：', '-', '—', ' ', '.'])
+                            .to_string();
+                        (Some(number).filter(|number| !number.is_empty()), rest)
+                    }
+                    _ => (None, full.to_string()),
+                },
+            };
+        Self {
+            href: href.to_string(),
+            number,
+            title,
+            level,
+        }
+    }
+
+    fn label(&self, show_number: bool) -> String {
+        let title = format!(
+            "<span class=\"toc-text\">{}</span>",
+            escape_html(&self.title)
+        );
+        match &self.number {
+            Some(number) if show_number => format!(
+                "<span class=\"toc-number\">{}</span>{}",
+                escape_html(number),
+                title
+            ),
+            _ => title,
+        }
+    }
 }
 
-fn append_standard_chapter_header(
-    html: &mut String,
-    title: &str,
+/// 渲染内嵌目录页，替换 epub-builder 生成的 `toc.xhtml`。
+///
+/// 卷下的章节嵌套在卷条目中；开启卷名分组标题时，卷名以 `<h2>` 显示。
+pub(super) fn render_toc_page(
+    entries: &[TocPageEntry],
+    toc_name: &str,
+    options: &TocOptions,
     language: &str,
-    style: &TextStyle,
-) {
-    let (label, title) = format_chapter_heading(title, language);
-    let header_class = merge_classes("chapter-header", &style.extra_chapter_class);
-    html.push_str(&format!("<div class=\"{}\">\n", header_class));
-    html.push_str("<div class=\"chapter-ornament\"></div>\n");
-    if let Some(title) = title {
-        html.push_str(&format!(
-            "<div class=\"chapter-label\">{}</div>\n",
-            escape_html(&label)
-        ));
-        if style.extra_title_class.trim().is_empty() {
-            html.push_str(&format!("<h2>{}</h2>\n", escape_html(&title)));
+    epub_version: EpubVersion,
+) -> String {
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    html.push_str(&format!("<title>{}</title>\n", escape_html(toc_name)));
+    html.push_str("</head>\n");
+    html.push_str("<body class=\"toc-page\">\n");
+    if epub_version.is_epub3() {
+        html.push_str("<nav epub:type=\"toc\" role=\"doc-toc\" id=\"toc\">\n");
+    } else {
+        html.push_str("<div class=\"toc\" id=\"toc\">\n");
+    }
+    html.push_str(&format!(
+        "<h1 class=\"toc-title\" id=\"toc-title\">{}</h1>\n",
+        escape_html(toc_name)
+    ));
+    html.push_str("<ol class=\"toc-list\">\n");
+    let link = |entry: &TocPageEntry| {
+        format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(&entry.href),
+            entry.label(options.show_chapter_numbers)
+        )
+    };
+    let mut index = 0;
+    while index < entries.len() {
+        let entry = &entries[index];
+        let children = entries[index + 1..]
+            .iter()
+            .take_while(|child| child.level > entry.level)
+            .count();
+        if children == 0 {
+            html.push_str(&format!("<li class=\"toc-entry\">{}</li>\n", link(entry)));
         } else {
-            html.push_str(&format!(
-                "<h2 class=\"{}\">{}</h2>\n",
-                escape_html(style.extra_title_class.trim()),
-                escape_html(&title)
-            ));
+            if options.volume_headings {
+                html.push_str(&format!(
+                    "<li class=\"toc-volume\"><h2 class=\"toc-volume-heading\">{}</h2>\n",
+                    link(entry)
+                ));
+            } else {
+                html.push_str(&format!("<li class=\"toc-entry\">{}\n", link(entry)));
+            }
+            html.push_str("<ol class=\"toc-list\">\n");
+            for child in &entries[index + 1..=index + children] {
+                html.push_str(&format!("<li class=\"toc-entry\">{}</li>\n", link(child)));
+            }
+            html.push_str("</ol>\n</li>\n");
         }
-    } else if style.extra_title_class.trim().is_empty() {
-        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&label)));
-    } else {
-        html.push_str(&format!(
-            "<h2 class=\"{}\">{}</h2>\n",
-            escape_html(style.extra_title_class.trim()),
-            escape_html(&label)
-        ));
+        index += children + 1;
     }
-    html.push_str("<div class=\"chapter-ornament\"></div>\n");
-    html.push_str("</div>\n");
+    html.push_str("</ol>\n");
+    html.push_str(if epub_version.is_epub3() {
+        "</nav>\n"
+    } else {
+        "</div>\n"
+    });
+    html.push_str("</body>\n</html>");
+    html
 }
 
-fn merge_classes(base: &str, extra: &str) -> String {
-    if extra.trim().is_empty() {
-        return base.to_string();
+/// 前置页在目录中的标题。
+pub(super) fn front_matter_title(page: FrontMatterPage, language: &str) -> &'static str {
+    let lang = language.trim().to_ascii_lowercase();
+    let chinese = lang.is_empty() || lang.starts_with("zh");
+    match (page, chinese) {
+        (FrontMatterPage::TitlePage, true) => "书名页",
+        (FrontMatterPage::TitlePage, false) => "Title Page",
+        (FrontMatterPage::Copyright, true) => "版权信息",
+        (FrontMatterPage::Copyright, false) => "Copyright",
+        (FrontMatterPage::Dedication, true) => "献词",
+        (FrontMatterPage::Dedication, false) => "Dedication",
+        (FrontMatterPage::Preface, true) => "前言",
+        (FrontMatterPage::Preface, false) => "Preface",
     }
-    let mut classes = vec![base.to_string()];
-    classes.extend(extra.split_whitespace().map(|s| s.to_string()));
-    classes.join(" ")
 }
 
-fn extract_marker_class(lines: &mut Vec<String>) -> Option<String> {
-    if lines.is_empty() {
-        return None;
+/// 渲染书名页：书名、作者与出版社。
+pub(super) fn render_title_page(
+    book_info: &BookInfo,
+    language: &str,
+    epub_version: EpubVersion,
+) -> String {
+    let title = if book_info.title.trim().is_empty() {
+        "Untitled"
+    } else {
+        book_info.title.trim()
+    };
+    let author = book_info.author();
+    let publisher = book_info.publisher.trim();
+
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    html.push_str("</head>\n");
+    html.push_str(&format!(
+        "<body class=\"title-page\"{}>\n",
+        epub_type_attr(epub_version, "titlepage")
+    ));
+    html.push_str(&format!(
+        "<h1 class=\"title-page-title\">{}</h1>\n",
+        escape_html(title)
+    ));
+    if !author.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"title-page-author\">{}</div>\n",
+            escape_html(&author)
+        ));
     }
-    let first_line = lines[0].clone();
-    let first = first_line.trim_start();
-    let lower = first.to_ascii_lowercase();
-    if !lower.starts_with("[class=") {
-        return None;
+    if !publisher.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"title-page-publisher\">{}</div>\n",
+            escape_html(publisher)
+        ));
     }
-    let end = first.find(']')?;
-    let class_value = first[7..end].trim();
-    if class_value.is_empty() {
-        return None;
+    html.push_str("</body>\n</html>");
+    html
+}
+
+/// 渲染版权页、献词或前言，每行一段；只有前言显示标题。
+pub(super) fn render_front_matter_page(
+    page: FrontMatterPage,
+    lines: &[String],
+    language: &str,
+    epub_version: EpubVersion,
+) -> String {
+    let (class, epub_type) = match page {
+        FrontMatterPage::TitlePage => ("title-page", "titlepage"),
+        FrontMatterPage::Copyright => ("copyright-page", "copyright-page"),
+        FrontMatterPage::Dedication => ("dedication-page", "dedication"),
+        FrontMatterPage::Preface => ("preface-page", "preface"),
+    };
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    html.push_str("</head>\n");
+    html.push_str(&format!(
+        "<body class=\"{}\"{}>\n",
+        class,
+        epub_type_attr(epub_version, epub_type)
+    ));
+    if page == FrontMatterPage::Preface {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n",
+            escape_html(front_matter_title(page, language))
+        ));
     }
-    let rest = first[end + 1..].trim_start();
-    if rest.is_empty() {
-        lines.remove(0);
-    } else {
-        lines[0] = rest.to_string();
+    html.push_str("<div class=\"front-matter-text\">\n");
+    for line in lines {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(line)));
     }
-    Some(class_value.to_string())
+    html.push_str("</div>\n");
+    html.push_str("</body>\n</html>");
+    html
 }
 
-pub(super) fn gallery_title(language: &str) -> &'static str {
+/// 书末页面未填写标题时使用的默认标题。
+pub(super) fn back_matter_title(kind: BackMatterKind, language: &str) -> &'static str {
     let lang = language.trim().to_ascii_lowercase();
-    if lang.is_empty() || lang.starts_with("zh") {
-        "插图"
-    } else {
-        "Illustrations"
+    let chinese = lang.is_empty() || lang.starts_with("zh");
+    match (kind, chinese) {
+        (BackMatterKind::Afterword, true) => "后记",
+        (BackMatterKind::Afterword, false) => "Afterword",
+        (BackMatterKind::Colophon, true) => "版权页",
+        (BackMatterKind::Colophon, false) => "Colophon",
     }
 }
 
-pub(super) fn render_gallery(images: &[ImageAsset], language: &str, title: &str) -> String {
+/// 渲染书末的后记或版权页，每行一段。
+pub(super) fn render_back_matter_page(
+    kind: BackMatterKind,
+    title: &str,
+    lines: &[String],
+    language: &str,
+    epub_version: EpubVersion,
+) -> String {
+    let epub_type = match kind {
+        BackMatterKind::Afterword => "afterword",
+        BackMatterKind::Colophon => "colophon",
+    };
     let mut html = String::new();
-    html.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
-    html.push('\n');
-    html.push_str(
-        r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">"#,
-    );
-    html.push('\n');
+    push_document_head(&mut html, language, epub_version);
+    html.push_str("</head>\n");
     html.push_str(&format!(
-        r#"<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="{}">"#,
-        language
+        "<body class=\"{}-page\"{}>\n",
+        epub_type,
+        epub_type_attr(epub_version, &format!("backmatter {epub_type}"))
     ));
-    html.push('\n');
-    html.push_str("<head>");
-    html.push('\n');
-    html.push_str(
-        r#"<meta http-equiv="Content-Type" content="application/xhtml+xml; charset=utf-8"/>"#,
-    );
-    html.push('\n');
-    html.push_str(r#"<link rel="stylesheet" type="text/css" href="stylesheet.css"/>"#);
-    html.push('\n');
-    html.push_str("</head>");
-    html.push('\n');
-    html.push_str("<body>");
-    html.push('\n');
     html.push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
-
-    for image in images {
-        let caption = image
-            .caption
-            .as_ref()
-            .map(|text| escape_html(text))
-            .unwrap_or_default();
-        html.push_str("<figure>\n");
-        html.push_str(&format!(
-            "<img src=\"images/{}\" alt=\"{}\"/>\n",
-            escape_html(&image.name),
-            caption
-        ));
-        if !caption.is_empty() {
-            html.push_str(&format!("<figcaption>{}</figcaption>\n", caption));
-        }
-        html.push_str("</figure>\n");
+    html.push_str("<div class=\"back-matter-text\">\n");
+    for line in lines {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(line)));
     }
-
-    html.push_str("</body>");
-    html.push('\n');
-    html.push_str("</html>");
+    html.push_str("</div>\n");
+    html.push_str("</body>\n</html>");
     html
 }
 
@@ -261,16 +1421,18 @@ pub(super) fn render_text_cover(
     book_info: &BookInfo,
     language: &str,
     template: CssTemplate,
+    epub_version: EpubVersion,
 ) -> String {
     let title = if book_info.title.trim().is_empty() {
         "Untitled"
     } else {
         book_info.title.trim()
     };
-    let author = if book_info.author.trim().is_empty() {
+    let author = book_info.author();
+    let author = if author.is_empty() {
         "Unknown"
     } else {
-        book_info.author.trim()
+        author.as_str()
     };
     let subtitle = book_info.category.trim();
     let publisher = book_info.publisher.trim();
@@ -286,24 +1448,7 @@ pub(super) fn render_text_cover(
     let meta = meta_parts.join(" · ");
 
     let mut html = String::new();
-    html.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
-    html.push('\n');
-    html.push_str(
-        r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">"#,
-    );
-    html.push('\n');
-    html.push_str(&format!(
-        r#"<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="{}">"#,
-        language
-    ));
-    html.push('\n');
-    html.push_str("<head>\n");
-    html.push_str(
-        r#"<meta http-equiv="Content-Type" content="application/xhtml+xml; charset=utf-8"/>"#,
-    );
-    html.push('\n');
-    html.push_str(r#"<link rel="stylesheet" type="text/css" href="stylesheet.css"/>"#);
-    html.push('\n');
+    push_document_head(&mut html, language, epub_version);
     html.push_str("</head>\n");
     let body_class = if matches!(template, CssTemplate::Folio) {
         "cover-page cover-folio"
@@ -312,7 +1457,11 @@ pub(super) fn render_text_cover(
     } else {
         "cover-page"
     };
-    html.push_str(&format!("<body class=\"{}\">\n", body_class));
+    html.push_str(&format!(
+        "<body class=\"{}\"{}>\n",
+        body_class,
+        epub_type_attr(epub_version, "cover")
+    ));
     html.push_str("<div class=\"cover-frame\">\n");
     html.push_str("<div class=\"cover-ornament\"></div>\n");
     html.push_str(&format!(
@@ -341,6 +1490,39 @@ pub(super) fn render_text_cover(
     html
 }
 
+/// 矢量封面页：以内联 `<svg>` 按原始比例显示 SVG 封面图。
+///
+/// EPUB 3 中该页面需在清单中声明 `properties="svg"`，`<svg>` 以 `doc-cover` 角色和书名标注。
+pub(super) fn render_svg_cover(
+    image: &str,
+    title: &str,
+    (width, height): (u32, u32),
+    language: &str,
+    epub_version: EpubVersion,
+) -> String {
+    let mut html = String::new();
+    push_document_head(&mut html, language, epub_version);
+    html.push_str("</head>\n");
+    html.push_str(&format!(
+        "<body class=\"cover-page cover-image\"{}>\n",
+        epub_type_attr(epub_version, "cover")
+    ));
+    let role = if epub_version.is_epub3() {
+        format!(" role=\"doc-cover\" aria-label=\"{}\"", escape_html(title))
+    } else {
+        String::new()
+    };
+    html.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"1.1\" width=\"100%\" height=\"100%\" viewBox=\"0 0 {width} {height}\" preserveAspectRatio=\"xMidYMid meet\"{role}>\n"
+    ));
+    html.push_str(&format!(
+        "<image width=\"{width}\" height=\"{height}\" xlink:href=\"{}\"/>\n",
+        escape_html(image)
+    ));
+    html.push_str("</svg>\n</body>\n</html>");
+    html
+}
+
 pub(super) fn escape_html(input: &str) -> String {
     let mut out = String::with_capacity(input.len());
     for ch in input.chars() {
@@ -394,8 +1576,53 @@ fn split_chinese_chapter_title(line: &str) -> Option<(String, String)> {
     None
 }
 
-fn format_chapter_heading(line: &str, language: &str) -> (String, Option<String>) {
+/// 去掉标题开头的“第N章”/“Chapter N”编号，返回剩余的副标题。
+///
+/// 没有可识别编号或编号后没有副标题时返回 `None`，此时保持原标题。
+fn strip_heading_number(line: &str, language: &str) -> Option<String> {
+    if let Some((_, rest)) = split_chinese_chapter_title(line) {
+        return Some(rest);
+    }
+    let trimmed = line.trim();
+    let mut parts = trimmed.split_whitespace();
+    let first = parts.next()?;
+    let number = parts.next()?;
+    if !first.eq_ignore_ascii_case("chapter") || !number.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    format_chapter_heading(trimmed, language, "").1
+}
+
+/// 标题开头的编号：完整编号、中文或阿拉伯数字、英文数字（含罗马数字）与其后的副标题。
+static HEADING_NUMBER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^(第\s*([0-9０-９零〇○一二两兩三四五六七八九十百千万萬]+)\s*[章回节節卷集部篇话話]|chapter\s+([0-9]+|[ivxlcdm]+)\b)[\s:：\-—–―·・.]*(.*)$",
+    )
+    .unwrap()
+});
+
+/// 把标题拆成序号标签与副标题。
+///
+/// `template` 非空时按模板合成一行标题（见 [`TextStyle::heading_template`]），不再拆分；
+/// 否则英文“Chapter N”的序号显示为罗马数字。
+fn format_chapter_heading(line: &str, language: &str, template: &str) -> (String, Option<String>) {
     let trimmed = line.trim();
+    if !template.is_empty() {
+        let heading = match HEADING_NUMBER.captures(trimmed) {
+            Some(caps) => {
+                let num = caps.get(2).or(caps.get(3)).map_or("", |num| num.as_str());
+                let rest = caps.get(4).map(|rest| rest.as_str().trim());
+                fill_heading_template(
+                    template,
+                    &caps[1],
+                    num,
+                    rest.filter(|rest| !rest.is_empty()),
+                )
+            }
+            None => trimmed.to_string(),
+        };
+        return (heading, None);
+    }
     let lower = trimmed.to_ascii_lowercase();
     let is_english = language.trim().to_ascii_lowercase().starts_with("en");
 
@@ -430,6 +1657,24 @@ fn format_chapter_heading(line: &str, language: &str) -> (String, Option<String>
     split_title_line(trimmed)
 }
 
+/// 按模板合成标题：`{label}` 为完整编号（`第12章`），`{num}` 为其中的数字，`{roman}` 为
+/// 阿拉伯数字的罗马数字写法，`{title}` 为副标题；没有副标题时只显示完整编号。
+fn fill_heading_template(template: &str, label: &str, num: &str, title: Option<&str>) -> String {
+    let Some(title) = title else {
+        return label.to_string();
+    };
+    let roman = num
+        .parse::<u32>()
+        .ok()
+        .filter(|num| *num > 0)
+        .map_or_else(|| num.to_string(), to_roman);
+    template
+        .replace("{label}", label)
+        .replace("{num}", num)
+        .replace("{roman}", &roman)
+        .replace("{title}", title)
+}
+
 fn to_roman(mut num: u32) -> String {
     let mut out = String::new();
     let numerals = [
@@ -456,7 +1701,115 @@ fn to_roman(mut num: u32) -> String {
     out
 }
 
-pub(super) fn split_paragraphs(content: &str) -> Vec<Vec<String>> {
+/// 把正文切分为段落，每段是需要以换行分隔的若干行。
+///
+/// `reflow` 为重排灵敏度时，按 [`reflow_paragraphs`] 把硬换行的文本重新合并为段落。
+/// 场景分隔行（见 [`is_scene_break`]）与连续三个以上的空行各自成为单独一段 `[scene]`。
+pub(crate) fn split_paragraphs(content: &str, reflow: Option<f32>) -> Vec<Vec<String>> {
+    let content = mark_blank_scene_breaks(content);
+    let paragraphs = match reflow {
+        Some(sensitivity) => reflow_paragraphs(&content, sensitivity),
+        None => split_plain_paragraphs(&content),
+    };
+    isolate_scene_breaks(paragraphs)
+}
+
+/// 场景分隔行：`[scene]` 标记，或由同一个分隔符号重复三次以上组成的行，
+/// 如 `***`、`* * *`、`———`、`---`、`＊＊＊`。
+pub(crate) fn is_scene_break(line: &str) -> bool {
+    let text = line.trim();
+    if text.eq_ignore_ascii_case("[scene]") {
+        return true;
+    }
+    let mut symbols = text.chars().filter(|ch| !ch.is_whitespace());
+    let Some(first) = symbols.next() else {
+        return false;
+    };
+    matches!(
+        first,
+        '*' | '＊'
+            | '—'
+            | '-'
+            | '─'
+            | '~'
+            | '～'
+            | '='
+            | '#'
+            | '·'
+            | '•'
+            | '☆'
+            | '★'
+            | '◇'
+            | '◆'
+    ) && symbols.clone().count() >= 2
+        && symbols.all(|ch| ch == first)
+}
+
+/// 连续三个以上的空行视为场景分隔，替换为 `[scene]` 标记；开头与结尾的空行不计。
+fn mark_blank_scene_breaks(content: &str) -> Cow<'_, str> {
+    let mut blank = 0usize;
+    let mut seen_text = false;
+    let mut found = false;
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            blank += 1;
+            continue;
+        }
+        found |= seen_text && blank >= 3;
+        seen_text = true;
+        blank = 0;
+    }
+    if !found {
+        return Cow::Borrowed(content);
+    }
+    let mut output = String::with_capacity(content.len() + 16);
+    let mut blank = 0usize;
+    let mut seen_text = false;
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            blank += 1;
+            continue;
+        }
+        if seen_text && blank >= 3 {
+            output.push_str("\n[scene]\n\n");
+        } else {
+            output.push_str(&"\n".repeat(blank));
+        }
+        seen_text = true;
+        blank = 0;
+        output.push_str(line);
+        output.push('\n');
+    }
+    Cow::Owned(output)
+}
+
+/// 把混在段落中的分隔行拆出来，单独成段。
+fn isolate_scene_breaks(paragraphs: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    let mut output = Vec::with_capacity(paragraphs.len());
+    for paragraph in paragraphs {
+        if paragraph.len() == 1 || !paragraph.iter().any(|line| is_scene_break(line)) {
+            output.push(paragraph);
+            continue;
+        }
+        let mut current = Vec::new();
+        for line in paragraph {
+            if is_scene_break(&line) {
+                if !current.is_empty() {
+                    output.push(std::mem::take(&mut current));
+                }
+                output.push(vec![line]);
+            } else {
+                current.push(line);
+            }
+        }
+        if !current.is_empty() {
+            output.push(current);
+        }
+    }
+    output
+}
+
+fn split_plain_paragraphs(content: &str) -> Vec<Vec<String>> {
     let lines: Vec<&str> = content.lines().collect();
     let has_blank = lines.iter().any(|line| line.trim().is_empty());
 
@@ -511,6 +1864,111 @@ pub(super) fn split_paragraphs(content: &str) -> Vec<Vec<String>> {
     paragraphs
 }
 
+/// 硬换行文本的段落重排。
+///
+/// 空行与（全文存在缩进时）缩进开头的行总是另起一段；其余行若不以句末标点结尾，
+/// 且长度达到常见行长的 `1 - sensitivity` 倍，就与下一行合并。灵敏度越高，合并越积极。
+/// `[verse]` 块内的诗行保持原样分行，以空行分节。
+fn reflow_paragraphs(content: &str, sensitivity: f32) -> Vec<Vec<String>> {
+    let lines: Vec<&str> = content.lines().map(str::trim_end).collect();
+    let min_len =
+        (typical_line_width(&lines) as f32 * (1.0 - sensitivity.clamp(0.0, 1.0))).ceil() as usize;
+    let indented = lines.iter().any(|line| starts_indented(line));
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut joinable = false;
+    let mut stanza: Vec<String> = Vec::new();
+    // `Some(true)` 表示诗行块有结束标记，`Some(false)` 表示只到本节为止。
+    let mut verse: Option<bool> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let text = line.trim();
+        if verse.is_none() && block_marker(text) == Some(ParagraphBlock::Verse) {
+            if !current.is_empty() {
+                paragraphs.push(vec![std::mem::take(&mut current)]);
+            }
+            joinable = false;
+            verse = Some(
+                lines[index..]
+                    .iter()
+                    .any(|line| ends_with_close_marker(line, ParagraphBlock::Verse)),
+            );
+        }
+        if let Some(closed) = verse {
+            if text.is_empty() {
+                if !stanza.is_empty() {
+                    paragraphs.push(std::mem::take(&mut stanza));
+                    if !closed {
+                        verse = None;
+                    }
+                }
+                continue;
+            }
+            stanza.push(text.to_string());
+            if ends_with_close_marker(text, ParagraphBlock::Verse) {
+                paragraphs.push(std::mem::take(&mut stanza));
+                verse = None;
+            }
+            continue;
+        }
+        if text.is_empty()
+            || !joinable
+            || is_scene_break(text)
+            || (indented && starts_indented(line))
+        {
+            if !current.is_empty() {
+                paragraphs.push(vec![std::mem::take(&mut current)]);
+            }
+            joinable = false;
+            if text.is_empty() {
+                continue;
+            }
+        }
+        // 中文行之间直接相连，西文单词之间补一个空格。
+        if current
+            .chars()
+            .last()
+            .is_some_and(|last| !is_cjk(last) && !text.starts_with(is_cjk))
+        {
+            current.push(' ');
+        }
+        current.push_str(text);
+        joinable = !ends_with_sentence_punct(text)
+            && !is_scene_break(text)
+            && text.chars().count() >= min_len;
+    }
+    if !current.is_empty() {
+        paragraphs.push(vec![current]);
+    }
+    if !stanza.is_empty() {
+        paragraphs.push(stanza);
+    }
+    paragraphs
+}
+
+/// 硬换行的行宽：非空行字数的第 90 百分位，忽略少数超长行。
+fn typical_line_width(lines: &[&str]) -> usize {
+    let mut lengths: Vec<usize> = lines
+        .iter()
+        .map(|line| line.trim().chars().count())
+        .filter(|&len| len > 0)
+        .collect();
+    if lengths.is_empty() {
+        return 0;
+    }
+    lengths.sort_unstable();
+    lengths[(lengths.len() * 9 / 10).min(lengths.len() - 1)]
+}
+
+fn starts_indented(line: &str) -> bool {
+    line.starts_with([' ', '\t', '\u{3000}'])
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{2E80}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}' | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}' | '\u{20000}'..='\u{2FA1F}')
+}
+
 fn ends_with_sentence_punct(text: &str) -> bool {
     let chars = text.chars().rev();
     for ch in chars {
@@ -0,0 +1,104 @@
+//! 拆分过长的章节：部分阅读器（如 Apple Books）打开超过约 300 KB 的 XHTML 时明显变慢。
+//!
+//! 只在正文顶层的块之间切开，每份沿用原章节的 `<head>`、`<body>` 与 EPUB 3 的
+//! `<section>` 开头；指向其他分页中锚点的链接（如脚注）改写为带文件名的链接。
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// 默认的拆分阈值（字节）。
+pub const DEFAULT_CHAPTER_SPLIT_BYTES: usize = 300 * 1024;
+
+const BLOCK_OPEN: [&str; 4] = ["<div", "<blockquote", "<section", "<aside"];
+const BLOCK_CLOSE: [&str; 4] = ["</div>", "</blockquote>", "</section>", "</aside>"];
+
+static ID: Lazy<Regex> = Lazy::new(|| Regex::new(r#" id="([^"]+)""#).unwrap());
+static FRAGMENT_HREF: Lazy<Regex> = Lazy::new(|| Regex::new(r##" href="#([^"]+)""##).unwrap());
+
+/// 把超过 `max_bytes` 的章节拆成 `chapter_0005_a.xhtml`、`chapter_0005_b.xhtml`……
+///
+/// 返回 `(文件名, 内容)`；不需要拆分或无法拆分时只有原文件一项。
+pub(super) fn split_chapter(html: &str, filename: &str, max_bytes: usize) -> Vec<(String, String)> {
+    let single = || vec![(filename.to_string(), html.to_string())];
+    if html.len() <= max_bytes {
+        return single();
+    }
+    let Some((start, end)) = content_range(html) else {
+        return single();
+    };
+    let (head, content, tail) = (&html[..start], &html[start..end], &html[end..]);
+    let budget = max_bytes.saturating_sub(head.len() + tail.len()).max(1);
+    let mut chunks = vec![String::new()];
+    let mut depth = 0usize;
+    for line in content.split_inclusive('\n') {
+        let current = chunks.last_mut().expect("chunk");
+        if depth == 0 && !current.is_empty() && current.len() + line.len() > budget {
+            chunks.push(String::new());
+        }
+        chunks.last_mut().expect("chunk").push_str(line);
+        let opens: usize = BLOCK_OPEN.iter().map(|tag| line.matches(tag).count()).sum();
+        let closes: usize = BLOCK_CLOSE
+            .iter()
+            .map(|tag| line.matches(tag).count())
+            .sum();
+        depth = (depth + opens).saturating_sub(closes);
+    }
+    if chunks.len() < 2 {
+        return single();
+    }
+
+    let stem = filename.strip_suffix(".xhtml").unwrap_or(filename);
+    let names: Vec<String> = (0..chunks.len())
+        .map(|index| format!("{stem}_{}.xhtml", part_suffix(index)))
+        .collect();
+    let mut anchors = HashMap::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        for caps in ID.captures_iter(chunk) {
+            anchors.entry(caps[1].to_string()).or_insert(index);
+        }
+    }
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let chunk =
+                FRAGMENT_HREF.replace_all(chunk, |caps: &regex::Captures<'_>| {
+                    match anchors.get(&caps[1]) {
+                        Some(&target) if target != index => {
+                            format!(" href=\"{}#{}\"", names[target], &caps[1])
+                        }
+                        _ => caps[0].to_string(),
+                    }
+                });
+            (names[index].clone(), format!("{head}{chunk}{tail}"))
+        })
+        .collect()
+}
+
+/// 正文内容在文档中的范围：`<body>`（EPUB 3 为其中的 `<section>`）开始标签之后到结束标签之前。
+fn content_range(html: &str) -> Option<(usize, usize)> {
+    let body = html.find("<body")?;
+    let mut start = body + html[body..].find(">\n")? + 2;
+    let mut end = html.rfind("</body>")?;
+    if html[start..].starts_with("<section") {
+        start += html[start..].find(">\n")? + 2;
+        end = html[..end].rfind("</section>")?;
+    }
+    (start <= end).then_some((start, end))
+}
+
+/// 分页后缀：`a`…`z`、`aa`、`ab`……
+fn part_suffix(mut index: usize) -> String {
+    let mut suffix = Vec::new();
+    loop {
+        suffix.push(b'a' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    suffix.reverse();
+    String::from_utf8(suffix).expect("ascii")
+}
@@ -0,0 +1,222 @@
+//! TrueType 字体子集化：只保留书中实际用到的字形。
+//!
+//! 采用“清空未用字形”的方式：字形编号保持不变，未用到的字形在 `glyf` 中置为空，
+//! 因此 `cmap`、`hmtx`、`GSUB` 等表无需重写。CJK 字体的体积几乎全部来自 `glyf`，
+//! 这种做法已能把 10–20 MB 的字体压缩到几百 KB。CFF（`OTTO`）字体与字体集合不支持，
+//! 调用方应回退为嵌入完整字体。
+
+use std::collections::BTreeSet;
+
+/// 不参与子集化、直接丢弃的表：`DSIG` 签名在修改字形后必然失效。
+const DROPPED_TABLES: [&[u8; 4]; 1] = [b"DSIG"];
+
+struct TableRecord {
+    tag: [u8; 4],
+    data: Vec<u8>,
+}
+
+/// 按 `used` 中的字符裁剪字体，返回新的字体数据。
+pub(crate) fn subset_font(data: &[u8], used: &BTreeSet<char>) -> Result<Vec<u8>, String> {
+    match data.get(0..4) {
+        Some([0x00, 0x01, 0x00, 0x00]) | Some(b"true") => {}
+        Some(b"OTTO") => return Err("CFF-based OpenType fonts are not supported".to_string()),
+        Some(b"ttcf") => return Err("font collections are not supported".to_string()),
+        _ => return Err("not a TrueType font".to_string()),
+    }
+    let face = ttf_parser::Face::parse(data, 0).map_err(|err| err.to_string())?;
+
+    let mut tables = read_tables(data)?;
+    let table = |tables: &[TableRecord], tag: &[u8; 4]| {
+        tables
+            .iter()
+            .position(|table| &table.tag == tag)
+            .ok_or_else(|| format!("missing {} table", String::from_utf8_lossy(tag)))
+    };
+    let head_index = table(&tables, b"head")?;
+    let loca_index = table(&tables, b"loca")?;
+    let glyf_index = table(&tables, b"glyf")?;
+    let maxp_index = table(&tables, b"maxp")?;
+
+    if tables[head_index].data.len() < 54 {
+        return Err("truncated head table".to_string());
+    }
+    let num_glyphs = read_u16(&tables[maxp_index].data, 4)? as usize;
+    let long_offsets = read_u16(&tables[head_index].data, 50)? != 0;
+    let offsets = read_loca(&tables[loca_index].data, num_glyphs, long_offsets)?;
+    let glyf = &tables[glyf_index].data;
+    let glyph = |gid: usize| -> Result<&[u8], String> {
+        let (start, end) = (offsets[gid], offsets[gid + 1]);
+        glyf.get(start..end)
+            .ok_or_else(|| format!("glyph {gid} is out of bounds"))
+    };
+
+    // .notdef 必须保留；复合字形引用的部件字形也要一并保留。
+    let mut keep = vec![false; num_glyphs];
+    let mut pending = vec![0usize];
+    pending.extend(
+        used.iter()
+            .filter_map(|&ch| face.glyph_index(ch))
+            .map(|id| id.0 as usize),
+    );
+    while let Some(gid) = pending.pop() {
+        if gid >= num_glyphs || keep[gid] {
+            continue;
+        }
+        keep[gid] = true;
+        pending.extend(composite_components(glyph(gid)?)?);
+    }
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((num_glyphs + 1) * 4);
+    for (gid, &kept) in keep.iter().enumerate() {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if kept {
+            new_glyf.extend_from_slice(glyph(gid)?);
+            while new_glyf.len() % 4 != 0 {
+                new_glyf.push(0);
+            }
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    tables[glyf_index].data = new_glyf;
+    tables[loca_index].data = new_loca;
+    // 统一改用长偏移 loca，并在写出时重算 checkSumAdjustment。
+    tables[head_index].data[50..52].copy_from_slice(&1u16.to_be_bytes());
+    tables[head_index].data[8..12].copy_from_slice(&[0; 4]);
+    tables.retain(|table| !DROPPED_TABLES.contains(&&table.tag));
+
+    Ok(write_font(
+        data[0..4].try_into().expect("sfnt version"),
+        tables,
+    ))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| "truncated font data".to_string())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| "truncated font data".to_string())
+}
+
+fn read_tables(data: &[u8]) -> Result<Vec<TableRecord>, String> {
+    let num_tables = read_u16(data, 4)? as usize;
+    let mut tables = Vec::with_capacity(num_tables);
+    for index in 0..num_tables {
+        let record = 12 + index * 16;
+        let tag: [u8; 4] = data
+            .get(record..record + 4)
+            .ok_or("truncated table directory")?
+            .try_into()
+            .expect("four bytes");
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        let table = data
+            .get(offset..offset + length)
+            .ok_or_else(|| format!("table {} is out of bounds", String::from_utf8_lossy(&tag)))?;
+        tables.push(TableRecord {
+            tag,
+            data: table.to_vec(),
+        });
+    }
+    Ok(tables)
+}
+
+fn read_loca(data: &[u8], num_glyphs: usize, long_offsets: bool) -> Result<Vec<usize>, String> {
+    (0..=num_glyphs)
+        .map(|index| {
+            if long_offsets {
+                read_u32(data, index * 4).map(|offset| offset as usize)
+            } else {
+                read_u16(data, index * 2).map(|offset| offset as usize * 2)
+            }
+        })
+        .collect()
+}
+
+/// 返回复合字形引用的部件字形编号；简单字形返回空列表。
+fn composite_components(glyph: &[u8]) -> Result<Vec<usize>, String> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+    if glyph.is_empty() || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(Vec::new());
+    }
+    let mut components = Vec::new();
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)? as usize);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// 按 OpenType 表目录格式写出字体，表数据 4 字节对齐，并回填 `head.checkSumAdjustment`。
+fn write_font(sfnt_version: [u8; 4], tables: Vec<TableRecord>) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = (u16::BITS - 1 - num_tables.max(1).leading_zeros()) as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    let range_shift = num_tables * 16 - search_range;
+
+    let mut font = Vec::new();
+    font.extend_from_slice(&sfnt_version);
+    for value in [num_tables, search_range, entry_selector, range_shift] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut body = Vec::new();
+    let mut head_offset = None;
+    for table in &tables {
+        if &table.tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(&table.tag);
+        font.extend_from_slice(&checksum(&table.data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.data.len() as u32).to_be_bytes());
+        body.extend_from_slice(&table.data);
+        while body.len() % 4 != 0 {
+            body.push(0);
+        }
+        offset = 12 + tables.len() * 16 + body.len();
+    }
+    font.extend_from_slice(&body);
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
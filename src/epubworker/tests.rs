@@ -1,5 +1,8 @@
 use super::*;
-use crate::TocOptions;
+use crate::{
+    Accessibility, Creator, CreatorRole, FontRole, MetadataEntry, SpineSection, TocOptions,
+    ZipCompression,
+};
 use bytes::Bytes;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -71,27 +74,221 @@ fn split_title_line_handles_title_and_label() {
 
 #[test]
 fn split_paragraphs_groups_lines() {
-    let paras = split_paragraphs("a\nb\n\nc\n\n\n");
+    let paras = split_paragraphs("a\nb\n\nc\n\n\n", None);
     assert_eq!(paras.len(), 2);
     assert_eq!(paras[0], vec!["a".to_string(), "b".to_string()]);
     assert_eq!(paras[1], vec!["c".to_string()]);
 }
 
+#[test]
+fn split_paragraphs_reflows_hard_wrapped_lines() {
+    let text = [
+        "　　他推开门，外面的雨已经下了整整一夜，街上",
+        "没有一个行人。",
+        "　　远处传来钟声，一下一下，敲得人心里发慌，",
+        "他想起了很多年前的那个下午。",
+        "　　短句。",
+    ]
+    .join("\n");
+    let paras = split_paragraphs(&text, Some(0.2));
+    assert_eq!(
+        paras,
+        vec![
+            vec!["他推开门，外面的雨已经下了整整一夜，街上没有一个行人。".to_string()],
+            vec![
+                "远处传来钟声，一下一下，敲得人心里发慌，他想起了很多年前的那个下午。".to_string()
+            ],
+            vec!["短句。".to_string()],
+        ]
+    );
+
+    let english = "It was a bright cold day in April, and the\nclocks were striking thirteen.\nShort line\nNext paragraph.";
+    assert_eq!(
+        split_paragraphs(english, Some(0.2)),
+        vec![
+            vec![
+                "It was a bright cold day in April, and the clocks were striking thirteen."
+                    .to_string()
+            ],
+            vec!["Short line".to_string()],
+            vec!["Next paragraph.".to_string()],
+        ]
+    );
+    // 诗行块内不合并，按空行分节。
+    let verse = "[verse]\n长长的第一句诗行没有标点\n第二句\n\n第三句\n[/verse]\n后文";
+    assert_eq!(
+        split_paragraphs(verse, Some(1.0)),
+        vec![
+            vec![
+                "[verse]".to_string(),
+                "长长的第一句诗行没有标点".to_string(),
+                "第二句".to_string()
+            ],
+            vec!["第三句".to_string(), "[/verse]".to_string()],
+            vec!["后文".to_string()],
+        ]
+    );
+    // 灵敏度最高时，只要不以句末标点结尾就合并。
+    assert_eq!(
+        split_paragraphs(english, Some(1.0))[1],
+        vec!["Short line Next paragraph.".to_string()]
+    );
+}
+
+#[test]
+fn render_chapter_converts_ruby_markers() {
+    let chapter = ChapterDraft {
+        title: "第一章".to_string(),
+        content: "{汉字|hàn zì}与{漢字|かんじ}，{不是|注音\n换行}。".to_string(),
+        ..Default::default()
+    };
+    let style = TextStyle {
+        inline_markup: true,
+        ..Default::default()
+    };
+    let render = |epub_version, style: &TextStyle| {
+        render_chapter(
+            &chapter,
+            1,
+            &ChapterRenderOptions {
+                language: "zh-CN",
+                style,
+                template: crate::CssTemplate::Classic,
+                header_image: None,
+                header_fullbleed: false,
+                epub_version,
+                markdown: false,
+                images: &[],
+            },
+        )
+    };
+    let html = render(EpubVersion::Epub3, &style);
+    assert!(html.contains("<ruby>汉字<rp>(</rp><rt>hàn zì</rt><rp>)</rp></ruby>"));
+    assert!(html.contains("<ruby>漢字<rp>(</rp><rt>かんじ</rt><rp>)</rp></ruby>"));
+    assert!(html.contains("{不是|注音"));
+    let html = render(EpubVersion::Epub2, &style);
+    assert!(html.contains("<ruby><rb>汉字</rb><rp>(</rp><rt>hàn zì</rt><rp>)</rp></ruby>"));
+    assert_eq!(strip_inline_markup("{汉字|hàn zì}", true), "汉字(hàn zì)");
+
+    // 未开启行内标记时花括号原样保留。
+    let html = render(EpubVersion::Epub3, &TextStyle::default());
+    assert!(!html.contains("<ruby>"));
+    assert!(html.contains("{汉字|hàn zì}与{漢字|かんじ}"));
+    assert_eq!(strip_inline_markup("{汉字|hàn zì}", false), "{汉字|hàn zì}");
+}
+
+#[test]
+fn render_chapter_renders_inline_emphasis() {
+    let style = TextStyle {
+        inline_markup: true,
+        ..Default::default()
+    };
+    let render = |content: &str, markdown| {
+        let chapter = ChapterDraft {
+            title: "第一章".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        render_chapter(
+            &chapter,
+            1,
+            &ChapterRenderOptions {
+                language: "zh-CN",
+                style: &style,
+                template: crate::CssTemplate::Classic,
+                header_image: None,
+                header_fullbleed: false,
+                epub_version: EpubVersion::Epub3,
+                markdown,
+                images: &[],
+            },
+        )
+    };
+    let html = render(
+        "这是**重要**的*提示*，..务必..记住。\n\n* * *\n\n\\*原样\\* 和 \\{不|注音}",
+        false,
+    );
+    assert!(html.contains(
+        "这是<strong>重要</strong>的<em>提示</em>，<span class=\"emphasis-dot\">务必</span>记住。"
+    ));
+    assert!(html.contains("<hr class=\"scene-break\"/>"));
+    assert!(html.contains(">*原样* 和 {不|注音}</p>"), "{html}");
+
+    let html = render("..着重..与 __粗体__", true);
+    assert!(html.contains("<span class=\"emphasis-dot\">着重</span>与 <strong>粗体</strong>"));
+
+    assert_eq!(
+        strip_inline_markup("**粗**与..点..，\\*星\\*", true),
+        "粗与点，*星*"
+    );
+
+    // 省略号与算式不是强调标记。
+    let html = render("好吧...我走了...\n\n3*4*5 与 2**10**3，..再..见.", false);
+    assert!(html.contains(">好吧...我走了...</p>"), "{html}");
+    assert!(
+        html.contains(">3*4*5 与 2**10**3，<span class=\"emphasis-dot\">再</span>见.</p>"),
+        "{html}"
+    );
+    let html = render("好吧....算了....", true);
+    assert!(html.contains("好吧....算了...."), "{html}");
+    assert_eq!(
+        strip_inline_markup("好吧...我走了...3*4*5", true),
+        "好吧...我走了...3*4*5"
+    );
+}
+
+#[test]
+fn render_chapter_keeps_markers_without_inline_markup() {
+    let chapter = ChapterDraft {
+        title: "第一章".to_string(),
+        content: "这是**重要**的*提示*，..务必..记住。".to_string(),
+        ..Default::default()
+    };
+    let html = render_chapter(
+        &chapter,
+        1,
+        &ChapterRenderOptions {
+            language: "zh-CN",
+            style: &TextStyle::default(),
+            template: crate::CssTemplate::Classic,
+            header_image: None,
+            header_fullbleed: false,
+            epub_version: EpubVersion::Epub3,
+            markdown: false,
+            images: &[],
+        },
+    );
+    assert!(
+        html.contains(">这是**重要**的*提示*，..务必..记住。</p>"),
+        "{html}"
+    );
+    assert_eq!(
+        strip_inline_markup("**粗**与..点..", false),
+        "**粗**与..点.."
+    );
+}
+
 #[test]
 fn render_chapter_includes_label_and_paragraphs() {
     let chapter = ChapterDraft {
         title: "Chapter 1 The Start".to_string(),
         content: "Line one\n\nLine two".to_string(),
+        ..Default::default()
     };
     let style = TextStyle::default();
     let html = render_chapter(
         &chapter,
-        "en",
-        &style,
-        crate::CssTemplate::Classic,
         1,
-        None,
-        false,
+        &ChapterRenderOptions {
+            language: "en",
+            style: &style,
+            template: crate::CssTemplate::Classic,
+            header_image: None,
+            header_fullbleed: false,
+            epub_version: EpubVersion::Epub2,
+            markdown: false,
+            images: &[],
+        },
     );
     assert!(html.contains("class=\"chapter-label\">Chapter I</div>"));
     assert!(html.contains("<h2>The Start</h2>"));
@@ -102,21 +299,207 @@ fn render_chapter_includes_label_and_paragraphs() {
     assert!(html.contains("Line two</p>"));
 }
 
+#[test]
+fn render_chapter_applies_heading_template() {
+    let render = |title: &str, language: &str, template: &str| {
+        let chapter = ChapterDraft {
+            title: title.to_string(),
+            content: "正文".to_string(),
+            ..Default::default()
+        };
+        let style = TextStyle {
+            heading_template: template.to_string(),
+            ..TextStyle::default()
+        };
+        render_chapter(
+            &chapter,
+            1,
+            &ChapterRenderOptions {
+                language,
+                style: &style,
+                template: crate::CssTemplate::Classic,
+                header_image: None,
+                header_fullbleed: false,
+                epub_version: EpubVersion::Epub2,
+                markdown: false,
+                images: &[],
+            },
+        )
+    };
+    let html = render("第十二章 雨夜", "zh-CN", "{label} · {title}");
+    assert!(html.contains("<h2>第十二章 · 雨夜</h2>"), "{html}");
+    assert!(!html.contains("chapter-label"));
+    let html = render("Chapter 3: The Start", "en", "{num}. {title}");
+    assert!(html.contains("<h2>3. The Start</h2>"), "{html}");
+    let html = render("Chapter 4 - Home", "en", "Part {roman}: {title}");
+    assert!(html.contains("<h2>Part IV: Home</h2>"), "{html}");
+    // 没有副标题或编号时显示原标题。
+    assert!(render("第五章", "zh-CN", "{num}. {title}").contains("<h2>第五章</h2>"));
+    assert!(render("序章", "zh-CN", "{num}. {title}").contains("<h2>序章</h2>"));
+}
+
+#[test]
+fn render_chapter_uses_structured_heading_until_title_changes() {
+    let mut chapter = ChapterDraft {
+        title: "Ep.7 雨夜".to_string(),
+        content: "正文".to_string(),
+        heading: Some(crate::ChapterHeading {
+            number: Some("Ep.7".to_string()),
+            title: Some("雨夜".to_string()),
+        }),
+        ..Default::default()
+    };
+    let style = TextStyle::default();
+    let options = ChapterRenderOptions {
+        language: "zh-CN",
+        style: &style,
+        template: crate::CssTemplate::Classic,
+        header_image: None,
+        header_fullbleed: false,
+        epub_version: EpubVersion::Epub2,
+        markdown: false,
+        images: &[],
+    };
+    let html = render_chapter(&chapter, 1, &options);
+    assert!(html.contains("class=\"chapter-label\">Ep.7</div>"));
+    assert!(html.contains("<h2>雨夜</h2>"));
+
+    let stripped_style = TextStyle {
+        strip_heading_number: true,
+        ..TextStyle::default()
+    };
+    let stripped = render_chapter(
+        &chapter,
+        1,
+        &ChapterRenderOptions {
+            style: &stripped_style,
+            ..options
+        },
+    );
+    assert!(!stripped.contains("chapter-label"));
+    assert!(stripped.contains("<h2>雨夜</h2>"));
+
+    // 标题被改动后不再使用结构化标题。
+    chapter.title = "雨夜".to_string();
+    let renamed = render_chapter(&chapter, 1, &options);
+    assert!(!renamed.contains("Ep.7"));
+}
+
+#[test]
+fn render_chapter_markdown_inline_and_blockquote() {
+    let chapter = ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "**Bold** and *soft* see [docs](https://example.com/a_b_c) <x> snake_case_name __strong__\n\n\
+                  [bad](javascript:alert(1)) [mail](mailto:a@b.c) [note](#n1) [Up](HTTP://x.org)\n\n\
+                  > quoted _line_\n> second"
+            .to_string(),
+        ..Default::default()
+    };
+    let style = TextStyle {
+        inline_markup: true,
+        ..Default::default()
+    };
+    let options = ChapterRenderOptions {
+        language: "en",
+        style: &style,
+        template: crate::CssTemplate::Classic,
+        header_image: None,
+        header_fullbleed: false,
+        epub_version: EpubVersion::Epub2,
+        markdown: true,
+        images: &[],
+    };
+    let html = render_chapter(&chapter, 1, &options);
+    assert!(html.contains("<strong>Bold</strong> and <em>soft</em>"));
+    assert!(html.contains("<a href=\"https://example.com/a_b_c\">docs</a>"));
+    assert!(html.contains("&lt;x&gt; snake_case_name <strong>strong</strong>"));
+    assert!(html.contains("[bad](javascript:alert(1))"));
+    assert!(!html.contains("href=\"javascript"));
+    assert!(html.contains("<a href=\"mailto:a@b.c\">mail</a>"));
+    assert!(html.contains("<a href=\"#n1\">note</a>"));
+    assert!(html.contains("<a href=\"HTTP://x.org\">Up</a>"));
+    assert!(html.contains("<blockquote><p class=\"chapter-paragraph\">quoted <em>line</em><br/>second</p></blockquote>"));
+
+    let plain = render_chapter(
+        &chapter,
+        1,
+        &ChapterRenderOptions {
+            markdown: false,
+            ..options
+        },
+    );
+    // 启用行内标记的纯文本也渲染强调，但不解析链接与引用块。
+    assert!(plain.contains("<strong>Bold</strong>"));
+    assert!(plain.contains("[docs](https://example.com/a_b_c)"));
+    assert!(!plain.contains("<blockquote>"));
+}
+
+#[test]
+fn render_chapter_footnotes() {
+    let chapter = ChapterDraft {
+        title: "第一章".to_string(),
+        content:
+            "正文[^1]，再引[^1]，还有[^note]和[^missing]。\n[^1]: 第一条 <注>\n[^note]: 第二条"
+                .to_string(),
+        ..Default::default()
+    };
+    let style = TextStyle::default();
+    let options = ChapterRenderOptions {
+        language: "zh-CN",
+        style: &style,
+        template: crate::CssTemplate::Classic,
+        header_image: None,
+        header_fullbleed: false,
+        epub_version: EpubVersion::Epub3,
+        markdown: false,
+        images: &[],
+    };
+    let html = render_chapter(&chapter, 1, &options);
+    assert!(html.contains(
+        "<a class=\"noteref\" epub:type=\"noteref\" role=\"doc-noteref\" href=\"#fn-1\" id=\"fnref-1\"><sup>1</sup></a>"
+    ));
+    assert_eq!(html.matches("id=\"fnref-1\"").count(), 1);
+    assert!(html.contains("href=\"#fn-2\" id=\"fnref-2\""));
+    assert!(html.contains("[^missing]"));
+    assert!(html.contains(
+        "<aside class=\"footnote\" epub:type=\"footnote\" role=\"doc-footnote\" id=\"fn-1\"><p><a href=\"#fnref-1\">1</a>. 第一条 &lt;注&gt;</p></aside>"
+    ));
+    assert!(!html.contains("[^1]:"));
+
+    let epub2 = render_chapter(
+        &chapter,
+        1,
+        &ChapterRenderOptions {
+            epub_version: EpubVersion::Epub2,
+            ..options
+        },
+    );
+    assert!(!epub2.contains("epub:type"));
+    assert!(!epub2.contains("role="));
+    assert!(epub2.contains("<div class=\"footnote\" id=\"fn-2\">"));
+}
+
 #[test]
 fn render_chapter_fantasy_header_structure() {
     let chapter = ChapterDraft {
         title: "第十二章 星落".to_string(),
         content: "内容".to_string(),
+        ..Default::default()
     };
     let style = TextStyle::default();
     let html = render_chapter(
         &chapter,
-        "zh-CN",
-        &style,
-        crate::CssTemplate::Fantasy,
         12,
-        None,
-        false,
+        &ChapterRenderOptions {
+            language: "zh-CN",
+            style: &style,
+            template: crate::CssTemplate::Fantasy,
+            header_image: None,
+            header_fullbleed: false,
+            epub_version: EpubVersion::Epub2,
+            markdown: false,
+            images: &[],
+        },
     );
     assert!(html.contains("Header-image-dk"));
     assert!(html.contains("images/头图.webp"));
@@ -133,6 +516,7 @@ fn render_chapter_includes_header_image() {
     let chapter = ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "Hello".to_string(),
+        ..Default::default()
     };
     let style = TextStyle::default();
     let header = ImageAsset {
@@ -140,15 +524,21 @@ fn render_chapter_includes_header_image() {
         bytes: Bytes::from_static(b"img"),
         mime: "image/png".to_string(),
         caption: None,
+        chapter: None,
     };
     let html = render_chapter(
         &chapter,
-        "en",
-        &style,
-        crate::CssTemplate::Classic,
         1,
-        Some(&header),
-        true,
+        &ChapterRenderOptions {
+            language: "en",
+            style: &style,
+            template: crate::CssTemplate::Classic,
+            header_image: Some(&header),
+            header_fullbleed: true,
+            epub_version: EpubVersion::Epub2,
+            markdown: false,
+            images: &[],
+        },
     );
     assert!(html.contains("chapter-head-image"));
     assert!(html.contains("fullbleed"));
@@ -160,6 +550,7 @@ fn render_chapter_applies_marker_classes() {
     let chapter = ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "[class=note important]Hello\n\nWorld".to_string(),
+        ..Default::default()
     };
     let style = TextStyle {
         extra_paragraph_class: "base".to_string(),
@@ -167,18 +558,138 @@ fn render_chapter_applies_marker_classes() {
     };
     let html = render_chapter(
         &chapter,
-        "en",
-        &style,
-        crate::CssTemplate::Classic,
         1,
-        None,
-        false,
+        &ChapterRenderOptions {
+            language: "en",
+            style: &style,
+            template: crate::CssTemplate::Classic,
+            header_image: None,
+            header_fullbleed: false,
+            epub_version: EpubVersion::Epub2,
+            markdown: false,
+            images: &[],
+        },
     );
     assert!(
         html.contains("class=\"chapter-paragraph chapter-paragraph-first base note important\"")
     );
 }
 
+#[test]
+fn split_paragraphs_isolates_scene_breaks() {
+    let text = "第一段。\n***\n第二段。\n\n\n\n第三段。\n\n— — —\n\n[Scene]\n第四段。\n\n\n\n";
+    let paras = split_paragraphs(text, None);
+    let lines: Vec<&str> = paras.iter().map(|p| p[0].as_str()).collect();
+    assert_eq!(
+        lines,
+        [
+            "第一段。",
+            "***",
+            "第二段。",
+            "[scene]",
+            "第三段。",
+            "— — —",
+            "[Scene]",
+            "第四段。"
+        ]
+    );
+    assert!(paras.iter().all(|p| p.len() == 1));
+    assert_eq!(
+        split_paragraphs("一行很长的文字没有标点\n***\n后文", Some(1.0)),
+        vec![
+            vec!["一行很长的文字没有标点".to_string()],
+            vec!["***".to_string()],
+            vec!["后文".to_string()],
+        ]
+    );
+    for line in ["——", "--", "*a*", "……", "* - *"] {
+        assert!(!is_scene_break(line), "{line}");
+    }
+}
+
+#[test]
+fn render_chapter_renders_scene_breaks() {
+    let chapter = ChapterDraft {
+        title: "第一章".to_string(),
+        content: "开头。\n\n继续。\n\n* * *\n\n新场景。".to_string(),
+        ..Default::default()
+    };
+    let html = render_chapter(
+        &chapter,
+        1,
+        &ChapterRenderOptions {
+            language: "zh-CN",
+            style: &TextStyle::default(),
+            template: crate::CssTemplate::Classic,
+            header_image: None,
+            header_fullbleed: false,
+            epub_version: EpubVersion::Epub3,
+            markdown: false,
+            images: &[],
+        },
+    );
+    assert!(html.contains(
+        "<hr class=\"scene-break\"/>\n<p class=\"chapter-paragraph\" style=\"text-indent: 0.00em;\">新场景。</p>"
+    ), "{html}");
+    assert!(!html.contains("* * *"));
+}
+
+#[test]
+fn render_chapter_wraps_quote_and_verse_blocks() {
+    let render = |content: &str| {
+        let chapter = ChapterDraft {
+            title: "Chapter 1".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        render_chapter(
+            &chapter,
+            1,
+            &ChapterRenderOptions {
+                language: "zh-CN",
+                style: &TextStyle::default(),
+                template: crate::CssTemplate::Classic,
+                header_image: None,
+                header_fullbleed: false,
+                epub_version: EpubVersion::Epub3,
+                markdown: false,
+                images: &[],
+            },
+        )
+    };
+    let html = render(
+        "开头。\n\n[quote]第一段引文。\n\n[class=sign]第二段引文。[/quote]\n\n[VERSE]\n床前明月光\n疑是地上霜\n\n举头望明月\n低头思故乡\n[/verse]\n\n结尾。",
+    );
+    assert!(html.contains(
+        "<blockquote class=\"quote\">\n<p class=\"chapter-paragraph\">第一段引文。</p>\n<p class=\"chapter-paragraph sign\">第二段引文。</p>\n</blockquote>"
+    ), "{html}");
+    assert!(html.contains(
+        "<div class=\"verse\">\n<p class=\"verse-stanza\">床前明月光<br/>疑是地上霜</p>\n<p class=\"verse-stanza\">举头望明月<br/>低头思故乡</p>\n</div>\n<p class=\"chapter-paragraph\""
+    ), "{html}");
+    assert!(!html.contains("[/"));
+
+    // 没有结束标记时只包裹一段。
+    let html = render("[quote]引文。\n\n正文。");
+    assert!(html.contains(
+        "<blockquote class=\"quote\">\n<p class=\"chapter-paragraph\">引文。</p>\n</blockquote>\n<p"
+    ));
+
+    let mut paragraph = vec!["[verse]诗行".to_string(), "末句[/verse]".to_string()];
+    strip_block_markers(&mut paragraph);
+    assert_eq!(paragraph, vec!["诗行".to_string(), "末句".to_string()]);
+}
+
+#[test]
+fn scope_chapter_css_prefixes_selectors() {
+    let css = "body { margin: 0; }\np.letter, .sign { font-family: cursive; }\n@font-face { font-family: X; }";
+    let scoped = scope_chapter_css(css, "chapter-css-0002");
+    assert!(scoped.contains("body.chapter-css-0002 { margin: 0; }"));
+    assert!(scoped.contains(
+        "body.chapter-css-0002 p.letter, body.chapter-css-0002 .sign { font-family: cursive; }"
+    ));
+    assert!(scoped.contains("@font-face { font-family: X; }"));
+}
+
 #[test]
 fn render_gallery_includes_captions() {
     let images = vec![
@@ -187,15 +698,17 @@ fn render_gallery_includes_captions() {
             bytes: Bytes::from_static(b"123"),
             mime: "image/png".to_string(),
             caption: Some("Cover".to_string()),
+            chapter: None,
         },
         ImageAsset {
             name: "image2.png".to_string(),
             bytes: Bytes::from_static(b"456"),
             mime: "image/png".to_string(),
             caption: None,
+            chapter: None,
         },
     ];
-    let html = render_gallery(&images, "zh-CN", "插图");
+    let html = render_gallery(&images, "zh-CN", "插图", EpubVersion::Epub2);
     assert!(html.contains("<figcaption>Cover</figcaption>"));
     assert!(html.contains("images/image2.png"));
 }
@@ -204,12 +717,12 @@ fn render_gallery_includes_captions() {
 fn render_text_cover_includes_title_author_and_frame() {
     let book = BookInfo {
         title: "Cover Title".to_string(),
-        author: "Cover Author".to_string(),
+        creators: vec![Creator::new("Cover Author", CreatorRole::Author)],
         publisher: "Pub".to_string(),
         publish_date: "2025".to_string(),
         ..Default::default()
     };
-    let html = render_text_cover(&book, "en", crate::CssTemplate::Classic);
+    let html = render_text_cover(&book, "en", crate::CssTemplate::Classic, EpubVersion::Epub2);
     assert!(html.contains("cover-frame"));
     assert!(html.contains("Cover Title"));
     assert!(html.contains("Cover Author"));
@@ -228,11 +741,24 @@ fn build_stylesheet_includes_custom_css_and_font() {
         family: "CustomFont".to_string(),
         bytes: Bytes::from_static(b"font"),
         mime: "font/ttf".to_string(),
+        roles: vec![FontRole::Body],
+    };
+
+    let heading = FontAsset {
+        name: "heading.ttf".to_string(),
+        family: "HeadingFont".to_string(),
+        bytes: Bytes::from_static(b"font"),
+        mime: "font/ttf".to_string(),
+        roles: vec![FontRole::Heading, FontRole::ChapterLabel],
     };
 
-    let css = build_stylesheet(&style, Some(&font)).expect("css");
-    assert!(css.contains("@font-face"));
-    assert!(css.contains("CustomFont"));
+    let css =
+        build_stylesheet(&style, &TocOptions::default(), &[font, heading], false).expect("css");
+    assert!(css.contains("src: url(\"fonts/custom.ttf\")"));
+    assert!(css.contains("src: url(\"fonts/heading.ttf\")"));
+    assert!(css.contains("body, p, li { font-family: \"CustomFont\""));
+    assert!(css.contains("h1, h2, h3 { font-family: \"HeadingFont\""));
+    assert!(css.contains(".chapter-label, p.nt, p.et { font-family: \"HeadingFont\""));
     assert!(css.contains("/* === custom css === */"));
     assert!(css.contains("p { color: red; }"));
 }
@@ -243,18 +769,34 @@ fn build_stylesheet_includes_folio_overrides() {
         css_template: crate::CssTemplate::Folio,
         ..Default::default()
     };
-    let css = build_stylesheet(&style, None).expect("css");
+    let css = build_stylesheet(&style, &TocOptions::default(), &[], false).expect("css");
     assert!(css.contains("/* === folio chapter header overrides === */"));
     assert!(css.contains("folio-divider.svg"));
 }
 
+#[test]
+fn build_stylesheet_vertical_writing_mode() {
+    let style = TextStyle {
+        vertical: true,
+        ..Default::default()
+    };
+    let css = build_stylesheet(&style, &TocOptions::default(), &[], false).expect("css");
+    assert!(css.contains("writing-mode: vertical-rl;"));
+    assert!(css.contains("-epub-writing-mode: vertical-rl;"));
+    assert!(css.contains(".tcy { text-combine-upright: all;"));
+
+    let css =
+        build_stylesheet(&TextStyle::default(), &TocOptions::default(), &[], false).expect("css");
+    assert!(!css.contains("vertical-rl"));
+}
+
 #[test]
 fn build_stylesheet_includes_fantasy_overrides() {
     let style = TextStyle {
         css_template: crate::CssTemplate::Fantasy,
         ..Default::default()
     };
-    let css = build_stylesheet(&style, None).expect("css");
+    let css = build_stylesheet(&style, &TocOptions::default(), &[], false).expect("css");
     assert!(css.contains("/* === fantasy chapter header overrides === */"));
     assert!(css.contains("fantasy-divider.svg"));
     assert!(css.contains("Header-image-dk"));
@@ -262,17 +804,110 @@ fn build_stylesheet_includes_fantasy_overrides() {
     assert!(css.contains("body.intro2"));
 }
 
+#[test]
+fn build_stylesheet_toc_page_options() {
+    let css =
+        build_stylesheet(&TextStyle::default(), &TocOptions::default(), &[], false).expect("css");
+    assert!(css.contains("/* === toc page === */"));
+    assert!(!css.contains("column-count"));
+    assert!(!css.contains("dotted"));
+
+    let style = TextStyle {
+        css_template: crate::CssTemplate::Folio,
+        ..Default::default()
+    };
+    let toc = TocOptions {
+        columns: 2,
+        dotted_leaders: true,
+        ..Default::default()
+    };
+    let css = build_stylesheet(&style, &toc, &[], false).expect("css");
+    assert!(css.contains("column-count: 2;"));
+    assert!(css.contains("border-bottom: 1px dotted #6b5b4b;"));
+}
+
 #[test]
 fn generate_filename_sanitizes_and_appends_extension() {
     let book = BookInfo {
         title: "My/Book".to_string(),
-        author: "A:B".to_string(),
+        creators: vec![Creator::new("A:B", CreatorRole::Author)],
         ..Default::default()
     };
-    let name = generate_filename(&book, "my*file");
+    let name = generate_filename(&book, "my*file", 0, crate::CssTemplate::Classic);
     assert_eq!(name, "myfile.epub");
 }
 
+#[test]
+fn generate_filename_expands_extended_variables() {
+    let book = BookInfo {
+        title: "三体".to_string(),
+        creators: vec![Creator::new("刘慈欣", CreatorRole::Author)],
+        isbn: " 9787536692930 ".to_string(),
+        publisher: "重庆出版社".to_string(),
+        publish_date: "2008年1月5日".to_string(),
+        ..Default::default()
+    };
+    let name = generate_filename(
+        &book,
+        "{书名}_{章节数}章_{isbn}_{出版社}_{模板}_{日期:%Y%m%d}_{日期:%y.%m}",
+        36,
+        crate::CssTemplate::Folio,
+    );
+    assert_eq!(
+        name,
+        "三体_36章_9787536692930_重庆出版社_Folio_20080105_08.01.epub"
+    );
+
+    let book = BookInfo {
+        publish_date: "2008".to_string(),
+        ..Default::default()
+    };
+    let name = generate_filename(&book, "{日期:%Y-%m}|{日期}", 0, crate::CssTemplate::Classic);
+    assert_eq!(name, "2008-2008.epub");
+    let book = BookInfo {
+        publish_date: "spring".to_string(),
+        ..Default::default()
+    };
+    let name = generate_filename(&book, "{日期:%Y}", 0, crate::CssTemplate::Classic);
+    assert_eq!(name, "spring.epub");
+}
+
+#[test]
+fn resolve_output_path_numbers_existing_files() {
+    let suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("reasypub-resolve-{suffix}"));
+    std::fs::create_dir_all(&dir).expect("create dir");
+    for name in ["书.kepub.epub", "书 (1).kepub.epub", "notes.txt"] {
+        std::fs::write(dir.join(name), b"x").expect("write");
+    }
+    let resolve = |name, policy| resolve_output_path(&dir, name, policy);
+    assert_eq!(
+        resolve("新书.epub", OverwritePolicy::Ask).expect("free name"),
+        dir.join("新书.epub")
+    );
+    assert_eq!(
+        resolve("书.kepub.epub", OverwritePolicy::Number).expect("numbered"),
+        dir.join("书 (2).kepub.epub")
+    );
+    assert_eq!(
+        resolve("notes.txt", OverwritePolicy::Number).expect("numbered"),
+        dir.join("notes (1).txt")
+    );
+    assert_eq!(
+        resolve("书.kepub.epub", OverwritePolicy::Overwrite).expect("overwrite"),
+        dir.join("书.kepub.epub")
+    );
+    assert!(matches!(
+        resolve("notes.txt", OverwritePolicy::Ask),
+        Err(BuildError::OutputExists(path)) if path == dir.join("notes.txt")
+    ));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn normalize_output_dir_defaults_to_current_dir() {
     let empty = PathBuf::new();
@@ -296,7 +931,7 @@ fn build_epub_writes_file() {
     let dir = unique_temp_dir("reasypub-epub");
     let book = BookInfo {
         title: "Test Book".to_string(),
-        author: "Tester".to_string(),
+        creators: vec![Creator::new("Tester", CreatorRole::Author)],
         ..Default::default()
     };
 
@@ -307,7 +942,7 @@ fn build_epub_writes_file() {
         style: TextStyle::default(),
         cover: None,
         images: Vec::new(),
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         include_images_section: false,
@@ -315,10 +950,12 @@ fn build_epub_writes_file() {
             insert_toc_page: false,
             ..Default::default()
         },
+        ..Default::default()
     };
     let chapters = vec![ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "Hello".to_string(),
+        ..Default::default()
     }];
 
     let output = build_epub(&chapters, &options).expect("build epub");
@@ -343,18 +980,21 @@ fn build_epub_contains_assets() {
         bytes: Bytes::from_static(b"cover"),
         mime: "image/jpeg".to_string(),
         caption: None,
+        chapter: None,
     };
     let images = vec![ImageAsset {
         name: "gallery.png".to_string(),
         bytes: Bytes::from_static(b"img"),
         mime: "image/png".to_string(),
         caption: Some("Gallery".to_string()),
+        chapter: None,
     }];
     let font = FontAsset {
         name: "custom.ttf".to_string(),
         family: "CustomFont".to_string(),
         bytes: Bytes::from_static(b"font"),
         mime: "font/ttf".to_string(),
+        roles: vec![FontRole::Body],
     };
 
     let options = EpubBuildOptions {
@@ -364,15 +1004,17 @@ fn build_epub_contains_assets() {
         style: TextStyle::default(),
         cover: Some(cover),
         images,
-        font: Some(font),
+        fonts: vec![font],
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         include_images_section: true,
         toc_options: TocOptions::default(),
+        ..Default::default()
     };
     let chapters = vec![ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "Hello".to_string(),
+        ..Default::default()
     }];
 
     let output = build_epub(&chapters, &options).expect("build epub");
@@ -402,90 +1044,686 @@ fn build_epub_contains_assets() {
 }
 
 #[test]
-fn build_epub_contains_chapter_header_image() {
-    let dir = unique_temp_dir("reasypub-header");
-    let book = BookInfo {
-        title: "Header Book".to_string(),
-        ..Default::default()
-    };
-
-    let header = ImageAsset {
-        name: "chapter-header.png".to_string(),
-        bytes: Bytes::from_static(b"header"),
+fn build_epub_places_images_in_chapters_or_gallery() {
+    let dir = unique_temp_dir("reasypub-image-placement");
+    let image = |name: &str, chapter: Option<usize>| ImageAsset {
+        name: name.to_string(),
+        bytes: Bytes::from(name.to_string()),
         mime: "image/png".to_string(),
-        caption: None,
+        caption: Some(format!("说明 {name}")),
+        chapter,
     };
-
     let options = EpubBuildOptions {
-        book_info: book,
-        output_dir: dir.clone(),
-        filename_template: "header_output".to_string(),
-        style: TextStyle::default(),
-        cover: None,
-        images: Vec::new(),
-        font: None,
-        chapter_header_image: Some(header),
-        chapter_header_fullbleed: true,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: false,
+        book_info: BookInfo {
+            title: "插图位置".to_string(),
             ..Default::default()
         },
+        output_dir: dir.clone(),
+        filename_template: "placement".to_string(),
+        images: vec![
+            image("c.png", None),
+            image("map.png", Some(1)),
+            image("a.png", Some(9)),
+            image("b.png", Some(1)),
+        ],
+        include_images_section: true,
+        ..Default::default()
     };
-    let chapters = vec![ChapterDraft {
-        title: "Chapter 1".to_string(),
-        content: "Hello".to_string(),
-    }];
+    let chapters: Vec<ChapterDraft> = ["第一章", "第二章"]
+        .into_iter()
+        .map(|title| ChapterDraft {
+            title: title.to_string(),
+            content: "正文".to_string(),
+            ..Default::default()
+        })
+        .collect();
 
     let output = build_epub(&chapters, &options).expect("build epub");
-    let entries = zip_entries(Path::new(&output));
-    assert!(
-        entries
-            .iter()
-            .any(|name| name.ends_with("images/chapter-header.png"))
-    );
+    let output = Path::new(&output);
+    let first = zip_read_to_string(output, "chapter_0001.xhtml");
+    assert!(!first.contains("<figure>"));
+    let second = zip_read_to_string(output, "chapter_0002.xhtml");
+    let map = second.find("images/map.png").expect("inline map");
+    let b = second.find("images/b.png").expect("inline b");
+    assert!(second.find("正文").expect("text") < map && map < b);
+    assert!(second.contains("<figcaption>说明 map.png</figcaption>"));
+
+    // 没有指定章节或章节不存在的插图按列表顺序留在插图页。
+    let gallery = zip_read_to_string(output, "images.xhtml");
+    assert!(!gallery.contains("map.png") && !gallery.contains("b.png"));
+    assert!(gallery.find("images/c.png").expect("c") < gallery.find("images/a.png").expect("a"));
+    let entries = zip_entries(output);
+    for name in ["c.png", "map.png", "a.png", "b.png"] {
+        assert!(
+            entries
+                .iter()
+                .any(|entry| entry.ends_with(&format!("images/{name}")))
+        );
+    }
 
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn build_epub_contains_fantasy_assets() {
-    let dir = unique_temp_dir("reasypub-fantasy-assets");
-    let book = BookInfo {
-        title: "Fantasy Assets".to_string(),
-        ..Default::default()
-    };
-
-    let style = TextStyle {
-        css_template: crate::CssTemplate::Fantasy,
-        ..Default::default()
+fn build_epub_renames_fonts_with_the_same_file_name() {
+    let dir = unique_temp_dir("reasypub-font-names");
+    let font = |family: &str, role: Option<FontRole>| FontAsset {
+        name: "Regular.ttf".to_string(),
+        family: family.to_string(),
+        bytes: Bytes::from(family.to_string()),
+        mime: "font/ttf".to_string(),
+        roles: role.into_iter().collect(),
     };
-
     let options = EpubBuildOptions {
-        book_info: book,
+        book_info: BookInfo {
+            title: "同名字体".to_string(),
+            ..Default::default()
+        },
         output_dir: dir.clone(),
-        filename_template: "fantasy_assets".to_string(),
-        style,
-        cover: None,
-        images: Vec::new(),
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: false,
+        filename_template: "font_names".to_string(),
+        // 两款字体都叫 Regular.ttf，第三款与 Fantasy 自带的 kt.ttf 同名。
+        fonts: vec![
+            font("Regular", Some(FontRole::Body)),
+            font("Regular", Some(FontRole::Heading)),
+            FontAsset {
+                name: "kt.ttf".to_string(),
+                ..font("kt", None)
+            },
+        ],
+        style: TextStyle {
+            css_template: crate::CssTemplate::Fantasy,
             ..Default::default()
         },
+        ..Default::default()
     };
     let chapters = vec![ChapterDraft {
-        title: "第十二章 星落".to_string(),
-        content: "内容".to_string(),
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
     }];
 
     let output = build_epub(&chapters, &options).expect("build epub");
-    let entries = zip_entries(Path::new(&output));
-
+    let path = Path::new(&output);
+    let entries = zip_entries(path);
+    for name in [
+        "fonts/Regular.ttf",
+        "fonts/Regular-2.ttf",
+        "fonts/kt.ttf",
+        "fonts/kt-2.ttf",
+    ] {
+        assert_eq!(
+            entries.iter().filter(|entry| entry.ends_with(name)).count(),
+            1,
+            "{name}"
+        );
+    }
+    let css = zip_read_to_string(path, "stylesheet.css");
+    assert!(css.contains("font-family: \"Regular\"; src: url(\"fonts/Regular.ttf\")"));
+    assert!(css.contains("font-family: \"Regular-2\"; src: url(\"fonts/Regular-2.ttf\")"));
+    assert!(css.contains("font-family: \"kt-2\"; src: url(\"fonts/kt-2.ttf\")"));
+    assert!(css.contains("h1, h2, h3 { font-family: \"Regular-2\""));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_embeds_duplicate_images_once() {
+    let dir = unique_temp_dir("reasypub-duplicate-images");
+    let image = |name: &str, bytes: &'static [u8], chapter: Option<usize>| ImageAsset {
+        name: name.to_string(),
+        bytes: Bytes::from_static(bytes),
+        mime: "image/png".to_string(),
+        caption: None,
+        chapter,
+    };
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "重复插图".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "duplicates".to_string(),
+        images: vec![
+            image("map.png", b"same", None),
+            image("other.png", b"other", None),
+            image("map-copy.png", b"same", Some(0)),
+        ],
+        include_images_section: true,
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub_with_warnings(&chapters, &options).expect("build epub");
+    let path = Path::new(&output.output_path);
+    let entries = zip_entries(path);
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry.ends_with("images/map.png"))
+    );
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry.ends_with("images/other.png"))
+    );
+    assert!(!entries.iter().any(|entry| entry.contains("map-copy")));
+    let opf = zip_read_to_string(path, "content.opf");
+    assert_eq!(opf.matches("images/map.png").count(), 1);
+    assert!(zip_read_to_string(path, "chapter_0001.xhtml").contains("images/map.png"));
+    assert!(
+        output
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("map-copy.png") && warning.contains("map.png"))
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_subsets_embedded_font() {
+    use std::io::Read;
+
+    let dir = unique_temp_dir("reasypub-subset");
+    let font_bytes =
+        std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/stdg-regular.ttf"))
+            .expect("read bundled font");
+    let full_size = font_bytes.len();
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "字体".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "subset_output".to_string(),
+        style: TextStyle {
+            subset_font: true,
+            ..Default::default()
+        },
+        fonts: vec![FontAsset {
+            name: "body.ttf".to_string(),
+            family: "Body".to_string(),
+            bytes: Bytes::from(font_bytes),
+            mime: "font/ttf".to_string(),
+            roles: vec![FontRole::Body],
+        }],
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第一章 龙门".to_string(),
+        content: "客栈".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub_with_warnings(&chapters, &options).expect("build epub");
+    assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+    let file = File::open(&output.output_path).expect("open epub");
+    let mut archive = ZipArchive::new(file).expect("zip");
+    let mut subset = Vec::new();
+    archive
+        .by_name("OEBPS/fonts/body.ttf")
+        .expect("font entry")
+        .read_to_end(&mut subset)
+        .expect("read font");
+    assert!(subset.len() < full_size / 10);
+
+    let face = ttf_parser::Face::parse(&subset, 0).expect("parse subset");
+    for used in ['龙', '客', 'A'] {
+        let glyph = face.glyph_index(used).expect("mapped glyph");
+        assert!(face.glyph_bounding_box(glyph).is_some(), "{used} lost");
+    }
+    let unused = face.glyph_index('鼠').expect("mapped glyph");
+    assert!(face.glyph_bounding_box(unused).is_none());
+
+    let invalid = EpubBuildOptions {
+        fonts: vec![FontAsset {
+            name: "broken.ttf".to_string(),
+            family: "Broken".to_string(),
+            bytes: Bytes::from_static(b"font"),
+            mime: "font/ttf".to_string(),
+            roles: vec![FontRole::Body],
+        }],
+        ..options
+    };
+    let output = build_epub_with_warnings(&chapters, &invalid).expect("build epub");
+    assert!(
+        output
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("Font subsetting skipped"))
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_obfuscates_fonts_with_encryption_xml() {
+    use std::io::Read;
+
+    let dir = unique_temp_dir("reasypub-obfuscate");
+    let original: Vec<u8> = (0..2048u32).map(|i| (i % 251) as u8).collect();
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "obfuscated".to_string(),
+        style: TextStyle {
+            obfuscate_fonts: true,
+            ..Default::default()
+        },
+        fonts: vec![FontAsset {
+            name: "licensed.ttf".to_string(),
+            family: "Licensed".to_string(),
+            bytes: Bytes::from(original.clone()),
+            mime: "font/ttf".to_string(),
+            roles: vec![FontRole::Body],
+        }],
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let path = Path::new(&output);
+    let entries = zip_entries(path);
+    assert_eq!(entries[0], "mimetype");
+    let encryption = zip_read_to_string(path, "META-INF/encryption.xml");
+    assert!(encryption.contains("Algorithm=\"http://www.idpf.org/2008/embedding\""));
+    assert!(encryption.contains("URI=\"OEBPS/fonts/licensed.ttf\""));
+
+    let opf = zip_read_to_string(path, ".opf");
+    let identifier = regex::Regex::new(r#"<dc:identifier id="epub-id-1">([^<]+)</dc:identifier>"#)
+        .expect("regex")
+        .captures(&opf)
+        .expect("identifier")[1]
+        .to_string();
+    let mut archive = ZipArchive::new(File::open(path).expect("open epub")).expect("zip");
+    let mut stored = Vec::new();
+    archive
+        .by_name("OEBPS/fonts/licensed.ttf")
+        .expect("font entry")
+        .read_to_end(&mut stored)
+        .expect("read font");
+    assert_ne!(stored, original);
+    obfuscate_font(&mut stored, &obfuscation_key(&identifier));
+    assert_eq!(stored, original);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_optimizes_images() {
+    use std::io::Read;
+
+    let dir = unique_temp_dir("reasypub-optimize-images");
+    let photo = image::RgbImage::from_fn(1600, 1200, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, ((x * y) % 256) as u8])
+    });
+    let mut original = Vec::new();
+    image::DynamicImage::ImageRgb8(photo)
+        .write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(
+            &mut original,
+            100,
+        ))
+        .expect("encode jpeg");
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "optimized".to_string(),
+        images: vec![
+            ImageAsset {
+                name: "photo.jpg".to_string(),
+                bytes: Bytes::from(original.clone()),
+                mime: "image/jpeg".to_string(),
+                caption: None,
+                chapter: None,
+            },
+            ImageAsset {
+                name: "broken.png".to_string(),
+                bytes: Bytes::from_static(b"not a png"),
+                mime: "image/png".to_string(),
+                caption: None,
+                chapter: None,
+            },
+        ],
+        image_optimization: ImageOptimization {
+            enabled: true,
+            max_dimension: 800,
+            jpeg_quality: 70,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub_with_warnings(&chapters, &options).expect("build epub");
+    assert_eq!(output.image_sizes.len(), 2);
+    assert_eq!(output.image_sizes[0].before, original.len());
+    assert!(output.image_sizes[0].after < original.len());
+    assert_eq!(output.image_sizes[1].before, output.image_sizes[1].after);
+    assert!(
+        output
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("Image optimization skipped for broken.png"))
+    );
+
+    let mut archive =
+        ZipArchive::new(File::open(&output.output_path).expect("open epub")).expect("zip");
+    let mut stored = Vec::new();
+    archive
+        .by_name("OEBPS/images/photo.jpg")
+        .expect("image entry")
+        .read_to_end(&mut stored)
+        .expect("read image");
+    assert_eq!(stored.len(), output.image_sizes[0].after);
+    let resized = image::load_from_memory(&stored).expect("decode image");
+    assert_eq!((resized.width(), resized.height()), (800, 600));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_transcodes_images_for_eink_profile() {
+    use image::codecs::gif::GifEncoder;
+    use image::codecs::webp::WebPEncoder;
+
+    let dir = unique_temp_dir("reasypub-image-compat");
+    let pixels = image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 40, 40, 128]));
+    let mut webp = Vec::new();
+    image::DynamicImage::ImageRgba8(pixels.clone())
+        .write_with_encoder(WebPEncoder::new_lossless(&mut webp))
+        .expect("encode webp");
+    let gif = |frames: usize| {
+        let mut bytes = Vec::new();
+        GifEncoder::new(&mut bytes)
+            .encode_frames((0..frames).map(|_| image::Frame::new(pixels.clone())))
+            .expect("encode gif");
+        bytes
+    };
+    let asset = |name: &str, bytes: Vec<u8>, mime: &str| ImageAsset {
+        name: name.to_string(),
+        bytes: Bytes::from(bytes),
+        mime: mime.to_string(),
+        caption: None,
+        chapter: None,
+    };
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "compat".to_string(),
+        images: vec![
+            asset("art.png", b"existing".to_vec(), "image/png"),
+            asset("art.webp", webp, "image/webp"),
+            asset("moving.gif", gif(3), "image/gif"),
+            asset("still.gif", gif(1), "image/gif"),
+        ],
+        include_images_section: true,
+        image_optimization: ImageOptimization {
+            compatibility: ImageCompatibility::EInk,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let path = Path::new(&output);
+    let entries = zip_entries(path);
+    for name in ["art.png", "art-2.png", "moving.png", "still.gif"] {
+        assert!(entries.contains(&format!("OEBPS/images/{name}")), "{name}");
+    }
+    assert!(!entries.iter().any(|entry| entry.ends_with(".webp")));
+    assert!(!entries.iter().any(|entry| entry.ends_with("moving.gif")));
+
+    let gallery = zip_read_to_string(path, "images.xhtml");
+    assert!(gallery.contains("src=\"images/art-2.png\""));
+    assert!(gallery.contains("src=\"images/moving.png\""));
+    let opf = zip_read_to_string(path, ".opf");
+    assert!(!opf.contains("image/webp"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_converts_images_to_grayscale() {
+    use image::codecs::gif::GifEncoder;
+    use std::io::Read;
+
+    let dir = unique_temp_dir("reasypub-grayscale");
+    let color = image::RgbaImage::from_fn(16, 16, |x, y| {
+        image::Rgba([(x * 16) as u8, (y * 16) as u8, 90, 255])
+    });
+    let encode = |format: image::ImageFormat| {
+        let mut bytes = Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(color.clone())
+            .to_rgb8()
+            .write_to(&mut bytes, format)
+            .expect("encode image");
+        bytes.into_inner()
+    };
+    let mut gif = Vec::new();
+    GifEncoder::new(&mut gif)
+        .encode_frames([image::Frame::new(color.clone())])
+        .expect("encode gif");
+    let asset = |name: &str, bytes: Vec<u8>, mime: &str| ImageAsset {
+        name: name.to_string(),
+        bytes: Bytes::from(bytes),
+        mime: mime.to_string(),
+        caption: None,
+        chapter: None,
+    };
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "grayscale".to_string(),
+        cover: Some(asset(
+            "cover.png",
+            encode(image::ImageFormat::Png),
+            "image/png",
+        )),
+        images: vec![
+            asset("photo.jpg", encode(image::ImageFormat::Jpeg), "image/jpeg"),
+            asset("still.gif", gif, "image/gif"),
+        ],
+        image_optimization: ImageOptimization {
+            grayscale: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub_with_warnings(&chapters, &options).expect("build epub");
+    assert_eq!(output.image_sizes.len(), 3);
+    let mut archive =
+        ZipArchive::new(File::open(&output.output_path).expect("open epub")).expect("zip");
+    let mut decode = |name: &str| {
+        let mut stored = Vec::new();
+        archive
+            .by_name(name)
+            .expect(name)
+            .read_to_end(&mut stored)
+            .expect("read image");
+        image::load_from_memory(&stored).expect("decode image")
+    };
+    assert_eq!(decode("OEBPS/cover.png").color(), image::ColorType::L8);
+    assert_eq!(
+        decode("OEBPS/images/photo.jpg").color(),
+        image::ColorType::L8
+    );
+    // GIF 先转为 PNG，透明通道保留。
+    assert_eq!(
+        decode("OEBPS/images/still.png").color(),
+        image::ColorType::La8
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_embeds_or_rasterizes_svg_images() {
+    let dir = unique_temp_dir("reasypub-svg");
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="60" height="90"><rect width="60" height="90" fill="#336699"/></svg>"##;
+    let asset = |name: &str| ImageAsset {
+        name: name.to_string(),
+        bytes: Bytes::from(svg),
+        mime: "image/svg+xml".to_string(),
+        caption: None,
+        chapter: None,
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+    let mut options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "vector".to_string(),
+        epub_version: EpubVersion::Epub3,
+        cover: Some(asset("cover.svg")),
+        images: vec![asset("map.svg")],
+        ..Default::default()
+    };
+
+    let path = PathBuf::from(build_epub(&chapters, &options).expect("build epub"));
+    let opf = zip_read_to_string(&path, "content.opf");
+    assert!(opf.contains(
+        r#"<item media-type="image/svg+xml" properties="cover-image" id="cover-image" href="cover.svg"/>"#
+    ));
+    assert!(opf.contains(r#"href="images/map.svg""#));
+    assert!(opf.contains(r#" href="cover.xhtml" properties="svg"/>"#));
+    let cover = zip_read_to_string(&path, "cover.xhtml");
+    assert!(cover.contains(r#"viewBox="0 0 60 90""#));
+    assert!(cover.contains(r#"xlink:href="cover.svg""#));
+    assert!(cover.contains(r#"role="doc-cover""#));
+
+    options.filename_template = "raster".to_string();
+    options.image_optimization.rasterize_svg = true;
+    options.image_optimization.max_dimension = 300;
+    let path = PathBuf::from(build_epub(&chapters, &options).expect("build epub"));
+    let entries = zip_entries(&path);
+    assert!(!entries.iter().any(|entry| entry.ends_with(".svg")));
+    let opf = zip_read_to_string(&path, "content.opf");
+    assert!(!opf.contains(r#"properties="svg""#));
+    let mut archive = ZipArchive::new(File::open(&path).expect("open epub")).expect("zip");
+    let mut png = Vec::new();
+    std::io::Read::read_to_end(
+        &mut archive.by_name("OEBPS/cover.png").expect("cover png"),
+        &mut png,
+    )
+    .expect("read cover");
+    let cover = image::load_from_memory(&png).expect("decode cover");
+    assert_eq!((cover.width(), cover.height()), (200, 300));
+    assert!(entries.contains(&"OEBPS/images/map.png".to_string()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_contains_chapter_header_image() {
+    let dir = unique_temp_dir("reasypub-header");
+    let book = BookInfo {
+        title: "Header Book".to_string(),
+        ..Default::default()
+    };
+
+    let header = ImageAsset {
+        name: "chapter-header.png".to_string(),
+        bytes: Bytes::from_static(b"header"),
+        mime: "image/png".to_string(),
+        caption: None,
+        chapter: None,
+    };
+
+    let options = EpubBuildOptions {
+        book_info: book,
+        output_dir: dir.clone(),
+        filename_template: "header_output".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: Some(header),
+        chapter_header_fullbleed: true,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let entries = zip_entries(Path::new(&output));
+    assert!(
+        entries
+            .iter()
+            .any(|name| name.ends_with("images/chapter-header.png"))
+    );
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_contains_fantasy_assets() {
+    let dir = unique_temp_dir("reasypub-fantasy-assets");
+    let book = BookInfo {
+        title: "Fantasy Assets".to_string(),
+        ..Default::default()
+    };
+
+    let style = TextStyle {
+        css_template: crate::CssTemplate::Fantasy,
+        ..Default::default()
+    };
+
+    let options = EpubBuildOptions {
+        book_info: book,
+        output_dir: dir.clone(),
+        filename_template: "fantasy_assets".to_string(),
+        style,
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第十二章 星落".to_string(),
+        content: "内容".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let entries = zip_entries(Path::new(&output));
+
     for required in [
         "images/头图.webp",
         "images/头图1.webp",
@@ -519,251 +1757,1527 @@ fn build_epub_writes_metadata() {
     let dir = unique_temp_dir("reasypub-meta");
     let book = BookInfo {
         title: "Meta Title".to_string(),
-        author: "Meta Author".to_string(),
+        creators: vec![
+            Creator::new("Meta Author", CreatorRole::Author),
+            Creator::new("Meta Translator", CreatorRole::Translator),
+            Creator::new("Second Author", CreatorRole::Author),
+        ],
         language: "en".to_string(),
         publisher: "Meta Pub".to_string(),
         isbn: "ISBN-123".to_string(),
         category: "Category".to_string(),
         publish_date: "2025-01-01".to_string(),
         description: "A description.".to_string(),
+        series: "Saga & Co".to_string(),
+        series_index: "2".to_string(),
+        custom_metadata: vec![
+            MetadataEntry::new("dc:rights", "© Meta & Co"),
+            MetadataEntry::new("calibre:user_metadata:#genre", "\"玄幻\""),
+            MetadataEntry::new("dc:bad name", "skipped"),
+            MetadataEntry::new("empty", " "),
+        ],
+        ..Default::default()
+    };
+
+    let options = EpubBuildOptions {
+        book_info: book,
+        output_dir: dir.clone(),
+        filename_template: "meta_output".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+
+    assert!(opf.contains("Meta Title"));
+    assert!(opf.contains("<dc:creator opf:role=\"aut\">Meta Author</dc:creator>"));
+    assert!(opf.contains("<dc:creator opf:role=\"aut\">Second Author</dc:creator>"));
+    assert!(opf.contains("<dc:contributor opf:role=\"trl\">Meta Translator</dc:contributor>"));
+    assert!(opf.contains("Meta Pub"));
+    assert!(opf.contains("<dc:identifier opf:scheme=\"ISBN\">ISBN-123</dc:identifier>"));
+    assert!(!opf.contains("name=\"identifier\""));
+    assert!(opf.contains("Category"));
+    assert!(opf.contains("2025-01-01"));
+    assert!(opf.contains("A description."));
+    assert!(opf.contains("<meta name=\"calibre:series\" content=\"Saga &amp; Co\"/>"));
+    assert!(opf.contains("<meta name=\"calibre:series_index\" content=\"2\"/>"));
+    assert!(!opf.contains("belongs-to-collection"));
+    assert!(opf.contains("<dc:rights>© Meta &amp; Co</dc:rights>"));
+    assert!(
+        opf.contains("<meta name=\"calibre:user_metadata:#genre\" content=\"&quot;玄幻&quot;\"/>")
+    );
+    assert!(!opf.contains("skipped"));
+    assert!(!opf.contains("name=\"empty\""));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_writes_accessibility_metadata() {
+    let dir = unique_temp_dir("reasypub-a11y");
+    let mut options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "A11y".to_string(),
+            accessibility: Accessibility {
+                access_modes: "textual，visual".to_string(),
+                hazards: String::new(),
+                summary: "Text & images".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "a11y2".to_string(),
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub2");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains("<meta name=\"schema:accessMode\" content=\"textual\"/>"));
+    assert!(opf.contains("<meta name=\"schema:accessMode\" content=\"visual\"/>"));
+    assert!(
+        opf.contains("<meta name=\"schema:accessibilitySummary\" content=\"Text &amp; images\"/>")
+    );
+    assert!(!opf.contains("accessibilityHazard"));
+
+    options.filename_template = "a11y3".to_string();
+    options.epub_version = EpubVersion::Epub3;
+    let output = build_epub(&chapters, &options).expect("build epub3");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains("<meta property=\"schema:accessibilityFeature\">tableOfContents</meta>"));
+    assert!(
+        opf.contains("<meta property=\"schema:accessibilitySummary\">Text &amp; images</meta>")
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_uses_stable_identifier() {
+    let dir = unique_temp_dir("reasypub-identifier");
+    let mut options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "同一本书".to_string(),
+            isbn: "978-7-5366-9293-0".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "identifier".to_string(),
+        epub_version: EpubVersion::Epub3,
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
+    }];
+    let identifier = |options: &EpubBuildOptions| {
+        let output = build_epub_with_warnings(&chapters, options).expect("build epub");
+        let opf = zip_read_to_string(Path::new(&output.output_path), ".opf");
+        let id = regex::Regex::new(r#"<dc:identifier id="epub-id-1">([^<]+)</dc:identifier>"#)
+            .expect("regex")
+            .captures(&opf)
+            .expect("identifier")[1]
+            .to_string();
+        (id, opf, output.warnings)
+    };
+
+    let (first, opf, warnings) = identifier(&options);
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(
+        first,
+        uuid::fmt::Urn::from_uuid(options.book_info.derived_uuid()).to_string()
+    );
+    assert_eq!(identifier(&options).0, first);
+    assert!(opf.contains("unique-identifier=\"epub-id-1\""));
+    assert!(opf.contains("<dc:identifier id=\"isbn\">urn:isbn:9787536692930</dc:identifier>"));
+
+    options.book_info.identifier = "67E55044-10B1-426F-9247-BB680E5FE0C8".to_string();
+    assert_eq!(
+        identifier(&options).0,
+        "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8"
+    );
+
+    options.book_info.identifier = "not-a-uuid".to_string();
+    let (fallback, _, warnings) = identifier(&options);
+    assert_eq!(fallback, first);
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.contains("not-a-uuid"))
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_normalizes_or_detects_language() {
+    let dir = unique_temp_dir("reasypub-language");
+    let mut options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "Language".to_string(),
+            language: "zh_tw".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "language".to_string(),
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "It was a bright cold day in April.".to_string(),
+        ..Default::default()
+    }];
+    let build = |options: &EpubBuildOptions| {
+        let output = build_epub_with_warnings(&chapters, options).expect("build epub");
+        let opf = zip_read_to_string(Path::new(&output.output_path), ".opf");
+        (opf, output.warnings)
+    };
+
+    let (opf, warnings) = build(&options);
+    assert!(opf.contains("<dc:language>zh-TW</dc:language>"), "{opf}");
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    options.book_info.language = "English (US)".to_string();
+    let (opf, warnings) = build(&options);
+    assert!(opf.contains("<dc:language>en</dc:language>"), "{opf}");
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.contains("English (US)"))
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_skips_empty_metadata() {
+    let dir = unique_temp_dir("reasypub-meta-empty");
+    let options = EpubBuildOptions {
+        book_info: BookInfo::default(),
+        output_dir: dir.clone(),
+        filename_template: "meta_empty".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+
+    assert!(!opf.contains("name=\"publisher\""));
+    assert!(!opf.contains("name=\"identifier\""));
+    assert!(!opf.contains("name=\"date\""));
+    assert!(!opf.contains("calibre:series"));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub3_writes_series_collection() {
+    let dir = unique_temp_dir("reasypub-series");
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "卷二".to_string(),
+            creators: vec![Creator::new("画师", CreatorRole::Illustrator)],
+            series: "长河<全集>".to_string(),
+            series_index: "第二".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "series_output".to_string(),
+        epub_version: EpubVersion::Epub3,
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+
+    assert!(opf.contains("<meta name=\"calibre:series\" content=\"长河&lt;全集&gt;\"/>"));
+    assert!(opf.contains(
+        "<meta id=\"series\" property=\"belongs-to-collection\">长河&lt;全集&gt;</meta>"
+    ));
+    assert!(opf.contains("<meta refines=\"#series\" property=\"collection-type\">series</meta>"));
+    assert!(opf.contains("<dc:contributor id=\"contributor-1\">画师</dc:contributor>"));
+    assert!(opf.contains(
+        "<meta refines=\"#contributor-1\" property=\"role\" scheme=\"marc:relators\">ill</meta>"
+    ));
+    assert!(!opf.contains("<dc:creator"));
+    // 序号不是数字时不写入。
+    assert!(!opf.contains("series_index"));
+    assert!(!opf.contains("group-position"));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_inserts_front_matter_before_chapters() {
+    let dir = unique_temp_dir("reasypub-front-matter");
+    let mut options = EpubBuildOptions {
+        book_info: BookInfo {
+            title: "三体".to_string(),
+            creators: vec![Creator::new("刘慈欣", CreatorRole::Author)],
+            publisher: "重庆出版社".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "front_matter".to_string(),
+        front_matter: FrontMatter {
+            title_page: true,
+            copyright: crate::DEFAULT_COPYRIGHT_TEMPLATE.to_string(),
+            dedication: "献给 <读者>".to_string(),
+            preface: "第一段\n\n第二段".to_string(),
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let path = Path::new(&output);
+    let opf = zip_read_to_string(path, ".opf");
+    for reference in [
+        "type=\"title-page\"",
+        "type=\"copyright\"",
+        "type=\"dedication\"",
+        "type=\"preface\"",
+    ] {
+        assert!(opf.contains(reference), "{reference}");
+    }
+    let spine: Vec<usize> = [
+        "title_page.xhtml",
+        "copyright.xhtml",
+        "dedication.xhtml",
+        "toc.xhtml",
+        "preface.xhtml",
+        "chapter_0001.xhtml",
+    ]
+    .iter()
+    .map(|file| opf.find(&format!("href=\"{file}\"")).expect(file))
+    .collect();
+    assert!(spine.is_sorted(), "{opf}");
+    let ncx = zip_read_to_string(path, "toc.ncx");
+    for title in ["书名页", "版权信息", "献词", "前言"] {
+        assert!(ncx.contains(title), "{title}");
+    }
+
+    let title_page = zip_read_to_string(path, "title_page.xhtml");
+    assert!(title_page.contains("<h1 class=\"title-page-title\">三体</h1>"));
+    assert!(title_page.contains("<div class=\"title-page-publisher\">重庆出版社</div>"));
+    // 变量展开后为空的行（没有日期与 ISBN）不输出。
+    let copyright = zip_read_to_string(path, "copyright.xhtml");
+    assert!(
+        copyright
+            .contains("<p>三体</p>\n<p>刘慈欣</p>\n<p>重庆出版社</p>\n<p>版权所有 侵权必究</p>")
+    );
+    assert!(!copyright.contains("ISBN"));
+    let dedication = zip_read_to_string(path, "dedication.xhtml");
+    assert!(dedication.contains("<p>献给 &lt;读者&gt;</p>"));
+    let preface = zip_read_to_string(path, "preface.xhtml");
+    assert!(preface.contains(
+        "<h2>前言</h2>\n<div class=\"front-matter-text\">\n<p>第一段</p>\n<p>第二段</p>"
+    ));
+
+    // EPUB 3 在 body 与 landmarks 中标注语义。
+    options.epub_version = EpubVersion::Epub3;
+    options.front_matter.title_page = false;
+    options.front_matter.dedication.clear();
+    let output = build_epub(&chapters, &options).expect("build epub3");
+    let path = Path::new(&output);
+    let nav = zip_read_to_string(path, "nav.xhtml");
+    assert!(nav.contains("epub:type=\"copyright-page\""));
+    assert!(nav.contains("epub:type=\"preface\""));
+    assert!(!nav.contains("titlepage"));
+    let copyright = zip_read_to_string(path, "copyright.xhtml");
+    assert!(copyright.contains("<body class=\"copyright-page\" epub:type=\"copyright-page\">"));
+    assert!(zip_read_to_string_optional(path, "dedication.xhtml").is_none());
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_skips_images_section_when_disabled() {
+    let dir = unique_temp_dir("reasypub-no-gallery");
+    let options = EpubBuildOptions {
+        book_info: BookInfo::default(),
+        output_dir: dir.clone(),
+        filename_template: "no_gallery".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: vec![ImageAsset {
+            name: "gallery.png".to_string(),
+            bytes: Bytes::from_static(b"img"),
+            mime: "image/png".to_string(),
+            caption: None,
+            chapter: None,
+        }],
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let entries = zip_entries(Path::new(&output));
+
+    assert!(
+        entries
+            .iter()
+            .any(|name| name.ends_with("images/gallery.png"))
+    );
+    assert!(!entries.iter().any(|name| name.ends_with("images.xhtml")));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_uses_custom_toc_title_override() {
+    let dir = unique_temp_dir("reasypub-toc-title-custom");
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            language: "en".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "toc_title_custom".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: true,
+            toc_title_override: "Contents (Custom)".to_string(),
+            include_gallery_in_toc: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains("Contents (Custom)"));
+
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    assert!(nav.contains("Contents (Custom)"));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_uses_default_toc_title_when_override_empty() {
+    let dir = unique_temp_dir("reasypub-toc-title-default");
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            language: "en".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "toc_title_default".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: true,
+            toc_title_override: String::new(),
+            include_gallery_in_toc: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains("Table Of Contents"));
+
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    assert!(nav.contains("Table Of Contents"));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_can_exclude_gallery_from_toc() {
+    let dir = unique_temp_dir("reasypub-gallery-toc-off");
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            language: "en".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "gallery_toc_off".to_string(),
+        style: TextStyle::default(),
+        cover: None,
+        images: vec![ImageAsset {
+            name: "gallery.png".to_string(),
+            bytes: Bytes::from_static(b"img"),
+            mime: "image/png".to_string(),
+            caption: Some("Gallery".to_string()),
+            chapter: None,
+        }],
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: true,
+        toc_options: TocOptions {
+            insert_toc_page: true,
+            toc_title_override: String::new(),
+            include_gallery_in_toc: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let entries = zip_entries(Path::new(&output));
+    assert!(entries.iter().any(|name| name.ends_with("images.xhtml")));
+
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    assert!(!nav.contains("images.xhtml"));
+
+    let ncx = zip_read_to_string_optional(Path::new(&output), "toc.ncx").unwrap_or_default();
+    assert!(!ncx.contains("images.xhtml"));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn render_chapter_page_markers() {
+    let chapter = ChapterDraft {
+        title: "第一章".to_string(),
+        content: "[page=5]\n\n首段[page=6]续文\n[page=7]\n次行\n\n[page=6]重复\n\n[page=8]"
+            .to_string(),
+        ..Default::default()
+    };
+    let style = TextStyle::default();
+    let options = ChapterRenderOptions {
+        language: "zh-CN",
+        style: &style,
+        template: crate::CssTemplate::Classic,
+        header_image: None,
+        header_fullbleed: false,
+        epub_version: EpubVersion::Epub3,
+        markdown: false,
+        images: &[],
+    };
+    let html = render_chapter(&chapter, 1, &options);
+    let anchor = |page: &str| {
+        format!(
+            "<span epub:type=\"pagebreak\" role=\"doc-pagebreak\" id=\"page-{page}\" aria-label=\"{page}\"></span>"
+        )
+    };
+    // 章首的标记并入首段，首段仍不缩进；只含标记的行不产生换行。
+    assert!(html.contains(&format!(
+        "chapter-paragraph-first\" style=\"text-indent: 0.00em;\">{}首段{}续文<br/>{}次行</p>",
+        anchor("5"),
+        anchor("6"),
+        anchor("7")
+    )));
+    assert!(html.contains(">重复</p>"));
+    assert!(html.contains(&format!("<div class=\"page-marker\">{}</div>", anchor("8"))));
+    assert!(!html.contains("[page="));
+    assert_eq!(page_breaks(&html), ["5", "6", "7", "8"]);
+
+    let epub2 = render_chapter(
+        &chapter,
+        1,
+        &ChapterRenderOptions {
+            epub_version: EpubVersion::Epub2,
+            ..options
+        },
+    );
+    assert!(epub2.contains("<span id=\"page-5\"></span>首段"));
+    assert!(page_breaks(&epub2).is_empty());
+    assert_eq!(strip_inline_markup("首段[page=6]续文", false), "首段续文");
+}
+
+#[test]
+fn build_epub_writes_page_list() {
+    let dir = unique_temp_dir("reasypub-page-list");
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "page_list".to_string(),
+        epub_version: EpubVersion::Epub3,
+        ..Default::default()
+    };
+    let chapters = vec![
+        ChapterDraft {
+            title: "第一章".to_string(),
+            content: "[page=1]\n正文".to_string(),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "第二章".to_string(),
+            content: "正文[page=ii]".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    let page_list = &nav[nav.find("epub:type=\"page-list\"").expect("page-list")..];
+    assert!(page_list.contains("<li><a href=\"chapter_0001.xhtml#page-1\">1</a></li>"));
+    assert!(page_list.contains("<li><a href=\"chapter_0002.xhtml#page-ii\">ii</a></li>"));
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains("<meta property=\"schema:accessibilityFeature\">pageNavigation</meta>"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_writes_styled_toc_page() {
+    let dir = unique_temp_dir("reasypub-toc-page");
+    let mut options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "toc_page3".to_string(),
+        epub_version: EpubVersion::Epub3,
+        toc_options: TocOptions {
+            insert_toc_page: true,
+            volume_headings: true,
+            show_chapter_numbers: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![
+        ChapterDraft {
+            title: "第一章 启程".to_string(),
+            content: "正文".to_string(),
+            volume: Some("上卷".to_string()),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "第二章 归来".to_string(),
+            content: "正文".to_string(),
+            volume: Some("上卷".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    let output = build_epub(&chapters, &options).expect("build epub3");
+    let toc = zip_read_to_string(Path::new(&output), "toc.xhtml");
+    assert!(toc.contains("<body class=\"toc-page\">"));
+    assert!(toc.contains("<nav epub:type=\"toc\" role=\"doc-toc\" id=\"toc\">"));
+    assert!(toc.contains(
+        "<li class=\"toc-volume\"><h2 class=\"toc-volume-heading\"><a href=\"volume_01.xhtml\"><span class=\"toc-text\">上卷</span></a></h2>"
+    ));
+    assert!(toc.contains(
+        "<li class=\"toc-entry\"><a href=\"chapter_0001.xhtml\"><span class=\"toc-text\">启程</span></a></li>"
+    ));
+    assert!(!toc.contains("toc-number"));
+
+    options.filename_template = "toc_page2".to_string();
+    options.epub_version = EpubVersion::Epub2;
+    options.toc_options.volume_headings = false;
+    options.toc_options.show_chapter_numbers = true;
+    let output = build_epub(&chapters, &options).expect("build epub2");
+    let toc = zip_read_to_string(Path::new(&output), "toc.xhtml");
+    assert!(toc.contains("<div class=\"toc\" id=\"toc\">"));
+    assert!(!toc.contains("epub:type"));
+    assert!(
+        toc.contains(
+            "<span class=\"toc-number\">第一章</span><span class=\"toc-text\">启程</span>"
+        )
+    );
+    assert!(!toc.contains("toc-volume-heading"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_follows_spine_order() {
+    let dir = unique_temp_dir("reasypub-spine-order");
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "spine_order".to_string(),
+        front_matter: FrontMatter {
+            dedication: "献给读者".to_string(),
+            preface: "前言".to_string(),
+            ..Default::default()
+        },
+        back_matter: BackMatter {
+            kind: BackMatterKind::Afterword,
+            text: "后记".to_string(),
+            ..Default::default()
+        },
+        spine_order: vec![
+            SpineSection::BackMatter,
+            SpineSection::Chapters,
+            SpineSection::TocPage,
+        ],
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    // 前言紧接在正文之前；未列出的封面与前置页按默认顺序排在最后。
+    let positions: Vec<usize> = [
+        "afterword.xhtml",
+        "preface.xhtml",
+        "chapter_0001.xhtml",
+        "toc.xhtml",
+        "cover.xhtml",
+        "dedication.xhtml",
+    ]
+    .iter()
+    .map(|file| opf.find(&format!("href=\"{file}\"")).expect(file))
+    .collect();
+    assert!(positions.is_sorted(), "{opf}");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_applies_smart_typography() {
+    let dir = unique_temp_dir("reasypub-smart-typography");
+    let mut options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "smart_typography".to_string(),
+        style: TextStyle {
+            smart_typography: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    options.book_info.language = "en".to_string();
+    let chapters = vec![ChapterDraft {
+        title: "The \"Last\" Stand".to_string(),
+        content: "\"Wait--it's late...\" she said.\n她说：\"好\"".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let chapter = zip_read_to_string(Path::new(&output), "chapter_0001.xhtml");
+    assert!(chapter.contains("“Wait—it’s late…” she said."), "{chapter}");
+    // 含中文的行不做替换。
+    assert!(chapter.contains("她说：&quot;好&quot;"), "{chapter}");
+    let ncx = zip_read_to_string(Path::new(&output), "toc.ncx");
+    assert!(ncx.contains("The “Last” Stand"), "{ncx}");
+
+    options.style.smart_typography = false;
+    let preview = preview_chapter(&chapters[0], 0, &options).expect("preview");
+    assert!(!preview.xhtml.contains('“'));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_writes_landmarks_and_guide() {
+    let dir = unique_temp_dir("reasypub-landmarks");
+    let mut options = EpubBuildOptions {
+        book_info: BookInfo {
+            language: "en".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "landmarks2".to_string(),
+        images: vec![ImageAsset {
+            name: "gallery.png".to_string(),
+            bytes: Bytes::from_static(b"img"),
+            mime: "image/png".to_string(),
+            caption: None,
+            chapter: None,
+        }],
+        include_images_section: true,
+        toc_options: TocOptions {
+            insert_toc_page: true,
+            include_gallery_in_toc: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters: Vec<ChapterDraft> = ["Chapter 1", "Chapter 2"]
+        .into_iter()
+        .map(|title| ChapterDraft {
+            title: title.to_string(),
+            content: "Hello".to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    // 正文只有第一章作为 guide 的 `text`，插图页为 `loi`。
+    let output = build_epub(&chapters, &options).expect("build epub2");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains("<reference type=\"cover\" title=\"\" href=\"cover.xhtml\"/>"));
+    assert!(opf.contains("type=\"toc\""));
+    assert_eq!(opf.matches("type=\"text\"").count(), 1);
+    assert!(opf.contains("type=\"text\" title=\"Chapter 1\" href=\"chapter_0001.xhtml\""));
+    assert!(opf.contains("type=\"loi\" title=\"\" href=\"images.xhtml\""));
+
+    options.filename_template = "landmarks3".to_string();
+    options.epub_version = EpubVersion::Epub3;
+    let output = build_epub(&chapters, &options).expect("build epub3");
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    let landmarks = &nav[nav.find("epub:type=\"landmarks\"").expect("landmarks")..];
+    let expected = [
+        "<a epub:type=\"cover\" href=\"cover.xhtml\">Cover</a>",
+        "<a epub:type=\"toc\" href=\"toc.xhtml\">Table Of Contents</a>",
+        "<a epub:type=\"bodymatter\" href=\"chapter_0001.xhtml\">Start</a>",
+        "<a epub:type=\"loi\" href=\"images.xhtml\">Illustrations</a>",
+    ];
+    let positions: Vec<usize> = expected
+        .iter()
+        .map(|entry| landmarks.find(entry).expect(entry))
+        .collect();
+    assert!(positions.is_sorted(), "{landmarks}");
+    assert_eq!(landmarks.matches("bodymatter").count(), 1);
+
+    // 没有内嵌目录页时，目录指向导航文档本身。
+    options.filename_template = "landmarks3_nav".to_string();
+    options.toc_options.insert_toc_page = false;
+    let output = build_epub(&chapters, &options).expect("build epub3 without toc page");
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    assert!(nav.contains("<a epub:type=\"toc\" href=\"nav.xhtml#toc\">"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_strip_heading_number_keeps_full_title_in_toc() {
+    let dir = unique_temp_dir("reasypub-strip-heading");
+    let options = EpubBuildOptions {
+        book_info: BookInfo {
+            language: "zh-CN".to_string(),
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "strip_heading".to_string(),
+        style: TextStyle {
+            strip_heading_number: true,
+            ..Default::default()
+        },
+        cover: None,
+        images: Vec::new(),
+        fonts: Vec::new(),
+        chapter_header_image: None,
+        chapter_header_fullbleed: false,
+        include_images_section: false,
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let chapters = vec![ChapterDraft {
+        title: "第5章 归途".to_string(),
+        content: "他终于踏上了回家的路。".to_string(),
+        ..Default::default()
+    }];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let chapter = zip_read_to_string(Path::new(&output), "chapter_0001.xhtml");
+    assert!(chapter.contains("<h2>归途</h2>"));
+    assert!(!chapter.contains("第5章"));
+    assert!(!chapter.contains("chapter-label"));
+
+    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
+    assert!(nav.contains("第5章 归途"));
+    let ncx = zip_read_to_string(Path::new(&output), "toc.ncx");
+    assert!(ncx.contains("第5章 归途"));
+
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_generator_meta_can_be_toggled() {
+    let dir = unique_temp_dir("reasypub-generator-meta");
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+    let expected = format!(
+        "<meta name=\"generator\" content=\"reasypub {}\"",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let enabled = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "generator_on".to_string(),
+        ..Default::default()
+    };
+    let output = build_epub(&chapters, &enabled).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(opf.contains(&expected));
+
+    let disabled = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "generator_off".to_string(),
+        include_generator_meta: false,
+        ..Default::default()
+    };
+    let output = build_epub(&chapters, &disabled).expect("build epub");
+    let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(!opf.contains("reasypub"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_scopes_chapter_css_to_single_chapter() {
+    let dir = unique_temp_dir("reasypub-chapter-css");
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "chapter_css".to_string(),
+        ..Default::default()
+    };
+    let chapters = vec![
+        ChapterDraft {
+            title: "Chapter 1".to_string(),
+            content: "Plain".to_string(),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "Chapter 2".to_string(),
+            content: "Dear friend,".to_string(),
+            custom_css: "p { font-family: cursive; }".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let first = zip_read_to_string(Path::new(&output), "chapter_0001.xhtml");
+    let second = zip_read_to_string(Path::new(&output), "chapter_0002.xhtml");
+    assert!(second.contains("<style type=\"text/css\">"));
+    assert!(second.contains("body.chapter-css-0002 p { font-family: cursive; }"));
+    assert!(second.contains("<body class=\"chapter chapter-css-0002\">"));
+    assert!(!first.contains("<style"));
+    assert!(!first.contains("chapter-css-"));
+    assert!(!first.contains("cursive"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn render_chapter_honors_variant_and_class_overrides() {
+    let style = TextStyle::default();
+    let header = ImageAsset {
+        name: "chapter-header.png".to_string(),
+        bytes: Bytes::from_static(b"img"),
+        mime: "image/png".to_string(),
+        caption: None,
+        chapter: None,
+    };
+    let options = ChapterRenderOptions {
+        language: "en",
+        style: &style,
+        template: crate::CssTemplate::Classic,
+        header_image: Some(&header),
+        header_fullbleed: false,
+        epub_version: EpubVersion::Epub2,
+        markdown: false,
+        images: &[],
+    };
+    let prologue = ChapterDraft {
+        title: "Chapter 1 Prologue".to_string(),
+        content: "Once".to_string(),
+        css_class: "prologue".to_string(),
+        header_image: ChapterHeaderImage::Hidden,
+        variant: crate::ChapterVariant::Plain,
+        ..Default::default()
+    };
+    let html = render_chapter(&prologue, 1, &options);
+    assert!(html.contains("<body class=\"chapter chapter-plain prologue\">"));
+    assert!(html.contains("<div class=\"chapter-header chapter-header-plain\">"));
+    assert!(html.contains("<h2>Chapter 1 Prologue</h2>"));
+    assert!(!html.contains("chapter-label"));
+    assert!(!html.contains("chapter-ornament"));
+    assert!(!html.contains("chapter-head-image"));
+
+    let centered = ChapterDraft {
+        title: "Chapter 2 Verse".to_string(),
+        content: "Lines".to_string(),
+        variant: crate::ChapterVariant::Centered,
+        ..Default::default()
     };
+    let html = render_chapter(&centered, 2, &options);
+    assert!(html.contains("<body class=\"chapter chapter-centered\">"));
+    assert!(html.contains("images/chapter-header.png"));
+    assert!(html.contains("chapter-label"));
+}
 
+#[test]
+fn build_epub_embeds_per_chapter_header_image() {
+    let dir = unique_temp_dir("reasypub-chapter-header-override");
     let options = EpubBuildOptions {
-        book_info: book,
         output_dir: dir.clone(),
-        filename_template: "meta_output".to_string(),
-        style: TextStyle::default(),
-        cover: None,
-        images: Vec::new(),
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: false,
+        filename_template: "chapter_header_override".to_string(),
+        ..Default::default()
+    };
+    let chapters = vec![
+        ChapterDraft {
+            title: "Chapter 1".to_string(),
+            content: "Plain".to_string(),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "Chapter 2".to_string(),
+            content: "Special".to_string(),
+            header_image: ChapterHeaderImage::Custom(ImageAsset {
+                name: "extra.PNG".to_string(),
+                bytes: Bytes::from_static(b"img"),
+                mime: "image/png".to_string(),
+                caption: None,
+                chapter: None,
+            }),
             ..Default::default()
         },
+    ];
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let entries = zip_entries(Path::new(&output));
+    assert!(entries.contains(&"OEBPS/images/chapter-header-0002.png".to_string()));
+    let first = zip_read_to_string(Path::new(&output), "chapter_0001.xhtml");
+    let second = zip_read_to_string(Path::new(&output), "chapter_0002.xhtml");
+    assert!(!first.contains("chapter-head-image"));
+    assert!(second.contains("<img src=\"images/chapter-header-0002.png\" alt=\"\"/>"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn render_chapter_vertical_punctuation_and_digits() {
+    let chapter = ChapterDraft {
+        title: "第1章 “开端”".to_string(),
+        content: "他说：“第12回，共365页。”\n\n‘好’".to_string(),
+        ..Default::default()
+    };
+    let style = TextStyle {
+        vertical: true,
+        ..Default::default()
     };
+    let options = ChapterRenderOptions {
+        language: "zh-CN",
+        style: &style,
+        template: crate::CssTemplate::Classic,
+        header_image: None,
+        header_fullbleed: false,
+        epub_version: EpubVersion::Epub2,
+        markdown: false,
+        images: &[],
+    };
+    let html = render_chapter(&chapter, 1, &options);
+    assert!(html.contains("「开端」"));
+    assert!(html.contains("他说：「第<span class=\"tcy\">12</span>回，共365页。」"));
+    assert!(html.contains("『好』"));
+    assert!(!html.contains("“"));
+}
+
+#[test]
+fn build_epub_vertical_sets_rtl_page_progression() {
+    let dir = unique_temp_dir("reasypub-vertical");
     let chapters = vec![ChapterDraft {
-        title: "Chapter 1".to_string(),
-        content: "Hello".to_string(),
+        title: "第一章".to_string(),
+        content: "正文".to_string(),
+        ..Default::default()
     }];
+    for epub_version in [EpubVersion::Epub2, EpubVersion::Epub3] {
+        let options = EpubBuildOptions {
+            output_dir: dir.clone(),
+            filename_template: format!("vertical_{:?}", epub_version),
+            style: TextStyle {
+                vertical: true,
+                ..Default::default()
+            },
+            epub_version,
+            ..Default::default()
+        };
+        let output = build_epub(&chapters, &options).expect("build epub");
+        let path = Path::new(&output);
+        assert_eq!(zip_entries(path)[0], "mimetype");
+        let opf = zip_read_to_string(path, ".opf");
+        assert!(opf.contains("page-progression-direction=\"rtl\""));
+        assert!(opf.contains("<meta name=\"primary-writing-mode\" content=\"vertical-rl\"/>"));
+        let css = zip_read_to_string(path, "stylesheet.css");
+        assert!(css.contains("writing-mode: vertical-rl;"));
+    }
 
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "horizontal".to_string(),
+        ..Default::default()
+    };
     let output = build_epub(&chapters, &options).expect("build epub");
     let opf = zip_read_to_string(Path::new(&output), ".opf");
+    assert!(!opf.contains("page-progression-direction"));
 
-    assert!(opf.contains("Meta Title"));
-    assert!(opf.contains("Meta Author"));
-    assert!(opf.contains("Meta Pub"));
-    assert!(opf.contains("ISBN-123"));
-    assert!(opf.contains("Category"));
-    assert!(opf.contains("2025-01-01"));
-    assert!(opf.contains("A description."));
-
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn build_epub_skips_empty_metadata() {
-    let dir = unique_temp_dir("reasypub-meta-empty");
-    let options = EpubBuildOptions {
-        book_info: BookInfo::default(),
-        output_dir: dir.clone(),
-        filename_template: "meta_empty".to_string(),
-        style: TextStyle::default(),
-        cover: None,
-        images: Vec::new(),
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: false,
-            ..Default::default()
-        },
-    };
+fn build_epub_rtl_page_progression_without_vertical_text() {
+    let dir = unique_temp_dir("reasypub-rtl");
     let chapters = vec![ChapterDraft {
         title: "Chapter 1".to_string(),
-        content: "Hello".to_string(),
+        content: "Panel".to_string(),
+        ..Default::default()
     }];
-
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "rtl".to_string(),
+        rtl_page_progression: true,
+        ..Default::default()
+    };
     let output = build_epub(&chapters, &options).expect("build epub");
-    let opf = zip_read_to_string(Path::new(&output), ".opf");
-
-    assert!(!opf.contains("name=\"publisher\""));
-    assert!(!opf.contains("name=\"identifier\""));
-    assert!(!opf.contains("name=\"date\""));
+    let path = Path::new(&output);
+    let opf = zip_read_to_string(path, ".opf");
+    assert!(opf.contains("<spine toc=\"ncx\" page-progression-direction=\"rtl\">"));
+    assert!(!opf.contains("primary-writing-mode"));
+    let css = zip_read_to_string(path, "stylesheet.css");
+    assert!(!css.contains("vertical-rl"));
 
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn build_epub_skips_images_section_when_disabled() {
-    let dir = unique_temp_dir("reasypub-no-gallery");
+fn build_epub_reproducible_output_is_byte_identical() {
+    let chapters = vec![ChapterDraft {
+        title: "第一章".to_string(),
+        content: "正文[page=1]".to_string(),
+        ..Default::default()
+    }];
     let options = EpubBuildOptions {
-        book_info: BookInfo::default(),
-        output_dir: dir.clone(),
-        filename_template: "no_gallery".to_string(),
-        style: TextStyle::default(),
-        cover: None,
+        epub_version: EpubVersion::Epub3,
+        reproducible: true,
+        ..Default::default()
+    };
+
+    let first = build_epub_bytes(&chapters, &options).expect("first build");
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let second = build_epub_bytes(&chapters, &options).expect("second build");
+    assert!(first == second, "reproducible builds differ");
+
+    let mut archive = ZipArchive::new(Cursor::new(&first)).expect("zip");
+    let mut names: Vec<String> = (0..archive.len())
+        .map(|idx| {
+            let entry = archive.by_index_raw(idx).expect("entry");
+            assert_eq!(entry.last_modified(), Some(zip::DateTime::default()));
+            entry.name().to_string()
+        })
+        .collect();
+    assert_eq!(names.remove(0), "mimetype");
+    assert!(names.is_sorted(), "{names:?}");
+    let opf = {
+        use std::io::Read;
+        let mut opf = String::new();
+        archive
+            .by_name("OEBPS/content.opf")
+            .expect("opf")
+            .read_to_string(&mut opf)
+            .expect("read opf");
+        opf
+    };
+    assert!(opf.contains("<meta property=\"dcterms:modified\">1980-01-01T00:00:00Z</meta>"));
+}
+
+#[test]
+fn build_epub_applies_zip_compression_settings() {
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+    let mut options = EpubBuildOptions {
         images: vec![ImageAsset {
             name: "gallery.png".to_string(),
             bytes: Bytes::from_static(b"img"),
             mime: "image/png".to_string(),
             caption: None,
+            chapter: None,
         }],
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: false,
+        compression: ZipCompression {
+            level: 9,
+            store_images: true,
+        },
+        ..Default::default()
+    };
+    let methods = |bytes: &[u8]| -> Vec<(String, zip::CompressionMethod)> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).expect("zip");
+        (0..archive.len())
+            .map(|idx| {
+                let entry = archive.by_index_raw(idx).expect("entry");
+                (entry.name().to_string(), entry.compression())
+            })
+            .collect()
+    };
+
+    let bytes = build_epub_bytes(&chapters, &options).expect("store images");
+    for (name, method) in methods(&bytes) {
+        let stored = name == "mimetype" || name.ends_with(".png");
+        assert_eq!(
+            method == zip::CompressionMethod::Stored,
+            stored,
+            "{name}: {method:?}"
+        );
+    }
+
+    options.compression.level = 0;
+    let bytes = build_epub_bytes(&chapters, &options).expect("store all");
+    assert!(
+        methods(&bytes)
+            .iter()
+            .all(|(_, method)| *method == zip::CompressionMethod::Stored)
+    );
+}
+
+#[test]
+fn build_epub_splits_oversized_chapters() {
+    let dir = unique_temp_dir("reasypub-split");
+    let mut content = String::from("第一段[^1]\n\n");
+    for index in 2..=120 {
+        content.push_str(&format!("这是第{index}段正文，用来撑大章节文件。\n\n"));
+    }
+    content.push_str("[^1]: 注释");
+    let chapters = vec![
+        ChapterDraft {
+            title: "第一章".to_string(),
+            content,
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "第二章".to_string(),
+            content: "短章".to_string(),
             ..Default::default()
         },
+    ];
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "split".to_string(),
+        epub_version: EpubVersion::Epub3,
+        split_chapter_bytes: Some(4096),
+        ..Default::default()
     };
+
+    let output = build_epub(&chapters, &options).expect("build epub");
+    let path = Path::new(&output);
+    let parts: Vec<String> = zip_entries(path)
+        .into_iter()
+        .filter(|name| name.starts_with("OEBPS/chapter_0001_"))
+        .collect();
+    assert!(parts.len() > 2, "{parts:?}");
+    assert!(!zip_entries(path).contains(&"OEBPS/chapter_0001.xhtml".to_string()));
+    assert!(zip_entries(path).contains(&"OEBPS/chapter_0002.xhtml".to_string()));
+    let last = parts
+        .last()
+        .expect("last part")
+        .trim_start_matches("OEBPS/");
+
+    let first = zip_read_to_string(path, "chapter_0001_a.xhtml");
+    assert!(first.contains("<section epub:type=\"chapter\" role=\"doc-chapter\">"));
+    assert!(first.contains(&format!("href=\"{last}#fn-1\"")));
+    let tail = zip_read_to_string(path, last);
+    assert!(tail.contains("href=\"chapter_0001_a.xhtml#fnref-1\""));
+    assert!(tail.ends_with("</section>\n</body>\n</html>"));
+
+    // 目录只有一项指向第一份，spine 按顺序包含每一份。
+    let ncx = zip_read_to_string(path, "toc.ncx");
+    assert!(ncx.contains("chapter_0001_a.xhtml"));
+    assert!(!ncx.contains("chapter_0001_b.xhtml"));
+    let opf = zip_read_to_string(path, ".opf");
+    let spine: Vec<usize> = parts
+        .iter()
+        .chain(["OEBPS/chapter_0002.xhtml".to_string()].iter())
+        .map(|part| {
+            let href = format!("href=\"{}\"", part.trim_start_matches("OEBPS/"));
+            opf.find(&href).expect("manifest item")
+        })
+        .collect();
+    assert!(spine.is_sorted(), "{opf}");
+    let report = crate::validation::validate_epub(path).expect("validate");
+    assert!(report.is_empty(), "{report:?}");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_epub_bytes_matches_file_output_without_writing() {
+    let dir = unique_temp_dir("reasypub-bytes");
     let chapters = vec![ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "Hello".to_string(),
+        ..Default::default()
     }];
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "bytes".to_string(),
+        rtl_page_progression: true,
+        ..Default::default()
+    };
 
-    let output = build_epub(&chapters, &options).expect("build epub");
-    let entries = zip_entries(Path::new(&output));
+    let bytes = build_epub_bytes(&chapters, &options).expect("build bytes");
+    assert!(!dir.exists());
+    let mut archive = ZipArchive::new(Cursor::new(&bytes)).expect("zip");
+    assert_eq!(archive.by_index(0).expect("entry").name(), "mimetype");
+    let mut opf = String::new();
+    use std::io::Read;
+    archive
+        .by_name("OEBPS/content.opf")
+        .expect("opf")
+        .read_to_string(&mut opf)
+        .expect("read opf");
+    assert!(opf.contains("page-progression-direction=\"rtl\""));
+
+    let mut written = Vec::new();
+    let output = build_epub_to_writer(&chapters, &options, &mut written).expect("build writer");
+    assert_eq!(output.output_path, "bytes.epub");
+    assert!(!written.is_empty());
+    assert!(!dir.exists());
+
+    let path = build_epub(&chapters, &options).expect("build file");
+    assert_eq!(zip_entries(Path::new(&path)).len(), archive.len());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
 
+#[test]
+fn kepubify_wraps_sentences_and_images() {
+    let html = "<html><head><title>T</title></head><body class=\"chapter\">\
+<h1>One</h1><p>Hi there. \u{201c}Go!\u{201d} Done</p><p><img src=\"a.png\" alt=\"a\"/></p>\
+<svg><text>skip</text></svg></body></html>";
+    let out = kepubify(html);
     assert!(
-        entries
-            .iter()
-            .any(|name| name.ends_with("images/gallery.png"))
+        out.contains("<body class=\"chapter\"><div id=\"book-columns\"><div id=\"book-inner\">")
+    );
+    assert!(out.contains("<h1><span class=\"koboSpan\" id=\"kobo.1.1\">One</span></h1>"));
+    assert!(out.contains(
+        "<p><span class=\"koboSpan\" id=\"kobo.2.1\">Hi there.</span> \
+<span class=\"koboSpan\" id=\"kobo.2.2\">\u{201c}Go!\u{201d}</span> \
+<span class=\"koboSpan\" id=\"kobo.2.3\">Done</span></p>"
+    ));
+    assert!(out.contains(
+        "<p><span class=\"koboSpan\" id=\"kobo.3.1\"><img src=\"a.png\" alt=\"a\"/></span></p>"
+    ));
+    assert!(out.contains("<text>skip</text>"));
+    assert!(out.ends_with("</div></div></body></html>"));
+}
+
+#[test]
+fn build_epub_kepub_output() {
+    let dir = unique_temp_dir("reasypub-kepub");
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "第一句。第二句！".to_string(),
+        ..Default::default()
+    }];
+    let options = EpubBuildOptions {
+        output_dir: dir.clone(),
+        filename_template: "kobo.epub".to_string(),
+        kepub: true,
+        ..Default::default()
+    };
+    let output = build_epub(&chapters, &options).expect("build epub");
+    assert!(output.ends_with("kobo.kepub.epub"));
+    let path = Path::new(&output);
+    let chapter = zip_read_to_string(path, "chapter_0001.xhtml");
+    assert!(chapter.contains("<div id=\"book-columns\"><div id=\"book-inner\">"));
+    assert!(chapter.contains("\">第一句。</span><span class=\"koboSpan\""));
+    assert!(
+        crate::validation::validate_epub(path)
+            .expect("validate")
+            .is_empty()
     );
-    assert!(!entries.iter().any(|name| name.ends_with("images.xhtml")));
 
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn build_epub_uses_custom_toc_title_override() {
-    let dir = unique_temp_dir("reasypub-toc-title-custom");
+fn build_epub_defaults_to_epub2_markup() {
+    let dir = unique_temp_dir("reasypub-epub2");
     let options = EpubBuildOptions {
-        book_info: BookInfo {
-            language: "en".to_string(),
-            ..Default::default()
-        },
         output_dir: dir.clone(),
-        filename_template: "toc_title_custom".to_string(),
-        style: TextStyle::default(),
-        cover: None,
-        images: Vec::new(),
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: true,
-            toc_title_override: "Contents (Custom)".to_string(),
-            include_gallery_in_toc: true,
-        },
+        filename_template: "epub2".to_string(),
+        ..Default::default()
     };
     let chapters = vec![ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "Hello".to_string(),
+        ..Default::default()
     }];
 
     let output = build_epub(&chapters, &options).expect("build epub");
     let opf = zip_read_to_string(Path::new(&output), ".opf");
-    assert!(opf.contains("Contents (Custom)"));
-
-    let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
-    assert!(nav.contains("Contents (Custom)"));
+    assert!(opf.contains("version=\"2.0\""));
+    let chapter = zip_read_to_string(Path::new(&output), "chapter_0001.xhtml");
+    assert!(chapter.contains("XHTML 1.1"));
+    assert!(!chapter.contains("epub:type"));
 
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn build_epub_uses_default_toc_title_when_override_empty() {
-    let dir = unique_temp_dir("reasypub-toc-title-default");
+fn build_epub_epub3_uses_nav_and_semantic_markup() {
+    let dir = unique_temp_dir("reasypub-epub3");
     let options = EpubBuildOptions {
-        book_info: BookInfo {
-            language: "en".to_string(),
-            ..Default::default()
-        },
         output_dir: dir.clone(),
-        filename_template: "toc_title_default".to_string(),
-        style: TextStyle::default(),
-        cover: None,
-        images: Vec::new(),
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: false,
-        toc_options: TocOptions {
-            insert_toc_page: true,
-            toc_title_override: String::new(),
-            include_gallery_in_toc: true,
-        },
+        filename_template: "epub3".to_string(),
+        epub_version: EpubVersion::Epub3,
+        ..Default::default()
     };
     let chapters = vec![ChapterDraft {
         title: "Chapter 1".to_string(),
         content: "Hello".to_string(),
+        ..Default::default()
     }];
 
     let output = build_epub(&chapters, &options).expect("build epub");
     let opf = zip_read_to_string(Path::new(&output), ".opf");
-    assert!(opf.contains("Table Of Contents"));
+    assert!(opf.contains("version=\"3.0\""));
+    assert!(opf.contains("properties=\"nav\""));
+    assert!(opf.contains("dcterms:modified"));
 
     let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
-    assert!(nav.contains("Table Of Contents"));
+    assert!(nav.contains("epub:type = \"toc\""));
+    assert!(nav.contains("role=\"doc-toc\""));
+
+    let chapter = zip_read_to_string(Path::new(&output), "chapter_0001.xhtml");
+    assert!(chapter.contains("<!DOCTYPE html>"));
+    assert!(chapter.contains("xmlns:epub=\"http://www.idpf.org/2007/ops\""));
+    assert!(chapter.contains("epub:type=\"bodymatter\""));
+    assert!(chapter.contains("<section epub:type=\"chapter\" role=\"doc-chapter\">"));
+    assert!(chapter.contains("</section>\n</body>"));
+    assert!(!chapter.contains("XHTML 1.1"));
+
+    let cover = zip_read_to_string(Path::new(&output), "cover.xhtml");
+    assert!(cover.contains("epub:type=\"cover\""));
 
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn build_epub_can_exclude_gallery_from_toc() {
-    let dir = unique_temp_dir("reasypub-gallery-toc-off");
+fn build_epub_nests_chapters_under_volumes() {
+    let dir = unique_temp_dir("reasypub-volumes");
     let options = EpubBuildOptions {
-        book_info: BookInfo {
-            language: "en".to_string(),
-            ..Default::default()
-        },
         output_dir: dir.clone(),
-        filename_template: "gallery_toc_off".to_string(),
-        style: TextStyle::default(),
-        cover: None,
-        images: vec![ImageAsset {
-            name: "gallery.png".to_string(),
-            bytes: Bytes::from_static(b"img"),
-            mime: "image/png".to_string(),
-            caption: Some("Gallery".to_string()),
-        }],
-        font: None,
-        chapter_header_image: None,
-        chapter_header_fullbleed: false,
-        include_images_section: true,
-        toc_options: TocOptions {
-            insert_toc_page: true,
-            toc_title_override: String::new(),
-            include_gallery_in_toc: false,
-        },
+        filename_template: "volumes".to_string(),
+        ..Default::default()
     };
-    let chapters = vec![ChapterDraft {
-        title: "Chapter 1".to_string(),
-        content: "Hello".to_string(),
-    }];
+    let mut chapters: Vec<ChapterDraft> = [
+        "第一卷 风起\n",
+        "第1章 出发\n内容",
+        "第2章 远行\n内容",
+        "第二卷 云涌\n",
+        "第3章 归来\n内容",
+    ]
+    .iter()
+    .map(|raw| ChapterDraft::from_raw(raw))
+    .collect();
+    crate::assign_volumes(&mut chapters);
+    chapters.push(ChapterDraft {
+        title: "第4章 番外".to_string(),
+        content: "内容".to_string(),
+        volume: Some("外传".to_string()),
+        ..Default::default()
+    });
 
     let output = build_epub(&chapters, &options).expect("build epub");
-    let entries = zip_entries(Path::new(&output));
-    assert!(entries.iter().any(|name| name.ends_with("images.xhtml")));
+    let ncx = zip_read_to_string(Path::new(&output), "toc.ncx");
+    let volume_one = ncx.find("第一卷 风起").expect("volume one");
+    let chapter_one = ncx.find("第1章 出发").expect("chapter one");
+    let volume_two = ncx.find("第二卷 云涌").expect("volume two");
+    assert!(volume_one < chapter_one && chapter_one < volume_two);
+    // 第1章 嵌套在第一卷的 navPoint 内，因此卷的 navPoint 闭合位置在第2章之后。
+    let first_close = ncx[volume_one..].find("</navPoint>").unwrap() + volume_one;
+    assert!(first_close > chapter_one);
 
     let nav = zip_read_to_string(Path::new(&output), "nav.xhtml");
-    assert!(!nav.contains("images.xhtml"));
+    let nested = nav
+        .find("第一卷 风起")
+        .and_then(|idx| nav[idx..].find("<ol>"));
+    assert!(nested.is_some());
 
-    let ncx = zip_read_to_string_optional(Path::new(&output), "toc.ncx").unwrap_or_default();
-    assert!(!ncx.contains("images.xhtml"));
+    // 没有对应卷标题章节的卷会补一页卷首页。
+    let volume_page = zip_read_to_string(Path::new(&output), "volume_01.xhtml");
+    assert!(volume_page.contains("<h1 class=\"volume-title\">外传</h1>"));
+    assert!(ncx.contains("外传"));
 
-    let _ = std::fs::remove_file(&output);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
@@ -777,7 +3291,7 @@ fn build_epub_rejects_empty_chapters() {
         style: TextStyle::default(),
         cover: None,
         images: Vec::new(),
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         include_images_section: false,
@@ -785,6 +3299,7 @@ fn build_epub_rejects_empty_chapters() {
             insert_toc_page: false,
             ..Default::default()
         },
+        ..Default::default()
     };
     let err = build_epub(&[], &options).expect_err("error");
     match err {
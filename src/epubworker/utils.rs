@@ -1,40 +1,150 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::BookInfo;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use crate::{BookInfo, CssTemplate, OverwritePolicy};
 
 use super::BuildError;
 
-pub(super) fn generate_filename(book_info: &BookInfo, template: &str) -> String {
-    let mut filename = template.to_string();
+// `{日期:%Y%m%d}`：按 strftime 风格的格式输出出版日期。
+static FORMATTED_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{日期:([^{}]*)\}").expect("valid date variable regex"));
+static VARIABLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{[^{}]*\}").expect("valid template variable regex"));
+
+/// 按文件名模板生成输出文件名。
+///
+/// 支持 `{书名}`、`{作者}`、`{日期}`、`{日期:格式}`、`{章节数}`、`{isbn}`、`{出版社}`
+/// 与 `{模板}`；结果去掉文件名中的非法字符并补上 `.epub` 扩展名。
+pub(crate) fn generate_filename(
+    book_info: &BookInfo,
+    template: &str,
+    chapter_count: usize,
+    css_template: CssTemplate,
+) -> String {
+    let expand = |template| {
+        sanitize_filename_component(&expand_filename_variables(
+            book_info,
+            template,
+            chapter_count,
+            css_template,
+        ))
+    };
+    let mut filename = expand(template);
+
+    if !filename.ends_with(".epub") {
+        filename.push_str(".epub");
+    }
+
+    if filename == ".epub" {
+        filename = format!("{}.epub", expand("{书名}_{作者}"));
+    }
+
+    filename
+}
+
+/// 展开文件名模板中的变量，不做字符清理；设备目录模板也用它展开。
+pub(crate) fn expand_filename_variables(
+    book_info: &BookInfo,
+    template: &str,
+    chapter_count: usize,
+    css_template: CssTemplate,
+) -> String {
+    let publish_date = book_info.publish_date.trim();
+    let mut filename = FORMATTED_DATE
+        .replace_all(template, |caps: &Captures<'_>| {
+            format_date(publish_date, &caps[1])
+        })
+        .into_owned();
     let title = if book_info.title.trim().is_empty() {
         "Untitled"
     } else {
         book_info.title.trim()
     };
-    let author = if book_info.author.trim().is_empty() {
+    let author = book_info.author();
+    let author = if author.is_empty() {
         "Unknown"
     } else {
-        book_info.author.trim()
+        author.as_str()
     };
 
     filename = filename.replace("{书名}", title);
     filename = filename.replace("{作者}", author);
-    filename = filename.replace("{日期}", book_info.publish_date.trim());
-
-    filename = sanitize_filename_component(&filename);
+    filename = filename.replace("{日期}", publish_date);
+    filename = filename.replace("{章节数}", &chapter_count.to_string());
+    filename = filename.replace("{isbn}", book_info.isbn.trim());
+    filename = filename.replace("{出版社}", book_info.publisher.trim());
+    filename = filename.replace("{模板}", &css_template.to_string());
+    filename
+}
 
-    if !filename.ends_with(".epub") {
-        filename.push_str(".epub");
-    }
+/// 逐行展开前置页文字中的变量，去掉空行。
+///
+/// 含变量、但变量全部展开为空的行（如没有 ISBN 时的 `ISBN {isbn}`）整行丢弃。
+pub(crate) fn expand_page_template(
+    book_info: &BookInfo,
+    template: &str,
+    chapter_count: usize,
+    css_template: CssTemplate,
+) -> Vec<String> {
+    template
+        .lines()
+        .filter_map(|line| {
+            let expanded = expand_filename_variables(book_info, line, chapter_count, css_template);
+            let expanded = expanded.trim();
+            let unfilled =
+                VARIABLE.is_match(line) && expanded == VARIABLE.replace_all(line, "").trim();
+            (!expanded.is_empty() && !unfilled).then(|| expanded.to_string())
+        })
+        .collect()
+}
 
-    if filename == ".epub" {
-        filename = format!("{}_{}.epub", title, author);
+/// 按 `%Y`、`%y`、`%m`、`%d`、`%%` 格式化出版日期。
+///
+/// 日期依次取年、月、日三组数字（如 `2024-03-05`、`2024年3月5日`），缺少的部分输出为空；
+/// 无法识别为日期时原样返回。
+fn format_date(date: &str, format: &str) -> String {
+    let groups: Vec<&str> = date
+        .split(|ch: char| !ch.is_ascii_digit())
+        .filter(|group| !group.is_empty())
+        .collect();
+    let number = |index: usize| {
+        groups
+            .get(index)
+            .and_then(|group| group.parse::<u32>().ok())
+    };
+    let Some(year) = number(0).filter(|_| groups[0].len() == 4) else {
+        return date.to_string();
+    };
+    let month = number(1).filter(|month| (1..=12).contains(month));
+    let day = number(2).filter(|day| (1..=31).contains(day));
+    let two_digits =
+        |value: Option<u32>| value.map(|value| format!("{value:02}")).unwrap_or_default();
+    let mut output = String::new();
+    let mut chars = format.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            output.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => output.push_str(&year.to_string()),
+            Some('y') => output.push_str(&format!("{:02}", year % 100)),
+            Some('m') => output.push_str(&two_digits(month)),
+            Some('d') => output.push_str(&two_digits(day)),
+            Some('%') => output.push('%'),
+            Some(other) => {
+                output.push('%');
+                output.push(other);
+            }
+            None => output.push('%'),
+        }
     }
-
-    filename
+    output
 }
 
-fn sanitize_filename_component(input: &str) -> String {
+pub(crate) fn sanitize_filename_component(input: &str) -> String {
     let invalid_chars = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
     let mut cleaned = input.to_string();
     for &c in &invalid_chars {
@@ -43,7 +153,46 @@ fn sanitize_filename_component(input: &str) -> String {
     cleaned.trim().to_string()
 }
 
-pub(super) fn normalize_output_dir(path: &PathBuf) -> Result<PathBuf, BuildError> {
+/// 按覆盖策略确定输出路径。
+///
+/// 目标文件不存在时直接使用；已存在时覆盖、在扩展名前追加 ` (1)`、` (2)` …，
+/// 或返回 `BuildError::OutputExists`。`.kepub.epub` 视为一个整体扩展名。
+pub(crate) fn resolve_output_path(
+    dir: &Path,
+    filename: &str,
+    policy: OverwritePolicy,
+) -> Result<PathBuf, BuildError> {
+    let path = dir.join(filename);
+    if !path.exists() {
+        return Ok(path);
+    }
+    match policy {
+        OverwritePolicy::Overwrite => Ok(path),
+        OverwritePolicy::Ask => Err(BuildError::OutputExists(path)),
+        OverwritePolicy::Number => {
+            let split = [".kepub.epub", ".epub", ".pdf"]
+                .into_iter()
+                .find_map(|ext| filename.strip_suffix(ext).map(|stem| (stem, ext)))
+                .or_else(|| {
+                    filename
+                        .rfind('.')
+                        .filter(|&dot| dot > 0)
+                        .map(|dot| filename.split_at(dot))
+                });
+            let (stem, ext) = split.unwrap_or((filename, ""));
+            let mut number = 1usize;
+            loop {
+                let candidate = dir.join(format!("{stem} ({number}){ext}"));
+                if !candidate.exists() {
+                    return Ok(candidate);
+                }
+                number += 1;
+            }
+        }
+    }
+}
+
+pub(crate) fn normalize_output_dir(path: &PathBuf) -> Result<PathBuf, BuildError> {
     if path.as_os_str().is_empty() || path == &PathBuf::from(".") {
         Ok(std::env::current_dir()?)
     } else {
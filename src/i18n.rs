@@ -1,20 +1,54 @@
+//! 界面文案：内置英文与简体中文，繁体中文由简体转换得到，日文缺少的文案回退到英文。
+//!
+//! 启动时还可从翻译目录载入 `<语言代码>.json` / `<语言代码>.toml`（如 `ja.toml`、`zh-TW.json`），
+//! 文件是以 [`Key`] 变体名为键的扁平映射，覆盖对应语言的内置文案，无需重新编译。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError, RwLock};
+
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+use crate::zhconv::ChineseConversion;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Locale {
     En,
     Zh,
+    ZhTw,
+    Ja,
 }
 
 impl Locale {
-    pub const ALL: [Locale; 2] = [Locale::En, Locale::Zh];
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::Zh, Locale::ZhTw, Locale::Ja];
 
     pub fn label(self) -> &'static str {
         match self {
             Locale::En => "English",
             Locale::Zh => "中文",
+            Locale::ZhTw => "繁體中文",
+            Locale::Ja => "日本語",
         }
     }
+
+    /// 翻译文件名使用的语言代码。
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Zh => "zh",
+            Locale::ZhTw => "zh-TW",
+            Locale::Ja => "ja",
+        }
+    }
+
+    /// 按语言代码查找界面语言，忽略大小写，`_` 与 `-` 等同。
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.replace('_', "-");
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(&code))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -36,6 +70,23 @@ pub enum Key {
     CoverPreview,
     CoverEmpty,
     ClearCover,
+    GenerateCover,
+    GenerateCoverHint,
+    CoverBackgroundLabel,
+    CoverBackgroundSolid,
+    CoverBackgroundGradient,
+    CoverBackgroundTexture,
+    CoverPrimaryColor,
+    CoverSecondaryColor,
+    CoverTextColor,
+    ChooseCoverTexture,
+    RefreshCoverPreview,
+    CoverFitEnable,
+    CoverFitRatio,
+    CoverFitCrop,
+    CoverFitPad,
+    CoverFitAnchor,
+    CoverFitPadColor,
     ExportSummary,
     TitleLabel,
     AuthorLabel,
@@ -55,10 +106,12 @@ pub enum Key {
     PanelCss,
     PanelImages,
     PanelMisc,
+    PanelBatch,
     SplitMethod,
     MethodRegex,
     MethodSimple,
     MethodConfig,
+    MethodMarkdown,
     ChooseConfigFile,
     ClearConfig,
     ValidateConfig,
@@ -76,6 +129,8 @@ pub enum Key {
     LineHeight,
     ParagraphSpacing,
     IndentEm,
+    StripHeadingNumber,
+    VerticalWriting,
     Template,
     StyleClassicName,
     StyleClassicDesc,
@@ -109,6 +164,10 @@ pub enum Key {
     ChooseFont,
     ClearFont,
     FontLabel,
+    FontRoleBody,
+    FontRoleHeading,
+    FontRoleChapterLabel,
+    FontRolesHint,
     AddImage,
     TotalImages,
     NoImages,
@@ -118,6 +177,12 @@ pub enum Key {
     BatchImport,
     ClearAll,
     Delete,
+    TextCleanup,
+    TextCleanupHint,
+    CleanupFind,
+    CleanupReplace,
+    CleanupRegex,
+    AddCleanupRule,
     LanguageField,
     Publisher,
     Isbn,
@@ -133,12 +198,65 @@ pub enum Key {
     TocTitle,
     TocTitleHint,
     IncludeGalleryInToc,
+    NestVolumes,
+    IncludeGeneratorMeta,
+    SafeMode,
+    RtlPageProgression,
+    KepubOutput,
+    OutputFormatLabel,
+    PdfPageSize,
+    PdfMargin,
+    PdfHint,
+    EpubVersionLabel,
+    EpubVersionHint,
+    SubsetFont,
+    SubsetFontHint,
+    ObfuscateFonts,
+    ObfuscateFontsHint,
+    ZhConvertLabel,
+    ZhConvertHint,
+    ZhConvertNone,
+    OptimizeImages,
+    OptimizeImagesHint,
+    ImageMaxDimension,
+    JpegQuality,
+    ImageOptimizationSummary,
+    ImageCompatLabel,
+    ImageCompatHint,
+    ImageCompatOriginal,
+    ImageCompatNoWebp,
+    ImageCompatEInk,
+    BatchHint,
+    BatchAddFiles,
+    BatchAddFolder,
+    BatchClear,
+    BatchStart,
+    BatchEmpty,
+    BatchProgress,
+    BatchPending,
+    BatchDone,
+    BatchFailed,
     TextEditor,
     Save,
     Close,
     Chars,
     ConversionResult,
+    ConversionWarnings,
+    ValidationPassed,
+    ValidationIssues,
+    RunEpubcheck,
+    EpubcheckPath,
+    EpubcheckHint,
+    EpubcheckSummary,
+    EpubcheckDetails,
     ConversionFailed,
+    ConversionCancelled,
+    ConversionRunning,
+    Cancel,
+    ProgressSplitting,
+    ProgressSplit,
+    ProgressRendering,
+    ProgressWriting,
     ConversionSuccess,
     OutputFile,
     OpenFolder,
@@ -160,12 +278,300 @@ pub enum Key {
     NoChapters,
     ChapterTitle,
     ChapterContent,
+    ChapterCss,
+    ChapterBodyClass,
+    ChapterVariantLabel,
+    ChapterVariantStandard,
+    ChapterVariantPlain,
+    ChapterVariantCentered,
+    ChapterHeaderOverride,
+    ChapterHeaderInherit,
+    ChapterHeaderHidden,
+    ChapterHeaderCustom,
     Up,
     Down,
     DesktopOnlyAction,
+    MenuFile,
+    OpenProject,
+    SaveProject,
+    SaveProjectAs,
+    ProjectFilter,
+    ProjectOpenFailed,
+    ProjectSaveFailed,
+    ProjectMissingFiles,
+    RecentFiles,
+    RecentTexts,
+    RecentOutputs,
+    NoRecentFiles,
+    ClearRecent,
+    DropFilesHint,
+    DropUnsupported,
+    ResetSettings,
+    ResetSettingsHint,
+    Undo,
+    Redo,
+    FindReplace,
+    FindHint,
+    ReplaceHint,
+    FindRegex,
+    FindMatchCase,
+    FindMatches,
+    ReplaceOne,
+    ReplaceAll,
+    ReplacedCount,
+    MergePrevious,
+    SplitAtCursor,
+    SplitNeedsCursor,
+    BulkRename,
+    RenameTemplate,
+    RenameStart,
+    RenameTemplateHint,
+    SelectAll,
+    SelectNone,
+    SelectedCount,
+    RenameSelectHint,
+    RenameApply,
+    DragToReorder,
+    MoveSelectedTo,
+    Move,
+    ChapterPreview,
+    ChapterPreviewTitle,
+    ChapterPreviewHint,
+    PreviousChapter,
+    NextChapter,
+    StyleSample,
+    StyleSampleTitle,
+    StyleSampleText,
+    DownloadFailed,
+    ReportChapters,
+    ReportLength,
+    ReportOutliers,
+    OutlierShort,
+    OutlierLong,
+    AddRegexPattern,
+    RegexPriorityHint,
+    TitleExclusions,
+    TitleExclusionsHint,
+    AddTitleExclusion,
+    MethodLength,
+    ChapterLengthChars,
+    MethodToc,
+    TocDetected,
+    TocNotFound,
+    MethodHeuristic,
+    ReviewInChapterEditor,
+    Series,
+    SeriesIndex,
+    Creators,
+    AddCreator,
+    RoleAuthor,
+    RoleTranslator,
+    RoleIllustrator,
+    RoleEditor,
+    AdvancedMetadata,
+    AdvancedMetadataHint,
+    AddMetadataEntry,
+    MetadataKey,
+    MetadataValue,
+    InvalidMetadataKey,
+    LookupMetadata,
+    LookupSource,
+    LookupSearch,
+    LookupSearching,
+    LookupNoResults,
+    LookupApply,
+    LookupDownloadCover,
+    LookupFailed,
+    CoverDownloadFailed,
+    Identifier,
+    IdentifierHint,
+    IdentifierInvalid,
+    DetectLanguage,
+    LanguageHint,
+    LanguageNormalized,
+    LanguageInvalid,
+    PunctuationNormalize,
+    PunctuationHint,
+    PunctuationOff,
+    PunctuationFullWidth,
+    PunctuationHalfWidth,
+    PreviewPunctuation,
+    PunctuationChanges,
+    PunctuationNoChanges,
+    ReflowParagraphs,
+    ReflowSensitivity,
+    ReflowHint,
+    FilenamePreview,
+    OverwriteLabel,
+    OverwriteAlways,
+    OverwriteNumber,
+    OverwriteAsk,
+    OutputExists,
+    OutputExistsPrompt,
+    OverwriteConfirm,
+    OverwriteRenumber,
+    PostExportActions,
+    OpenAfterExport,
+    AddToCalibreAfterExport,
+    CalibredbPath,
+    CalibreLibrary,
+    CalibreHint,
+    SendToCalibre,
+    CalibreAdding,
+    CalibreAdded,
+    CalibreFailed,
+    SendToKindle,
+    SendToKindleAfterExport,
+    KindleSettingsButton,
+    KindleSettingsTitle,
+    SmtpServer,
+    SmtpSecurityLabel,
+    SmtpPort,
+    SmtpUsername,
+    SmtpPassword,
+    KindleFrom,
+    KindleTo,
+    KindleHint,
+    KindleIncomplete,
+    KindleSending,
+    KindleSent,
+    KindleFailed,
+    CopyToDevice,
+    RefreshDevices,
+    NoDevices,
+    DeviceCopied,
+    DeviceCopyFailed,
+    DeviceFolders,
+    DeviceFoldersHint,
+    EpubImported,
+    FrontMatter,
+    FrontMatterHint,
+    TitlePage,
+    CopyrightPage,
+    DedicationPage,
+    PrefacePage,
+    UseCopyrightTemplate,
+    BackMatter,
+    BackMatterHint,
+    Afterword,
+    Colophon,
+    BackMatterTitle,
+    BackMatterFromText,
+    GrayscaleImages,
+    GrayscaleImagesHint,
+    RasterizeSvg,
+    RasterizeSvgHint,
+    Theme,
+    ThemeSepia,
+    ThemeMidnight,
+    AccentColor,
+    PanelFill,
+    CornerRadius,
+    ResetTheme,
+    Help,
+    HelpWorkflow,
+    KeyboardShortcuts,
+    CustomizeShortcuts,
+    ShortcutsHint,
+    ShortcutRecording,
+    ShortcutConflict,
+    ResetShortcuts,
+    Accessibility,
+    AccessibilityHint,
+    AccessModes,
+    AccessibilityFeatures,
+    AccessibilityHazards,
+    AccessibilitySummary,
+    TocColumns,
+    TocDottedLeaders,
+    TocVolumeHeadings,
+    TocShowChapterNumbers,
+    ReproducibleBuild,
+    ReproducibleBuildHint,
+    CompressionLevel,
+    StoreImagesUncompressed,
+    SplitLargeChapters,
+    SplitLargeChaptersHint,
+    SpineOrder,
+    SpineOrderHint,
+    SpineCover,
+    SpineFrontMatter,
+    SpineTocPage,
+    SpineChapters,
+    SpineGallery,
+    SpineBackMatter,
+    ResetSpineOrder,
+    SmartTypography,
+    SmartTypographyHint,
+    CleanWhitespace,
+    CleanWhitespaceHint,
+    WhitespaceRemoved,
+    WhitespaceNothing,
+    WhitespaceBom,
+    WhitespaceZeroWidth,
+    WhitespaceSoftHyphens,
+    WhitespaceTrailing,
+    WhitespaceBlankLines,
+    ReportWhitespace,
+    CountWhitespace,
+    TitleNumeralsLabel,
+    TitleNumeralsKeep,
+    TitleNumeralsArabic,
+    TitleNumeralsChinese,
+    TitleNumeralsHint,
+    HeadingTemplate,
+    HeadingTemplateHint,
+    DescriptionFromText,
+    DescriptionLength,
+    TextHeaderFound,
+    FillFromTextHeader,
+    StripTextHeader,
+    StripTextHeaderHint,
+    MergeTextFiles,
+    MergeFilesTitle,
+    MergeFilesHint,
+    MergeFilesEmpty,
+    MergeAndLoad,
+    MergedFiles,
+    MergeFileColumn,
+    MergeVolumeColumn,
+    MergeEncodingColumn,
+    EncodingAuto,
+    ExtractEpubText,
+    ExtractedText,
+    ExtractTextFailed,
+    ReadingPreview,
+    ReadingPreviewHint,
+    PreviousPage,
+    NextPage,
+    PageNumber,
+    ReadingTime,
+    LongestChapter,
+    ChapterLength,
+    ExportChapterList,
+    ImportChapterList,
+    ChapterListHint,
+    ChapterListFilter,
+    ChapterListExportFailed,
+    ChapterListImportFailed,
+    ChapterFind,
+    ChapterFindResults,
+    ReplaceSelected,
+    InsertIntoChapter,
+    InsertIntoGallery,
+    ImageChapterMissing,
+    DuplicateImage,
+    MergeDuplicate,
+    MergeDuplicateHint,
+    InlineMarkup,
+    InlineMarkupHint,
+    ProgressDecoding,
 }
 
 pub fn t(locale: Locale, key: Key) -> &'static str {
+    if let Some(text) = runtime_translation(locale, key) {
+        return text;
+    }
     match (locale, key) {
         (Locale::En, Key::Subtitle) => "TXT -> EPUB",
         (Locale::Zh, Key::Subtitle) => "TXT -> EPUB",
@@ -183,8 +589,8 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::EditTxt) => "编辑 TXT",
         (Locale::En, Key::OpenTextFile) => "📂 Open text file",
         (Locale::Zh, Key::OpenTextFile) => "📂 打开文本文件",
-        (Locale::En, Key::TextFileFilter) => "Text files",
-        (Locale::Zh, Key::TextFileFilter) => "文本文件",
+        (Locale::En, Key::TextFileFilter) => "Text or EPUB files",
+        (Locale::Zh, Key::TextFileFilter) => "文本或 EPUB 文件",
         (Locale::En, Key::InputTxtPlaceholder) => "TXT to EPUB, choose file",
         (Locale::Zh, Key::InputTxtPlaceholder) => "TXT 转 EPUB，选择文件",
         (Locale::En, Key::ChapterEditor) => "Chapter Editor",
@@ -201,6 +607,44 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::CoverEmpty) => "未选择封面",
         (Locale::En, Key::ClearCover) => "Clear cover",
         (Locale::Zh, Key::ClearCover) => "清除封面",
+        (Locale::En, Key::GenerateCover) => "Generate cover",
+        (Locale::Zh, Key::GenerateCover) => "生成封面",
+        (Locale::En, Key::GenerateCoverHint) => {
+            "Typesets title, author and publisher into a PNG cover at conversion time. Uses the heading or body font when one is embedded."
+        }
+        (Locale::Zh, Key::GenerateCoverHint) => {
+            "转换时把书名、作者与出版社排版为 PNG 封面；已嵌入标题或正文字体时使用该字体。"
+        }
+        (Locale::En, Key::CoverBackgroundLabel) => "Background:",
+        (Locale::Zh, Key::CoverBackgroundLabel) => "背景:",
+        (Locale::En, Key::CoverBackgroundSolid) => "Solid color",
+        (Locale::Zh, Key::CoverBackgroundSolid) => "纯色",
+        (Locale::En, Key::CoverBackgroundGradient) => "Gradient",
+        (Locale::Zh, Key::CoverBackgroundGradient) => "渐变",
+        (Locale::En, Key::CoverBackgroundTexture) => "Texture image",
+        (Locale::Zh, Key::CoverBackgroundTexture) => "纹理图片",
+        (Locale::En, Key::CoverPrimaryColor) => "Main color",
+        (Locale::Zh, Key::CoverPrimaryColor) => "主色",
+        (Locale::En, Key::CoverSecondaryColor) => "Gradient end",
+        (Locale::Zh, Key::CoverSecondaryColor) => "渐变终点",
+        (Locale::En, Key::CoverTextColor) => "Text color",
+        (Locale::Zh, Key::CoverTextColor) => "文字颜色",
+        (Locale::En, Key::ChooseCoverTexture) => "Choose texture",
+        (Locale::Zh, Key::ChooseCoverTexture) => "选择纹理",
+        (Locale::En, Key::RefreshCoverPreview) => "Refresh preview",
+        (Locale::Zh, Key::RefreshCoverPreview) => "刷新预览",
+        (Locale::En, Key::CoverFitEnable) => "Adjust aspect ratio",
+        (Locale::Zh, Key::CoverFitEnable) => "调整封面比例",
+        (Locale::En, Key::CoverFitRatio) => "Ratio:",
+        (Locale::Zh, Key::CoverFitRatio) => "比例:",
+        (Locale::En, Key::CoverFitCrop) => "Crop",
+        (Locale::Zh, Key::CoverFitCrop) => "裁切",
+        (Locale::En, Key::CoverFitPad) => "Pad",
+        (Locale::Zh, Key::CoverFitPad) => "补边",
+        (Locale::En, Key::CoverFitAnchor) => "Crop position:",
+        (Locale::Zh, Key::CoverFitAnchor) => "裁切位置:",
+        (Locale::En, Key::CoverFitPadColor) => "Pad color",
+        (Locale::Zh, Key::CoverFitPadColor) => "补边颜色",
         (Locale::En, Key::ExportSummary) => "Export Summary",
         (Locale::Zh, Key::ExportSummary) => "导出摘要",
         (Locale::En, Key::TitleLabel) => "Title",
@@ -239,6 +683,8 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::PanelImages) => "插图",
         (Locale::En, Key::PanelMisc) => "Misc",
         (Locale::Zh, Key::PanelMisc) => "杂项",
+        (Locale::En, Key::PanelBatch) => "Batch",
+        (Locale::Zh, Key::PanelBatch) => "批量转换",
         (Locale::En, Key::SplitMethod) => "Split method:",
         (Locale::Zh, Key::SplitMethod) => "分章方式:",
         (Locale::En, Key::MethodRegex) => "Regex",
@@ -247,6 +693,8 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::MethodSimple) => "简易规则",
         (Locale::En, Key::MethodConfig) => "From File",
         (Locale::Zh, Key::MethodConfig) => "从文件加载",
+        (Locale::En, Key::MethodMarkdown) => "Markdown Headings",
+        (Locale::Zh, Key::MethodMarkdown) => "Markdown 标题",
         (Locale::En, Key::ChooseConfigFile) => "Choose config file",
         (Locale::Zh, Key::ChooseConfigFile) => "选择配置文件",
         (Locale::En, Key::ClearConfig) => "Clear config",
@@ -281,6 +729,12 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::ParagraphSpacing) => "段间距:",
         (Locale::En, Key::IndentEm) => "Indent (em):",
         (Locale::Zh, Key::IndentEm) => "首行缩进 (em):",
+        (Locale::En, Key::StripHeadingNumber) => {
+            "Hide chapter numbers in body headings (TOC keeps full titles)"
+        }
+        (Locale::Zh, Key::StripHeadingNumber) => "正文标题隐藏章节编号（目录保留完整标题）",
+        (Locale::En, Key::VerticalWriting) => "Vertical writing (right-to-left pages)",
+        (Locale::Zh, Key::VerticalWriting) => "竖排（从右向左翻页）",
         (Locale::En, Key::Template) => "Template:",
         (Locale::Zh, Key::Template) => "排版风格:",
         (Locale::En, Key::StyleClassicName) => "Classic Serif",
@@ -347,12 +801,24 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::FontSize) => "字体大小 (px):",
         (Locale::En, Key::FontColor) => "Font color:",
         (Locale::Zh, Key::FontColor) => "字体颜色:",
-        (Locale::En, Key::ChooseFont) => "Choose font file",
-        (Locale::Zh, Key::ChooseFont) => "选择字体文件",
-        (Locale::En, Key::ClearFont) => "Clear font",
-        (Locale::Zh, Key::ClearFont) => "清除字体",
+        (Locale::En, Key::ChooseFont) => "Add font file",
+        (Locale::Zh, Key::ChooseFont) => "添加字体文件",
+        (Locale::En, Key::ClearFont) => "Clear fonts",
+        (Locale::Zh, Key::ClearFont) => "清除全部字体",
         (Locale::En, Key::FontLabel) => "Font: {}",
         (Locale::Zh, Key::FontLabel) => "字体: {}",
+        (Locale::En, Key::FontRoleBody) => "Body",
+        (Locale::Zh, Key::FontRoleBody) => "正文",
+        (Locale::En, Key::FontRoleHeading) => "Headings",
+        (Locale::Zh, Key::FontRoleHeading) => "标题",
+        (Locale::En, Key::FontRoleChapterLabel) => "Chapter label",
+        (Locale::Zh, Key::FontRoleChapterLabel) => "章节序号",
+        (Locale::En, Key::FontRolesHint) => {
+            "Each role uses one font. Fonts without a role are still embedded for use in custom CSS."
+        }
+        (Locale::Zh, Key::FontRolesHint) => {
+            "每个角色只使用一款字体；未分配角色的字体仍会嵌入，可在自定义 CSS 中引用。"
+        }
         (Locale::En, Key::AddImage) => "Add image",
         (Locale::Zh, Key::AddImage) => "添加图片",
         (Locale::En, Key::TotalImages) => "Total: {}",
@@ -371,6 +837,22 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::ClearAll) => "清空全部",
         (Locale::En, Key::Delete) => "Delete",
         (Locale::Zh, Key::Delete) => "删除",
+        (Locale::En, Key::TextCleanup) => "Text cleanup (before splitting)",
+        (Locale::Zh, Key::TextCleanup) => "文本清理（分章前）",
+        (Locale::En, Key::TextCleanupHint) => {
+            "Rules run top to bottom. Regex rules use multi-line mode; leave Replace empty to delete matches."
+        }
+        (Locale::Zh, Key::TextCleanupHint) => {
+            "规则自上而下依次执行；正则规则为多行模式，替换留空即删除匹配内容。"
+        }
+        (Locale::En, Key::CleanupFind) => "Find:",
+        (Locale::Zh, Key::CleanupFind) => "查找:",
+        (Locale::En, Key::CleanupReplace) => "Replace:",
+        (Locale::Zh, Key::CleanupReplace) => "替换:",
+        (Locale::En, Key::CleanupRegex) => "Regex",
+        (Locale::Zh, Key::CleanupRegex) => "正则",
+        (Locale::En, Key::AddCleanupRule) => "Add rule",
+        (Locale::Zh, Key::AddCleanupRule) => "添加规则",
         (Locale::En, Key::LanguageField) => "Language:",
         (Locale::Zh, Key::LanguageField) => "语言:",
         (Locale::En, Key::Publisher) => "Publisher:",
@@ -389,8 +871,12 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::Browse) => "浏览",
         (Locale::En, Key::FilenameTemplate) => "Filename template:",
         (Locale::Zh, Key::FilenameTemplate) => "文件命名模板:",
-        (Locale::En, Key::VarsHint) => "(vars: {书名}, {作者}, {日期})",
-        (Locale::Zh, Key::VarsHint) => "(变量: {书名}, {作者}, {日期})",
+        (Locale::En, Key::VarsHint) => {
+            "Variables: {书名} title, {作者} author, {日期} date, {日期:%Y%m%d} formatted date, {章节数} chapter count, {isbn}, {出版社} publisher, {模板} CSS template"
+        }
+        (Locale::Zh, Key::VarsHint) => {
+            "变量: {书名}, {作者}, {日期}, {日期:%Y%m%d}（格式化日期）, {章节数}, {isbn}, {出版社}, {模板}（CSS 模板）"
+        }
         (Locale::En, Key::Current) => "Current:",
         (Locale::Zh, Key::Current) => "当前配置:",
         (Locale::En, Key::TocSettings) => "TOC settings",
@@ -401,6 +887,112 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::TocTitleHint) => "留空将自动使用当前语言默认标题。",
         (Locale::En, Key::IncludeGalleryInToc) => "Include gallery chapter in TOC",
         (Locale::Zh, Key::IncludeGalleryInToc) => "插图章节显示在目录中",
+        (Locale::En, Key::NestVolumes) => "Group chapters under volume headings (nested TOC)",
+        (Locale::Zh, Key::NestVolumes) => "按卷/部分组章节（嵌套目录）",
+        (Locale::En, Key::IncludeGeneratorMeta) => "Record reasypub version in metadata",
+        (Locale::Zh, Key::IncludeGeneratorMeta) => "在元数据中记录 reasypub 版本",
+        (Locale::En, Key::SafeMode) => "Safe mode (skip bundled theme assets on disk)",
+        (Locale::Zh, Key::SafeMode) => "安全模式（不读取磁盘上的内置主题素材）",
+        (Locale::En, Key::RtlPageProgression) => {
+            "Right-to-left page progression (manga, RTL languages)"
+        }
+        (Locale::Zh, Key::RtlPageProgression) => "从右向左翻页（漫画、RTL 语言）",
+        (Locale::En, Key::KepubOutput) => "Kobo KEPUB output (.kepub.epub)",
+        (Locale::Zh, Key::KepubOutput) => "输出 Kobo KEPUB（.kepub.epub）",
+        (Locale::En, Key::OutputFormatLabel) => "Output format:",
+        (Locale::Zh, Key::OutputFormatLabel) => "输出格式:",
+        (Locale::En, Key::PdfPageSize) => "Page size:",
+        (Locale::Zh, Key::PdfPageSize) => "纸张:",
+        (Locale::En, Key::PdfMargin) => "Margins:",
+        (Locale::Zh, Key::PdfMargin) => "页边距:",
+        (Locale::En, Key::PdfHint) => {
+            "PDF uses the Style settings and the body font; images and vertical writing are EPUB-only."
+        }
+        (Locale::Zh, Key::PdfHint) => "PDF 沿用版式设置与正文字体；插图与竖排仅用于 EPUB。",
+        (Locale::En, Key::EpubVersionLabel) => "EPUB version:",
+        (Locale::Zh, Key::EpubVersionLabel) => "EPUB 版本:",
+        (Locale::En, Key::EpubVersionHint) => {
+            "EPUB 2 suits older readers; EPUB 3 adds a semantic nav document."
+        }
+        (Locale::Zh, Key::EpubVersionHint) => {
+            "EPUB 2 兼容老旧阅读器；EPUB 3 生成带语义的导航文档。"
+        }
+        (Locale::En, Key::SubsetFont) => "Subset embedded font",
+        (Locale::Zh, Key::SubsetFont) => "字体子集化",
+        (Locale::En, Key::SubsetFontHint) => {
+            "Keep only the glyphs used in the book. TrueType fonts only; others are embedded in full."
+        }
+        (Locale::Zh, Key::SubsetFontHint) => {
+            "只嵌入书中用到的字形，可大幅减小 CJK 字体体积；仅支持 TrueType 字体，其余字体完整嵌入。"
+        }
+        (Locale::En, Key::ObfuscateFonts) => "Obfuscate embedded fonts",
+        (Locale::Zh, Key::ObfuscateFonts) => "混淆嵌入字体",
+        (Locale::En, Key::ObfuscateFontsHint) => {
+            "Applies IDPF font obfuscation and writes encryption.xml, as many commercial font licenses require."
+        }
+        (Locale::Zh, Key::ObfuscateFontsHint) => {
+            "按 IDPF 规范混淆字体并写入 encryption.xml，满足多数商业字体的嵌入授权要求。"
+        }
+        (Locale::En, Key::ZhConvertLabel) => "Chinese script:",
+        (Locale::Zh, Key::ZhConvertLabel) => "简繁转换:",
+        (Locale::En, Key::ZhConvertHint) => {
+            "Converts chapter titles, body text and book metadata. Built-in table covers common characters only."
+        }
+        (Locale::Zh, Key::ZhConvertHint) => {
+            "同时转换章节标题、正文与书籍信息；内置字表仅覆盖常用字。"
+        }
+        (Locale::En, Key::ZhConvertNone) => "No conversion",
+        (Locale::Zh, Key::ZhConvertNone) => "不转换",
+        (Locale::En, Key::OptimizeImages) => "Optimize images",
+        (Locale::Zh, Key::OptimizeImages) => "优化图片体积",
+        (Locale::En, Key::OptimizeImagesHint) => {
+            "Downscales oversized JPEG/PNG images and recompresses them; an image is kept as-is if it would not get smaller."
+        }
+        (Locale::Zh, Key::OptimizeImagesHint) => {
+            "缩小超出尺寸上限的 JPEG/PNG 图片并重新压缩；压缩后不变小的图片保留原图。"
+        }
+        (Locale::En, Key::ImageMaxDimension) => "Max side (px):",
+        (Locale::Zh, Key::ImageMaxDimension) => "长边上限（像素）:",
+        (Locale::En, Key::JpegQuality) => "JPEG quality:",
+        (Locale::Zh, Key::JpegQuality) => "JPEG 质量:",
+        (Locale::En, Key::ImageOptimizationSummary) => "Images: {} → {}",
+        (Locale::Zh, Key::ImageOptimizationSummary) => "图片体积：{} → {}",
+        (Locale::En, Key::ImageCompatLabel) => "Reader compatibility:",
+        (Locale::Zh, Key::ImageCompatLabel) => "阅读器兼容性:",
+        (Locale::En, Key::ImageCompatHint) => {
+            "Transcodes WebP (and animated GIF for e-ink) to JPEG/PNG. Animated GIFs keep only the first frame."
+        }
+        (Locale::Zh, Key::ImageCompatHint) => {
+            "把 WebP（墨水屏配置下还包括动图 GIF）转码为 JPEG/PNG；动图只保留首帧。"
+        }
+        (Locale::En, Key::ImageCompatOriginal) => "Keep original formats",
+        (Locale::Zh, Key::ImageCompatOriginal) => "保留原格式",
+        (Locale::En, Key::ImageCompatNoWebp) => "Older readers (no WebP)",
+        (Locale::Zh, Key::ImageCompatNoWebp) => "旧版阅读器（不支持 WebP）",
+        (Locale::En, Key::ImageCompatEInk) => "E-ink readers (no WebP / animated GIF)",
+        (Locale::Zh, Key::ImageCompatEInk) => "墨水屏阅读器（不支持 WebP / 动图）",
+        (Locale::En, Key::BatchHint) => {
+            "Convert several text files in a row with the current settings. Titles come from file names."
+        }
+        (Locale::Zh, Key::BatchHint) => "使用当前设置依次转换多个文本文件，书名取自文件名。",
+        (Locale::En, Key::BatchAddFiles) => "Add files",
+        (Locale::Zh, Key::BatchAddFiles) => "添加文件",
+        (Locale::En, Key::BatchAddFolder) => "Add folder",
+        (Locale::Zh, Key::BatchAddFolder) => "添加文件夹",
+        (Locale::En, Key::BatchClear) => "Clear queue",
+        (Locale::Zh, Key::BatchClear) => "清空队列",
+        (Locale::En, Key::BatchStart) => "Start batch",
+        (Locale::Zh, Key::BatchStart) => "开始批量转换",
+        (Locale::En, Key::BatchEmpty) => "Queue is empty.",
+        (Locale::Zh, Key::BatchEmpty) => "队列为空。",
+        (Locale::En, Key::BatchProgress) => "Progress: {} / {}",
+        (Locale::Zh, Key::BatchProgress) => "进度: {} / {}",
+        (Locale::En, Key::BatchPending) => "Pending",
+        (Locale::Zh, Key::BatchPending) => "等待中",
+        (Locale::En, Key::BatchDone) => "Done: {}",
+        (Locale::Zh, Key::BatchDone) => "完成: {}",
+        (Locale::En, Key::BatchFailed) => "Failed: {}",
+        (Locale::Zh, Key::BatchFailed) => "失败: {}",
         (Locale::En, Key::TextEditor) => "Text Editor",
         (Locale::Zh, Key::TextEditor) => "文本编辑器",
         (Locale::En, Key::Save) => "Save",
@@ -411,6 +1003,40 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::Chars) => "字符数: {}",
         (Locale::En, Key::ConversionResult) => "Conversion Result",
         (Locale::Zh, Key::ConversionResult) => "转换结果",
+        (Locale::En, Key::ConversionWarnings) => "Warnings:",
+        (Locale::Zh, Key::ConversionWarnings) => "警告:",
+        (Locale::En, Key::ValidationPassed) => "Self-check passed: no packaging problems found.",
+        (Locale::Zh, Key::ValidationPassed) => "自检通过：未发现打包问题。",
+        (Locale::En, Key::ValidationIssues) => "Self-check found {} problem(s):",
+        (Locale::Zh, Key::ValidationIssues) => "自检发现 {} 个问题:",
+        (Locale::En, Key::RunEpubcheck) => "Run epubcheck after conversion",
+        (Locale::Zh, Key::RunEpubcheck) => "转换后运行 epubcheck",
+        (Locale::En, Key::EpubcheckPath) => "epubcheck:",
+        (Locale::Zh, Key::EpubcheckPath) => "epubcheck 路径:",
+        (Locale::En, Key::EpubcheckHint) => {
+            "Point to an epubcheck executable or epubcheck.jar (requires Java)."
+        }
+        (Locale::Zh, Key::EpubcheckHint) => {
+            "选择 epubcheck 可执行文件或 epubcheck.jar（需要 Java）。"
+        }
+        (Locale::En, Key::EpubcheckSummary) => "epubcheck: {} error(s), {} warning(s)",
+        (Locale::Zh, Key::EpubcheckSummary) => "epubcheck：{} 个错误，{} 个警告",
+        (Locale::En, Key::EpubcheckDetails) => "epubcheck messages",
+        (Locale::Zh, Key::EpubcheckDetails) => "epubcheck 消息",
+        (Locale::En, Key::ConversionCancelled) => "Conversion cancelled.",
+        (Locale::Zh, Key::ConversionCancelled) => "转换已取消。",
+        (Locale::En, Key::ConversionRunning) => "Converting...",
+        (Locale::Zh, Key::ConversionRunning) => "正在转换...",
+        (Locale::En, Key::Cancel) => "Cancel",
+        (Locale::Zh, Key::Cancel) => "取消",
+        (Locale::En, Key::ProgressSplitting) => "Splitting chapters...",
+        (Locale::Zh, Key::ProgressSplitting) => "正在分章...",
+        (Locale::En, Key::ProgressSplit) => "Found {} chapters",
+        (Locale::Zh, Key::ProgressSplit) => "已识别 {} 章",
+        (Locale::En, Key::ProgressRendering) => "Rendering chapters: {} / {}",
+        (Locale::Zh, Key::ProgressRendering) => "正在渲染章节: {} / {}",
+        (Locale::En, Key::ProgressWriting) => "Writing EPUB...",
+        (Locale::Zh, Key::ProgressWriting) => "正在写出 EPUB...",
         (Locale::En, Key::ConversionFailed) => "Conversion Failed",
         (Locale::Zh, Key::ConversionFailed) => "转换失败",
         (Locale::En, Key::ConversionSuccess) => "Conversion Success",
@@ -435,8 +1061,8 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::PreviewTextEmpty) => "文本内容为空。",
         (Locale::En, Key::PreviewNoConfig) => "No config file selected",
         (Locale::Zh, Key::PreviewNoConfig) => "未选择配置文件",
-        (Locale::En, Key::ConfigRegexOk) => "Config regex OK",
-        (Locale::Zh, Key::ConfigRegexOk) => "配置正则有效",
+        (Locale::En, Key::ConfigRegexOk) => "Split config OK",
+        (Locale::Zh, Key::ConfigRegexOk) => "分章配置有效",
         (Locale::En, Key::ChapterEditorTitle) => "Chapter Editor",
         (Locale::Zh, Key::ChapterEditorTitle) => "章节编辑",
         (Locale::En, Key::Refresh) => "Refresh",
@@ -455,13 +1081,1009 @@ pub fn t(locale: Locale, key: Key) -> &'static str {
         (Locale::Zh, Key::ChapterTitle) => "章节标题:",
         (Locale::En, Key::ChapterContent) => "Chapter content:",
         (Locale::Zh, Key::ChapterContent) => "章节内容:",
+        (Locale::En, Key::ChapterCss) => "Chapter CSS (this chapter only):",
+        (Locale::Zh, Key::ChapterCss) => "本章 CSS（仅作用于本章）:",
+        (Locale::En, Key::ChapterBodyClass) => "Body class:",
+        (Locale::Zh, Key::ChapterBodyClass) => "正文 class:",
+        (Locale::En, Key::ChapterVariantLabel) => "Layout:",
+        (Locale::Zh, Key::ChapterVariantLabel) => "版式:",
+        (Locale::En, Key::ChapterVariantStandard) => "Standard",
+        (Locale::Zh, Key::ChapterVariantStandard) => "标准",
+        (Locale::En, Key::ChapterVariantPlain) => "Plain (title only)",
+        (Locale::Zh, Key::ChapterVariantPlain) => "简洁（仅标题）",
+        (Locale::En, Key::ChapterVariantCentered) => "Centered",
+        (Locale::Zh, Key::ChapterVariantCentered) => "居中",
+        (Locale::En, Key::ChapterHeaderOverride) => "Header image:",
+        (Locale::Zh, Key::ChapterHeaderOverride) => "头图:",
+        (Locale::En, Key::ChapterHeaderInherit) => "Book default",
+        (Locale::Zh, Key::ChapterHeaderInherit) => "沿用全书设置",
+        (Locale::En, Key::ChapterHeaderHidden) => "None",
+        (Locale::Zh, Key::ChapterHeaderHidden) => "不显示",
+        (Locale::En, Key::ChapterHeaderCustom) => "Choose image...",
+        (Locale::Zh, Key::ChapterHeaderCustom) => "选择图片...",
         (Locale::En, Key::Up) => "Up",
         (Locale::Zh, Key::Up) => "上移",
         (Locale::En, Key::Down) => "Down",
         (Locale::Zh, Key::Down) => "下移",
         (Locale::En, Key::DesktopOnlyAction) => "This action is available in desktop builds only.",
         (Locale::Zh, Key::DesktopOnlyAction) => "此操作仅在桌面版可用。",
+        (Locale::En, Key::MenuFile) => "File",
+        (Locale::Zh, Key::MenuFile) => "文件",
+        (Locale::En, Key::OpenProject) => "Open Project...",
+        (Locale::Zh, Key::OpenProject) => "打开工程...",
+        (Locale::En, Key::SaveProject) => "Save Project",
+        (Locale::Zh, Key::SaveProject) => "保存工程",
+        (Locale::En, Key::SaveProjectAs) => "Save Project As...",
+        (Locale::Zh, Key::SaveProjectAs) => "工程另存为...",
+        (Locale::En, Key::ProjectFilter) => "Reasypub project",
+        (Locale::Zh, Key::ProjectFilter) => "Reasypub 工程",
+        (Locale::En, Key::ProjectOpenFailed) => "Failed to open project: {}",
+        (Locale::Zh, Key::ProjectOpenFailed) => "打开工程失败: {}",
+        (Locale::En, Key::ProjectSaveFailed) => "Failed to save project: {}",
+        (Locale::Zh, Key::ProjectSaveFailed) => "保存工程失败: {}",
+        (Locale::En, Key::ProjectMissingFiles) => {
+            "Project opened, but some files could not be read: {}"
+        }
+        (Locale::Zh, Key::ProjectMissingFiles) => "工程已打开，但部分文件无法读取: {}",
+        (Locale::En, Key::RecentFiles) => "Recent",
+        (Locale::Zh, Key::RecentFiles) => "最近使用",
+        (Locale::En, Key::RecentTexts) => "Recent texts",
+        (Locale::Zh, Key::RecentTexts) => "最近打开的文本",
+        (Locale::En, Key::RecentOutputs) => "Recent outputs",
+        (Locale::Zh, Key::RecentOutputs) => "最近生成的文件",
+        (Locale::En, Key::NoRecentFiles) => "Nothing yet",
+        (Locale::Zh, Key::NoRecentFiles) => "暂无记录",
+        (Locale::En, Key::ClearRecent) => "Clear recent",
+        (Locale::Zh, Key::ClearRecent) => "清除最近记录",
+        (Locale::En, Key::DropFilesHint) => {
+            "Drop a text file to load it, one image to set the cover, or several images to add illustrations"
+        }
+        (Locale::Zh, Key::DropFilesHint) => {
+            "拖入文本文件载入正文，单张图片设为封面，多张图片加入插图"
+        }
+        (Locale::En, Key::DropUnsupported) => "Ignored dropped files: {}",
+        (Locale::Zh, Key::DropUnsupported) => "已忽略不支持的拖入文件: {}",
+        (Locale::En, Key::Undo) => "Undo",
+        (Locale::Zh, Key::Undo) => "撤销",
+        (Locale::En, Key::Redo) => "Redo",
+        (Locale::Zh, Key::Redo) => "重做",
+        (Locale::En, Key::FindReplace) => "Find & Replace",
+        (Locale::Zh, Key::FindReplace) => "查找替换",
+        (Locale::En, Key::FindHint) => "Find",
+        (Locale::Zh, Key::FindHint) => "查找",
+        (Locale::En, Key::ReplaceHint) => "Replace with",
+        (Locale::Zh, Key::ReplaceHint) => "替换为",
+        (Locale::En, Key::FindRegex) => "Regex",
+        (Locale::Zh, Key::FindRegex) => "正则",
+        (Locale::En, Key::FindMatchCase) => "Match case",
+        (Locale::Zh, Key::FindMatchCase) => "区分大小写",
+        (Locale::En, Key::FindMatches) => "{} / {} matches",
+        (Locale::Zh, Key::FindMatches) => "第 {} 个，共 {} 个匹配",
+        (Locale::En, Key::ReplaceOne) => "Replace",
+        (Locale::Zh, Key::ReplaceOne) => "替换",
+        (Locale::En, Key::ReplaceAll) => "Replace all",
+        (Locale::Zh, Key::ReplaceAll) => "全部替换",
+        (Locale::En, Key::ReplacedCount) => "Replaced {} matches",
+        (Locale::Zh, Key::ReplacedCount) => "已替换 {} 处",
+        (Locale::En, Key::MergePrevious) => "Merge with previous",
+        (Locale::Zh, Key::MergePrevious) => "合并到上一章",
+        (Locale::En, Key::SplitAtCursor) => "Split at cursor",
+        (Locale::Zh, Key::SplitAtCursor) => "从光标处拆分",
+        (Locale::En, Key::SplitNeedsCursor) => {
+            "Click in the chapter content before the line that should start the new chapter."
+        }
+        (Locale::Zh, Key::SplitNeedsCursor) => "请先在章节正文中点击，把光标放在新章节标题行之前。",
+        (Locale::En, Key::BulkRename) => "Bulk rename",
+        (Locale::Zh, Key::BulkRename) => "批量重命名",
+        (Locale::En, Key::RenameTemplate) => "Template",
+        (Locale::Zh, Key::RenameTemplate) => "模板",
+        (Locale::En, Key::RenameStart) => "Start at",
+        (Locale::Zh, Key::RenameStart) => "起始序号",
+        (Locale::En, Key::RenameTemplateHint) => {
+            "{n} number, {原标题} original title, {标题} original title without its chapter number"
+        }
+        (Locale::Zh, Key::RenameTemplateHint) => {
+            "{n} 序号，{原标题} 原标题，{标题} 去掉章节编号的原标题"
+        }
+        (Locale::En, Key::SelectAll) => "Select all",
+        (Locale::Zh, Key::SelectAll) => "全选",
+        (Locale::En, Key::SelectNone) => "Select none",
+        (Locale::Zh, Key::SelectNone) => "全不选",
+        (Locale::En, Key::SelectedCount) => "{} selected",
+        (Locale::Zh, Key::SelectedCount) => "已选 {} 章",
+        (Locale::En, Key::RenameSelectHint) => "Tick the chapters to rename.",
+        (Locale::Zh, Key::RenameSelectHint) => "勾选要重命名的章节。",
+        (Locale::En, Key::RenameApply) => "Rename selected",
+        (Locale::Zh, Key::RenameApply) => "重命名所选章节",
+        (Locale::En, Key::DragToReorder) => {
+            "Drag to reorder; dragging a ticked chapter moves all ticked chapters"
+        }
+        (Locale::Zh, Key::DragToReorder) => "拖动调整顺序；拖动已勾选的章节会一起移动所有勾选章节",
+        (Locale::En, Key::MoveSelectedTo) => "Move to position",
+        (Locale::Zh, Key::MoveSelectedTo) => "移动到第",
+        (Locale::En, Key::Move) => "Move",
+        (Locale::Zh, Key::Move) => "移动",
+        (Locale::En, Key::ChapterPreview) => "Preview",
+        (Locale::Zh, Key::ChapterPreview) => "预览",
+        (Locale::En, Key::ChapterPreviewTitle) => "Chapter preview",
+        (Locale::Zh, Key::ChapterPreviewTitle) => "章节预览",
+        (Locale::En, Key::ChapterPreviewHint) => {
+            "Rendered with the current Format and CSS settings; reading apps may differ slightly."
+        }
+        (Locale::Zh, Key::ChapterPreviewHint) => {
+            "按当前格式与 CSS 设置渲染，阅读器中的效果可能略有不同。"
+        }
+        (Locale::En, Key::PreviousChapter) => "Previous",
+        (Locale::Zh, Key::PreviousChapter) => "上一章",
+        (Locale::En, Key::NextChapter) => "Next",
+        (Locale::Zh, Key::NextChapter) => "下一章",
+        (Locale::En, Key::StyleSample) => "Style preview",
+        (Locale::Zh, Key::StyleSample) => "样式预览",
+        (Locale::En, Key::StyleSampleTitle) => "Chapter 1 A Rainy Night",
+        (Locale::Zh, Key::StyleSampleTitle) => "第一章 雨夜",
+        (Locale::En, Key::StyleSampleText) => {
+            "The rain had not stopped since dusk. She closed the book, listened to the water on the eaves, and wondered whether the letter would ever arrive.\n\nIn the morning the street was quiet again, and the first light fell across the unfinished page."
+        }
+        (Locale::Zh, Key::StyleSampleText) => {
+            "雨从黄昏起就没有停过。她合上书，听着檐下的水声，心想那封信大概不会来了。\n\n第二天清晨，街上重新安静下来，第一缕光落在那页尚未写完的稿纸上。"
+        }
+        (Locale::En, Key::DownloadFailed) => "Download failed: {}",
+        (Locale::Zh, Key::DownloadFailed) => "下载失败: {}",
+        (Locale::En, Key::ReportChapters) => "Chapters: {} · Images: {}",
+        (Locale::Zh, Key::ReportChapters) => "章节：{} · 图片：{}",
+        (Locale::En, Key::ReportLength) => "Characters: {} · Words: {}",
+        (Locale::Zh, Key::ReportLength) => "字数：{} · 词数：{}",
+        (Locale::En, Key::ReportOutliers) => "Unusually short or long chapters ({})",
+        (Locale::Zh, Key::ReportOutliers) => "篇幅异常的章节（{}）",
+        (Locale::En, Key::OutlierShort) => "short",
+        (Locale::Zh, Key::OutlierShort) => "偏短",
+        (Locale::En, Key::OutlierLong) => "long",
+        (Locale::Zh, Key::OutlierLong) => "偏长",
+        (Locale::En, Key::AddRegexPattern) => "Add pattern",
+        (Locale::Zh, Key::AddRegexPattern) => "添加正则",
+        (Locale::En, Key::RegexPriorityHint) => {
+            "Lines matching any pattern start a chapter; when several match at the same place, the higher one wins."
+        }
+        (Locale::Zh, Key::RegexPriorityHint) => {
+            "匹配任一正则的行都会开始新章节；多条正则在同一位置匹配时，以靠前的为准。"
+        }
+        (Locale::En, Key::TitleExclusions) => "Title exclusions",
+        (Locale::Zh, Key::TitleExclusions) => "标题排除规则",
+        (Locale::En, Key::TitleExclusionsHint) => {
+            "Lines matching any of these patterns never start a chapter, even if they look like a heading."
+        }
+        (Locale::Zh, Key::TitleExclusionsHint) => {
+            "匹配任一规则的行即使形似标题也不会开始新章节，例如目录或正文中的“见第三章”。"
+        }
+        (Locale::En, Key::AddTitleExclusion) => "Add exclusion",
+        (Locale::Zh, Key::AddTitleExclusion) => "添加排除规则",
+        (Locale::En, Key::MethodLength) => "By length",
+        (Locale::Zh, Key::MethodLength) => "按字数",
+        (Locale::En, Key::ChapterLengthChars) => "characters per chapter, split between paragraphs",
+        (Locale::Zh, Key::ChapterLengthChars) => "字一章，在段落之间切分",
+        (Locale::En, Key::MethodToc) => "Split by table of contents",
+        (Locale::Zh, Key::MethodToc) => "用目录切分",
+        (Locale::En, Key::TocDetected) => "Contents found: {} titles (removed from the text)",
+        (Locale::Zh, Key::TocDetected) => "检测到目录：{} 个标题（目录本身会被移除）",
+        (Locale::En, Key::TocNotFound) => "No contents list found at the top of the text",
+        (Locale::Zh, Key::TocNotFound) => "文本开头未检测到目录",
+        (Locale::En, Key::MethodHeuristic) => "Detect automatically",
+        (Locale::Zh, Key::MethodHeuristic) => "自动识别",
+        (Locale::En, Key::ReviewInChapterEditor) => "Review in Chapter Editor",
+        (Locale::Zh, Key::ReviewInChapterEditor) => "在章节编辑器中审阅",
+        (Locale::En, Key::Series) => "Series:",
+        (Locale::Zh, Key::Series) => "系列:",
+        (Locale::En, Key::SeriesIndex) => "Series index:",
+        (Locale::Zh, Key::SeriesIndex) => "系列序号:",
+        (Locale::En, Key::Creators) => "Creators:",
+        (Locale::Zh, Key::Creators) => "署名:",
+        (Locale::En, Key::AddCreator) => "Add creator",
+        (Locale::Zh, Key::AddCreator) => "添加署名",
+        (Locale::En, Key::RoleAuthor) => "Author",
+        (Locale::Zh, Key::RoleAuthor) => "作者",
+        (Locale::En, Key::RoleTranslator) => "Translator",
+        (Locale::Zh, Key::RoleTranslator) => "译者",
+        (Locale::En, Key::RoleIllustrator) => "Illustrator",
+        (Locale::Zh, Key::RoleIllustrator) => "插画",
+        (Locale::En, Key::RoleEditor) => "Editor",
+        (Locale::Zh, Key::RoleEditor) => "编辑",
+        (Locale::En, Key::AdvancedMetadata) => "Advanced metadata",
+        (Locale::Zh, Key::AdvancedMetadata) => "高级元数据",
+        (Locale::En, Key::AdvancedMetadataHint) => {
+            "Keys starting with dc: become Dublin Core elements (e.g. dc:rights); other keys become <meta name=… content=…/> (e.g. calibre:user_metadata:#genre)."
+        }
+        (Locale::Zh, Key::AdvancedMetadataHint) => {
+            "以 dc: 开头的键写成 Dublin Core 元素（如 dc:rights），其余写成 <meta name=… content=…/>（如 calibre:user_metadata:#genre）。"
+        }
+        (Locale::En, Key::AddMetadataEntry) => "Add entry",
+        (Locale::Zh, Key::AddMetadataEntry) => "添加条目",
+        (Locale::En, Key::MetadataKey) => "Key",
+        (Locale::Zh, Key::MetadataKey) => "键",
+        (Locale::En, Key::MetadataValue) => "Value",
+        (Locale::Zh, Key::MetadataValue) => "值",
+        (Locale::En, Key::InvalidMetadataKey) => "Invalid key, skipped",
+        (Locale::Zh, Key::InvalidMetadataKey) => "键无效，将被跳过",
+        (Locale::En, Key::LookupMetadata) => "Look up online…",
+        (Locale::Zh, Key::LookupMetadata) => "在线查询元数据…",
+        (Locale::En, Key::LookupSource) => "Source:",
+        (Locale::Zh, Key::LookupSource) => "数据源:",
+        (Locale::En, Key::LookupSearch) => "Search",
+        (Locale::Zh, Key::LookupSearch) => "查询",
+        (Locale::En, Key::LookupSearching) => "Searching…",
+        (Locale::Zh, Key::LookupSearching) => "查询中…",
+        (Locale::En, Key::LookupNoResults) => "No matches found.",
+        (Locale::Zh, Key::LookupNoResults) => "没有找到匹配的书籍。",
+        (Locale::En, Key::LookupApply) => "Use",
+        (Locale::Zh, Key::LookupApply) => "使用",
+        (Locale::En, Key::LookupDownloadCover) => "Also download the cover",
+        (Locale::Zh, Key::LookupDownloadCover) => "同时下载封面",
+        (Locale::En, Key::LookupFailed) => "Lookup failed: {}",
+        (Locale::Zh, Key::LookupFailed) => "查询失败: {}",
+        (Locale::En, Key::CoverDownloadFailed) => "Cover download failed: {}",
+        (Locale::Zh, Key::CoverDownloadFailed) => "封面下载失败: {}",
+        (Locale::En, Key::Identifier) => "Identifier (UUID):",
+        (Locale::Zh, Key::Identifier) => "唯一标识 (UUID):",
+        (Locale::En, Key::IdentifierHint) => {
+            "Leave empty to use {}, derived from the title and author."
+        }
+        (Locale::Zh, Key::IdentifierHint) => "留空时使用由书名和作者生成的 {}。",
+        (Locale::En, Key::IdentifierInvalid) => {
+            "Not a UUID; the generated identifier will be used."
+        }
+        (Locale::Zh, Key::IdentifierInvalid) => "不是有效的 UUID，将使用生成的标识。",
+        (Locale::En, Key::DetectLanguage) => "Detect",
+        (Locale::Zh, Key::DetectLanguage) => "从正文识别",
+        (Locale::En, Key::LanguageHint) => "Leave empty to detect the language from the text.",
+        (Locale::Zh, Key::LanguageHint) => "留空时从正文推断语言。",
+        (Locale::En, Key::LanguageNormalized) => "Will be written as {}.",
+        (Locale::Zh, Key::LanguageNormalized) => "将写入为 {}。",
+        (Locale::En, Key::LanguageInvalid) => {
+            "Not a valid BCP 47 tag; the language will be detected from the text."
+        }
+        (Locale::Zh, Key::LanguageInvalid) => "不是有效的 BCP 47 语言标签，将从正文推断语言。",
+        (Locale::En, Key::PunctuationNormalize) => "Punctuation and width:",
+        (Locale::Zh, Key::PunctuationNormalize) => "标点与全半角:",
+        (Locale::En, Key::PunctuationHint) => {
+            "Applied after cleanup rules and before splitting. Also collapses 。。。 into …… and repairs quote direction."
+        }
+        (Locale::Zh, Key::PunctuationHint) => {
+            "在清理规则之后、分章之前应用；同时把“。。。”合并为“……”并修正引号方向。"
+        }
+        (Locale::En, Key::PunctuationOff) => "Leave unchanged",
+        (Locale::Zh, Key::PunctuationOff) => "不处理",
+        (Locale::En, Key::PunctuationFullWidth) => "Chinese: half-width → full-width",
+        (Locale::Zh, Key::PunctuationFullWidth) => "中文：半角标点转全角",
+        (Locale::En, Key::PunctuationHalfWidth) => "English: full-width → half-width",
+        (Locale::Zh, Key::PunctuationHalfWidth) => "英文：全角标点转半角",
+        (Locale::En, Key::PreviewPunctuation) => "Preview changes",
+        (Locale::Zh, Key::PreviewPunctuation) => "预览改动",
+        (Locale::En, Key::PunctuationChanges) => "{} line(s) will change:",
+        (Locale::Zh, Key::PunctuationChanges) => "将修改 {} 行：",
+        (Locale::En, Key::PunctuationNoChanges) => "Nothing to change.",
+        (Locale::Zh, Key::PunctuationNoChanges) => "没有需要修改的内容。",
+        (Locale::En, Key::ReflowParagraphs) => "Re-flow hard-wrapped lines into paragraphs",
+        (Locale::Zh, Key::ReflowParagraphs) => "合并硬换行为段落",
+        (Locale::En, Key::ReflowSensitivity) => "Sensitivity:",
+        (Locale::Zh, Key::ReflowSensitivity) => "灵敏度:",
+        (Locale::En, Key::ReflowHint) => {
+            "For texts wrapped at a fixed width. Lines that do not end a sentence are joined with the next one; higher sensitivity also joins shorter lines."
+        }
+        (Locale::Zh, Key::ReflowHint) => {
+            "用于按固定字数折行的文本：不以句末标点结尾的行与下一行合并；灵敏度越高，较短的行也会被合并。"
+        }
+        (Locale::En, Key::FilenamePreview) => "Output file: {}",
+        (Locale::Zh, Key::FilenamePreview) => "输出文件：{}",
+        (Locale::En, Key::OverwriteLabel) => "If the file exists:",
+        (Locale::Zh, Key::OverwriteLabel) => "文件已存在时:",
+        (Locale::En, Key::OverwriteAlways) => "Overwrite",
+        (Locale::Zh, Key::OverwriteAlways) => "覆盖",
+        (Locale::En, Key::OverwriteNumber) => "Add (1), (2)…",
+        (Locale::Zh, Key::OverwriteNumber) => "追加 (1)、(2)…",
+        (Locale::En, Key::OverwriteAsk) => "Ask",
+        (Locale::Zh, Key::OverwriteAsk) => "询问",
+        (Locale::En, Key::OutputExists) => "File already exists",
+        (Locale::Zh, Key::OutputExists) => "文件已存在",
+        (Locale::En, Key::OutputExistsPrompt) => {
+            "{} already exists. Overwrite it, or save under a numbered name?"
+        }
+        (Locale::Zh, Key::OutputExistsPrompt) => "{} 已存在。覆盖原文件，还是另存为带编号的文件？",
+        (Locale::En, Key::OverwriteConfirm) => "Overwrite",
+        (Locale::Zh, Key::OverwriteConfirm) => "覆盖",
+        (Locale::En, Key::OverwriteRenumber) => "Save as numbered copy",
+        (Locale::Zh, Key::OverwriteRenumber) => "另存为编号文件",
+        (Locale::En, Key::PostExportActions) => "After export",
+        (Locale::Zh, Key::PostExportActions) => "导出完成后",
+        (Locale::En, Key::OpenAfterExport) => "Open the book in the default reader",
+        (Locale::Zh, Key::OpenAfterExport) => "用默认阅读器打开",
+        (Locale::En, Key::AddToCalibreAfterExport) => "Add the book to Calibre",
+        (Locale::Zh, Key::AddToCalibreAfterExport) => "加入 Calibre 书库",
+        (Locale::En, Key::CalibredbPath) => "calibredb:",
+        (Locale::Zh, Key::CalibredbPath) => "calibredb：",
+        (Locale::En, Key::CalibreLibrary) => "Library:",
+        (Locale::Zh, Key::CalibreLibrary) => "书库：",
+        (Locale::En, Key::CalibreHint) => {
+            "Leave the library empty to use Calibre's default library. If Calibre is running, enable connections from other programs in its preferences."
+        }
+        (Locale::Zh, Key::CalibreHint) => {
+            "书库留空则使用 Calibre 默认书库；Calibre 正在运行时，需在其偏好设置中允许其他程序连接。"
+        }
+        (Locale::En, Key::SendToCalibre) => "Add to Calibre",
+        (Locale::Zh, Key::SendToCalibre) => "添加到 Calibre",
+        (Locale::En, Key::CalibreAdding) => "Adding to Calibre…",
+        (Locale::Zh, Key::CalibreAdding) => "正在加入 Calibre…",
+        (Locale::En, Key::CalibreAdded) => "Added to Calibre: {}",
+        (Locale::Zh, Key::CalibreAdded) => "已加入 Calibre：{}",
+        (Locale::En, Key::CalibreFailed) => "Could not add to Calibre: {}",
+        (Locale::Zh, Key::CalibreFailed) => "加入 Calibre 失败：{}",
+        (Locale::En, Key::SendToKindle) => "Send to Kindle",
+        (Locale::Zh, Key::SendToKindle) => "发送到 Kindle",
+        (Locale::En, Key::SendToKindleAfterExport) => "Email the book to Kindle",
+        (Locale::Zh, Key::SendToKindleAfterExport) => "通过邮件发送到 Kindle",
+        (Locale::En, Key::KindleSettingsButton) => "Kindle email settings…",
+        (Locale::Zh, Key::KindleSettingsButton) => "Kindle 邮箱设置…",
+        (Locale::En, Key::KindleSettingsTitle) => "Kindle email settings",
+        (Locale::Zh, Key::KindleSettingsTitle) => "Kindle 邮箱设置",
+        (Locale::En, Key::SmtpServer) => "SMTP server:",
+        (Locale::Zh, Key::SmtpServer) => "SMTP 服务器：",
+        (Locale::En, Key::SmtpSecurityLabel) => "Encryption:",
+        (Locale::Zh, Key::SmtpSecurityLabel) => "加密方式：",
+        (Locale::En, Key::SmtpPort) => "Port:",
+        (Locale::Zh, Key::SmtpPort) => "端口：",
+        (Locale::En, Key::SmtpUsername) => "Username:",
+        (Locale::Zh, Key::SmtpUsername) => "用户名：",
+        (Locale::En, Key::SmtpPassword) => "Password:",
+        (Locale::Zh, Key::SmtpPassword) => "密码：",
+        (Locale::En, Key::KindleFrom) => "Sender address:",
+        (Locale::Zh, Key::KindleFrom) => "发件地址：",
+        (Locale::En, Key::KindleTo) => "Kindle address:",
+        (Locale::Zh, Key::KindleTo) => "Kindle 收件地址：",
+        (Locale::En, Key::KindleHint) => {
+            "Add the sender address to the approved personal document email list in your Amazon account. Leave it empty to use the username. Many providers require an app password. The password is kept only until the app closes."
+        }
+        (Locale::Zh, Key::KindleHint) => {
+            "发件地址需加入亚马逊账户的“已认可的发件人电子邮箱列表”，留空则使用用户名；多数邮箱需使用授权码或应用专用密码。密码只保留到应用关闭。"
+        }
+        (Locale::En, Key::KindleIncomplete) => "Settings incomplete: {}",
+        (Locale::Zh, Key::KindleIncomplete) => "设置不完整：{}",
+        (Locale::En, Key::KindleSending) => "Sending to Kindle…",
+        (Locale::Zh, Key::KindleSending) => "正在发送到 Kindle…",
+        (Locale::En, Key::KindleSent) => "Sent to {}",
+        (Locale::Zh, Key::KindleSent) => "已发送到 {}",
+        (Locale::En, Key::KindleFailed) => "Could not send to Kindle: {}",
+        (Locale::Zh, Key::KindleFailed) => "发送到 Kindle 失败：{}",
+        (Locale::En, Key::CopyToDevice) => "Copy to {}",
+        (Locale::Zh, Key::CopyToDevice) => "复制到 {}",
+        (Locale::En, Key::RefreshDevices) => "Detect devices",
+        (Locale::Zh, Key::RefreshDevices) => "检测设备",
+        (Locale::En, Key::NoDevices) => "No Kindle or Kobo connected",
+        (Locale::Zh, Key::NoDevices) => "未检测到 Kindle 或 Kobo",
+        (Locale::En, Key::DeviceCopied) => "Copied to {}",
+        (Locale::Zh, Key::DeviceCopied) => "已复制到 {}",
+        (Locale::En, Key::DeviceCopyFailed) => "Could not copy to the device: {}",
+        (Locale::Zh, Key::DeviceCopyFailed) => "复制到设备失败：{}",
+        (Locale::En, Key::DeviceFolders) => "Device folders:",
+        (Locale::Zh, Key::DeviceFolders) => "设备目录：",
+        (Locale::En, Key::DeviceFoldersHint) => {
+            "Relative to the device root; filename variables such as {作者} and {书名} work here. Kindles do not open EPUB files copied over USB, only PDFs; use Send to Kindle for EPUB."
+        }
+        (Locale::Zh, Key::DeviceFoldersHint) => {
+            "相对设备根目录，可使用 {作者}、{书名} 等文件名变量。Kindle 不能打开通过 USB 复制的 EPUB（PDF 可以），EPUB 请用“发送到 Kindle”。"
+        }
+        (Locale::En, Key::EpubImported) => {
+            "Imported {} chapters from the EPUB; restyle or re-split them, then convert to rebuild the book."
+        }
+        (Locale::Zh, Key::EpubImported) => {
+            "已从 EPUB 导入 {} 章，可重新设置样式或分章后再次转换生成。"
+        }
+        (Locale::En, Key::FrontMatter) => "Front matter",
+        (Locale::Zh, Key::FrontMatter) => "前置页",
+        (Locale::En, Key::FrontMatterHint) => {
+            "Pages placed before the first chapter. Each line becomes a paragraph, filename variables such as {书名} and {作者} work here, and empty pages are left out."
+        }
+        (Locale::Zh, Key::FrontMatterHint) => {
+            "插入在第一章之前。每行一段，可使用 {书名}、{作者} 等文件名变量，留空的页面不生成。"
+        }
+        (Locale::En, Key::TitlePage) => "Title page",
+        (Locale::Zh, Key::TitlePage) => "书名页",
+        (Locale::En, Key::CopyrightPage) => "Copyright page",
+        (Locale::Zh, Key::CopyrightPage) => "版权页",
+        (Locale::En, Key::DedicationPage) => "Dedication",
+        (Locale::Zh, Key::DedicationPage) => "献词",
+        (Locale::En, Key::PrefacePage) => "Preface",
+        (Locale::Zh, Key::PrefacePage) => "前言",
+        (Locale::En, Key::UseCopyrightTemplate) => "Use template",
+        (Locale::Zh, Key::UseCopyrightTemplate) => "使用模板",
+        (Locale::En, Key::BackMatter) => "Back matter",
+        (Locale::Zh, Key::BackMatter) => "后记 / 版权页",
+        (Locale::En, Key::BackMatterHint) => {
+            "A final page after the chapters and gallery, with its own TOC entry. Each line becomes a paragraph; leave the text empty to skip it."
+        }
+        (Locale::Zh, Key::BackMatterHint) => {
+            "排在正文与插图之后的最后一页，在目录中单列一项。每行一段，文字为空时不生成。"
+        }
+        (Locale::En, Key::Afterword) => "Afterword",
+        (Locale::Zh, Key::Afterword) => "后记",
+        (Locale::En, Key::Colophon) => "Colophon",
+        (Locale::Zh, Key::Colophon) => "版权页",
+        (Locale::En, Key::BackMatterTitle) => "Title (defaults to the page type):",
+        (Locale::Zh, Key::BackMatterTitle) => "标题（留空使用页面类型）：",
+        (Locale::En, Key::BackMatterFromText) => {
+            "Take it from a trailing 后记 / Afterword marker in the text"
+        }
+        (Locale::Zh, Key::BackMatterFromText) => "从正文末尾的“后记”“跋”等标记处截取",
+        (Locale::En, Key::GrayscaleImages) => "E-ink optimization (grayscale)",
+        (Locale::Zh, Key::GrayscaleImages) => "e-ink 优化（灰度）",
+        (Locale::En, Key::GrayscaleImagesHint) => {
+            "Converts every image to 8-bit grayscale with extra contrast: smaller files and less banding on monochrome readers. WebP and GIF become PNG/JPEG."
+        }
+        (Locale::Zh, Key::GrayscaleImagesHint) => {
+            "把所有图片转为提高对比度的 8 位灰度图，体积更小，黑白屏上色带更少；WebP 与 GIF 会转为 PNG/JPEG。"
+        }
+        (Locale::En, Key::RasterizeSvg) => "Rasterize SVG images to PNG",
+        (Locale::Zh, Key::RasterizeSvg) => "SVG 栅格化为 PNG",
+        (Locale::En, Key::RasterizeSvgHint) => {
+            "For readers without SVG support: SVG covers and illustrations are rendered to PNG at the longest-side limit. Otherwise they are embedded as vectors."
+        }
+        (Locale::Zh, Key::RasterizeSvgHint) => {
+            "供不支持 SVG 的阅读器使用：SVG 封面与插图按长边上限渲染为 PNG；不勾选时按矢量图嵌入。"
+        }
+        (Locale::En, Key::Theme) => "Theme",
+        (Locale::Zh, Key::Theme) => "主题",
+        (Locale::En, Key::ThemeSepia) => "Sepia",
+        (Locale::Zh, Key::ThemeSepia) => "护眼",
+        (Locale::En, Key::ThemeMidnight) => "Midnight",
+        (Locale::Zh, Key::ThemeMidnight) => "午夜",
+        (Locale::En, Key::AccentColor) => "Accent color",
+        (Locale::Zh, Key::AccentColor) => "强调色",
+        (Locale::En, Key::PanelFill) => "Panel background",
+        (Locale::Zh, Key::PanelFill) => "面板底色",
+        (Locale::En, Key::CornerRadius) => "Corner radius",
+        (Locale::Zh, Key::CornerRadius) => "圆角",
+        (Locale::En, Key::ResetTheme) => "Restore preset colors",
+        (Locale::Zh, Key::ResetTheme) => "恢复预设配色",
+        (Locale::En, Key::Help) => "Help",
+        (Locale::Zh, Key::Help) => "帮助",
+        (Locale::En, Key::HelpWorkflow) => {
+            "1. Open a TXT, Markdown or EPUB file.\n2. Check the chapter split in the Chapters panel or the chapter editor.\n3. Fill in the publish info and cover.\n4. Convert."
+        }
+        (Locale::Zh, Key::HelpWorkflow) => {
+            "1. 打开 TXT、Markdown 或 EPUB 文件。\n2. 在“章节”面板或章节编辑器中检查分章。\n3. 填写出版信息与封面。\n4. 开始转换。"
+        }
+        (Locale::En, Key::KeyboardShortcuts) => "Keyboard shortcuts",
+        (Locale::Zh, Key::KeyboardShortcuts) => "快捷键",
+        (Locale::En, Key::CustomizeShortcuts) => "Customize shortcuts...",
+        (Locale::Zh, Key::CustomizeShortcuts) => "自定义快捷键...",
+        (Locale::En, Key::ShortcutsHint) => {
+            "Click a shortcut, then press the new key combination (Esc cancels)."
+        }
+        (Locale::Zh, Key::ShortcutsHint) => "点击快捷键后按下新的组合键（Esc 取消）。",
+        (Locale::En, Key::ShortcutRecording) => "Press keys...",
+        (Locale::Zh, Key::ShortcutRecording) => "请按键...",
+        (Locale::En, Key::ShortcutConflict) => "Same as \"{}\"",
+        (Locale::Zh, Key::ShortcutConflict) => "与“{}”相同",
+        (Locale::En, Key::ResetShortcuts) => "Restore default shortcuts",
+        (Locale::Zh, Key::ResetShortcuts) => "恢复默认快捷键",
+        (Locale::En, Key::Accessibility) => "Accessibility",
+        (Locale::Zh, Key::Accessibility) => "无障碍",
+        (Locale::En, Key::AccessibilityHint) => {
+            "schema.org accessibility metadata written to the OPF; separate multiple values with commas"
+        }
+        (Locale::Zh, Key::AccessibilityHint) => {
+            "写入 OPF 的 schema.org 无障碍元数据；多个取值用逗号分隔"
+        }
+        (Locale::En, Key::AccessModes) => "Access modes (accessMode)",
+        (Locale::Zh, Key::AccessModes) => "访问方式（accessMode）",
+        (Locale::En, Key::AccessibilityFeatures) => "Features (accessibilityFeature)",
+        (Locale::Zh, Key::AccessibilityFeatures) => "无障碍特性（accessibilityFeature）",
+        (Locale::En, Key::AccessibilityHazards) => "Hazards (accessibilityHazard)",
+        (Locale::Zh, Key::AccessibilityHazards) => "风险（accessibilityHazard）",
+        (Locale::En, Key::AccessibilitySummary) => "Summary (accessibilitySummary)",
+        (Locale::Zh, Key::AccessibilitySummary) => "无障碍说明（accessibilitySummary）",
+        (Locale::En, Key::TocColumns) => "TOC page columns",
+        (Locale::Zh, Key::TocColumns) => "目录页栏数",
+        (Locale::En, Key::TocDottedLeaders) => "Dotted leaders between title and edge",
+        (Locale::Zh, Key::TocDottedLeaders) => "标题后显示点线引导",
+        (Locale::En, Key::TocVolumeHeadings) => "Show volumes as group headings",
+        (Locale::Zh, Key::TocVolumeHeadings) => "卷名显示为分组标题",
+        (Locale::En, Key::TocShowChapterNumbers) => "Show chapter numbers",
+        (Locale::Zh, Key::TocShowChapterNumbers) => "显示章节编号",
+        (Locale::En, Key::ReproducibleBuild) => "Reproducible build",
+        (Locale::Zh, Key::ReproducibleBuild) => "可复现输出",
+        (Locale::En, Key::ReproducibleBuildHint) => {
+            "Fixed timestamps and entry order: converting the same input twice gives byte-identical EPUBs"
+        }
+        (Locale::Zh, Key::ReproducibleBuildHint) => {
+            "固定时间戳与条目顺序，相同输入两次转换得到逐字节相同的 EPUB"
+        }
+        (Locale::En, Key::CompressionLevel) => "ZIP compression level (0 = store):",
+        (Locale::Zh, Key::CompressionLevel) => "ZIP 压缩级别（0 为不压缩）：",
+        (Locale::En, Key::StoreImagesUncompressed) => {
+            "Store images uncompressed (JPEG/PNG/WebP are already compressed)"
+        }
+        (Locale::Zh, Key::StoreImagesUncompressed) => "图片不再压缩（JPEG/PNG/WebP 本身已压缩）",
+        (Locale::En, Key::SplitLargeChapters) => "Split chapters over 300 KB",
+        (Locale::Zh, Key::SplitLargeChapters) => "拆分超过 300 KB 的章节",
+        (Locale::En, Key::SplitLargeChaptersHint) => {
+            "Long chapters are written as several XHTML files with a single TOC entry; some readers slow down on large files"
+        }
+        (Locale::Zh, Key::SplitLargeChaptersHint) => {
+            "过长的章节拆成多个 XHTML 文件，目录中仍只有一项；部分阅读器打开大文件时会变慢"
+        }
+        (Locale::En, Key::SpineOrder) => "Reading order",
+        (Locale::Zh, Key::SpineOrder) => "阅读顺序",
+        (Locale::En, Key::SpineOrderHint) => {
+            "Drag to reorder the sections of the book; the preface always comes right before the first chapter"
+        }
+        (Locale::Zh, Key::SpineOrderHint) => {
+            "拖动调整各部分在书中的先后顺序；前言总是紧接在正文之前"
+        }
+        (Locale::En, Key::SpineCover) => "Cover page",
+        (Locale::Zh, Key::SpineCover) => "封面页",
+        (Locale::En, Key::SpineFrontMatter) => "Front matter (title page, copyright, dedication)",
+        (Locale::Zh, Key::SpineFrontMatter) => "前置页（书名页、版权页、献词）",
+        (Locale::En, Key::SpineTocPage) => "TOC page",
+        (Locale::Zh, Key::SpineTocPage) => "目录页",
+        (Locale::En, Key::SpineChapters) => "Preface and chapters",
+        (Locale::Zh, Key::SpineChapters) => "前言与正文",
+        (Locale::En, Key::SpineGallery) => "Gallery",
+        (Locale::Zh, Key::SpineGallery) => "插图页",
+        (Locale::En, Key::SpineBackMatter) => "Back matter",
+        (Locale::Zh, Key::SpineBackMatter) => "后记 / 版权页",
+        (Locale::En, Key::ResetSpineOrder) => "Default order",
+        (Locale::Zh, Key::ResetSpineOrder) => "恢复默认顺序",
+        (Locale::En, Key::SmartTypography) => "Smart typography (quotes, dashes, ellipses)",
+        (Locale::Zh, Key::SmartTypography) => "智能排版（引号、破折号、省略号）",
+        (Locale::En, Key::SmartTypographyHint) => {
+            "Turns straight quotes, -- and ... into curly quotes, dashes and ellipses using the book language's conventions. Chinese, Japanese and Korean text is left unchanged."
+        }
+        (Locale::Zh, Key::SmartTypographyHint) => {
+            "按书籍语言的习惯把直引号、-- 与 ... 换成弯引号、破折号与省略号；中文、日文与韩文内容保持不变。"
+        }
+        (Locale::En, Key::CleanWhitespace) => "Remove invisible characters and extra whitespace",
+        (Locale::Zh, Key::CleanWhitespace) => "清理不可见字符与多余空白",
+        (Locale::En, Key::CleanWhitespaceHint) => {
+            "Removes BOMs, zero-width spaces, soft hyphens and trailing spaces before the cleanup rules, and collapses three or more blank lines into one (blank-line scene breaks are lost)."
+        }
+        (Locale::Zh, Key::CleanWhitespaceHint) => {
+            "在清理规则之前去掉 BOM、零宽空格、软连字符与行尾空白，并把连续三个以上的空行合并为一个（以空行表示的场景分隔会消失）。"
+        }
+        (Locale::En, Key::WhitespaceRemoved) => "Removes {} character(s):",
+        (Locale::Zh, Key::WhitespaceRemoved) => "将去掉 {} 个字符：",
+        (Locale::En, Key::WhitespaceNothing) => "Nothing to remove.",
+        (Locale::Zh, Key::WhitespaceNothing) => "没有需要去掉的字符。",
+        (Locale::En, Key::WhitespaceBom) => "BOM {}",
+        (Locale::Zh, Key::WhitespaceBom) => "BOM {}",
+        (Locale::En, Key::WhitespaceZeroWidth) => "zero-width spaces {}",
+        (Locale::Zh, Key::WhitespaceZeroWidth) => "零宽空格 {}",
+        (Locale::En, Key::WhitespaceSoftHyphens) => "soft hyphens {}",
+        (Locale::Zh, Key::WhitespaceSoftHyphens) => "软连字符 {}",
+        (Locale::En, Key::WhitespaceTrailing) => "trailing spaces {}",
+        (Locale::Zh, Key::WhitespaceTrailing) => "行尾空白 {}",
+        (Locale::En, Key::WhitespaceBlankLines) => "blank lines {}",
+        (Locale::Zh, Key::WhitespaceBlankLines) => "空行 {}",
+        (Locale::En, Key::ReportWhitespace) => "Whitespace cleanup removed {} character(s):",
+        (Locale::Zh, Key::ReportWhitespace) => "空白清理去掉了 {} 个字符：",
+        (Locale::En, Key::CountWhitespace) => "Count",
+        (Locale::Zh, Key::CountWhitespace) => "统计",
+        (Locale::En, Key::TitleNumeralsLabel) => "Chapter numbers",
+        (Locale::Zh, Key::TitleNumeralsLabel) => "章节编号",
+        (Locale::En, Key::TitleNumeralsKeep) => "Keep as written",
+        (Locale::Zh, Key::TitleNumeralsKeep) => "保持原样",
+        (Locale::En, Key::TitleNumeralsArabic) => "Arabic (第103章)",
+        (Locale::Zh, Key::TitleNumeralsArabic) => "阿拉伯数字（第103章）",
+        (Locale::En, Key::TitleNumeralsChinese) => "Chinese (第一百零三章)",
+        (Locale::Zh, Key::TitleNumeralsChinese) => "中文数字（第一百零三章）",
+        (Locale::En, Key::TitleNumeralsHint) => {
+            "Rewrites numbers such as 第一百零三章 / 第103章 in chapter titles, volume names and the table of contents."
+        }
+        (Locale::Zh, Key::TitleNumeralsHint) => {
+            "统一章节标题、卷名与目录中“第N章”一类编号的数字写法。"
+        }
+        (Locale::En, Key::HeadingTemplate) => "Heading template",
+        (Locale::Zh, Key::HeadingTemplate) => "标题模板",
+        (Locale::En, Key::HeadingTemplateHint) => {
+            "One-line chapter heading, e.g. {label} · {title} or {num}. {title}. {label} is the full number (第12章, Chapter 3), {num} the number, {roman} its Roman numeral and {title} the subtitle. Leave empty for the label above the title."
+        }
+        (Locale::Zh, Key::HeadingTemplateHint) => {
+            "合成一行章节标题，如 {label} · {title} 或 {num}. {title}。{label} 为完整编号（第12章、Chapter 3），{num} 为数字，{roman} 为罗马数字，{title} 为副标题。留空时编号与副标题分两行显示。"
+        }
+        (Locale::En, Key::DescriptionFromText) => "Generate from text",
+        (Locale::Zh, Key::DescriptionFromText) => "从正文生成简介",
+        (Locale::En, Key::DescriptionLength) => "max chars",
+        (Locale::Zh, Key::DescriptionLength) => "字数上限",
+        (Locale::En, Key::TextHeaderFound) => "Book info found at the start of the text:",
+        (Locale::Zh, Key::TextHeaderFound) => "正文开头检测到书籍信息：",
+        (Locale::En, Key::FillFromTextHeader) => "Fill book info",
+        (Locale::Zh, Key::FillFromTextHeader) => "填入书籍信息",
+        (Locale::En, Key::StripTextHeader) => "Remove the title/author block before splitting",
+        (Locale::Zh, Key::StripTextHeader) => "分章前去掉开头的书名、作者信息",
+        (Locale::En, Key::StripTextHeaderHint) => {
+            "Drops lines such as 书名：… / 作者：… / 简介：… at the start of the text so they do not end up in a preface chapter."
+        }
+        (Locale::Zh, Key::StripTextHeaderHint) => {
+            "去掉正文开头“书名：”“作者：”“简介：”一类的信息块，避免被分进序章。"
+        }
+        (Locale::En, Key::MergeTextFiles) => "Merge files…",
+        (Locale::Zh, Key::MergeTextFiles) => "合并多个文件…",
+        (Locale::En, Key::MergeFilesTitle) => "Merge text files",
+        (Locale::Zh, Key::MergeFilesTitle) => "合并多个文本文件",
+        (Locale::En, Key::MergeFilesHint) => {
+            "One file per volume: the files are joined in this order and each one starts with a volume heading (第一卷 …), so the table of contents nests its chapters under the volume. Check the first-line preview to pick the right encoding."
+        }
+        (Locale::Zh, Key::MergeFilesHint) => {
+            "每卷一个文件：按列表顺序拼接，每个文件前插入卷标题（第一卷 …），目录中各文件的章节嵌套在对应卷下。可根据首行预览确认编码是否正确。"
+        }
+        (Locale::En, Key::MergeFilesEmpty) => "No files added yet.",
+        (Locale::Zh, Key::MergeFilesEmpty) => "尚未添加文件。",
+        (Locale::En, Key::MergeAndLoad) => "Merge and load",
+        (Locale::Zh, Key::MergeAndLoad) => "合并并载入",
+        (Locale::En, Key::MergedFiles) => "Merged from {} files",
+        (Locale::Zh, Key::MergedFiles) => "由 {} 个文件合并",
+        (Locale::En, Key::MergeFileColumn) => "File",
+        (Locale::Zh, Key::MergeFileColumn) => "文件",
+        (Locale::En, Key::MergeVolumeColumn) => "Volume",
+        (Locale::Zh, Key::MergeVolumeColumn) => "卷名",
+        (Locale::En, Key::MergeEncodingColumn) => "Encoding",
+        (Locale::Zh, Key::MergeEncodingColumn) => "编码",
+        (Locale::En, Key::EncodingAuto) => "Auto-detect",
+        (Locale::Zh, Key::EncodingAuto) => "自动识别",
+        (Locale::En, Key::ExtractEpubText) => "EPUB to TXT…",
+        (Locale::Zh, Key::ExtractEpubText) => "EPUB 转 TXT…",
+        (Locale::En, Key::ExtractedText) => "Extracted to {}; loaded for re-splitting.",
+        (Locale::Zh, Key::ExtractedText) => "已导出到 {}，并已载入以便重新分章。",
+        (Locale::En, Key::ExtractTextFailed) => "EPUB to TXT failed: {}",
+        (Locale::Zh, Key::ExtractTextFailed) => "EPUB 转 TXT 失败: {}",
+        (Locale::En, Key::ReadingPreview) => "Reading preview",
+        (Locale::Zh, Key::ReadingPreview) => "预览阅读",
+        (Locale::En, Key::ReadingPreviewHint) => {
+            "Flip through the chapters laid out as book pages with the current style; use ←/→ to turn pages."
+        }
+        (Locale::Zh, Key::ReadingPreviewHint) => "按当前版式把章节排成书页翻看，可用 ←/→ 键翻页。",
+        (Locale::En, Key::PreviousPage) => "Previous page",
+        (Locale::Zh, Key::PreviousPage) => "上一页",
+        (Locale::En, Key::NextPage) => "Next page",
+        (Locale::Zh, Key::NextPage) => "下一页",
+        (Locale::En, Key::PageNumber) => "Page {} of {}",
+        (Locale::Zh, Key::PageNumber) => "第 {} / {} 页",
+        (Locale::En, Key::ReadingTime) => "About {} min to read",
+        (Locale::Zh, Key::ReadingTime) => "约需 {} 分钟读完",
+        (Locale::En, Key::LongestChapter) => "Longest: #{} ({} chars)",
+        (Locale::Zh, Key::LongestChapter) => "最长：#{}（{} 字）",
+        (Locale::En, Key::ChapterLength) => "{} chars · {} min",
+        (Locale::Zh, Key::ChapterLength) => "{} 字 · {} 分钟",
+        (Locale::En, Key::ExportChapterList) => "Export list…",
+        (Locale::Zh, Key::ExportChapterList) => "导出章节列表…",
+        (Locale::En, Key::ImportChapterList) => "Import list…",
+        (Locale::Zh, Key::ImportChapterList) => "导入章节列表…",
+        (Locale::En, Key::ChapterListHint) => {
+            "CSV or JSON with index, title, and character count. Reorder, rename, or delete rows in a spreadsheet and import it back; the index refers to the exported chapter."
+        }
+        (Locale::Zh, Key::ChapterListHint) => {
+            "CSV 或 JSON，含序号、标题与字数。可在表格中调整行序、修改标题或删除行后导入；序号对应导出时的章节。"
+        }
+        (Locale::En, Key::ChapterListFilter) => "Chapter list",
+        (Locale::Zh, Key::ChapterListFilter) => "章节列表",
+        (Locale::En, Key::ChapterListExportFailed) => "Failed to export the chapter list: {}",
+        (Locale::Zh, Key::ChapterListExportFailed) => "导出章节列表失败: {}",
+        (Locale::En, Key::ChapterListImportFailed) => "Failed to import the chapter list: {}",
+        (Locale::Zh, Key::ChapterListImportFailed) => "导入章节列表失败: {}",
+        (Locale::En, Key::ChapterFind) => "Find & replace",
+        (Locale::Zh, Key::ChapterFind) => "跨章查找替换",
+        (Locale::En, Key::ChapterFindResults) => "{} matches in {} chapters",
+        (Locale::Zh, Key::ChapterFindResults) => "{} 处匹配，分布在 {} 章",
+        (Locale::En, Key::ReplaceSelected) => "Replace selected ({})",
+        (Locale::Zh, Key::ReplaceSelected) => "替换所选（{}）",
+        (Locale::En, Key::InsertIntoChapter) => "Insert into chapter",
+        (Locale::Zh, Key::InsertIntoChapter) => "插入到章节",
+        (Locale::En, Key::InsertIntoGallery) => "Gallery page only",
+        (Locale::Zh, Key::InsertIntoGallery) => "仅放在插图页",
+        (Locale::En, Key::ImageChapterMissing) => "#{} (no such chapter, goes to the gallery)",
+        (Locale::Zh, Key::ImageChapterMissing) => "#{}（章节不存在，放在插图页）",
+        (Locale::En, Key::DuplicateImage) => "Same content as image #{}; embedded only once",
+        (Locale::Zh, Key::DuplicateImage) => "与图片 #{} 内容相同，导出时只嵌入一次",
+        (Locale::En, Key::MergeDuplicate) => "Merge",
+        (Locale::Zh, Key::MergeDuplicate) => "合并",
+        (Locale::En, Key::MergeDuplicateHint) => {
+            "Remove this copy; the first image keeps its caption and takes over this chapter if it has none"
+        }
+        (Locale::Zh, Key::MergeDuplicateHint) => {
+            "移除这份副本；首张图片保留自己的说明，未指定章节时沿用此副本的章节"
+        }
+        (Locale::En, Key::InlineMarkup) => "Inline markup (**bold**, *italic*, ..dots..)",
+        (Locale::Zh, Key::InlineMarkup) => "行内标记（**粗体**、*斜体*、..着重号..）",
+        (Locale::En, Key::InlineMarkupHint) => {
+            "Renders emphasis markers in plain-text chapters and {base|ruby} annotations. Leave off for text that uses ..., * or braces literally."
+        }
+        (Locale::Zh, Key::InlineMarkupHint) => {
+            "在纯文本章节中渲染强调标记与 {基文|注音}；文中把 ...、* 或花括号当普通字符使用时请保持关闭。"
+        }
+        (Locale::En, Key::ProgressDecoding) => "Reading input file...",
+        (Locale::Zh, Key::ProgressDecoding) => "正在读取源文件...",
+        (Locale::En, Key::ResetSettings) => "Reset settings to defaults",
+        (Locale::Zh, Key::ResetSettings) => "恢复默认设置",
+        (Locale::En, Key::ResetSettingsHint) => {
+            "Restores style, output, and interface settings; loaded text, images, chapter edits, and book info are kept."
+        }
+        (Locale::Zh, Key::ResetSettingsHint) => {
+            "恢复版式、输出与界面设置；已载入的文本、图片、章节编辑和书籍信息保持不变。"
+        }
+        (Locale::ZhTw, _) => traditional(key),
+        (Locale::Ja, _) => japanese(key).unwrap_or_else(|| t(Locale::En, key)),
+    }
+}
+
+/// 繁体中文文案由简体文案经 s2tw 转换得到，结果按键缓存。
+fn traditional(key: Key) -> &'static str {
+    static CACHE: Lazy<Mutex<HashMap<Key, &'static str>>> = Lazy::new(Default::default);
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    cache.entry(key).or_insert_with(|| {
+        Box::leak(
+            ChineseConversion::S2TW
+                .convert(t(Locale::Zh, key))
+                .into_boxed_str(),
+        )
+    })
+}
+
+/// 内置的日文文案，只覆盖常用界面；其余回退到英文，可用翻译文件补全。
+fn japanese(key: Key) -> Option<&'static str> {
+    Some(match key {
+        Key::Subtitle => "TXT -> EPUB",
+        Key::ThemeDark => "ダーク",
+        Key::ThemeLight => "ライト",
+        Key::LanguageLabel => "言語",
+        Key::Sections => "セクション",
+        Key::QuickActions => "クイック操作",
+        Key::EditTxt => "TXT を編集",
+        Key::OpenTextFile => "📂 テキストファイルを開く",
+        Key::TextFileFilter => "テキストまたは EPUB ファイル",
+        Key::InputTxtPlaceholder => "TXT を EPUB に変換、ファイルを選択",
+        Key::ChapterEditor => "章エディター",
+        Key::UseChapterEdits => "章の編集結果を使用",
+        Key::IncludeGallery => "挿絵の章を追加",
+        Key::InsertToc => "目次ページを挿入",
+        Key::CoverPreview => "表紙プレビュー",
+        Key::CoverEmpty => "表紙が選択されていません",
+        Key::ClearCover => "表紙をクリア",
+        Key::GenerateCover => "表紙を生成",
+        Key::ExportSummary => "書き出しの概要",
+        Key::TitleLabel => "タイトル",
+        Key::AuthorLabel => "著者",
+        Key::OutputLabel => "出力: {}",
+        Key::TemplateLabel => "テンプレート: {}",
+        Key::ImagesLabel => "画像: {}",
+        Key::Basics => "基本情報",
+        Key::ChangeCover => "表紙を変更",
+        Key::InputImagePlaceholder => "表紙画像、ファイルを選択",
+        Key::FileTooLarge => "ファイルが大きすぎます（10MB 超）",
+        Key::ReadFailed => "読み込みに失敗しました: {}",
+        Key::Convert => "変換",
+        Key::PanelChapters => "章",
+        Key::PanelLayout => "レイアウト",
+        Key::PanelFonts => "フォント",
+        Key::PanelPublishInfo => "出版情報",
+        Key::PanelCss => "CSS と HTML",
+        Key::PanelImages => "画像",
+        Key::PanelMisc => "その他",
+        Key::PanelBatch => "一括変換",
+        Key::SplitMethod => "分割方法:",
+        Key::MethodRegex => "正規表現",
+        Key::MethodSimple => "簡易ルール",
+        Key::MethodConfig => "設定ファイル",
+        Key::MethodMarkdown => "Markdown 見出し",
+        Key::RegexPattern => "正規表現パターン:",
+        Key::RegexOk => "正規表現 OK",
+        Key::RegexError => "正規表現エラー: {}",
+        Key::PreviewChapters => "章をプレビュー",
+        Key::NoPreview => "プレビューはまだありません。",
+        Key::ChaptersCount => "章数: {}",
+        Key::ChapterIndex => "#{} {}",
+        Key::LineHeight => "行の高さ:",
+        Key::ParagraphSpacing => "段落の間隔:",
+        Key::IndentEm => "字下げ (em):",
+        Key::Template => "テンプレート:",
+        Key::CustomCss => "カスタム CSS:",
+        Key::ImportCss => "CSS を読み込む",
+        Key::ExportCss => "CSS を書き出す",
+        Key::FontSize => "フォントサイズ (px):",
+        Key::FontColor => "文字色:",
+        Key::ChooseFont => "フォントファイルを追加",
+        Key::ClearFont => "フォントをクリア",
+        Key::FontLabel => "フォント: {}",
+        Key::AddImage => "画像を追加",
+        Key::TotalImages => "合計: {}",
+        Key::NoImages => "画像がありません",
+        Key::ImageIndex => "画像 #{}",
+        Key::Caption => "キャプション:",
+        Key::Loading => "読み込み中...",
+        Key::BatchImport => "一括読み込み",
+        Key::ClearAll => "すべてクリア",
+        Key::Delete => "削除",
+        Key::LanguageField => "言語:",
+        Key::Publisher => "出版社:",
+        Key::Isbn => "ISBN:",
+        Key::Category => "カテゴリー:",
+        Key::PublishDate => "出版日:",
+        Key::Description => "説明:",
+        Key::OutputFolder => "出力フォルダー:",
+        Key::Browse => "参照",
+        Key::FilenameTemplate => "ファイル名テンプレート:",
+        Key::Current => "現在:",
+        Key::TocSettings => "目次の設定",
+        Key::TocTitle => "目次のタイトル:",
+        Key::OutputFormatLabel => "出力形式:",
+        Key::EpubVersionLabel => "EPUB バージョン:",
+        Key::OptimizeImages => "画像を最適化",
+        Key::BatchAddFiles => "ファイルを追加",
+        Key::BatchAddFolder => "フォルダーを追加",
+        Key::BatchClear => "キューをクリア",
+        Key::BatchStart => "一括変換を開始",
+        Key::BatchEmpty => "キューは空です。",
+        Key::BatchProgress => "進捗: {} / {}",
+        Key::BatchPending => "待機中",
+        Key::BatchDone => "完了: {}",
+        Key::BatchFailed => "失敗: {}",
+        Key::TextEditor => "テキストエディター",
+        Key::Save => "保存",
+        Key::Close => "閉じる",
+        Key::Chars => "文字数: {}",
+        Key::ConversionResult => "変換結果",
+        Key::ConversionWarnings => "警告:",
+        Key::ConversionCancelled => "変換をキャンセルしました。",
+        Key::ConversionRunning => "変換中...",
+        Key::Cancel => "キャンセル",
+        Key::ProgressSplitting => "章を分割中...",
+        Key::ProgressSplit => "{} 章が見つかりました",
+        Key::ProgressRendering => "章を生成中: {} / {}",
+        Key::ProgressWriting => "EPUB を書き込み中...",
+        Key::ProgressDecoding => "入力ファイルを読み込み中...",
+        Key::ConversionFailed => "変換に失敗しました",
+        Key::ConversionSuccess => "変換に成功しました",
+        Key::OutputFile => "出力ファイル:",
+        Key::OpenFolder => "フォルダーを開く",
+        Key::OpenFile => "ファイルを開く",
+        Key::PlaceholderUntitled => "無題",
+        Key::PlaceholderUnknown => "不明",
+        Key::ChapterEditorTitle => "章エディター",
+        Key::Refresh => "更新",
+        Key::AddChapter => "章を追加",
+        Key::NewChapter => "新しい章",
+        Key::Clear => "クリア",
+        Key::ChapterTitle => "章のタイトル:",
+        Key::ChapterContent => "章の本文:",
+        Key::Up => "上へ",
+        Key::Down => "下へ",
+        Key::MenuFile => "ファイル",
+        Key::OpenProject => "プロジェクトを開く...",
+        Key::SaveProject => "プロジェクトを保存",
+        Key::SaveProjectAs => "名前を付けてプロジェクトを保存...",
+        Key::ProjectFilter => "Reasypub プロジェクト",
+        Key::RecentFiles => "最近使ったファイル",
+        Key::RecentTexts => "最近のテキスト",
+        Key::RecentOutputs => "最近の出力",
+        Key::NoRecentFiles => "まだありません",
+        Key::ClearRecent => "履歴をクリア",
+        Key::Undo => "元に戻す",
+        Key::Redo => "やり直し",
+        Key::FindReplace => "検索と置換",
+        Key::FindHint => "検索",
+        Key::ReplaceHint => "置換後の文字列",
+        Key::FindRegex => "正規表現",
+        Key::FindMatchCase => "大文字と小文字を区別",
+        Key::FindMatches => "{} / {} 件",
+        Key::ReplaceOne => "置換",
+        Key::ReplaceAll => "すべて置換",
+        Key::ReplacedCount => "{} 件を置換しました",
+        Key::SelectAll => "すべて選択",
+        Key::SelectNone => "選択を解除",
+        Key::ChapterPreview => "プレビュー",
+        Key::PreviousChapter => "前へ",
+        Key::NextChapter => "次へ",
+        Key::Theme => "テーマ",
+        Key::ThemeSepia => "セピア",
+        Key::ThemeMidnight => "ミッドナイト",
+        Key::AccentColor => "アクセントカラー",
+        Key::PanelFill => "パネルの背景色",
+        Key::CornerRadius => "角の丸み",
+        Key::ResetTheme => "プリセットの配色に戻す",
+        Key::Help => "ヘルプ",
+        Key::KeyboardShortcuts => "キーボードショートカット",
+        Key::Accessibility => "アクセシビリティ",
+        Key::ResetSettings => "設定を既定値に戻す",
+        _ => return None,
+    })
+}
+
+/// 运行时载入的翻译：语言 → `Key` 变体名 → 译文。
+static RUNTIME_TRANSLATIONS: Lazy<RwLock<HashMap<Locale, HashMap<String, &'static str>>>> =
+    Lazy::new(Default::default);
+
+fn runtime_translation(locale: Locale, key: Key) -> Option<&'static str> {
+    let translations = RUNTIME_TRANSLATIONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    let table = translations.get(&locale)?;
+    table.get(format!("{key:?}").as_str()).copied()
+}
+
+/// 为 `locale` 添加或覆盖译文，返回载入的条数。
+///
+/// 译文在程序运行期间一直有效（只在启动时载入一次）。键名不对应任何 [`Key`] 的条目不会生效。
+pub fn add_translations(
+    locale: Locale,
+    entries: impl IntoIterator<Item = (String, String)>,
+) -> usize {
+    let mut translations = RUNTIME_TRANSLATIONS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    let table = translations.entry(locale).or_default();
+    let mut count = 0;
+    for (key, text) in entries {
+        table.insert(key, Box::leak(text.into_boxed_str()));
+        count += 1;
+    }
+    count
+}
+
+/// 解析翻译文件内容：`json` 为真时按 JSON 对象读取，否则按 TOML 读取；值必须是字符串。
+pub fn parse_translations(text: &str, json: bool) -> Result<Vec<(String, String)>, String> {
+    if !json {
+        let table: HashMap<String, String> = toml::from_str(text).map_err(|err| err.to_string())?;
+        return Ok(table.into_iter().collect());
+    }
+    let table: HashMap<String, String> =
+        serde_json::from_str(text).map_err(|err| err.to_string())?;
+    Ok(table.into_iter().collect())
+}
+
+/// 默认的翻译目录：环境变量 `REASYPUB_TRANSLATIONS`，否则为可执行文件旁的 `translations`。
+pub fn translations_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("REASYPUB_TRANSLATIONS") {
+        return Some(PathBuf::from(dir));
+    }
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join("translations"))
+}
+
+/// 载入目录中的全部翻译文件，返回无法识别或解析失败的文件说明；目录不存在时不做任何事。
+pub fn load_translation_dir(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+    let mut problems = Vec::new();
+    for path in paths {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let json = match extension.as_deref() {
+            Some("json") => true,
+            Some("toml") => false,
+            _ => continue,
+        };
+        let Some(locale) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(Locale::from_code)
+        else {
+            problems.push(format!("{}: unknown language code", path.display()));
+            continue;
+        };
+        let result = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| parse_translations(&text, json));
+        match result {
+            Ok(entries) => {
+                add_translations(locale, entries);
+            }
+            Err(err) => problems.push(format!("{}: {err}", path.display())),
+        }
     }
+    problems
 }
 
 pub fn t1<T: std::fmt::Display>(locale: Locale, key: Key, arg: T) -> String {
@@ -0,0 +1,305 @@
+//! 通过 SMTP 把生成的电子书发送到 Kindle 邮箱（Send to Kindle）。
+//!
+//! 邮件构造与 SMTP 会话交给 `lettre`（rustls 加密）：支持 SSL/TLS（常用 465 端口）或
+//! STARTTLS（常用 587 端口）与 AUTH PLAIN/LOGIN 登录，邮件带一个附件。发件地址需要先加入
+//! 亚马逊账户的“已认可的发件人电子邮箱列表”。网络发送只在桌面端可用。
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// SMTP 连接与读写超时（秒）。
+#[cfg(not(target_arch = "wasm32"))]
+const TIMEOUT_SECS: u64 = 60;
+
+/// 与 SMTP 服务器之间的加密方式。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SmtpSecurity {
+    /// 连接建立后直接握手 TLS。
+    #[default]
+    Tls,
+    /// 先以明文连接，再用 `STARTTLS` 升级。
+    StartTls,
+}
+
+impl SmtpSecurity {
+    pub const ALL: [Self; 2] = [Self::Tls, Self::StartTls];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tls => "SSL/TLS",
+            Self::StartTls => "STARTTLS",
+        }
+    }
+
+    pub fn default_port(self) -> u16 {
+        match self {
+            Self::Tls => 465,
+            Self::StartTls => 587,
+        }
+    }
+}
+
+/// 发信设置。密码只保存在内存中，不随应用设置写盘。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct KindleSettings {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    #[serde(skip)]
+    pub password: String,
+    /// 发件地址；留空时使用登录用户名。
+    pub from: String,
+    /// Kindle 收件地址（`@kindle.com`）。
+    pub to: String,
+    /// 转换成功后自动发送。
+    pub send_after_export: bool,
+}
+
+impl Default for KindleSettings {
+    fn default() -> Self {
+        Self {
+            smtp_host: String::new(),
+            smtp_port: SmtpSecurity::default().default_port(),
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            to: String::new(),
+            send_after_export: false,
+        }
+    }
+}
+
+impl KindleSettings {
+    pub fn sender(&self) -> &str {
+        let from = self.from.trim();
+        if from.is_empty() {
+            self.username.trim()
+        } else {
+            from
+        }
+    }
+
+    /// 检查发信所需的字段，返回第一个缺失或无效的字段说明。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.smtp_host.trim().is_empty() {
+            return Err("SMTP server is empty".to_string());
+        }
+        if self.smtp_port == 0 {
+            return Err("SMTP port is invalid".to_string());
+        }
+        // 换行等控制字符和尖括号会让地址逃出 SMTP 命令或邮件头，一律拒绝。
+        for (field, value) in [
+            ("SMTP server", self.smtp_host.trim()),
+            ("SMTP user name", self.username.trim()),
+            ("sender address", self.sender()),
+            ("Kindle address", self.to.trim()),
+        ] {
+            if value
+                .chars()
+                .any(|ch| ch.is_control() || ch == '<' || ch == '>')
+            {
+                return Err(format!("{field} contains invalid characters"));
+            }
+        }
+        if !self.sender().contains('@') {
+            return Err("sender address is invalid".to_string());
+        }
+        if !self.to.trim().contains('@') {
+            return Err("Kindle address is invalid".to_string());
+        }
+        if !self.username.trim().is_empty() && self.password.is_empty() {
+            return Err("SMTP password is empty".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+/// 把 `book` 作为附件发送到 Kindle 邮箱。
+#[cfg(not(target_arch = "wasm32"))]
+pub fn send_to_kindle(settings: &KindleSettings, book: &Path) -> Result<(), String> {
+    use lettre::Transport;
+
+    settings.validate()?;
+    let data = std::fs::read(book).map_err(|err| format!("{}: {err}", book.display()))?;
+    let filename = book
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "book.epub".to_string());
+    let message = build_message(settings, &filename, data)?;
+    transport(settings)?
+        .send(&message)
+        .map(|_| ())
+        .map_err(|err| format!("SMTP: {err}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn send_to_kindle(_settings: &KindleSettings, _book: &Path) -> Result<(), String> {
+    Err("Send to Kindle is only available in the desktop app".to_string())
+}
+
+/// 构造 `multipart/mixed` 邮件：一段说明文字加一个附件，主题为文件名（不含扩展名）。
+#[cfg(not(target_arch = "wasm32"))]
+fn build_message(
+    settings: &KindleSettings,
+    filename: &str,
+    data: Vec<u8>,
+) -> Result<lettre::Message, String> {
+    use lettre::message::header::ContentType;
+    use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+
+    let mailbox = |address: &str, field: &str| {
+        address
+            .parse::<Mailbox>()
+            .map_err(|err| format!("{field} is invalid: {err}"))
+    };
+    let stem = Path::new(filename)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string());
+    let content_type =
+        ContentType::parse(attachment_mime(filename)).map_err(|err| err.to_string())?;
+    let body = MultiPart::mixed()
+        .singlepart(SinglePart::plain(filename.to_string()))
+        .singlepart(Attachment::new(filename.to_string()).body(data, content_type));
+    lettre::Message::builder()
+        .from(mailbox(settings.sender(), "sender address")?)
+        .to(mailbox(settings.to.trim(), "Kindle address")?)
+        .subject(stem)
+        .multipart(body)
+        .map_err(|err| err.to_string())
+}
+
+/// 按加密方式、端口与登录信息配置 SMTP 连接；用户名为空时不登录。
+#[cfg(not(target_arch = "wasm32"))]
+fn transport(settings: &KindleSettings) -> Result<lettre::SmtpTransport, String> {
+    use lettre::transport::smtp::authentication::Credentials;
+
+    let host = settings.smtp_host.trim();
+    let builder = match settings.security {
+        SmtpSecurity::Tls => lettre::SmtpTransport::relay(host),
+        SmtpSecurity::StartTls => lettre::SmtpTransport::starttls_relay(host),
+    }
+    .map_err(|err| format!("{host}: {err}"))?;
+    let mut builder = builder
+        .port(settings.smtp_port)
+        .timeout(Some(std::time::Duration::from_secs(TIMEOUT_SECS)));
+    let username = settings.username.trim();
+    if !username.is_empty() {
+        builder = builder.credentials(Credentials::new(
+            username.to_string(),
+            settings.password.clone(),
+        ));
+    }
+    Ok(builder.build())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn attachment_mime(filename: &str) -> &'static str {
+    let lower = filename.to_ascii_lowercase();
+    if lower.ends_with(".epub") {
+        "application/epub+zip"
+    } else if lower.ends_with(".pdf") {
+        "application/pdf"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    fn settings() -> KindleSettings {
+        KindleSettings {
+            smtp_host: "smtp.example.com".to_string(),
+            username: "me@example.com".to_string(),
+            password: "secret".to_string(),
+            to: "reader@kindle.com".to_string(),
+            ..KindleSettings::default()
+        }
+    }
+
+    #[test]
+    fn validate_reports_missing_fields() {
+        assert!(settings().is_complete());
+        assert_eq!(settings().sender(), "me@example.com");
+        let mut missing = settings();
+        missing.to.clear();
+        assert!(missing.validate().unwrap_err().contains("Kindle"));
+        missing = settings();
+        missing.password.clear();
+        assert!(missing.validate().unwrap_err().contains("password"));
+        assert!(!KindleSettings::default().is_complete());
+    }
+
+    #[test]
+    fn validate_rejects_command_injection() {
+        let injected = "reader@kindle.com>\r\nRCPT TO:<other@example.com";
+        let cases: [fn(&mut KindleSettings, &str); 3] = [
+            |settings, value| settings.to = value.to_string(),
+            |settings, value| settings.from = value.to_string(),
+            |settings, value| settings.username = value.to_string(),
+        ];
+        for set in cases {
+            for value in [
+                injected,
+                "me@example.com\nBcc: x@example.com",
+                "<me@example.com>",
+            ] {
+                let mut settings = settings();
+                set(&mut settings, value);
+                let err = settings.validate().unwrap_err();
+                assert!(err.contains("invalid characters"), "{value:?}: {err}");
+            }
+        }
+    }
+
+    #[test]
+    fn build_message_attaches_book() {
+        let data: Vec<u8> = (0..=255).cycle().take(200).collect();
+        let message = build_message(&settings(), "三体.epub", data.clone()).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+        assert!(formatted.contains("From: me@example.com\r\n"));
+        assert!(formatted.contains("To: reader@kindle.com\r\n"));
+        assert!(formatted.contains("Content-Type: application/epub+zip"));
+
+        let body = formatted
+            .split("Content-Disposition: attachment;")
+            .nth(1)
+            .and_then(|part| part.split("\r\n\r\n").nth(1))
+            .unwrap();
+        let encoded: String = body
+            .lines()
+            .take_while(|line| !line.starts_with("--"))
+            .collect();
+        assert_eq!(STANDARD.decode(encoded).unwrap(), data);
+
+        let message = build_message(&settings(), "book.pdf", Vec::new()).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+        assert!(formatted.contains("Subject: book\r\n"));
+        let mut invalid = settings();
+        invalid.to = "not an address@".to_string();
+        assert!(build_message(&invalid, "book.pdf", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn transport_builds_for_both_security_modes() {
+        for security in SmtpSecurity::ALL {
+            let settings = KindleSettings {
+                security,
+                smtp_port: security.default_port(),
+                ..settings()
+            };
+            assert!(transport(&settings).is_ok());
+        }
+    }
+}
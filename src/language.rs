@@ -0,0 +1,250 @@
+//! 书籍语言：从正文推断语言，以及把用户填写的语言规范为 BCP 47 标签。
+//!
+//! 推断只区分简体中文、繁体中文、日文和英文；规范化接受 `zh_CN`、`ZH-hant`、
+//! “中文”“English” 等常见写法，输出大小写规范的标签（如 `zh-CN`、`zh-Hant`）。
+
+use crate::{ChapterDraft, zhconv};
+
+/// 推断语言时最多检查的字符数，足以判断整本书的语言。
+const DETECT_SAMPLE_CHARS: usize = 20_000;
+
+/// 从正文推断语言，返回 `zh-Hans`、`zh-Hant`、`ja` 或 `en`；没有可判断的文字时返回 `None`。
+///
+/// 含一定比例假名的判为日文；汉字占多数时按简繁专用字的多少区分简体与繁体；
+/// 其余以拉丁字母为主的文本判为英文。
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let sample: String = text.chars().take(DETECT_SAMPLE_CHARS).collect();
+    let (mut han, mut kana, mut latin) = (0usize, 0usize, 0usize);
+    for ch in sample.chars() {
+        match ch {
+            '\u{3040}'..='\u{309f}' | '\u{30a0}'..='\u{30ff}' => kana += 1,
+            '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => han += 1,
+            ch if ch.is_ascii_alphabetic() => latin += 1,
+            _ => {}
+        }
+    }
+    if kana > 0 && kana * 10 >= han + kana {
+        Some("ja")
+    } else if han > 0 && han * 2 >= latin {
+        let (simplified, traditional) = zhconv::script_counts(&sample);
+        Some(if traditional > simplified {
+            "zh-Hant"
+        } else {
+            "zh-Hans"
+        })
+    } else if latin > 0 {
+        Some("en")
+    } else {
+        None
+    }
+}
+
+/// 把用户填写的语言规范为 BCP 47 标签；不是合法标签时返回 `None`。
+///
+/// 下划线视为连字符，各子标签按规范调整大小写（语言小写、文字首字母大写、地区大写），
+/// 并识别“中文”“简体中文”“English”“日本語”等名称。
+pub fn normalize_language_tag(input: &str) -> Option<String> {
+    let input = input.trim().replace('_', "-");
+    if let Some(tag) = language_alias(&input.to_lowercase()) {
+        return Some(tag.to_string());
+    }
+    let subtags: Vec<&str> = input.split('-').collect();
+    let mut normalized = Vec::with_capacity(subtags.len());
+    let mut stage = Stage::Language;
+    for subtag in subtags {
+        let len = subtag.len();
+        if len == 0 || len > 8 || !subtag.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+            return None;
+        }
+        let alpha = subtag.chars().all(|ch| ch.is_ascii_alphabetic());
+        let digits = subtag.chars().all(|ch| ch.is_ascii_digit());
+        let lower = subtag.to_ascii_lowercase();
+        stage = match stage {
+            Stage::Language if alpha && (2..=3).contains(&len) => Stage::Extlang(0),
+            Stage::Language if alpha && len >= 4 => Stage::Script,
+            Stage::Language if lower == "x" => Stage::PrivateUse(0),
+            Stage::Language => return None,
+            Stage::PrivateUse(_) => Stage::PrivateUse(1),
+            Stage::Extension(_) if len >= 2 => Stage::Extension(1),
+            Stage::Extension(0) => return None,
+            _ if len == 1 => {
+                if lower == "x" {
+                    Stage::PrivateUse(0)
+                } else {
+                    Stage::Extension(0)
+                }
+            }
+            Stage::Extlang(count) if alpha && len == 3 && count < 3 => Stage::Extlang(count + 1),
+            Stage::Extlang(_) | Stage::Script if alpha && len == 4 => {
+                normalized.push(titlecase(&lower));
+                stage = Stage::Region;
+                continue;
+            }
+            Stage::Extlang(_) | Stage::Script | Stage::Region
+                if (alpha && len == 2) || (digits && len == 3) =>
+            {
+                normalized.push(subtag.to_ascii_uppercase());
+                stage = Stage::Variant;
+                continue;
+            }
+            Stage::Extlang(_) | Stage::Script | Stage::Region | Stage::Variant
+                if len >= 5 || (len == 4 && subtag.starts_with(|ch: char| ch.is_ascii_digit())) =>
+            {
+                Stage::Variant
+            }
+            _ => return None,
+        };
+        normalized.push(lower);
+    }
+    match stage {
+        Stage::Extension(0) | Stage::PrivateUse(0) => None,
+        _ => Some(normalized.join("-")),
+    }
+}
+
+/// 导出时写入的语言标签，以及填写的语言无效时的警告。
+///
+/// 填写的语言规范化后使用；未填写或无效时从章节正文推断，仍无法判断时使用 `zh-CN`。
+pub fn resolve_language(field: &str, chapters: &[ChapterDraft]) -> (String, Option<String>) {
+    let field = field.trim();
+    let warning = match normalize_language_tag(field) {
+        Some(tag) => return (tag, None),
+        None if field.is_empty() => None,
+        None => Some(format!(
+            "Language \"{field}\" is not a valid BCP 47 tag; the language was detected from the text instead."
+        )),
+    };
+    let mut sample = String::new();
+    for chapter in chapters {
+        if sample.chars().count() >= DETECT_SAMPLE_CHARS {
+            break;
+        }
+        sample.push_str(&chapter.title);
+        sample.push('\n');
+        sample.push_str(&chapter.content);
+        sample.push('\n');
+    }
+    let language = detect_language(&sample).unwrap_or("zh-CN");
+    (language.to_string(), warning)
+}
+
+/// 解析标签时所处的位置，按 BCP 47 规定的子标签顺序推进。
+#[derive(Clone, Copy)]
+enum Stage {
+    Language,
+    /// 已读到的扩展语言子标签个数（最多 3 个）。
+    Extlang(u8),
+    Script,
+    Region,
+    Variant,
+    /// 扩展或私用段中已读到的子标签个数，单字母引导符后至少要有一个。
+    Extension(u8),
+    PrivateUse(u8),
+}
+
+fn titlecase(lower: &str) -> String {
+    let mut chars = lower.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// 常见的语言名称与非标准写法。
+fn language_alias(lower: &str) -> Option<&'static str> {
+    Some(match lower {
+        "cn" | "chinese" | "中文" | "汉语" | "漢語" => "zh",
+        "zh-chs" | "简体中文" | "简中" | "簡體中文" => "zh-Hans",
+        "zh-cht" | "繁体中文" | "繁體中文" | "繁中" => "zh-Hant",
+        "english" | "英文" | "英语" | "英語" => "en",
+        "jp" | "japanese" | "日文" | "日语" | "日本語" | "日本语" => "ja",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_scripts_and_languages() {
+        assert_eq!(
+            detect_language("这是一个关于时间与记忆的故事。"),
+            Some("zh-Hans")
+        );
+        assert_eq!(
+            detect_language("這是一個關於時間與記憶的故事。"),
+            Some("zh-Hant")
+        );
+        assert_eq!(
+            detect_language("これは時間と記憶についての物語です。"),
+            Some("ja")
+        );
+        assert_eq!(detect_language("It was the best of times."), Some("en"));
+        assert_eq!(
+            detect_language("第1章 Hello 世界，这是开始。"),
+            Some("zh-Hans")
+        );
+        assert_eq!(detect_language("123 —— ……"), None);
+    }
+
+    #[test]
+    fn normalizes_common_tags() {
+        assert_eq!(normalize_language_tag("zh_cn").as_deref(), Some("zh-CN"));
+        assert_eq!(
+            normalize_language_tag(" ZH-hant-tw ").as_deref(),
+            Some("zh-Hant-TW")
+        );
+        assert_eq!(normalize_language_tag("EN").as_deref(), Some("en"));
+        assert_eq!(normalize_language_tag("es-419").as_deref(), Some("es-419"));
+        assert_eq!(
+            normalize_language_tag("de-CH-1996").as_deref(),
+            Some("de-CH-1996")
+        );
+        assert_eq!(
+            normalize_language_tag("en-US-x-Twain").as_deref(),
+            Some("en-US-x-twain")
+        );
+        assert_eq!(normalize_language_tag("中文").as_deref(), Some("zh"));
+        assert_eq!(normalize_language_tag("Japanese").as_deref(), Some("ja"));
+        assert_eq!(
+            normalize_language_tag("繁體中文").as_deref(),
+            Some("zh-Hant")
+        );
+    }
+
+    #[test]
+    fn resolves_invalid_or_missing_language_from_text() {
+        let chapters = [ChapterDraft {
+            title: "Chapter 1".to_string(),
+            content: "It was a bright cold day in April.".to_string(),
+            ..Default::default()
+        }];
+        assert_eq!(
+            resolve_language("zh_tw", &chapters),
+            ("zh-TW".to_string(), None)
+        );
+        assert_eq!(resolve_language("", &chapters), ("en".to_string(), None));
+        let (language, warning) = resolve_language("English (US)", &chapters);
+        assert_eq!(language, "en");
+        assert!(warning.unwrap().contains("English (US)"));
+        assert_eq!(resolve_language("", &[]), ("zh-CN".to_string(), None));
+    }
+
+    #[test]
+    fn rejects_malformed_tags() {
+        for tag in [
+            "",
+            "z",
+            "zh-",
+            "zh--CN",
+            "zh CN",
+            "简体",
+            "en-US-x",
+            "toolongtag",
+            "zh-a",
+        ] {
+            assert_eq!(normalize_language_tag(tag), None, "{tag:?}");
+        }
+    }
+}
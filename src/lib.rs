@@ -1,17 +1,39 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+pub mod calibre;
+pub mod chapter_list;
+pub mod cli;
 pub mod components;
 pub mod conversion;
+pub mod cover;
+pub mod devices;
+pub mod epub_import;
+pub mod epubcheck;
 pub mod epubworker;
 pub mod i18n;
+pub mod kindle;
+pub mod language;
+pub mod merge;
+pub mod metadata_lookup;
+pub mod numerals;
+mod ocf;
+pub mod pdfworker;
+pub mod project;
+pub mod punctuation;
+pub mod split_config;
+pub mod text_header;
+pub mod toc;
+pub mod typography;
+pub mod validation;
+pub mod whitespace;
+pub mod zhconv;
 
 pub use i18n::{Key, Locale, t, t1, t2};
 
 pub use app::MainApp;
 use bytes::Bytes;
 use egui::{ColorImage, TextureHandle};
-use image::ImageError;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -22,6 +44,13 @@ pub enum ConversionMethod {
     Regex,
     CustomConfig,
     SimpleRules,
+    MarkdownHeadings,
+    /// 没有可识别标题时按字数切分。
+    ByLength,
+    /// 按文本开头的内嵌目录中的标题分章。
+    FromToc,
+    /// 不用正则，按行长、标点、数字与空行等特征推测章节标题。
+    Heuristic,
 }
 
 impl ConversionMethod {
@@ -30,6 +59,10 @@ impl ConversionMethod {
             Self::SimpleRules => t(locale, Key::MethodSimple),
             Self::Regex => t(locale, Key::MethodRegex),
             Self::CustomConfig => t(locale, Key::MethodConfig),
+            Self::MarkdownHeadings => t(locale, Key::MethodMarkdown),
+            Self::ByLength => t(locale, Key::MethodLength),
+            Self::FromToc => t(locale, Key::MethodToc),
+            Self::Heuristic => t(locale, Key::MethodHeuristic),
         }
     }
 }
@@ -41,6 +74,10 @@ impl std::fmt::Display for ConversionMethod {
             Self::SimpleRules => write!(f, "Simple Rules"),
             Self::Regex => write!(f, "Regex"),
             Self::CustomConfig => write!(f, "From File"),
+            Self::MarkdownHeadings => write!(f, "Markdown Headings"),
+            Self::ByLength => write!(f, "By Length"),
+            Self::FromToc => write!(f, "From Table of Contents"),
+            Self::Heuristic => write!(f, "Heuristic"),
         }
     }
 }
@@ -53,6 +90,7 @@ pub enum PanelIndex {
     CSS,
     Images,
     Misc,
+    Batch,
 }
 
 impl PanelIndex {
@@ -65,6 +103,7 @@ impl PanelIndex {
             Self::CSS => t(locale, Key::PanelCss),
             Self::Images => t(locale, Key::PanelImages),
             Self::Misc => t(locale, Key::PanelMisc),
+            Self::Batch => t(locale, Key::PanelBatch),
         }
     }
 }
@@ -80,24 +119,44 @@ impl std::fmt::Display for PanelIndex {
             Self::CSS => write!(f, "CSS & HTML"),
             Self::Images => write!(f, "Images"),
             Self::Misc => write!(f, "Misc"),
+            Self::Batch => write!(f, "Batch"),
         }
     }
 }
 
+/// 根据小写扩展名推断图片 MIME 类型，未知扩展名按 PNG 处理。
+pub fn image_mime_from_extension(ext: &str) -> &'static str {
+    match ext {
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => "image/png",
+    }
+}
+
 /// 将分章相关输入哈希为稳定签名。
 ///
-/// 用于在文本、分章方法、正则或配置文件内容变化时，
+/// 用于在文本、分章前的预处理、分章方法、正则、标题排除规则、每章字数或配置文件内容变化时，
 /// 判断章节预览/章节编辑内容是否已过期。
 pub fn chapter_signature(
     text: &str,
+    preprocessing: &conversion::Preprocessing<'_>,
     method: ConversionMethod,
     regex: &str,
+    options: &conversion::SplitOptions<'_>,
     config_path: Option<&std::path::Path>,
 ) -> u64 {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     text.hash(&mut hasher);
+    preprocessing.strip_text_header.hash(&mut hasher);
+    preprocessing.clean_whitespace.hash(&mut hasher);
+    preprocessing.cleanup_rules.hash(&mut hasher);
+    preprocessing.punctuation.hash(&mut hasher);
     method.hash(&mut hasher);
     regex.hash(&mut hasher);
+    options.title_exclusions.hash(&mut hasher);
+    options.chapter_length.hash(&mut hasher);
     if let Some(path) = config_path {
         match std::fs::read(path) {
             Ok(bytes) => bytes.hash(&mut hasher),
@@ -110,22 +169,153 @@ pub fn chapter_signature(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::conversion::{Preprocessing, SplitOptions};
+    use crate::punctuation::PunctuationNormalization;
     use std::path::Path;
 
+    #[test]
+    fn cleanup_rules_apply_in_order() {
+        let text = "第1章 开始\n某某网首发\n正文广告词内容\n";
+        let rules = vec![
+            CleanupRule {
+                find: r"^某某网首发\n".to_string(),
+                regex: true,
+                ..Default::default()
+            },
+            CleanupRule {
+                find: "广告词".to_string(),
+                ..Default::default()
+            },
+            CleanupRule {
+                enabled: false,
+                find: "正文".to_string(),
+                ..Default::default()
+            },
+            CleanupRule {
+                find: r"第(\d+)章".to_string(),
+                replace: "Chapter $1".to_string(),
+                regex: true,
+                ..Default::default()
+            },
+        ];
+        let cleaned = apply_cleanup_rules(text, &rules).expect("apply");
+        assert_eq!(cleaned, "Chapter 1 开始\n正文内容\n");
+
+        let invalid = [CleanupRule {
+            find: "(".to_string(),
+            regex: true,
+            ..Default::default()
+        }];
+        assert!(apply_cleanup_rules(text, &invalid).is_err());
+    }
+
+    #[test]
+    fn description_from_text_skips_titles_and_truncates() {
+        let text = "书名\n作者：某人\n\n第一章 开端\n\n　　清晨的雾气笼罩着小镇，**少年**推开木门，望向远处的山。\n***\n　　他决定今天出发。\n";
+        assert_eq!(
+            description_from_text(text, 200).as_deref(),
+            Some("清晨的雾气笼罩着小镇，少年推开木门，望向远处的山。\n他决定今天出发。")
+        );
+        // 超出上限时在句末截断，句末太靠前时补省略号。
+        assert_eq!(
+            description_from_text(text, 30).as_deref(),
+            Some("清晨的雾气笼罩着小镇，少年推开木门，望向远处的山。")
+        );
+        assert_eq!(
+            description_from_text(text, 10).as_deref(),
+            Some("清晨的雾气笼罩着小镇…")
+        );
+        assert_eq!(description_from_text("第一章\n\n短行\n", 200), None);
+    }
+
     #[test]
     fn chapter_signature_changes_on_inputs() {
-        let base = chapter_signature("text", ConversionMethod::Regex, "", None);
-        let with_method = chapter_signature("text", ConversionMethod::SimpleRules, "", None);
-        let with_regex = chapter_signature("text", ConversionMethod::Regex, "abc", None);
+        let options = SplitOptions::default();
+        let pre = Preprocessing::default();
+        let base = chapter_signature("text", &pre, ConversionMethod::Regex, "", &options, None);
+        let with_method = chapter_signature(
+            "text",
+            &pre,
+            ConversionMethod::SimpleRules,
+            "",
+            &options,
+            None,
+        );
+        let with_regex =
+            chapter_signature("text", &pre, ConversionMethod::Regex, "abc", &options, None);
+        let exclusions = ["见书末$".to_string()];
+        let with_exclusion = chapter_signature(
+            "text",
+            &pre,
+            ConversionMethod::Regex,
+            "",
+            &SplitOptions {
+                title_exclusions: &exclusions,
+                ..options
+            },
+            None,
+        );
+        let with_length = chapter_signature(
+            "text",
+            &pre,
+            ConversionMethod::ByLength,
+            "",
+            &SplitOptions {
+                chapter_length: 100,
+                ..options
+            },
+            None,
+        );
         let with_path = chapter_signature(
             "text",
+            &pre,
             ConversionMethod::Regex,
             "",
+            &options,
             Some(Path::new("config.txt")),
         );
         assert_ne!(base, with_method);
         assert_ne!(base, with_regex);
+        assert_ne!(base, with_exclusion);
+        assert_ne!(
+            with_length,
+            chapter_signature("text", &pre, ConversionMethod::ByLength, "", &options, None)
+        );
         assert_ne!(base, with_path);
+
+        let rules = [CleanupRule {
+            find: "广告".to_string(),
+            ..Default::default()
+        }];
+        let with_rules = Preprocessing {
+            cleanup_rules: &rules,
+            ..pre
+        };
+        let with_punctuation = Preprocessing {
+            punctuation: PunctuationNormalization::FullWidth,
+            ..pre
+        };
+        let with_whitespace = Preprocessing {
+            clean_whitespace: true,
+            ..pre
+        };
+        let with_header = Preprocessing {
+            strip_text_header: true,
+            ..pre
+        };
+        for changed in [with_rules, with_punctuation, with_whitespace, with_header] {
+            assert_ne!(
+                base,
+                chapter_signature(
+                    "text",
+                    &changed,
+                    ConversionMethod::Regex,
+                    "",
+                    &options,
+                    None
+                )
+            );
+        }
     }
 
     #[test]
@@ -134,6 +324,29 @@ mod tests {
         assert_eq!(t2(Locale::En, Key::ChapterIndex, 1, "Title"), "#1 Title");
     }
 
+    #[test]
+    fn i18n_derives_traditional_and_falls_back_for_japanese() {
+        assert_eq!(t(Locale::ZhTw, Key::Basics), "基礎信息");
+        assert_eq!(t(Locale::Ja, Key::PanelMisc), "その他");
+        assert_eq!(t(Locale::Ja, Key::PdfHint), t(Locale::En, Key::PdfHint));
+        assert_eq!(Locale::from_code("zh_tw"), Some(Locale::ZhTw));
+        assert_eq!(Locale::from_code("fr"), None);
+    }
+
+    #[test]
+    fn i18n_loads_runtime_translations() {
+        let toml = i18n::parse_translations("NoImages = \"画像なし\"\n", false).expect("toml");
+        assert_eq!(toml, [("NoImages".to_string(), "画像なし".to_string())]);
+        let json = i18n::parse_translations(r#"{"NoImages": "画像なし", "Extra": "x"}"#, true)
+            .expect("json");
+        assert_eq!(json.len(), 2);
+        assert!(i18n::parse_translations(r#"{"NoImages": 1}"#, true).is_err());
+
+        assert_eq!(i18n::add_translations(Locale::Ja, json), 2);
+        assert_eq!(t(Locale::Ja, Key::NoImages), "画像なし");
+        assert_eq!(t(Locale::En, Key::NoImages), "No images");
+    }
+
     #[test]
     fn text_processor_simple_rules_detects_chapters() {
         let text = "第1章 开始\n内容\n\n第2章 继续\n更多";
@@ -165,6 +378,21 @@ mod tests {
         assert_eq!(drafts[1].title, "第二章 继续");
     }
 
+    #[test]
+    fn text_processor_skips_excluded_title_lines() {
+        let text = "第1章 开始\n他说\n第9章见本书末尾\n第2章 继续\n内容";
+        let exclusions = vec![Regex::new("见本书末尾$").unwrap()];
+        for pattern in [Pattern::ChineseChapter, Pattern::SimpleRules] {
+            let processor =
+                TextProcessor::new(pattern, text.to_string()).with_exclusions(exclusions.clone());
+            let drafts = processor.split_to_drafts();
+            assert_eq!(drafts.len(), 2);
+            assert_eq!(drafts[0].title, "第1章 开始");
+            assert!(drafts[0].content.contains("第9章见本书末尾"));
+            assert_eq!(drafts[1].title, "第2章 继续");
+        }
+    }
+
     #[test]
     fn text_processor_simple_rules_accepts_preface_markers() {
         let text = "序章\n内容\n第一章 开始\n内容";
@@ -175,6 +403,85 @@ mod tests {
         assert_eq!(drafts[1].title, "第一章 开始");
     }
 
+    #[test]
+    fn text_processor_markdown_headings_strip_hashes() {
+        let text = "# Chapter 1\nHello\n### Scene\nMore\n## Chapter 2\nWorld";
+        let processor = TextProcessor::new(Pattern::MarkdownHeading, text.to_string());
+        let drafts = processor.split_to_drafts();
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].title, "Chapter 1");
+        assert_eq!(drafts[0].content, "Hello\n### Scene\nMore");
+        assert_eq!(drafts[1].title, "Chapter 2");
+    }
+
+    #[test]
+    fn assign_volumes_groups_chapters_under_volume_titles() {
+        let mut chapters: Vec<ChapterDraft> = [
+            "序章",
+            "第一卷 风起",
+            "第1章 出发",
+            "第二卷 云涌",
+            "第2章 归来",
+        ]
+        .iter()
+        .map(|title| ChapterDraft::from_raw(title))
+        .collect();
+        assign_volumes(&mut chapters);
+        assert_eq!(chapters[0].volume, None);
+        assert_eq!(chapters[1].volume, None);
+        assert_eq!(chapters[2].volume.as_deref(), Some("第一卷 风起"));
+        assert_eq!(chapters[3].volume, None);
+        assert_eq!(chapters[4].volume.as_deref(), Some("第二卷 云涌"));
+        assert!(!is_volume_title("第1章 卷土重来"));
+    }
+
+    #[test]
+    fn take_back_matter_uses_trailing_marker() {
+        assert!(is_back_matter_marker("【后记】"));
+        assert!(is_back_matter_marker("後記：致读者"));
+        assert!(is_back_matter_marker("  afterword"));
+        assert!(!is_back_matter_marker("后记得那天的雨"));
+
+        let mut chapters = vec![
+            ChapterDraft::from_raw("第1章 出发\n正文"),
+            ChapterDraft::from_raw("后记\n感谢阅读。\n\n再见。"),
+        ];
+        let back = take_back_matter(&mut chapters);
+        assert_eq!(
+            back,
+            Some(("后记".to_string(), "感谢阅读。\n\n再见。".to_string()))
+        );
+        assert_eq!(chapters.len(), 1);
+
+        let mut chapters = vec![ChapterDraft::from_raw(
+            "第1章 出发\n正文\n【后记】\n写完了。\n",
+        )];
+        let back = take_back_matter(&mut chapters);
+        assert_eq!(back, Some(("【后记】".to_string(), "写完了。".to_string())));
+        assert_eq!(chapters[0].content, "正文");
+
+        // 唯一一章的标题是标记、或标记前没有正文时不截取。
+        let mut chapters = vec![ChapterDraft::from_raw("后记\n全文")];
+        assert_eq!(take_back_matter(&mut chapters), None);
+        assert_eq!(chapters[0].content, "全文");
+    }
+
+    #[test]
+    fn source_format_detects_markdown_extensions() {
+        assert_eq!(
+            SourceFormat::from_path(Path::new("book.md")),
+            SourceFormat::Markdown
+        );
+        assert_eq!(
+            SourceFormat::from_path(Path::new("book.MARKDOWN")),
+            SourceFormat::Markdown
+        );
+        assert_eq!(
+            SourceFormat::from_path(Path::new("book.txt")),
+            SourceFormat::PlainText
+        );
+    }
+
     #[test]
     fn chapter_draft_from_raw_splits_title_and_body() {
         let raw = "Title line\nSecond line\nThird line";
@@ -193,6 +500,56 @@ mod tests {
             assert!(!template.description(Locale::En).trim().is_empty());
         }
     }
+
+    #[test]
+    fn spine_order_drops_duplicates_and_appends_missing_sections() {
+        assert_eq!(SpineSection::ordered(&[]), SpineSection::ALL);
+        assert_eq!(
+            SpineSection::ordered(&[
+                SpineSection::Chapters,
+                SpineSection::Cover,
+                SpineSection::Chapters,
+            ]),
+            [
+                SpineSection::Chapters,
+                SpineSection::Cover,
+                SpineSection::FrontMatter,
+                SpineSection::TocPage,
+                SpineSection::Gallery,
+                SpineSection::BackMatter,
+            ]
+        );
+    }
+
+    #[test]
+    fn book_uuid_is_stable_and_overridable() {
+        let mut info = BookInfo {
+            title: "书名".to_string(),
+            creators: vec![Creator::new("作者", CreatorRole::Author)],
+            ..Default::default()
+        };
+        let derived = info.derived_uuid();
+        assert_eq!(derived.get_version_num(), 5);
+        // 固定值：已生成的书重新导出时标识不能变。
+        assert_eq!(derived.to_string(), "786e8c91-5418-5090-95a8-318056072d4b");
+        assert_eq!(info.uuid(), Ok(derived));
+        assert_eq!(info.clone().derived_uuid(), derived);
+
+        // 译者不参与生成，书名改动后标识随之改变。
+        info.creators
+            .push(Creator::new("译者", CreatorRole::Translator));
+        assert_eq!(info.derived_uuid(), derived);
+        info.title = "另一本".to_string();
+        assert_ne!(info.derived_uuid(), derived);
+
+        info.identifier = "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8".to_string();
+        assert_eq!(
+            info.uuid().map(|id| id.to_string()).as_deref(),
+            Ok("67e55044-10b1-426f-9247-bb680e5fe0c8")
+        );
+        info.identifier = "my-book".to_string();
+        assert!(info.uuid().is_err());
+    }
 }
 
 #[derive(Default)]
@@ -209,6 +566,8 @@ pub struct ImageFileReader {
     pub path: Option<std::path::PathBuf>,
     pub texture: Option<TextureHandle>, // 存储纹理句柄
     pub caption: Option<String>,
+    pub chapter: Option<usize>, // 插入到的章节（从 0 开始）
+    pub content_hash: u64,      // 读入图片时计算一次，用于查找重复插图
 }
 
 impl std::fmt::Debug for ImageFileReader {
@@ -218,6 +577,7 @@ impl std::fmt::Debug for ImageFileReader {
             .field("error", &self.error)
             .field("content_len", &self.content.len())
             .field("caption", &self.caption)
+            .field("chapter", &self.chapter)
             .finish()
     }
 }
@@ -233,16 +593,29 @@ impl ImageFileReader {
         }
     }
 
-    /// 解码图片并生成纹理。
-    fn load_texture(&self, ctx: &egui::Context) -> Result<TextureHandle, ImageError> {
-        // 解码图片。
-        let img = image::load_from_memory(&self.content)?;
-        let rgba = img.to_rgba8();
-
-        // 转换为 egui 需要的像素格式。
-        let size = [rgba.width() as _, rgba.height() as _];
-        let pixels = rgba.as_flat_samples();
-        let color_image = ColorImage::from_rgba_unmultiplied(size, pixels.as_slice());
+    /// 解码图片并生成纹理；SVG 按自身尺寸渲染。
+    fn load_texture(&self, ctx: &egui::Context) -> Result<TextureHandle, String> {
+        let color_image = if self.is_svg() {
+            let tree = resvg::usvg::Tree::from_data(&self.content, &Default::default())
+                .map_err(|err| err.to_string())?;
+            let size = tree.size().to_int_size();
+            let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height())
+                .ok_or("invalid SVG size")?;
+            resvg::render(&tree, Default::default(), &mut pixmap.as_mut());
+            ColorImage::from_rgba_premultiplied(
+                [size.width() as _, size.height() as _],
+                pixmap.data(),
+            )
+        } else {
+            // 解码图片。
+            let img = image::load_from_memory(&self.content).map_err(|err| err.to_string())?;
+            let rgba = img.to_rgba8();
+
+            // 转换为 egui 需要的像素格式。
+            let size = [rgba.width() as _, rgba.height() as _];
+            let pixels = rgba.as_flat_samples();
+            ColorImage::from_rgba_unmultiplied(size, pixels.as_slice())
+        };
 
         // 上传纹理到 GPU。
         Ok(ctx.load_texture(
@@ -252,6 +625,13 @@ impl ImageFileReader {
         ))
     }
 
+    pub fn is_svg(&self) -> bool {
+        self.path
+            .as_ref()
+            .and_then(|path| path.extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    }
+
     fn texture_key(&self) -> String {
         if let Some(path) = &self.path {
             return format!("image:{}", path.to_string_lossy());
@@ -268,6 +648,194 @@ pub struct ImageAsset {
     pub bytes: Bytes,
     pub mime: String,
     pub caption: Option<String>,
+    /// 插入到第几章（从 0 开始）的正文末尾；`None` 或超出章节数时放在插图页。
+    pub chapter: Option<usize>,
+}
+
+/// 转换输出格式：EPUB 电子书，或按 `TextStyle` 排版的可打印 PDF。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum OutputFormat {
+    #[default]
+    Epub,
+    Pdf,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 2] = [OutputFormat::Epub, OutputFormat::Pdf];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputFormat::Epub => "EPUB",
+            OutputFormat::Pdf => "PDF",
+        }
+    }
+}
+
+/// 输出文件已存在时的处理方式。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum OverwritePolicy {
+    /// 直接覆盖已有文件。
+    #[default]
+    Overwrite,
+    /// 在文件名后追加 ` (1)`、` (2)` … 直到不与已有文件重名。
+    Number,
+    /// 不写出文件，返回 `BuildError::OutputExists`，由调用方询问是否覆盖。
+    Ask,
+}
+
+impl OverwritePolicy {
+    pub const ALL: [OverwritePolicy; 3] = [
+        OverwritePolicy::Ask,
+        OverwritePolicy::Number,
+        OverwritePolicy::Overwrite,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            OverwritePolicy::Overwrite => t(locale, Key::OverwriteAlways),
+            OverwritePolicy::Number => t(locale, Key::OverwriteNumber),
+            OverwritePolicy::Ask => t(locale, Key::OverwriteAsk),
+        }
+    }
+}
+
+/// PDF 纸张尺寸。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PdfPageSize {
+    A4,
+    #[default]
+    A5,
+    B6,
+    Letter,
+}
+
+impl PdfPageSize {
+    pub const ALL: [PdfPageSize; 4] = [
+        PdfPageSize::A4,
+        PdfPageSize::A5,
+        PdfPageSize::B6,
+        PdfPageSize::Letter,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PdfPageSize::A4 => "A4",
+            PdfPageSize::A5 => "A5",
+            PdfPageSize::B6 => "B6",
+            PdfPageSize::Letter => "Letter",
+        }
+    }
+
+    /// 纸张宽高（毫米）。
+    pub fn size_mm(self) -> (f32, f32) {
+        match self {
+            PdfPageSize::A4 => (210.0, 297.0),
+            PdfPageSize::A5 => (148.0, 210.0),
+            PdfPageSize::B6 => (125.0, 176.0),
+            PdfPageSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+/// PDF 输出的版面设置；字号、行距、缩进与字体沿用 `TextStyle`。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PdfOptions {
+    pub page_size: PdfPageSize,
+    /// 四边页边距（毫米）。
+    pub margin_mm: f32,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PdfPageSize::default(),
+            margin_mm: 18.0,
+        }
+    }
+}
+
+/// 构建时的图片优化：缩小超出尺寸上限的图片，并重新压缩 JPEG/PNG。
+///
+/// 结果不比原图小时保留原图；GIF、WebP 等其他格式原样嵌入。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ImageOptimization {
+    pub enabled: bool,
+    /// 图片长边的像素上限，超出时等比缩小。
+    pub max_dimension: u32,
+    /// JPEG 重新编码质量（1–100）；PNG 始终无损压缩。
+    pub jpeg_quality: u8,
+    /// 目标阅读器的图片兼容性配置，不受 `enabled` 影响。
+    pub compatibility: ImageCompatibility,
+    /// 墨水屏优化：所有图片转为提高对比度的 8 位灰度 PNG/JPEG（WebP 与 GIF 一并转码），
+    /// 不受 `enabled` 影响。
+    pub grayscale: bool,
+    /// 把 SVG 封面与插图按长边上限栅格化为 PNG，供不支持 SVG 的阅读器使用；
+    /// 不受 `enabled` 影响。
+    pub rasterize_svg: bool,
+}
+
+impl Default for ImageOptimization {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_dimension: 1600,
+            jpeg_quality: 85,
+            compatibility: ImageCompatibility::default(),
+            grayscale: false,
+            rasterize_svg: false,
+        }
+    }
+}
+
+/// EPUB 容器的 ZIP 压缩设置。
+///
+/// JPEG、PNG、WebP 等图片本身已压缩，再用 Deflate 压缩往往只是浪费时间，甚至略微变大。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ZipCompression {
+    /// Deflate 压缩级别（0–9）；0 表示所有条目都不压缩（Stored）。
+    pub level: u8,
+    /// 图片条目不压缩（Stored）。
+    pub store_images: bool,
+}
+
+impl Default for ZipCompression {
+    fn default() -> Self {
+        Self {
+            level: 6,
+            store_images: false,
+        }
+    }
+}
+
+/// 按目标阅读器决定哪些图片格式需要在构建时转码为 JPEG/PNG。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageCompatibility {
+    /// 保留原始格式。
+    #[default]
+    Original,
+    /// 旧版阅读软件：WebP 转码为 JPEG（含透明通道时为 PNG）。
+    NoWebp,
+    /// 墨水屏阅读器：WebP 与动图 GIF 均转码，动图只保留首帧。
+    EInk,
+}
+
+impl ImageCompatibility {
+    pub const ALL: [ImageCompatibility; 3] = [
+        ImageCompatibility::Original,
+        ImageCompatibility::NoWebp,
+        ImageCompatibility::EInk,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Original => t(locale, Key::ImageCompatOriginal),
+            Self::NoWebp => t(locale, Key::ImageCompatNoWebp),
+            Self::EInk => t(locale, Key::ImageCompatEInk),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -276,11 +844,108 @@ pub struct FontAsset {
     pub family: String,
     pub bytes: Bytes,
     pub mime: String,
+    /// 该字体承担的排版角色；为空时只声明 `@font-face`，供自定义 CSS 引用。
+    pub roles: Vec<FontRole>,
+}
+
+/// 嵌入字体可承担的排版角色，每个角色至多对应一款字体。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontRole {
+    Body,
+    Heading,
+    ChapterLabel,
+}
+
+impl FontRole {
+    pub const ALL: [FontRole; 3] = [FontRole::Body, FontRole::Heading, FontRole::ChapterLabel];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Body => t(locale, Key::FontRoleBody),
+            Self::Heading => t(locale, Key::FontRoleHeading),
+            Self::ChapterLabel => t(locale, Key::FontRoleChapterLabel),
+        }
+    }
+
+    /// 该角色在样式表中对应的选择器。
+    pub fn css_selector(self) -> &'static str {
+        match self {
+            Self::Body => "body, p, li",
+            Self::Heading => "h1, h2, h3",
+            Self::ChapterLabel => ".chapter-label, p.nt, p.et",
+        }
+    }
+}
+
+/// 用户选择的一款字体文件及其角色（持久化在 `TextStyle.fonts` 中）。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct FontChoice {
+    pub path: String,
+    pub roles: Vec<FontRole>,
+}
+
+/// 署名角色，对应 MARC relator 代码。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CreatorRole {
+    #[default]
+    Author,
+    Translator,
+    Illustrator,
+    Editor,
+}
+
+impl CreatorRole {
+    pub const ALL: [CreatorRole; 4] = [
+        CreatorRole::Author,
+        CreatorRole::Translator,
+        CreatorRole::Illustrator,
+        CreatorRole::Editor,
+    ];
+
+    /// MARC relator 代码，写入 `opf:role` 或 `role` 细化元数据。
+    pub fn marc_code(self) -> &'static str {
+        match self {
+            Self::Author => "aut",
+            Self::Translator => "trl",
+            Self::Illustrator => "ill",
+            Self::Editor => "edt",
+        }
+    }
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Author => t(locale, Key::RoleAuthor),
+            Self::Translator => t(locale, Key::RoleTranslator),
+            Self::Illustrator => t(locale, Key::RoleIllustrator),
+            Self::Editor => t(locale, Key::RoleEditor),
+        }
+    }
+}
+
+/// 一条署名：作者写入 `dc:creator`，其余角色写入 `dc:contributor`。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct Creator {
+    pub name: String,
+    pub role: CreatorRole,
+}
+
+impl Creator {
+    pub fn new(name: impl Into<String>, role: CreatorRole) -> Self {
+        Self {
+            name: name.into(),
+            role,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct BookInfo {
-    pub author: String,
+    /// 作者、译者等署名，按显示顺序排列。旧版工程中的 `author` 字符串读作一位作者。
+    #[serde(alias = "author", deserialize_with = "deserialize_creators")]
+    pub creators: Vec<Creator>,
     pub title: String,
     pub language: String,
     pub publisher: String,
@@ -288,6 +953,259 @@ pub struct BookInfo {
     pub category: String,
     pub publish_date: String,
     pub description: String,
+    /// 所属系列（丛书）名称，为空时不写入系列元数据。
+    pub series: String,
+    /// 在系列中的序号，如 `2` 或 `2.5`；不是数字时忽略。
+    pub series_index: String,
+    /// 用户自定义的其他元数据，按顺序写入 OPF。
+    pub custom_metadata: Vec<MetadataEntry>,
+    /// 出版物唯一标识（UUID，可带 `urn:uuid:` 前缀）；为空时由书名和作者生成。
+    pub identifier: String,
+    /// schema.org 无障碍元数据。
+    pub accessibility: Accessibility,
+}
+
+/// 写入 OPF 的 schema.org 无障碍元数据，供 Ace 等检查工具与阅读器识别。
+///
+/// 多值字段用逗号分隔，如 `textual, visual`；字段为空时不写入对应元素。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Accessibility {
+    /// `schema:accessMode`，如 `textual`、`visual`。
+    pub access_modes: String,
+    /// `schema:accessibilityFeature`，如 `structuralNavigation`、`tableOfContents`。
+    pub features: String,
+    /// `schema:accessibilityHazard`，无风险时为 `none`。
+    pub hazards: String,
+    /// `schema:accessibilitySummary`，一段描述无障碍特性的文字。
+    pub summary: String,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            access_modes: "textual".to_string(),
+            features: "structuralNavigation, tableOfContents, readingOrder".to_string(),
+            hazards: "none".to_string(),
+            summary: "Reflowable text with chapter headings, a navigable table of contents \
+                      and a logical reading order."
+                .to_string(),
+        }
+    }
+}
+
+impl Accessibility {
+    /// 追加一项 `accessibilityFeature`，已存在时不重复。
+    pub fn add_feature(&mut self, feature: &str) {
+        if self
+            .features
+            .split([',', '，'])
+            .any(|existing| existing.trim() == feature)
+        {
+            return;
+        }
+        if !self.features.trim().is_empty() {
+            self.features.push_str(", ");
+        }
+        self.features.push_str(feature);
+    }
+
+    /// 按 schema.org 属性名列出各个取值，顺序与 OPF 中一致。
+    pub fn properties(&self) -> Vec<(&'static str, &str)> {
+        let mut properties = Vec::new();
+        for (property, values) in [
+            ("accessMode", &self.access_modes),
+            ("accessibilityFeature", &self.features),
+            ("accessibilityHazard", &self.hazards),
+        ] {
+            properties.extend(
+                values
+                    .split([',', '，'])
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(|value| (property, value)),
+            );
+        }
+        let summary = self.summary.trim();
+        if !summary.is_empty() {
+            properties.push(("accessibilitySummary", summary));
+        }
+        properties
+    }
+}
+
+/// 一条自定义元数据。
+///
+/// 以 `dc:` 开头的键写成同名 Dublin Core 元素（如 `dc:rights`），其余写成
+/// `<meta name="…" content="…"/>`（如 Calibre 自定义列 `calibre:user_metadata:#genre`）。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct MetadataEntry {
+    pub key: String,
+    pub value: String,
+}
+
+impl MetadataEntry {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// `dc:` 元素的本地名；键不以 `dc:` 开头时为 `None`。
+    pub fn dc_element(&self) -> Option<&str> {
+        self.key.trim().strip_prefix("dc:")
+    }
+
+    /// 键能否作为元素名或 `name` 属性写入 OPF，值为空的条目也视为无效。
+    pub fn is_valid(&self) -> bool {
+        let key = self.key.trim();
+        if key.is_empty() || self.value.trim().is_empty() {
+            return false;
+        }
+        match self.dc_element() {
+            Some(name) => !name.is_empty() && name.chars().all(|ch| ch.is_ascii_alphabetic()),
+            None => key
+                .chars()
+                .all(|ch| ch.is_alphanumeric() || matches!(ch, ':' | '_' | '-' | '.' | '#')),
+        }
+    }
+}
+
+/// 生成出版物标识所用的 UUID v5 命名空间。
+const IDENTIFIER_NAMESPACE: uuid::Uuid = uuid::uuid!("11c6ed3c-803e-4785-aeb2-e8ad8e66ba2d");
+
+impl BookInfo {
+    /// 以逗号连接的作者姓名（不含译者等），用于文件名、封面和预览。
+    pub fn author(&self) -> String {
+        self.creators
+            .iter()
+            .filter(|creator| creator.role == CreatorRole::Author)
+            .map(|creator| creator.name.trim())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// 由书名和作者确定性生成的标识（UUID v5），同一本书重复生成时保持不变，
+    /// 阅读器据此把新版本识别为同一本书。
+    pub fn derived_uuid(&self) -> uuid::Uuid {
+        let name = format!("{}\u{1f}{}", self.title.trim(), self.author());
+        uuid::Uuid::new_v5(&IDENTIFIER_NAMESPACE, name.as_bytes())
+    }
+
+    /// 写入 `dc:identifier` 的标识：用户填写的 UUID 优先，未填写时用 [`Self::derived_uuid`]。
+    ///
+    /// 填写的内容不是 UUID 时返回错误，调用方可改用生成的标识并提示用户。
+    pub fn uuid(&self) -> Result<uuid::Uuid, uuid::Error> {
+        let identifier = self.identifier.trim();
+        if identifier.is_empty() {
+            Ok(self.derived_uuid())
+        } else {
+            uuid::Uuid::parse_str(identifier)
+        }
+    }
+
+    /// 第一位作者的姓名，供主面板的作者输入框编辑；没有作者时在最前面插入一条。
+    pub fn primary_author_mut(&mut self) -> &mut String {
+        let index = match self
+            .creators
+            .iter()
+            .position(|creator| creator.role == CreatorRole::Author)
+        {
+            Some(index) => index,
+            None => {
+                self.creators
+                    .insert(0, Creator::new(String::new(), CreatorRole::Author));
+                0
+            }
+        };
+        &mut self.creators[index].name
+    }
+}
+
+/// 从正文生成简介时的默认字数上限。
+pub const DEFAULT_DESCRIPTION_CHARS: usize = 200;
+
+/// 从正文开头摘取简介：跳过章节标题、Markdown 标题、分隔行与不成句的短行（书名、作者等），
+/// 依次取段落直到约 `max_chars` 个字符，超出时尽量在句末截断。没有可用段落时返回 `None`。
+pub fn description_from_text(text: &str, max_chars: usize) -> Option<String> {
+    const SHORT_LINE_CHARS: usize = 20;
+    const SENTENCE_END: [char; 10] = ['。', '！', '？', '…', '”', '」', '.', '!', '?', '"'];
+    let mut paragraphs = Vec::new();
+    let mut total = 0;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || TextProcessor::is_chapter_title_line(line)
+            || epubworker::is_scene_break(line)
+        {
+            continue;
+        }
+        // 简介是纯文字，无论是否启用行内标记都去掉强调符号。
+        let line = epubworker::strip_inline_markup(line, true);
+        let chars = line.chars().count();
+        if chars < SHORT_LINE_CHARS && !line.ends_with(SENTENCE_END) {
+            continue;
+        }
+        total += chars;
+        paragraphs.push(line);
+        if total >= max_chars {
+            break;
+        }
+    }
+    if paragraphs.is_empty() {
+        return None;
+    }
+    let chars: Vec<char> = paragraphs.join("\n").chars().collect();
+    if chars.len() <= max_chars {
+        return Some(chars.into_iter().collect());
+    }
+    let cut = &chars[..max_chars];
+    // 句末位置太靠前时直接截断并补省略号，避免简介过短。
+    Some(match cut.iter().rposition(|ch| SENTENCE_END.contains(ch)) {
+        Some(end) if end + 1 >= max_chars / 2 => cut[..=end].iter().collect(),
+        _ => cut.iter().collect::<String>() + "…",
+    })
+}
+
+/// 读取署名列表，兼容旧版只有一个作者字符串的格式。
+fn deserialize_creators<'de, D>(deserializer: D) -> Result<Vec<Creator>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct CreatorsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for CreatorsVisitor {
+        type Value = Vec<Creator>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("an author name or a list of creators")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(if value.trim().is_empty() {
+                Vec::new()
+            } else {
+                vec![Creator::new(value, CreatorRole::Author)]
+            })
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut creators = Vec::new();
+            while let Some(creator) = seq.next_element()? {
+                creators.push(creator);
+            }
+            Ok(creators)
+        }
+    }
+
+    deserializer.deserialize_any(CreatorsVisitor)
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
@@ -296,6 +1214,16 @@ pub struct TocOptions {
     pub insert_toc_page: bool,
     pub toc_title_override: String,
     pub include_gallery_in_toc: bool,
+    /// 按“卷/部”分组，生成两级嵌套目录。
+    pub nest_volumes: bool,
+    /// 目录页的栏数（1–3），多栏适合章节很多的书。
+    pub columns: u8,
+    /// 目录页条目后绘制点状引导线。
+    pub dotted_leaders: bool,
+    /// 目录页中卷名显示为分组标题，其下章节不再缩进。
+    pub volume_headings: bool,
+    /// 目录页显示章节编号（如“第1章”）；关闭时只显示编号后的标题。
+    pub show_chapter_numbers: bool,
 }
 
 impl Default for TocOptions {
@@ -304,10 +1232,255 @@ impl Default for TocOptions {
             insert_toc_page: true,
             toc_title_override: String::new(),
             include_gallery_in_toc: true,
+            nest_volumes: false,
+            columns: 1,
+            dotted_leaders: false,
+            volume_headings: false,
+            show_chapter_numbers: true,
+        }
+    }
+}
+
+/// EPUB spine 中可以调整先后顺序的主要部分。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpineSection {
+    /// 封面页（`cover.xhtml`）。
+    Cover,
+    /// 书名页、版权页与献词；前言总是紧接在正文之前。
+    FrontMatter,
+    /// 内嵌目录页。
+    TocPage,
+    /// 前言、卷首页与各章。
+    Chapters,
+    /// 插图页。
+    Gallery,
+    /// 后记或版权页。
+    BackMatter,
+}
+
+impl SpineSection {
+    /// 默认顺序。
+    pub const ALL: [SpineSection; 6] = [
+        SpineSection::Cover,
+        SpineSection::FrontMatter,
+        SpineSection::TocPage,
+        SpineSection::Chapters,
+        SpineSection::Gallery,
+        SpineSection::BackMatter,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Cover => t(locale, Key::SpineCover),
+            Self::FrontMatter => t(locale, Key::SpineFrontMatter),
+            Self::TocPage => t(locale, Key::SpineTocPage),
+            Self::Chapters => t(locale, Key::SpineChapters),
+            Self::Gallery => t(locale, Key::SpineGallery),
+            Self::BackMatter => t(locale, Key::SpineBackMatter),
+        }
+    }
+
+    /// 按 `order` 排列的全部部分：去掉重复项，缺少的部分按默认顺序补在最后。
+    pub fn ordered(order: &[SpineSection]) -> Vec<SpineSection> {
+        let mut sections = Vec::with_capacity(Self::ALL.len());
+        for section in order.iter().chain(Self::ALL.iter()) {
+            if !sections.contains(section) {
+                sections.push(*section);
+            }
+        }
+        sections
+    }
+}
+
+/// 正文之前的前置页。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontMatterPage {
+    TitlePage,
+    Copyright,
+    Dedication,
+    Preface,
+}
+
+impl FrontMatterPage {
+    pub const ALL: [FrontMatterPage; 4] = [
+        FrontMatterPage::TitlePage,
+        FrontMatterPage::Copyright,
+        FrontMatterPage::Dedication,
+        FrontMatterPage::Preface,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::TitlePage => t(locale, Key::TitlePage),
+            Self::Copyright => t(locale, Key::CopyrightPage),
+            Self::Dedication => t(locale, Key::DedicationPage),
+            Self::Preface => t(locale, Key::PrefacePage),
+        }
+    }
+}
+
+/// 版权页的默认模板；变量与文件名模板相同，变量全部为空的行不输出。
+pub const DEFAULT_COPYRIGHT_TEMPLATE: &str =
+    "{书名}\n{作者}\n{出版社}\n{日期}\nISBN {isbn}\n版权所有 侵权必究";
+
+/// 前置页设置：书名页按书籍信息排版，其余页面使用填写的文字，文字为空时不生成。
+///
+/// 文字按行分段，可使用文件名模板的变量（如 `{书名}`、`{作者}`）。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct FrontMatter {
+    pub title_page: bool,
+    pub copyright: String,
+    pub dedication: String,
+    pub preface: String,
+}
+
+impl FrontMatter {
+    /// 会生成的前置页，按书中顺序排列。
+    pub fn pages(&self) -> Vec<FrontMatterPage> {
+        FrontMatterPage::ALL
+            .into_iter()
+            .filter(|page| match page {
+                FrontMatterPage::TitlePage => self.title_page,
+                _ => !self.text(*page).trim().is_empty(),
+            })
+            .collect()
+    }
+
+    /// 页面的文字；书名页没有可编辑文字，返回空字符串。
+    pub fn text(&self, page: FrontMatterPage) -> &str {
+        match page {
+            FrontMatterPage::TitlePage => "",
+            FrontMatterPage::Copyright => &self.copyright,
+            FrontMatterPage::Dedication => &self.dedication,
+            FrontMatterPage::Preface => &self.preface,
+        }
+    }
+}
+
+/// 书末页面的类型，决定 guide 引用与 `epub:type`。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackMatterKind {
+    #[default]
+    Afterword,
+    Colophon,
+}
+
+impl BackMatterKind {
+    pub const ALL: [BackMatterKind; 2] = [BackMatterKind::Afterword, BackMatterKind::Colophon];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Afterword => t(locale, Key::Afterword),
+            Self::Colophon => t(locale, Key::Colophon),
+        }
+    }
+}
+
+/// 书末的后记或版权页，排在插图页之后，文字为空时不生成。
+///
+/// 文字与前置页相同：每行一段，可使用文件名模板的变量。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct BackMatter {
+    pub kind: BackMatterKind,
+    /// 页面与目录条目的标题；为空时按类型使用“后记”或“版权页”。
+    pub title: String,
+    pub text: String,
+    /// 从正文末尾的后记标记（见 `take_back_matter`）截取内容；截取到时取代 `text`。
+    pub from_text: bool,
+}
+
+/// 分章前应用的一条查找/替换规则（文本清理）。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct CleanupRule {
+    pub enabled: bool,
+    pub find: String,
+    pub replace: String,
+    /// 为 `true` 时 `find` 按正则（多行模式）匹配，`replace` 可引用 `$1` 等分组。
+    pub regex: bool,
+}
+
+impl Default for CleanupRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            find: String::new(),
+            replace: String::new(),
+            regex: false,
         }
     }
 }
 
+/// 按顺序应用文本清理规则；未启用或查找内容为空的规则会被跳过。
+///
+/// 正则规则以多行模式编译，`^`/`$` 匹配行首行尾，便于整行删除水印或广告。
+pub fn apply_cleanup_rules(text: &str, rules: &[CleanupRule]) -> Result<String, regex::Error> {
+    let mut output = text.to_string();
+    for rule in rules {
+        if !rule.enabled || rule.find.is_empty() {
+            continue;
+        }
+        output = if rule.regex {
+            let re = Regex::new(&format!("(?m){}", rule.find))?;
+            re.replace_all(&output, rule.replace.as_str()).into_owned()
+        } else {
+            output.replace(&rule.find, &rule.replace)
+        };
+    }
+    Ok(output)
+}
+
+/// 输入文本的源格式。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SourceFormat {
+    #[default]
+    PlainText,
+    Markdown,
+}
+
+impl SourceFormat {
+    /// 按扩展名识别源格式：`.md` / `.markdown` 视为 Markdown，其余按纯文本处理。
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("md") | Some("markdown") => SourceFormat::Markdown,
+            _ => SourceFormat::PlainText,
+        }
+    }
+}
+
+/// 输出的 EPUB 规范版本。
+///
+/// EPUB 2 仅依赖 NCX 目录，兼容老旧阅读器；EPUB 3 额外生成带 `epub:type`
+/// 语义的导航文档与 HTML5 章节文档。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum EpubVersion {
+    #[default]
+    Epub2,
+    Epub3,
+}
+
+impl EpubVersion {
+    pub const ALL: [EpubVersion; 2] = [EpubVersion::Epub2, EpubVersion::Epub3];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EpubVersion::Epub2 => "EPUB 2",
+            EpubVersion::Epub3 => "EPUB 3",
+        }
+    }
+
+    pub fn is_epub3(self) -> bool {
+        matches!(self, EpubVersion::Epub3)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Copy)]
 pub enum CssTemplate {
     Classic,
@@ -369,6 +1542,10 @@ p {
   margin-top: 0.85em;
   margin-bottom: 0.3em;
 }
+blockquote.quote { margin: 1em 2em; padding: 0; color: #3f3f3f; }
+blockquote.quote p { margin-bottom: 0.4em; }
+.verse { margin: 1.1em 0 1.1em 2.5em; }
+.verse p.verse-stanza { text-indent: 0; text-align: left; margin: 0 0 0.9em 0; }
 "#
             }
             CssTemplate::Modern => {
@@ -399,6 +1576,10 @@ p {
   margin-top: 0.55em;
   margin-bottom: 0.2em;
 }
+blockquote.quote { margin: 1em 0 1em 1em; padding: 0.1em 0 0.1em 0.9em; border-left: 3px solid #2f5d50; }
+blockquote.quote p { margin-bottom: 0.4em; }
+.verse { margin: 1em 0 1em 2em; font-style: italic; }
+.verse p.verse-stanza { text-indent: 0; text-align: left; margin: 0 0 0.8em 0; }
 "#
             }
             CssTemplate::Clean => {
@@ -415,6 +1596,9 @@ p {
   -moz-hyphens: auto;
 }
 .chapter-label { font-size: 0.78em; color: #3b3b3b; letter-spacing: 0.16em; text-align: center; }
+blockquote.quote { margin: 1em 1.5em; padding: 0; }
+.verse { margin: 1em 0 1em 2em; }
+.verse p.verse-stanza { text-indent: 0; text-align: left; margin: 0 0 0.8em 0; }
 "#
             }
             CssTemplate::Elegant => {
@@ -451,6 +1635,10 @@ p {
   margin-top: 0.9em;
   margin-bottom: 0.35em;
 }
+blockquote.quote { margin: 1.2em 2.2em; padding: 0; font-style: italic; color: #4a3d30; }
+blockquote.quote p { margin-bottom: 0.45em; }
+.verse { margin: 1.3em 0; text-align: center; }
+.verse p.verse-stanza { text-indent: 0; text-align: center; margin: 0 0 1em 0; letter-spacing: 0.04em; }
 "#
             }
             CssTemplate::Folio => {
@@ -486,6 +1674,10 @@ p {
   margin-top: 0.75em;
   margin-bottom: 0.3em;
 }
+blockquote.quote { margin: 1.2em 2em; padding: 0.5em 0; border-top: 1px solid #cbbda9; border-bottom: 1px solid #cbbda9; color: #4a3d30; }
+blockquote.quote p { margin-bottom: 0.4em; }
+.verse { margin: 1.3em 0 1.3em 3em; }
+.verse p.verse-stanza { text-indent: 0; text-align: left; margin: 0 0 1em 0; letter-spacing: 0.03em; }
 "#
             }
             CssTemplate::Fantasy => {
@@ -522,6 +1714,10 @@ p {
   margin-top: 0.82em;
   margin-bottom: 0.35em;
 }
+blockquote.quote { margin: 1.2em 1.6em; padding: 0.6em 0.9em; border-left: 2px solid #bca68a; background: #f8f1e6; color: #4a3424; }
+blockquote.quote p { margin-bottom: 0.4em; }
+.verse { margin: 1.4em 0; font-family: "kt", "KaiTi", "STKaiti", "Kaiti SC", serif; color: #7a3a24; text-align: center; }
+.verse p.verse-stanza { text-indent: 0; duokan-text-indent: 0em; text-align: center; margin: 0 0 1em 0; letter-spacing: 0.08em; }
 "#
             }
             CssTemplate::Minimal => {
@@ -539,6 +1735,9 @@ p {
   -moz-hyphens: auto;
 }
 .chapter-label { font-size: 0.75em; color: #2f2f2f; letter-spacing: 0.18em; text-align: center; }
+blockquote.quote { margin: 1em 2em; padding: 0; }
+.verse { margin: 1em 0 1em 2em; }
+.verse p.verse-stanza { text-indent: 0; text-align: left; margin: 0 0 0.8em 0; }
 "#
             }
         }
@@ -599,13 +1798,34 @@ pub struct TextStyle {
     pub text_indent: f32,
     pub font_size: f32,
     pub font_color: egui::Color32,
-    pub font_path: String,
+    /// 嵌入字体及其角色分配。
+    pub fonts: Vec<FontChoice>,
     pub css_template: CssTemplate,
     pub custom_css: String,
     pub extra_body_class: String,
     pub extra_chapter_class: String,
     pub extra_title_class: String,
     pub extra_paragraph_class: String,
+    /// 正文标题去掉“第N章”等编号，仅保留副标题；目录仍使用完整标题。
+    pub strip_heading_number: bool,
+    /// 标准版式的章节标题模板，如 `{label} · {title}`、`{num}. {title}`，合成一行标题；
+    /// 为空时分为序号标签与副标题两行，英文“Chapter N”的序号显示为罗马数字。
+    pub heading_template: String,
+    /// 嵌入字体前按书中实际用到的字符裁剪字形（仅支持 TrueType 轮廓字体）。
+    pub subset_font: bool,
+    /// 按 IDPF 字体混淆算法处理嵌入字体并写入 `encryption.xml`，用于分发授权字体。
+    pub obfuscate_fonts: bool,
+    /// 竖排（从右到左翻页），用于古典中文与日文小说。
+    pub vertical: bool,
+    /// 把按固定字数硬换行的文本重新合并为段落。
+    pub reflow_paragraphs: bool,
+    /// 段落重排的灵敏度（0–1），越高越倾向于把不以句末标点结尾的短行并入下一行。
+    pub reflow_sensitivity: f32,
+    /// 按书籍语言把直引号、`--` 与 `...` 换成弯引号、破折号与省略号，见 [`typography`]。
+    pub smart_typography: bool,
+    /// 识别 `**粗体**`、`*斜体*`、`..着重号..` 与注音 `{基文|注音}`；网络小说常见的 `...`、
+    /// 算式与花括号容易误判，默认关闭。Markdown 章节的强调不受此开关影响。
+    pub inline_markup: bool,
 }
 
 impl Default for TextStyle {
@@ -616,21 +1836,169 @@ impl Default for TextStyle {
             text_indent: 2.0,
             font_size: 16.0,
             font_color: egui::Color32::BLACK,
-            font_path: String::new(),
+            fonts: Vec::new(),
             css_template: CssTemplate::Classic,
             custom_css: String::new(),
             extra_body_class: String::new(),
             extra_chapter_class: String::new(),
             extra_title_class: String::new(),
             extra_paragraph_class: String::new(),
+            strip_heading_number: false,
+            heading_template: String::new(),
+            subset_font: false,
+            obfuscate_fonts: false,
+            vertical: false,
+            reflow_paragraphs: false,
+            reflow_sensitivity: 0.2,
+            smart_typography: false,
+            inline_markup: false,
         }
     }
 }
 
+impl TextStyle {
+    /// 启用段落重排时的灵敏度，供切分段落使用。
+    pub fn reflow(&self) -> Option<f32> {
+        self.reflow_paragraphs.then_some(self.reflow_sensitivity)
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ChapterDraft {
     pub title: String,
     pub content: String,
+    /// 仅作用于本章的 CSS 片段，渲染时限定在该章专属的 body class 下。
+    pub custom_css: String,
+    /// 所属卷标题；为 `Some` 时该章在目录中嵌套到对应卷之下。
+    pub volume: Option<String>,
+    /// 追加到本章 `<body>` 上的 class，可配合全书自定义 CSS 使用。
+    pub css_class: String,
+    /// 本章头图：沿用全书设置、隐藏，或使用单独的图片。
+    pub header_image: ChapterHeaderImage,
+    /// 本章的版式变体，用于序章、番外等需要不同排版的章节。
+    pub variant: ChapterVariant,
+    /// 自定义正则的命名分组提取出的结构化标题；标题被改动后不再使用。
+    pub heading: Option<ChapterHeading>,
+}
+
+/// 由自定义正则中的 `num` / `title` 命名分组得到的章节标题结构。
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChapterHeading {
+    /// 章节编号部分，如 `第12章`、`Chapter 3`。
+    pub number: Option<String>,
+    /// 编号之后的副标题。
+    pub title: Option<String>,
+}
+
+impl ChapterHeading {
+    /// 从一次正则匹配的命名分组提取标题结构；`vol` 分组单独返回。
+    ///
+    /// 没有 `num` 与 `title` 分组（或均为空）时返回 `None`。
+    pub fn from_captures(caps: &regex::Captures<'_>) -> (Option<Self>, Option<String>) {
+        let group = |name: &str| {
+            caps.name(name)
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let heading = Self {
+            number: group("num"),
+            title: group("title"),
+        };
+        let heading = (heading.number.is_some() || heading.title.is_some()).then_some(heading);
+        (heading, group("vol"))
+    }
+
+    /// 编号与副标题以一个空格连接，作为统一格式的章节标题。
+    pub fn display(&self) -> String {
+        [self.number.as_deref(), self.title.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl ChapterDraft {
+    /// 标题未被改动时返回结构化标题。
+    pub fn structured_heading(&self) -> Option<&ChapterHeading> {
+        self.heading
+            .as_ref()
+            .filter(|heading| heading.display() == self.title.trim())
+    }
+
+    /// 按正则的命名分组填充结构化标题与所属卷，并把标题规范为“编号 副标题”。
+    ///
+    /// 依次尝试 `patterns`，使用第一条能匹配标题行的正则。
+    pub fn apply_named_groups(&mut self, patterns: &[Regex]) {
+        let Some(caps) = patterns.iter().find_map(|re| re.captures(&self.title)) else {
+            return;
+        };
+        let (heading, volume) = ChapterHeading::from_captures(&caps);
+        if let Some(volume) = volume {
+            self.volume = Some(volume);
+        }
+        if let Some(heading) = heading {
+            self.title = heading.display();
+            self.heading = Some(heading);
+        }
+    }
+}
+
+/// 正则是否含有章节标题相关的命名分组（`vol` / `num` / `title`）。
+pub fn has_heading_groups(re: &Regex) -> bool {
+    re.capture_names()
+        .flatten()
+        .any(|name| matches!(name, "vol" | "num" | "title"))
+}
+
+/// 单章头图覆盖。
+#[derive(Clone, Debug, Default)]
+pub enum ChapterHeaderImage {
+    /// 沿用全书的章节头图。
+    #[default]
+    Inherit,
+    /// 本章不显示头图。
+    Hidden,
+    /// 本章使用单独的头图。
+    Custom(ImageAsset),
+}
+
+/// 单章版式变体。
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChapterVariant {
+    /// 与全书模板一致。
+    #[default]
+    Standard,
+    /// 只保留标题，不显示章节序号与装饰线，适合番外、后记等。
+    Plain,
+    /// 标题与正文居中排列，适合序章、题记等短篇章。
+    Centered,
+}
+
+impl ChapterVariant {
+    pub const ALL: [ChapterVariant; 3] = [
+        ChapterVariant::Standard,
+        ChapterVariant::Plain,
+        ChapterVariant::Centered,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            Self::Standard => t(locale, Key::ChapterVariantStandard),
+            Self::Plain => t(locale, Key::ChapterVariantPlain),
+            Self::Centered => t(locale, Key::ChapterVariantCentered),
+        }
+    }
+
+    /// 追加到 `<body>` 上的变体 class；标准版式不追加。
+    pub fn body_class(self) -> Option<&'static str> {
+        match self {
+            Self::Standard => None,
+            Self::Plain => Some("chapter-plain"),
+            Self::Centered => Some("chapter-centered"),
+        }
+    }
 }
 
 impl ChapterDraft {
@@ -642,7 +2010,86 @@ impl ChapterDraft {
             .trim()
             .to_string();
         let content = lines.collect::<Vec<_>>().join("\n");
-        Self { title, content }
+        Self {
+            title,
+            content,
+            ..Default::default()
+        }
+    }
+}
+
+/// 判断标题是否为“卷/部”级标题，如 `第一卷 风起`、`卷二`、`第3部`。
+pub fn is_volume_title(title: &str) -> bool {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^\s*(?:第[0-9０-９一二三四五六七八九十零〇○百千万两]+[卷部]|卷[0-9０-９一二三四五六七八九十零〇○百千万两]+)",
+        )
+        .unwrap()
+    });
+    RE.is_match(title)
+}
+
+/// 为卷标题之后的章节标注所属卷。
+///
+/// 卷标题本身保持为顶层条目；已显式设置 `volume` 的章节不会被覆盖。
+pub fn assign_volumes(chapters: &mut [ChapterDraft]) {
+    assign_volumes_by(chapters, is_volume_title);
+}
+
+/// 判断一行是否为书末后记的标记，如 `后记`、`【跋】`、`後記：致读者`、`Afterword`。
+pub fn is_back_matter_marker(line: &str) -> bool {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?i)^\s*[【\[〔（(]?\s*(?:后记|後記|跋|完本感言|Afterword|Postscript|Colophon)\s*[】\]〕）)]?(?:[\s:：·—-].*)?$",
+        )
+        .unwrap()
+    });
+    RE.is_match(line)
+}
+
+/// 从最后一章取出后记，返回标题与正文。
+///
+/// 最后一章的标题本身是后记标记时整章取出（至少保留一章）；否则从最后一章正文中
+/// 最后一个标记行截取到末尾，标记行之前须有正文。
+pub fn take_back_matter(chapters: &mut Vec<ChapterDraft>) -> Option<(String, String)> {
+    if chapters.len() > 1
+        && chapters
+            .last()
+            .is_some_and(|c| is_back_matter_marker(&c.title))
+    {
+        let chapter = chapters.pop()?;
+        return Some((
+            chapter.title.trim().to_string(),
+            chapter.content.trim().to_string(),
+        ));
+    }
+    let last = chapters.last_mut()?;
+    let mut offset = 0;
+    let mut marker = None;
+    for line in last.content.split_inclusive('\n') {
+        if is_back_matter_marker(line) && !last.content[..offset].trim().is_empty() {
+            marker = Some((offset, line.len()));
+        }
+        offset += line.len();
+    }
+    let (start, len) = marker?;
+    let title = last.content[start..start + len].trim().to_string();
+    let text = last.content[start + len..].trim().to_string();
+    last.content.truncate(start);
+    last.content.truncate(last.content.trim_end().len());
+    Some((title, text))
+}
+
+/// 与 `assign_volumes` 相同，但由 `is_volume` 判断哪些标题是卷标题。
+pub fn assign_volumes_by(chapters: &mut [ChapterDraft], is_volume: impl Fn(&str) -> bool) {
+    let mut current: Option<String> = None;
+    for chapter in chapters.iter_mut() {
+        if is_volume(&chapter.title) {
+            current = Some(chapter.title.trim().to_string());
+            chapter.volume = None;
+        } else if chapter.volume.is_none() {
+            chapter.volume = current.clone();
+        }
     }
 }
 
@@ -651,6 +2098,7 @@ pub enum Pattern {
     ChineseChapter,
     EnglishChapter,
     SimpleRules,
+    MarkdownHeading,
     Custom(Regex),
 }
 
@@ -671,6 +2119,12 @@ impl Pattern {
                     Lazy::new(|| Regex::new(r"(?m)^\s*Chapter\s*[0-9]+[^\n]*").unwrap());
                 &RE
             }
+            Pattern::MarkdownHeading => {
+                // 仅匹配一级/二级标题，`###` 及更深层级视为正文。
+                static RE: Lazy<Regex> =
+                    Lazy::new(|| Regex::new(r"(?m)^[ \t]*#{1,2}[ \t]+\S[^\n]*").unwrap());
+                &RE
+            }
             Pattern::SimpleRules => {
                 // SimpleRules 不直接依赖正则，这里返回“匹配全部”的后备正则。
                 static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r".*").unwrap());
@@ -689,18 +2143,54 @@ impl Pattern {
 pub(crate) struct TextProcessor {
     pattern: Pattern,
     text: String,
+    /// 标题排除规则：标题所在整行匹配任一规则时不作为章节边界。
+    exclusions: Vec<Regex>,
 }
 
 impl TextProcessor {
     pub(crate) fn new(pattern: Pattern, text: String) -> Self {
-        Self { pattern, text }
+        Self {
+            pattern,
+            text,
+            exclusions: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_exclusions(mut self, exclusions: Vec<Regex>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    fn is_excluded_line(&self, line: &str) -> bool {
+        let line = line.trim();
+        self.exclusions.iter().any(|re| re.is_match(line))
+    }
+
+    /// `start` 所在整行是否被排除规则命中。
+    fn is_excluded_at(&self, text: &str, start: usize) -> bool {
+        if self.exclusions.is_empty() {
+            return false;
+        }
+        let line_start = text[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = text[start..]
+            .find('\n')
+            .map_or(text.len(), |pos| start + pos);
+        self.is_excluded_line(&text[line_start..line_end])
     }
 
     pub(crate) fn split_to_drafts(&self) -> Vec<ChapterDraft> {
-        self.split_by_pattern()
+        let mut drafts: Vec<ChapterDraft> = self
+            .split_by_pattern()
             .into_iter()
             .map(|raw| ChapterDraft::from_raw(&raw))
-            .collect()
+            .collect();
+        if matches!(self.pattern, Pattern::MarkdownHeading) {
+            // Markdown 标题去掉 `#` 前缀后作为章节标题。
+            for draft in &mut drafts {
+                draft.title = draft.title.trim_start_matches('#').trim().to_string();
+            }
+        }
+        drafts
     }
 
     fn split_by_pattern(&self) -> Vec<String> {
@@ -720,11 +2210,14 @@ impl TextProcessor {
 
         let mut result = Vec::new();
         let mut last_end = 0;
+        let headings: Vec<_> = re
+            .find_iter(&t)
+            .filter(|mat| !self.is_excluded_at(&t, mat.start()))
+            .collect();
 
         // 遍历所有匹配到的章节标题。
-        for mat in re.find_iter(&t) {
+        for (index, mat) in headings.iter().enumerate() {
             let start = mat.start();
-            let end = mat.end();
 
             // 1）若为首个章节，先提取前置非章节内容（如前言）。
             if result.is_empty() && start > 0 {
@@ -735,10 +2228,7 @@ impl TextProcessor {
             }
 
             // 2）找到下一个章节标题位置（或文本末尾）。
-            let next_match = re
-                .find(&t[end..])
-                .map(|m| end + m.start())
-                .unwrap_or(t.len());
+            let next_match = headings.get(index + 1).map_or(t.len(), |next| next.start());
 
             // 3）提取当前章节（标题 + 内容）。
             let chapter = t[start..next_match].trim();
@@ -773,7 +2263,7 @@ impl TextProcessor {
 
         for line in lines {
             let trimmed = line.trim();
-            if Self::is_chapter_title_line(trimmed) {
+            if Self::is_chapter_title_line(trimmed) && !self.is_excluded_line(trimmed) {
                 // 当前章节非空时先落盘到结果集。
                 if !current_chapter.trim().is_empty() {
                     result.push(current_chapter.trim().to_string());
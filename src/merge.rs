@@ -0,0 +1,181 @@
+//! 多文件合并：一卷一个 TXT，按指定顺序拼接成一份源文本，每个文件成为一卷。
+//!
+//! 合并时在每个文件前插入卷标题行（如 `第一卷 风起`），按内置章节规则分章并开启“按卷分组”
+//! 后，各文件的章节嵌套在对应卷之下；文件本身已以该卷标题开头时不再重复插入。网上流传的
+//! TXT 编码不一，每个文件可以单独指定编码。
+
+use std::path::{Path, PathBuf};
+
+use encoding_rs::{BIG5, Encoding, GB18030, UTF_8, UTF_16BE, UTF_16LE};
+use serde::{Deserialize, Serialize};
+
+use crate::numerals::to_chinese;
+use crate::{Key, Locale, is_volume_title, t};
+
+/// 源文本编码。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub enum TextEncoding {
+    /// 按 BOM 判断；没有 BOM 时是合法 UTF-8 则按 UTF-8，否则按 GB18030（兼容 GBK）。
+    #[default]
+    Auto,
+    Utf8,
+    Gb18030,
+    Big5,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 6] = [
+        TextEncoding::Auto,
+        TextEncoding::Utf8,
+        TextEncoding::Gb18030,
+        TextEncoding::Big5,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            TextEncoding::Auto => t(locale, Key::EncodingAuto),
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Gb18030 => "GBK / GB18030",
+            TextEncoding::Big5 => "Big5",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+        }
+    }
+
+    /// 解码为文本；带 BOM 时以 BOM 为准，无法解码的字节替换为 U+FFFD。
+    pub fn decode(self, bytes: &[u8]) -> String {
+        let encoding: &'static Encoding = match self {
+            TextEncoding::Auto => match Encoding::for_bom(bytes) {
+                Some((encoding, _)) => encoding,
+                None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+                None => GB18030,
+            },
+            TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Gb18030 => GB18030,
+            TextEncoding::Big5 => BIG5,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+        };
+        encoding.decode(bytes).0.into_owned()
+    }
+}
+
+/// 合并列表中的一个文件。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct MergeInput {
+    pub path: PathBuf,
+    /// 卷名；不是 `第N卷` / `卷N` 形式时合并时按顺序补上编号。
+    pub volume_title: String,
+    pub encoding: TextEncoding,
+}
+
+impl MergeInput {
+    /// 卷名默认取文件名（不含扩展名）。
+    pub fn new(path: PathBuf) -> Self {
+        let volume_title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            path,
+            volume_title,
+            encoding: TextEncoding::Auto,
+        }
+    }
+
+    /// 按指定编码读取文件。
+    pub fn read(&self) -> std::io::Result<String> {
+        Ok(self.encoding.decode(&std::fs::read(&self.path)?))
+    }
+}
+
+/// 第 `number` 卷（从 1 开始）的标题行：卷名已是卷标题时原样使用，否则补上 `第N卷`。
+pub fn volume_heading(number: usize, title: &str) -> String {
+    let title = title.trim();
+    if is_volume_title(title) {
+        return title.to_string();
+    }
+    let number = to_chinese(number as u64).unwrap_or_else(|| number.to_string());
+    if title.is_empty() {
+        format!("第{number}卷")
+    } else {
+        format!("第{number}卷 {title}")
+    }
+}
+
+/// 按顺序拼接各卷 `(卷名, 正文)`，每卷之前插入卷标题行。
+pub fn merge_volumes<'a>(volumes: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut merged = String::new();
+    for (index, (title, text)) in volumes.into_iter().enumerate() {
+        let heading = volume_heading(index + 1, title);
+        let text = text.trim_start_matches('\u{feff}');
+        let first_line = text.lines().map(str::trim).find(|line| !line.is_empty());
+        if !merged.is_empty() {
+            merged.push('\n');
+        }
+        if first_line != Some(heading.as_str()) {
+            merged.push_str(&heading);
+            merged.push_str("\n\n");
+        }
+        merged.push_str(text.trim_end());
+        merged.push('\n');
+    }
+    merged
+}
+
+/// 读取并合并列表中的文件；读取失败时错误信息带上文件路径。
+pub fn merge_files(inputs: &[MergeInput]) -> std::io::Result<String> {
+    let texts = inputs
+        .iter()
+        .map(|input| input.read().map_err(|err| with_path(&input.path, err)))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    Ok(merge_volumes(inputs.iter().zip(&texts).map(
+        |(input, text)| (input.volume_title.as_str(), text.as_str()),
+    )))
+}
+
+fn with_path(path: &Path, err: std::io::Error) -> std::io::Error {
+    std::io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_common_encodings() {
+        let text = "第一章 雾起";
+        let (gbk, _, _) = GB18030.encode(text);
+        let (big5, _, _) = BIG5.encode("第一章 霧起");
+        assert_eq!(TextEncoding::Auto.decode(text.as_bytes()), text);
+        assert_eq!(TextEncoding::Auto.decode(&gbk), text);
+        assert_eq!(TextEncoding::Big5.decode(&big5), "第一章 霧起");
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(TextEncoding::Auto.decode(&utf16), text);
+        // BOM 优先于手动指定的编码。
+        assert_eq!(TextEncoding::Gb18030.decode(&utf16), text);
+    }
+
+    #[test]
+    fn merges_files_as_numbered_volumes() {
+        assert_eq!(volume_heading(1, "风起"), "第一卷 风起");
+        assert_eq!(volume_heading(12, " "), "第十二卷");
+        assert_eq!(volume_heading(2, "卷二 云涌"), "卷二 云涌");
+
+        let merged = merge_volumes([
+            ("风起", "\u{feff}第一章 开端\n正文\n\n"),
+            ("第二卷 云涌", "第二卷 云涌\n第二章 继续\n"),
+        ]);
+        assert_eq!(
+            merged,
+            "第一卷 风起\n\n第一章 开端\n正文\n\n第二卷 云涌\n第二章 继续\n"
+        );
+    }
+}
@@ -0,0 +1,689 @@
+//! 在线查询书籍元数据。
+//!
+//! 按书名/作者或 ISBN 查询 Open Library、Google Books 或豆瓣，返回候选结果，
+//! 由用户挑选后填入 `BookInfo`。各数据源的 JSON 解析与网络请求分开，解析部分可离线测试；
+//! 网络请求只在桌面端可用，Web 端直接返回错误。
+//!
+//! 豆瓣的搜索建议只有书名、作者、年份与封面，简介、标签、出版社与 ISBN 要在选用时
+//! 另行读取条目页面（[`fetch_details`]）。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{BookInfo, Creator, CreatorRole};
+
+/// 每个数据源最多返回的候选数。
+const MAX_RESULTS: usize = 10;
+/// 请求超时（秒）。
+#[cfg(not(target_arch = "wasm32"))]
+const TIMEOUT_SECS: u64 = 15;
+
+/// 元数据来源。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetadataSource {
+    #[default]
+    OpenLibrary,
+    GoogleBooks,
+    Douban,
+}
+
+impl MetadataSource {
+    pub const ALL: [MetadataSource; 3] = [
+        MetadataSource::OpenLibrary,
+        MetadataSource::GoogleBooks,
+        MetadataSource::Douban,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OpenLibrary => "Open Library",
+            Self::GoogleBooks => "Google Books",
+            Self::Douban => "豆瓣 / Douban",
+        }
+    }
+
+    fn endpoint(self) -> &'static str {
+        match self {
+            Self::OpenLibrary => "https://openlibrary.org/search.json",
+            Self::GoogleBooks => "https://www.googleapis.com/books/v1/volumes",
+            Self::Douban => "https://book.douban.com/j/subject_suggest",
+        }
+    }
+
+    /// 该数据源的查询参数；有 ISBN 时只按 ISBN 查询。
+    fn query_params(self, query: &LookupQuery) -> Vec<(&'static str, String)> {
+        let isbn = query.isbn();
+        let title = query.title.trim();
+        let author = query.author.trim();
+        match self {
+            Self::OpenLibrary => {
+                let mut params = match isbn {
+                    Some(isbn) => vec![("isbn", isbn)],
+                    None => [("title", title), ("author", author)]
+                        .into_iter()
+                        .filter(|(_, value)| !value.is_empty())
+                        .map(|(key, value)| (key, value.to_string()))
+                        .collect(),
+                };
+                params.push(("limit", MAX_RESULTS.to_string()));
+                params.push((
+                    "fields",
+                    "title,author_name,publisher,first_publish_year,isbn,subject,language,cover_i"
+                        .to_string(),
+                ));
+                params
+            }
+            Self::GoogleBooks => {
+                let q = match isbn {
+                    Some(isbn) => format!("isbn:{isbn}"),
+                    None => [("intitle:", title), ("inauthor:", author)]
+                        .into_iter()
+                        .filter(|(_, value)| !value.is_empty())
+                        .map(|(prefix, value)| format!("{prefix}{value}"))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                vec![("q", q), ("maxResults", MAX_RESULTS.to_string())]
+            }
+            Self::Douban => {
+                let q = isbn.unwrap_or_else(|| format!("{title} {author}").trim().to_string());
+                vec![("q", q)]
+            }
+        }
+    }
+
+    /// 解析该数据源返回的 JSON。
+    pub fn parse(self, json: &str) -> Result<Vec<MetadataCandidate>, String> {
+        let error = |err: serde_json::Error| err.to_string();
+        let mut candidates = match self {
+            Self::OpenLibrary => parse_open_library(serde_json::from_str(json).map_err(error)?),
+            Self::GoogleBooks => parse_google_books(serde_json::from_str(json).map_err(error)?),
+            Self::Douban => parse_douban(serde_json::from_str(json).map_err(error)?),
+        };
+        candidates.retain(|candidate| !candidate.title.is_empty());
+        candidates.truncate(MAX_RESULTS);
+        Ok(candidates)
+    }
+}
+
+/// 查询条件。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LookupQuery {
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+}
+
+impl LookupQuery {
+    pub fn from_book_info(info: &BookInfo) -> Self {
+        Self {
+            title: info.title.trim().to_string(),
+            author: info.author(),
+            isbn: info.isbn.trim().to_string(),
+        }
+    }
+
+    /// 既没有 ISBN 也没有书名、作者时无法查询。
+    pub fn is_empty(&self) -> bool {
+        self.isbn().is_none() && self.title.trim().is_empty() && self.author.trim().is_empty()
+    }
+
+    /// 去掉连字符和空白后的 10 位或 13 位 ISBN。
+    fn isbn(&self) -> Option<String> {
+        let isbn: String = self
+            .isbn
+            .chars()
+            .filter(|ch| !ch.is_whitespace() && *ch != '-')
+            .collect();
+        let valid = matches!(isbn.len(), 10 | 13)
+            && isbn
+                .char_indices()
+                .all(|(index, ch)| ch.is_ascii_digit() || (index == 9 && ch == 'X'));
+        valid.then_some(isbn)
+    }
+}
+
+/// 一条候选结果；数据源没有提供的字段为空。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataCandidate {
+    pub source: MetadataSource,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub publisher: String,
+    pub publish_date: String,
+    pub description: String,
+    pub categories: Vec<String>,
+    pub isbn: String,
+    pub language: String,
+    pub cover_url: Option<String>,
+    /// 需要另行读取详情的条目页面（目前只有豆瓣）。
+    pub details_url: Option<String>,
+}
+
+impl MetadataCandidate {
+    /// 用候选结果填充书籍信息。
+    ///
+    /// 简介、分类、出版社和出版日期以候选结果为准；书名、作者、ISBN 和语言只在原值为空时填入。
+    pub fn apply_to(&self, info: &mut BookInfo) {
+        for (field, value) in [
+            (&mut info.description, &self.description),
+            (&mut info.publisher, &self.publisher),
+            (&mut info.publish_date, &self.publish_date),
+        ] {
+            if !value.is_empty() {
+                *field = value.clone();
+            }
+        }
+        if let Some(category) = self.categories.first() {
+            info.category = category.clone();
+        }
+        for (field, value) in [
+            (&mut info.title, &self.title),
+            (&mut info.isbn, &self.isbn),
+            (&mut info.language, &self.language),
+        ] {
+            if field.trim().is_empty() {
+                *field = value.clone();
+            }
+        }
+        if info.author().is_empty() && !self.authors.is_empty() {
+            info.creators
+                .retain(|creator| !creator.name.trim().is_empty());
+            let authors = self
+                .authors
+                .iter()
+                .map(|name| Creator::new(name.as_str(), CreatorRole::Author));
+            info.creators.splice(0..0, authors);
+        }
+    }
+}
+
+/// 查询一个数据源，返回候选结果。
+pub fn lookup(
+    source: MetadataSource,
+    query: &LookupQuery,
+) -> Result<Vec<MetadataCandidate>, String> {
+    if query.is_empty() {
+        return Err("Enter a title, author or ISBN to search.".to_string());
+    }
+    let body = http_get(source.endpoint(), &source.query_params(query))?;
+    source.parse(&String::from_utf8_lossy(&body))
+}
+
+/// 读取候选结果的条目页面，补全搜索结果中缺少的简介、标签、出版社与 ISBN。
+///
+/// 没有条目页面的候选结果原样返回。
+pub fn fetch_details(candidate: &MetadataCandidate) -> Result<MetadataCandidate, String> {
+    let Some(url) = &candidate.details_url else {
+        return Ok(candidate.clone());
+    };
+    let page = http_get(url, &[])?;
+    Ok(parse_douban_subject(
+        candidate,
+        &String::from_utf8_lossy(&page),
+    ))
+}
+
+/// 下载封面图片。
+pub fn fetch_cover(url: &str) -> Result<Vec<u8>, String> {
+    http_get(url, &[])
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn http_get(url: &str, params: &[(&str, String)]) -> Result<Vec<u8>, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(TIMEOUT_SECS)))
+        .user_agent(concat!("reasypub/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into();
+    let mut request = agent.get(url);
+    for (key, value) in params {
+        request = request.query(*key, value);
+    }
+    let mut response = request.call().map_err(|err| err.to_string())?;
+    response
+        .body_mut()
+        .read_to_vec()
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn http_get(_url: &str, _params: &[(&str, String)]) -> Result<Vec<u8>, String> {
+    Err("Online lookup is only available in the desktop app.".to_string())
+}
+
+/// Open Library `search.json` 的响应。
+#[derive(Deserialize)]
+struct OpenLibraryResponse {
+    #[serde(default)]
+    docs: Vec<OpenLibraryDoc>,
+}
+
+#[derive(Deserialize)]
+struct OpenLibraryDoc {
+    title: Option<String>,
+    #[serde(default)]
+    author_name: Vec<String>,
+    #[serde(default)]
+    publisher: Vec<String>,
+    first_publish_year: Option<i64>,
+    #[serde(default)]
+    isbn: Vec<String>,
+    #[serde(default)]
+    subject: Vec<String>,
+    #[serde(default)]
+    language: Vec<String>,
+    cover_i: Option<i64>,
+}
+
+/// Google Books `volumes` 的响应。
+#[derive(Deserialize)]
+struct GoogleBooksResponse {
+    #[serde(default)]
+    items: Vec<GoogleBooksItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleBooksItem {
+    volume_info: Option<GoogleVolumeInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleVolumeInfo {
+    title: Option<String>,
+    #[serde(default)]
+    authors: Vec<String>,
+    publisher: Option<String>,
+    published_date: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    categories: Vec<String>,
+    language: Option<String>,
+    #[serde(default)]
+    industry_identifiers: Vec<GoogleIdentifier>,
+    image_links: Option<GoogleImageLinks>,
+}
+
+#[derive(Deserialize)]
+struct GoogleIdentifier {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    identifier: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleImageLinks {
+    thumbnail: Option<String>,
+    small_thumbnail: Option<String>,
+}
+
+/// 豆瓣 `subject_suggest` 返回的一条建议。
+#[derive(Deserialize)]
+struct DoubanSuggestion {
+    title: Option<String>,
+    author_name: Option<String>,
+    year: Option<String>,
+    pic: Option<String>,
+    url: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+fn parse_open_library(response: OpenLibraryResponse) -> Vec<MetadataCandidate> {
+    response
+        .docs
+        .into_iter()
+        .map(|doc| MetadataCandidate {
+            source: MetadataSource::OpenLibrary,
+            title: text(doc.title),
+            authors: strings(doc.author_name),
+            publisher: strings(doc.publisher)
+                .into_iter()
+                .next()
+                .unwrap_or_default(),
+            publish_date: doc
+                .first_publish_year
+                .map(|year| year.to_string())
+                .unwrap_or_default(),
+            categories: strings(doc.subject).into_iter().take(5).collect(),
+            isbn: strings(doc.isbn).into_iter().next().unwrap_or_default(),
+            language: strings(doc.language)
+                .first()
+                .map(|code| marc_language(code).to_string())
+                .unwrap_or_default(),
+            cover_url: doc
+                .cover_i
+                .map(|id| format!("https://covers.openlibrary.org/b/id/{id}-L.jpg")),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn parse_google_books(response: GoogleBooksResponse) -> Vec<MetadataCandidate> {
+    response
+        .items
+        .into_iter()
+        .filter_map(|item| item.volume_info)
+        .map(|info| {
+            let isbn = ["ISBN_13", "ISBN_10"]
+                .iter()
+                .find_map(|kind| {
+                    info.industry_identifiers
+                        .iter()
+                        .find(|id| id.kind.as_deref() == Some(kind))
+                })
+                .map(|id| text(id.identifier.clone()))
+                .unwrap_or_default();
+            let cover_url = info
+                .image_links
+                .and_then(|links| links.thumbnail.or(links.small_thumbnail))
+                .map(|url| url.replacen("http://", "https://", 1));
+            MetadataCandidate {
+                source: MetadataSource::GoogleBooks,
+                title: text(info.title),
+                authors: strings(info.authors),
+                publisher: text(info.publisher),
+                publish_date: text(info.published_date),
+                description: text(info.description),
+                categories: strings(info.categories),
+                isbn,
+                language: text(info.language),
+                cover_url,
+                details_url: None,
+            }
+        })
+        .collect()
+}
+
+fn parse_douban(suggestions: Vec<DoubanSuggestion>) -> Vec<MetadataCandidate> {
+    suggestions
+        .into_iter()
+        // 豆瓣的搜索建议混有作者条目，只保留图书（`type` 为 `b`）。
+        .filter(|item| item.kind.as_deref().is_none_or(|kind| kind == "b"))
+        .map(|item| MetadataCandidate {
+            source: MetadataSource::Douban,
+            title: text(item.title),
+            authors: [text(item.author_name)]
+                .into_iter()
+                .filter(|name| !name.is_empty())
+                .collect(),
+            publish_date: text(item.year),
+            cover_url: item.pic.filter(|url| !url.is_empty()),
+            details_url: item.url.filter(|url| url.starts_with("https://")),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// 从豆瓣条目页面补全简介（取“内容简介”的完整版本）、前五个标签、出版社、出版年与 ISBN。
+fn parse_douban_subject(candidate: &MetadataCandidate, html: &str) -> MetadataCandidate {
+    static INTRO: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?s)<div class="intro">(.*?)</div>"#).unwrap());
+    static PARAGRAPH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<p>(.*?)</p>").unwrap());
+    static TAG: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"<a[^>]*href="/tag/[^"]*"[^>]*>([^<]+)</a>"#).unwrap());
+    static INFO: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#"<span class="pl">\s*([^<:：]+?)\s*[:：]?\s*</span>\s*[:：]?\s*(?:<a[^>]*>)?([^<]*)"#,
+        )
+        .unwrap()
+    });
+
+    let mut details = candidate.clone();
+    // 内容简介在 `link-report` 中，被折叠时完整版本排在最后；作者简介在其后，不计入。
+    if let Some(start) = html.find("id=\"link-report\"") {
+        let section = &html[start..];
+        let section = section
+            .find("作者简介")
+            .map_or(section, |end| &section[..end]);
+        if let Some(intro) = INTRO.captures_iter(section).last() {
+            let paragraphs: Vec<String> = PARAGRAPH
+                .captures_iter(&intro[1])
+                .map(|paragraph| html_text(&paragraph[1]))
+                .filter(|paragraph| !paragraph.is_empty())
+                .collect();
+            if !paragraphs.is_empty() {
+                details.description = paragraphs.join("\n");
+            }
+        }
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for caps in TAG.captures_iter(html) {
+        let tag = html_text(&caps[1]);
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if !tags.is_empty() {
+        details.categories = tags.into_iter().take(5).collect();
+    }
+    for caps in INFO.captures_iter(html) {
+        let value = html_text(&caps[2]);
+        if value.is_empty() {
+            continue;
+        }
+        match caps[1].trim() {
+            "出版社" => details.publisher = value,
+            "出版年" => details.publish_date = value,
+            "ISBN" => details.isbn = value,
+            _ => {}
+        }
+    }
+    details
+}
+
+/// 去掉标签并还原常见字符实体，折叠空白。
+fn html_text(fragment: &str) -> String {
+    static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+    let text = TAG
+        .replace_all(fragment, "")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn text(value: Option<String>) -> String {
+    value.as_deref().unwrap_or_default().trim().to_string()
+}
+
+fn strings(values: Vec<String>) -> Vec<String> {
+    values
+        .iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Open Library 使用 MARC 语言代码，转换为常用的 BCP 47 标签。
+fn marc_language(code: &str) -> &str {
+    match code {
+        "chi" => "zh",
+        "eng" => "en",
+        "jpn" => "ja",
+        "kor" => "ko",
+        "fre" => "fr",
+        "ger" => "de",
+        "spa" => "es",
+        "rus" => "ru",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_source() {
+        let open_library = r#"{"numFound": 1, "docs": [{
+            "title": "三体", "author_name": ["刘慈欣"], "publisher": ["重庆出版社"],
+            "first_publish_year": 2008, "isbn": ["9787536692930"],
+            "subject": ["Science fiction"], "language": ["chi"], "cover_i": 12345
+        }, {"author_name": ["无书名"]}]}"#;
+        let candidates = MetadataSource::OpenLibrary
+            .parse(open_library)
+            .expect("open library");
+        assert_eq!(
+            candidates,
+            [MetadataCandidate {
+                source: MetadataSource::OpenLibrary,
+                title: "三体".to_string(),
+                authors: vec!["刘慈欣".to_string()],
+                publisher: "重庆出版社".to_string(),
+                publish_date: "2008".to_string(),
+                categories: vec!["Science fiction".to_string()],
+                isbn: "9787536692930".to_string(),
+                language: "zh".to_string(),
+                cover_url: Some("https://covers.openlibrary.org/b/id/12345-L.jpg".to_string()),
+                ..Default::default()
+            }]
+        );
+
+        let google = r#"{"items": [{"volumeInfo": {
+            "title": "The Three-Body Problem", "authors": ["Cixin Liu"],
+            "publishedDate": "2014-11-11", "description": "A novel.",
+            "categories": ["Fiction"], "language": "en",
+            "industryIdentifiers": [
+                {"type": "ISBN_10", "identifier": "0765377063"},
+                {"type": "ISBN_13", "identifier": "9780765377067"}
+            ],
+            "imageLinks": {"thumbnail": "http://books.google.com/cover.jpg"}
+        }}]}"#;
+        let candidate = &MetadataSource::GoogleBooks.parse(google).expect("google")[0];
+        assert_eq!(candidate.isbn, "9780765377067");
+        assert_eq!(candidate.description, "A novel.");
+        assert_eq!(
+            candidate.cover_url.as_deref(),
+            Some("https://books.google.com/cover.jpg")
+        );
+
+        let douban = r#"[
+            {"title": "三体", "author_name": "刘慈欣", "year": "2008", "pic": "https://img/s.jpg",
+             "url": "https://book.douban.com/subject/2567698/", "type": "b"},
+            {"title": "刘慈欣", "type": "a"}
+        ]"#;
+        let candidates = MetadataSource::Douban.parse(douban).expect("douban");
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].authors, ["刘慈欣"]);
+        assert_eq!(
+            candidates[0].details_url.as_deref(),
+            Some("https://book.douban.com/subject/2567698/")
+        );
+        assert!(MetadataSource::Douban.parse("<html>").is_err());
+    }
+
+    #[test]
+    fn parses_douban_subject_page() {
+        let candidate = MetadataCandidate {
+            source: MetadataSource::Douban,
+            title: "三体".to_string(),
+            publish_date: "2008".to_string(),
+            ..Default::default()
+        };
+        let html = r#"<div id="info">
+            <span class="pl">出版社:</span> <a href="https://book.douban.com/press/2130">重庆出版社</a><br/>
+            <span class="pl">出版年:</span> 2008-1<br/>
+            <span class="pl">ISBN:</span> 9787536692930<br/>
+        </div>
+        <div class="related_info">
+            <span class="">内容简介</span>
+            <div id="link-report">
+                <span class="short"><div class="intro"><p>文化大革命如火如荼……</p></div></span>
+                <span class="all hidden"><div class="intro">
+                    <p>文化大革命如火如荼进行的同时，</p>
+                    <p>军方探寻外星文明的绝秘计划“红岸工程” &amp; 更多。</p>
+                </div></span>
+            </div>
+            <span class="">作者简介</span>
+            <div class="intro"><p>刘慈欣，1963年生。</p></div>
+        </div>
+        <div id="db-tags-section">
+            <a class="tag" href="/tag/科幻">科幻</a>
+            <a class="tag" href="/tag/刘慈欣">刘慈欣</a>
+            <a class="tag" href="/tag/科幻">科幻</a>
+        </div>"#;
+        let details = parse_douban_subject(&candidate, html);
+        assert_eq!(
+            details.description,
+            "文化大革命如火如荼进行的同时，\n军方探寻外星文明的绝秘计划“红岸工程” & 更多。"
+        );
+        assert_eq!(details.categories, ["科幻", "刘慈欣"]);
+        assert_eq!(details.publisher, "重庆出版社");
+        assert_eq!(details.publish_date, "2008-1");
+        assert_eq!(details.isbn, "9787536692930");
+        assert_eq!(details.title, "三体");
+
+        // 没有条目页面的候选结果原样返回，不发起请求。
+        let plain = MetadataCandidate::default();
+        assert_eq!(fetch_details(&plain), Ok(plain.clone()));
+    }
+
+    #[test]
+    fn builds_queries_preferring_isbn() {
+        let query = LookupQuery {
+            title: "三体".to_string(),
+            author: "刘慈欣".to_string(),
+            isbn: "978-7-5366-9293-0".to_string(),
+        };
+        assert_eq!(
+            MetadataSource::GoogleBooks.query_params(&query)[0],
+            ("q", "isbn:9787536692930".to_string())
+        );
+
+        let query = LookupQuery {
+            isbn: "not an isbn".to_string(),
+            ..query
+        };
+        assert_eq!(
+            MetadataSource::GoogleBooks.query_params(&query)[0],
+            ("q", "intitle:三体 inauthor:刘慈欣".to_string())
+        );
+        assert_eq!(
+            MetadataSource::Douban.query_params(&query),
+            [("q", "三体 刘慈欣".to_string())]
+        );
+        assert!(LookupQuery::default().is_empty());
+    }
+
+    #[test]
+    fn apply_fills_book_info() {
+        let mut info = BookInfo {
+            title: "已有书名".to_string(),
+            creators: vec![
+                Creator::new("", CreatorRole::Author),
+                Creator::new("译者", CreatorRole::Translator),
+            ],
+            description: "旧简介".to_string(),
+            ..Default::default()
+        };
+        let candidate = MetadataCandidate {
+            title: "三体".to_string(),
+            authors: vec!["刘慈欣".to_string()],
+            publish_date: "2008".to_string(),
+            categories: vec!["科幻".to_string(), "小说".to_string()],
+            language: "zh".to_string(),
+            ..Default::default()
+        };
+        candidate.apply_to(&mut info);
+        assert_eq!(info.title, "已有书名");
+        assert_eq!(
+            info.creators,
+            [
+                Creator::new("刘慈欣", CreatorRole::Author),
+                Creator::new("译者", CreatorRole::Translator),
+            ]
+        );
+        assert_eq!(info.description, "旧简介");
+        assert_eq!(info.publish_date, "2008");
+        assert_eq!(info.category, "科幻");
+        assert_eq!(info.language, "zh");
+    }
+}
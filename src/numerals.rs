@@ -0,0 +1,202 @@
+//! 章节标题中的中文数字与阿拉伯数字互转：`第一百零三章` ↔ `第103章`。
+//!
+//! 只改写“第N章 / 回 / 节 / 卷 / 集 / 部 / 篇 / 话”形式的编号，标题其余部分保持不变。
+//! 全书编号写法统一后，阅读器中按编号排序与搜索更方便。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{Key, Locale, t};
+
+static NUMBERED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"第([0-9０-９零〇○一二两兩三四五六七八九十百千万萬]+)([章回节節卷集部篇话話])")
+        .unwrap()
+});
+
+const DIGITS: [char; 10] = ['零', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+/// 章节编号的数字写法。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub enum TitleNumerals {
+    #[default]
+    Keep,
+    /// 中文数字改为阿拉伯数字：`第一百零三章` → `第103章`。
+    Arabic,
+    /// 阿拉伯数字改为中文数字：`第103章` → `第一百零三章`。
+    Chinese,
+}
+
+impl TitleNumerals {
+    pub const ALL: [TitleNumerals; 3] = [
+        TitleNumerals::Keep,
+        TitleNumerals::Arabic,
+        TitleNumerals::Chinese,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            TitleNumerals::Keep => t(locale, Key::TitleNumeralsKeep),
+            TitleNumerals::Arabic => t(locale, Key::TitleNumeralsArabic),
+            TitleNumerals::Chinese => t(locale, Key::TitleNumeralsChinese),
+        }
+    }
+
+    /// 改写标题中的章节编号；`Keep` 或无法解析的编号原样保留。
+    pub fn convert(self, title: &str) -> String {
+        if self == TitleNumerals::Keep {
+            return title.to_string();
+        }
+        NUMBERED
+            .replace_all(title, |caps: &regex::Captures<'_>| {
+                let number = parse_number(&caps[1]).and_then(|value| match self {
+                    TitleNumerals::Keep => None,
+                    TitleNumerals::Arabic => Some(value.to_string()),
+                    TitleNumerals::Chinese => to_chinese(value),
+                });
+                match number {
+                    Some(number) => format!("第{number}{}", &caps[2]),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+}
+
+/// 解析阿拉伯数字（含全角）、逐位书写的中文数字（`一〇三`）与带单位的中文数字（`一百零三`）。
+fn parse_number(text: &str) -> Option<u64> {
+    let arabic = |ch: char| match ch {
+        '0'..='9' => ch.to_digit(10),
+        '０'..='９' => Some(ch as u32 - '０' as u32),
+        _ => None,
+    };
+    let chinese = |ch: char| match ch {
+        '零' | '〇' | '○' => Some(0),
+        '两' | '兩' => Some(2),
+        _ => DIGITS
+            .iter()
+            .position(|digit| *digit == ch)
+            .map(|d| d as u32),
+    };
+    let positional = |digit: &dyn Fn(char) -> Option<u32>| {
+        text.chars().try_fold(0u64, |acc, ch| {
+            acc.checked_mul(10)?.checked_add(u64::from(digit(ch)?))
+        })
+    };
+    if let Some(value) = positional(&arabic) {
+        return Some(value);
+    }
+    if !text.contains(['十', '百', '千', '万', '萬']) {
+        return positional(&chinese);
+    }
+
+    let (mut total, mut section, mut current) = (0u64, 0u64, None::<u64>);
+    for ch in text.chars() {
+        let unit = match ch {
+            '十' => 10,
+            '百' => 100,
+            '千' => 1000,
+            '万' | '萬' => {
+                let value = section.checked_add(current.take().unwrap_or(0))?;
+                total = total.checked_add(value.checked_mul(10_000)?)?;
+                section = 0;
+                continue;
+            }
+            _ => {
+                current = Some(u64::from(chinese(ch)?));
+                continue;
+            }
+        };
+        // “十二”省略了开头的“一”。
+        section = section.checked_add(current.take().unwrap_or(1) * unit)?;
+    }
+    total.checked_add(section.checked_add(current.unwrap_or(0))?)
+}
+
+/// 按常规写法输出中文数字，支持到一亿以下；`10`–`19` 写作“十”“十二”。
+pub(crate) fn to_chinese(value: u64) -> Option<String> {
+    if value >= 100_000_000 {
+        return None;
+    }
+    if value == 0 {
+        return Some(DIGITS[0].to_string());
+    }
+    let (high, low) = (value / 10_000, value % 10_000);
+    let mut output = String::new();
+    if high > 0 {
+        output.push_str(&chinese_section(high));
+        output.push('万');
+        if low > 0 && low < 1000 {
+            output.push(DIGITS[0]);
+        }
+    }
+    if low > 0 {
+        output.push_str(&chinese_section(low));
+    }
+    if (10..20).contains(&value) {
+        output.remove(0);
+    }
+    Some(output)
+}
+
+/// 一万以下的部分，中间的零只写一个“零”。
+fn chinese_section(value: u64) -> String {
+    let mut output = String::new();
+    let mut zero = false;
+    for (unit, name) in [
+        (1000, Some('千')),
+        (100, Some('百')),
+        (10, Some('十')),
+        (1, None),
+    ] {
+        let digit = (value / unit % 10) as usize;
+        if digit == 0 {
+            zero = !output.is_empty();
+            continue;
+        }
+        if zero {
+            output.push(DIGITS[0]);
+            zero = false;
+        }
+        output.push(DIGITS[digit]);
+        output.extend(name);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_chinese_numerals_to_arabic() {
+        let arabic = TitleNumerals::Arabic;
+        assert_eq!(arabic.convert("第一百零三章 重逢"), "第103章 重逢");
+        assert_eq!(arabic.convert("第十二回"), "第12回");
+        assert_eq!(arabic.convert("第两千零一十节"), "第2010节");
+        assert_eq!(arabic.convert("第一〇三章"), "第103章");
+        assert_eq!(arabic.convert("第１２章"), "第12章");
+        assert_eq!(arabic.convert("第兩百節"), "第200節");
+        assert_eq!(arabic.convert("第三卷 第二十章"), "第3卷 第20章");
+        // 不是章节编号的中文数字保持不变。
+        assert_eq!(arabic.convert("一百个愿望"), "一百个愿望");
+    }
+
+    #[test]
+    fn converts_arabic_numerals_to_chinese() {
+        let chinese = TitleNumerals::Chinese;
+        assert_eq!(chinese.convert("第103章 重逢"), "第一百零三章 重逢");
+        for (value, text) in [
+            (10, "十"),
+            (12, "十二"),
+            (110, "一百一十"),
+            (1001, "一千零一"),
+            (10_012, "一万零一十二"),
+            (25_000, "二万五千"),
+        ] {
+            assert_eq!(to_chinese(value).as_deref(), Some(text));
+            assert_eq!(parse_number(text), Some(value));
+        }
+        assert_eq!(TitleNumerals::Keep.convert("第103章"), "第103章");
+    }
+}
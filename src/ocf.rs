@@ -0,0 +1,128 @@
+//! EPUB 容器（OCF）的公共解析：`container.xml`、OPF 清单与 spine，以及容器内路径的换算。
+//!
+//! 供生成后自检与 EPUB 导入共用。
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+pub(crate) const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// OPF 清单中的一项。
+pub(crate) struct ManifestItem {
+    pub id: String,
+    pub path: String,
+    pub media_type: String,
+    pub properties: String,
+}
+
+pub(crate) fn attribute(start: &BytesStart<'_>, name: &str) -> Option<String> {
+    start
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+pub(crate) fn rootfile_path(container: &str) -> Option<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start) | Event::Empty(start))
+                if start.local_name().as_ref() == b"rootfile" =>
+            {
+                return attribute(&start, "full-path");
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// 解析 OPF 的清单与 spine；清单路径已换算为容器内完整路径。
+pub(crate) fn parse_opf(opf: &str, opf_dir: &str) -> (Vec<ManifestItem>, Vec<String>) {
+    let mut manifest = Vec::new();
+    let mut spine = Vec::new();
+    let mut reader = Reader::from_str(opf);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start) | Event::Empty(start)) => match start.local_name().as_ref() {
+                b"item" => {
+                    let href = attribute(&start, "href").unwrap_or_default();
+                    manifest.push(ManifestItem {
+                        id: attribute(&start, "id").unwrap_or_default(),
+                        path: resolve_href(opf_dir, &href),
+                        media_type: attribute(&start, "media-type").unwrap_or_default(),
+                        properties: attribute(&start, "properties").unwrap_or_default(),
+                    });
+                }
+                b"itemref" => {
+                    if let Some(idref) = attribute(&start, "idref") {
+                        spine.push(idref);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    (manifest, spine)
+}
+
+pub(crate) fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// 将相对链接按所在目录换算为容器内路径，处理 `./`、`../` 与百分号编码。
+pub(crate) fn resolve_href(base_dir: &str, href: &str) -> String {
+    let mut parts: Vec<String> = base_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect();
+    for part in percent_decode(href).split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part.to_string()),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(hex) = input.get(index + 1..index + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            out.push(byte);
+            index += 3;
+            continue;
+        }
+        out.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_href_handles_relative_and_encoded_paths() {
+        assert_eq!(
+            resolve_href("OEBPS", "images/%E5%A4%B4.png"),
+            "OEBPS/images/头.png"
+        );
+        assert_eq!(resolve_href("OEBPS/text", "../toc.ncx"), "OEBPS/toc.ncx");
+        assert_eq!(resolve_href("", "./a.xhtml"), "a.xhtml");
+    }
+}
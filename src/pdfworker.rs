@@ -0,0 +1,487 @@
+//! PDF 输出：把分好的章节按 `TextStyle` 排成可打印的分页 PDF。
+//!
+//! 版面为横排单栏：书名页之后每章另起一页，章节标题居中，正文沿用字号、行高、
+//! 段间距、首行缩进与文字颜色，页脚居中显示页码，章节写入 PDF 书签。
+//! 只输出文字，不嵌入封面与插图；字体取 `FontRole::Body`，未指定时使用内置中文字体。
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cover::{BUNDLED_FONT, NO_LINE_START};
+use crate::epubworker::{
+    BuildError, BuildProgress, generate_filename, generator_name, is_scene_break,
+    normalize_output_dir, resolve_output_path, split_paragraphs, strip_block_markers,
+    strip_inline_markup,
+};
+use crate::language::resolve_language;
+use crate::typography::smarten_chapters;
+use crate::{BookInfo, ChapterDraft, FontAsset, FontRole, OverwritePolicy, PdfOptions, TextStyle};
+
+mod font;
+mod writer;
+
+use font::PdfFont;
+use writer::{ObjectId, PdfWriter, number, text_string};
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+pub struct PdfBuildOptions {
+    pub book_info: BookInfo,
+    pub output_dir: PathBuf,
+    pub filename_template: String,
+    pub style: TextStyle,
+    pub fonts: Vec<FontAsset>,
+    pub pdf: PdfOptions,
+    /// 在文档信息中写入 `reasypub <版本>` 生成器信息。
+    pub include_generator_meta: bool,
+    /// 输出文件已存在时覆盖、自动编号或报错。
+    pub overwrite_policy: OverwritePolicy,
+}
+
+impl Default for PdfBuildOptions {
+    fn default() -> Self {
+        Self {
+            book_info: BookInfo::default(),
+            output_dir: PathBuf::from("."),
+            filename_template: "{书名}_{作者}.pdf".to_string(),
+            style: TextStyle::default(),
+            fonts: Vec::new(),
+            pdf: PdfOptions::default(),
+            include_generator_meta: true,
+            overwrite_policy: OverwritePolicy::default(),
+        }
+    }
+}
+
+/// `build_pdf` 的输出：文件路径与非致命警告。
+#[derive(Debug, Clone, Default)]
+pub struct PdfBuildOutput {
+    pub output_path: String,
+    pub warnings: Vec<String>,
+}
+
+pub fn build_pdf(
+    chapters: &[ChapterDraft],
+    options: &PdfBuildOptions,
+) -> Result<PdfBuildOutput, BuildError> {
+    build_pdf_with_progress(chapters, options, &mut |_| {}, &AtomicBool::new(false))
+}
+
+/// 同 [`build_pdf`]，并在排版每章后回报进度；`cancel` 置位时尽快中止，不留下输出文件。
+pub fn build_pdf_with_progress(
+    chapters: &[ChapterDraft],
+    options: &PdfBuildOptions,
+    on_progress: &mut dyn FnMut(BuildProgress),
+    cancel: &AtomicBool,
+) -> Result<PdfBuildOutput, BuildError> {
+    if chapters.is_empty() {
+        return Err(BuildError::InvalidInput(
+            "No chapters provided.".to_string(),
+        ));
+    }
+    let (width_mm, height_mm) = options.pdf.page_size.size_mm();
+    let margin_mm = options.pdf.margin_mm.max(0.0);
+    if margin_mm * 2.0 >= width_mm.min(height_mm) * 0.8 {
+        return Err(BuildError::InvalidInput(format!(
+            "PDF margin of {margin_mm} mm leaves no room for text."
+        )));
+    }
+
+    let output_dir = normalize_output_dir(&options.output_dir)?;
+    fs::create_dir_all(&output_dir)?;
+    let filename = pdf_filename(&generate_filename(
+        &options.book_info,
+        &options.filename_template,
+        chapters.len(),
+        options.style.css_template,
+    ));
+    let outpath = resolve_output_path(&output_dir, &filename, options.overwrite_policy)?;
+
+    let (language, language_warning) = resolve_language(&options.book_info.language, chapters);
+    let mut warnings: Vec<String> = language_warning.into_iter().collect();
+    let smart_chapters = options
+        .style
+        .smart_typography
+        .then(|| smarten_chapters(chapters, &language))
+        .flatten();
+    let chapters = smart_chapters.as_deref().unwrap_or(chapters);
+    if options.style.vertical {
+        warnings.push(
+            "PDF output is set horizontally; vertical writing only applies to EPUB.".to_string(),
+        );
+    }
+    let mut font = select_font(&options.fonts, &mut warnings)?;
+
+    let body_size = options.style.font_size.max(1.0) * 0.75;
+    let mut layout = Layout {
+        font: &font,
+        width: width_mm * POINTS_PER_MM,
+        height: height_mm * POINTS_PER_MM,
+        margin: margin_mm * POINTS_PER_MM,
+        pages: Vec::new(),
+        cursor: 0.0,
+    };
+
+    layout.title_page(&options.book_info, body_size);
+    let mut bookmarks = Vec::with_capacity(chapters.len());
+    for (index, chapter) in chapters.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(BuildError::Cancelled);
+        }
+        bookmarks.push((chapter.title.trim().to_string(), layout.pages.len()));
+        layout.chapter(chapter, &options.style, body_size);
+        on_progress(BuildProgress::ChapterRendered {
+            done: index + 1,
+            total: chapters.len(),
+        });
+    }
+    layout.page_numbers(body_size * 0.75);
+
+    if cancel.load(Ordering::Relaxed) {
+        return Err(BuildError::Cancelled);
+    }
+    on_progress(BuildProgress::Writing);
+    let (width, height, pages) = (layout.width, layout.height, layout.pages);
+    let color = options.style.font_color;
+    let color = format!(
+        "{} {} {} rg\n",
+        number(f32::from(color.r()) / 255.0),
+        number(f32::from(color.g()) / 255.0),
+        number(f32::from(color.b()) / 255.0)
+    );
+    let contents: Vec<String> = pages
+        .iter()
+        .map(|lines| {
+            let mut stream = color.clone();
+            for line in lines {
+                stream.push_str(&format!(
+                    "BT /F1 {} Tf {} {} Td {} Tj ET\n",
+                    number(line.size),
+                    number(line.x),
+                    number(line.y),
+                    font.encode(&line.text)
+                ));
+            }
+            stream
+        })
+        .collect();
+    if !font.missing().is_empty() {
+        let sample: String = font.missing().iter().take(10).collect();
+        warnings.push(format!(
+            "PDF font has no glyphs for {} character(s), e.g. {sample}",
+            font.missing().len()
+        ));
+    }
+
+    let mut writer = PdfWriter::new();
+    let catalog = writer.reserve();
+    let page_tree = writer.reserve();
+    let page_ids: Vec<ObjectId> = contents.iter().map(|_| writer.reserve()).collect();
+    for (page, content) in page_ids.iter().zip(&contents) {
+        let stream = writer.reserve();
+        writer.stream(stream, "", content.as_bytes());
+        writer.object(
+            *page,
+            &format!("<< /Type /Page /Parent {page_tree} /Contents {stream} >>"),
+        );
+    }
+    let font_id = font.write(&mut writer);
+    let kids: Vec<String> = page_ids.iter().map(ToString::to_string).collect();
+    writer.object(
+        page_tree,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} /MediaBox [0 0 {} {}] \
+/Resources << /Font << /F1 {font_id} >> >> >>",
+            kids.join(" "),
+            page_ids.len(),
+            number(width),
+            number(height)
+        ),
+    );
+    let outlines = write_outlines(&mut writer, &bookmarks, &page_ids, height);
+
+    let info = writer.reserve();
+    let mut info_dict = String::from("<<");
+    for (key, value) in [
+        ("Title", &options.book_info.title),
+        ("Author", &options.book_info.author()),
+        ("Subject", &options.book_info.description),
+    ] {
+        if !value.trim().is_empty() {
+            info_dict.push_str(&format!(" /{key} {}", text_string(value.trim())));
+        }
+    }
+    if options.include_generator_meta {
+        info_dict.push_str(&format!(" /Creator {}", text_string(&generator_name())));
+    }
+    info_dict.push_str(" >>");
+    writer.object(info, &info_dict);
+
+    writer.object(
+        catalog,
+        &format!(
+            "<< /Type /Catalog /Pages {page_tree} /Outlines {outlines} /PageMode /UseOutlines \
+/Lang {} >>",
+            text_string(&language)
+        ),
+    );
+
+    fs::write(&outpath, writer.finish(catalog, info))?;
+    Ok(PdfBuildOutput {
+        output_path: outpath.display().to_string(),
+        warnings,
+    })
+}
+
+/// 把输出文件名换成 `.pdf` 后缀。
+pub(crate) fn pdf_filename(filename: &str) -> String {
+    let stem = filename.strip_suffix(".epub").unwrap_or(filename);
+    if stem.ends_with(".pdf") {
+        stem.to_string()
+    } else {
+        format!("{stem}.pdf")
+    }
+}
+
+/// 选用正文字体；无法嵌入时退回内置字体并给出警告。
+fn select_font<'a>(
+    fonts: &'a [FontAsset],
+    warnings: &mut Vec<String>,
+) -> Result<PdfFont<'a>, BuildError> {
+    if let Some(asset) = fonts
+        .iter()
+        .find(|font| font.roles.contains(&FontRole::Body))
+    {
+        match PdfFont::parse(&asset.bytes) {
+            Ok(font) => return Ok(font),
+            Err(err) => warnings.push(format!(
+                "PDF: {} cannot be embedded ({err}); the bundled font was used.",
+                asset.name
+            )),
+        }
+    }
+    PdfFont::parse(BUNDLED_FONT).map_err(BuildError::InvalidInput)
+}
+
+fn write_outlines(
+    writer: &mut PdfWriter,
+    bookmarks: &[(String, usize)],
+    pages: &[ObjectId],
+    page_height: f32,
+) -> ObjectId {
+    let outlines = writer.reserve();
+    let items: Vec<ObjectId> = bookmarks.iter().map(|_| writer.reserve()).collect();
+    for (index, (title, page)) in bookmarks.iter().enumerate() {
+        let mut dict = format!(
+            "<< /Title {} /Parent {outlines} /Dest [{} /XYZ 0 {} null]",
+            text_string(title),
+            pages[*page],
+            number(page_height)
+        );
+        if index > 0 {
+            dict.push_str(&format!(" /Prev {}", items[index - 1]));
+        }
+        if let Some(next) = items.get(index + 1) {
+            dict.push_str(&format!(" /Next {next}"));
+        }
+        dict.push_str(" >>");
+        writer.object(items[index], &dict);
+    }
+    match (items.first(), items.last()) {
+        (Some(first), Some(last)) => writer.object(
+            outlines,
+            &format!(
+                "<< /Type /Outlines /First {first} /Last {last} /Count {} >>",
+                items.len()
+            ),
+        ),
+        _ => writer.object(outlines, "<< /Type /Outlines /Count 0 >>"),
+    }
+    outlines
+}
+
+/// 一行已定位的文字；坐标为基线起点，单位为磅。
+struct TextLine {
+    x: f32,
+    y: f32,
+    size: f32,
+    text: String,
+}
+
+enum Align {
+    Left { indent: f32 },
+    Center,
+}
+
+struct Layout<'f, 'a> {
+    font: &'f PdfFont<'a>,
+    width: f32,
+    height: f32,
+    margin: f32,
+    pages: Vec<Vec<TextLine>>,
+    /// 当前页已排到的位置（距页面底边）。
+    cursor: f32,
+}
+
+impl Layout<'_, '_> {
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.cursor = self.height - self.margin;
+    }
+
+    fn text_width(&self) -> f32 {
+        self.width - self.margin * 2.0
+    }
+
+    fn title_page(&mut self, info: &BookInfo, body_size: f32) {
+        self.new_page();
+        self.cursor = self.height * 0.68;
+        let title = if info.title.trim().is_empty() {
+            "Untitled"
+        } else {
+            info.title.trim()
+        };
+        let size = body_size * 2.2;
+        self.paragraph(title, size, size * 1.3, Align::Center);
+        let author = info.author();
+        if !author.is_empty() {
+            self.cursor -= body_size * 2.0;
+            let size = body_size * 1.2;
+            self.paragraph(&author, size, size * 1.4, Align::Center);
+        }
+    }
+
+    fn chapter(&mut self, chapter: &ChapterDraft, style: &TextStyle, body_size: f32) {
+        self.new_page();
+        self.cursor -= body_size * 2.0;
+        let heading_size = body_size * 1.5;
+        self.paragraph(
+            chapter.title.trim(),
+            heading_size,
+            heading_size * 1.4,
+            Align::Center,
+        );
+        self.cursor -= body_size * 1.5;
+
+        let leading = body_size * style.line_height.max(1.0);
+        let indent = body_size * style.text_indent.max(0.0);
+        for mut paragraph in split_paragraphs(&chapter.content, style.reflow()) {
+            if let [line] = paragraph.as_slice()
+                && is_scene_break(line)
+            {
+                self.paragraph("*　　*　　*", body_size, leading, Align::Center);
+                self.cursor -= body_size * style.paragraph_spacing.max(0.0);
+                continue;
+            }
+            strip_block_markers(&mut paragraph);
+            if paragraph.is_empty() {
+                continue;
+            }
+            // 只含页码标记的行删去标记后为空，不占行。
+            let lines: Vec<String> = paragraph
+                .iter()
+                .map(|line| strip_inline_markup(line.trim(), style.inline_markup))
+                .filter(|line| !line.trim().is_empty())
+                .collect();
+            if lines.is_empty() {
+                continue;
+            }
+            for (index, line) in lines.iter().enumerate() {
+                let indent = if index == 0 { indent } else { 0.0 };
+                self.paragraph(line, body_size, leading, Align::Left { indent });
+            }
+            self.cursor -= body_size * style.paragraph_spacing.max(0.0);
+        }
+    }
+
+    /// 折行排入一段文字，版心放不下时换页。
+    fn paragraph(&mut self, text: &str, size: f32, leading: f32, align: Align) {
+        let width = self.text_width();
+        let first_width = match align {
+            Align::Left { indent } => width - indent,
+            Align::Center => width,
+        };
+        for (index, line) in wrap_text(self.font, text, size, first_width, width)
+            .into_iter()
+            .enumerate()
+        {
+            if self.cursor - leading < self.margin {
+                self.new_page();
+            }
+            // 字身在行距中垂直居中，基线约在字身底部上方 0.12 字号处。
+            let baseline = self.cursor - (leading + size) / 2.0 + size * 0.12;
+            let x = match align {
+                Align::Left { indent } if index == 0 => self.margin + indent,
+                Align::Left { .. } => self.margin,
+                Align::Center => {
+                    self.margin + (width - self.font.text_width(&line, size)).max(0.0) / 2.0
+                }
+            };
+            self.cursor -= leading;
+            if let Some(page) = self.pages.last_mut() {
+                page.push(TextLine {
+                    x,
+                    y: baseline,
+                    size,
+                    text: line,
+                });
+            }
+        }
+    }
+
+    /// 除书名页外，在页脚居中写入页码。
+    fn page_numbers(&mut self, size: f32) {
+        let y = (self.margin - size) / 2.0;
+        for (index, page) in self.pages.iter_mut().enumerate().skip(1) {
+            let text = index.to_string();
+            let x = (self.width - self.font.text_width(&text, size)) / 2.0;
+            page.push(TextLine { x, y, size, text });
+        }
+    }
+}
+
+/// 按宽度贪心折行：行首避头标点悬挂在上一行末尾，西文单词尽量不拆开。
+fn wrap_text(
+    font: &PdfFont<'_>,
+    text: &str,
+    size: f32,
+    first_width: f32,
+    width: f32,
+) -> Vec<String> {
+    let is_word_char = |ch: char| ch.is_ascii_alphanumeric() || matches!(ch, '\'' | '-');
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0.0;
+    let mut limit = first_width;
+    for ch in text.chars() {
+        let advance = font.advance(ch, size);
+        if line_width + advance > limit && !line.is_empty() && !NO_LINE_START.contains(ch) {
+            let mut carry = String::new();
+            if is_word_char(ch)
+                && let Some(space) = line.rfind(' ')
+                && space > 0
+                && line[space + 1..].chars().all(is_word_char)
+            {
+                carry = line[space + 1..].to_string();
+                line.truncate(space);
+            }
+            lines.push(line.trim_end().to_string());
+            line = carry;
+            line_width = font.text_width(&line, size);
+            limit = width;
+            if ch.is_whitespace() && line.is_empty() {
+                continue;
+            }
+        }
+        line.push(ch);
+        line_width += advance;
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests;
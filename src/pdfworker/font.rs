@@ -0,0 +1,185 @@
+//! 以 CIDFontType2（Identity-H 编码）嵌入 TrueType 字体。
+//!
+//! 正文以字形编号写入内容流，`ToUnicode` 映射保证文字可复制与检索。
+//! 嵌入前按实际用到的字符裁剪字形；裁剪保持字形编号不变，
+//! 因此 `CIDToGIDMap` 可直接使用 `Identity`。
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ttf_parser::{Face, GlyphId};
+
+use super::writer::{ObjectId, PdfWriter, number};
+use crate::epubworker::subset_font;
+
+pub(super) struct PdfFont<'a> {
+    data: &'a [u8],
+    face: Face<'a>,
+    units_per_em: f32,
+    /// 用到的字形及其对应字符，用于宽度表与 `ToUnicode`。
+    used: BTreeMap<u16, char>,
+    missing: BTreeSet<char>,
+}
+
+impl<'a> PdfFont<'a> {
+    /// 只接受 TrueType 轮廓字体；CFF 字体与字体集合返回错误。
+    pub(super) fn parse(data: &'a [u8]) -> Result<Self, String> {
+        match data.get(0..4) {
+            Some([0x00, 0x01, 0x00, 0x00]) | Some(b"true") => {}
+            Some(b"OTTO") => return Err("CFF-based OpenType fonts are not supported".to_string()),
+            Some(b"ttcf") => return Err("font collections are not supported".to_string()),
+            _ => return Err("not a TrueType font".to_string()),
+        }
+        let face = Face::parse(data, 0).map_err(|err| err.to_string())?;
+        Ok(Self {
+            data,
+            units_per_em: f32::from(face.units_per_em()),
+            face,
+            used: BTreeMap::new(),
+            missing: BTreeSet::new(),
+        })
+    }
+
+    /// 字体中缺少字形的字符，排版时以 `.notdef` 代替。
+    pub(super) fn missing(&self) -> &BTreeSet<char> {
+        &self.missing
+    }
+
+    /// `size` 磅下字符的前进宽度。
+    pub(super) fn advance(&self, ch: char, size: f32) -> f32 {
+        let glyph = self.face.glyph_index(ch).unwrap_or(GlyphId(0));
+        let advance = self.face.glyph_hor_advance(glyph).unwrap_or(0);
+        f32::from(advance) / self.units_per_em * size
+    }
+
+    pub(super) fn text_width(&self, text: &str, size: f32) -> f32 {
+        text.chars().map(|ch| self.advance(ch, size)).sum()
+    }
+
+    /// 把文本编码为内容流中的十六进制字形串，并记录用到的字形。
+    pub(super) fn encode(&mut self, text: &str) -> String {
+        let mut hex = String::with_capacity(text.len() * 4 + 2);
+        hex.push('<');
+        for ch in text.chars() {
+            let glyph = match self.face.glyph_index(ch) {
+                Some(glyph) => {
+                    self.used.entry(glyph.0).or_insert(ch);
+                    glyph.0
+                }
+                None => {
+                    self.missing.insert(ch);
+                    0
+                }
+            };
+            hex.push_str(&format!("{glyph:04X}"));
+        }
+        hex.push('>');
+        hex
+    }
+
+    /// 写出 Type0 字体及其下属对象，返回 Type0 字体的编号。
+    pub(super) fn write(&self, writer: &mut PdfWriter) -> ObjectId {
+        let scale = |value: f32| number(value * 1000.0 / self.units_per_em);
+        let base_name = format!("RSPBAA+{}", self.postscript_name());
+
+        let chars: BTreeSet<char> = self.used.values().copied().collect();
+        let font_data = subset_font(self.data, &chars).unwrap_or_else(|_| self.data.to_vec());
+        let font_file = writer.reserve();
+        writer.stream(
+            font_file,
+            &format!("/Length1 {}", font_data.len()),
+            &font_data,
+        );
+
+        let bbox = self.face.global_bounding_box();
+        let cap_height = self.face.capital_height().unwrap_or(self.face.ascender());
+        let descriptor = writer.reserve();
+        writer.object(
+            descriptor,
+            &format!(
+                "<< /Type /FontDescriptor /FontName /{base_name} /Flags 4 \
+/FontBBox [{} {} {} {}] /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} \
+/StemV 80 /FontFile2 {font_file} >>",
+                scale(f32::from(bbox.x_min)),
+                scale(f32::from(bbox.y_min)),
+                scale(f32::from(bbox.x_max)),
+                scale(f32::from(bbox.y_max)),
+                scale(f32::from(self.face.ascender())),
+                scale(f32::from(self.face.descender())),
+                scale(f32::from(cap_height)),
+            ),
+        );
+
+        let mut widths = String::new();
+        for &glyph in self.used.keys() {
+            let advance = self.face.glyph_hor_advance(GlyphId(glyph)).unwrap_or(0);
+            widths.push_str(&format!("{glyph} [{}] ", scale(f32::from(advance))));
+        }
+        let cid_font = writer.reserve();
+        writer.object(
+            cid_font,
+            &format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{base_name} \
+/CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+/FontDescriptor {descriptor} /DW 1000 /W [{}] /CIDToGIDMap /Identity >>",
+                widths.trim_end()
+            ),
+        );
+
+        let to_unicode = writer.reserve();
+        writer.stream(to_unicode, "", self.to_unicode_cmap().as_bytes());
+
+        let font = writer.reserve();
+        writer.object(
+            font,
+            &format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{base_name} /Encoding /Identity-H \
+/DescendantFonts [{cid_font}] /ToUnicode {to_unicode} >>"
+            ),
+        );
+        font
+    }
+
+    fn postscript_name(&self) -> String {
+        let name: String = self
+            .face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .find_map(|name| name.to_string())
+            .unwrap_or_default()
+            .chars()
+            .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '-')
+            .collect();
+        if name.is_empty() {
+            "ReasypubFont".to_string()
+        } else {
+            name
+        }
+    }
+
+    fn to_unicode_cmap(&self) -> String {
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+/CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        let entries: Vec<_> = self.used.iter().collect();
+        // 每个 bfchar 段最多 100 项。
+        for chunk in entries.chunks(100) {
+            cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+            for (glyph, ch) in chunk {
+                let mut units = [0u16; 2];
+                let unicode: String = ch
+                    .encode_utf16(&mut units)
+                    .iter()
+                    .map(|unit| format!("{unit:04X}"))
+                    .collect();
+                cmap.push_str(&format!("<{glyph:04X}> <{unicode}>\n"));
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        cmap
+    }
+}
@@ -0,0 +1,157 @@
+use super::*;
+use crate::{Creator, CreatorRole, PdfPageSize};
+use std::io::Read;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn unique_temp_dir(prefix: &str) -> PathBuf {
+    let suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    std::env::temp_dir().join(format!("{prefix}-{suffix}"))
+}
+
+/// 解压 PDF 中全部 FlateDecode 流，拼接为文本以便断言。
+fn inflate_streams(pdf: &[u8]) -> String {
+    let mut text = String::new();
+    let mut rest = pdf;
+    while let Some(start) = find(rest, b"stream\n") {
+        let data = &rest[start + 7..];
+        let Some(end) = find(data, b"\nendstream") else {
+            break;
+        };
+        let mut decoded = String::new();
+        if flate2::read::ZlibDecoder::new(&data[..end])
+            .read_to_string(&mut decoded)
+            .is_ok()
+        {
+            text.push_str(&decoded);
+        }
+        rest = &data[end + b"\nendstream".len()..];
+    }
+    text
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[test]
+fn pdf_filename_replaces_epub_extension() {
+    assert_eq!(pdf_filename("书名_作者.epub"), "书名_作者.pdf");
+    assert_eq!(pdf_filename("book.pdf.epub"), "book.pdf");
+    assert_eq!(pdf_filename("book"), "book.pdf");
+}
+
+#[test]
+fn wrap_text_hangs_punctuation_and_keeps_words() {
+    let font = PdfFont::parse(BUNDLED_FONT).expect("font");
+    let width = font.text_width("字字字字", 10.0);
+    let lines = wrap_text(&font, "一二三四。五六", 10.0, width, width);
+    assert_eq!(lines, vec!["一二三四。", "五六"]);
+
+    let width = font.text_width("The quick brown", 10.0);
+    let lines = wrap_text(&font, "The quick brown fox jumps", 10.0, width, width);
+    assert_eq!(lines, vec!["The quick brown", "fox jumps"]);
+}
+
+#[test]
+fn build_pdf_writes_pages_outline_and_text() {
+    let dir = unique_temp_dir("reasypub-pdf");
+    let chapters = vec![
+        ChapterDraft {
+            title: "第一章 开始".to_string(),
+            content: "这是第一段。\n\n这是第二段。".to_string(),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "第二章 继续".to_string(),
+            content: "更多内容。".repeat(400),
+            ..Default::default()
+        },
+    ];
+    let options = PdfBuildOptions {
+        book_info: BookInfo {
+            title: "测试".to_string(),
+            creators: vec![Creator::new("作者", CreatorRole::Author)],
+            ..Default::default()
+        },
+        output_dir: dir.clone(),
+        filename_template: "book".to_string(),
+        pdf: PdfOptions {
+            page_size: PdfPageSize::A5,
+            margin_mm: 15.0,
+        },
+        ..Default::default()
+    };
+    let output = build_pdf(&chapters, &options).expect("build pdf");
+    assert!(output.output_path.ends_with("book.pdf"));
+    assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+
+    let pdf = fs::read(Path::new(&output.output_path)).expect("read pdf");
+    assert!(pdf.starts_with(b"%PDF-1.7"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+    let raw = String::from_utf8_lossy(&pdf);
+    // 书名页 + 第一章 1 页 + 第二章至少 2 页。
+    let count: usize = raw
+        .split("/Type /Pages")
+        .nth(1)
+        .and_then(|rest| rest.split("/Count ").nth(1))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+        .expect("page count");
+    assert!(count >= 4, "{count} pages");
+    assert!(raw.contains("/MediaBox [0 0 419.53 595.28]"));
+    assert!(raw.contains("/Subtype /CIDFontType2"));
+    assert!(raw.contains("/Outlines"));
+    assert!(raw.contains(&text_string("第二章 继续")));
+    assert!(raw.contains(&format!("/Title {}", text_string("测试"))));
+
+    // 交叉引用表中的偏移应指向对应对象。
+    let startxref = raw.rfind("startxref\n").expect("startxref");
+    let xref: usize = raw[startxref + 10..]
+        .lines()
+        .next()
+        .and_then(|line| line.parse().ok())
+        .expect("xref offset");
+    let table = String::from_utf8_lossy(&pdf[xref..]);
+    assert!(table.starts_with("xref\n"));
+    for (number, entry) in table.lines().skip(3).enumerate() {
+        if !entry.ends_with(" n ") {
+            break;
+        }
+        let offset: usize = entry[..10].parse().expect("offset");
+        assert!(pdf[offset..].starts_with(format!("{} 0 obj", number + 1).as_bytes()));
+    }
+
+    let streams = inflate_streams(&pdf);
+    assert!(streams.contains("/F1 12 Tf"));
+    // ToUnicode 映射包含正文用到的字符。
+    assert!(streams.contains("<8FD9>"));
+    assert!(streams.contains("beginbfchar"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn build_pdf_rejects_oversized_margin() {
+    let chapters = vec![ChapterDraft {
+        title: "Chapter 1".to_string(),
+        content: "Hello".to_string(),
+        ..Default::default()
+    }];
+    let options = PdfBuildOptions {
+        pdf: PdfOptions {
+            page_size: PdfPageSize::B6,
+            margin_mm: 60.0,
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        build_pdf(&chapters, &options),
+        Err(BuildError::InvalidInput(_))
+    ));
+}
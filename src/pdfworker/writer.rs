@@ -0,0 +1,106 @@
+//! 最小化的 PDF 对象写出器：按编号写出间接对象，最后生成交叉引用表。
+
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+/// 间接对象编号。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ObjectId(usize);
+
+impl std::fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} 0 R", self.0)
+    }
+}
+
+pub(super) struct PdfWriter {
+    buffer: Vec<u8>,
+    /// 下标为对象编号，0 号对象保留；`None` 表示已分配但尚未写出。
+    offsets: Vec<Option<usize>>,
+}
+
+impl PdfWriter {
+    pub(super) fn new() -> Self {
+        let mut buffer = b"%PDF-1.7\n".to_vec();
+        // 注释行中的高位字节提示传输工具按二进制处理。
+        buffer.extend_from_slice(b"%\xE2\xE3\xCF\xD3\n");
+        Self {
+            buffer,
+            offsets: vec![None],
+        }
+    }
+
+    /// 预先分配编号，供尚未写出的对象被引用。
+    pub(super) fn reserve(&mut self) -> ObjectId {
+        self.offsets.push(None);
+        ObjectId(self.offsets.len() - 1)
+    }
+
+    /// 写出字典或其他直接值构成的对象。
+    pub(super) fn object(&mut self, id: ObjectId, body: &str) {
+        self.begin(id);
+        self.buffer.extend_from_slice(body.as_bytes());
+        self.buffer.extend_from_slice(b"\nendobj\n");
+    }
+
+    /// 以 FlateDecode 压缩写出流对象；`dict` 为除 `/Length` 与 `/Filter` 外的字典项。
+    pub(super) fn stream(&mut self, id: ObjectId, dict: &str, data: &[u8]) {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        // 写入内存缓冲区不会失败。
+        let _ = encoder.write_all(data);
+        let compressed = encoder.finish().unwrap_or_default();
+        self.begin(id);
+        self.buffer.extend_from_slice(
+            format!(
+                "<< {dict} /Length {} /Filter /FlateDecode >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        self.buffer.extend_from_slice(&compressed);
+        self.buffer.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn begin(&mut self, id: ObjectId) {
+        self.offsets[id.0] = Some(self.buffer.len());
+        self.buffer
+            .extend_from_slice(format!("{} 0 obj\n", id.0).as_bytes());
+    }
+
+    /// 写出交叉引用表与文件尾，返回完整的 PDF 数据。
+    pub(super) fn finish(mut self, catalog: ObjectId, info: ObjectId) -> Vec<u8> {
+        let xref = self.buffer.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len());
+        for offset in &self.offsets[1..] {
+            match offset {
+                Some(offset) => table.push_str(&format!("{offset:010} 00000 n \n")),
+                None => table.push_str("0000000000 65535 f \n"),
+            }
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root {catalog} /Info {info} >>\nstartxref\n{xref}\n%%EOF\n",
+            self.offsets.len()
+        ));
+        self.buffer.extend_from_slice(table.as_bytes());
+        self.buffer
+    }
+}
+
+/// 编码为 PDF 文本字符串：UTF-16BE 加字节序标记，以十六进制书写。
+pub(super) fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(&format!("{unit:04X}"));
+    }
+    hex.push('>');
+    hex
+}
+
+/// 把数值写成 PDF 接受的小数形式（不使用科学计数法）。
+pub(super) fn number(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0" } else { text }.to_string()
+}
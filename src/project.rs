@@ -0,0 +1,335 @@
+//! 工程文件（`.reasypub`）：保存整本书的工作状态，便于中途保存、稍后继续。
+//!
+//! 工程以 RON 文本保存。源文本、封面、章节头图、插图与字体只记录路径，打开工程时
+//! 重新读取；章节编辑结果（含单章头图）直接写入工程，源文件改动后也不会丢失。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::conversion::DEFAULT_CHAPTER_LENGTH;
+use crate::cover::{CoverDesign, CoverFit};
+use crate::merge::MergeInput;
+use crate::numerals::TitleNumerals;
+use crate::punctuation::PunctuationNormalization;
+use crate::zhconv::ChineseConversion;
+use crate::{
+    BackMatter, BookInfo, ChapterDraft, ChapterHeaderImage, ChapterHeading, ChapterVariant,
+    CleanupRule, ConversionMethod, EpubVersion, FrontMatter, ImageAsset, ImageOptimization,
+    OutputFormat, OverwritePolicy, PdfOptions, SpineSection, TextStyle, TocOptions, ZipCompression,
+};
+
+/// 工程文件扩展名（不含点）。
+pub const PROJECT_EXTENSION: &str = "reasypub";
+
+/// 当前工程格式版本；读取到更高版本时拒绝打开。
+pub const PROJECT_VERSION: u32 = 1;
+
+/// 一本书的完整工作状态。
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct Project {
+    pub version: u32,
+    // 源文本与分章
+    pub input_path: Option<PathBuf>,
+    /// 源文本由多个文件合并而来时的文件列表；不为空时打开工程按此重新合并，忽略 `input_path`。
+    pub merge_inputs: Vec<MergeInput>,
+    pub method: ConversionMethod,
+    pub custom_regex: String,
+    pub custom_regex_path: Option<PathBuf>,
+    /// 分章前去掉开头的书名、作者信息块。
+    pub strip_text_header: bool,
+    /// 清理规则之前去掉不可见字符与多余空白。
+    pub clean_whitespace: bool,
+    pub cleanup_rules: Vec<CleanupRule>,
+    /// 分章前的标点与全半角规范化。
+    pub punctuation: PunctuationNormalization,
+    /// 标题排除规则：命中的行不作为章节标题。
+    pub title_exclusions: Vec<String>,
+    /// 按字数分章时每章的目标字数。
+    pub chapter_length: usize,
+    /// 章节编辑器中的章节；为 `Some` 时转换使用这些章节而不是重新分章。
+    pub chapters: Option<Vec<ProjectChapter>>,
+    // 书籍信息与版式
+    pub book_info: BookInfo,
+    pub style: TextStyle,
+    pub cover_path: Option<PathBuf>,
+    pub generate_cover: bool,
+    pub cover_design: CoverDesign,
+    pub cover_fit: CoverFit,
+    pub chapter_header_image_path: Option<PathBuf>,
+    pub chapter_header_fullbleed: bool,
+    pub images: Vec<ProjectImage>,
+    pub include_images_section: bool,
+    pub image_optimization: ImageOptimization,
+    pub toc_options: TocOptions,
+    pub front_matter: FrontMatter,
+    pub back_matter: BackMatter,
+    // 输出设置
+    pub output_dir: String,
+    pub filename_template: String,
+    pub include_generator_meta: bool,
+    pub safe_mode: bool,
+    pub epub_version: EpubVersion,
+    pub rtl_page_progression: bool,
+    pub kepub: bool,
+    pub reproducible: bool,
+    pub compression: ZipCompression,
+    pub split_large_chapters: bool,
+    pub spine_order: Vec<SpineSection>,
+    pub output_format: OutputFormat,
+    pub pdf: PdfOptions,
+    pub overwrite_policy: OverwritePolicy,
+    pub chinese_conversion: ChineseConversion,
+    /// 章节标题与卷名中编号的数字写法。
+    pub title_numerals: TitleNumerals,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            input_path: None,
+            merge_inputs: Vec::new(),
+            method: ConversionMethod::Regex,
+            custom_regex: String::new(),
+            custom_regex_path: None,
+            strip_text_header: false,
+            clean_whitespace: false,
+            cleanup_rules: Vec::new(),
+            punctuation: PunctuationNormalization::default(),
+            title_exclusions: Vec::new(),
+            chapter_length: DEFAULT_CHAPTER_LENGTH,
+            chapters: None,
+            book_info: BookInfo::default(),
+            style: TextStyle::default(),
+            cover_path: None,
+            generate_cover: false,
+            cover_design: CoverDesign::default(),
+            cover_fit: CoverFit::default(),
+            chapter_header_image_path: None,
+            chapter_header_fullbleed: false,
+            images: Vec::new(),
+            include_images_section: true,
+            image_optimization: ImageOptimization::default(),
+            toc_options: TocOptions::default(),
+            front_matter: FrontMatter::default(),
+            back_matter: BackMatter::default(),
+            output_dir: ".".to_string(),
+            filename_template: "{书名}_{作者}.epub".to_string(),
+            include_generator_meta: true,
+            safe_mode: false,
+            epub_version: EpubVersion::default(),
+            rtl_page_progression: false,
+            kepub: false,
+            reproducible: false,
+            compression: ZipCompression::default(),
+            split_large_chapters: false,
+            spine_order: SpineSection::ALL.to_vec(),
+            output_format: OutputFormat::default(),
+            pdf: PdfOptions::default(),
+            overwrite_policy: OverwritePolicy::Ask,
+            chinese_conversion: ChineseConversion::default(),
+            title_numerals: TitleNumerals::default(),
+        }
+    }
+}
+
+impl Project {
+    /// 以 RON 文本写入 `path`。
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(std::io::Error::other)?;
+        std::fs::write(path, text)
+    }
+
+    /// 读取工程文件；格式错误或版本过新时返回 `InvalidData`。
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_ron(&text)
+    }
+
+    pub fn from_ron(text: &str) -> std::io::Result<Self> {
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        let project: Self = ron::from_str(text).map_err(|err| invalid(err.to_string()))?;
+        if project.version > PROJECT_VERSION {
+            return Err(invalid(format!(
+                "project format version {} is newer than supported version {PROJECT_VERSION}",
+                project.version
+            )));
+        }
+        Ok(project)
+    }
+}
+
+/// 插图路径、图注与插入的章节；列表顺序即插图页中的顺序。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ProjectImage {
+    pub path: PathBuf,
+    pub caption: Option<String>,
+    pub chapter: Option<usize>,
+}
+
+/// 可序列化的 [`ChapterDraft`]。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ProjectChapter {
+    pub title: String,
+    pub content: String,
+    pub custom_css: String,
+    pub volume: Option<String>,
+    pub css_class: String,
+    pub header_image: ProjectHeaderImage,
+    pub variant: ChapterVariant,
+    pub heading: Option<ChapterHeading>,
+}
+
+/// 可序列化的 [`ChapterHeaderImage`]；单章头图没有来源路径，数据直接写入工程。
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProjectHeaderImage {
+    #[default]
+    Inherit,
+    Hidden,
+    Custom {
+        name: String,
+        mime: String,
+        bytes: Vec<u8>,
+    },
+}
+
+impl From<&ChapterDraft> for ProjectChapter {
+    fn from(chapter: &ChapterDraft) -> Self {
+        Self {
+            title: chapter.title.clone(),
+            content: chapter.content.clone(),
+            custom_css: chapter.custom_css.clone(),
+            volume: chapter.volume.clone(),
+            css_class: chapter.css_class.clone(),
+            header_image: match &chapter.header_image {
+                ChapterHeaderImage::Inherit => ProjectHeaderImage::Inherit,
+                ChapterHeaderImage::Hidden => ProjectHeaderImage::Hidden,
+                ChapterHeaderImage::Custom(image) => ProjectHeaderImage::Custom {
+                    name: image.name.clone(),
+                    mime: image.mime.clone(),
+                    bytes: image.bytes.to_vec(),
+                },
+            },
+            variant: chapter.variant,
+            heading: chapter.heading.clone(),
+        }
+    }
+}
+
+impl From<ProjectChapter> for ChapterDraft {
+    fn from(chapter: ProjectChapter) -> Self {
+        Self {
+            title: chapter.title,
+            content: chapter.content,
+            custom_css: chapter.custom_css,
+            volume: chapter.volume,
+            css_class: chapter.css_class,
+            header_image: match chapter.header_image {
+                ProjectHeaderImage::Inherit => ChapterHeaderImage::Inherit,
+                ProjectHeaderImage::Hidden => ChapterHeaderImage::Hidden,
+                ProjectHeaderImage::Custom { name, mime, bytes } => {
+                    ChapterHeaderImage::Custom(ImageAsset {
+                        name,
+                        bytes: bytes.into(),
+                        mime,
+                        caption: None,
+                        chapter: None,
+                    })
+                }
+            },
+            variant: chapter.variant,
+            heading: chapter.heading,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Creator, CreatorRole};
+
+    #[test]
+    fn project_round_trips_through_ron() {
+        let project = Project {
+            input_path: Some(PathBuf::from("book.txt")),
+            custom_regex: r"^第\d+章".to_string(),
+            title_exclusions: vec!["见书末$".to_string()],
+            chapters: Some(vec![
+                ProjectChapter {
+                    title: "序章".to_string(),
+                    content: "正文".to_string(),
+                    variant: ChapterVariant::Centered,
+                    ..Default::default()
+                },
+                ProjectChapter {
+                    title: "第一章".to_string(),
+                    header_image: ProjectHeaderImage::Custom {
+                        name: "h.png".to_string(),
+                        mime: "image/png".to_string(),
+                        bytes: vec![0x89, b'P', b'N', b'G'],
+                    },
+                    ..Default::default()
+                },
+            ]),
+            book_info: BookInfo {
+                title: "书名".to_string(),
+                ..Default::default()
+            },
+            images: vec![ProjectImage {
+                path: PathBuf::from("images/a.png"),
+                caption: Some("图一".to_string()),
+                chapter: Some(2),
+            }],
+            output_format: OutputFormat::Pdf,
+            ..Default::default()
+        };
+        let text = ron::ser::to_string_pretty(&project, ron::ser::PrettyConfig::default())
+            .expect("serialize");
+        let loaded = Project::from_ron(&text).expect("parse");
+        let reserialized = ron::ser::to_string_pretty(&loaded, ron::ser::PrettyConfig::default())
+            .expect("serialize");
+        assert_eq!(reserialized, text);
+        assert_eq!(loaded.output_format, OutputFormat::Pdf);
+        assert_eq!(loaded.images, project.images);
+
+        let chapter: ChapterDraft = loaded.chapters.expect("chapters")[1].clone().into();
+        let ChapterHeaderImage::Custom(image) = &chapter.header_image else {
+            panic!("expected custom header image");
+        };
+        assert_eq!(image.bytes.as_ref(), &[0x89, b'P', b'N', b'G']);
+        let chapters = project.chapters.expect("chapters");
+        assert_eq!(ProjectChapter::from(&chapter), chapters[1]);
+    }
+
+    #[test]
+    fn project_fills_missing_fields_and_rejects_newer_versions() {
+        let project =
+            Project::from_ron("(version: 1, book_info: (title: \"旧工程\"))").expect("parse");
+        assert_eq!(project.book_info.title, "旧工程");
+        assert_eq!(project.filename_template, "{书名}_{作者}.epub");
+
+        // 旧版的单一作者字符串读作一位作者。
+        let project = Project::from_ron("(version: 1, book_info: (author: \"旧作者\"))")
+            .expect("parse legacy author");
+        assert_eq!(
+            project.book_info.creators,
+            [Creator::new("旧作者", CreatorRole::Author)]
+        );
+        let project = Project::from_ron(
+            "(version: 1, book_info: (creators: [(name: \"甲\"), (name: \"乙\", role: Translator)]))",
+        )
+        .expect("parse creators");
+        assert_eq!(project.book_info.author(), "甲");
+        assert_eq!(project.book_info.creators[1].role, CreatorRole::Translator);
+
+        let err = Project::from_ron("(version: 99)").expect_err("newer version");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(Project::from_ron("not a project").is_err());
+    }
+}
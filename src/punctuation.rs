@@ -0,0 +1,292 @@
+//! 标点与全半角规范化：分章前按行统一标点写法。
+//!
+//! 中文模式只处理含汉字或假名的行：紧跟中文的半角标点改为全角，连续的“。。。”“...”
+//! 改为“……”，并按出现顺序重新配对引号，修正“你好“这类方向错误。英文模式只处理不含
+//! 中文的行，把全角标点换成半角并补上必要的空格。两种模式都把全角字母和数字改为半角。
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Key, Locale, t};
+
+/// 标点规范化方式。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub enum PunctuationNormalization {
+    #[default]
+    Off,
+    /// 中文文本：半角标点转全角。
+    FullWidth,
+    /// 英文文本：全角标点转半角。
+    HalfWidth,
+}
+
+/// 预览中的一行改动（行号从 1 开始）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PunctuationChange {
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// 改动预览：改动的总行数与前若干行的对照。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PunctuationPreview {
+    pub total: usize,
+    pub changes: Vec<PunctuationChange>,
+}
+
+impl PunctuationNormalization {
+    pub const ALL: [PunctuationNormalization; 3] = [
+        PunctuationNormalization::Off,
+        PunctuationNormalization::FullWidth,
+        PunctuationNormalization::HalfWidth,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            PunctuationNormalization::Off => t(locale, Key::PunctuationOff),
+            PunctuationNormalization::FullWidth => t(locale, Key::PunctuationFullWidth),
+            PunctuationNormalization::HalfWidth => t(locale, Key::PunctuationHalfWidth),
+        }
+    }
+
+    /// 规范化整段文本；`Off` 时原样返回。
+    pub fn normalize(self, text: &str) -> String {
+        if self == PunctuationNormalization::Off {
+            return text.to_string();
+        }
+        text.split_inclusive('\n')
+            .map(|line| {
+                let content = line.trim_end_matches(['\r', '\n']);
+                let mut output = self.normalize_line(content);
+                output.push_str(&line[content.len()..]);
+                output
+            })
+            .collect()
+    }
+
+    /// 列出会被修改的行，最多保留 `limit` 行对照。
+    pub fn preview(self, text: &str, limit: usize) -> PunctuationPreview {
+        let mut preview = PunctuationPreview::default();
+        if self == PunctuationNormalization::Off {
+            return preview;
+        }
+        for (index, line) in text.lines().enumerate() {
+            let after = self.normalize_line(line);
+            if after == line {
+                continue;
+            }
+            preview.total += 1;
+            if preview.changes.len() < limit {
+                preview.changes.push(PunctuationChange {
+                    line: index + 1,
+                    before: line.to_string(),
+                    after,
+                });
+            }
+        }
+        preview
+    }
+
+    fn normalize_line(self, line: &str) -> String {
+        let chars: Vec<char> = line.chars().map(narrow_alphanumeric).collect();
+        let cjk = chars.iter().any(|&ch| is_cjk(ch));
+        match self {
+            PunctuationNormalization::Off => line.to_string(),
+            PunctuationNormalization::FullWidth if cjk => full_width_line(&chars),
+            PunctuationNormalization::HalfWidth if !cjk => half_width_line(&chars),
+            _ => chars.into_iter().collect(),
+        }
+    }
+}
+
+/// 全角字母与数字改为半角。
+fn narrow_alphanumeric(ch: char) -> char {
+    match ch {
+        '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+            char::from_u32(ch as u32 - 0xfee0).unwrap_or(ch)
+        }
+        _ => ch,
+    }
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch, '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}')
+}
+
+/// 可以视为中文上下文的字符：汉字、假名与全角标点。
+fn is_cjk_context(ch: char) -> bool {
+    is_cjk(ch) || matches!(ch, '\u{3000}'..='\u{303f}' | '\u{ff00}'..='\u{ffef}' | '”' | '’' | '…')
+}
+
+fn full_width_line(chars: &[char]) -> String {
+    let mut output: Vec<char> = Vec::with_capacity(chars.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        let prev = output.last().copied();
+        let next = chars.get(index + 1).copied();
+        let after_cjk = prev.is_some_and(is_cjk_context);
+        // 省略号：连续三个以上的句号或点，以及单个“…”。
+        let run = chars[index..].iter().take_while(|&&c| c == ch).count();
+        if (matches!(ch, '。' | '.') && run >= 3) || ch == '…' {
+            output.extend(['…', '…']);
+            index += run;
+            continue;
+        }
+        let converted = match ch {
+            ',' if after_cjk => Some('，'),
+            '.' if after_cjk => Some('。'),
+            '!' if after_cjk => Some('！'),
+            '?' if after_cjk => Some('？'),
+            ':' if after_cjk => Some('：'),
+            ';' if after_cjk => Some('；'),
+            '(' if after_cjk || next.is_some_and(is_cjk) => Some('（'),
+            ')' if after_cjk => Some('）'),
+            _ => None,
+        };
+        match converted {
+            Some(full) => {
+                output.push(full);
+                index += 1;
+                // 全角标点自带间距，去掉其后紧接中文或引号之前的空格。
+                let spaces = chars[index..].iter().take_while(|c| **c == ' ').count();
+                let next = chars.get(index + spaces).copied();
+                if spaces > 0
+                    && next
+                        .is_some_and(|c| is_cjk_context(c) || matches!(c, '"' | '\'' | '“' | '‘'))
+                {
+                    index += spaces;
+                }
+            }
+            None => {
+                output.push(ch);
+                index += 1;
+            }
+        }
+    }
+    pair_quotes(&mut output, &['"', '“', '”'], ('“', '”'));
+    pair_quotes(&mut output, &['\'', '‘', '’'], ('‘', '’'));
+    output.into_iter().collect()
+}
+
+/// 按出现顺序把引号改为交替的左右引号。
+///
+/// 单数个引号时最后一个保留为左引号，对应跨段引语只写开引号的习惯；
+/// 夹在两个英文字母之间的单引号视为撇号，不参与配对。
+fn pair_quotes(chars: &mut [char], quotes: &[char], (open, close): (char, char)) {
+    let mut opening = true;
+    for index in 0..chars.len() {
+        if !quotes.contains(&chars[index]) {
+            continue;
+        }
+        let apostrophe = index > 0
+            && chars[index - 1].is_ascii_alphabetic()
+            && chars
+                .get(index + 1)
+                .is_some_and(|c| c.is_ascii_alphabetic());
+        if apostrophe {
+            continue;
+        }
+        chars[index] = if opening { open } else { close };
+        opening = !opening;
+    }
+}
+
+fn half_width_line(chars: &[char]) -> String {
+    let mut output = String::with_capacity(chars.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        let run = chars[index..].iter().take_while(|&&c| c == ch).count();
+        let (narrow, spaced) = match ch {
+            '…' | '。' if ch == '…' || run >= 3 => {
+                index += run - 1;
+                ("...", true)
+            }
+            '，' | '、' => (",", true),
+            '。' => (".", true),
+            '！' => ("!", true),
+            '？' => ("?", true),
+            '：' => (":", true),
+            '；' => (";", true),
+            '）' => (")", true),
+            '（' => ("(", false),
+            '“' | '”' => ("\"", false),
+            '‘' | '’' => ("'", false),
+            '\u{3000}' => (" ", false),
+            _ => {
+                output.push(ch);
+                index += 1;
+                continue;
+            }
+        };
+        if ch == '（' && output.ends_with(|c: char| !c.is_whitespace() && c != '(') {
+            output.push(' ');
+        }
+        output.push_str(narrow);
+        index += 1;
+        if spaced && chars.get(index).is_some_and(|c| c.is_ascii_alphanumeric()) {
+            output.push(' ');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_width_converts_punctuation_next_to_chinese() {
+        let mode = PunctuationNormalization::FullWidth;
+        assert_eq!(
+            mode.normalize("他说, 好吧!你去哪(北京)? 版本1.2"),
+            "他说，好吧！你去哪（北京）？版本1.2"
+        );
+        assert_eq!(mode.normalize("等等。。。还有...和…"), "等等……还有……和……");
+        assert_eq!(mode.normalize("第１２章 ＡＢＣ"), "第12章 ABC");
+        // 不含中文的行保持不变。
+        assert_eq!(mode.normalize("Hello, world..."), "Hello, world...");
+    }
+
+    #[test]
+    fn full_width_repairs_quote_direction() {
+        let mode = PunctuationNormalization::FullWidth;
+        assert_eq!(mode.normalize("“你好“，他说。"), "“你好”，他说。");
+        assert_eq!(
+            mode.normalize("\"走吧\"，她说\"马上\""),
+            "“走吧”，她说“马上”"
+        );
+        assert_eq!(mode.normalize("‘好’的，don't 急"), "‘好’的，don't 急");
+        // 跨段引语只有开引号。
+        assert_eq!(mode.normalize("”第一段没有结束"), "“第一段没有结束");
+    }
+
+    #[test]
+    fn half_width_converts_english_lines() {
+        let mode = PunctuationNormalization::HalfWidth;
+        assert_eq!(
+            mode.normalize("Hello，world！“Yes”（maybe）……"),
+            "Hello, world!\"Yes\" (maybe)..."
+        );
+        assert_eq!(mode.normalize("中文，不变"), "中文，不变");
+    }
+
+    #[test]
+    fn keeps_line_endings_and_previews_changes() {
+        let mode = PunctuationNormalization::FullWidth;
+        let text = "第一行, 改\r\n不变。\n第三行!\n";
+        assert_eq!(mode.normalize(text), "第一行，改\r\n不变。\n第三行！\n");
+        let preview = mode.preview(text, 1);
+        assert_eq!(preview.total, 2);
+        assert_eq!(
+            preview.changes,
+            vec![PunctuationChange {
+                line: 1,
+                before: "第一行, 改".to_string(),
+                after: "第一行，改".to_string(),
+            }]
+        );
+        assert_eq!(PunctuationNormalization::Off.normalize(text), text);
+    }
+}
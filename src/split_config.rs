@@ -0,0 +1,139 @@
+//! “从文件加载”分章方式使用的配置文件。
+//!
+//! 配置为 TOML，可以写多条按优先顺序排列的章节正则、标题排除规则、卷标题正则
+//! 和分章前的文本清理规则：
+//!
+//! ```toml
+//! patterns = ['(?m)^第.+卷.*$', '(?m)^第.+章.*$']
+//! exclude = ['见书末$']
+//! volume_patterns = ['^第.+卷']
+//!
+//! [[cleanup]]
+//! find = '^本章完$'
+//! regex = true
+//! ```
+//!
+//! 旧版配置文件只有一行正则；非 `.toml` 文件无法按 TOML 解析时仍按这种格式读取。
+
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::CleanupRule;
+use crate::conversion::ConversionError;
+
+/// 结构化的分章配置。
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SplitConfig {
+    /// 章节标题正则，按优先顺序排列。
+    pub patterns: Vec<String>,
+    /// 标题排除规则：标题所在整行匹配任一规则时不分章。
+    pub exclude: Vec<String>,
+    /// 卷标题正则：匹配的章节作为卷，之后的章节归入该卷。
+    pub volume_patterns: Vec<String>,
+    /// 分章前依次应用的文本清理规则。
+    pub cleanup: Vec<CleanupRule>,
+}
+
+impl SplitConfig {
+    /// 读取配置文件；`.toml` 文件必须是合法的 TOML。
+    pub fn load(path: &Path) -> Result<Self, ConversionError> {
+        let text = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            Self::from_toml(&text)
+        } else {
+            Ok(Self::parse(&text))
+        }
+    }
+
+    /// 解析 TOML 配置。
+    pub fn from_toml(text: &str) -> Result<Self, ConversionError> {
+        toml::from_str(text).map_err(|err| {
+            ConversionError::InvalidInput(format!("Invalid split config: {}", err.message()))
+        })
+    }
+
+    /// 先按 TOML 解析，失败时把整个文件当作一条正则（旧版格式）。
+    pub fn parse(text: &str) -> Self {
+        Self::from_toml(text).unwrap_or_else(|_| Self {
+            patterns: vec![text.trim().to_string()],
+            ..Default::default()
+        })
+    }
+
+    /// 检查所有正则能否编译，并要求至少有一条章节正则。
+    pub fn validate(&self) -> Result<(), ConversionError> {
+        if self
+            .patterns
+            .iter()
+            .all(|pattern| pattern.trim().is_empty())
+        {
+            return Err(ConversionError::InvalidInput(
+                "The split config has no chapter patterns.".to_string(),
+            ));
+        }
+        for pattern in self
+            .patterns
+            .iter()
+            .chain(&self.exclude)
+            .chain(&self.volume_patterns)
+        {
+            Regex::new(pattern.trim())?;
+        }
+        for rule in self.cleanup.iter().filter(|rule| rule.regex) {
+            Regex::new(&rule.find)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_toml_and_legacy_regex_files() {
+        let config = SplitConfig::parse(
+            r#"
+patterns = ['^第.+卷', '^第.+章']
+exclude = ['见书末$']
+volume_patterns = ['^第.+卷']
+
+[[cleanup]]
+find = "广告"
+"#,
+        );
+        assert_eq!(config.patterns, ["^第.+卷", "^第.+章"]);
+        assert_eq!(config.exclude, ["见书末$"]);
+        assert_eq!(config.volume_patterns, ["^第.+卷"]);
+        assert_eq!(config.cleanup.len(), 1);
+        assert!(config.cleanup[0].enabled);
+        assert!(config.validate().is_ok());
+
+        let legacy = SplitConfig::parse("(?m)^Chapter \\d+\n");
+        assert_eq!(legacy.patterns, ["(?m)^Chapter \\d+"]);
+        assert!(legacy.exclude.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_empty_patterns() {
+        assert!(SplitConfig::from_toml("pattern = ['^第']").is_err());
+        assert!(
+            SplitConfig::from_toml("exclude = ['x']")
+                .unwrap()
+                .validate()
+                .is_err()
+        );
+        let invalid = SplitConfig {
+            patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(invalid.validate(), Err(ConversionError::Regex(_))));
+    }
+}
@@ -0,0 +1,221 @@
+//! TXT 文件头信息：识别正文开头 `书名：xxx` / `作者：yyy` / `简介：…` 一类的信息块。
+//!
+//! 网络小说的 TXT 常在第一章之前附带这样一段，识别后可以填入书籍信息，并在分章前从正文中
+//! 去掉，免得它被分进序章。键名支持 `书名：`、`书名:` 与 `【书名】` 三种写法；简介可以跨行，
+//! 遇到空行、下一个键或章节标题为止。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{BookInfo, Key, TextProcessor};
+
+static FIELD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:【\s*(?P<bracket>[^】]{1,8}?)\s*】|(?P<plain>[^:：]{1,12}?)\s*[:：])\s*(?P<value>.*)$")
+        .unwrap()
+});
+
+/// 只在开头这么多行之内寻找信息块。
+const MAX_HEADER_LINES: usize = 60;
+
+/// 文件头中识别到的书籍信息；未出现的字段为空字符串。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextHeader {
+    pub title: String,
+    pub author: String,
+    pub description: String,
+    pub publisher: String,
+    pub category: String,
+    pub series: String,
+    /// 信息块结束处的字节偏移，正文从这里开始。
+    pub end: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Author,
+    Description,
+    Publisher,
+    Category,
+    Series,
+}
+
+impl TextHeader {
+    /// 识别文本开头的信息块；没有书名或作者时返回 `None`。
+    pub fn detect(text: &str) -> Option<Self> {
+        let mut header = Self::default();
+        let mut offset = if text.starts_with('\u{feff}') { 3 } else { 0 };
+        let mut in_description = false;
+        let mut first_line = true;
+        for line in text[offset..].split_inclusive('\n').take(MAX_HEADER_LINES) {
+            let next = offset + line.len();
+            offset = next;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                in_description &= header.description.is_empty();
+                continue;
+            }
+            let is_first = std::mem::take(&mut first_line);
+            if let Some((field, value)) = parse_field(trimmed) {
+                header.set(field, value);
+                in_description = field == Field::Description;
+            } else if TextProcessor::is_chapter_title_line(trimmed) {
+                break;
+            } else if in_description {
+                header.description.push('\n');
+                header.description.push_str(trimmed);
+            } else if let Some(title) = is_first
+                .then(|| trimmed.strip_prefix('《')?.strip_suffix('》'))
+                .flatten()
+            {
+                // 开头单独一行的《书名》。
+                header.title = title.trim().to_string();
+            } else if !trimmed.chars().any(char::is_alphanumeric) {
+                // 分隔线，如 `----` 或 `==========`。
+            } else {
+                break;
+            }
+            header.end = next;
+        }
+        header.description = header.description.trim().to_string();
+        (!header.title.is_empty() || !header.author.is_empty()).then_some(header)
+    }
+
+    fn set(&mut self, field: Field, value: &str) {
+        let target = match field {
+            Field::Title => &mut self.title,
+            Field::Author => &mut self.author,
+            Field::Description => &mut self.description,
+            Field::Publisher => &mut self.publisher,
+            Field::Category => &mut self.category,
+            Field::Series => &mut self.series,
+        };
+        let value = value.trim();
+        *target = match field {
+            Field::Title => value.trim_start_matches('《').trim_end_matches('》'),
+            _ => value,
+        }
+        .to_string();
+    }
+
+    /// 界面显示用的字段列表，只含识别到的字段。
+    pub fn fields(&self) -> Vec<(Key, &str)> {
+        [
+            (Key::TitleLabel, &self.title),
+            (Key::AuthorLabel, &self.author),
+            (Key::Publisher, &self.publisher),
+            (Key::Category, &self.category),
+            (Key::Series, &self.series),
+            (Key::Description, &self.description),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(key, value)| (key, value.as_str()))
+        .collect()
+    }
+
+    /// 把识别到的字段填入书籍信息；`overwrite` 为 `false` 时只填写空字段。
+    pub fn apply_to(&self, info: &mut BookInfo, overwrite: bool) {
+        for (value, target) in [
+            (&self.title, &mut info.title),
+            (&self.description, &mut info.description),
+            (&self.publisher, &mut info.publisher),
+            (&self.category, &mut info.category),
+            (&self.series, &mut info.series),
+        ] {
+            if !value.is_empty() && (overwrite || target.trim().is_empty()) {
+                target.clone_from(value);
+            }
+        }
+        if !self.author.is_empty() && (overwrite || info.author().is_empty()) {
+            info.primary_author_mut().clone_from(&self.author);
+        }
+    }
+}
+
+/// 去掉开头的信息块；没有识别到信息块时原样返回。
+pub fn strip_text_header(text: &str) -> &str {
+    match TextHeader::detect(text) {
+        Some(header) => text[header.end..].trim_start_matches(['\r', '\n']),
+        None => text,
+    }
+}
+
+/// 启用时去掉开头的信息块，否则原样返回。
+pub fn strip_text_header_if(text: &str, enabled: bool) -> &str {
+    if enabled {
+        strip_text_header(text)
+    } else {
+        text
+    }
+}
+
+fn parse_field(line: &str) -> Option<(Field, &str)> {
+    let caps = FIELD.captures(line)?;
+    let key = caps
+        .name("bracket")
+        .or_else(|| caps.name("plain"))?
+        .as_str();
+    let key: String = key
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    let field = match key.as_str() {
+        "书名" | "書名" | "作品名" | "作品名称" | "小说名" | "标题" | "title" => {
+            Field::Title
+        }
+        "作者" | "著者" | "原作者" | "author" => Field::Author,
+        "简介" | "簡介" | "内容简介" | "內容簡介" | "作品简介" | "内容介绍" | "文案" | "摘要"
+        | "description" | "summary" => Field::Description,
+        "出版社" | "出版" | "publisher" => Field::Publisher,
+        "分类" | "分類" | "类别" | "类型" | "题材" | "标签" | "category" | "genre" => {
+            Field::Category
+        }
+        "系列" | "丛书" | "series" => Field::Series,
+        _ => return None,
+    };
+    Some((field, caps.name("value").map_or("", |value| value.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "\u{feff}《雾镇》\n书名：《雾镇》\n作 者: 某人\n【类型】悬疑\n\n内容简介：\n　　小镇被雾笼罩。\n　　少年决定出发。\n\n==========\n\n第一章 开端\n正文\n";
+
+    #[test]
+    fn parses_header_fields() {
+        let header = TextHeader::detect(TEXT).expect("header");
+        assert_eq!(header.title, "雾镇");
+        assert_eq!(header.author, "某人");
+        assert_eq!(header.category, "悬疑");
+        assert_eq!(header.description, "小镇被雾笼罩。\n少年决定出发。");
+        assert_eq!(strip_text_header(TEXT), "第一章 开端\n正文\n");
+
+        let mut info = BookInfo {
+            title: "文件名".to_string(),
+            publisher: "保留".to_string(),
+            ..Default::default()
+        };
+        header.apply_to(&mut info, false);
+        assert_eq!(info.title, "文件名");
+        assert_eq!(info.author(), "某人");
+        header.apply_to(&mut info, true);
+        assert_eq!(info.title, "雾镇");
+        assert_eq!(info.publisher, "保留");
+    }
+
+    #[test]
+    fn ignores_text_without_header() {
+        for text in [
+            "第一章 开端\n书名：不是文件头\n",
+            "他说：好。\n作者：某人\n",
+            "类型：悬疑\n",
+        ] {
+            assert_eq!(TextHeader::detect(text), None, "{text}");
+            assert_eq!(strip_text_header(text), text);
+        }
+        assert_eq!(strip_text_header_if(TEXT, false), TEXT);
+    }
+}
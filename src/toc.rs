@@ -0,0 +1,123 @@
+//! 文本开头的内嵌目录。
+//!
+//! 不少 TXT 在正文前附带一份“目录”，列出全部章节标题。识别出这份列表后，
+//! 既可以把它从正文中剥离（避免目录条目被当成空章节），也可以直接用这些标题分章，
+//! 适合标题没有统一格式、难以写出正则的书。
+
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// 目录标记行，去掉首尾空白后整行比较。
+const TOC_MARKERS: [&str; 5] = ["目录", "目 录", "目次", "contents", "table of contents"];
+/// 只在文本开头这么多行内查找目录标记。
+const TOC_SEARCH_LINES: usize = 200;
+/// 超过该字数的行不视为目录条目。
+const MAX_TITLE_CHARS: usize = 30;
+/// 至少要有这么多条目才视为目录。
+const MIN_TITLES: usize = 2;
+
+/// 识别出的内嵌目录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineToc {
+    /// 目录中的章节标题，按出现顺序去重。
+    pub titles: Vec<String>,
+    /// 目录块所占的行（含标记行），用于从正文中剥离。
+    lines: RangeInclusive<usize>,
+}
+
+impl InlineToc {
+    /// 在文本开头查找“目录”标记及其后的标题列表。
+    ///
+    /// 标题列表在遇到含句读的行、过长的行或重复的标题（即正文开始）时结束。
+    pub fn detect(text: &str) -> Option<Self> {
+        let lines: Vec<&str> = text.lines().collect();
+        let marker = lines
+            .iter()
+            .take(TOC_SEARCH_LINES)
+            .position(|line| is_marker(line.trim()))?;
+
+        let mut titles = Vec::new();
+        let mut seen = HashSet::new();
+        let mut last = marker;
+        for (index, raw) in lines.iter().enumerate().skip(marker + 1) {
+            let line = raw.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if !is_title_line(line) || !seen.insert(line) {
+                break;
+            }
+            titles.push(line.to_string());
+            last = index;
+        }
+
+        (titles.len() >= MIN_TITLES).then_some(Self {
+            titles,
+            lines: marker..=last,
+        })
+    }
+
+    /// 去掉目录块（标记行与各条目），其余内容保持不变。
+    pub fn strip(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for (index, line) in text.lines().enumerate() {
+            if self.lines.contains(&index) {
+                continue;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// 逐行匹配目录标题的分章正则。
+    pub fn pattern(&self) -> String {
+        let joined = self
+            .titles
+            .iter()
+            .map(|title| regex::escape(title))
+            .collect::<Vec<_>>()
+            .join("|");
+        format!(r"(?m)^\s*(?:{joined})\s*$")
+    }
+}
+
+fn is_marker(line: &str) -> bool {
+    TOC_MARKERS
+        .iter()
+        .any(|marker| line.eq_ignore_ascii_case(marker))
+}
+
+fn is_title_line(line: &str) -> bool {
+    line.chars().count() <= MAX_TITLE_CHARS && !line.contains(['。', '，', '？', '！', '；'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str =
+        "书名\n\n目录\n序幕\n\n数过去\n数盘子\n\n序幕\n\n正文第一段。\n数过去\n正文。\n";
+
+    #[test]
+    fn detects_and_strips_leading_toc() {
+        let toc = InlineToc::detect(TEXT).expect("toc");
+        assert_eq!(toc.titles, ["序幕", "数过去", "数盘子"]);
+        assert_eq!(
+            toc.strip(TEXT),
+            "书名\n\n\n序幕\n\n正文第一段。\n数过去\n正文。\n"
+        );
+
+        let re = regex::Regex::new(&toc.pattern()).expect("pattern");
+        assert!(re.is_match("\u{3000}\u{3000}数过去"));
+        assert!(!re.is_match("数过去了"));
+    }
+
+    #[test]
+    fn ignores_text_without_a_usable_toc() {
+        assert!(InlineToc::detect("第一章\n正文。").is_none());
+        assert!(InlineToc::detect("目录\n只有一条\n\n这是正文，开始了。").is_none());
+        let late = format!("{}目录\n甲\n乙\n", "正文。\n".repeat(TOC_SEARCH_LINES));
+        assert!(InlineToc::detect(&late).is_none());
+    }
+}
@@ -0,0 +1,259 @@
+//! 智能排版：直引号改为弯引号，`--` 改为破折号，`...` 改为省略号。
+//!
+//! 引号与破折号按书籍语言选择规则：英文 “”‘’ 与 em dash，德文 „“‚‘ 与 en dash，
+//! 法文 « »（内侧加不换行空格），俄文 « » 与 „“。中文、日文与韩文书籍不处理；
+//! 其他语言的书中含汉字、假名或谚文的行也原样保留，这些行交给 [`crate::punctuation`]。
+
+use crate::ChapterDraft;
+
+/// 一种语言的替换规则。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypographyRules {
+    /// 双引号的开、闭符号。
+    pub double: (&'static str, &'static str),
+    /// 单引号的开、闭符号；词中的撇号总是 `’`。
+    pub single: (&'static str, &'static str),
+    /// `--` 替换成的破折号。
+    pub dash: &'static str,
+}
+
+const ENGLISH: TypographyRules = TypographyRules {
+    double: ("“", "”"),
+    single: ("‘", "’"),
+    dash: "—",
+};
+
+const GERMAN: TypographyRules = TypographyRules {
+    double: ("„", "“"),
+    single: ("‚", "‘"),
+    dash: "–",
+};
+
+const FRENCH: TypographyRules = TypographyRules {
+    double: ("«\u{a0}", "\u{a0}»"),
+    single: ("‹\u{a0}", "\u{a0}›"),
+    dash: "—",
+};
+
+const RUSSIAN: TypographyRules = TypographyRules {
+    double: ("«", "»"),
+    single: ("„", "“"),
+    dash: "—",
+};
+
+impl TypographyRules {
+    /// 按 BCP 47 语言标签选择规则；中文、日文与韩文返回 `None`，未知语言按英文处理。
+    pub fn for_language(language: &str) -> Option<Self> {
+        let primary = language
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "zh" | "ja" | "ko" => None,
+            "de" => Some(GERMAN),
+            "fr" => Some(FRENCH),
+            "ru" | "uk" | "be" => Some(RUSSIAN),
+            _ => Some(ENGLISH),
+        }
+    }
+
+    /// 处理整段文本并保留换行；含中日韩文字的行与场景分隔线原样保留。
+    pub fn apply(&self, text: &str) -> String {
+        text.split_inclusive('\n')
+            .map(|line| {
+                let content = line.trim_end_matches(['\r', '\n']);
+                if content.chars().any(is_cjk) || is_rule_line(content) {
+                    return line.to_string();
+                }
+                let mut output = self.apply_line(content);
+                output.push_str(&line[content.len()..]);
+                output
+            })
+            .collect()
+    }
+
+    /// 对章节标题与正文应用规则。
+    pub fn apply_chapter(&self, chapter: &ChapterDraft) -> ChapterDraft {
+        ChapterDraft {
+            title: self.apply(&chapter.title),
+            content: self.apply(&chapter.content),
+            ..chapter.clone()
+        }
+    }
+
+    fn apply_line(&self, line: &str) -> String {
+        let chars: Vec<char> = line.chars().collect();
+        let mut output = String::with_capacity(line.len());
+        let mut index = 0;
+        let mut double_open = false;
+        while index < chars.len() {
+            let ch = chars[index];
+            let next = chars.get(index + 1).copied();
+            match ch {
+                // 转义的字符与 Markdown 链接目标 `](...)` 原样保留。
+                '\\' if next.is_some() => {
+                    output.extend(&chars[index..index + 2]);
+                    index += 2;
+                    continue;
+                }
+                ']' if next == Some('(') => {
+                    if let Some(close) = chars[index..].iter().position(|c| *c == ')') {
+                        output.extend(&chars[index..=index + close]);
+                        index += close + 1;
+                        continue;
+                    }
+                    output.push(ch);
+                }
+                '.' if chars[index..].starts_with(&['.', '.', '.']) => {
+                    output.push('…');
+                    index += 3;
+                    continue;
+                }
+                '-' if next == Some('-') => {
+                    output.push_str(self.dash);
+                    index += 2;
+                    while chars.get(index) == Some(&'-') {
+                        index += 1;
+                    }
+                    continue;
+                }
+                '"' => {
+                    let quotes = self.double;
+                    // 法文习惯在引号内侧留空格，无法凭后一个字符判断，改为按是否已有未闭合的引号判断。
+                    let open = if quotes.0.ends_with('\u{a0}') {
+                        !double_open && self.opens(&output, None)
+                    } else {
+                        self.opens(&output, next)
+                    };
+                    double_open = open;
+                    self.push_quote(&mut output, quotes, open);
+                    index += 1;
+                    if open {
+                        index = skip_spaces(&chars, index, quotes.0);
+                    }
+                    continue;
+                }
+                '\'' => {
+                    let prev = output.chars().next_back();
+                    let apostrophe = prev.is_some_and(char::is_alphanumeric)
+                        || (self.opens(&output, next) && next.is_some_and(|c| c.is_ascii_digit()));
+                    if apostrophe {
+                        output.push('’');
+                    } else {
+                        let quotes = self.single;
+                        let open = self.opens(&output, next);
+                        self.push_quote(&mut output, quotes, open);
+                        if open {
+                            index = skip_spaces(&chars, index + 1, quotes.0);
+                            continue;
+                        }
+                    }
+                }
+                _ => output.push(ch),
+            }
+            index += 1;
+        }
+        output
+    }
+
+    /// 引号是否为开引号：位于行首、空白、左括号、破折号或开引号之后，且后面还有文字。
+    fn opens(&self, output: &str, next: Option<char>) -> bool {
+        let after_opening = match output.chars().next_back() {
+            None => true,
+            Some(prev) => {
+                prev.is_whitespace()
+                    || matches!(prev, '(' | '[' | '{' | '—' | '–' | '-' | '/')
+                    || self.double.0.ends_with(prev)
+                    || self.single.0.ends_with(prev)
+            }
+        };
+        after_opening && next.is_none_or(|c| !c.is_whitespace())
+    }
+
+    fn push_quote(&self, output: &mut String, (open, close): (&str, &str), opening: bool) {
+        if opening {
+            output.push_str(open);
+        } else {
+            // 法文闭引号自带不换行空格，去掉原文中引号前的空格。
+            if close.starts_with('\u{a0}') {
+                output.truncate(output.trim_end_matches(' ').len());
+            }
+            output.push_str(close);
+        }
+    }
+}
+
+/// 按 `language` 的规则处理全部章节；语言无需处理时返回 `None`。
+pub fn smarten_chapters(chapters: &[ChapterDraft], language: &str) -> Option<Vec<ChapterDraft>> {
+    let rules = TypographyRules::for_language(language)?;
+    Some(
+        chapters
+            .iter()
+            .map(|chapter| rules.apply_chapter(chapter))
+            .collect(),
+    )
+}
+
+/// 开引号自带不换行空格时，跳过原文中紧随其后的空格。
+fn skip_spaces(chars: &[char], mut index: usize, open: &str) -> usize {
+    if open.ends_with('\u{a0}') {
+        while chars.get(index) == Some(&' ') {
+            index += 1;
+        }
+    }
+    index
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch, '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}')
+}
+
+/// `---`、`* * *` 等场景分隔线与 Markdown 水平线。
+fn is_rule_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && trimmed
+            .chars()
+            .all(|c| matches!(c, '-' | '*' | '_' | '~' | '=' | ' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_quotes_dashes_and_ellipses() {
+        let rules = TypographyRules::for_language("en-US").expect("rules");
+        assert_eq!(
+            rules.apply("\"Don't go--please...\" she said. 'Yes,' he said."),
+            "“Don’t go—please…” she said. ‘Yes,’ he said."
+        );
+        assert_eq!(rules.apply("the '90s (\"quoted\")"), "the ’90s (“quoted”)");
+        // 转义、链接目标与分隔线保持不变。
+        assert_eq!(
+            rules.apply("\\\"raw\\\" [it's](http://a.com/it's--x)\n---\n"),
+            "\\\"raw\\\" [it’s](http://a.com/it's--x)\n---\n"
+        );
+    }
+
+    #[test]
+    fn language_rulesets() {
+        let german = TypographyRules::for_language("de").expect("de");
+        assert_eq!(german.apply("\"Ja\" -- sagte er."), "„Ja“ – sagte er.");
+        let french = TypographyRules::for_language("fr-FR").expect("fr");
+        assert_eq!(french.apply("\" Bonjour \""), "«\u{a0}Bonjour\u{a0}»");
+        assert_eq!(TypographyRules::for_language("zh-CN"), None);
+        assert_eq!(TypographyRules::for_language("ja"), None);
+    }
+
+    #[test]
+    fn skips_cjk_lines() {
+        let rules = TypographyRules::for_language("en").expect("rules");
+        assert_eq!(
+            rules.apply("\"中文\"...\n\"English\"...\r\n"),
+            "\"中文\"...\n“English”…\r\n"
+        );
+    }
+}
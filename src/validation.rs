@@ -0,0 +1,452 @@
+//! 生成后的 EPUB 自检：在不依赖外部工具的前提下检查常见的打包错误。
+//!
+//! 检查项：`mimetype` 是否为首个且未压缩的条目、`container.xml` 与 OPF 能否解析、
+//! 清单中的文件是否都存在、XHTML/OPF/NCX 是否为格式良好的 XML、
+//! 目录（`toc.ncx` 与 `nav.xhtml`）中的链接能否解析，以及图片的 MIME 类型是否与实际数据一致。
+//!
+//! 自检不替代 epubcheck，只用于在转换完成时尽早发现明显问题。
+
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::ocf::{
+    CONTAINER_PATH, ManifestItem, attribute, parent_dir, parse_opf, resolve_href, rootfile_path,
+};
+
+const MIMETYPE: &str = "application/epub+zip";
+
+/// 一条自检问题：出问题的容器内路径（整体问题为空）与说明。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// 读取并检查磁盘上的 EPUB 文件。
+pub fn validate_epub(path: &Path) -> std::io::Result<Vec<ValidationIssue>> {
+    let file = std::fs::File::open(path)?;
+    Ok(validate_archive(file))
+}
+
+/// 检查内存中的 EPUB 数据。
+pub fn validate_epub_bytes(bytes: &[u8]) -> Vec<ValidationIssue> {
+    validate_archive(Cursor::new(bytes))
+}
+
+fn validate_archive<R: Read + Seek>(reader: R) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut archive = match ZipArchive::new(reader) {
+        Ok(archive) => archive,
+        Err(err) => {
+            issues.push(ValidationIssue::new(
+                "",
+                format!("not a ZIP archive: {err}"),
+            ));
+            return issues;
+        }
+    };
+    let names: HashSet<String> = archive.file_names().map(str::to_string).collect();
+
+    check_mimetype(&mut archive, &mut issues);
+
+    let Some(container) = read_text(&mut archive, CONTAINER_PATH, &mut issues) else {
+        return issues;
+    };
+    if let Err(err) = check_well_formed(&container) {
+        issues.push(ValidationIssue::new(CONTAINER_PATH, err));
+        return issues;
+    }
+    let Some(opf_path) = rootfile_path(&container) else {
+        issues.push(ValidationIssue::new(
+            CONTAINER_PATH,
+            "no rootfile full-path found",
+        ));
+        return issues;
+    };
+    let Some(opf) = read_text(&mut archive, &opf_path, &mut issues) else {
+        return issues;
+    };
+    if let Err(err) = check_well_formed(&opf) {
+        issues.push(ValidationIssue::new(opf_path.as_str(), err));
+        return issues;
+    }
+
+    let opf_dir = parent_dir(&opf_path);
+    let (manifest, spine) = parse_opf(&opf, opf_dir);
+    let ids: HashSet<&str> = manifest.iter().map(|item| item.id.as_str()).collect();
+    for idref in &spine {
+        if !ids.contains(idref.as_str()) {
+            issues.push(ValidationIssue::new(
+                opf_path.as_str(),
+                format!("spine itemref \"{idref}\" is not in the manifest"),
+            ));
+        }
+    }
+
+    for item in &manifest {
+        if !names.contains(&item.path) {
+            issues.push(ValidationIssue::new(
+                item.path.as_str(),
+                "listed in the manifest but missing from the archive",
+            ));
+            continue;
+        }
+        if is_xml_media_type(&item.media_type) {
+            let Some(text) = read_text(&mut archive, &item.path, &mut issues) else {
+                continue;
+            };
+            if let Err(err) = check_well_formed(&text) {
+                issues.push(ValidationIssue::new(item.path.as_str(), err));
+                continue;
+            }
+            let links = if item.media_type == "application/x-dtbncx+xml" {
+                ncx_links(&text)
+            } else if item.properties.split_whitespace().any(|p| p == "nav") {
+                nav_links(&text)
+            } else {
+                Vec::new()
+            };
+            check_links(&item.path, &links, &names, &mut issues);
+        } else if item.media_type.starts_with("image/") {
+            check_image(&mut archive, item, &mut issues);
+        }
+    }
+    issues
+}
+
+fn check_mimetype<R: Read + Seek>(archive: &mut ZipArchive<R>, issues: &mut Vec<ValidationIssue>) {
+    let Ok(mut first) = archive.by_index(0) else {
+        issues.push(ValidationIssue::new("", "archive is empty"));
+        return;
+    };
+    if first.name() != "mimetype" {
+        issues.push(ValidationIssue::new(
+            "mimetype",
+            "must be the first entry in the archive",
+        ));
+        return;
+    }
+    if first.compression() != CompressionMethod::Stored {
+        issues.push(ValidationIssue::new(
+            "mimetype",
+            "must be stored uncompressed",
+        ));
+    }
+    let mut content = String::new();
+    if first.read_to_string(&mut content).is_err() || content != MIMETYPE {
+        issues.push(ValidationIssue::new(
+            "mimetype",
+            format!("must contain exactly \"{MIMETYPE}\""),
+        ));
+    }
+}
+
+fn read_text<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &str,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<String> {
+    let mut entry = match archive.by_name(path) {
+        Ok(entry) => entry,
+        Err(_) => {
+            issues.push(ValidationIssue::new(path, "missing from the archive"));
+            return None;
+        }
+    };
+    let mut text = String::new();
+    match entry.read_to_string(&mut text) {
+        Ok(_) => Some(text),
+        Err(err) => {
+            issues.push(ValidationIssue::new(path, format!("unreadable: {err}")));
+            None
+        }
+    }
+}
+
+/// 检查 XML 是否格式良好：标签闭合匹配、属性合法，且只使用 XML 预定义实体。
+fn check_well_formed(text: &str) -> Result<(), String> {
+    let mut reader = Reader::from_str(text);
+    let mut depth = 0usize;
+    loop {
+        let position = reader.buffer_position();
+        let event = reader
+            .read_event()
+            .map_err(|err| format!("malformed XML near byte {position}: {err}"))?;
+        match event {
+            Event::Start(start) => {
+                check_attributes(&start)?;
+                depth += 1;
+            }
+            Event::Empty(start) => check_attributes(&start)?,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::GeneralRef(reference) => {
+                if reference.is_char_ref() {
+                    reference
+                        .resolve_char_ref()
+                        .map_err(|err| format!("invalid character reference: {err}"))?;
+                } else {
+                    let name = String::from_utf8_lossy(reference.as_ref()).to_string();
+                    if !matches!(name.as_str(), "amp" | "lt" | "gt" | "quot" | "apos") {
+                        return Err(format!("undefined entity &{name};"));
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return Err("unexpected end of document: unclosed elements".to_string());
+    }
+    Ok(())
+}
+
+fn check_attributes(start: &BytesStart<'_>) -> Result<(), String> {
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|err| format!("malformed attribute: {err}"))?;
+        attribute
+            .unescape_value()
+            .map_err(|err| format!("malformed attribute value: {err}"))?;
+    }
+    Ok(())
+}
+
+fn ncx_links(ncx: &str) -> Vec<String> {
+    collect_attribute(ncx, b"content", "src")
+}
+
+fn nav_links(nav: &str) -> Vec<String> {
+    collect_attribute(nav, b"a", "href")
+}
+
+fn collect_attribute(text: &str, element: &[u8], name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut reader = Reader::from_str(text);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start) | Event::Empty(start))
+                if start.local_name().as_ref() == element =>
+            {
+                if let Some(value) = attribute(&start, name) {
+                    values.push(value);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    values
+}
+
+fn check_links(
+    source: &str,
+    links: &[String],
+    names: &HashSet<String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let dir = parent_dir(source);
+    for link in links {
+        if link.contains("://") || link.starts_with("mailto:") {
+            continue;
+        }
+        let target = link.split('#').next().unwrap_or_default();
+        if target.is_empty() {
+            continue;
+        }
+        if !names.contains(&resolve_href(dir, target)) {
+            issues.push(ValidationIssue::new(
+                source,
+                format!("TOC link \"{link}\" does not resolve to a file"),
+            ));
+        }
+    }
+}
+
+fn check_image<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    item: &ManifestItem,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let mut bytes = Vec::new();
+    let Ok(mut entry) = archive.by_name(&item.path) else {
+        return;
+    };
+    if entry.read_to_end(&mut bytes).is_err() {
+        issues.push(ValidationIssue::new(item.path.as_str(), "unreadable"));
+        return;
+    }
+    let actual = if item.media_type == "image/svg+xml" {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_string();
+        head.contains("<svg").then_some("image/svg+xml")
+    } else {
+        image::guess_format(&bytes)
+            .ok()
+            .map(|format| format.to_mime_type())
+    };
+    match actual {
+        Some(actual) if actual == item.media_type => {}
+        Some(actual) => issues.push(ValidationIssue::new(
+            item.path.as_str(),
+            format!("declared as {} but the data is {}", item.media_type, actual),
+        )),
+        None => issues.push(ValidationIssue::new(
+            item.path.as_str(),
+            format!(
+                "declared as {} but the data is not a recognized image",
+                item.media_type
+            ),
+        )),
+    }
+}
+
+fn is_xml_media_type(media_type: &str) -> bool {
+    matches!(
+        media_type,
+        "application/xhtml+xml" | "application/x-dtbncx+xml" | "image/svg+xml"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?><container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container"><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#;
+
+    fn png_bytes() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(2, 2)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .expect("png");
+        bytes
+    }
+
+    fn epub(entries: &[(&str, &[u8])], compress_mimetype: bool) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            let method = if *name == "mimetype" && !compress_mimetype {
+                CompressionMethod::Stored
+            } else {
+                CompressionMethod::Deflated
+            };
+            zip.start_file(
+                *name,
+                SimpleFileOptions::default().compression_method(method),
+            )
+            .expect("start");
+            zip.write_all(data).expect("write");
+        }
+        zip.finish().expect("finish").into_inner()
+    }
+
+    fn opf(items: &str, spine: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><package xmlns="http://www.idpf.org/2007/opf" version="2.0"><manifest><item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>{items}</manifest><spine toc="ncx">{spine}</spine></package>"#
+        )
+    }
+
+    fn ncx(src: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><ncx xmlns="http://www.daisy.org/z3986/2005/ncx/"><navMap><navPoint id="n1"><navLabel><text>One</text></navLabel><content src="{src}"/></navPoint></navMap></ncx>"#
+        )
+    }
+
+    const CHAPTER: &str = r#"<?xml version="1.0"?><html xmlns="http://www.w3.org/1999/xhtml"><body><p>One &amp; two</p></body></html>"#;
+
+    #[test]
+    fn validate_accepts_minimal_epub() {
+        let png = png_bytes();
+        let opf = opf(
+            r#"<item id="c1" href="chapter%201.xhtml" media-type="application/xhtml+xml"/><item id="img" href="images/a.png" media-type="image/png"/>"#,
+            r#"<itemref idref="c1"/>"#,
+        );
+        let ncx = ncx("chapter%201.xhtml#top");
+        let bytes = epub(
+            &[
+                ("mimetype", MIMETYPE.as_bytes()),
+                (CONTAINER_PATH, CONTAINER.as_bytes()),
+                ("OEBPS/content.opf", opf.as_bytes()),
+                ("OEBPS/toc.ncx", ncx.as_bytes()),
+                ("OEBPS/chapter 1.xhtml", CHAPTER.as_bytes()),
+                ("OEBPS/images/a.png", &png),
+            ],
+            false,
+        );
+        assert_eq!(validate_epub_bytes(&bytes), Vec::new());
+    }
+
+    #[test]
+    fn validate_reports_packaging_problems() {
+        let opf = opf(
+            r#"<item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/><item id="gone" href="gone.xhtml" media-type="application/xhtml+xml"/><item id="img" href="a.jpg" media-type="image/jpeg"/>"#,
+            r#"<itemref idref="c1"/><itemref idref="missing"/>"#,
+        );
+        let ncx = ncx("nowhere.xhtml");
+        let png = png_bytes();
+        let bytes = epub(
+            &[
+                ("mimetype", MIMETYPE.as_bytes()),
+                (CONTAINER_PATH, CONTAINER.as_bytes()),
+                ("OEBPS/content.opf", opf.as_bytes()),
+                ("OEBPS/toc.ncx", ncx.as_bytes()),
+                (
+                    "OEBPS/c1.xhtml",
+                    b"<html><body><p>open &nbsp;</body></html>",
+                ),
+                ("OEBPS/a.jpg", &png),
+            ],
+            true,
+        );
+        let issues: Vec<String> = validate_epub_bytes(&bytes)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let has = |needle: &str| issues.iter().any(|issue| issue.contains(needle));
+        assert!(has("mimetype: must be stored uncompressed"), "{issues:?}");
+        assert!(has("spine itemref \"missing\""), "{issues:?}");
+        assert!(
+            has("OEBPS/gone.xhtml: listed in the manifest"),
+            "{issues:?}"
+        );
+        assert!(has("OEBPS/c1.xhtml: undefined entity &nbsp;"), "{issues:?}");
+        assert!(has("TOC link \"nowhere.xhtml\""), "{issues:?}");
+        assert!(
+            has("OEBPS/a.jpg: declared as image/jpeg but the data is image/png"),
+            "{issues:?}"
+        );
+    }
+
+    #[test]
+    fn check_well_formed_rejects_html_entities_and_unclosed_tags() {
+        assert!(check_well_formed("<p>a&#160;b &lt;</p>").is_ok());
+        assert!(check_well_formed("<p>a&nbsp;b</p>").is_err());
+        assert!(check_well_formed("<div><p>a</p>").is_err());
+        assert!(check_well_formed("<p class=\"a\" class=\"b\">x</p>").is_err());
+    }
+}
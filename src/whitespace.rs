@@ -0,0 +1,169 @@
+//! 空白与不可见字符清理：分章前去掉 BOM、零宽空格、软连字符与行尾空白，
+//! 并把连续三个以上的空行合并为一个。
+//!
+//! 网页抓取的 TXT 常夹带这些字符，使 `^第.+章$` 一类的标题正则匹配失败。零宽连接符
+//! U+200C / U+200D 会影响 emoji 与部分文字的显示，不做处理。合并空行后，正文中以连续
+//! 空行表示的场景分隔也随之消失。
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::{Key, Locale, t, t1};
+
+/// 清理掉的字符数，按类别统计。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WhitespaceSummary {
+    /// 字节顺序标记 U+FEFF。
+    pub bom: usize,
+    /// 零宽空格 U+200B 与单词连接符 U+2060。
+    pub zero_width: usize,
+    /// 软连字符 U+00AD。
+    pub soft_hyphens: usize,
+    /// 行尾的空格、制表符、不换行空格与全角空格。
+    pub trailing_spaces: usize,
+    /// 合并连续空行时去掉的空行（每行计一个换行符）。
+    pub blank_lines: usize,
+}
+
+impl WhitespaceSummary {
+    /// 去掉的字符总数。
+    pub fn total(&self) -> usize {
+        self.bom + self.zero_width + self.soft_hyphens + self.trailing_spaces + self.blank_lines
+    }
+
+    /// 界面显示的分类明细，如 `BOM 1 · 零宽空格 8 · 行尾空白 20`；数量为零的类别不显示。
+    pub fn details(&self, locale: Locale) -> String {
+        [
+            (Key::WhitespaceBom, self.bom),
+            (Key::WhitespaceZeroWidth, self.zero_width),
+            (Key::WhitespaceSoftHyphens, self.soft_hyphens),
+            (Key::WhitespaceTrailing, self.trailing_spaces),
+            (Key::WhitespaceBlankLines, self.blank_lines),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(key, count)| t1(locale, key, count))
+        .collect::<Vec<_>>()
+        .join(" · ")
+    }
+
+    /// 界面显示的一行摘要。
+    pub fn label(&self, locale: Locale) -> String {
+        match self.total() {
+            0 => t(locale, Key::WhitespaceNothing).to_string(),
+            total => t1(locale, Key::WhitespaceRemoved, total),
+        }
+    }
+}
+
+impl fmt::Display for WhitespaceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "whitespace cleanup removed {} characters: {} BOM, {} zero-width, {} soft hyphens, \
+{} trailing spaces, {} blank lines.",
+            self.total(),
+            self.bom,
+            self.zero_width,
+            self.soft_hyphens,
+            self.trailing_spaces,
+            self.blank_lines
+        )
+    }
+}
+
+/// 清理文本并返回各类被去掉的字符数；换行符（`\n` 或 `\r\n`）保持原样。
+pub fn clean_whitespace(text: &str) -> (String, WhitespaceSummary) {
+    let mut summary = WhitespaceSummary::default();
+    let mut output = String::with_capacity(text.len());
+    // 尚未输出的连续空行的换行符，遇到非空行或文本结束时决定保留几行。
+    let mut blank_endings: Vec<&str> = Vec::new();
+    for line in text.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[body.len()..];
+        let mut cleaned = String::with_capacity(body.len());
+        for ch in body.chars() {
+            match ch {
+                '\u{feff}' => summary.bom += 1,
+                '\u{200b}' | '\u{2060}' => summary.zero_width += 1,
+                '\u{ad}' => summary.soft_hyphens += 1,
+                _ => cleaned.push(ch),
+            }
+        }
+        let kept = cleaned
+            .trim_end_matches([' ', '\t', '\u{a0}', '\u{3000}'])
+            .len();
+        summary.trailing_spaces += cleaned[kept..].chars().count();
+        cleaned.truncate(kept);
+
+        if cleaned.is_empty() {
+            blank_endings.push(ending);
+            continue;
+        }
+        flush_blank_lines(&mut output, &mut blank_endings, &mut summary);
+        output.push_str(&cleaned);
+        output.push_str(ending);
+    }
+    flush_blank_lines(&mut output, &mut blank_endings, &mut summary);
+    (output, summary)
+}
+
+/// 启用时清理文本，否则原样借用；用于分章预览等不需要统计的场合。
+pub fn clean_whitespace_if(text: &str, enabled: bool) -> Cow<'_, str> {
+    if enabled {
+        Cow::Owned(clean_whitespace(text).0)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// 输出缓存的空行：一到两行原样保留，三行以上只保留一行。
+fn flush_blank_lines(
+    output: &mut String,
+    blank_endings: &mut Vec<&str>,
+    summary: &mut WhitespaceSummary,
+) {
+    if blank_endings.len() >= 3 {
+        summary.blank_lines += blank_endings.len() - 1;
+        blank_endings.truncate(1);
+    }
+    for ending in blank_endings.drain(..) {
+        output.push_str(ending);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_invisible_characters_and_trailing_spaces() {
+        let (text, summary) =
+            clean_whitespace("\u{feff}第\u{200b}一章 \t\r\n　　正文\u{ad}内容\u{3000}\n结尾");
+        assert_eq!(text, "第一章\r\n　　正文内容\n结尾");
+        assert_eq!(
+            summary,
+            WhitespaceSummary {
+                bom: 1,
+                zero_width: 1,
+                soft_hyphens: 1,
+                trailing_spaces: 3,
+                blank_lines: 0,
+            }
+        );
+        assert_eq!(summary.total(), 6);
+    }
+
+    #[test]
+    fn collapses_three_or_more_blank_lines() {
+        let (text, summary) = clean_whitespace("一\n\n二\n\n\n  \n\n三\n\n\n\n");
+        assert_eq!(text, "一\n\n二\n\n三\n\n");
+        assert_eq!(summary.blank_lines, 5);
+        assert_eq!(summary.trailing_spaces, 2);
+
+        let (text, summary) = clean_whitespace("emoji 👩\u{200d}💻 stays\n");
+        assert_eq!(text, "emoji 👩\u{200d}💻 stays\n");
+        assert_eq!(summary.total(), 0);
+        assert_eq!(summary.label(Locale::En), "Nothing to remove.");
+    }
+}
@@ -0,0 +1,151 @@
+//! 简繁转换，使用 OpenCC 的 s2t / s2tw / t2s / tw2s 配置与词典数据。
+//!
+//! 词典（STPhrases、STCharacters、TWVariants 等）由 `ferrous-opencc` 编译进程序，
+//! 按 OpenCC 的最长词组优先分词后转换，结果与 OpenCC 一致。
+
+use std::collections::HashMap;
+
+use ferrous_opencc::OpenCC;
+use ferrous_opencc::config::BuiltinConfig;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{Key, Locale, t};
+
+/// 简繁转换方向。
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub enum ChineseConversion {
+    #[default]
+    None,
+    /// 简体 → 繁体（OpenCC 标准字形）。
+    S2T,
+    /// 简体 → 繁体（台湾字形，如“裡”“著”“線”）。
+    S2TW,
+    /// 繁体 → 简体。
+    T2S,
+    /// 台湾繁体 → 简体。
+    TW2S,
+}
+
+impl ChineseConversion {
+    pub const ALL: [ChineseConversion; 5] = [
+        ChineseConversion::None,
+        ChineseConversion::S2T,
+        ChineseConversion::S2TW,
+        ChineseConversion::T2S,
+        ChineseConversion::TW2S,
+    ];
+
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            ChineseConversion::None => t(locale, Key::ZhConvertNone),
+            ChineseConversion::S2T => "s2t (简体 → 繁體)",
+            ChineseConversion::S2TW => "s2tw (简体 → 臺灣正體)",
+            ChineseConversion::T2S => "t2s (繁體 → 简体)",
+            ChineseConversion::TW2S => "tw2s (臺灣正體 → 简体)",
+        }
+    }
+
+    /// 按当前方向转换文本；`None` 时原样返回。
+    pub fn convert(self, text: &str) -> String {
+        let converter: &OpenCC = match self {
+            ChineseConversion::None => return text.to_string(),
+            ChineseConversion::S2T => &S2T,
+            ChineseConversion::S2TW => &S2TW,
+            ChineseConversion::T2S => &T2S,
+            ChineseConversion::TW2S => &TW2S,
+        };
+        converter.convert(text)
+    }
+}
+
+/// 加载内置的 OpenCC 配置；词典随程序编译，加载失败说明构建本身有误。
+fn builtin(config: BuiltinConfig) -> OpenCC {
+    OpenCC::from_config(config).expect("built-in OpenCC dictionaries")
+}
+
+static S2T: Lazy<OpenCC> = Lazy::new(|| builtin(BuiltinConfig::S2t));
+static S2TW: Lazy<OpenCC> = Lazy::new(|| builtin(BuiltinConfig::S2tw));
+static T2S: Lazy<OpenCC> = Lazy::new(|| builtin(BuiltinConfig::T2s));
+static TW2S: Lazy<OpenCC> = Lazy::new(|| builtin(BuiltinConfig::Tw2s));
+
+/// 统计文本中简体专用字与繁体专用字的个数，用于判断书写体系。
+///
+/// 简→繁会改变的字算作简体字，繁→简会改变的字算作繁体字。
+pub(crate) fn script_counts(text: &str) -> (usize, usize) {
+    // 每个不同的字只查一次词典。
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for ch in text.chars().filter(|ch| !ch.is_ascii()) {
+        *counts.entry(ch).or_default() += 1;
+    }
+    let mut buffer = [0u8; 4];
+    counts
+        .into_iter()
+        .fold((0, 0), |(simplified, traditional), (ch, count)| {
+            let ch = ch.encode_utf8(&mut buffer);
+            if S2T.convert(ch) != *ch {
+                (simplified + count, traditional)
+            } else if T2S.convert(ch) != *ch {
+                (simplified, traditional + count)
+            } else {
+                (simplified, traditional)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 期望结果取自 OpenCC 上游测试用例（test/testcases）及 `opencc -c s2t.json` 的输出。
+    #[test]
+    fn s2t_matches_opencc() {
+        for (input, expected) in [
+            ("太后的头发很干燥。", "太后的頭髮很乾燥。"),
+            ("我干什么不干你事。", "我幹什麼不干你事。"),
+            ("新的理论被发现了。", "新的理論被發現了。"),
+            ("经理发现后劝谕两人", "經理發現後勸諭兩人"),
+            ("夸夸其谈 夸父逐日", "誇誇其談 夸父逐日"),
+            (
+                "想到自己一紧张就口吃，我就没胃口吃饭",
+                "想到自己一緊張就口吃，我就沒胃口喫飯",
+            ),
+            ("发丝 冲洗 钟情 三只", "髮絲 沖洗 鍾情 三隻"),
+        ] {
+            assert_eq!(ChineseConversion::S2T.convert(input), expected, "{input}");
+        }
+        assert_eq!(ChineseConversion::None.convert("发丝"), "发丝");
+    }
+
+    #[test]
+    fn s2tw_matches_opencc() {
+        assert_eq!(
+            ChineseConversion::S2TW.convert("着装污染虚伪发泄棱柱群众里面"),
+            "著裝汙染虛偽發洩稜柱群眾裡面"
+        );
+        assert_eq!(
+            ChineseConversion::S2TW.convert("鲶鱼和鲇鱼是一种生物。"),
+            "鯰魚和鯰魚是一種生物。"
+        );
+    }
+
+    #[test]
+    fn t2s_and_tw2s_match_opencc() {
+        assert_eq!(
+            ChineseConversion::T2S.convert("寫一個解算器程式來解決方程式"),
+            "写一个解算器程式来解决方程式"
+        );
+        assert_eq!(ChineseConversion::T2S.convert("二噁英"), "二𫫇英");
+        assert_eq!(
+            ChineseConversion::TW2S.convert("著裝著作汙染虛偽發洩稜柱群眾裡面"),
+            "着装著作污染虚伪发泄棱柱群众里面"
+        );
+    }
+
+    #[test]
+    fn script_counts_tell_scripts_apart() {
+        assert_eq!(script_counts("这个头发"), (4, 0));
+        assert_eq!(script_counts("這個頭髮"), (0, 4));
+        assert_eq!(script_counts("Hello 大家"), (0, 0));
+    }
+}
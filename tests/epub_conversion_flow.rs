@@ -1,9 +1,11 @@
 use reasypub::conversion::{ConversionFacade, ConversionRequest, StrategyFactory};
+use reasypub::epub_import::import_epub;
+use reasypub::toc::InlineToc;
 use reasypub::{
-    BookInfo, ChapterDraft, ConversionMethod, FontAsset, ImageAsset, TextStyle, TocOptions,
+    BookInfo, ChapterDraft, ConversionMethod, Creator, CreatorRole, EpubVersion, FontAsset,
+    FontRole, ImageAsset, TextStyle, TocOptions,
 };
 use regex::Regex;
-use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use zip::ZipArchive;
@@ -172,83 +174,6 @@ fn assert_images_after_chapters_in_toc(epub: &Path) {
     }
 }
 
-const TOC_MARKER: &str = "\u{76ee}\u{5f55}";
-
-fn toc_titles_from_text(text: &str) -> Vec<String> {
-    let mut titles = Vec::new();
-    let mut seen = HashSet::new();
-    let mut in_toc = false;
-    for raw in text.lines() {
-        let line = raw.trim();
-        if !in_toc {
-            if line == TOC_MARKER {
-                in_toc = true;
-            }
-            continue;
-        }
-        if line.is_empty() {
-            continue;
-        }
-        let len = line.chars().count();
-        if len > 12
-            || line.contains('。')
-            || line.contains('，')
-            || line.contains('？')
-            || line.contains('！')
-        {
-            break;
-        }
-        if seen.insert(line.to_string()) {
-            titles.push(line.to_string());
-        }
-    }
-    titles
-}
-
-fn strip_toc_list(text: &str, titles: &[String]) -> String {
-    let mut out = String::new();
-    let mut in_toc = false;
-    let mut skip_index = 0usize;
-    for raw in text.lines() {
-        let line = raw.trim();
-        if !in_toc {
-            if line == TOC_MARKER {
-                in_toc = true;
-            }
-            out.push_str(raw);
-            out.push('\n');
-            continue;
-        }
-        if skip_index >= titles.len() {
-            out.push_str(raw);
-            out.push('\n');
-            continue;
-        }
-        if line.is_empty() {
-            out.push_str(raw);
-            out.push('\n');
-            continue;
-        }
-        if line == titles[skip_index] {
-            skip_index += 1;
-            continue;
-        }
-        skip_index = titles.len();
-        out.push_str(raw);
-        out.push('\n');
-    }
-    out
-}
-
-fn toc_regex_from_titles(titles: &[String]) -> String {
-    let joined = titles
-        .iter()
-        .map(|title| regex::escape(title))
-        .collect::<Vec<_>>()
-        .join("|");
-    format!(r"(?m)^\s*(?:{})\s*$", joined)
-}
-
 #[test]
 fn export_epubs_for_manual_check() {
     if std::env::var("REASYPUB_EXPORT_EPUBS").is_err() {
@@ -275,8 +200,9 @@ fn export_epubs_for_manual_check() {
                       label: &str| {
         let mut text = read_fixture_text(&path);
         let custom_regex = if let Some(regex) = regex {
-            let toc_titles = toc_titles_from_text(&text);
-            text = strip_toc_list(&text, &toc_titles);
+            if let Some(toc) = InlineToc::detect(&text) {
+                text = toc.strip(&text);
+            }
             regex
         } else {
             String::new()
@@ -284,7 +210,7 @@ fn export_epubs_for_manual_check() {
 
         let book = BookInfo {
             title: title.to_string(),
-            author: author.to_string(),
+            creators: vec![Creator::new(author, CreatorRole::Author)],
             language: "zh-CN".to_string(),
             ..Default::default()
         };
@@ -304,12 +230,13 @@ fn export_epubs_for_manual_check() {
             style,
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             chapters_override: None,
             include_images_section: false,
             toc_options: TocOptions::default(),
+            ..Default::default()
         };
 
         let result = ConversionFacade::convert(request).expect("convert");
@@ -372,8 +299,7 @@ fn export_epubs_for_manual_check() {
     );
 
     let shubuqing_text = read_fixture_text(&shubuqing);
-    let toc_titles = toc_titles_from_text(&shubuqing_text);
-    let shubuqing_regex = toc_regex_from_titles(&toc_titles);
+    let shubuqing_regex = InlineToc::detect(&shubuqing_text).expect("toc").pattern();
     export_one(
         shubuqing.clone(),
         "数不清的井",
@@ -441,13 +367,14 @@ fn chinese_novel_txt_conversion_flow() {
 
     let book = BookInfo {
         title: "雾桥夜灯".to_string(),
-        author: "测试作者".to_string(),
+        creators: vec![Creator::new("测试作者", CreatorRole::Author)],
         language: "zh-CN".to_string(),
         publisher: "测试出版社".to_string(),
         isbn: "ISBN-0000".to_string(),
         publish_date: "2025-01-01".to_string(),
         category: "幻想".to_string(),
         description: "测试描述".to_string(),
+        ..Default::default()
     };
 
     let out_dir = temp_output_dir("reasypub-flow");
@@ -462,12 +389,13 @@ fn chinese_novel_txt_conversion_flow() {
         style: TextStyle::default(),
         cover: None,
         images: Vec::new(),
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         chapters_override: None,
         include_images_section: false,
         toc_options: TocOptions::default(),
+        ..Default::default()
     };
 
     let result = ConversionFacade::convert(request).expect("convert");
@@ -501,13 +429,14 @@ fn chinese_novel_full_pipeline_with_assets() {
 
     let book = BookInfo {
         title: "雾桥夜灯".to_string(),
-        author: "测试作者".to_string(),
+        creators: vec![Creator::new("测试作者", CreatorRole::Author)],
         language: "zh-CN".to_string(),
         publisher: "测试出版社".to_string(),
         isbn: "ISBN-0000".to_string(),
         publish_date: "2025-01-01".to_string(),
         category: "幻想".to_string(),
         description: "测试描述".to_string(),
+        ..Default::default()
     };
 
     let style = TextStyle {
@@ -520,6 +449,7 @@ fn chinese_novel_full_pipeline_with_assets() {
         bytes: bytes::Bytes::from_static(b"cover"),
         mime: "image/jpeg".to_string(),
         caption: None,
+        chapter: None,
     };
     let images = vec![
         ImageAsset {
@@ -527,12 +457,14 @@ fn chinese_novel_full_pipeline_with_assets() {
             bytes: bytes::Bytes::from_static(b"img1"),
             mime: "image/png".to_string(),
             caption: Some("山路".to_string()),
+            chapter: None,
         },
         ImageAsset {
             name: "scene2.png".to_string(),
             bytes: bytes::Bytes::from_static(b"img2"),
             mime: "image/png".to_string(),
             caption: Some("夜灯".to_string()),
+            chapter: None,
         },
     ];
     let font = FontAsset {
@@ -540,6 +472,7 @@ fn chinese_novel_full_pipeline_with_assets() {
         family: "CustomFont".to_string(),
         bytes: bytes::Bytes::from_static(b"font"),
         mime: "font/ttf".to_string(),
+        roles: vec![FontRole::Body],
     };
 
     let out_dir = temp_output_dir("reasypub-full");
@@ -554,12 +487,13 @@ fn chinese_novel_full_pipeline_with_assets() {
         style,
         cover: Some(cover),
         images,
-        font: Some(font),
+        fonts: vec![font],
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         chapters_override: None,
         include_images_section: true,
         toc_options: TocOptions::default(),
+        ..Default::default()
     };
 
     let result = ConversionFacade::convert(request).expect("convert");
@@ -596,10 +530,7 @@ fn chinese_novel_full_pipeline_with_assets() {
         extract_meta_content(&opf, "publisher").as_deref(),
         Some("测试出版社")
     );
-    assert_eq!(
-        extract_meta_content(&opf, "identifier").as_deref(),
-        Some("ISBN-0000")
-    );
+    assert!(opf.contains("<dc:identifier opf:scheme=\"ISBN\">ISBN-0000</dc:identifier>"));
     assert_eq!(
         extract_meta_content(&opf, "date").as_deref(),
         Some("2025-01-01")
@@ -661,7 +592,7 @@ fn fixtures_txt_conversion_flow() {
                 .and_then(|s| s.to_str())
                 .unwrap_or("Untitled")
                 .to_string(),
-            author: "测试作者".to_string(),
+            creators: vec![Creator::new("测试作者", CreatorRole::Author)],
             language: "zh-CN".to_string(),
             ..Default::default()
         };
@@ -678,12 +609,13 @@ fn fixtures_txt_conversion_flow() {
             style: TextStyle::default(),
             cover: None,
             images: Vec::new(),
-            font: None,
+            fonts: Vec::new(),
             chapter_header_image: None,
             chapter_header_fullbleed: false,
             chapters_override: None,
             include_images_section: false,
             toc_options: TocOptions::default(),
+            ..Default::default()
         };
 
         let result = ConversionFacade::convert(request).expect("convert");
@@ -714,7 +646,7 @@ fn hongloumeng_custom_case() {
     let out_dir = temp_output_dir("reasypub-hlm");
     let book = BookInfo {
         title: "红楼梦".to_string(),
-        author: "曹雪芹".to_string(),
+        creators: vec![Creator::new("曹雪芹", CreatorRole::Author)],
         language: "zh-CN".to_string(),
         ..Default::default()
     };
@@ -730,12 +662,13 @@ fn hongloumeng_custom_case() {
         style: TextStyle::default(),
         cover: None,
         images: Vec::new(),
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         chapters_override: None,
         include_images_section: false,
         toc_options: TocOptions::default(),
+        ..Default::default()
     };
 
     let result = ConversionFacade::convert(request).expect("convert");
@@ -770,7 +703,7 @@ fn chulong_custom_case() {
     let out_dir = temp_output_dir("reasypub-chulong");
     let book = BookInfo {
         title: "黜龙".to_string(),
-        author: "榴弹怕水".to_string(),
+        creators: vec![Creator::new("榴弹怕水", CreatorRole::Author)],
         language: "zh-CN".to_string(),
         ..Default::default()
     };
@@ -786,12 +719,13 @@ fn chulong_custom_case() {
         style: TextStyle::default(),
         cover: None,
         images: Vec::new(),
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         chapters_override: None,
         include_images_section: false,
         toc_options: TocOptions::default(),
+        ..Default::default()
     };
 
     let result = ConversionFacade::convert(request).expect("convert");
@@ -822,21 +756,28 @@ fn shubuqing_custom_case() {
         .join("fixtures")
         .join("数不清的井.txt");
     let text = read_fixture_text(&fixture);
-    let toc_titles = toc_titles_from_text(&text);
-    let cleaned_text = strip_toc_list(&text, &toc_titles);
+    let toc = InlineToc::detect(&text).expect("toc");
+    let toc_titles = &toc.titles;
+    let cleaned_text = toc.strip(&text);
     assert_eq!(toc_titles.len(), 23);
     assert_eq!(toc_titles.first().map(String::as_str), Some("序幕"));
     assert_eq!(toc_titles.last().map(String::as_str), Some("数不清的井"));
 
-    let custom_regex = toc_regex_from_titles(&toc_titles);
+    let custom_regex = toc.pattern();
     let chapters = split_chapters(&cleaned_text, ConversionMethod::Regex, &custom_regex);
     assert!(chapters.len() >= toc_titles.len());
     assert!(chapters.len() <= toc_titles.len() + 1);
+    // 用目录切分得到同样的章节。
+    let from_toc = split_chapters(&text, ConversionMethod::FromToc, "");
+    assert_eq!(
+        from_toc.iter().map(|c| &c.title).collect::<Vec<_>>(),
+        chapters.iter().map(|c| &c.title).collect::<Vec<_>>()
+    );
 
     let out_dir = temp_output_dir("reasypub-shubuqing");
     let book = BookInfo {
         title: "数不清的井".to_string(),
-        author: "京极夏彦".to_string(),
+        creators: vec![Creator::new("京极夏彦", CreatorRole::Author)],
         language: "zh-CN".to_string(),
         ..Default::default()
     };
@@ -852,12 +793,13 @@ fn shubuqing_custom_case() {
         style: TextStyle::default(),
         cover: None,
         images: Vec::new(),
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         chapters_override: None,
         include_images_section: false,
         toc_options: TocOptions::default(),
+        ..Default::default()
     };
 
     let result = ConversionFacade::convert(request).expect("convert");
@@ -887,7 +829,7 @@ fn toc_title_override_and_gallery_visibility_flow() {
         custom_config_path: None,
         book_info: BookInfo {
             title: "TOC Test".to_string(),
-            author: "Tester".to_string(),
+            creators: vec![Creator::new("Tester", CreatorRole::Author)],
             language: "en".to_string(),
             ..Default::default()
         },
@@ -900,8 +842,9 @@ fn toc_title_override_and_gallery_visibility_flow() {
             bytes: bytes::Bytes::from_static(b"img"),
             mime: "image/png".to_string(),
             caption: Some("Gallery".to_string()),
+            chapter: None,
         }],
-        font: None,
+        fonts: Vec::new(),
         chapter_header_image: None,
         chapter_header_fullbleed: false,
         chapters_override: None,
@@ -910,7 +853,9 @@ fn toc_title_override_and_gallery_visibility_flow() {
             insert_toc_page: true,
             toc_title_override: "Contents (Flow)".to_string(),
             include_gallery_in_toc: false,
+            ..Default::default()
         },
+        ..Default::default()
     };
 
     let result = ConversionFacade::convert(request).expect("convert");
@@ -930,3 +875,83 @@ fn toc_title_override_and_gallery_visibility_flow() {
     let _ = std::fs::remove_file(output);
     let _ = std::fs::remove_dir_all(&out_dir);
 }
+
+#[test]
+fn imported_epub_round_trips_chapters_and_metadata() {
+    let chapters = vec![
+        ChapterDraft {
+            title: "第一章 开端".to_string(),
+            content: "第一段。\n第二段 & 更多。\n[scene]\n第三段。".to_string(),
+            volume: Some("第一卷".to_string()),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "第二章 发展".to_string(),
+            content: "继续。".to_string(),
+            volume: Some("第一卷".to_string()),
+            ..Default::default()
+        },
+        ChapterDraft {
+            title: "尾声".to_string(),
+            content: "结束。".to_string(),
+            ..Default::default()
+        },
+    ];
+    let book_info = BookInfo {
+        title: "往返".to_string(),
+        creators: vec![
+            Creator::new("作者甲", CreatorRole::Author),
+            Creator::new("译者乙", CreatorRole::Translator),
+        ],
+        language: "zh-CN".to_string(),
+        publisher: "测试出版社".to_string(),
+        isbn: "9787536692930".to_string(),
+        publish_date: "2024-03-05".to_string(),
+        series: "系列".to_string(),
+        series_index: "2".to_string(),
+        ..Default::default()
+    };
+    for epub_version in [EpubVersion::Epub2, EpubVersion::Epub3] {
+        let out_dir = temp_output_dir("reasypub-import-flow");
+        let request = ConversionRequest {
+            text: "占位".to_string(),
+            method: ConversionMethod::SimpleRules,
+            book_info: book_info.clone(),
+            output_dir: out_dir.clone(),
+            filename_template: "round_trip".to_string(),
+            chapters_override: Some(chapters.clone()),
+            toc_options: TocOptions {
+                insert_toc_page: true,
+                nest_volumes: true,
+                ..Default::default()
+            },
+            epub_version,
+            ..Default::default()
+        };
+        let result = ConversionFacade::convert(request).expect("convert");
+
+        let imported = import_epub(Path::new(&result.output_path)).expect("import");
+        let restored: Vec<(&str, &str, Option<&str>)> = imported
+            .chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.content.as_str(), c.volume.as_deref()))
+            .collect();
+        let expected: Vec<(&str, &str, Option<&str>)> = chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.content.as_str(), c.volume.as_deref()))
+            .collect();
+        assert_eq!(restored, expected, "{epub_version:?}");
+        let info = &imported.book_info;
+        assert_eq!(info.title, book_info.title);
+        assert_eq!(info.creators, book_info.creators, "{epub_version:?}");
+        assert_eq!(info.publisher, book_info.publisher);
+        assert_eq!(info.isbn, book_info.isbn, "{epub_version:?}");
+        assert_eq!(info.publish_date, book_info.publish_date);
+        assert_eq!(
+            (info.series.as_str(), info.series_index.as_str()),
+            ("系列", "2")
+        );
+
+        let _ = std::fs::remove_dir_all(&out_dir);
+    }
+}
@@ -5,7 +5,7 @@ use egui_kittest::{
     Harness,
     kittest::{NodeT, Queryable as _},
 };
-use reasypub::{Key, Locale, MainApp, t};
+use reasypub::{DEFAULT_COPYRIGHT_TEMPLATE, Key, Locale, MainApp, SpineSection, t, t1, t2};
 
 fn new_harness() -> Harness<'static, MainApp> {
     Harness::builder()
@@ -45,6 +45,22 @@ fn gui_locale_switch_to_english() {
     harness.get_by_label(t(Locale::En, Key::Sections));
 }
 
+#[test]
+fn gui_locale_switch_to_traditional_chinese() {
+    let mut harness = new_harness();
+
+    harness
+        .get_by(|node| {
+            node.role() == Role::ComboBox && node.value() == Some(Locale::Zh.label().to_string())
+        })
+        .click();
+    harness.run();
+    harness.get_by_label(Locale::ZhTw.label()).click();
+    harness.run();
+    harness.get_by_label(t(Locale::ZhTw, Key::Basics));
+    harness.get_by_label(t(Locale::ZhTw, Key::Sections));
+}
+
 #[test]
 fn gui_panel_navigation() {
     let locale = Locale::Zh;
@@ -59,6 +75,7 @@ fn gui_panel_navigation() {
         (Key::PanelCss, Key::CustomCss),
         (Key::PanelImages, Key::AddImage),
         (Key::PanelMisc, Key::OutputFolder),
+        (Key::PanelBatch, Key::BatchEmpty),
     ];
 
     for (panel, expected) in panels {
@@ -179,6 +196,686 @@ fn gui_quick_actions_and_windows() {
     harness.get_by_label(tr(Key::AddChapter));
 }
 
+#[test]
+fn gui_file_menu_lists_project_and_recent_actions() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness.get_by_label(tr(Key::MenuFile)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::OpenProject));
+    // 菜单项标签附带快捷键文本。
+    harness.get_by_label_contains(tr(Key::SaveProject));
+    harness.get_by_label(tr(Key::SaveProjectAs));
+    harness.get_by_label(tr(Key::ClearRecent));
+
+    harness.get_by_label_contains(tr(Key::RecentTexts)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::NoRecentFiles));
+}
+
+#[test]
+fn gui_theme_menu_applies_preset() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness.get_by_label(tr(Key::Theme)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::AccentColor));
+    harness.get_by_label(tr(Key::PanelFill));
+    assert!(
+        harness
+            .get_by_label(tr(Key::ResetTheme))
+            .accesskit_node()
+            .is_disabled()
+    );
+
+    harness.get_by_label(tr(Key::ThemeSepia)).click();
+    harness.run();
+    assert_eq!(
+        harness.ctx.style().visuals.panel_fill,
+        egui::Color32::from_rgb(246, 239, 226)
+    );
+    assert!(!harness.ctx.style().visuals.dark_mode);
+}
+
+#[test]
+fn gui_shortcuts_open_windows_and_can_be_remapped() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness.key_press(egui::Key::F1);
+    harness.run();
+    harness.get_by_label(tr(Key::HelpWorkflow));
+    harness.get_by_label(tr(Key::CustomizeShortcuts)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::ShortcutsHint));
+
+    harness.get_by_role_and_label(Role::Button, "F1").click();
+    harness.run();
+    harness.get_by_role_and_label(Role::Button, tr(Key::ShortcutRecording));
+    harness.key_press(egui::Key::F2);
+    harness.run();
+    harness.get_by_role_and_label(Role::Button, "F2");
+
+    harness.key_press_modifiers(egui::Modifiers::COMMAND, egui::Key::E);
+    harness.run();
+    harness.get_by_label(tr(Key::TextEditor));
+}
+
+#[test]
+fn gui_dropped_files_load_text_and_images() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-drop-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("拖入测试_作者.txt");
+    std::fs::write(&text, "第一章 开始\n正文").expect("write text");
+    let images: Vec<_> = ["a.png", "b.png"]
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            image::RgbaImage::new(1, 1).save(&path).expect("write png");
+            path
+        })
+        .collect();
+
+    let drop = |harness: &mut Harness<'static, MainApp>, paths: &[std::path::PathBuf]| {
+        harness
+            .input_mut()
+            .dropped_files
+            .extend(paths.iter().map(|path| egui::DroppedFile {
+                path: Some(path.clone()),
+                ..Default::default()
+            }));
+        harness.run();
+    };
+
+    drop(&mut harness, std::slice::from_ref(&text));
+    harness.get_by_label(text.to_string_lossy().as_ref());
+    assert!(harness.query_all_by_value("拖入测试").next().is_some());
+
+    drop(&mut harness, &images);
+    harness.get_by_label(&t1(locale, Key::TotalImages, 2));
+    harness.get_by_label(tr(Key::ChapterHeaderImage));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn gui_text_editor_undo_redo() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_all_by_label(tr(Key::EditTxt))
+        .find(|node| node.accesskit_node().role() == Role::Button)
+        .expect("Edit TXT button")
+        .click();
+    harness.run();
+    let editor = harness.get_by_role(Role::MultilineTextInput);
+    editor.focus();
+    editor.type_text("第一章");
+    harness.run();
+    let editor_text = |harness: &Harness<'static, MainApp>| {
+        harness
+            .get_by_role(Role::MultilineTextInput)
+            .accesskit_node()
+            .value()
+    };
+    assert_eq!(editor_text(&harness).as_deref(), Some("第一章"));
+
+    harness.get_by_label(tr(Key::Undo)).click();
+    harness.run();
+    assert_eq!(editor_text(&harness).as_deref(), Some(""));
+
+    harness.get_by_label(tr(Key::Redo)).click();
+    harness.run();
+    assert_eq!(editor_text(&harness).as_deref(), Some("第一章"));
+}
+
+#[test]
+fn gui_text_editor_find_replace() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_all_by_label(tr(Key::EditTxt))
+        .find(|node| node.accesskit_node().role() == Role::Button)
+        .expect("Edit TXT button")
+        .click();
+    harness.run();
+    let editor = harness.get_by_role(Role::MultilineTextInput);
+    editor.focus();
+    editor.type_text("第1章 甲\n第2章 乙\n第3章 丙");
+    harness.run();
+
+    harness.get_by_label(tr(Key::FindReplace)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::FindRegex)).click();
+    harness.run();
+    let query = harness.get_by(|node| node.placeholder() == Some(tr(Key::FindHint)));
+    query.focus();
+    query.type_text(r"第(\d)章");
+    harness.run();
+    harness.get_by_label(&t2(locale, Key::FindMatches, 1, 3));
+
+    let replacement = harness.get_by(|node| node.placeholder() == Some(tr(Key::ReplaceHint)));
+    replacement.focus();
+    replacement.type_text("Chapter $1");
+    harness.run();
+    harness.get_by_label(tr(Key::ReplaceOne)).click();
+    harness.run();
+    harness.get_by_label(&t2(locale, Key::FindMatches, 1, 2));
+
+    harness.get_by_label(tr(Key::ReplaceAll)).click();
+    harness.run();
+    harness.get_by_label(&t1(locale, Key::ReplacedCount, 2));
+    let text = harness
+        .get_by_role(Role::MultilineTextInput)
+        .accesskit_node()
+        .value();
+    assert_eq!(
+        text.as_deref(),
+        Some("Chapter 1 甲\nChapter 2 乙\nChapter 3 丙")
+    );
+
+    // 全部替换作为一步撤销。
+    harness.get_by_label(tr(Key::Undo)).click();
+    harness.run();
+    let text = harness
+        .get_by_role(Role::MultilineTextInput)
+        .accesskit_node()
+        .value();
+    assert_eq!(text.as_deref(), Some("Chapter 1 甲\n第2章 乙\n第3章 丙"));
+}
+
+#[test]
+fn gui_chapter_preview_renders_selected_chapter() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_all_by_label(tr(Key::ChapterEditor))
+        .find(|node| node.accesskit_node().role() == Role::Button)
+        .expect("Chapter Editor button")
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::AddChapter)).click();
+    harness.run();
+    let content = harness
+        .get_all_by_role(Role::MultilineTextInput)
+        .next()
+        .expect("chapter content editor");
+    content.focus();
+    content.type_text("开头一段\n\n第二段正文");
+    harness.run();
+    harness.get_by_label(tr(Key::ChapterPreview)).click();
+    harness.run();
+
+    harness.get_by_label(tr(Key::ChapterPreviewTitle));
+    harness.get_by_label(tr(Key::ChapterPreviewHint));
+    harness.get_by_label("第二段正文");
+}
+
+#[test]
+fn gui_format_and_font_panels_show_style_sample() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+    let first_paragraph = tr(Key::StyleSampleText)
+        .split("\n\n")
+        .next()
+        .expect("sample paragraph");
+
+    for panel in [Key::PanelLayout, Key::PanelFonts] {
+        harness
+            .get_by_role_and_label(Role::Button, tr(panel))
+            .click();
+        harness.run();
+        harness.get_by_label(tr(Key::StyleSample));
+        harness.get_by_label(first_paragraph);
+    }
+}
+
+#[test]
+fn gui_chapter_panel_edits_regex_pattern_list() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelChapters))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::BuiltinChinesePattern));
+    for _ in 0..2 {
+        harness.get_by_label(tr(Key::AddRegexPattern)).click();
+        harness.run();
+    }
+    harness.get_by_label("1.");
+    harness.get_by_label("2.");
+    // 空白正则不生效，仍使用内置规则。
+    harness.get_by_label(tr(Key::BuiltinChinesePattern));
+
+    harness
+        .get_all_by_label(tr(Key::Delete))
+        .next()
+        .expect("delete")
+        .click();
+    harness.run();
+    harness.get_by_label("1.");
+    assert!(harness.query_by_label("2.").is_none());
+}
+
+#[test]
+fn gui_chapter_panel_adds_title_exclusion() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    // 分章方式较多，排除规则位于面板下方，用更高的窗口避免被滚动区域裁掉。
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(1280.0, 1400.0))
+        .with_os(OperatingSystem::Windows)
+        .build_eframe(|cc| MainApp::new(cc));
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelChapters))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::TitleExclusionsHint));
+    assert!(harness.query_by_label("1.").is_none());
+    harness.get_by_label(tr(Key::AddTitleExclusion)).click();
+    harness.run();
+    harness.get_by_label("1.");
+}
+
+#[test]
+fn gui_chapters_panel_counts_whitespace_cleanup() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(1280.0, 1400.0))
+        .with_os(OperatingSystem::Windows)
+        .build_eframe(|cc| MainApp::new(cc));
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelChapters))
+        .click();
+    harness.run();
+    let count_disabled = |harness: &Harness<'static, MainApp>| {
+        harness
+            .get_by_role_and_label(Role::Button, tr(Key::CountWhitespace))
+            .accesskit_node()
+            .is_disabled()
+    };
+    assert!(count_disabled(&harness));
+    harness.get_by_label(tr(Key::CleanWhitespace)).click();
+    harness.run();
+    assert!(!count_disabled(&harness));
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::CountWhitespace))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::WhitespaceNothing));
+}
+
+#[test]
+fn gui_chapters_panel_previews_punctuation_changes() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    // 标点规范化位于清理规则之后，同样需要更高的窗口。
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(1280.0, 1400.0))
+        .with_os(OperatingSystem::Windows)
+        .build_eframe(|cc| MainApp::new(cc));
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelChapters))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::PunctuationHint));
+    assert!(
+        harness
+            .get_by_role_and_label(Role::Button, tr(Key::PreviewPunctuation))
+            .accesskit_node()
+            .is_disabled()
+    );
+    harness
+        .get_by(|node| {
+            node.role() == Role::ComboBox
+                && node.value().as_deref() == Some(tr(Key::PunctuationOff))
+        })
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::PunctuationFullWidth)).click();
+    harness.run();
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PreviewPunctuation))
+        .click();
+    harness.run();
+    // 尚未载入文本，没有需要修改的行。
+    harness.get_by_label(tr(Key::PunctuationNoChanges));
+}
+
+#[test]
+fn gui_heuristic_split_opens_chapter_editor_for_review() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelChapters))
+        .click();
+    harness.run();
+    assert!(harness.query_by_label(tr(Key::BulkRename)).is_none());
+    harness.get_by_label(tr(Key::ReviewInChapterEditor)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::BulkRename));
+}
+
+#[test]
+fn gui_chapter_editor_shows_chapter_statistics() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-stats-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("统计.txt");
+    let long = "长".repeat(2000);
+    std::fs::write(
+        &text,
+        format!("第一章 甲\n一二三\n第二章 乙\n四五六\n第三章 丙\n{long}"),
+    )
+    .expect("write text");
+    harness.input_mut().dropped_files.push(egui::DroppedFile {
+        path: Some(text),
+        ..Default::default()
+    });
+    harness.run();
+
+    harness
+        .get_all_by_label(tr(Key::ChapterEditor))
+        .find(|node| node.accesskit_node().role() == Role::Button)
+        .expect("Chapter Editor button")
+        .click();
+    harness.run();
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::Refresh))
+        .click();
+    harness.run();
+    harness.get_by_label(&t1(locale, Key::ChaptersCount, 3));
+    // 总字数含标题：每个标题 4 字。
+    harness.get_by_label(&t2(locale, Key::ReportLength, 2018, 2018));
+    harness.get_by_label(&t1(locale, Key::ReadingTime, 6));
+    harness.get_by_label(&t2(locale, Key::LongestChapter, 3, 2004));
+    assert_eq!(
+        harness
+            .get_all_by_label(&t2(locale, Key::ChapterLength, 7, 1))
+            .count(),
+        2
+    );
+    harness.get_by_label(&t2(locale, Key::ChapterLength, 2004, 6));
+}
+
+#[test]
+fn gui_chapter_editor_finds_and_replaces_across_chapters() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-find-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("查找.txt");
+    std::fs::write(
+        &text,
+        "第一章 甲\n雾起。雾散。\n第二章 乙\n无\n第三章 丙\n浓雾",
+    )
+    .expect("write text");
+    harness.input_mut().dropped_files.push(egui::DroppedFile {
+        path: Some(text),
+        ..Default::default()
+    });
+    harness.run();
+
+    harness
+        .get_all_by_label(tr(Key::ChapterEditor))
+        .find(|node| node.accesskit_node().role() == Role::Button)
+        .expect("Chapter Editor button")
+        .click();
+    harness.run();
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::Refresh))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::ChapterFind)).click();
+    harness.run();
+
+    let query = harness.get_by(|node| node.placeholder() == Some(tr(Key::FindHint)));
+    query.focus();
+    query.type_text("雾");
+    harness.run();
+    harness.get_by_label(&t2(locale, Key::ChapterFindResults, 3, 2));
+
+    let replacement = harness.get_by(|node| node.placeholder() == Some(tr(Key::ReplaceHint)));
+    replacement.focus();
+    replacement.type_text("霾");
+    harness.run();
+    harness
+        .get_by_role_and_label(Role::Button, &t1(locale, Key::ReplaceSelected, 3))
+        .click();
+    harness.run();
+    harness.get_by_label(&t1(locale, Key::ReplacedCount, 3));
+    harness.get_by_label(&t2(locale, Key::ChapterFindResults, 0, 0));
+    let contents: Vec<String> = harness
+        .get_all_by_role(Role::MultilineTextInput)
+        .filter_map(|node| node.accesskit_node().value())
+        .collect();
+    assert!(contents.iter().any(|content| content == "霾起。霾散。"));
+}
+
+#[test]
+fn gui_chapter_editor_lists_huge_books_lazily() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-huge-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("长篇.txt");
+    let body: String = (1..=2000)
+        .map(|number| format!("第{number}章 标题{number}\n正文{number}。\n"))
+        .collect();
+    std::fs::write(&text, body).expect("write text");
+    harness.input_mut().dropped_files.push(egui::DroppedFile {
+        path: Some(text),
+        ..Default::default()
+    });
+    harness.run();
+
+    harness
+        .get_all_by_label(tr(Key::ChapterEditor))
+        .find(|node| node.accesskit_node().role() == Role::Button)
+        .expect("Chapter Editor button")
+        .click();
+    harness.run();
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::Refresh))
+        .click();
+    harness.run();
+    harness.get_by_label(&t1(locale, Key::ChaptersCount, 2000));
+    let rows = |harness: &Harness<'static, MainApp>| {
+        harness
+            .query_all_by(|node| node.label().is_some_and(|label| label.starts_with('#')))
+            .count()
+    };
+    assert!(rows(&harness) < 100, "{} rows rendered", rows(&harness));
+    harness.get_by_label("#1 第1章 标题1");
+    assert!(harness.query_by_label("#1999 第1999章 标题1999").is_none());
+    let content_editors = |harness: &Harness<'static, MainApp>, value: &str| {
+        harness
+            .query_all_by(|node| {
+                node.role() == Role::MultilineTextInput && node.value().as_deref() == Some(value)
+            })
+            .count()
+    };
+    assert_eq!(content_editors(&harness, "正文1。"), 1);
+    assert_eq!(content_editors(&harness, "正文2。"), 0);
+
+    // 从查找结果跳到列表之外的章节：编辑区切换到该章，列表滚动到该行。
+    harness.get_by_label(tr(Key::ChapterFind)).click();
+    harness.run();
+    let query = harness.get_by(|node| node.placeholder() == Some(tr(Key::FindHint)));
+    query.focus();
+    query.type_text("正文1999");
+    harness.run();
+    harness.get_by_label("#1999 第1999章 标题1999 (1)").click();
+    harness.run();
+    harness.run();
+    assert_eq!(content_editors(&harness, "正文1999。"), 1);
+    harness.get_by_label("#1999 第1999章 标题1999");
+}
+
+#[test]
+fn gui_images_panel_loads_thumbnails_in_background() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-gallery-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    for index in 1..=40 {
+        let path = dir.join(format!("插图{index:02}.png"));
+        image::RgbaImage::from_pixel(800, 600, image::Rgba([index * 6, 90, 160, 255]))
+            .save(&path)
+            .expect("write image");
+        harness.input_mut().dropped_files.push(egui::DroppedFile {
+            path: Some(path),
+            ..Default::default()
+        });
+    }
+    harness.step();
+    harness.get_by_label(&t1(locale, Key::ImageIndex, 40));
+
+    // 解码在后台进行，界面照常刷新，可见的缩略图陆续出现。
+    let loading =
+        |harness: &Harness<'static, MainApp>| harness.query_all_by_label(tr(Key::Loading)).count();
+    for _ in 0..200 {
+        harness.step();
+        if loading(&harness) == 0 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(loading(&harness), 0);
+    harness.get_by_label(&t1(locale, Key::ImageIndex, 1));
+}
+
+#[test]
+fn gui_images_panel_assigns_image_to_chapter() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-placement-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("正文.txt");
+    std::fs::write(&text, "第一章 甲\n雾起。\n第二章 乙\n雾散。\n").expect("write text");
+    let mut dropped = vec![text];
+    for name in ["地图.png", "人物.png"] {
+        let path = dir.join(name);
+        image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 90, 40, 255]))
+            .save(&path)
+            .expect("write image");
+        dropped.push(path);
+    }
+    for path in dropped {
+        harness.input_mut().dropped_files.push(egui::DroppedFile {
+            path: Some(path),
+            ..Default::default()
+        });
+    }
+    harness.step();
+    harness.step();
+
+    let placements = |harness: &Harness<'static, MainApp>| {
+        harness
+            .query_all_by(|node| node.role() == Role::ComboBox)
+            .filter_map(|node| node.value())
+            .filter(|value| value == tr(Key::InsertIntoGallery) || value.starts_with('#'))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        placements(&harness),
+        [tr(Key::InsertIntoGallery), tr(Key::InsertIntoGallery)]
+    );
+    harness
+        .query_all_by(|node| {
+            node.role() == Role::ComboBox
+                && node.value().as_deref() == Some(tr(Key::InsertIntoGallery))
+        })
+        .next()
+        .expect("chapter combo box")
+        .click();
+    harness.step();
+    harness.get_by_label("#2 第二章 乙").click_accesskit();
+    harness.step();
+    harness.step();
+    assert_eq!(
+        placements(&harness),
+        ["#2 第二章 乙", tr(Key::InsertIntoGallery)]
+    );
+}
+
+#[test]
+fn gui_images_panel_merges_duplicate_images() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-duplicates-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let mut dropped = Vec::new();
+    for (name, red) in [("地图.png", 200), ("人物.png", 40), ("地图副本.png", 200)] {
+        let path = dir.join(name);
+        image::RgbaImage::from_pixel(8, 8, image::Rgba([red, 90, 40, 255]))
+            .save(&path)
+            .expect("write image");
+        dropped.push(path);
+    }
+    for path in dropped {
+        harness.input_mut().dropped_files.push(egui::DroppedFile {
+            path: Some(path),
+            ..Default::default()
+        });
+    }
+    harness.step();
+    harness.step();
+
+    let duplicate = t1(locale, Key::DuplicateImage, 1);
+    assert_eq!(harness.query_all_by_label(&duplicate).count(), 1);
+    harness
+        .get_by_label(tr(Key::MergeDuplicate))
+        .click_accesskit();
+    harness.step();
+    harness.step();
+    assert!(harness.query_by_label(&duplicate).is_none());
+    assert!(
+        harness
+            .query_by_label(&t1(locale, Key::TotalImages, 2))
+            .is_some()
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn gui_snapshots() {
     let mut harness = new_harness();
@@ -201,3 +898,314 @@ fn gui_snapshots() {
     harness.run();
     harness.snapshot("chapter_editor");
 }
+
+#[test]
+fn gui_publish_info_edits_creators_and_custom_metadata() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    // 高级元数据位于面板底部，用更高的窗口避免被滚动区域裁掉。
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(1280.0, 1400.0))
+        .with_os(OperatingSystem::Windows)
+        .build_eframe(|cc| MainApp::new(cc));
+    let author_roles = |harness: &Harness<'static, MainApp>| {
+        harness
+            .query_all_by(|node| {
+                node.role() == Role::ComboBox
+                    && node.value().as_deref() == Some(tr(Key::RoleAuthor))
+            })
+            .count()
+    };
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelPublishInfo))
+        .click();
+    harness.run();
+    // 主面板的作者输入框对应第一条署名。
+    assert_eq!(author_roles(&harness), 1);
+    harness.get_by_label(tr(Key::AddCreator)).click();
+    harness.run();
+    assert_eq!(author_roles(&harness), 2);
+
+    assert!(harness.query_by_label(tr(Key::AddMetadataEntry)).is_none());
+    harness.get_by_label(tr(Key::AdvancedMetadata)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::AdvancedMetadataHint));
+    harness.get_by_label(tr(Key::AddMetadataEntry)).click();
+    harness.run();
+    harness.get_by_label(tr(Key::MetadataKey));
+    assert!(
+        harness
+            .query_by_label(tr(Key::InvalidMetadataKey))
+            .is_none()
+    );
+}
+
+#[test]
+fn gui_publish_info_fills_copyright_template() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(1280.0, 1400.0))
+        .with_os(OperatingSystem::Windows)
+        .build_eframe(|cc| MainApp::new(cc));
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelPublishInfo))
+        .click();
+    harness.run();
+    assert!(harness.query_by_label(tr(Key::FrontMatterHint)).is_none());
+    harness.get_by_label(tr(Key::FrontMatter)).click_accesskit();
+    harness.run();
+    harness.get_by_label(tr(Key::FrontMatterHint));
+    harness.get_by_label(tr(Key::TitlePage));
+    harness
+        .get_by_label(tr(Key::UseCopyrightTemplate))
+        .click_accesskit();
+    harness.run();
+    assert!(
+        harness
+            .query_all_by(|node| {
+                node.role() == Role::MultilineTextInput
+                    && node.value().as_deref() == Some(DEFAULT_COPYRIGHT_TEMPLATE)
+            })
+            .next()
+            .is_some()
+    );
+}
+
+#[test]
+fn gui_publish_info_checks_language_tag() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelPublishInfo))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::LanguageHint));
+    // 尚未载入文本时无法从正文识别语言。
+    assert!(
+        harness
+            .get_by_role_and_label(Role::Button, tr(Key::DetectLanguage))
+            .accesskit_node()
+            .is_disabled()
+    );
+    assert!(harness.query_by_label(tr(Key::LanguageInvalid)).is_none());
+}
+
+#[test]
+fn gui_merge_files_window_opens_from_basics() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::MergeTextFiles))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::MergeFilesTitle));
+    harness.get_by_label(tr(Key::MergeFilesEmpty));
+    // 列表为空时无法合并。
+    assert!(
+        harness
+            .get_by_role_and_label(Role::Button, tr(Key::MergeAndLoad))
+            .accesskit_node()
+            .is_disabled()
+    );
+}
+
+#[test]
+fn gui_reading_preview_flips_between_chapters() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-reading-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("翻页.txt");
+    std::fs::write(&text, "第一章 开始\n第一段\n\n第二章 继续\n第二段").expect("write text");
+    harness.input_mut().dropped_files.push(egui::DroppedFile {
+        path: Some(text),
+        ..Default::default()
+    });
+    harness.run();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::ReadingPreview))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::ReadingPreviewHint));
+    harness.get_by_label(&t2(locale, Key::PageNumber, 1, 1));
+    let disabled = |harness: &Harness<'static, MainApp>, key| {
+        harness
+            .get_by_role_and_label(Role::Button, tr(key))
+            .accesskit_node()
+            .is_disabled()
+    };
+    assert!(disabled(&harness, Key::PreviousPage));
+    assert!(!disabled(&harness, Key::NextPage));
+
+    // 章末继续翻页进入下一章。
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::NextPage))
+        .click();
+    harness.run();
+    assert!(!disabled(&harness, Key::PreviousPage));
+    assert!(disabled(&harness, Key::NextPage));
+}
+
+#[test]
+fn gui_publish_info_fills_book_info_from_text_header() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    let dir = std::env::temp_dir().join(format!("reasypub-header-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let text = dir.join("下载.txt");
+    std::fs::write(&text, "书名：雾镇\n作者：某人\n\n第一章 开始\n正文").expect("write text");
+    harness.input_mut().dropped_files.push(egui::DroppedFile {
+        path: Some(text),
+        ..Default::default()
+    });
+    harness.run();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelPublishInfo))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::TextHeaderFound));
+    assert!(harness.query_all_by_value("雾镇").next().is_none());
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::FillFromTextHeader))
+        .click();
+    harness.run();
+    assert!(harness.query_all_by_value("雾镇").next().is_some());
+    assert!(harness.query_all_by_value("某人").next().is_some());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn gui_publish_info_opens_metadata_lookup() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelPublishInfo))
+        .click();
+    harness.run();
+    assert!(harness.query_by_label(tr(Key::LookupSearch)).is_none());
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::LookupMetadata))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::LookupSource));
+    harness.get_by_label(tr(Key::LookupDownloadCover));
+    // 没有书名、作者或 ISBN 时不能查询，也不会发出网络请求。
+    harness.get_by_label(tr(Key::LookupSearch)).click();
+    harness.run();
+    assert!(harness.query_by_label(tr(Key::LookupSearching)).is_none());
+}
+
+#[test]
+fn gui_misc_panel_previews_output_filename() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelMisc))
+        .click();
+    harness.run();
+    harness.get_by_label(&t1(locale, Key::FilenamePreview, "Untitled_Unknown.epub"));
+    harness.get_by_label("PDF").click();
+    harness.run();
+    harness.get_by_label(&t1(locale, Key::FilenamePreview, "Untitled_Unknown.pdf"));
+    harness.get_by_label(tr(Key::OverwriteLabel));
+    for key in [Key::OverwriteAsk, Key::OverwriteNumber] {
+        harness.get_by_label(tr(key));
+    }
+}
+
+#[test]
+fn gui_misc_panel_offers_post_export_actions() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelMisc))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::PostExportActions));
+    for key in [Key::OpenAfterExport, Key::AddToCalibreAfterExport] {
+        let checkbox = harness.get_by_label(tr(key));
+        assert_eq!(checkbox.accesskit_node().toggled(), Some(Toggled::False));
+    }
+    harness
+        .get_by_label(tr(Key::AddToCalibreAfterExport))
+        .click_accesskit();
+    harness.run();
+    assert_eq!(
+        harness
+            .get_by_label(tr(Key::AddToCalibreAfterExport))
+            .accesskit_node()
+            .toggled(),
+        Some(Toggled::True)
+    );
+    harness.get_by_label(tr(Key::CalibredbPath));
+    harness.get_by_label(tr(Key::CalibreLibrary));
+    harness.get_by_label(tr(Key::DeviceFolders));
+    harness.get_by_label("Kindle");
+    harness.get_by_label("Kobo");
+}
+
+#[test]
+fn gui_misc_panel_lists_spine_sections() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelMisc))
+        .click();
+    harness.run();
+    harness.get_by_label(tr(Key::SpineOrder));
+    for section in SpineSection::ALL {
+        harness.get_by_label(section.label(locale));
+    }
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::ResetSpineOrder))
+        .click();
+    harness.run();
+}
+
+#[test]
+fn gui_kindle_settings_dialog_opens_from_misc_panel() {
+    let locale = Locale::Zh;
+    let tr = |key| t(locale, key);
+    let mut harness = new_harness();
+
+    harness
+        .get_by_role_and_label(Role::Button, tr(Key::PanelMisc))
+        .click();
+    harness.run();
+    assert!(
+        harness
+            .query_by_label(tr(Key::KindleSettingsTitle))
+            .is_none()
+    );
+    harness
+        .get_by_label(tr(Key::KindleSettingsButton))
+        .click_accesskit();
+    harness.run();
+    harness.get_by_label(tr(Key::KindleSettingsTitle));
+    for key in [Key::SmtpServer, Key::SmtpPassword, Key::KindleTo] {
+        harness.get_by_label(tr(key));
+    }
+    harness.get_by_label(&t1(locale, Key::KindleIncomplete, "SMTP server is empty"));
+}
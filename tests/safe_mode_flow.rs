@@ -0,0 +1,122 @@
+//! 安全模式流程测试。
+//!
+//! 该测试会切换进程工作目录到一个不含 `assets/` 的临时目录，
+//! 因此单独放在一个测试二进制中，避免影响依赖相对路径素材的其他测试。
+
+use reasypub::conversion::{ConversionFacade, ConversionRequest};
+use reasypub::{
+    BookInfo, ConversionMethod, Creator, CreatorRole, CssTemplate, TextStyle, TocOptions,
+};
+use regex::Regex;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::ZipArchive;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    std::env::temp_dir().join(format!("{prefix}-{suffix}"))
+}
+
+/// 把相对引用解析为压缩包内的路径。
+fn resolve(base: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').filter(|part| !part.is_empty()).collect();
+    for part in target.split('#').next().unwrap_or_default().split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+#[test]
+fn safe_mode_converts_without_assets_folder() {
+    let workdir = temp_dir("reasypub-safe-mode");
+    std::fs::create_dir_all(&workdir).expect("create workdir");
+    std::env::set_current_dir(&workdir).expect("chdir");
+    assert!(!workdir.join("assets").exists());
+
+    let request = ConversionRequest {
+        text: "第1章 开始\n内容\n第2章 继续\n更多内容".to_string(),
+        method: ConversionMethod::Regex,
+        book_info: BookInfo {
+            title: "安全模式".to_string(),
+            creators: vec![Creator::new("测试", CreatorRole::Author)],
+            ..Default::default()
+        },
+        output_dir: workdir.join("out"),
+        filename_template: "safe_mode".to_string(),
+        style: TextStyle {
+            css_template: CssTemplate::Fantasy,
+            ..Default::default()
+        },
+        toc_options: TocOptions {
+            insert_toc_page: false,
+            ..Default::default()
+        },
+        safe_mode: true,
+        ..Default::default()
+    };
+
+    let result = ConversionFacade::convert(request).expect("convert in safe mode");
+    assert!(
+        result
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("book.css"))
+    );
+
+    let file = std::fs::File::open(&result.output_path).expect("open epub");
+    let mut archive = ZipArchive::new(file).expect("zip");
+    let names: Vec<String> = (0..archive.len())
+        .map(|idx| archive.by_index(idx).expect("entry").name().to_string())
+        .collect();
+    assert!(
+        names
+            .iter()
+            .any(|name| name.ends_with("chapter_0002.xhtml"))
+    );
+    assert!(names.iter().any(|name| name.ends_with("fonts/kt.ttf")));
+
+    // 正文与样式表中引用的每个资源都必须打包在 EPUB 内。
+    let reference = Regex::new(r#"src="([^"]+)"|url\("?([^")]+)"?\)"#).expect("regex");
+    let mut checked = 0;
+    for name in &names {
+        if !(name.ends_with(".xhtml") || name.ends_with(".css")) {
+            continue;
+        }
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .expect("entry")
+            .read_to_string(&mut content)
+            .expect("read entry");
+        let base = name.rsplit_once('/').map_or("", |(dir, _)| dir);
+        for captures in reference.captures_iter(&content) {
+            let target = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .expect("target")
+                .as_str();
+            if target.starts_with('#') || target.contains(':') {
+                continue;
+            }
+            let resolved = resolve(base, target);
+            assert!(
+                names.contains(&resolved),
+                "{name} references missing resource {target}"
+            );
+            checked += 1;
+        }
+    }
+    assert!(checked > 0);
+
+    let _ = std::fs::remove_dir_all(&workdir);
+}